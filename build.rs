@@ -0,0 +1,30 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Expõe o commit git do build em `GUARDUPLOAD_GIT_COMMIT` (usado por `--version --json`).
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=GUARDUPLOAD_GIT_COMMIT");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+
+    // Builds a partir de tarball informam o commit pela variável de ambiente.
+    let commit = std::env::var("GUARDUPLOAD_GIT_COMMIT")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .map(|commit| commit.trim().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=GUARDUPLOAD_GIT_COMMIT={commit}");
+}
@@ -0,0 +1,179 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Digests adicionais (MD5, SHA-1, SHA-512, BLAKE3 e TLSH) calculados junto do SHA-256.
+
+use super::tlsh::TlshBuilder;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Tamanho do bloco entregue a cada hasher por iteração.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Algoritmos extras aceitos por `--hash`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha512,
+    Blake3,
+    /// Hash de similaridade; ausente para arquivos curtos ou uniformes demais.
+    Tlsh,
+}
+
+impl HashAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Tlsh => "tlsh",
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().replace('-', "").as_str() {
+            "md5" => Ok(HashAlgorithm::Md5),
+            "sha1" => Ok(HashAlgorithm::Sha1),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            "tlsh" => Ok(HashAlgorithm::Tlsh),
+            other => Err(format!(
+                "algoritmo de hash desconhecido: {other} (use md5, sha1, sha512, blake3 ou tlsh)"
+            )),
+        }
+    }
+}
+
+/// Resultado de uma passada: SHA-256 (sempre) e os digests extras pedidos.
+#[derive(Debug, Clone)]
+pub struct Digests {
+    pub sha256: String,
+    pub extra: BTreeMap<String, String>,
+}
+
+enum Hasher {
+    Md5(md5::Md5),
+    Sha1(sha1::Sha1),
+    Sha512(Box<Sha512>),
+    Blake3(Box<blake3::Hasher>),
+    Tlsh(Box<TlshBuilder>),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Md5 => Hasher::Md5(md5::Md5::new()),
+            HashAlgorithm::Sha1 => Hasher::Sha1(sha1::Sha1::new()),
+            HashAlgorithm::Sha512 => Hasher::Sha512(Box::default()),
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::default()),
+            HashAlgorithm::Tlsh => Hasher::Tlsh(Box::default()),
+        }
+    }
+
+    fn update(&mut self, chunk: &[u8]) {
+        match self {
+            Hasher::Md5(hasher) => hasher.update(chunk),
+            Hasher::Sha1(hasher) => hasher.update(chunk),
+            Hasher::Sha512(hasher) => hasher.update(chunk),
+            Hasher::Blake3(hasher) => {
+                hasher.update(chunk);
+            }
+            Hasher::Tlsh(builder) => builder.update(chunk),
+        }
+    }
+
+    fn finalize(self) -> Option<String> {
+        match self {
+            Hasher::Md5(hasher) => Some(hex::encode(hasher.finalize())),
+            Hasher::Sha1(hasher) => Some(hex::encode(hasher.finalize())),
+            Hasher::Sha512(hasher) => Some(hex::encode(hasher.finalize())),
+            Hasher::Blake3(hasher) => Some(hasher.finalize().to_hex().to_string()),
+            Hasher::Tlsh(builder) => builder.finalize(),
+        }
+    }
+}
+
+/// Calcula SHA-256 e os algoritmos pedidos percorrendo os dados uma única vez.
+pub fn compute_digests(data: &[u8], algorithms: &[HashAlgorithm]) -> Digests {
+    let mut sha256 = Sha256::new();
+    let mut algorithms = algorithms.to_vec();
+    algorithms.sort();
+    algorithms.dedup();
+    let mut hashers: Vec<(HashAlgorithm, Hasher)> = algorithms
+        .into_iter()
+        .map(|algorithm| (algorithm, Hasher::new(algorithm)))
+        .collect();
+
+    for chunk in data.chunks(CHUNK_SIZE) {
+        sha256.update(chunk);
+        for (_, hasher) in &mut hashers {
+            hasher.update(chunk);
+        }
+    }
+
+    Digests {
+        sha256: hex::encode(sha256.finalize()),
+        extra: hashers
+            .into_iter()
+            .filter_map(|(algorithm, hasher)| {
+                Some((algorithm.as_str().to_string(), hasher.finalize()?))
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_requested_digests_in_one_pass() {
+        let digests = compute_digests(
+            b"abc",
+            &[
+                HashAlgorithm::Sha1,
+                HashAlgorithm::Md5,
+                HashAlgorithm::Blake3,
+                HashAlgorithm::Md5,
+            ],
+        );
+        assert_eq!(
+            digests.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(digests.extra.len(), 3);
+        assert_eq!(digests.extra["md5"], "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(
+            digests.extra["sha1"],
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            digests.extra["blake3"],
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        assert!(compute_digests(b"abc", &[]).extra.is_empty());
+    }
+
+    #[test]
+    fn parses_algorithm_names() {
+        assert_eq!("SHA-512".parse(), Ok(HashAlgorithm::Sha512));
+        assert_eq!("blake3".parse(), Ok(HashAlgorithm::Blake3));
+        assert!("crc32".parse::<HashAlgorithm>().is_err());
+    }
+}
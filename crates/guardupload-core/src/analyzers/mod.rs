@@ -0,0 +1,176 @@
+// GuardUpload
+// Criado em: 2025-11-01
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Analisadores auxiliares (entropia, estatísticas estruturais).
+
+pub mod hashes;
+pub mod pii;
+pub mod secrets;
+pub mod structure;
+pub mod tlsh;
+
+use serde::{Deserialize, Serialize};
+
+/// Janela padrão (bytes) para o cálculo de entropia deslizante.
+pub const DEFAULT_ENTROPY_WINDOW: usize = 4096;
+
+/// Estrutura para resultados de análise de entropia.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntropyAnalysis {
+    /// Entropia de Shannon do arquivo inteiro (bits por byte, 0..=8).
+    pub entropy: f32,
+    pub window_size: usize,
+    pub windows: usize,
+    pub min: f32,
+    pub avg: f32,
+    pub max: f32,
+}
+
+/// Calcula a entropia de Shannon (bits por byte) de um bloco.
+pub fn shannon_entropy(data: &[u8]) -> f32 {
+    let mut counts = [0u64; 256];
+    for byte in data {
+        counts[*byte as usize] += 1;
+    }
+    entropy_from_counts(&counts, data.len())
+}
+
+/// Calcula entropia global e estatísticas por janelas deslizantes (passo de meia janela).
+pub fn analyze_entropy(data: &[u8], window_size: usize) -> EntropyAnalysis {
+    let window_size = window_size.max(1);
+    let entropy = shannon_entropy(data);
+
+    if data.len() <= window_size {
+        return EntropyAnalysis {
+            entropy,
+            window_size,
+            windows: 1,
+            min: entropy,
+            avg: entropy,
+            max: entropy,
+        };
+    }
+
+    let step = (window_size / 2).max(1);
+    let mut counts = [0u64; 256];
+    for byte in &data[..window_size] {
+        counts[*byte as usize] += 1;
+    }
+
+    let mut min = f32::MAX;
+    let mut max = 0.0f32;
+    let mut sum = 0.0f64;
+    let mut windows = 0usize;
+    let mut start = 0usize;
+    loop {
+        let value = entropy_from_counts(&counts, window_size);
+        min = min.min(value);
+        max = max.max(value);
+        sum += value as f64;
+        windows += 1;
+
+        let next = start + step;
+        if next + window_size > data.len() {
+            break;
+        }
+        for byte in &data[start..next] {
+            counts[*byte as usize] -= 1;
+        }
+        for byte in &data[start + window_size..next + window_size] {
+            counts[*byte as usize] += 1;
+        }
+        start = next;
+    }
+
+    EntropyAnalysis {
+        entropy,
+        window_size,
+        windows,
+        min,
+        avg: (sum / windows as f64) as f32,
+        max,
+    }
+}
+
+/// Amostra usada para decidir se conteúdo sem MIME textual é texto.
+const TEXT_SAMPLE: usize = 8 * 1024;
+
+/// MIMEs `application/*` tratados como texto.
+const TEXT_MIMES: &[&str] = &[
+    "application/json",
+    "application/xml",
+    "application/yaml",
+    "application/x-yaml",
+    "application/toml",
+    "application/javascript",
+    "application/x-sh",
+    "application/x-shellscript",
+    "application/x-pem-file",
+    "application/sql",
+];
+
+/// Conteúdo textual pelo MIME ou, sem MIME textual, por uma amostra sem bytes
+/// nulos e ao menos 95% imprimível (analisadores de segredos e PII).
+pub(crate) fn is_text_like(mime: &str, data: &[u8]) -> bool {
+    if mime.starts_with("text/") || TEXT_MIMES.contains(&mime) || mime.ends_with("+json") {
+        return true;
+    }
+    let sample = &data[..data.len().min(TEXT_SAMPLE)];
+    !sample.is_empty()
+        && !sample.contains(&0)
+        && sample
+            .iter()
+            .filter(|byte| byte.is_ascii_graphic() || byte.is_ascii_whitespace() || **byte >= 0x80)
+            .count()
+            * 100
+            >= sample.len() * 95
+}
+
+fn entropy_from_counts(counts: &[u64; 256], total: usize) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+    let total = total as f64;
+    let entropy: f64 = counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / total;
+            -p * p.log2()
+        })
+        .sum();
+    entropy as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shannon_entropy_bounds() {
+        assert_eq!(shannon_entropy(b""), 0.0);
+        assert_eq!(shannon_entropy(b"aaaaaaaa"), 0.0);
+        let all_bytes: Vec<u8> = (0..=255).collect();
+        assert!((shannon_entropy(&all_bytes) - 8.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sliding_windows_capture_local_variation() {
+        let mut data = vec![0u8; 64];
+        data.extend((0..=255u8).cycle().take(64));
+        let analysis = analyze_entropy(&data, 32);
+        assert_eq!(analysis.windows, 7);
+        assert_eq!(analysis.min, 0.0);
+        assert!((analysis.max - 5.0).abs() < 1e-4);
+        assert!(analysis.avg > analysis.min && analysis.avg < analysis.max);
+    }
+
+    #[test]
+    fn small_input_uses_single_window() {
+        let analysis = analyze_entropy(b"abcd", 4096);
+        assert_eq!(analysis.windows, 1);
+        assert_eq!(analysis.min, analysis.max);
+    }
+}
@@ -0,0 +1,548 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Dados pessoais (LGPD/GDPR) em texto e no texto extraído de PDFs.
+//!
+//! Reconhece e-mails, CPF e CNPJ (com dígitos verificadores) e números de cartão
+//! (Luhn). Em PDFs, o texto vem dos operadores `Tj`/`TJ` dos content streams
+//! (sem filtro ou `FlateDecode`) e dos streams XMP de metadados; strings
+//! hexadecimais de fontes CID não são mapeadas. O relatório guarda só uma versão
+//! mascarada de cada achado.
+
+use super::is_text_like;
+use crate::decompress::{decompress, default_backend, Codec, DecompressLimits};
+use crate::limits::Deadline;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Bytes de texto examinados por arquivo; o restante é ignorado (`truncated`).
+const MAX_SCAN_BYTES: usize = 16 * 1024 * 1024;
+/// Bytes descomprimidos por stream de PDF.
+const MAX_STREAM_BYTES: u64 = 4 * 1024 * 1024;
+/// Achados registrados por arquivo.
+const MAX_FINDINGS: usize = 100;
+/// Streams de PDF sem texto: imagens, fontes embutidas, xref e object streams.
+const SKIPPED_STREAM_KEYS: &[&[u8]] = &[
+    b"/Image",
+    b"/Length1",
+    b"/Type1C",
+    b"/CIDFontType0C",
+    b"/OpenType",
+    b"/XRef",
+    b"/ObjStm",
+];
+
+/// Dados pessoais encontrados no conteúdo.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PiiAnalysis {
+    pub findings: Vec<PiiFinding>,
+    /// `true` quando o limite de bytes, de achados ou o prazo interrompeu a busca.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub truncated: bool,
+}
+
+/// Um dado pessoal e onde ele está.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PiiFinding {
+    /// `email`, `cpf`, `cnpj` ou `credit_card`.
+    pub kind: String,
+    /// Linha (a partir de 1) do texto; em PDFs, do texto extraído do stream.
+    pub line: u64,
+    /// Objeto PDF (`12 0 obj`) cujo stream contém o achado.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub object: Option<String>,
+    /// Valor mascarado (`j***@example.com`, `***.***.***-25`, `****1111`).
+    pub masked: String,
+}
+
+impl PiiAnalysis {
+    /// Quantidade de achados por tipo.
+    pub fn counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for finding in &self.findings {
+            *counts.entry(finding.kind.as_str()).or_default() += 1;
+        }
+        counts
+    }
+
+    fn push(&mut self, kind: &str, line: u64, object: Option<&str>, value: &[u8]) {
+        if self.findings.len() >= MAX_FINDINGS {
+            self.truncated = true;
+            return;
+        }
+        self.findings.push(PiiFinding {
+            kind: kind.to_string(),
+            line,
+            object: object.map(str::to_string),
+            masked: mask(kind, value),
+        });
+    }
+}
+
+/// Procura dados pessoais em texto ou no texto de um PDF; `None` sem achados.
+pub fn analyze_pii(mime: &str, data: &[u8], deadline: &Deadline) -> Option<PiiAnalysis> {
+    let mut analysis = PiiAnalysis::default();
+    if mime == "application/pdf" || data.starts_with(b"%PDF-") {
+        scan_pdf(data, deadline, &mut analysis);
+    } else if is_text_like(mime, data) {
+        analysis.truncated = data.len() > MAX_SCAN_BYTES;
+        scan_text(&data[..data.len().min(MAX_SCAN_BYTES)], None, &mut analysis);
+    }
+    (!analysis.findings.is_empty()).then_some(analysis)
+}
+
+fn scan_pdf(data: &[u8], deadline: &Deadline, analysis: &mut PiiAnalysis) {
+    let mut budget = MAX_SCAN_BYTES;
+    let mut pos = 0;
+    while let Some(start) = find_from(data, b"stream", pos) {
+        pos = start + 6;
+        // `stream` seguido de fim de linha e precedido de espaço ou `>>` (não `endstream`).
+        let keyword = matches!(data.get(pos), Some(b'\r' | b'\n'))
+            && start
+                .checked_sub(1)
+                .is_some_and(|before| data[before].is_ascii_whitespace() || data[before] == b'>');
+        if !keyword {
+            continue;
+        }
+        if deadline.is_expired() || budget == 0 {
+            analysis.truncated = true;
+            return;
+        }
+        let object_start = data[..start]
+            .windows(4)
+            .rposition(|window| window == b" obj")
+            .unwrap_or(start.saturating_sub(1024));
+        let dictionary = &data[object_start..start];
+        let body_start = pos
+            + data[pos..]
+                .iter()
+                .take_while(|byte| matches!(byte, b'\r' | b'\n'))
+                .count();
+        let body_end = find_from(data, b"endstream", body_start).unwrap_or(data.len());
+        pos = body_end;
+        if SKIPPED_STREAM_KEYS
+            .iter()
+            .any(|key| find_from(dictionary, key, 0).is_some())
+        {
+            continue;
+        }
+        let body = &data[body_start..body_end];
+        let decoded;
+        let body = if find_from(dictionary, b"/FlateDecode", 0).is_some() {
+            // FlateDecode usa o envelope zlib: 2 bytes de cabeçalho antes do deflate.
+            let limits = DecompressLimits::new(MAX_STREAM_BYTES).with_deadline(deadline.clone());
+            match body.get(2..).map(|compressed| {
+                decompress(default_backend(), Codec::Deflate, compressed, &limits)
+            }) {
+                Some(Ok(inflated)) => {
+                    decoded = inflated;
+                    decoded.as_slice()
+                }
+                _ => continue,
+            }
+        } else if find_from(dictionary, b"/Filter", 0).is_some() {
+            continue;
+        } else {
+            body
+        };
+        let text = if find_from(dictionary, b"/Metadata", 0).is_some() {
+            body.to_vec()
+        } else {
+            content_text(body)
+        };
+        let text = &text[..text.len().min(budget)];
+        budget -= text.len();
+        let object = object_name(data, object_start);
+        scan_text(text, object.as_deref(), analysis);
+    }
+}
+
+/// `N G obj` terminado em `offset` (posição de ` obj`).
+fn object_name(data: &[u8], offset: usize) -> Option<String> {
+    let head = std::str::from_utf8(data.get(offset.saturating_sub(24)..offset)?).ok()?;
+    let mut parts = head.split_ascii_whitespace().rev();
+    let generation: u32 = parts.next()?.parse().ok()?;
+    let number: u32 = parts.next()?.parse().ok()?;
+    Some(format!("{number} {generation} obj"))
+}
+
+/// Texto mostrado por um content stream: strings literais dos operadores
+/// `Tj`/`TJ`/`'`/`"`, com quebra de linha nos operadores de posicionamento.
+fn content_text(stream: &[u8]) -> Vec<u8> {
+    let mut text = Vec::new();
+    let mut pending = Vec::new();
+    let mut index = 0;
+    while index < stream.len() {
+        match stream[index] {
+            b'(' => index = literal_string(stream, index, &mut pending),
+            b'%' => {
+                index += stream[index..]
+                    .iter()
+                    .position(|&byte| matches!(byte, b'\r' | b'\n'))
+                    .unwrap_or(stream.len() - index);
+            }
+            b'/' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' => {
+                index += 1;
+                if stream[index - 1] == b'/' {
+                    index += stream[index..]
+                        .iter()
+                        .take_while(|&&byte| is_regular_char(byte))
+                        .count();
+                }
+            }
+            byte if byte.is_ascii_alphabetic() || matches!(byte, b'\'' | b'"') => {
+                let length = stream[index..]
+                    .iter()
+                    .take_while(|&&byte| is_regular_char(byte))
+                    .count()
+                    .max(1);
+                let operator = &stream[index..index + length];
+                index += length;
+                match operator {
+                    b"Tj" | b"TJ" => text.append(&mut pending),
+                    b"'" | b"\"" => {
+                        text.push(b'\n');
+                        text.append(&mut pending);
+                    }
+                    b"Td" | b"TD" | b"T*" | b"Tm" | b"ET" => {
+                        pending.clear();
+                        if text.last().is_some_and(|&byte| byte != b'\n') {
+                            text.push(b'\n');
+                        }
+                    }
+                    _ => pending.clear(),
+                }
+            }
+            _ => index += 1,
+        }
+    }
+    text
+}
+
+/// Decodifica a string literal que começa em `start` (`(`) em `output`; devolve
+/// a posição após o `)` correspondente.
+fn literal_string(stream: &[u8], start: usize, output: &mut Vec<u8>) -> usize {
+    let mut depth = 0usize;
+    let mut index = start;
+    while index < stream.len() {
+        let byte = stream[index];
+        index += 1;
+        match byte {
+            b'(' => {
+                depth += 1;
+                if depth > 1 {
+                    output.push(byte);
+                }
+            }
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return index;
+                }
+                output.push(byte);
+            }
+            b'\\' => {
+                let Some(&escaped) = stream.get(index) else {
+                    break;
+                };
+                index += 1;
+                match escaped {
+                    b'n' => output.push(b'\n'),
+                    b'r' => output.push(b'\r'),
+                    b't' => output.push(b'\t'),
+                    b'b' => output.push(0x08),
+                    b'f' => output.push(0x0c),
+                    b'\r' | b'\n' => {
+                        if escaped == b'\r' && stream.get(index) == Some(&b'\n') {
+                            index += 1;
+                        }
+                    }
+                    b'0'..=b'7' => {
+                        let mut value = u32::from(escaped - b'0');
+                        for _ in 0..2 {
+                            match stream.get(index) {
+                                Some(digit @ b'0'..=b'7') => {
+                                    value = value * 8 + u32::from(digit - b'0');
+                                    index += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        output.push(value as u8);
+                    }
+                    other => output.push(other),
+                }
+            }
+            _ => output.push(byte),
+        }
+    }
+    stream.len()
+}
+
+fn is_regular_char(byte: u8) -> bool {
+    !byte.is_ascii_whitespace() && !b"()<>[]{}/%".contains(&byte)
+}
+
+fn scan_text(text: &[u8], object: Option<&str>, analysis: &mut PiiAnalysis) {
+    let mut line = 1;
+    let mut cursor = 0;
+    while cursor < text.len() {
+        let byte = text[cursor];
+        if byte == b'\n' {
+            line += 1;
+            cursor += 1;
+        } else if byte == b'@' {
+            match email_at(text, cursor) {
+                Some((start, end)) => {
+                    analysis.push("email", line, object, &text[start..end]);
+                    cursor = end;
+                }
+                None => cursor += 1,
+            }
+        } else if byte.is_ascii_digit()
+            && !cursor
+                .checked_sub(1)
+                .is_some_and(|before| text[before].is_ascii_alphanumeric())
+        {
+            let end = numeric_run_end(text, cursor);
+            if !text.get(end).is_some_and(u8::is_ascii_alphanumeric) {
+                for (kind, value) in classify_number(&text[cursor..end]) {
+                    analysis.push(kind, line, object, value);
+                }
+            }
+            cursor = end;
+        } else {
+            cursor += 1;
+        }
+    }
+}
+
+/// E-mail em torno do `@` em `at`: parte local e domínio com TLD alfabético.
+fn email_at(text: &[u8], at: usize) -> Option<(usize, usize)> {
+    let is_local = |byte: &u8| byte.is_ascii_alphanumeric() || b"._%+-".contains(byte);
+    let is_domain = |byte: &u8| byte.is_ascii_alphanumeric() || b".-".contains(byte);
+    let start = at
+        - text[..at]
+            .iter()
+            .rev()
+            .take_while(|byte| is_local(byte))
+            .count();
+    let start = start
+        + text[start..at]
+            .iter()
+            .take_while(|&&byte| byte == b'.')
+            .count();
+    let domain_len = text[at + 1..]
+        .iter()
+        .take_while(|byte| is_domain(byte))
+        .count();
+    let domain = text[at + 1..at + 1 + domain_len].trim_ascii_end();
+    let domain = match domain.iter().rposition(u8::is_ascii_alphanumeric) {
+        Some(last) => &domain[..=last],
+        None => return None,
+    };
+    let tld = domain.rsplit(|&byte| byte == b'.').next()?;
+    let valid = start < at
+        && domain.contains(&b'.')
+        && !domain.windows(2).any(|pair| pair == b"..")
+        && tld.len() >= 2
+        && tld.iter().all(u8::is_ascii_alphabetic);
+    valid.then_some((start, at + 1 + domain.len()))
+}
+
+/// Fim de uma sequência de dígitos com separadores simples (`.`, `-`, `/`, espaço).
+fn numeric_run_end(text: &[u8], start: usize) -> usize {
+    let mut end = start;
+    loop {
+        end += text[end..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        match (text.get(end), text.get(end + 1)) {
+            (Some(b'.' | b'-' | b'/' | b' '), Some(next)) if next.is_ascii_digit() => end += 1,
+            _ => return end,
+        }
+    }
+}
+
+/// Documentos e cartões na sequência numérica; se ela não for um só valor,
+/// cada trecho separado por espaço é examinado à parte.
+fn classify_number(run: &[u8]) -> Vec<(&'static str, &[u8])> {
+    let digits: Vec<u8> = run
+        .iter()
+        .filter(|byte| byte.is_ascii_digit())
+        .map(|byte| byte - b'0')
+        .collect();
+    let plain = digits.len() == run.len();
+    if (plain || has_shape(run, b"ddd.ddd.ddd-dd")) && is_valid_cpf(&digits) {
+        return vec![("cpf", run)];
+    }
+    if (plain || has_shape(run, b"dd.ddd.ddd/dddd-dd")) && is_valid_cnpj(&digits) {
+        return vec![("cnpj", run)];
+    }
+    if is_card_number(run, &digits) {
+        return vec![("credit_card", run)];
+    }
+    if run.contains(&b' ') {
+        return run
+            .split(|&byte| byte == b' ')
+            .flat_map(classify_number)
+            .collect();
+    }
+    Vec::new()
+}
+
+/// `shape` com `d` no lugar de cada dígito (`ddd.ddd.ddd-dd`).
+fn has_shape(run: &[u8], shape: &[u8]) -> bool {
+    run.len() == shape.len()
+        && run
+            .iter()
+            .zip(shape)
+            .all(|(byte, expected)| match expected {
+                b'd' => byte.is_ascii_digit(),
+                _ => byte == expected,
+            })
+}
+
+fn all_same(digits: &[u8]) -> bool {
+    digits.windows(2).all(|pair| pair[0] == pair[1])
+}
+
+fn is_valid_cpf(digits: &[u8]) -> bool {
+    let check = |length: usize| {
+        let sum: u32 = digits[..length]
+            .iter()
+            .enumerate()
+            .map(|(index, &digit)| u32::from(digit) * (length as u32 + 1 - index as u32))
+            .sum();
+        (sum * 10 % 11 % 10) as u8
+    };
+    digits.len() == 11 && !all_same(digits) && check(9) == digits[9] && check(10) == digits[10]
+}
+
+fn is_valid_cnpj(digits: &[u8]) -> bool {
+    const WEIGHTS: [u32; 13] = [6, 5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2];
+    let check = |length: usize| {
+        let sum: u32 = digits[..length]
+            .iter()
+            .zip(&WEIGHTS[13 - length..])
+            .map(|(&digit, weight)| u32::from(digit) * weight)
+            .sum();
+        match sum % 11 {
+            0 | 1 => 0,
+            rest => (11 - rest) as u8,
+        }
+    };
+    digits.len() == 14 && !all_same(digits) && check(12) == digits[12] && check(13) == digits[13]
+}
+
+/// 13 a 19 dígitos de bandeira conhecida (2–6), contínuos ou agrupados por um
+/// mesmo separador (espaço ou hífen), com dígito de Luhn válido.
+fn is_card_number(run: &[u8], digits: &[u8]) -> bool {
+    let mut separators = run.iter().filter(|byte| !byte.is_ascii_digit());
+    let consistent = match separators.next() {
+        None => true,
+        Some(first) => matches!(first, b' ' | b'-') && separators.all(|byte| byte == first),
+    };
+    let luhn = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, &digit)| match index % 2 {
+            0 => u32::from(digit),
+            _ if digit >= 5 => u32::from(digit) * 2 - 9,
+            _ => u32::from(digit) * 2,
+        })
+        .sum::<u32>()
+        % 10
+        == 0;
+    consistent
+        && (13..=19).contains(&digits.len())
+        && (2..=6).contains(&digits[0])
+        && !all_same(digits)
+        && luhn
+}
+
+fn mask(kind: &str, value: &[u8]) -> String {
+    let value = String::from_utf8_lossy(value);
+    let digits: String = value.chars().filter(char::is_ascii_digit).collect();
+    let tail = |count: usize| &digits[digits.len().saturating_sub(count)..];
+    match kind {
+        "email" => match value.split_once('@') {
+            Some((local, domain)) => {
+                format!("{}***@{domain}", local.chars().next().unwrap_or_default())
+            }
+            None => "***".to_string(),
+        },
+        "cpf" => format!("***.***.***-{}", tail(2)),
+        "cnpj" => format!("**.***.***/****-{}", tail(2)),
+        _ => format!("****{}", tail(4)),
+    }
+}
+
+fn find_from(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| from + position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn kinds(analysis: &PiiAnalysis) -> Vec<(&str, u64, &str)> {
+        analysis
+            .findings
+            .iter()
+            .map(|finding| (finding.kind.as_str(), finding.line, finding.masked.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn personal_data_is_found_in_text_and_pdf_content_streams() {
+        let text = b"cliente: joao.silva@example.com.br\n\
+            CPF 529.982.247-25 (invalido: 529.982.247-26, 111.111.111-11)\n\
+            CNPJ 11222333000181 pedido 2024 12345678901234\n\
+            cartao 4111 1111 1111 1111 validade 12/30\n\
+            versao 1.2.3 @admin user@localhost";
+        let analysis = analyze_pii("text/plain", text, &Deadline::none()).expect("pii");
+        assert_eq!(
+            kinds(&analysis),
+            [
+                ("email", 1, "j***@example.com.br"),
+                ("cpf", 2, "***.***.***-25"),
+                ("cnpj", 3, "**.***.***/****-81"),
+                ("credit_card", 4, "****1111"),
+            ]
+        );
+        assert_eq!(analysis.counts()["cpf"], 1);
+
+        let content = b"BT /F1 12 Tf 72 700 Td (Titular: Maria) Tj T* [(52998)-20(224725)] TJ ET";
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut pdf =
+            b"%PDF-1.7\n4 0 obj\n<< /Length 99 /Filter /FlateDecode >>\nstream\n".to_vec();
+        pdf.extend_from_slice(&compressed);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n%%EOF\n");
+        let analysis = analyze_pii("application/pdf", &pdf, &Deadline::none()).expect("pii");
+        assert_eq!(analysis.findings.len(), 1);
+        assert_eq!(analysis.findings[0].kind, "cpf");
+        assert_eq!(analysis.findings[0].line, 2);
+        assert_eq!(analysis.findings[0].object.as_deref(), Some("4 0 obj"));
+
+        assert!(analyze_pii(
+            "image/png",
+            b"\x89PNG\0\0 529.982.247-25",
+            &Deadline::none()
+        )
+        .is_none());
+    }
+}
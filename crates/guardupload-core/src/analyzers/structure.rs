@@ -0,0 +1,271 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Bytes fora da estrutura de formatos conhecidos e arquivos poliglotas.
+//!
+//! Verifica dados após o EOI do JPEG e o IEND do PNG, bytes antes do primeiro
+//! cabeçalho local de um ZIP (localizado pelo EOCD) e PDFs cujo `%PDF-` não está
+//! no offset 0. Conteúdo válido em mais de um desses formatos é poliglota.
+
+use serde::{Deserialize, Serialize};
+
+/// Distância máxima do início em que leitores de PDF aceitam o cabeçalho `%PDF-`.
+const PDF_HEADER_WINDOW: usize = 1024;
+/// Distância máxima do fim em que o `%%EOF` de um PDF é procurado.
+const PDF_TRAILER_WINDOW: usize = 1024;
+/// EOCD (22 bytes) mais o maior comentário possível.
+const ZIP_EOCD_WINDOW: usize = 22 + u16::MAX as usize;
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Resultado da análise estrutural.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StructureAnalysis {
+    /// Formatos cuja estrutura é válida no conteúdo (`jpeg`, `png`, `zip`, `pdf`).
+    pub formats: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub anomalies: Vec<StructureAnomaly>,
+}
+
+/// Bytes fora da estrutura de um formato.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructureAnomaly {
+    /// `trailing_data` (após o fim do formato) ou `prepended_data` (antes do início).
+    pub kind: String,
+    pub format: String,
+    pub bytes: u64,
+}
+
+impl StructureAnalysis {
+    /// Conteúdo válido em mais de um formato ao mesmo tempo.
+    pub fn is_polyglot(&self) -> bool {
+        self.formats.len() > 1
+    }
+
+    /// Nada a reportar: um único formato, sem bytes fora da estrutura.
+    pub fn is_clean(&self) -> bool {
+        self.anomalies.is_empty() && !self.is_polyglot()
+    }
+
+    fn push(&mut self, kind: &str, format: &str, bytes: usize) {
+        self.anomalies.push(StructureAnomaly {
+            kind: kind.to_string(),
+            format: format.to_string(),
+            bytes: bytes as u64,
+        });
+    }
+}
+
+/// Analisa a estrutura de `data`; `None` quando nenhum formato conhecido é reconhecido.
+pub fn analyze_structure(data: &[u8]) -> Option<StructureAnalysis> {
+    let mut analysis = StructureAnalysis::default();
+
+    if let Some(end) = jpeg_end(data) {
+        analysis.formats.push("jpeg".to_string());
+        if has_payload(&data[end..]) {
+            analysis.push("trailing_data", "jpeg", data.len() - end);
+        }
+    }
+    if let Some(end) = png_end(data) {
+        analysis.formats.push("png".to_string());
+        if has_payload(&data[end..]) {
+            analysis.push("trailing_data", "png", data.len() - end);
+        }
+    }
+    if let Some(offset) = pdf_offset(data) {
+        analysis.formats.push("pdf".to_string());
+        if offset > 0 {
+            analysis.push("prepended_data", "pdf", offset);
+        }
+    }
+    if let Some(prepended) = zip_prepended(data) {
+        analysis.formats.push("zip".to_string());
+        if prepended > 0 {
+            analysis.push("prepended_data", "zip", prepended);
+        }
+    }
+
+    (!analysis.formats.is_empty()).then_some(analysis)
+}
+
+/// Sobras compostas só de zeros ou espaços (preenchimento comum de câmeras e
+/// editores) não contam como dados extras.
+fn has_payload(rest: &[u8]) -> bool {
+    rest.iter()
+        .any(|byte| *byte != 0 && !byte.is_ascii_whitespace())
+}
+
+/// Offset logo após o EOI do primeiro quadro JPEG, seguindo os segmentos e os
+/// dados de varredura entrópica.
+fn jpeg_end(data: &[u8]) -> Option<usize> {
+    if !data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return None;
+    }
+    let mut pos = 2;
+    loop {
+        while data.get(pos) == Some(&0xFF) && data.get(pos + 1) == Some(&0xFF) {
+            pos += 1;
+        }
+        if *data.get(pos)? != 0xFF {
+            return None;
+        }
+        let marker = *data.get(pos + 1)?;
+        pos += 2;
+        match marker {
+            0xD9 => return Some(pos),
+            0x01 | 0xD0..=0xD7 => continue,
+            _ => {}
+        }
+        let length = u16::from_be_bytes([*data.get(pos)?, *data.get(pos + 1)?]) as usize;
+        if length < 2 {
+            return None;
+        }
+        pos = pos.checked_add(length)?;
+        if pos > data.len() {
+            return None;
+        }
+        if marker == 0xDA {
+            // Dados entrópicos: 0xFF só aparece como 0xFF00, RSTn ou marcador real.
+            loop {
+                let found = data.get(pos..)?.iter().position(|byte| *byte == 0xFF)?;
+                pos += found;
+                match *data.get(pos + 1)? {
+                    0x00 | 0xD0..=0xD7 | 0xFF => pos += 1,
+                    _ => break,
+                }
+            }
+        }
+    }
+}
+
+/// Offset logo após o chunk IEND (incluindo o CRC).
+fn png_end(data: &[u8]) -> Option<usize> {
+    if !data.starts_with(PNG_SIGNATURE) {
+        return None;
+    }
+    let mut pos = PNG_SIGNATURE.len();
+    loop {
+        let header = data.get(pos..pos + 8)?;
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let end = pos.checked_add(12)?.checked_add(length)?;
+        if end > data.len() {
+            return None;
+        }
+        if &header[4..8] == b"IEND" {
+            return Some(end);
+        }
+        pos = end;
+    }
+}
+
+/// Offset do cabeçalho `%PDF-` quando ele está no início e há `%%EOF` no fim.
+fn pdf_offset(data: &[u8]) -> Option<usize> {
+    let head = &data[..data.len().min(PDF_HEADER_WINDOW)];
+    let offset = head.windows(5).position(|window| window == b"%PDF-")?;
+    let tail = &data[data.len().saturating_sub(PDF_TRAILER_WINDOW)..];
+    tail.windows(5)
+        .any(|window| window == b"%%EOF")
+        .then_some(offset)
+}
+
+/// Bytes antes do primeiro cabeçalho local de um ZIP localizado pelo EOCD.
+///
+/// O diretório central começa em `eocd - cd_size`; a diferença para o offset
+/// gravado no EOCD é o que foi colocado antes do arquivo (SFX, imagem etc.).
+fn zip_prepended(data: &[u8]) -> Option<usize> {
+    let window_start = data.len().saturating_sub(ZIP_EOCD_WINDOW);
+    let eocd = data[window_start..]
+        .windows(4)
+        .rposition(|window| window == b"PK\x05\x06")?
+        + window_start;
+    let record = data.get(eocd..eocd + 22)?;
+    let cd_size = u32::from_le_bytes([record[12], record[13], record[14], record[15]]) as usize;
+    let cd_offset = u32::from_le_bytes([record[16], record[17], record[18], record[19]]) as usize;
+    if cd_offset == u32::MAX as usize {
+        // ZIP64: os offsets reais ficam no registro estendido.
+        return None;
+    }
+    let cd_start = eocd.checked_sub(cd_size)?;
+    let prepended = cd_start.checked_sub(cd_offset)?;
+    let entries = u16::from_le_bytes([record[10], record[11]]);
+    let central_ok = entries == 0 || data.get(cd_start..cd_start + 4) == Some(b"PK\x01\x02");
+    let local_ok = entries == 0 || data.get(prepended..prepended + 4) == Some(b"PK\x03\x04");
+    (central_ok && local_ok).then_some(prepended)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jpeg_bytes() -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x4A, 0x46];
+        // SOS com dados entrópicos contendo 0xFF00 e um RST.
+        data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0xFF, 0x00, 0x34, 0xFF, 0xD0]);
+        data.extend_from_slice(&[0x56, 0xFF, 0xD9]);
+        data
+    }
+
+    fn zip_bytes() -> Vec<u8> {
+        let mut local = b"PK\x03\x04".to_vec();
+        local.extend_from_slice(&[0; 22]);
+        local.extend_from_slice(&[1, 0, 0, 0]);
+        local.push(b'a');
+        let mut central = b"PK\x01\x02".to_vec();
+        central.extend_from_slice(&[0; 24]);
+        central.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        central.push(b'a');
+        let mut data = local.clone();
+        data.extend_from_slice(&central);
+        data.extend_from_slice(b"PK\x05\x06\0\0\0\0\x01\0\x01\0");
+        data.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(local.len() as u32).to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data
+    }
+
+    #[test]
+    fn clean_files_have_no_anomalies() {
+        let jpeg = analyze_structure(&jpeg_bytes()).expect("jpeg");
+        assert_eq!(jpeg.formats, vec!["jpeg"]);
+        assert!(jpeg.is_clean());
+        assert!(analyze_structure(&zip_bytes()).expect("zip").is_clean());
+        assert!(analyze_structure(b"%PDF-1.4\n%%EOF\n")
+            .expect("pdf")
+            .is_clean());
+        assert!(analyze_structure(b"texto simples").is_none());
+
+        let mut padded = jpeg_bytes();
+        padded.extend_from_slice(&[0, 0, b'\n']);
+        assert!(analyze_structure(&padded).expect("jpeg").is_clean());
+    }
+
+    #[test]
+    fn detects_trailing_prepended_data_and_polyglots() {
+        let mut jpeg_zip = jpeg_bytes();
+        let jpeg_len = jpeg_zip.len();
+        jpeg_zip.extend_from_slice(&zip_bytes());
+        let analysis = analyze_structure(&jpeg_zip).expect("jpeg+zip");
+        assert_eq!(analysis.formats, vec!["jpeg", "zip"]);
+        assert!(analysis.is_polyglot());
+        assert_eq!(analysis.anomalies[0].kind, "trailing_data");
+        assert_eq!(analysis.anomalies[1].kind, "prepended_data");
+        assert_eq!(analysis.anomalies[1].bytes, jpeg_len as u64);
+
+        let mut pdf_zip = b"%PDF-1.4\n1 0 obj\n<< >>\nendobj\n".to_vec();
+        pdf_zip.extend_from_slice(&zip_bytes());
+        pdf_zip.extend_from_slice(b"%%EOF\n");
+        let analysis = analyze_structure(&pdf_zip).expect("pdf+zip");
+        assert_eq!(analysis.formats, vec!["pdf", "zip"]);
+
+        let analysis = analyze_structure(b"MZ\0\0%PDF-1.7\n%%EOF").expect("pdf");
+        assert_eq!(analysis.anomalies[0].kind, "prepended_data");
+        assert_eq!(analysis.anomalies[0].bytes, 4);
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend_from_slice(&[0, 0, 0, 0]);
+        png.extend_from_slice(b"IEND\xAE\x42\x60\x82<script>");
+        let analysis = analyze_structure(&png).expect("png");
+        assert_eq!(analysis.anomalies[0].bytes, 8);
+    }
+}
@@ -0,0 +1,291 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! TLSH (Trend Micro Locality Sensitive Hash, 128 buckets, checksum de 1 byte).
+//!
+//! Digest no formato `T1` + 70 dígitos hex; arquivos parecidos têm distância
+//! pequena (0 = idênticos para o algoritmo, valores acima de ~100 indicam pouca relação).
+
+/// Tamanho mínimo aceito pelo TLSH.
+pub const TLSH_MIN_LEN: usize = 50;
+
+const BUCKETS: usize = 128;
+const CODE_SIZE: usize = 32;
+const WINDOW: usize = 5;
+
+/// Tabela de Pearson do TLSH de referência.
+const V_TABLE: [u8; 256] = [
+    1, 87, 49, 12, 176, 178, 102, 166, 121, 193, 6, 84, 249, 230, 44, 163, 14, 197, 213, 181, 161,
+    85, 218, 80, 64, 239, 24, 226, 236, 142, 38, 200, 110, 177, 104, 103, 141, 253, 255, 50, 77,
+    101, 81, 18, 45, 96, 31, 222, 25, 107, 190, 70, 86, 237, 240, 34, 72, 242, 20, 214, 244, 227,
+    149, 235, 97, 234, 57, 22, 60, 250, 82, 175, 208, 5, 127, 199, 111, 62, 135, 248, 174, 169,
+    211, 58, 66, 154, 106, 195, 245, 171, 17, 187, 182, 179, 0, 243, 132, 56, 148, 75, 128, 133,
+    158, 100, 130, 126, 91, 13, 153, 246, 216, 219, 119, 68, 223, 78, 83, 88, 201, 99, 122, 11, 92,
+    32, 136, 114, 52, 10, 138, 30, 48, 183, 156, 35, 61, 26, 143, 74, 251, 94, 129, 162, 63, 152,
+    170, 7, 115, 167, 241, 206, 3, 150, 55, 59, 151, 220, 90, 53, 23, 131, 125, 173, 15, 238, 79,
+    95, 89, 16, 105, 137, 225, 224, 217, 160, 37, 123, 118, 73, 2, 157, 46, 116, 9, 145, 134, 228,
+    207, 212, 202, 215, 69, 229, 27, 188, 67, 124, 168, 252, 42, 4, 29, 108, 21, 247, 19, 205, 39,
+    203, 233, 40, 186, 147, 198, 192, 155, 33, 164, 191, 98, 204, 165, 180, 117, 76, 140, 36, 210,
+    172, 41, 54, 159, 8, 185, 232, 113, 196, 231, 47, 146, 120, 51, 65, 28, 144, 254, 221, 93, 189,
+    194, 139, 112, 43, 71, 109, 184, 209,
+];
+
+fn b_mapping(salt: u8, i: u8, j: u8, k: u8) -> u8 {
+    let mut h = V_TABLE[salt as usize];
+    h = V_TABLE[(h ^ i) as usize];
+    h = V_TABLE[(h ^ j) as usize];
+    V_TABLE[(h ^ k) as usize]
+}
+
+/// Cálculo incremental do TLSH (aceita os dados em blocos).
+#[derive(Debug, Clone)]
+pub struct TlshBuilder {
+    buckets: [u32; 256],
+    window: [u8; WINDOW],
+    checksum: u8,
+    len: usize,
+}
+
+impl Default for TlshBuilder {
+    fn default() -> Self {
+        Self {
+            buckets: [0; 256],
+            window: [0; WINDOW],
+            checksum: 0,
+            len: 0,
+        }
+    }
+}
+
+impl TlshBuilder {
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            let j = self.len % WINDOW;
+            self.window[j] = byte;
+            if self.len >= WINDOW - 1 {
+                let at = |back: usize| self.window[(j + WINDOW - back) % WINDOW];
+                let (a, b, c, d, e) = (at(0), at(1), at(2), at(3), at(4));
+                self.checksum = b_mapping(0, a, b, self.checksum);
+                for index in [
+                    b_mapping(2, a, b, c),
+                    b_mapping(3, a, b, d),
+                    b_mapping(5, a, c, d),
+                    b_mapping(7, a, c, e),
+                    b_mapping(11, a, b, e),
+                    b_mapping(13, a, d, e),
+                ] {
+                    self.buckets[index as usize] += 1;
+                }
+            }
+            self.len += 1;
+        }
+    }
+
+    /// Digest `T1…`, ou `None` quando os dados são curtos ou uniformes demais.
+    pub fn finalize(&self) -> Option<String> {
+        if self.len < TLSH_MIN_LEN {
+            return None;
+        }
+        let buckets = &self.buckets[..BUCKETS];
+        let nonzero = buckets.iter().filter(|count| **count > 0).count();
+        if nonzero <= 4 * CODE_SIZE / 2 {
+            return None;
+        }
+        let mut sorted = buckets.to_vec();
+        sorted.sort_unstable();
+        let (q1, q2, q3) = (sorted[31], sorted[63], sorted[95]);
+        if q3 == 0 {
+            return None;
+        }
+
+        let mut code = [0u8; CODE_SIZE];
+        for (index, byte) in code.iter_mut().enumerate() {
+            for (slot, &count) in buckets[index * 4..index * 4 + 4].iter().enumerate() {
+                let level = if count > q3 {
+                    3
+                } else if count > q2 {
+                    2
+                } else if count > q1 {
+                    1
+                } else {
+                    0
+                };
+                *byte |= level << (slot * 2);
+            }
+        }
+
+        let digest = Tlsh {
+            checksum: self.checksum,
+            lvalue: l_capturing(self.len),
+            q1_ratio: ((q1 as u64 * 100 / q3 as u64) % 16) as u8,
+            q2_ratio: ((q2 as u64 * 100 / q3 as u64) % 16) as u8,
+            code,
+        };
+        Some(digest.to_hex())
+    }
+}
+
+/// Codificação logarítmica do tamanho usada pelo TLSH.
+fn l_capturing(len: usize) -> u8 {
+    let len = len as f64;
+    let value = if len <= 656.0 {
+        (len.ln() / 1.5f64.ln()).floor()
+    } else if len <= 3199.0 {
+        (len.ln() / 1.3f64.ln() - 8.72777).floor()
+    } else {
+        (len.ln() / 1.1f64.ln() - 62.5472).floor()
+    };
+    (value as i64 & 0xFF) as u8
+}
+
+fn swap_nibbles(byte: u8) -> u8 {
+    byte.rotate_left(4)
+}
+
+/// Digest TLSH decodificado, usado para calcular distâncias.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tlsh {
+    checksum: u8,
+    lvalue: u8,
+    q1_ratio: u8,
+    q2_ratio: u8,
+    code: [u8; CODE_SIZE],
+}
+
+impl Tlsh {
+    /// Calcula o digest de um bloco completo.
+    pub fn digest(data: &[u8]) -> Option<String> {
+        let mut builder = TlshBuilder::default();
+        builder.update(data);
+        builder.finalize()
+    }
+
+    fn to_hex(&self) -> String {
+        let mut bytes = Vec::with_capacity(3 + CODE_SIZE);
+        bytes.push(swap_nibbles(self.checksum));
+        bytes.push(swap_nibbles(self.lvalue));
+        bytes.push((self.q1_ratio << 4) | self.q2_ratio);
+        bytes.extend(self.code.iter().rev());
+        format!("T1{}", hex::encode_upper(bytes))
+    }
+
+    /// Interpreta um digest `T1…` (o prefixo é opcional).
+    pub fn parse(digest: &str) -> Option<Self> {
+        let digest = digest.trim();
+        let hex_part = digest
+            .strip_prefix("T1")
+            .or_else(|| digest.strip_prefix("t1"))
+            .unwrap_or(digest);
+        let bytes = hex::decode(hex_part).ok()?;
+        if bytes.len() != 3 + CODE_SIZE {
+            return None;
+        }
+        let mut code = [0u8; CODE_SIZE];
+        for (slot, byte) in code.iter_mut().rev().zip(&bytes[3..]) {
+            *slot = *byte;
+        }
+        Some(Self {
+            checksum: swap_nibbles(bytes[0]),
+            lvalue: swap_nibbles(bytes[1]),
+            q1_ratio: bytes[2] >> 4,
+            q2_ratio: bytes[2] & 0x0F,
+            code,
+        })
+    }
+
+    /// Distância TLSH (com componente de tamanho); 0 indica digests iguais.
+    pub fn distance(&self, other: &Tlsh) -> u32 {
+        let mut diff = match mod_diff(self.lvalue, other.lvalue, 256) {
+            0 => 0,
+            1 => 1,
+            ldiff => ldiff * 12,
+        };
+        for (a, b) in [
+            (self.q1_ratio, other.q1_ratio),
+            (self.q2_ratio, other.q2_ratio),
+        ] {
+            let qdiff = mod_diff(a, b, 16);
+            diff += if qdiff <= 1 { qdiff } else { (qdiff - 1) * 12 };
+        }
+        if self.checksum != other.checksum {
+            diff += 1;
+        }
+        diff + self
+            .code
+            .iter()
+            .zip(other.code.iter())
+            .map(|(a, b)| code_distance(*a, *b))
+            .sum::<u32>()
+    }
+}
+
+fn mod_diff(x: u8, y: u8, range: u32) -> u32 {
+    let direct = (x as i32 - y as i32).unsigned_abs();
+    direct.min(range - direct)
+}
+
+/// Soma das diferenças entre pares de bits (diferença 3 pesa 6).
+fn code_distance(a: u8, b: u8) -> u32 {
+    (0..4)
+        .map(|slot| {
+            let x = (a >> (slot * 2)) & 0b11;
+            let y = (b >> (slot * 2)) & 0b11;
+            match (x as i32 - y as i32).unsigned_abs() {
+                3 => 6,
+                d => d,
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(seed: u32, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn digest_roundtrips_and_streams_in_chunks() {
+        let data = sample(7, 4096);
+        let digest = Tlsh::digest(&data).expect("digest");
+        assert_eq!(digest.len(), 72);
+        assert!(digest.starts_with("T1"));
+
+        let mut builder = TlshBuilder::default();
+        for chunk in data.chunks(333) {
+            builder.update(chunk);
+        }
+        assert_eq!(builder.finalize().as_deref(), Some(digest.as_str()));
+
+        let parsed = Tlsh::parse(&digest).expect("parse");
+        assert_eq!(parsed.to_hex(), digest);
+        assert_eq!(parsed.distance(&parsed), 0);
+
+        assert!(Tlsh::digest(b"curto demais").is_none());
+        assert!(Tlsh::digest(&[0u8; 4096]).is_none());
+    }
+
+    #[test]
+    fn variants_are_closer_than_unrelated_data() {
+        let original = sample(1, 8192);
+        let mut variant = original.clone();
+        variant[100..140].copy_from_slice(&[0x41; 40]);
+        let unrelated = sample(2, 8192);
+
+        let hash = |data: &[u8]| Tlsh::parse(&Tlsh::digest(data).expect("digest")).expect("parse");
+        let near = hash(&original).distance(&hash(&variant));
+        let far = hash(&original).distance(&hash(&unrelated));
+        assert!(near < far, "near={near} far={far}");
+        assert!(near <= 50, "near={near}");
+    }
+}
@@ -0,0 +1,174 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Serialização JSON canônica (RFC 8785 / JCS) para hashing, assinatura e
+//! comparação byte a byte de relatórios.
+//!
+//! Sem espaços, chaves ordenadas por unidades UTF-16 e números no formato do
+//! `Number.prototype.toString` do ECMAScript.
+
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+/// Maior inteiro representável sem perda em um double (2^53).
+const MAX_SAFE_INTEGER: u64 = 1 << 53;
+
+/// Serializa `value` em JSON canônico.
+///
+/// O valor passa antes pelo serializador padrão, de modo que campos `f32` mantêm
+/// a representação curta (`3.4182959`) em vez da expansão para `f64`.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    let value: Value = serde_json::from_str(&serde_json::to_string(value)?)?;
+    let mut output = String::new();
+    write_value(&value, &mut output)?;
+    Ok(output)
+}
+
+fn write_value(value: &Value, output: &mut String) -> serde_json::Result<()> {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Bool(flag) => output.push_str(if *flag { "true" } else { "false" }),
+        Value::Number(number) => output.push_str(&format_number(number)),
+        // O escape do serde_json (só `"`, `\` e controles, hex minúsculo) é o do JCS.
+        Value::String(text) => output.push_str(&serde_json::to_string(text)?),
+        Value::Array(items) => {
+            output.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_value(item, output)?;
+            }
+            output.push(']');
+        }
+        Value::Object(map) => write_object(map, output)?,
+    }
+    Ok(())
+}
+
+fn write_object(map: &Map<String, Value>, output: &mut String) -> serde_json::Result<()> {
+    let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+    output.push('{');
+    for (index, (key, value)) in entries.into_iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        output.push_str(&serde_json::to_string(key)?);
+        output.push(':');
+        write_value(value, output)?;
+    }
+    output.push('}');
+    Ok(())
+}
+
+fn format_number(number: &Number) -> String {
+    if let Some(unsigned) = number.as_u64().filter(|n| *n <= MAX_SAFE_INTEGER) {
+        return unsigned.to_string();
+    }
+    if let Some(signed) = number
+        .as_i64()
+        .filter(|n| n.unsigned_abs() <= MAX_SAFE_INTEGER)
+    {
+        return signed.to_string();
+    }
+    format_double(number.as_f64().unwrap_or(0.0))
+}
+
+/// `Number.prototype.toString` (ECMA-262, 7.1.12.1) para valores finitos.
+fn format_double(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    // `{:e}` produz os dígitos mínimos que reconstroem o valor: "d.ddde±x".
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific.split_once('e').expect("formato científico");
+    let digits: String = mantissa.chars().filter(|ch| *ch != '.').collect();
+    let exponent: i32 = exponent.parse().expect("expoente");
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    let body = if k <= n && n <= 21 {
+        format!("{digits}{}", "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{digits}", "0".repeat((-n) as usize))
+    } else {
+        let sign = if n - 1 < 0 { '-' } else { '+' };
+        let (head, tail) = digits.split_at(1);
+        let fraction = if tail.is_empty() {
+            String::new()
+        } else {
+            format!(".{tail}")
+        };
+        format!("{head}{fraction}e{sign}{}", (n - 1).abs())
+    };
+    if value < 0.0 {
+        format!("-{body}")
+    } else {
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn numbers_follow_ecmascript_formatting() {
+        let cases = [
+            (1e21, "1e+21"),
+            (1e-7, "1e-7"),
+            (333_333_333.333_333_3, "333333333.3333333"),
+            (4.5, "4.5"),
+            (0.002, "0.002"),
+            (1.234e-6, "0.000001234"),
+            (1e30, "1e+30"),
+            (-1.5e-9, "-1.5e-9"),
+            (1.234_567_890_123_456_8e20, "123456789012345680000"),
+            (-0.0, "0"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(format_double(value), expected, "{value:e}");
+        }
+    }
+
+    #[test]
+    fn objects_are_sorted_by_utf16_and_compact() {
+        // Exemplo de ordenação da seção 3.2.3 da RFC 8785.
+        let value = json!({
+            "\u{20ac}": "Euro Sign",
+            "\r": "Carriage Return",
+            "\u{fb33}": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\u{1f600}": "Emoji: Grinning Face",
+            "\u{0080}": "Control",
+            "\u{00f6}": "Latin Small Letter O With Diaeresis",
+            "lista": [1, 2.50, null, true, "a\u{1f}/"],
+        });
+        let canonical = to_string(&value).unwrap();
+        let keys = [
+            "\\r",
+            "1",
+            "lista",
+            "\u{0080}",
+            "\u{00f6}",
+            "\u{20ac}",
+            "\u{1f600}",
+            "\u{fb33}",
+        ];
+        let mut position = 0;
+        for key in keys {
+            let found = canonical[position..]
+                .find(&format!("\"{key}\":"))
+                .unwrap_or_else(|| panic!("{key} fora de ordem em {canonical}"));
+            position += found + 1;
+        }
+        assert!(canonical.contains(r#""lista":[1,2.5,null,true,"a\u001f/"]"#));
+        assert!(canonical.starts_with(r#"{"\r":"Carriage Return","1":"One","#));
+    }
+}
@@ -0,0 +1,1078 @@
+// GuardUpload
+// Criado em: 2025-11-01
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Carregamento e validação de configurações de política em YAML.
+
+use crate::policy::{matches_pattern, ResolvedPolicy};
+use crate::ranges;
+use crate::sniff::CustomSignature;
+use crate::transfer::TransferEncoding;
+use crate::validators::{ValidatorVersion, VersionRequirement};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+
+/// Política embutida conservadora (`--builtin-policy strict`).
+pub const BUILTIN_STRICT_POLICY: &str = include_str!("../policies/strict.yaml");
+
+/// Política embutida permissiva (`--builtin-policy permissive`).
+pub const BUILTIN_PERMISSIVE_POLICY: &str = include_str!("../policies/permissive.yaml");
+
+/// Configuração raiz carregada a partir do YAML de políticas.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PolicyConfig {
+    #[serde(default)]
+    pub defaults: DefaultsSection,
+    #[serde(default)]
+    pub pdf: PdfPolicySection,
+    #[serde(default)]
+    pub image: ImagePolicySection,
+    #[serde(default)]
+    pub archive: ArchivePolicySection,
+    #[serde(default)]
+    pub svg: SvgPolicySection,
+    #[serde(default)]
+    pub office: OfficePolicySection,
+    #[serde(default)]
+    pub pkcs7: Pkcs7PolicySection,
+    #[serde(default)]
+    pub encoding: EncodingPolicySection,
+    #[serde(default)]
+    pub binary: BinaryPolicySection,
+    #[serde(default)]
+    pub filename: FilenamePolicySection,
+    #[serde(default)]
+    pub secrets: SecretsPolicySection,
+    #[serde(default)]
+    pub pii: PiiPolicySection,
+    #[serde(default)]
+    pub hashes: HashListsSection,
+    #[serde(default)]
+    pub report: ReportPolicySection,
+    #[serde(default)]
+    pub session: SessionPolicySection,
+    #[serde(default)]
+    pub validators: ValidatorsSection,
+    #[serde(default)]
+    pub plugins: Vec<PluginSection>,
+    #[serde(default)]
+    pub clamav: ClamavPolicySection,
+    #[serde(default)]
+    pub overrides: Vec<PolicyOverride>,
+    #[serde(default)]
+    pub history: HistoryPolicySection,
+    #[serde(default)]
+    pub notifications: NotificationsPolicySection,
+    #[serde(default)]
+    pub rate_limits: RateLimitsPolicySection,
+    #[serde(default)]
+    pub routing: RoutingPolicySection,
+    #[serde(default)]
+    pub sniff: SniffPolicySection,
+    /// Severidade dos achados de validadores por `<validador>:<código>`, com
+    /// curingas (`pdf:javascript: warn`, `archive:*: deny`); o código é um de
+    /// `details.codes` ou, sem códigos, o status (`deny`/`warn`).
+    #[serde(default)]
+    pub severity_overrides: BTreeMap<String, SeverityOverride>,
+    /// Versão mínima de cada validador de que a política depende
+    /// (`pdf: ">=2"`); binários mais antigos recusam a política.
+    #[serde(default)]
+    pub require: BTreeMap<String, String>,
+    /// Avisos do carregamento: valores acima do teto reduzidos ao limite
+    /// (ver [`crate::ranges`]).
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+impl PolicyConfig {
+    /// Carrega o YAML de política a partir de um caminho.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("falha ao abrir política: {}", path.display()))?;
+        let mut config: PolicyConfig = serde_yaml::from_reader(file)
+            .with_context(|| format!("falha ao parsear YAML {}", path.display()))?;
+        config
+            .hashes
+            .load(Some(path.parent().unwrap_or_else(|| Path::new("."))))?;
+        config.validate_overrides()?;
+        config.validate_rate_limits()?;
+        config.validate_sniff()?;
+        config.validate_severity_overrides()?;
+        config.validate_versions()?;
+        config.warnings = ranges::check_config(&mut config)?;
+        Ok(config)
+    }
+
+    /// Interpreta a política a partir de um texto YAML já carregado, sem acessar
+    /// o sistema de arquivos.
+    ///
+    /// `hashes` só aceita digests literais aqui; listas em arquivo exigem
+    /// [`PolicyConfig::from_path`], que as resolve a partir do diretório da política.
+    pub fn from_yaml_str(raw: &str) -> Result<Self> {
+        let mut config: PolicyConfig =
+            serde_yaml::from_str(raw).context("falha ao parsear YAML de política")?;
+        config.hashes.load(None)?;
+        config.validate_overrides()?;
+        config.validate_rate_limits()?;
+        config.validate_sniff()?;
+        config.validate_severity_overrides()?;
+        config.validate_versions()?;
+        config.warnings = ranges::check_config(&mut config)?;
+        Ok(config)
+    }
+
+    /// Acrescenta um override ao fim da lista (vale sobre os existentes),
+    /// conferindo o `set` como no carregamento.
+    pub fn with_override(mut self, rule: PolicyOverride) -> Result<Self> {
+        self.overrides.push(rule);
+        self.validate_overrides()?;
+        Ok(self)
+    }
+
+    /// Confere se cada `set` dos overrides aponta para campos existentes com valores válidos.
+    fn validate_overrides(&self) -> Result<()> {
+        let base = ResolvedPolicy::from_config(self);
+        for (index, rule) in self.overrides.iter().enumerate() {
+            base.clone()
+                .apply_set(&rule.set)
+                .with_context(|| format!("overrides[{index}].set inválido"))?;
+        }
+        base.clone()
+            .apply_set(&self.history.set)
+            .context("history.set inválido")?;
+        Ok(())
+    }
+
+    fn validate_rate_limits(&self) -> Result<()> {
+        let limits = [
+            ("webhook", &self.rate_limits.webhook),
+            ("clamav", &self.rate_limits.clamav),
+        ];
+        for (name, limit) in limits {
+            if let Some(limit) = limit {
+                anyhow::ensure!(
+                    limit.per_second.is_finite() && limit.per_second > 0.0,
+                    "rate_limits.{name}.per_second deve ser positivo"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_severity_overrides(&self) -> Result<()> {
+        for key in self.severity_overrides.keys() {
+            anyhow::ensure!(
+                key.split_once(':')
+                    .is_some_and(|(validator, code)| !validator.is_empty() && !code.is_empty()),
+                "severity_overrides: chave '{key}' deve ter o formato <validador>:<código>"
+            );
+        }
+        Ok(())
+    }
+
+    /// Confere a sintaxe de `require` e das versões declaradas pelos plugins; a
+    /// existência dos validadores é conferida com o registro final
+    /// ([`ValidatorRegistry::check_requirements`](crate::validators::ValidatorRegistry::check_requirements)).
+    fn validate_versions(&self) -> Result<()> {
+        for (name, requirement) in &self.require {
+            VersionRequirement::parse(requirement)
+                .with_context(|| format!("require.{name} inválido"))?;
+        }
+        for (index, plugin) in self.plugins.iter().enumerate() {
+            if let Some(version) = &plugin.version {
+                ValidatorVersion::parse(version)
+                    .with_context(|| format!("plugins[{index}].version inválido"))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_sniff(&self) -> Result<()> {
+        for (index, section) in self.sniff.custom.iter().enumerate() {
+            CustomSignature::compile(section)
+                .with_context(|| format!("sniff.custom[{index}] inválido"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Valores padrão aplicados a todos os tipos.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DefaultsSection {
+    pub max_size_mb: Option<u32>,
+    #[serde(default)]
+    pub allow_types: Vec<String>,
+    #[serde(default)]
+    pub deny_types: Vec<String>,
+    #[serde(default)]
+    pub entropy_threshold: Option<f32>,
+    /// Tamanho da janela deslizante de entropia (bytes).
+    #[serde(default)]
+    pub entropy_window_bytes: Option<usize>,
+    /// Ação quando a entropia excede o limite (`warn` ou `deny`).
+    #[serde(default)]
+    pub entropy_action: Option<String>,
+    /// Ação para imagens de disco (ISO/UDF/VHD/VMDK/DMG/IMG) e instaladores MSI
+    /// (`allow`, `warn` ou `deny`; padrão `deny`).
+    #[serde(default)]
+    pub images_and_installers: Option<String>,
+    /// Ação para nomes de arquivo com caracteres de controle (CR/LF, ANSI,
+    /// bidi): `allow`, `warn` ou `deny` (padrão `warn`).
+    #[serde(default)]
+    pub unsafe_file_names: Option<String>,
+    /// Bloqueia arquivos cuja extensão implica um MIME diferente do detectado
+    /// (ex.: `fatura.pdf` que é executável).
+    #[serde(default)]
+    pub deny_on_mime_mismatch: Option<bool>,
+    /// Score mínimo (0 a 1) do MIME detectado; abaixo dele, ou com outro MIME
+    /// apontado pelo conteúdo acima dele, o arquivo é tratado como ambíguo.
+    #[serde(default)]
+    pub min_sniff_confidence: Option<f32>,
+    /// Ação para arquivos ambíguos pelo `min_sniff_confidence`: `warn` ou
+    /// `deny` (padrão `warn`).
+    #[serde(default)]
+    pub sniff_confidence_action: Option<String>,
+    /// Ação para arquivos maiores que `--max-read-bytes`, lidos só no início e
+    /// no fim: `allow`, `warn` ou `deny` (padrão `deny`).
+    #[serde(default)]
+    pub partial_read_action: Option<String>,
+    /// Ação para bytes antes ou depois da estrutura de JPEG/PNG/ZIP/PDF e para
+    /// arquivos poliglotas: `allow`, `warn` ou `deny` (padrão `warn`).
+    #[serde(default)]
+    pub structure_action: Option<String>,
+    /// Severidade que provoca código de saída diferente de zero (`deny`, `warn`
+    /// ou `error`); `--fail-on` na linha de comando tem precedência.
+    #[serde(default)]
+    pub fail_on: Option<String>,
+    /// Validadores coletam todas as violações do arquivo (`details.findings`) em vez
+    /// de parar na primeira; `--report-all-findings` liga o modo no `scan`.
+    #[serde(default)]
+    pub report_all_findings: Option<bool>,
+}
+
+/// Política específica para PDFs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfPolicySection {
+    pub allow_javascript: Option<bool>,
+    pub max_pages: Option<u32>,
+    pub forbid_embedded_files: Option<bool>,
+    #[serde(default)]
+    pub forbid_open_action: Option<bool>,
+    #[serde(default)]
+    pub forbid_launch: Option<bool>,
+    #[serde(default)]
+    pub forbid_external_uris: Option<bool>,
+    #[serde(default)]
+    pub forbid_acroform: Option<bool>,
+    #[serde(default)]
+    pub forbid_encrypted: Option<bool>,
+    /// Objetos indiretos, somando os guardados em object streams (`/ObjStm /N`).
+    #[serde(default)]
+    pub max_objects: Option<u64>,
+    /// Fontes embutidas (`/FontFile`, `/FontFile2`, `/FontFile3`).
+    #[serde(default)]
+    pub max_embedded_fonts: Option<u32>,
+    /// Maior stream permitido (bytes entre `stream` e `endstream`, ainda comprimidos).
+    #[serde(default)]
+    pub max_stream_bytes: Option<u64>,
+}
+
+impl Default for PdfPolicySection {
+    fn default() -> Self {
+        Self {
+            allow_javascript: Some(false),
+            max_pages: Some(200),
+            forbid_embedded_files: Some(true),
+            forbid_open_action: Some(false),
+            forbid_launch: Some(true),
+            forbid_external_uris: Some(false),
+            forbid_acroform: Some(false),
+            forbid_encrypted: Some(false),
+            max_objects: None,
+            max_embedded_fonts: None,
+            max_stream_bytes: None,
+        }
+    }
+}
+
+/// Política específica para imagens.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ImagePolicySection {
+    pub max_dimensions: Option<[u32; 2]>,
+    /// Largura e altura mínimas (`[largura, altura]`).
+    #[serde(default)]
+    pub min_dimensions: Option<[u32; 2]>,
+    /// Proporções aceitas como `"L:A"` (ex.: `"1:1"`, `"16:9"`) ou decimal (`"1.91"`).
+    #[serde(default)]
+    pub allowed_aspect_ratios: Vec<String>,
+    /// Diferença relativa tolerada em `allowed_aspect_ratios` (padrão `0.01`).
+    #[serde(default)]
+    pub aspect_ratio_tolerance: Option<f64>,
+    pub max_frames: Option<u32>,
+    /// Conta os frames de GIF pelos cabeçalhos em vez de decodificá-los (menos
+    /// memória; `max_frames` continua valendo).
+    #[serde(default)]
+    pub header_only: Option<bool>,
+    /// Orçamento de pixels decodificados (largura × altura × frames), calculado
+    /// pelos cabeçalhos; padrão 64 MP, `0` desliga.
+    pub max_pixels: Option<u64>,
+    pub strip_metadata: Option<String>,
+    /// Ação quando dimensões/orientação/miniatura EXIF divergem da imagem
+    /// decodificada (`allow`, `warn` ou `deny`; padrão `warn`).
+    #[serde(default)]
+    pub exif_mismatch: Option<String>,
+    /// Nega imagens com coordenadas GPS no EXIF ou no XMP.
+    pub deny_gps_metadata: Option<bool>,
+    /// Gera WARN quando a imagem traz metadados EXIF, XMP ou IPTC.
+    pub warn_on_metadata: Option<bool>,
+}
+
+/// Política específica para arquivos compactados.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ArchivePolicySection {
+    pub zip_max_depth: Option<u32>,
+    pub zip_max_ratio: Option<u32>,
+    pub forbid_symlinks: Option<bool>,
+    pub forbid_path_traversal: Option<bool>,
+    #[serde(default)]
+    pub forbid_encrypted_entries: Option<bool>,
+    /// Lista o conteúdo de imagens ISO 9660 aplicando as checagens de listagem
+    /// (`max_entries`, path traversal, nomes repetidos, `zip_max_depth`).
+    #[serde(default)]
+    pub enumerate_iso: Option<bool>,
+    /// Entradas que o ZIP precisa conter (caminho exato, ex.: `data.csv`).
+    #[serde(default)]
+    pub required_entries: Vec<String>,
+    /// Quando não vazia, só são aceitas entradas que casem com algum glob
+    /// (`*` e `?` não atravessam `/`; `**` atravessa) ou com `required_entries`.
+    #[serde(default)]
+    pub allowed_entry_globs: Vec<String>,
+    /// Anomalias de metadados típicas de ZIPs forjados (desligadas por padrão).
+    #[serde(default)]
+    pub metadata_checks: ArchiveMetadataChecks,
+    /// Máximo de entradas no central directory.
+    #[serde(default)]
+    pub max_entries: Option<u32>,
+    /// Máximo da soma dos tamanhos descomprimidos de todas as entradas (MB); com
+    /// `verify_crc`, também limita o volume efetivamente descomprimido.
+    #[serde(default)]
+    pub max_total_uncompressed_mb: Option<u64>,
+    /// Descomprime cada entrada e confere CRC-32 e tamanho declarado.
+    #[serde(default)]
+    pub verify_crc: Option<bool>,
+    /// Nega entradas cujos dados se sobrepõem a outra entrada e nomes repetidos
+    /// após normalização (padrão `true`).
+    #[serde(default)]
+    pub forbid_overlapping_entries: Option<bool>,
+    /// Inventário das entradas (nome, tamanho, taxa e MIME interno) em
+    /// `details.index`, para sistemas de indexação e busca.
+    #[serde(default)]
+    pub index_entries: Option<bool>,
+    /// Máximo de entradas em `details.index` (padrão 1000).
+    #[serde(default)]
+    pub index_max_entries: Option<u32>,
+}
+
+/// Verificações de datas e de coerência entre header local e central directory.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ArchiveMetadataChecks {
+    /// Datas DOS impossíveis (mês 13, 31 de fevereiro, 25h, segundos acima de 59).
+    pub invalid_dates: Option<bool>,
+    /// Entradas datadas mais de N dias no futuro.
+    pub max_future_days: Option<u32>,
+    /// Entradas datadas antes deste ano.
+    pub min_year: Option<u16>,
+    /// Tamanhos, CRC-32 ou nome diferentes entre header local e central directory.
+    pub header_mismatch: Option<bool>,
+}
+
+/// Política específica para SVG (conteúdo ativo e referências).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SvgPolicySection {
+    pub forbid_scripts: Option<bool>,
+    pub forbid_event_handlers: Option<bool>,
+    pub forbid_foreign_object: Option<bool>,
+    pub forbid_external_references: Option<bool>,
+    pub forbid_data_uris: Option<bool>,
+}
+
+/// Política específica para documentos Office (OOXML e OLE2 legados).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OfficePolicySection {
+    pub allow_macros: Option<bool>,
+    pub forbid_external_relationships: Option<bool>,
+    pub forbid_dde: Option<bool>,
+    pub forbid_embedded_objects: Option<bool>,
+    /// Máximo de bytes descomprimidos por parte XML examinada (padrão: 4 MiB);
+    /// partes maiores são ignoradas.
+    #[serde(default)]
+    pub max_part_bytes: Option<u64>,
+}
+
+/// Política para conteúdo assinado/envelopado (PKCS#7, CMS, S/MIME).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Pkcs7PolicySection {
+    /// Extrai o conteúdo assinado e o analisa com a mesma política (padrão `true`).
+    pub unwrap_inner: Option<bool>,
+    /// Bloqueia conteúdo criptografado (EnvelopedData), que não pode ser inspecionado.
+    pub forbid_enveloped: Option<bool>,
+}
+
+/// Conteúdo transferido em base64 ou quoted-printable (e-mail, APIs que gravam
+/// uploads codificados): decodificado e analisado com a mesma política.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EncodingPolicySection {
+    /// Detecta a codificação pelo conteúdo (padrão `true`).
+    pub detect: Option<bool>,
+    /// Globs de nome com codificação declarada (`"*.b64": base64`); conteúdo
+    /// declarado que não decodifica é negado (`encoding:invalid`).
+    #[serde(default)]
+    pub declared: BTreeMap<String, TransferEncoding>,
+    /// Limite do conteúdo decodificado em MiB (padrão: 64); acima dele, WARN `encoding:limit`.
+    pub max_decoded_mb: Option<u64>,
+}
+
+/// Política para executáveis PE/ELF/Mach-O (padrão: WARN).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BinaryPolicySection {
+    /// Bloqueia qualquer executável reconhecido.
+    pub deny_all: Option<bool>,
+    /// Aceita apenas executáveis com assinatura embutida (presença, sem verificação).
+    pub allow_signed_only: Option<bool>,
+}
+
+/// Nome do arquivo enviado (validador `filename`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FilenamePolicySection {
+    /// Tamanho máximo do nome em bytes (padrão: 255).
+    pub max_length: Option<u32>,
+    /// Quando não vazia, o nome precisa casar com um destes padrões (`*.pdf`).
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Padrões de nome bloqueados (prevalecem sobre `allow`); sem diferenciar maiúsculas.
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// Busca de credenciais em arquivos de texto (`analyzers::secrets`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SecretsPolicySection {
+    /// Liga a busca (padrão: desligada).
+    pub enabled: Option<bool>,
+    /// `warn` ou `deny` para cada tipo de segredo encontrado (padrão: `warn`).
+    pub action: Option<String>,
+}
+
+/// Dados pessoais em texto e PDFs (`analyzers::pii`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PiiPolicySection {
+    /// Liga a busca (padrão: desligada).
+    pub enabled: Option<bool>,
+    /// `allow`, `warn` ou `deny` para cada tipo encontrado (padrão: `warn`).
+    pub action: Option<String>,
+    /// Ação por tipo (`email`, `cpf`, `cnpj`, `credit_card`), sobre `action`.
+    #[serde(default)]
+    pub actions: BTreeMap<String, String>,
+}
+
+/// Listas de SHA-256 consultadas antes dos validadores.
+///
+/// Cada entrada é um digest literal ou um arquivo com um digest por linha (ou CSV,
+/// usando a primeira coluna com 64 dígitos hexadecimais).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HashListsSection {
+    /// Hashes aceitos sem passar pelos validadores (`ALLOW`).
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Hashes bloqueados de imediato (`hash:blocklisted`).
+    #[serde(default)]
+    pub deny: Vec<String>,
+    #[serde(skip)]
+    pub allow_digests: BTreeSet<String>,
+    #[serde(skip)]
+    pub deny_digests: BTreeSet<String>,
+}
+
+impl HashListsSection {
+    /// Carrega os digests referenciados; caminhos relativos partem de `base_dir`.
+    /// Sem `base_dir`, listas em arquivo são recusadas.
+    pub fn load(&mut self, base_dir: Option<&Path>) -> Result<()> {
+        self.allow_digests = load_digests(&self.allow, base_dir)?;
+        self.deny_digests = load_digests(&self.deny, base_dir)?;
+        Ok(())
+    }
+
+    /// Arquivos de lista referenciados em `allow` e `deny`, a partir de `base_dir`.
+    pub fn list_files(&self, base_dir: &Path) -> Vec<PathBuf> {
+        self.allow
+            .iter()
+            .chain(&self.deny)
+            .filter(|entry| !is_sha256_hex(entry.trim()))
+            .map(|entry| base_dir.join(entry))
+            .collect()
+    }
+}
+
+fn load_digests(entries: &[String], base_dir: Option<&Path>) -> Result<BTreeSet<String>> {
+    let mut digests = BTreeSet::new();
+    for entry in entries {
+        if is_sha256_hex(entry.trim()) {
+            digests.insert(entry.trim().to_ascii_lowercase());
+            continue;
+        }
+        let Some(base_dir) = base_dir else {
+            bail!("lista de hashes '{entry}' em arquivo só é aceita em política carregada de um caminho");
+        };
+        let path = base_dir.join(entry);
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("falha ao ler lista de hashes {}", path.display()))?;
+        digests.extend(parse_digest_list(&raw));
+    }
+    Ok(digests)
+}
+
+/// Extrai digests SHA-256 de uma lista (uma linha por digest ou CSV); ignora
+/// linhas vazias, comentários `#` e cabeçalhos.
+pub fn parse_digest_list(raw: &str) -> BTreeSet<String> {
+    raw.lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| {
+            line.split([',', ';', '\t', ' '])
+                .map(|field| field.trim().trim_matches('"'))
+                .find(|field| is_sha256_hex(field))
+                .map(str::to_ascii_lowercase)
+        })
+        .collect()
+}
+
+fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Regras aplicadas ao conjunto de arquivos de uma mesma submissão (`--session-id`
+/// ou campo `session` das entradas inline).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SessionPolicySection {
+    /// Soma máxima dos tamanhos dos arquivos da sessão.
+    pub max_total_size_mb: Option<u32>,
+    /// Quantidade máxima de arquivos por sessão.
+    pub max_files: Option<u32>,
+    /// Bloqueia sessões que misturam executáveis e documentos.
+    pub forbid_mixed_executable_document: Option<bool>,
+    /// Ação para violações de sessão (`warn` ou `deny`; padrão `deny`).
+    pub action: Option<String>,
+}
+
+/// Seleção de validadores por nome (`pdf`, `image`, `archive`, customizados...),
+/// com curingas `*`/`?` (`disabled: ["*"]` deixa só sniff e limites da política).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ValidatorsSection {
+    /// Quando não vazia, apenas estes validadores rodam.
+    #[serde(default)]
+    pub enabled: Vec<String>,
+    /// Validadores que nunca rodam (prevalece sobre `enabled`).
+    #[serde(default)]
+    pub disabled: Vec<String>,
+}
+
+impl ValidatorsSection {
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self
+            .disabled
+            .iter()
+            .any(|disabled| matches_pattern(disabled, name))
+            && (self.enabled.is_empty()
+                || self
+                    .enabled
+                    .iter()
+                    .any(|enabled| matches_pattern(enabled, name)))
+    }
+}
+
+/// Despacho entre validadores.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RoutingPolicySection {
+    /// O que fazer quando o validador especializado falha (padrão: `error`).
+    pub on_validator_error: Option<ValidatorErrorAction>,
+}
+
+/// Severidade aplicada a um achado de validador por `severity_overrides`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeverityOverride {
+    /// Ignora o achado.
+    Allow,
+    Warn,
+    Deny,
+}
+
+/// Ajustes do sniff de MIME.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SniffPolicySection {
+    /// Assinaturas de formatos próprios, verificadas na ordem declarada antes
+    /// da base magic; a primeira que casar define o MIME.
+    #[serde(default)]
+    pub custom: Vec<CustomMagicSection>,
+}
+
+/// Assinatura de `sniff.custom`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomMagicSection {
+    pub mime: String,
+    /// Posição dos bytes a partir do início do arquivo (padrão: 0).
+    #[serde(default)]
+    pub offset: u64,
+    /// Bytes esperados em hexadecimal; espaços são ignorados (`"47 55 50 01"`).
+    pub bytes_hex: String,
+    /// Máscara aplicada ao arquivo e a `bytes_hex` antes da comparação, em
+    /// hexadecimal e do mesmo tamanho (`"FF FF FF F0"`).
+    #[serde(default)]
+    pub mask: Option<String>,
+}
+
+/// Tratamento do `error` de um validador especializado (timeouts não mudam).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorErrorAction {
+    /// Roda o validador `generic` e decide por ele; a falha vira WARN
+    /// (`validator:<nome>:error:fallback`).
+    Fallback,
+    /// Trata a falha como violação (`validator:<nome>:deny`).
+    Deny,
+    /// Mantém o erro operacional (`validator:<nome>:error`, DENY).
+    Error,
+}
+
+/// Varredura antivírus via clamd (protocolo `INSTREAM`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ClamavPolicySection {
+    /// `tcp://host:porta`, `host:porta` ou caminho do socket Unix
+    /// (`/run/clamav/clamd.ctl`); ausente desliga o validador.
+    pub socket: Option<String>,
+    /// Tempo máximo de conexão, envio e resposta (padrão: 30000).
+    pub timeout_ms: Option<u64>,
+    /// Com clamd indisponível: `true` emite WARN e segue; `false` (padrão) nega.
+    pub fail_open: Option<bool>,
+}
+
+/// Tratamento de reincidentes: hashes que já receberam WARN.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HistoryPolicySection {
+    /// Arquivo JSONL onde a CLI persiste os WARN entre execuções; sem ele, o
+    /// histórico vale só enquanto o processo estiver ativo.
+    pub path: Option<String>,
+    /// WARN anteriores necessários para aplicar `set` (padrão: 1).
+    pub min_warnings: Option<u32>,
+    /// Ajustes aplicados à política resolvida de reincidentes, no formato dos
+    /// `overrides` (ex.: `defaults.entropy_action: deny`); vazio desliga o recurso.
+    #[serde(default)]
+    pub set: BTreeMap<String, Value>,
+}
+
+/// Notificações de decisões por webhook (usadas por `scan`, `serve` e `watch`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct NotificationsPolicySection {
+    /// Endpoint que recebe o `FileReport` em JSON via POST; ausente desliga o envio.
+    pub webhook_url: Option<String>,
+    /// Decisões notificadas (padrão: `[WARN, DENY]`).
+    #[serde(default)]
+    pub decisions: Vec<String>,
+    /// Variável de ambiente com a chave HMAC-SHA256 do cabeçalho
+    /// `X-GuardUpload-Signature` (padrão: `GUARDUPLOAD_WEBHOOK_SECRET`).
+    pub secret_env: Option<String>,
+    /// Novas tentativas após falha de rede, 429 ou 5xx (padrão: 3).
+    pub retries: Option<u32>,
+    /// Tempo máximo de cada tentativa (padrão: 10000).
+    pub timeout_ms: Option<u64>,
+}
+
+/// Limites de taxa das integrações de rede, compartilhados por todas as análises
+/// do processo; integração sem entrada não é limitada.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RateLimitsPolicySection {
+    /// Entregas do webhook de `notifications`.
+    pub webhook: Option<RateLimitSection>,
+    /// Consultas ao clamd.
+    pub clamav: Option<RateLimitSection>,
+}
+
+/// Token bucket de uma integração.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitSection {
+    /// Chamadas por segundo em regime (fichas repostas por segundo).
+    pub per_second: f64,
+    /// Chamadas em rajada acima do regime (padrão: `per_second` arredondado para cima).
+    pub burst: Option<u32>,
+    /// Sem ficha disponível: `defer` (padrão) espera, `drop` descarta a chamada.
+    pub on_limit: Option<RateLimitAction>,
+    /// Espera máxima de `defer`, contada a partir do pedido (padrão: 30000);
+    /// acima dela a chamada é descartada.
+    pub max_wait_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitAction {
+    Defer,
+    Drop,
+}
+
+/// Validador externo executado como subprocesso (protocolo JSON em stdin/stdout).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginSection {
+    /// Nome do validador no relatório (`validator:<nome>:*`).
+    pub name: String,
+    /// Programa e argumentos, sem shell (`["clamscan-json", "--fast"]`).
+    pub command: Vec<String>,
+    /// MIMEs reais atendidos (`*` e `?` aceitos); vazio atende todos.
+    #[serde(default)]
+    pub mime_patterns: Vec<String>,
+    /// `stdin` envia o conteúdo em base64 no JSON; `path` grava um arquivo temporário.
+    #[serde(default)]
+    pub input: PluginInput,
+    /// Tempo máximo de execução (padrão: 10000).
+    pub timeout_ms: Option<u64>,
+    /// Limite de bytes lidos do stdout (padrão: 65536).
+    pub max_output_bytes: Option<usize>,
+    /// Versão do plugin (`2.1.0`), conferida contra `require` (padrão: 1.0.0).
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginInput {
+    #[default]
+    Stdin,
+    Path,
+}
+
+/// Controle dos campos sensíveis emitidos nos relatórios.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ReportPolicySection {
+    #[serde(default)]
+    pub redact: Vec<RedactField>,
+}
+
+/// Campos derivados que podem conter dados pessoais.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactField {
+    /// Dono do arquivo no sistema de origem (substituído por hash).
+    FileOwner,
+    /// Coordenadas GPS extraídas de metadados EXIF (omitidas).
+    ExifGps,
+    /// Pré-visualizações geradas do conteúdo (omitidas).
+    Preview,
+}
+
+/// Regras condicionais para ajustes finos da política.
+///
+/// `set` usa caminhos `secao.campo` (ex.: `pdf.max_pages: 5`) sobre as seções
+/// resolvidas por arquivo (`defaults`, `pdf`, `image`, `archive`, `svg`, `office`,
+/// `pkcs7`, `encoding`, `binary`, `validators`); campos sem seção valem para
+/// `defaults`. Overrides posteriores prevalecem.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PolicyOverride {
+    #[serde(default)]
+    pub if_mime: Option<Vec<String>>,
+    #[serde(default)]
+    pub if_source: Option<String>,
+    /// Janela de tempo em que o override vale, avaliada no momento do scan.
+    #[serde(default)]
+    pub active_when: Option<ActiveWhen>,
+    #[serde(default)]
+    pub set: BTreeMap<String, Value>,
+}
+
+impl PolicyOverride {
+    /// Indica se o override vale para o MIME real informado no instante `now`.
+    pub fn applies(&self, mime_real: &str, now: OffsetDateTime) -> bool {
+        // A origem do upload ainda não chega ao núcleo: overrides por origem não valem.
+        if self.if_source.is_some() {
+            return false;
+        }
+        let mime_matches = match &self.if_mime {
+            Some(patterns) => patterns
+                .iter()
+                .any(|pattern| matches_pattern(&pattern.to_ascii_lowercase(), mime_real)),
+            None => true,
+        };
+        let in_window = match &self.active_when {
+            Some(window) => window.is_active(now),
+            None => true,
+        };
+        mime_matches && in_window
+    }
+}
+
+/// Chaves de um override reconhecidas na forma de uma linha.
+const OVERRIDE_KEYS: &[&str] = &["if_mime", "if_source", "active_when", "set"];
+
+/// Override em YAML ou na forma de uma linha usada na CLI
+/// (`if_mime: [image/*] set: {max_size_mb: 5}`).
+impl std::str::FromStr for PolicyOverride {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        serde_yaml::from_str(raw)
+            .or_else(|_| serde_yaml::from_str(&split_override_keys(raw)))
+            .with_context(|| format!("override inválido: {raw}"))
+    }
+}
+
+/// Quebra a linha antes de cada chave de override fora de colchetes, chaves e aspas.
+fn split_override_keys(raw: &str) -> String {
+    let mut output = String::with_capacity(raw.len());
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (index, ch) in raw.char_indices() {
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth = depth.saturating_sub(1),
+            (None, ' ' | '\t')
+                if depth == 0
+                    && OVERRIDE_KEYS
+                        .iter()
+                        .any(|key| raw[index + 1..].starts_with(&format!("{key}:"))) =>
+            {
+                output.push('\n');
+                continue;
+            }
+            _ => {}
+        }
+        output.push(ch);
+    }
+    output
+}
+
+/// Dias e faixas de horário (no fuso `utc_offset`) em que um override está ativo.
+///
+/// Faixas que viram a meia-noite (`19:00-07:00`) usam o dia do próprio instante.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ActiveWhen {
+    /// Dias (`mon`, `sat-sun`, `mon-fri`); vazio = todos.
+    #[serde(default)]
+    pub days: Vec<DayRange>,
+    /// Faixas `HH:MM-HH:MM` ou `H-H`, fim exclusivo; vazio = o dia todo.
+    #[serde(default)]
+    pub hours: Vec<HourRange>,
+    /// Fuso das faixas (`-03:00`); padrão UTC.
+    #[serde(default)]
+    pub utc_offset: Option<UtcOffset>,
+}
+
+impl ActiveWhen {
+    pub fn is_active(&self, now: OffsetDateTime) -> bool {
+        let offset = self.utc_offset.map_or(0, |offset| offset.0);
+        let local = now + time::Duration::minutes(offset.into());
+        let weekday = local.weekday().number_days_from_monday();
+        let minute = u16::from(local.hour()) * 60 + u16::from(local.minute());
+        (self.days.is_empty() || self.days.iter().any(|days| days.contains(weekday)))
+            && (self.hours.is_empty() || self.hours.iter().any(|hours| hours.contains(minute)))
+    }
+}
+
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// Faixa de dias da semana (0 = segunda), inclusiva e circular (`fri-mon`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DayRange {
+    start: u8,
+    end: u8,
+}
+
+impl DayRange {
+    fn contains(&self, weekday: u8) -> bool {
+        if self.start <= self.end {
+            (self.start..=self.end).contains(&weekday)
+        } else {
+            weekday >= self.start || weekday <= self.end
+        }
+    }
+}
+
+impl TryFrom<String> for DayRange {
+    type Error = String;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        let day = |name: &str| {
+            let name = name.trim().to_ascii_lowercase();
+            WEEKDAYS
+                .iter()
+                .position(|day| name.starts_with(day))
+                .map(|index| index as u8)
+                .ok_or_else(|| format!("dia da semana inválido: {raw}"))
+        };
+        let (start, end) = match raw.split_once('-') {
+            Some((start, end)) => (day(start)?, day(end)?),
+            None => (day(&raw)?, day(&raw)?),
+        };
+        Ok(Self { start, end })
+    }
+}
+
+impl From<DayRange> for String {
+    fn from(range: DayRange) -> Self {
+        let (start, end) = (WEEKDAYS[range.start as usize], WEEKDAYS[range.end as usize]);
+        if start == end {
+            start.to_string()
+        } else {
+            format!("{start}-{end}")
+        }
+    }
+}
+
+/// Faixa de horário em minutos do dia, fim exclusivo e circular (`22:00-06:00`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct HourRange {
+    start: u16,
+    end: u16,
+}
+
+impl HourRange {
+    fn contains(&self, minute: u16) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+}
+
+impl TryFrom<String> for HourRange {
+    type Error = String;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        let minute = |value: &str| -> Option<u16> {
+            let (hours, minutes) = value.trim().split_once(':').unwrap_or((value.trim(), "0"));
+            let (hours, minutes) = (hours.parse::<u16>().ok()?, minutes.parse::<u16>().ok()?);
+            (hours <= 24 && minutes < 60 && hours * 60 + minutes <= 24 * 60)
+                .then_some(hours * 60 + minutes)
+        };
+        raw.split_once('-')
+            .and_then(|(start, end)| Some((minute(start)?, minute(end)?)))
+            .map(|(start, end)| Self { start, end })
+            .ok_or_else(|| format!("faixa de horário inválida (esperado HH:MM-HH:MM): {raw}"))
+    }
+}
+
+impl From<HourRange> for String {
+    fn from(range: HourRange) -> Self {
+        format!(
+            "{:02}:{:02}-{:02}:{:02}",
+            range.start / 60,
+            range.start % 60,
+            range.end / 60,
+            range.end % 60
+        )
+    }
+}
+
+/// Deslocamento em minutos em relação a UTC (`-03:00`, `+05:30`, `Z`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct UtcOffset(i16);
+
+impl TryFrom<String> for UtcOffset {
+    type Error = String;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        let trimmed = raw.trim();
+        if trimmed.eq_ignore_ascii_case("z") || trimmed.eq_ignore_ascii_case("utc") {
+            return Ok(Self(0));
+        }
+        let invalid = || format!("utc_offset inválido (esperado ±HH:MM): {raw}");
+        let (sign, rest) = match trimmed.as_bytes().first() {
+            Some(b'+') => (1, &trimmed[1..]),
+            Some(b'-') => (-1, &trimmed[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+        let hours: i16 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i16 = minutes.parse().map_err(|_| invalid())?;
+        if hours > 14 || minutes >= 60 {
+            return Err(invalid());
+        }
+        Ok(Self(sign * (hours * 60 + minutes)))
+    }
+}
+
+impl From<UtcOffset> for String {
+    fn from(offset: UtcOffset) -> Self {
+        offset.to_string()
+    }
+}
+
+impl fmt::Display for UtcOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { '-' } else { '+' };
+        let minutes = self.0.unsigned_abs();
+        write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_lists_accept_lines_csv_and_literals() {
+        let digest = "A".repeat(64);
+        let csv = format!(
+            "sha256,name\n\"{}\",nota.txt\n# comentário\n\n{}  x.bin\n",
+            digest,
+            "b".repeat(64)
+        );
+        let parsed = parse_digest_list(&csv);
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed.contains(&"a".repeat(64)));
+
+        let config =
+            PolicyConfig::from_yaml_str(&format!("hashes:\n  deny: [\"{}\"]\n", "c".repeat(64)))
+                .expect("policy");
+        assert!(config.hashes.deny_digests.contains(&"c".repeat(64)));
+        // Sem caminho de origem, listas em arquivo são recusadas sem tocar o disco.
+        let err = PolicyConfig::from_yaml_str("hashes:\n  allow: [lista.txt]\n").unwrap_err();
+        assert!(err.to_string().contains("lista.txt"));
+
+        let dir =
+            std::env::temp_dir().join(format!("guardupload-hash-lists-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("dir");
+        std::fs::write(dir.join("lista.txt"), format!("{}\n", "d".repeat(64))).expect("lista");
+        let path = dir.join("policy.yaml");
+        std::fs::write(&path, "hashes:\n  allow: [lista.txt]\n").expect("policy");
+        let config = PolicyConfig::from_path(&path);
+        std::fs::remove_dir_all(&dir).expect("cleanup");
+        let config = config.expect("policy");
+        assert!(config.hashes.allow_digests.contains(&"d".repeat(64)));
+        assert_eq!(config.hashes.list_files(&dir), vec![dir.join("lista.txt")]);
+    }
+
+    #[test]
+    fn builtin_policies_parse() {
+        let strict = PolicyConfig::from_yaml_str(BUILTIN_STRICT_POLICY).expect("strict");
+        assert!(strict
+            .defaults
+            .deny_types
+            .iter()
+            .any(|mime| mime == "application/x-executable"));
+        assert_eq!(strict.office.allow_macros, Some(false));
+
+        let permissive =
+            PolicyConfig::from_yaml_str(BUILTIN_PERMISSIVE_POLICY).expect("permissive");
+        assert_eq!(permissive.defaults.max_size_mb, Some(100));
+    }
+}
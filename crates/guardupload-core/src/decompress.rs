@@ -0,0 +1,234 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Descompressão de entradas ZIP e fluxos gzip com backend plugável e orçamento.
+//!
+//! O backend padrão usa `miniz_oxide` (Rust puro, compila para WASM); com a feature
+//! `zlib-ng`, o `flate2` passa a usar a zlib-ng, mais rápida em corpora com muitos
+//! containers. Limite de saída e prazo são aplicados aqui, qualquer que seja o backend.
+//! LZMA/LZMA2 (cabeçalhos 7z) tem decodificador próprio em [`lzma`], fora do backend.
+
+mod lzma;
+
+pub use lzma::{decompress_lzma, decompress_lzma2};
+
+use crate::limits::Deadline;
+use flate2::read::{DeflateDecoder, MultiGzDecoder};
+use std::cell::Cell;
+use std::io::{self, Read, Seek};
+use thiserror::Error;
+use zip::{CompressionMethod, ZipArchive};
+
+/// Bytes descomprimidos entre consultas ao prazo.
+const CHUNK_BYTES: usize = 64 * 1024;
+
+thread_local! {
+    /// Bytes descomprimidos nesta thread desde o início do processo.
+    static DECOMPRESSED_BYTES: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Total de bytes descomprimidos ou extraídos nesta thread (contador crescente);
+/// a diferença entre duas leituras mede o gasto de um trecho.
+pub fn decompressed_bytes() -> u64 {
+    DECOMPRESSED_BYTES.with(Cell::get)
+}
+
+/// Formato do fluxo comprimido.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// Deflate bruto (entradas ZIP com método 8).
+    Deflate,
+    /// Um ou mais membros gzip concatenados.
+    Gzip,
+}
+
+/// Implementação de descompressão; recebe o fluxo comprimido e devolve o descomprimido.
+pub trait DecompressBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn decoder<'a>(&self, codec: Codec, compressed: Box<dyn Read + 'a>) -> Box<dyn Read + 'a>;
+}
+
+/// Backend `flate2`; a implementação nativa é escolhida pela feature `zlib-ng`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Flate2Backend;
+
+impl DecompressBackend for Flate2Backend {
+    fn name(&self) -> &'static str {
+        if cfg!(feature = "zlib-ng") {
+            "zlib-ng"
+        } else {
+            "miniz_oxide"
+        }
+    }
+
+    fn decoder<'a>(&self, codec: Codec, compressed: Box<dyn Read + 'a>) -> Box<dyn Read + 'a> {
+        match codec {
+            Codec::Deflate => Box::new(DeflateDecoder::new(compressed)),
+            Codec::Gzip => Box::new(MultiGzDecoder::new(compressed)),
+        }
+    }
+}
+
+/// Backend usado pelos validadores embutidos.
+pub fn default_backend() -> &'static dyn DecompressBackend {
+    &Flate2Backend
+}
+
+/// Orçamento de uma descompressão.
+#[derive(Debug, Clone)]
+pub struct DecompressLimits {
+    /// Máximo de bytes descomprimidos aceitos.
+    pub max_output_bytes: u64,
+    pub deadline: Deadline,
+}
+
+impl DecompressLimits {
+    pub fn new(max_output_bytes: u64) -> Self {
+        Self {
+            max_output_bytes,
+            deadline: Deadline::none(),
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DecompressError {
+    #[error("conteúdo descomprimido excede {limit} bytes")]
+    LimitExceeded { limit: u64 },
+    #[error("tempo limite excedido durante a descompressão")]
+    Timeout,
+    #[error("método de compressão não suportado: {0}")]
+    Unsupported(String),
+    #[error("fluxo comprimido inválido: {0}")]
+    Corrupt(#[from] io::Error),
+}
+
+/// Descomprime `compressed` inteiro respeitando `limits`.
+pub fn decompress(
+    backend: &dyn DecompressBackend,
+    codec: Codec,
+    compressed: &[u8],
+    limits: &DecompressLimits,
+) -> Result<Vec<u8>, DecompressError> {
+    read_limited(backend.decoder(codec, Box::new(compressed)), limits)
+}
+
+/// Lê a entrada `index` do ZIP, descomprimindo com `backend` em vez do decodificador
+/// interno do crate `zip`.
+pub fn read_zip_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    index: usize,
+    backend: &dyn DecompressBackend,
+    limits: &DecompressLimits,
+) -> Result<Vec<u8>, DecompressError> {
+    let entry = archive
+        .by_index_raw(index)
+        .map_err(|err| DecompressError::Corrupt(io::Error::other(err)))?;
+    match entry.compression() {
+        CompressionMethod::Stored => read_limited(entry, limits),
+        CompressionMethod::Deflated => {
+            read_limited(backend.decoder(Codec::Deflate, Box::new(entry)), limits)
+        }
+        other => Err(DecompressError::Unsupported(other.to_string())),
+    }
+}
+
+/// Lê no máximo `max_bytes` iniciais da entrada `index` (o restante é ignorado,
+/// sem erro), para sniff do conteúdo interno.
+pub fn read_zip_entry_head<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    index: usize,
+    backend: &dyn DecompressBackend,
+    max_bytes: u64,
+    deadline: &Deadline,
+) -> Result<Vec<u8>, DecompressError> {
+    let entry = archive
+        .by_index_raw(index)
+        .map_err(|err| DecompressError::Corrupt(io::Error::other(err)))?;
+    let limits = DecompressLimits::new(max_bytes).with_deadline(deadline.clone());
+    match entry.compression() {
+        CompressionMethod::Stored => read_limited(entry.take(max_bytes), &limits),
+        CompressionMethod::Deflated => read_limited(
+            backend
+                .decoder(Codec::Deflate, Box::new(entry))
+                .take(max_bytes),
+            &limits,
+        ),
+        other => Err(DecompressError::Unsupported(other.to_string())),
+    }
+}
+
+fn read_limited(
+    mut reader: impl Read,
+    limits: &DecompressLimits,
+) -> Result<Vec<u8>, DecompressError> {
+    let mut output = Vec::new();
+    let mut chunk = vec![0u8; CHUNK_BYTES];
+    loop {
+        if limits.deadline.is_expired() {
+            return Err(DecompressError::Timeout);
+        }
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => return Ok(output),
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+        DECOMPRESSED_BYTES.with(|total| total.set(total.get() + read as u64));
+        if output.len() as u64 + read as u64 > limits.max_output_bytes {
+            return Err(DecompressError::LimitExceeded {
+                limit: limits.max_output_bytes,
+            });
+        }
+        output.extend_from_slice(&chunk[..read]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::{Cursor, Write};
+    use zip::write::FileOptions;
+
+    #[test]
+    fn gzip_and_zip_entries_respect_output_budget() {
+        let payload = vec![b'A'; 200_000];
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&payload).unwrap();
+        let gzip = encoder.finish().unwrap();
+
+        let backend = default_backend();
+        let before = decompressed_bytes();
+        let inflated = decompress(backend, Codec::Gzip, &gzip, &DecompressLimits::new(1 << 20));
+        assert_eq!(inflated.unwrap(), payload);
+        assert_eq!(decompressed_bytes() - before, payload.len() as u64);
+        assert!(matches!(
+            decompress(backend, Codec::Gzip, &gzip, &DecompressLimits::new(1000)),
+            Err(DecompressError::LimitExceeded { limit: 1000 })
+        ));
+
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer.start_file("a.txt", FileOptions::default()).unwrap();
+        writer.write_all(&payload).unwrap();
+        let data = writer.finish().unwrap().into_inner();
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+        let entry = read_zip_entry(&mut archive, 0, backend, &DecompressLimits::new(1 << 20));
+        assert_eq!(entry.unwrap(), payload);
+        let expired =
+            DecompressLimits::new(1 << 20).with_deadline(Deadline::after(Default::default()));
+        assert!(matches!(
+            read_zip_entry(&mut archive, 0, backend, &expired),
+            Err(DecompressError::Timeout)
+        ));
+    }
+}
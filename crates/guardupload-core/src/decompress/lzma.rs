@@ -0,0 +1,555 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Decodificador LZMA/LZMA2 em Rust puro, usado nos cabeçalhos comprimidos do 7z.
+//!
+//! Segue a especificação de referência do LZMA SDK (`LzmaSpec.cpp`); a saída
+//! inteira fica em memória, então o orçamento de [`DecompressLimits`] vale antes
+//! de qualquer alocação.
+
+use super::{DecompressError, DecompressLimits, DECOMPRESSED_BYTES};
+use std::io;
+
+const NUM_STATES: usize = 12;
+const POS_BITS_MAX: usize = 4;
+const NUM_LEN_TO_POS_STATES: usize = 4;
+const END_POS_MODEL_INDEX: u32 = 14;
+const NUM_FULL_DISTANCES: usize = 1 << (END_POS_MODEL_INDEX >> 1);
+const MATCH_MIN_LEN: usize = 2;
+const PROB_INIT: u16 = 1024;
+/// Iterações do laço de decodificação entre consultas ao prazo.
+const DEADLINE_STRIDE: u32 = 1 << 16;
+
+fn corrupt(message: &str) -> DecompressError {
+    DecompressError::Corrupt(io::Error::new(
+        io::ErrorKind::InvalidData,
+        message.to_string(),
+    ))
+}
+
+/// Decodificador de intervalo (range coder) sobre um trecho comprimido.
+struct RangeDecoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    range: u32,
+    code: u32,
+    overrun: bool,
+}
+
+impl<'a> RangeDecoder<'a> {
+    fn new(data: &'a [u8]) -> Result<Self, DecompressError> {
+        if data.len() < 5 || data[0] != 0 {
+            return Err(corrupt("fluxo LZMA inválido"));
+        }
+        let code = u32::from_be_bytes([data[1], data[2], data[3], data[4]]);
+        if code == u32::MAX {
+            return Err(corrupt("fluxo LZMA inválido"));
+        }
+        Ok(Self {
+            data,
+            pos: 5,
+            range: u32::MAX,
+            code,
+            overrun: false,
+        })
+    }
+
+    fn next_byte(&mut self) -> u32 {
+        match self.data.get(self.pos) {
+            Some(byte) => {
+                self.pos += 1;
+                u32::from(*byte)
+            }
+            None => {
+                self.overrun = true;
+                0
+            }
+        }
+    }
+
+    fn normalize(&mut self) {
+        if self.range < 1 << 24 {
+            self.range <<= 8;
+            self.code = (self.code << 8) | self.next_byte();
+        }
+    }
+
+    fn decode_bit(&mut self, prob: &mut u16) -> u32 {
+        let bound = (self.range >> 11) * u32::from(*prob);
+        let bit = if self.code < bound {
+            *prob += (2048 - *prob) >> 5;
+            self.range = bound;
+            0
+        } else {
+            *prob -= *prob >> 5;
+            self.code -= bound;
+            self.range -= bound;
+            1
+        };
+        self.normalize();
+        bit
+    }
+
+    fn decode_direct_bits(&mut self, count: u32) -> u32 {
+        let mut result = 0u32;
+        for _ in 0..count {
+            self.range >>= 1;
+            self.code = self.code.wrapping_sub(self.range);
+            let mask = 0u32.wrapping_sub(self.code >> 31);
+            self.code = self.code.wrapping_add(self.range & mask);
+            self.normalize();
+            result = (result << 1).wrapping_add(mask.wrapping_add(1));
+        }
+        result
+    }
+
+    fn bit_tree(&mut self, probs: &mut [u16], bits: u32) -> u32 {
+        let mut m = 1usize;
+        for _ in 0..bits {
+            m = (m << 1) + self.decode_bit(&mut probs[m]) as usize;
+        }
+        (m - (1 << bits)) as u32
+    }
+
+    fn reverse_bit_tree(&mut self, probs: &mut [u16], bits: u32) -> u32 {
+        let mut m = 1usize;
+        let mut symbol = 0u32;
+        for i in 0..bits {
+            let bit = self.decode_bit(&mut probs[m]);
+            m = (m << 1) + bit as usize;
+            symbol |= bit << i;
+        }
+        symbol
+    }
+}
+
+/// Decodificador de comprimentos (match e rep).
+#[derive(Clone)]
+struct LenDecoder {
+    choice: u16,
+    choice2: u16,
+    low: Vec<u16>,
+    mid: Vec<u16>,
+    high: Vec<u16>,
+}
+
+impl LenDecoder {
+    fn new() -> Self {
+        Self {
+            choice: PROB_INIT,
+            choice2: PROB_INIT,
+            low: vec![PROB_INIT; 8 << POS_BITS_MAX],
+            mid: vec![PROB_INIT; 8 << POS_BITS_MAX],
+            high: vec![PROB_INIT; 256],
+        }
+    }
+
+    fn decode(&mut self, rc: &mut RangeDecoder<'_>, pos_state: usize) -> usize {
+        if rc.decode_bit(&mut self.choice) == 0 {
+            return rc.bit_tree(&mut self.low[pos_state << 3..(pos_state + 1) << 3], 3) as usize;
+        }
+        if rc.decode_bit(&mut self.choice2) == 0 {
+            return 8 + rc.bit_tree(&mut self.mid[pos_state << 3..(pos_state + 1) << 3], 3)
+                as usize;
+        }
+        16 + rc.bit_tree(&mut self.high, 8) as usize
+    }
+}
+
+/// Parâmetros `lc`/`lp`/`pb` codificados no byte de propriedades.
+#[derive(Debug, Clone, Copy)]
+struct Props {
+    lc: u32,
+    lp: u32,
+    pb: u32,
+}
+
+impl Props {
+    fn from_byte(byte: u8) -> Result<Self, DecompressError> {
+        let mut value = u32::from(byte);
+        if value >= 9 * 5 * 5 {
+            return Err(corrupt("propriedades LZMA inválidas"));
+        }
+        let lc = value % 9;
+        value /= 9;
+        Ok(Self {
+            lc,
+            lp: value % 5,
+            pb: value / 5,
+        })
+    }
+}
+
+/// Estado do decodificador, preservado entre chunks LZMA2.
+struct Decoder {
+    props: Props,
+    literals: Vec<u16>,
+    pos_slot: Vec<u16>,
+    pos_decoders: Vec<u16>,
+    align: Vec<u16>,
+    is_match: Vec<u16>,
+    is_rep: Vec<u16>,
+    is_rep_g0: Vec<u16>,
+    is_rep_g1: Vec<u16>,
+    is_rep_g2: Vec<u16>,
+    is_rep0_long: Vec<u16>,
+    len: LenDecoder,
+    rep_len: LenDecoder,
+    state: usize,
+    reps: [usize; 4],
+}
+
+impl Decoder {
+    fn new(props: Props) -> Self {
+        Self {
+            props,
+            literals: vec![PROB_INIT; 0x300 << (props.lc + props.lp)],
+            pos_slot: vec![PROB_INIT; NUM_LEN_TO_POS_STATES << 6],
+            pos_decoders: vec![PROB_INIT; 1 + NUM_FULL_DISTANCES - END_POS_MODEL_INDEX as usize],
+            align: vec![PROB_INIT; 1 << 4],
+            is_match: vec![PROB_INIT; NUM_STATES << POS_BITS_MAX],
+            is_rep: vec![PROB_INIT; NUM_STATES],
+            is_rep_g0: vec![PROB_INIT; NUM_STATES],
+            is_rep_g1: vec![PROB_INIT; NUM_STATES],
+            is_rep_g2: vec![PROB_INIT; NUM_STATES],
+            is_rep0_long: vec![PROB_INIT; NUM_STATES << POS_BITS_MAX],
+            len: LenDecoder::new(),
+            rep_len: LenDecoder::new(),
+            state: 0,
+            reps: [0; 4],
+        }
+    }
+
+    fn decode_distance(&mut self, rc: &mut RangeDecoder<'_>, len: usize) -> u32 {
+        let len_state = len.min(NUM_LEN_TO_POS_STATES - 1);
+        let pos_slot = rc.bit_tree(&mut self.pos_slot[len_state << 6..(len_state + 1) << 6], 6);
+        if pos_slot < 4 {
+            return pos_slot;
+        }
+        let direct_bits = (pos_slot >> 1) - 1;
+        let mut distance = (2 | (pos_slot & 1)) << direct_bits;
+        if pos_slot < END_POS_MODEL_INDEX {
+            let base = (distance - pos_slot) as usize;
+            distance += rc.reverse_bit_tree(&mut self.pos_decoders[base..], direct_bits);
+        } else {
+            distance = distance.wrapping_add(rc.decode_direct_bits(direct_bits - 4) << 4);
+            distance = distance.wrapping_add(rc.reverse_bit_tree(&mut self.align, 4));
+        }
+        distance
+    }
+
+    /// Decodifica até `out` alcançar `target` bytes (ou até o marcador de fim).
+    fn decode(
+        &mut self,
+        rc: &mut RangeDecoder<'_>,
+        out: &mut Vec<u8>,
+        target: usize,
+        limits: &DecompressLimits,
+    ) -> Result<(), DecompressError> {
+        let pb_mask = (1usize << self.props.pb) - 1;
+        let lp_mask = (1usize << self.props.lp) - 1;
+        let mut stride = 0u32;
+        while out.len() < target {
+            stride += 1;
+            if stride == DEADLINE_STRIDE {
+                stride = 0;
+                if limits.deadline.is_expired() {
+                    return Err(DecompressError::Timeout);
+                }
+            }
+            if rc.overrun {
+                return Err(corrupt("fluxo LZMA truncado"));
+            }
+            let pos_state = out.len() & pb_mask;
+            let state = self.state;
+            if rc.decode_bit(&mut self.is_match[(state << POS_BITS_MAX) + pos_state]) == 0 {
+                let previous = out.last().copied().unwrap_or(0);
+                let lit_state = ((out.len() & lp_mask) << self.props.lc)
+                    + (usize::from(previous) >> (8 - self.props.lc));
+                let probs = &mut self.literals[0x300 * lit_state..0x300 * (lit_state + 1)];
+                let mut symbol = 1usize;
+                if state >= 7 {
+                    let mut match_byte = usize::from(match_from(out, self.reps[0])?);
+                    while symbol < 0x100 {
+                        let match_bit = (match_byte >> 7) & 1;
+                        match_byte <<= 1;
+                        let bit =
+                            rc.decode_bit(&mut probs[((1 + match_bit) << 8) + symbol]) as usize;
+                        symbol = (symbol << 1) | bit;
+                        if match_bit != bit {
+                            break;
+                        }
+                    }
+                }
+                while symbol < 0x100 {
+                    symbol = (symbol << 1) | rc.decode_bit(&mut probs[symbol]) as usize;
+                }
+                out.push((symbol - 0x100) as u8);
+                self.state = match state {
+                    0..=3 => 0,
+                    4..=9 => state - 3,
+                    _ => state - 6,
+                };
+                continue;
+            }
+
+            let len = if rc.decode_bit(&mut self.is_rep[state]) != 0 {
+                if out.is_empty() {
+                    return Err(corrupt("fluxo LZMA inválido"));
+                }
+                if rc.decode_bit(&mut self.is_rep_g0[state]) == 0 {
+                    if rc.decode_bit(&mut self.is_rep0_long[(state << POS_BITS_MAX) + pos_state])
+                        == 0
+                    {
+                        self.state = if state < 7 { 9 } else { 11 };
+                        let byte = match_from(out, self.reps[0])?;
+                        out.push(byte);
+                        continue;
+                    }
+                } else {
+                    let distance = if rc.decode_bit(&mut self.is_rep_g1[state]) == 0 {
+                        self.reps[1]
+                    } else {
+                        let distance = if rc.decode_bit(&mut self.is_rep_g2[state]) == 0 {
+                            self.reps[2]
+                        } else {
+                            let distance = self.reps[3];
+                            self.reps[3] = self.reps[2];
+                            distance
+                        };
+                        self.reps[2] = self.reps[1];
+                        distance
+                    };
+                    self.reps[1] = self.reps[0];
+                    self.reps[0] = distance;
+                }
+                let len = self.rep_len.decode(rc, pos_state);
+                self.state = if state < 7 { 8 } else { 11 };
+                len
+            } else {
+                self.reps[3] = self.reps[2];
+                self.reps[2] = self.reps[1];
+                self.reps[1] = self.reps[0];
+                let len = self.len.decode(rc, pos_state);
+                self.state = if state < 7 { 7 } else { 10 };
+                let distance = self.decode_distance(rc, len);
+                if distance == u32::MAX {
+                    // Marcador de fim explícito.
+                    return Ok(());
+                }
+                self.reps[0] = distance as usize;
+                len
+            };
+            let len = (len + MATCH_MIN_LEN).min(target - out.len());
+            let distance = self.reps[0] + 1;
+            if distance > out.len() {
+                return Err(corrupt("distância LZMA fora da janela"));
+            }
+            for _ in 0..len {
+                let byte = out[out.len() - distance];
+                out.push(byte);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn match_from(out: &[u8], rep0: usize) -> Result<u8, DecompressError> {
+    out.len()
+        .checked_sub(rep0 + 1)
+        .map(|index| out[index])
+        .ok_or_else(|| corrupt("distância LZMA fora da janela"))
+}
+
+fn check_budget(unpack_size: u64, limits: &DecompressLimits) -> Result<usize, DecompressError> {
+    if unpack_size > limits.max_output_bytes {
+        return Err(DecompressError::LimitExceeded {
+            limit: limits.max_output_bytes,
+        });
+    }
+    usize::try_from(unpack_size).map_err(|_| DecompressError::LimitExceeded {
+        limit: limits.max_output_bytes,
+    })
+}
+
+fn account(out: Vec<u8>) -> Vec<u8> {
+    DECOMPRESSED_BYTES.with(|total| total.set(total.get() + out.len() as u64));
+    out
+}
+
+/// Descomprime um fluxo LZMA bruto (propriedades do coder 7z: byte `lc/lp/pb`
+/// seguido do tamanho do dicionário) com tamanho descomprimido conhecido.
+pub fn decompress_lzma(
+    properties: &[u8],
+    compressed: &[u8],
+    unpack_size: u64,
+    limits: &DecompressLimits,
+) -> Result<Vec<u8>, DecompressError> {
+    let target = check_budget(unpack_size, limits)?;
+    let props = Props::from_byte(
+        *properties
+            .first()
+            .ok_or_else(|| corrupt("propriedades LZMA ausentes"))?,
+    )?;
+    let mut out = Vec::with_capacity(target.min(compressed.len().saturating_mul(64)));
+    let mut rc = RangeDecoder::new(compressed)?;
+    Decoder::new(props).decode(&mut rc, &mut out, target, limits)?;
+    if out.len() != target {
+        return Err(corrupt("fluxo LZMA terminou antes do tamanho declarado"));
+    }
+    Ok(account(out))
+}
+
+/// Descomprime um fluxo LZMA2 (sequência de chunks) com tamanho conhecido.
+pub fn decompress_lzma2(
+    compressed: &[u8],
+    unpack_size: u64,
+    limits: &DecompressLimits,
+) -> Result<Vec<u8>, DecompressError> {
+    let target = check_budget(unpack_size, limits)?;
+    let mut out = Vec::with_capacity(target.min(compressed.len().saturating_mul(64)));
+    let mut decoder: Option<Decoder> = None;
+    let mut pos = 0usize;
+    let read_u16 = |pos: usize| {
+        compressed
+            .get(pos..pos + 2)
+            .map(|bytes| usize::from(u16::from_be_bytes([bytes[0], bytes[1]])))
+            .ok_or_else(|| corrupt("chunk LZMA2 truncado"))
+    };
+    loop {
+        let control = *compressed
+            .get(pos)
+            .ok_or_else(|| corrupt("chunk LZMA2 truncado"))?;
+        pos += 1;
+        match control {
+            0x00 => break,
+            0x01 | 0x02 => {
+                let size = read_u16(pos)? + 1;
+                pos += 2;
+                let chunk = compressed
+                    .get(pos..pos + size)
+                    .ok_or_else(|| corrupt("chunk LZMA2 truncado"))?;
+                if out.len() + size > target {
+                    return Err(corrupt("LZMA2 excede o tamanho declarado"));
+                }
+                out.extend_from_slice(chunk);
+                pos += size;
+            }
+            0x80..=0xFF => {
+                let unpacked = (usize::from(control & 0x1F) << 16) + read_u16(pos)? + 1;
+                let packed = read_u16(pos + 2)? + 1;
+                pos += 4;
+                let mode = (control >> 5) & 0x03;
+                if mode >= 2 {
+                    let props = Props::from_byte(
+                        *compressed
+                            .get(pos)
+                            .ok_or_else(|| corrupt("chunk LZMA2 truncado"))?,
+                    )?;
+                    if props.lc + props.lp > 4 {
+                        return Err(corrupt("propriedades LZMA2 inválidas"));
+                    }
+                    pos += 1;
+                    decoder = Some(Decoder::new(props));
+                } else if mode == 1 {
+                    let props = decoder
+                        .as_ref()
+                        .ok_or_else(|| corrupt("chunk LZMA2 sem propriedades"))?
+                        .props;
+                    decoder = Some(Decoder::new(props));
+                }
+                let decoder = decoder
+                    .as_mut()
+                    .ok_or_else(|| corrupt("chunk LZMA2 sem propriedades"))?;
+                let chunk = compressed
+                    .get(pos..pos + packed)
+                    .ok_or_else(|| corrupt("chunk LZMA2 truncado"))?;
+                let chunk_target = out.len() + unpacked;
+                if chunk_target > target {
+                    return Err(corrupt("LZMA2 excede o tamanho declarado"));
+                }
+                let mut rc = RangeDecoder::new(chunk)?;
+                decoder.decode(&mut rc, &mut out, chunk_target, limits)?;
+                if out.len() != chunk_target {
+                    return Err(corrupt("chunk LZMA2 terminou antes do tamanho declarado"));
+                }
+                pos += packed;
+            }
+            _ => return Err(corrupt("controle LZMA2 inválido")),
+        }
+    }
+    if out.len() != target {
+        return Err(corrupt("LZMA2 terminou antes do tamanho declarado"));
+    }
+    Ok(account(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gerados com `lzma.compress(..., format=FORMAT_RAW)` do Python (dicionário de 64 KiB).
+    const LZMA_VECTOR: &str = concat!(
+        "00361a4a1f4cbeadc35470d26899439070e0e9afab84c96fb2009e1127d9f5951abbc46e1f5cf6b99128f22a",
+        "629c0a4de123c1dd4ca7b93239a6d7d4f045babb5aeea663f61ea20aebe6ff5c4d67a44159316939fb0ad189",
+        "de75fb37f30d24d7b909d1765f7a6166c6a883227017d102eca176d8386801a074e396ccb3752bff414ab039",
+        "02f21d577640e654bd55ad264b3c02b7723c4f35873cb1b6e8d8621f1ee98a6961facac03d78c6109fa18f96",
+        "9ac210050572533a0f114be5c9bfe58ad0b4e094c8446fd2052708148491b704718d25a5678b4bcfe98b6bee",
+        "38d7e4ba1e85322ff6f68f8a246f9b38b3986065775018210eaa1de36e0293f4dde56020ce7198b96f3ee095",
+        "4821b3e99f6ed9f549be576c58d68c9619b7e5f6cf2ab5dd961feccafa1ef683565d40a8ed46e9006b29285c",
+        "b1243b6ead6b71144522e713ff90f4b5d581c3aa9238df65eb1d7623f8b978646e65735ef5a6260d5ee4d9b5",
+        "2e8a12b0a90750568907a8ee2337c1953f7ba5a3d54ccf506db20632b49509a494cde10a28b56cda9ffed27a",
+        "f9",
+    );
+    const LZMA2_VECTOR: &str = concat!(
+        "e00f4401875d00361a4a1f4cbeadc35470d26899439070e0e9afab84c96fb2009e1127d9f5951abbc46e1f5c",
+        "f6b99128f22a629c0a4de123c1dd4ca7b93239a6d7d4f045babb5aeea663f61ea20aebe6ff5c4d67a4415931",
+        "6939fb0ad189de75fb37f30d24d7b909d1765f7a6166c6a883227017d102eca176d8386801a074e396ccb375",
+        "2bff414ab03902f21d577640e654bd55ad264b3c02b7723c4f35873cb1b6e8d8621f1ee98a6961facac03d78",
+        "c6109fa18f969ac210050572533a0f114be5c9bfe58ad0b4e094c8446fd2052708148491b704718d25a5678b",
+        "4bcfe98b6bee38d7e4ba1e85322ff6f68f8a246f9b38b3986065775018210eaa1de36e0293f4dde56020ce71",
+        "98b96f3ee0954821b3e99f6ed9f549be576c58d68c9619b7e5f6cf2ab5dd961feccafa1ef683565d40a8ed46",
+        "e9006b29285cb1243b6ead6b71144522e713ff90f4b5d581c3aa9238df65eb1d7623f8b978646e65735ef5a6",
+        "260d5ee4d9b52e8a12b0a90750568907a8ee2337c1953f7ba5a3d54ccf506db20632b49509a494cde10a0e61",
+        "ce0000",
+    );
+
+    fn sample() -> Vec<u8> {
+        (0..120)
+            .flat_map(|i| {
+                format!("linha {} guardupload {} softctrl\n", i % 37, i * i % 101).into_bytes()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decodes_lzma_and_lzma2_streams() {
+        let expected = sample();
+        let limits = DecompressLimits::new(1 << 20);
+        let lzma = hex::decode(LZMA_VECTOR).unwrap();
+        let decoded = decompress_lzma(&[0x5D, 0, 0, 1, 0], &lzma, expected.len() as u64, &limits)
+            .expect("lzma");
+        assert_eq!(decoded, expected);
+        let lzma2 = hex::decode(LZMA2_VECTOR).unwrap();
+        let decoded = decompress_lzma2(&lzma2, expected.len() as u64, &limits).expect("lzma2");
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn rejects_oversized_and_corrupt_streams() {
+        let lzma = hex::decode(LZMA_VECTOR).unwrap();
+        let small = DecompressLimits::new(100);
+        assert!(matches!(
+            decompress_lzma(&[0x5D], &lzma, 3909, &small),
+            Err(DecompressError::LimitExceeded { limit: 100 })
+        ));
+        let limits = DecompressLimits::new(1 << 20);
+        assert!(matches!(
+            decompress_lzma(&[0x5D], &lzma[..lzma.len() / 2], 3909, &limits),
+            Err(DecompressError::Corrupt(_))
+        ));
+        assert!(decompress_lzma2(&[0x03], 10, &limits).is_err());
+    }
+}
@@ -0,0 +1,519 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Desarme e reconstrução de conteúdo (CDR) usado pelo `guardupload sanitize`.
+//!
+//! Cada formato é reconstruído em um equivalente sem conteúdo ativo: imagens são
+//! decodificadas e recodificadas (sem metadados nem bytes extras), PDFs têm as
+//! chaves de JavaScript, ações automáticas e arquivos embutidos neutralizadas e
+//! ZIPs são reempacotados sem symlinks, path traversal ou entradas cifradas.
+
+use crate::decompress::{decompress, default_backend, Codec, DecompressLimits};
+use crate::validators::archive::{is_encrypted, is_symlink};
+use crate::validators::image::{gif_frame_count, DEFAULT_MAX_PIXELS};
+use crate::validators::pdf::{decode_name, name_end};
+use image::codecs::bmp::BmpEncoder;
+use image::codecs::gif::{GifDecoder, GifEncoder, Repeat};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{AnimationDecoder, DynamicImage, ImageFormat, ImageReader};
+use std::io::Cursor;
+use thiserror::Error;
+use zip::{ZipArchive, ZipWriter};
+
+/// Qualidade das imagens JPEG recodificadas.
+const JPEG_QUALITY: u8 = 90;
+/// Limite descomprimido de cada object stream examinado após o desarme do PDF.
+const MAX_OBJECT_STREAM_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Chaves PDF neutralizadas. A troca da primeira letra por `X` mantém o tamanho
+/// do arquivo, e portanto os offsets da tabela xref, e os leitores ignoram
+/// chaves desconhecidas.
+const PDF_ACTIVE_KEYS: &[&[u8]] = &[
+    b"/JavaScript",
+    b"/JS",
+    b"/OpenAction",
+    b"/AA",
+    b"/Launch",
+    b"/EmbeddedFiles",
+    b"/EmbeddedFile",
+    b"/RichMedia",
+    b"/SubmitForm",
+    b"/ImportData",
+];
+
+/// Conteúdo reconstruído e as ações aplicadas (`image:reencoded:png`,
+/// `pdf:neutralized:/JavaScript`, `zip:removed:symlink:link`...).
+#[derive(Debug, Clone)]
+pub struct Disarmed {
+    pub data: Vec<u8>,
+    pub actions: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum DisarmError {
+    #[error("formato sem reconstrução disponível: {0}")]
+    Unsupported(String),
+    #[error("conteúdo inválido: {0}")]
+    Invalid(String),
+    #[error("conteúdo ativo fora do alcance do desarme: {0}")]
+    Residual(String),
+}
+
+/// Reconstrói `data` conforme o MIME real detectado.
+pub fn disarm(mime: &str, data: &[u8]) -> Result<Disarmed, DisarmError> {
+    match mime {
+        "image/png" | "image/jpeg" | "image/gif" | "image/webp" | "image/bmp" => {
+            disarm_image(mime, data)
+        }
+        "application/pdf" => disarm_pdf(data),
+        "application/zip" | "application/x-zip-compressed" | "application/x-zip" => {
+            disarm_zip(data)
+        }
+        other => Err(DisarmError::Unsupported(other.to_string())),
+    }
+}
+
+fn disarm_image(mime: &str, data: &[u8]) -> Result<Disarmed, DisarmError> {
+    let invalid = |err: image::ImageError| DisarmError::Invalid(err.to_string());
+    let reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|err| DisarmError::Invalid(err.to_string()))?;
+    let format = reader
+        .format()
+        .ok_or_else(|| DisarmError::Unsupported(mime.to_string()))?;
+    let (width, height) = reader.into_dimensions().map_err(invalid)?;
+    let frames = if format == ImageFormat::Gif {
+        gif_frame_count(data).max(1)
+    } else {
+        1
+    };
+    let pixels = u64::from(width) * u64::from(height) * u64::from(frames);
+    if pixels > DEFAULT_MAX_PIXELS {
+        return Err(DisarmError::Invalid(format!(
+            "imagem excede o orçamento de pixels: {pixels} > {DEFAULT_MAX_PIXELS}"
+        )));
+    }
+
+    let mut output = Vec::new();
+    let label = match format {
+        ImageFormat::Gif => {
+            let decoder = GifDecoder::new(Cursor::new(data)).map_err(invalid)?;
+            let frames = decoder.into_frames().collect_frames().map_err(invalid)?;
+            let mut encoder = GifEncoder::new(&mut output);
+            encoder.set_repeat(Repeat::Infinite).map_err(invalid)?;
+            encoder.encode_frames(frames).map_err(invalid)?;
+            drop(encoder);
+            "gif"
+        }
+        _ => {
+            let image = image::load_from_memory_with_format(data, format).map_err(invalid)?;
+            match format {
+                ImageFormat::Png => image.write_with_encoder(PngEncoder::new(&mut output)),
+                ImageFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+                    .write_with_encoder(JpegEncoder::new_with_quality(&mut output, JPEG_QUALITY)),
+                ImageFormat::WebP => DynamicImage::ImageRgba8(image.to_rgba8())
+                    .write_with_encoder(WebPEncoder::new_lossless(&mut output)),
+                ImageFormat::Bmp => image.write_with_encoder(BmpEncoder::new(&mut output)),
+                _ => return Err(DisarmError::Unsupported(mime.to_string())),
+            }
+            .map_err(invalid)?;
+            format.extensions_str().first().copied().unwrap_or("img")
+        }
+    };
+    Ok(Disarmed {
+        data: output,
+        actions: vec![format!("image:reencoded:{label}")],
+    })
+}
+
+fn disarm_pdf(data: &[u8]) -> Result<Disarmed, DisarmError> {
+    if !data.starts_with(b"%PDF-") {
+        return Err(DisarmError::Invalid("header %PDF- ausente".to_string()));
+    }
+    let mut output = data.to_vec();
+    let mut actions = Vec::new();
+    let mut object_streams = Vec::new();
+    let mut pos = 0;
+    while pos < output.len() {
+        // Dados de streams ficam intactos: os bytes de conteúdo comprimido
+        // podem coincidir com as chaves por acaso.
+        if is_stream_keyword(&output, pos) {
+            pos = find_from(&output, b"endstream", pos + 6).map_or(output.len(), |end| end + 9);
+            continue;
+        }
+        if output[pos] != b'/' {
+            pos += 1;
+            continue;
+        }
+        // Nomes são comparados já sem os escapes `#xx` (`/J#61vaScript`).
+        let end = name_end(&output, pos + 1);
+        let name = decode_name(&output[pos + 1..end]);
+        if name == b"ObjStm" {
+            object_streams.push(end);
+        }
+        if let Some(key) = active_key(&name) {
+            // Trocar o primeiro byte também desfaz um escape inicial (`/#4A#53`
+            // vira `/X4A#53`), mantendo o tamanho.
+            output[pos + 1] = b'X';
+            let action = format!("pdf:neutralized:{}", String::from_utf8_lossy(key));
+            if !actions.contains(&action) {
+                actions.push(action);
+            }
+        }
+        pos = end.max(pos + 1);
+    }
+    for offset in object_streams {
+        object_stream_residual(&output, offset).map_err(DisarmError::Residual)?;
+    }
+    Ok(Disarmed {
+        data: output,
+        actions,
+    })
+}
+
+/// Chave ativa cujo nome decodificado (sem a `/`) é exatamente `name`.
+fn active_key(name: &[u8]) -> Option<&'static [u8]> {
+    PDF_ACTIVE_KEYS
+        .iter()
+        .find(|key| &key[1..] == name)
+        .copied()
+}
+
+/// Verifica o object stream (`/ObjStm`) cujo dicionário contém `offset`. Esses
+/// objetos não podem ser reescritos sem recalcular os offsets do PDF, então
+/// qualquer chave ativa dentro deles, ou um stream que não dá para decodificar,
+/// impede o desarme.
+fn object_stream_residual(data: &[u8], offset: usize) -> Result<(), String> {
+    let mut cursor = offset;
+    let start = loop {
+        match find_from(data, b"stream", cursor) {
+            Some(found) if is_stream_keyword(data, found) => break found,
+            Some(found) => cursor = found + 6,
+            None => return Err("object stream sem dados".to_string()),
+        }
+    };
+    let dictionary_start = data[..offset]
+        .windows(3)
+        .rposition(|window| window == b"obj")
+        .unwrap_or(0);
+    let filters = stream_filters(&data[dictionary_start..start])?;
+
+    let body_start = start
+        + 6
+        + match data.get(start + 6..start + 8) {
+            Some(b"\r\n") => 2,
+            _ => 1,
+        };
+    let body_end = find_from(data, b"endstream", body_start).unwrap_or(data.len());
+    let body = &data[body_start..body_end];
+    let objects = match filters.as_slice() {
+        [] => body.to_vec(),
+        [filter] if filter == b"FlateDecode" || filter == b"Fl" => {
+            if !is_zlib_header(body) {
+                return Err("object stream sem cabeçalho zlib válido".to_string());
+            }
+            let limits = DecompressLimits::new(MAX_OBJECT_STREAM_BYTES);
+            let objects = decompress(default_backend(), Codec::Deflate, &body[2..], &limits)
+                .map_err(|err| format!("object stream não pôde ser descomprimido: {err}"))?;
+            // O decodificador aceita fluxos truncados; o Adler-32 final prova que
+            // o stream inteiro foi lido.
+            let trailer = adler32(&objects).to_be_bytes();
+            if !(0..=2).any(|eol| body[..body.len() - eol.min(body.len())].ends_with(&trailer)) {
+                return Err("object stream comprimido incompleto".to_string());
+            }
+            objects
+        }
+        _ => {
+            let names: Vec<String> = filters
+                .iter()
+                .map(|filter| format!("/{}", String::from_utf8_lossy(filter)))
+                .collect();
+            return Err(format!(
+                "object stream com filtro não verificável: {}",
+                names.join(" ")
+            ));
+        }
+    };
+
+    let mut pos = 0;
+    while let Some(found) = find_from(&objects, b"/", pos) {
+        let end = name_end(&objects, found + 1);
+        if let Some(key) = active_key(&decode_name(&objects[found + 1..end])) {
+            return Err(format!(
+                "{} dentro de object stream comprimido",
+                String::from_utf8_lossy(key)
+            ));
+        }
+        pos = end.max(found + 1);
+    }
+    Ok(())
+}
+
+/// Filtros (`/Filter`) declarados no dicionário de um stream, sem a `/`.
+/// Parâmetros de decodificação (`/DecodeParms`) não são suportados.
+fn stream_filters(dictionary: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+    let mut filters = Vec::new();
+    let mut pos = 0;
+    while let Some(found) = find_from(dictionary, b"/", pos) {
+        let end = name_end(dictionary, found + 1);
+        pos = end.max(found + 1);
+        match decode_name(&dictionary[found + 1..end]).as_slice() {
+            b"DecodeParms" | b"DP" => {
+                return Err("object stream com /DecodeParms não verificável".to_string())
+            }
+            b"Filter" => {}
+            _ => continue,
+        }
+        let value = &dictionary[end..];
+        let value_start = value
+            .iter()
+            .position(|byte| !byte.is_ascii_whitespace())
+            .unwrap_or(value.len());
+        let value = &value[value_start..];
+        let list = match value.first() {
+            Some(b'[') => {
+                &value[1..value
+                    .iter()
+                    .position(|byte| *byte == b']')
+                    .unwrap_or(value.len())]
+            }
+            Some(b'/') => &value[..name_end(value, 1)],
+            _ => return Err("object stream com /Filter não verificável".to_string()),
+        };
+        let mut cursor = 0;
+        while let Some(name) = find_from(list, b"/", cursor) {
+            let name_end = name_end(list, name + 1);
+            filters.push(decode_name(&list[name + 1..name_end]));
+            cursor = name_end.max(name + 1);
+        }
+        pos = end + value_start + list.len();
+    }
+    Ok(filters)
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += u32::from(*byte);
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+/// Cabeçalho zlib (RFC 1950) de um fluxo deflate sem dicionário predefinido.
+fn is_zlib_header(body: &[u8]) -> bool {
+    match body {
+        [cmf, flg, ..] => {
+            cmf & 0x0f == 8
+                && cmf >> 4 <= 7
+                && flg & 0x20 == 0
+                && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0
+        }
+        _ => false,
+    }
+}
+
+fn disarm_zip(data: &[u8]) -> Result<Disarmed, DisarmError> {
+    let invalid = |err: zip::result::ZipError| DisarmError::Invalid(err.to_string());
+    let mut archive = ZipArchive::new(Cursor::new(data)).map_err(invalid)?;
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let mut actions = Vec::new();
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index).map_err(invalid)?;
+        let reason = if entry.enclosed_name().is_none() {
+            Some("traversal")
+        } else if entry.unix_mode().is_some_and(is_symlink) {
+            Some("symlink")
+        } else if is_encrypted(data, &entry) {
+            Some("encrypted")
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => actions.push(format!("zip:removed:{reason}:{}", entry.name())),
+            None => writer.raw_copy_file(entry).map_err(invalid)?,
+        }
+    }
+    if !archive.comment().is_empty() {
+        actions.push("zip:removed:comment".to_string());
+    }
+    writer.set_comment("");
+    Ok(Disarmed {
+        data: writer.finish().map_err(invalid)?.into_inner(),
+        actions,
+    })
+}
+
+/// Palavra-chave `stream` que abre os dados de um objeto (seguida de fim de linha
+/// e logo após o `>>` do dicionário), e não texto qualquer com essas letras.
+fn is_stream_keyword(data: &[u8], pos: usize) -> bool {
+    data[pos..].starts_with(b"stream")
+        && matches!(data.get(pos + 6), Some(b'\r' | b'\n'))
+        && data[..pos].trim_ascii_end().ends_with(b">>")
+}
+
+fn find_from(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| pos + from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageEncoder, RgbImage};
+    use std::io::Write;
+    use zip::write::FileOptions;
+
+    fn stored() -> FileOptions {
+        FileOptions::default().compression_method(zip::CompressionMethod::Stored)
+    }
+
+    #[test]
+    fn images_are_reencoded_and_pdf_zip_are_disarmed() {
+        let mut png = Vec::new();
+        PngEncoder::new(&mut png)
+            .write_image(
+                RgbImage::new(4, 4).as_raw(),
+                4,
+                4,
+                image::ExtendedColorType::Rgb8,
+            )
+            .expect("png");
+        png.extend_from_slice(b"PK\x03\x04trailing");
+        let disarmed = disarm("image/png", &png).expect("png");
+        assert_eq!(disarmed.actions, vec!["image:reencoded:png"]);
+        assert!(!disarmed.data.ends_with(b"trailing"));
+        assert_eq!(
+            image::load_from_memory(&disarmed.data)
+                .expect("decodifica")
+                .width(),
+            4
+        );
+
+        let pdf = b"%PDF-1.7\n1 0 obj\n<< /OpenAction 2 0 R /Names << /JavaScript 3 0 R >> \
+            /AAPL 1 >>\nendobj\n4 0 obj\n<< /Length 3 >>\nstream\n/JS\nendstream\n(livestream) /JS\nendobj\n%%EOF\n";
+        let disarmed = disarm("application/pdf", pdf).expect("pdf");
+        assert_eq!(disarmed.data.len(), pdf.len());
+        let text = String::from_utf8_lossy(&disarmed.data);
+        assert!(text.contains("/XpenAction") && text.contains("/XavaScript"));
+        assert!(text.contains("/AAPL") && text.contains("stream\n/JS\n"));
+        assert_eq!(
+            disarmed.actions,
+            vec![
+                "pdf:neutralized:/OpenAction",
+                "pdf:neutralized:/JavaScript",
+                "pdf:neutralized:/JS"
+            ]
+        );
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer.start_file("docs/ok.txt", stored()).unwrap();
+        writer.write_all(b"ok").unwrap();
+        writer.start_file("../evil.sh", stored()).unwrap();
+        writer.write_all(b"x").unwrap();
+        writer.add_symlink("link", "/etc/passwd", stored()).unwrap();
+        let zip = writer.finish().unwrap().into_inner();
+        let disarmed = disarm("application/zip", &zip).expect("zip");
+        assert_eq!(
+            disarmed.actions,
+            vec![
+                "zip:removed:traversal:../evil.sh",
+                "zip:removed:symlink:link"
+            ]
+        );
+        let repacked = ZipArchive::new(Cursor::new(&disarmed.data)).expect("zip");
+        assert_eq!(
+            repacked.file_names().collect::<Vec<_>>(),
+            vec!["docs/ok.txt"]
+        );
+
+        assert!(matches!(
+            disarm("text/plain", b"oi"),
+            Err(DisarmError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn escaped_pdf_names_are_neutralized() {
+        let pdf = b"%PDF-1.7\n1 0 obj\n<< /Open#41ction 2 0 R /Names << /J#61vaScript 3 0 R >> \
+            /AA << /O 4 0 R >> /#4A#53 (x) /L#61unch#zz 1 >>\nendobj\n%%EOF\n";
+        let disarmed = disarm("application/pdf", pdf).expect("pdf");
+        assert_eq!(disarmed.data.len(), pdf.len());
+        let text = String::from_utf8_lossy(&disarmed.data);
+        assert!(text.contains("/Xpen#41ction") && text.contains("/X#61vaScript"));
+        assert!(text.contains("/XA <<") && text.contains("/X4A#53 (x)"));
+        assert!(text.contains("/L#61unch#zz"));
+        assert_eq!(
+            disarmed.actions,
+            vec![
+                "pdf:neutralized:/OpenAction",
+                "pdf:neutralized:/JavaScript",
+                "pdf:neutralized:/AA",
+                "pdf:neutralized:/JS"
+            ]
+        );
+    }
+
+    #[test]
+    fn object_streams_must_decode_clean() {
+        fn object_stream(filter: &[u8], body: &[u8]) -> Vec<u8> {
+            let mut pdf = b"%PDF-1.7\n5 0 obj\n<< /Type /Obj#53tm /N 1 /First 4 ".to_vec();
+            pdf.extend_from_slice(filter);
+            pdf.extend_from_slice(b" >>\nstream\n");
+            pdf.extend_from_slice(body);
+            pdf.extend_from_slice(b"\nendstream\nendobj\n%%EOF\n");
+            pdf
+        }
+        fn zlib(data: &[u8]) -> Vec<u8> {
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data).unwrap();
+            encoder.finish().unwrap()
+        }
+
+        let clean = object_stream(b"/Filter /FlateDecode", &zlib(b"6 0 << /Type /Page >>"));
+        assert!(disarm("application/pdf", &clean).is_ok());
+
+        let rejected = [
+            object_stream(
+                b"/Filter /FlateDecode",
+                &zlib(b"6 0 << /S /J#61vaScript >>"),
+            ),
+            object_stream(
+                b"/Filter [/FlateDecode]",
+                &zlib(b"6 0 << /Open#41ction 7 0 R >>"),
+            ),
+            object_stream(b"", b"6 0 << /JS (app.alert(1)) >>"),
+            object_stream(b"/Filter /ASCIIHexDecode", b"2F4A53>"),
+            object_stream(b"/Filter /LZWDecode", b"\x80\x0b\x60\x50"),
+            object_stream(b"/Filter [/ASCIIHexDecode /FlateDecode]", b"789c>"),
+            object_stream(
+                b"/Filter /FlateDecode /DecodeParms << /Predictor 12 >>",
+                &zlib(b""),
+            ),
+            object_stream(b"/Filter /FlateDecode", b"\x00\x00garbage"),
+            object_stream(b"/Filter /FlateDecode", &zlib(b"6 0 << >>")[..6]),
+        ];
+        for pdf in rejected {
+            assert!(
+                matches!(
+                    disarm("application/pdf", &pdf),
+                    Err(DisarmError::Residual(_))
+                ),
+                "{}",
+                String::from_utf8_lossy(&pdf)
+            );
+        }
+    }
+}
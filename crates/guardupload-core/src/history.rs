@@ -0,0 +1,51 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Histórico de veredictos por SHA-256 para tratar reincidentes.
+//!
+//! Quando um hash já recebeu WARN ao menos `history.min_warnings` vezes, a
+//! política resolvida recebe os ajustes de `history.set` (limites menores,
+//! validadores extras) nas análises seguintes do mesmo conteúdo.
+
+use crate::policy::Decision;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, PoisonError};
+
+/// Armazenamento do histórico; a CLI persiste em arquivo, o núcleo mantém em memória.
+pub trait VerdictHistory: Send + Sync + fmt::Debug {
+    /// Quantas vezes o hash recebeu WARN.
+    fn warnings(&self, sha256: &str) -> u32;
+
+    /// Registra a decisão final de uma análise.
+    fn record(&self, sha256: &str, decision: Decision);
+}
+
+/// Histórico em memória, válido enquanto o processo (ou o motor de políticas) viver.
+#[derive(Debug, Default)]
+pub struct MemoryHistory {
+    warnings: Mutex<HashMap<String, u32>>,
+}
+
+impl MemoryHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl VerdictHistory for MemoryHistory {
+    fn warnings(&self, sha256: &str) -> u32 {
+        let warnings = self.warnings.lock().unwrap_or_else(PoisonError::into_inner);
+        warnings.get(sha256).copied().unwrap_or(0)
+    }
+
+    fn record(&self, sha256: &str, decision: Decision) {
+        if decision != Decision::Warn || sha256.is_empty() {
+            return;
+        }
+        let mut warnings = self.warnings.lock().unwrap_or_else(PoisonError::into_inner);
+        *warnings.entry(sha256.to_string()).or_default() += 1;
+    }
+}
@@ -0,0 +1,167 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Relatório HTML autocontido (sem recursos externos) para revisão humana.
+
+use crate::report::{FileReport, SummaryReport};
+use std::fmt::Write as _;
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2rem;color:#1f2933}\
+table{border-collapse:collapse;width:100%}\
+th,td{border:1px solid #d9e2ec;padding:.4rem .6rem;text-align:left;vertical-align:top}\
+th{background:#f0f4f8;cursor:pointer;user-select:none}\
+th:after{content:' \\2195';color:#9fb3c8}\
+.ALLOW{color:#1f7a3a;font-weight:600}.WARN{color:#b7791f;font-weight:600}\
+.DENY,.ERROR{color:#c53030;font-weight:600}\
+pre{white-space:pre-wrap;margin:.3rem 0;font-size:.85em}\
+ul{margin:0;padding-left:1.1rem}.summary span{margin-right:1.2rem}";
+
+/// Ordena a tabela ao clicar no cabeçalho (usa `data-sort` quando presente).
+const SCRIPT: &str = "document.querySelectorAll('th').forEach(function(th,i){\
+th.addEventListener('click',function(){var tb=th.closest('table').tBodies[0];\
+var asc=th.dataset.asc!=='1';th.dataset.asc=asc?'1':'0';\
+var rows=Array.from(tb.rows);rows.sort(function(a,b){\
+var x=a.cells[i].dataset.sort||a.cells[i].textContent;\
+var y=b.cells[i].dataset.sort||b.cells[i].textContent;\
+var nx=parseFloat(x),ny=parseFloat(y);\
+var c=(!isNaN(nx)&&!isNaN(ny))?nx-ny:x.localeCompare(y);return asc?c:-c;});\
+rows.forEach(function(r){tb.appendChild(r);});});});";
+
+/// Acumula linhas arquivo a arquivo e gera o documento ao final do scan.
+#[derive(Debug, Clone, Default)]
+pub struct HtmlReportBuilder {
+    rows: Vec<String>,
+}
+
+impl HtmlReportBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, report: &FileReport) {
+        let decision = escape(&report.policy.decision);
+        let mut row = String::new();
+        let _ = write!(
+            row,
+            "<tr><td>{file}</td><td class=\"{decision}\" data-sort=\"{severity}\">{decision}</td>\
+<td>{mime}</td><td data-sort=\"{size}\">{size}</td><td>",
+            file = escape(&report.file.display().to_string()),
+            severity = severity(&report.policy.decision),
+            mime = escape(&report.sniff.mime_real),
+            size = report.size_bytes,
+        );
+        if !report.policy.rules_triggered.is_empty() {
+            row.push_str("<ul>");
+            for rule in &report.policy.rules_triggered {
+                let _ = write!(row, "<li><code>{}</code></li>", escape(rule));
+            }
+            row.push_str("</ul>");
+        }
+        row.push_str("</td><td>");
+        if let Some(error) = report.error.as_deref() {
+            let _ = write!(row, "<pre>{}</pre>", escape(error));
+        }
+        for validator in &report.validators {
+            let details = serde_json::to_string_pretty(&validator.details).unwrap_or_default();
+            let _ = write!(
+                row,
+                "<details><summary>{} — {}</summary><pre>{}</pre></details>",
+                escape(&validator.name),
+                escape(&validator.status),
+                escape(&details),
+            );
+        }
+        row.push_str("</td></tr>");
+        self.rows.push(row);
+    }
+
+    /// Gera o documento completo, incluindo o resumo agregado.
+    pub fn render(&self, summary: &SummaryReport) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html><html lang=\"pt-BR\"><head><meta charset=\"utf-8\">\
+<title>GuardUpload — relatório</title><style>{STYLE}</style></head><body>\
+<h1>GuardUpload — relatório de scan</h1><p class=\"summary\">\
+<span>Arquivos: <b>{}</b></span><span class=\"ALLOW\">ALLOW: {}</span>\
+<span class=\"WARN\">WARN: {}</span><span class=\"DENY\">DENY: {}</span>\
+<span class=\"ERROR\">ERROR: {}</span></p>\
+<table><thead><tr><th>Arquivo</th><th>Decisão</th><th>MIME real</th>\
+<th>Tamanho (bytes)</th><th>Regras</th><th>Validadores</th></tr></thead><tbody>",
+            summary.scanned, summary.allow, summary.warn, summary.deny, summary.errors,
+        );
+        for row in &self.rows {
+            html.push_str(row);
+        }
+        let _ = writeln!(
+            html,
+            "</tbody></table><p><small>GuardUpload {}</small></p><script>{SCRIPT}</script></body></html>",
+            env!("CARGO_PKG_VERSION"),
+        );
+        html
+    }
+}
+
+fn severity(decision: &str) -> u8 {
+    match decision {
+        "ALLOW" => 0,
+        "WARN" => 1,
+        "DENY" => 2,
+        _ => 3,
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{PolicyDecision, SniffReport, ValidatorEntry};
+    use serde_json::json;
+    use std::path::Path;
+
+    #[test]
+    fn render_escapes_content_and_lists_rules() {
+        let sniff = SniffReport::new("image/svg+xml".into(), None, None);
+        let mut report = FileReport::new(Path::new("<x>.svg"), 12, "abcd".into(), sniff);
+        report.policy = PolicyDecision {
+            decision: "DENY".into(),
+            rules_triggered: vec!["validator:svg:deny".into()],
+            ..PolicyDecision::default()
+        };
+        report.validators.push(ValidatorEntry {
+            name: "svg".into(),
+            status: "deny".into(),
+            details: json!({ "message": "<script> encontrado" }),
+            version: None,
+        });
+
+        let mut summary = SummaryReport::default();
+        summary.update(&report.policy);
+        let mut builder = HtmlReportBuilder::new();
+        builder.push(&report);
+        let html = builder.render(&summary);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("&lt;x&gt;.svg"));
+        assert!(html.contains("<code>validator:svg:deny</code>"));
+        assert!(html.contains("&lt;script&gt; encontrado"));
+        assert!(!html.contains("<script> encontrado"));
+        assert!(html.contains("DENY: 1"));
+    }
+}
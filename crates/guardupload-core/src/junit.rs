@@ -0,0 +1,182 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Relatório JUnit XML para gates de CI (Jenkins, GitLab).
+//!
+//! Cada arquivo é um `testcase`: WARN e DENY viram `failure` com as regras
+//! disparadas, ERROR vira `error` e ALLOW passa.
+
+use crate::report::{FileReport, SummaryReport};
+use crate::rules;
+use std::fmt::Write as _;
+
+/// Acumula casos de teste arquivo a arquivo e gera o documento ao final do scan.
+#[derive(Debug, Clone, Default)]
+pub struct JunitReportBuilder {
+    cases: Vec<String>,
+    time_secs: f64,
+}
+
+impl JunitReportBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, report: &FileReport) {
+        let time = f64::from(report.timings_ms.total) / 1000.0;
+        self.time_secs += time;
+        let mut case = String::new();
+        let _ = write!(
+            case,
+            "    <testcase name=\"{}\" classname=\"guardupload.{}\" time=\"{time:.3}\"",
+            escape(&report.file.display().to_string()),
+            escape(&report.sniff.mime_real),
+        );
+        let decision = report.policy.decision.as_str();
+        match decision {
+            "WARN" | "DENY" => {
+                let _ = write!(
+                    case,
+                    ">\n      <failure type=\"{decision}\" message=\"{}\">{}</failure>\n    </testcase>\n",
+                    escape(report.policy.primary_rule.as_deref().unwrap_or(decision)),
+                    escape(&failure_details(report)),
+                );
+            }
+            "ERROR" => {
+                let _ = write!(
+                    case,
+                    ">\n      <error type=\"ERROR\" message=\"{}\"/>\n    </testcase>\n",
+                    escape(
+                        report
+                            .error
+                            .as_deref()
+                            .unwrap_or("falha ao processar o arquivo")
+                    ),
+                );
+            }
+            _ => case.push_str("/>\n"),
+        }
+        self.cases.push(case);
+    }
+
+    /// Gera o documento com uma única `testsuite` e as contagens do resumo.
+    pub fn render(&self, summary: &SummaryReport) -> String {
+        let failures = summary.warn + summary.deny;
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"GuardUpload\" tests=\"{}\" failures=\"{failures}\" errors=\"{}\" time=\"{:.3}\">",
+            summary.scanned, summary.errors, self.time_secs,
+        );
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"guardupload {}\" tests=\"{}\" failures=\"{failures}\" errors=\"{}\" skipped=\"0\" time=\"{:.3}\">",
+            env!("CARGO_PKG_VERSION"),
+            summary.scanned,
+            summary.errors,
+            self.time_secs,
+        );
+        for case in &self.cases {
+            xml.push_str(case);
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+/// Uma linha por regra (código, regra e descrição do catálogo) e as mensagens
+/// dos validadores que não passaram.
+fn failure_details(report: &FileReport) -> String {
+    let mut details = String::new();
+    for rule in &report.policy.rules_triggered {
+        let code = rules::code_for(rule);
+        let _ = write!(details, "{code} {rule}");
+        if let Some(doc) = rules::explain(&code) {
+            let _ = write!(details, ": {}", doc.description);
+        }
+        details.push('\n');
+    }
+    for validator in report.validators.iter().filter(|v| v.status != "pass") {
+        if let Some(message) = validator.details["message"].as_str() {
+            let _ = writeln!(
+                details,
+                "{} ({}): {message}",
+                validator.name, validator.status
+            );
+        }
+    }
+    details
+}
+
+/// Escapa texto para atributos e conteúdo XML; caracteres de controle inválidos
+/// no XML 1.0 são omitidos.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\t' | '\r' => escaped.push(ch),
+            ch if ch.is_control() => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{PolicyDecision, SniffReport, ValidatorEntry};
+    use serde_json::json;
+    use std::path::Path;
+
+    #[test]
+    fn deny_and_warn_become_failures_with_rule_details() {
+        let mut summary = SummaryReport::default();
+        let mut builder = JunitReportBuilder::new();
+
+        let sniff = SniffReport::new("image/svg+xml".into(), None, None);
+        let mut denied = FileReport::new(Path::new("<x>.svg"), 12, "abcd".into(), sniff);
+        denied.policy = PolicyDecision {
+            decision: "DENY".into(),
+            rules_triggered: vec!["validator:svg:deny".into()],
+            primary_rule: Some("validator:svg:deny".into()),
+            ..PolicyDecision::default()
+        };
+        denied.validators.push(ValidatorEntry {
+            name: "svg".into(),
+            status: "deny".into(),
+            details: json!({ "message": "<script> encontrado" }),
+            version: None,
+        });
+        let sniff = SniffReport::new("text/plain".into(), None, None);
+        let mut allowed = FileReport::new(Path::new("a.txt"), 3, "ef".into(), sniff);
+        allowed.policy.decision = "ALLOW".into();
+        let failed = FileReport::error(Path::new("sumiu.bin"), "não encontrado");
+        for report in [&denied, &allowed, &failed] {
+            summary.update(&report.policy);
+            builder.push(report);
+        }
+
+        let xml = builder.render(&summary);
+        assert!(xml.starts_with("<?xml version=\"1.0\""));
+        assert!(xml.contains("tests=\"3\" failures=\"1\" errors=\"1\""));
+        assert!(xml
+            .contains("<testcase name=\"&lt;x&gt;.svg\" classname=\"guardupload.image/svg+xml\""));
+        assert!(xml.contains(
+            "<failure type=\"DENY\" message=\"validator:svg:deny\">GU-SVG-DENY validator:svg:deny"
+        ));
+        assert!(xml.contains("svg (deny): &lt;script&gt; encontrado"));
+        assert!(xml.contains(
+            "<testcase name=\"a.txt\" classname=\"guardupload.text/plain\" time=\"0.000\"/>"
+        ));
+        assert!(xml.contains("<error type=\"ERROR\" message=\"não encontrado\"/>"));
+    }
+}
@@ -0,0 +1,34 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! GuardUpload Core — regras de validação puras, sem CLI, logging ou varredura de diretórios.
+//!
+//! Este crate concentra sniffing, validadores, motor de políticas e schemas de
+//! relatório para que aplicações móveis, serviços e builds WASM apliquem
+//! exatamente as mesmas regras do servidor.
+
+pub mod analyzers;
+pub mod canonical;
+pub mod config;
+pub mod decompress;
+pub mod disarm;
+pub mod error;
+pub mod history;
+pub mod html;
+pub mod junit;
+pub mod limits;
+pub mod pipeline;
+pub mod policy;
+pub mod precheck;
+pub mod preview;
+pub mod ranges;
+pub mod report;
+pub mod risk;
+pub mod rules;
+pub mod sarif;
+pub mod scanner;
+pub mod sniff;
+pub mod transfer;
+pub mod validators;
@@ -0,0 +1,169 @@
+// GuardUpload
+// Criado em: 2025-11-01
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Gestão de limites de tempo, memória e entropia.
+
+use crate::config::PolicyConfig;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Parâmetros de limites globais para aplicarmos no pipeline.
+#[derive(Debug, Default, Clone)]
+pub struct LimitSettings {
+    /// Timeout máximo (em segundos) para processar um arquivo.
+    pub timeout_secs: Option<u64>,
+    /// Entropia máxima permitida antes de disparar WARN/DENY.
+    pub entropy_threshold: Option<f32>,
+}
+
+/// `--max-read-bytes` implícito do perfil `embedded`: 16 MiB.
+const EMBEDDED_MAX_READ_BYTES: u64 = 16 * 1024 * 1024;
+/// `office.max_part_bytes` do perfil `embedded`: 1 MiB.
+const EMBEDDED_MAX_PART_BYTES: u64 = 1024 * 1024;
+
+/// Perfil de recursos (`--profile`).
+///
+/// `embedded` é para dispositivos com pouca memória: arquivos grandes são lidos
+/// só no início e no fim, GIFs têm os frames contados pelos cabeçalhos, envelopes
+/// assinados e imagens ISO não são abertos e as partes OOXML descomprimidas
+/// ficam limitadas a 1 MiB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResourceProfile {
+    #[default]
+    Default,
+    Embedded,
+}
+
+impl ResourceProfile {
+    /// Limite de leitura por arquivo quando `--max-read-bytes` não é informado.
+    pub fn max_read_bytes(self) -> Option<u64> {
+        match self {
+            ResourceProfile::Default => None,
+            ResourceProfile::Embedded => Some(EMBEDDED_MAX_READ_BYTES),
+        }
+    }
+
+    /// Ajusta a política carregada; valores já definidos em overrides continuam valendo.
+    pub fn apply(self, config: &mut PolicyConfig) {
+        if self == ResourceProfile::Default {
+            return;
+        }
+        config.image.header_only = Some(true);
+        config.pkcs7.unwrap_inner = Some(false);
+        config.archive.enumerate_iso = Some(false);
+        let max_part_bytes = config
+            .office
+            .max_part_bytes
+            .map_or(EMBEDDED_MAX_PART_BYTES, |bytes| {
+                bytes.min(EMBEDDED_MAX_PART_BYTES)
+            });
+        config.office.max_part_bytes = Some(max_part_bytes);
+    }
+}
+
+impl FromStr for ResourceProfile {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "default" => Ok(ResourceProfile::Default),
+            "embedded" => Ok(ResourceProfile::Embedded),
+            other => Err(format!(
+                "perfil desconhecido: {other} (use default ou embedded)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ResourceProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ResourceProfile::Default => "default",
+            ResourceProfile::Embedded => "embedded",
+        })
+    }
+}
+
+/// Prazo cooperativo repassado aos validadores.
+///
+/// Loops longos consultam `is_expired` periodicamente e devolvem um resultado
+/// parcial (`error:timeout`) em vez de bloquear o scan.
+#[derive(Debug, Clone, Default)]
+pub struct Deadline {
+    expires_at: Option<Instant>,
+    cancelled: Option<Arc<AtomicBool>>,
+}
+
+impl Deadline {
+    /// Prazo que nunca expira (a menos que cancelado).
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Prazo que expira após `timeout` a partir de agora.
+    pub fn after(timeout: Duration) -> Self {
+        Self {
+            expires_at: Instant::now().checked_add(timeout),
+            cancelled: None,
+        }
+    }
+
+    /// Associa um sinalizador de cancelamento compartilhado.
+    pub fn with_cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancelled = Some(flag);
+        self
+    }
+
+    /// Indica se o prazo expirou ou se o cancelamento foi solicitado.
+    pub fn is_expired(&self) -> bool {
+        self.cancelled
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+            || self
+                .expires_at
+                .is_some_and(|expires_at| Instant::now() >= expires_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadline_expires_by_time_or_cancellation() {
+        assert!(!Deadline::none().is_expired());
+        assert!(Deadline::after(Duration::ZERO).is_expired());
+        assert!(!Deadline::after(Duration::from_secs(60)).is_expired());
+
+        let flag = Arc::new(AtomicBool::new(false));
+        let deadline = Deadline::none().with_cancel_flag(flag.clone());
+        assert!(!deadline.is_expired());
+        flag.store(true, Ordering::Relaxed);
+        assert!(deadline.is_expired());
+    }
+
+    #[test]
+    fn embedded_profile_lowers_policy_limits() {
+        let mut config = PolicyConfig::from_yaml_str(
+            "pkcs7:\n  unwrap_inner: true\noffice:\n  max_part_bytes: 65536\n",
+        )
+        .expect("yaml");
+        let untouched = serde_json::to_value(&config).unwrap();
+        ResourceProfile::Default.apply(&mut config);
+        assert_eq!(serde_json::to_value(&config).unwrap(), untouched);
+
+        let profile: ResourceProfile = "Embedded".parse().expect("perfil");
+        profile.apply(&mut config);
+        assert_eq!(config.image.header_only, Some(true));
+        assert_eq!(config.pkcs7.unwrap_inner, Some(false));
+        assert_eq!(config.archive.enumerate_iso, Some(false));
+        assert_eq!(config.office.max_part_bytes, Some(65536));
+        assert_eq!(profile.max_read_bytes(), Some(EMBEDDED_MAX_READ_BYTES));
+        assert!("tiny".parse::<ResourceProfile>().is_err());
+    }
+}
@@ -0,0 +1,615 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Núcleo de validação sem acesso a sistema de arquivos (sniff → validadores → política).
+//!
+//! Este módulo compila para `wasm32-wasip1` com `--no-default-features`, permitindo
+//! executar a mesma decisão do CLI dentro de edge workers e filtros WASM.
+
+use crate::analyzers::hashes::{compute_digests, HashAlgorithm};
+use crate::analyzers::pii::analyze_pii;
+use crate::analyzers::secrets::analyze_secrets;
+use crate::analyzers::structure::analyze_structure;
+use crate::analyzers::{analyze_entropy, DEFAULT_ENTROPY_WINDOW};
+use crate::config::EncodingPolicySection;
+use crate::limits::Deadline;
+use crate::policy::{Decision, DecisionOutcome, PolicyEngine};
+use crate::report::{FileReport, SniffReport, ValidatorEntry};
+use crate::sniff::{self, ClaimedMimeMap, CustomSignature};
+use crate::transfer::{decode_transfer, detect_transfer_encoding, TransferEncoding, TransferError};
+use crate::validators::{glob_matches, signed_inner_content, ValidationContext, ValidatorRegistry};
+use anyhow::{Context, Result};
+use base64::Engine as _;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Limite de envelopes assinados ou codificados aninhados desembrulhados.
+const MAX_UNWRAP_DEPTH: usize = 3;
+/// `encoding.max_decoded_mb` padrão.
+const DEFAULT_MAX_DECODED_MB: u64 = 64;
+
+/// Conteúdo recebido inline em JSON (`{"name": ..., "content_b64": ...}`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct InlineInput {
+    pub name: String,
+    pub content_b64: String,
+    /// Submissão à qual o arquivo pertence; prevalece sobre `--session-id`.
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+impl InlineInput {
+    /// Decodifica `content_b64` (base64 padrão, espaços e quebras de linha ignorados).
+    pub fn decode(&self) -> Result<Vec<u8>> {
+        let compact: String = self
+            .content_b64
+            .chars()
+            .filter(|ch| !ch.is_ascii_whitespace())
+            .collect();
+        base64::engine::general_purpose::STANDARD
+            .decode(compact)
+            .with_context(|| format!("content_b64 inválido para {}", self.name))
+    }
+}
+
+/// Analisa bytes já carregados em memória e devolve relatório + decisão.
+///
+/// Os validadores vêm de [`PolicyEngine::validators`] (embutidos, sem motor).
+///
+/// `name` é usado apenas para rotular o relatório e derivar a extensão (e, via
+/// `claimed_mimes`, o MIME declarado); `deadline` é repassado aos validadores
+/// para interrupção cooperativa.
+pub fn analyze_bytes(
+    name: &Path,
+    data: &[u8],
+    policy_engine: Option<&PolicyEngine>,
+    claimed_mimes: &ClaimedMimeMap,
+    hash_algorithms: &[HashAlgorithm],
+    deadline: &Deadline,
+) -> Result<(FileReport, DecisionOutcome)> {
+    analyze_bytes_with_validators(
+        name,
+        data,
+        policy_engine,
+        claimed_mimes,
+        hash_algorithms,
+        deadline,
+        match policy_engine {
+            Some(engine) => engine.validators(),
+            None => ValidatorRegistry::shared_builtin(),
+        },
+    )
+}
+
+/// Igual a [`analyze_bytes`], despachando para os validadores de `validators`.
+pub fn analyze_bytes_with_validators(
+    name: &Path,
+    data: &[u8],
+    policy_engine: Option<&PolicyEngine>,
+    claimed_mimes: &ClaimedMimeMap,
+    hash_algorithms: &[HashAlgorithm],
+    deadline: &Deadline,
+    validators: &ValidatorRegistry,
+) -> Result<(FileReport, DecisionOutcome)> {
+    Analysis {
+        policy_engine,
+        claimed_mimes,
+        hash_algorithms,
+        deadline,
+        validators,
+    }
+    .run(name, data, 0)
+}
+
+/// Parâmetros fixos de uma análise, compartilhados com os envelopes desembrulhados.
+struct Analysis<'a> {
+    policy_engine: Option<&'a PolicyEngine>,
+    claimed_mimes: &'a ClaimedMimeMap,
+    hash_algorithms: &'a [HashAlgorithm],
+    deadline: &'a Deadline,
+    validators: &'a ValidatorRegistry,
+}
+
+impl Analysis<'_> {
+    fn custom_signatures(&self) -> &[CustomSignature] {
+        self.policy_engine
+            .map_or(&[], PolicyEngine::custom_signatures)
+    }
+
+    fn run(&self, name: &Path, data: &[u8], depth: usize) -> Result<(FileReport, DecisionOutcome)> {
+        let policy_engine = self.policy_engine;
+        let started = Instant::now();
+        let size_bytes = data.len() as u64;
+        let digests = compute_digests(data, self.hash_algorithms);
+        let hash_ms = elapsed_ms(started);
+
+        let sniff_started = Instant::now();
+        let sniff_report =
+            sniff_with_claimed(name, data, self.claimed_mimes, self.custom_signatures())?;
+        let sniff_ms = elapsed_ms(sniff_started);
+
+        let mut report = FileReport::new(name, size_bytes, digests.sha256, sniff_report);
+        report.bytes.read = size_bytes;
+        report.bytes.hashed = size_bytes * (1 + digests.extra.len() as u64);
+        report.hashes = digests.extra;
+
+        // Listas de hashes valem antes de qualquer validador.
+        if let Some(verdict) = policy_engine.and_then(|engine| engine.hash_verdict(&report.sha256))
+        {
+            let mut outcome = DecisionOutcome::new();
+            let rule = match verdict {
+                Decision::Deny => "hash:blocklisted",
+                _ => "hash:allowlisted",
+            };
+            outcome.record(verdict, rule);
+            report.timings_ms.hash = Some(hash_ms);
+            report.timings_ms.sniff = Some(sniff_ms);
+            report.timings_ms.total = elapsed_ms(started);
+            return Ok((report, outcome));
+        }
+        let window = policy_engine
+            .and_then(|engine| engine.config().defaults.entropy_window_bytes)
+            .unwrap_or(DEFAULT_ENTROPY_WINDOW);
+        report.entropy = Some(analyze_entropy(data, window));
+        report.structure = analyze_structure(data).filter(|analysis| !analysis.is_clean());
+
+        let resolved_policy = policy_engine.map(|engine| engine.resolve(&report));
+        if policy_engine.is_some_and(|engine| engine.is_repeat_offender(&report.sha256)) {
+            report
+                .notes
+                .push("reincidente: WARN anterior para o hash, aplicado history.set".to_string());
+        }
+        let resolved_policy_ref = resolved_policy.as_ref();
+        if resolved_policy_ref.is_some_and(|policy| policy.secrets.enabled.unwrap_or(false)) {
+            report.secrets = analyze_secrets(&report.sniff.mime_real, data, self.deadline);
+        }
+        if resolved_policy_ref.is_some_and(|policy| policy.pii.enabled.unwrap_or(false)) {
+            report.pii = analyze_pii(&report.sniff.mime_real, data, self.deadline);
+        }
+        let validate_started = Instant::now();
+        // Conteúdo desembrulhado herda o nome do envelope; só o original tem nome próprio.
+        let file_name = name.file_name().map(|name| name.to_string_lossy());
+        let validator_outcomes = self.validators.evaluate(&ValidationContext {
+            mime: report.sniff.mime_real.as_str(),
+            data,
+            policy: resolved_policy_ref,
+            deadline: self.deadline,
+            file_name: file_name.as_deref().filter(|_| depth == 0),
+        });
+        let validate_ms = elapsed_ms(validate_started);
+        report.validators = validator_outcomes
+            .iter()
+            .map(ValidatorEntry::from)
+            .collect();
+        for validator in &validator_outcomes {
+            if let Some(used) = validator.details["decompressed_bytes"].as_u64() {
+                report.bytes.decompressed += used;
+                *report
+                    .bytes
+                    .by_validator
+                    .entry(validator.name.to_string())
+                    .or_default() += used;
+            }
+        }
+
+        let mut outcome = if let Some(engine) = policy_engine {
+            engine.decide(&report, &validator_outcomes, resolved_policy_ref)
+        } else {
+            DecisionOutcome::new()
+        };
+
+        // Conteúdo assinado (PKCS#7/S-MIME) é reanalisado com a mesma política.
+        let unwrap_inner = resolved_policy_ref
+            .and_then(|policy| policy.pkcs7.unwrap_inner)
+            .unwrap_or(true);
+        let is_signed = validator_outcomes
+            .iter()
+            .any(|validator| validator.name == "pkcs7");
+        if unwrap_inner && is_signed && depth < MAX_UNWRAP_DEPTH {
+            if let Some(inner_data) = signed_inner_content(data) {
+                let (mut inner_report, inner_outcome) =
+                    self.run(&inner_name(name), &inner_data, depth + 1)?;
+                outcome.merge_prefixed(&inner_outcome, "inner:");
+                inner_report.policy = inner_outcome.into();
+                report.bytes.add(&inner_report.bytes);
+                report.inner = Some(Box::new(inner_report));
+            }
+        }
+
+        // Conteúdo em base64/quoted-printable é decodificado e reanalisado.
+        let encoding_policy = resolved_policy_ref
+            .map(|policy| policy.encoding.clone())
+            .unwrap_or_default();
+        if report.inner.is_none() && depth < MAX_UNWRAP_DEPTH {
+            if let Some((encoding, declared)) = transfer_encoding_for(name, data, &encoding_policy)
+            {
+                let max_bytes = encoding_policy
+                    .max_decoded_mb
+                    .unwrap_or(DEFAULT_MAX_DECODED_MB)
+                    .saturating_mul(1024 * 1024);
+                match decode_transfer(data, encoding, max_bytes) {
+                    // Sem declaração, base64 só vale se o resultado tiver tipo conhecido.
+                    Ok(decoded)
+                        if declared
+                            || encoding == TransferEncoding::QuotedPrintable
+                            || !sniff::is_ambiguous(
+                                &sniff::sniff_bytes_with(&decoded, self.custom_signatures())?
+                                    .mime_real,
+                            ) =>
+                    {
+                        let (mut inner_report, inner_outcome) =
+                            self.run(&inner_name(name), &decoded, depth + 1)?;
+                        outcome.merge_prefixed(&inner_outcome, "inner:");
+                        inner_report.policy = inner_outcome.into();
+                        report.bytes.add(&inner_report.bytes);
+                        report.transfer_encoding = Some(encoding);
+                        report.inner = Some(Box::new(inner_report));
+                    }
+                    Ok(_) => {}
+                    Err(err @ TransferError::LimitExceeded { .. }) => {
+                        outcome.record(
+                            Decision::Warn,
+                            format!("encoding:limit:{}", encoding.as_str()),
+                        );
+                        report.notes.push(err.to_string());
+                    }
+                    Err(err) if declared => {
+                        outcome.record(
+                            Decision::Deny,
+                            format!("encoding:invalid:{}", encoding.as_str()),
+                        );
+                        report.notes.push(err.to_string());
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+
+        if let Some(history) = policy_engine.and_then(PolicyEngine::history) {
+            history.record(&report.sha256, outcome.decision);
+        }
+
+        report.timings_ms.hash = Some(hash_ms);
+        report.timings_ms.sniff = Some(sniff_ms);
+        report.timings_ms.validate = Some(validate_ms);
+        report.timings_ms.total = elapsed_ms(started);
+
+        Ok((report, outcome))
+    }
+}
+
+/// Analisa um arquivo maior que o limite de leitura a partir do início e do fim.
+///
+/// O MIME vem do trecho inicial, `partial_sha256` cobre `head` seguido de `tail`
+/// (`sha256` fica vazio: listas de hashes e histórico não se aplicam) e nenhum
+/// validador é executado; a política decide com a regra `size:partially_read`.
+pub fn analyze_partial(
+    name: &Path,
+    head: &[u8],
+    tail: &[u8],
+    size_bytes: u64,
+    policy_engine: Option<&PolicyEngine>,
+    claimed_mimes: &ClaimedMimeMap,
+) -> Result<(FileReport, DecisionOutcome)> {
+    let started = Instant::now();
+    let mut hasher = Sha256::new();
+    hasher.update(head);
+    hasher.update(tail);
+    let partial_sha256 = hex::encode(hasher.finalize());
+    let hash_ms = elapsed_ms(started);
+
+    let sniff_started = Instant::now();
+    let custom = policy_engine.map_or(&[][..], PolicyEngine::custom_signatures);
+    let sniff_report = sniff_with_claimed(name, head, claimed_mimes, custom)?;
+    let sniff_ms = elapsed_ms(sniff_started);
+
+    let mut report = FileReport::new(name, size_bytes, String::new(), sniff_report);
+    report.partially_read = true;
+    report.partial_sha256 = Some(partial_sha256);
+    report.bytes.read = (head.len() + tail.len()) as u64;
+    report.bytes.hashed = report.bytes.read;
+    report.notes.push(format!(
+        "lidos apenas {} bytes iniciais e {} finais de {size_bytes}",
+        head.len(),
+        tail.len()
+    ));
+
+    let outcome = match policy_engine {
+        Some(engine) => {
+            let resolved = engine.resolve(&report);
+            engine.decide(&report, &[], Some(&resolved))
+        }
+        None => DecisionOutcome::new(),
+    };
+
+    report.timings_ms.hash = Some(hash_ms);
+    report.timings_ms.sniff = Some(sniff_ms);
+    report.timings_ms.total = elapsed_ms(started);
+    Ok((report, outcome))
+}
+
+fn sniff_with_claimed(
+    name: &Path,
+    data: &[u8],
+    claimed_mimes: &ClaimedMimeMap,
+    custom: &[CustomSignature],
+) -> Result<SniffReport> {
+    let mut sniff_result = sniff::sniff_bytes_with(data, custom)?;
+    let ext = name
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| format!(".{}", s.to_ascii_lowercase()));
+    let mime_claimed = ext
+        .as_deref()
+        .and_then(|ext| claimed_mimes.claimed_for(ext))
+        .map(str::to_string);
+    if let Some(claimed) = &mime_claimed {
+        sniff_result.add_extension_hint(claimed);
+    }
+    let score = sniff_result.score();
+    let candidates = sniff_result.alternatives().to_vec();
+    let mut sniff_report = SniffReport::new(sniff_result.mime_real, sniff_result.magic, ext);
+    sniff_report.escalation = sniff_result.escalation;
+    sniff_report.mime_claimed = mime_claimed;
+    sniff_report.score = Some(score);
+    sniff_report.candidates = candidates;
+    Ok(sniff_report)
+}
+
+/// Codificação declarada para o nome em `encoding.declared` ou detectada no
+/// conteúdo (`encoding.detect`); o booleano indica se foi declarada.
+fn transfer_encoding_for(
+    name: &Path,
+    data: &[u8],
+    policy: &EncodingPolicySection,
+) -> Option<(TransferEncoding, bool)> {
+    let file_name = name.file_name()?.to_string_lossy();
+    if let Some((_, encoding)) = policy
+        .declared
+        .iter()
+        .find(|(glob, _)| glob_matches(glob, &file_name))
+    {
+        return Some((*encoding, true));
+    }
+    if !policy.detect.unwrap_or(true) {
+        return None;
+    }
+    detect_transfer_encoding(data).map(|encoding| (encoding, false))
+}
+
+/// Nome do conteúdo interno: `contrato.pdf.p7m` → `contrato.pdf`.
+fn inner_name(name: &Path) -> PathBuf {
+    let is_envelope = name
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            [
+                "p7m", "p7s", "p7b", "p7c", "p7", "eml", "b64", "base64", "qp",
+            ]
+            .iter()
+            .any(|known| ext.eq_ignore_ascii_case(known))
+        });
+    if is_envelope {
+        name.with_extension("")
+    } else {
+        name.to_path_buf()
+    }
+}
+
+/// Milissegundos decorridos desde `start`.
+pub fn elapsed_ms(start: Instant) -> f32 {
+    start.elapsed().as_secs_f32() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PolicyConfig;
+
+    #[test]
+    fn inline_input_decodes_base64_with_line_breaks() {
+        let input = InlineInput {
+            name: "nota.txt".into(),
+            content_b64: "aGVsbG8g\nd29ybGQ=".into(),
+            session: None,
+        };
+        assert_eq!(input.decode().expect("decode"), b"hello world");
+
+        let invalid = InlineInput {
+            name: "x".into(),
+            content_b64: "%%%".into(),
+            session: None,
+        };
+        assert!(invalid.decode().is_err());
+    }
+
+    #[test]
+    fn analyze_bytes_runs_without_filesystem() {
+        let config = PolicyConfig::from_yaml_str("defaults:\n  allow_types: [\"text/plain\"]\n")
+            .expect("policy");
+        let engine = PolicyEngine::new(config);
+        let (report, outcome) = analyze_bytes(
+            Path::new("nota.txt"),
+            b"hello world\n",
+            Some(&engine),
+            &ClaimedMimeMap::default(),
+            &[],
+            &Deadline::none(),
+        )
+        .expect("ok");
+        assert_eq!(report.sniff.mime_real, "text/plain");
+        assert_eq!(report.sniff.ext.as_deref(), Some(".txt"));
+        assert_eq!(report.sniff.mime_claimed.as_deref(), Some("text/plain"));
+        assert_eq!(outcome.decision, Decision::Allow);
+        assert!(report.timings_ms.sniff.is_some());
+        assert!(report.timings_ms.total >= report.timings_ms.sniff.unwrap());
+        assert_eq!((report.bytes.read, report.bytes.hashed), (12, 12));
+        assert_eq!(report.bytes.decompressed, 0);
+    }
+
+    #[test]
+    fn hash_lists_short_circuit_before_validators() {
+        let data = b"%PDF-1.4\n/JavaScript (app.alert(1))\n%%EOF\n";
+        let digest = hex::encode(Sha256::digest(data));
+        let analyze = |yaml: String| {
+            let engine = PolicyEngine::new(PolicyConfig::from_yaml_str(&yaml).expect("policy"));
+            analyze_bytes(
+                Path::new("a.pdf"),
+                data,
+                Some(&engine),
+                &ClaimedMimeMap::default(),
+                &[],
+                &Deadline::none(),
+            )
+            .expect("ok")
+        };
+
+        let (report, outcome) = analyze(format!(
+            "defaults:\n  allow_types: [\"application/pdf\"]\nhashes:\n  deny: [\"{digest}\"]\n  allow: [\"{digest}\"]\n"
+        ));
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert_eq!(outcome.rules_triggered, vec!["hash:blocklisted"]);
+        assert!(report.validators.is_empty());
+
+        let (_, outcome) = analyze(format!(
+            "defaults:\n  deny_types: [\"application/pdf\"]\nhashes:\n  allow: [\"{digest}\"]\n"
+        ));
+        assert_eq!(outcome.decision, Decision::Allow);
+        assert_eq!(outcome.rules_triggered, vec!["hash:allowlisted"]);
+    }
+
+    fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag, content.len() as u8];
+        out.extend_from_slice(content);
+        out
+    }
+
+    #[test]
+    fn signed_envelope_is_unwrapped_and_inner_policy_applies() {
+        let signed_data_oid = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02];
+        let data_oid = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x01];
+        let inner = b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\n%%EOF\n";
+        let encap = der(
+            0x30,
+            &[der(0x06, &data_oid), der(0xA0, &der(0x04, inner))].concat(),
+        );
+        let signed_data = der(
+            0x30,
+            &[der(0x02, &[1]), der(0x31, &[]), encap, der(0x31, &[])].concat(),
+        );
+        let envelope = der(
+            0x30,
+            &[der(0x06, &signed_data_oid), der(0xA0, &signed_data)].concat(),
+        );
+
+        let config =
+            PolicyConfig::from_yaml_str("defaults:\n  deny_types: [\"application/pdf\"]\n")
+                .expect("policy");
+        let engine = PolicyEngine::new(config);
+        let (report, outcome) = analyze_bytes(
+            Path::new("contrato.pdf.p7m"),
+            &envelope,
+            Some(&engine),
+            &ClaimedMimeMap::default(),
+            &[],
+            &Deadline::none(),
+        )
+        .expect("ok");
+
+        assert_eq!(report.validators[0].name, "pkcs7");
+        let inner_report = report.inner.as_ref().expect("inner");
+        assert_eq!(inner_report.file, PathBuf::from("contrato.pdf"));
+        assert_eq!(inner_report.sniff.mime_real, "application/pdf");
+        assert_eq!(inner_report.policy.decision, "DENY");
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert!(outcome
+            .rules_triggered
+            .contains(&"inner:mime:deny:application/pdf".to_string()));
+    }
+
+    #[test]
+    fn encoded_content_is_decoded_and_inner_policy_applies() {
+        let pdf = b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\ntrailer\n%%EOF\n";
+        let encoded = base64::engine::general_purpose::STANDARD.encode(pdf);
+        let config = PolicyConfig::from_yaml_str(
+            "defaults:\n  deny_types: [\"application/pdf\"]\nencoding:\n  declared:\n    \"*.qp\": quoted-printable\n",
+        )
+        .expect("policy");
+        let engine = PolicyEngine::new(config);
+        let analyze = |name: &str, data: &[u8]| {
+            analyze_bytes(
+                Path::new(name),
+                data,
+                Some(&engine),
+                &ClaimedMimeMap::default(),
+                &[],
+                &Deadline::none(),
+            )
+            .expect("ok")
+        };
+
+        let (report, outcome) = analyze("contrato.pdf.b64", encoded.as_bytes());
+        assert_eq!(report.transfer_encoding, Some(TransferEncoding::Base64));
+        let inner_report = report.inner.as_ref().expect("inner");
+        assert_eq!(inner_report.file, PathBuf::from("contrato.pdf"));
+        assert_eq!(inner_report.sniff.mime_real, "application/pdf");
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert!(outcome
+            .rules_triggered
+            .contains(&"inner:mime:deny:application/pdf".to_string()));
+
+        let (report, outcome) = analyze("nota.qp", b"texto =ZZ invalido");
+        assert!(report.inner.is_none());
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert!(outcome
+            .rules_triggered
+            .contains(&"encoding:invalid:quoted-printable".to_string()));
+
+        let (report, _) = analyze("notas.txt", b"linha comum de texto\n");
+        assert!(report.transfer_encoding.is_none());
+    }
+
+    #[test]
+    fn inner_primary_rule_keeps_its_own_severity() {
+        let pdf = b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\ntrailer\n%%EOF\n";
+        let encoded = base64::engine::general_purpose::STANDARD.encode(pdf);
+        // Entropia (WARN) é registrada antes do MIME proibido (DENY) no arquivo interno.
+        let config = PolicyConfig::from_yaml_str(
+            "defaults:\n  deny_types: [\"application/pdf\"]\n  entropy_threshold: 0.5\n",
+        )
+        .expect("policy");
+        let engine = PolicyEngine::new(config);
+        let (report, outcome) = analyze_bytes(
+            Path::new("contrato.pdf.b64"),
+            encoded.as_bytes(),
+            Some(&engine),
+            &ClaimedMimeMap::default(),
+            &[],
+            &Deadline::none(),
+        )
+        .expect("ok");
+
+        let inner = report.inner.as_ref().expect("inner");
+        assert_eq!(
+            inner.policy.rules_triggered[0].split(':').next(),
+            Some("entropy")
+        );
+        assert_eq!(
+            inner.policy.primary_rule.as_deref(),
+            Some("mime:deny:application/pdf")
+        );
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert_eq!(
+            outcome.primary_rule.as_deref(),
+            Some("inner:mime:deny:application/pdf")
+        );
+        assert!(outcome
+            .rules_triggered
+            .iter()
+            .any(|rule| rule.starts_with("inner:entropy:")));
+    }
+}
@@ -0,0 +1,1341 @@
+// GuardUpload
+// Criado em: 2025-11-01
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Motor de políticas responsável por decisões ALLOW/WARN/DENY.
+
+use crate::config::{
+    ArchivePolicySection, BinaryPolicySection, DefaultsSection, EncodingPolicySection,
+    FilenamePolicySection, ImagePolicySection, OfficePolicySection, PdfPolicySection,
+    PiiPolicySection, Pkcs7PolicySection, PolicyConfig, RoutingPolicySection, SecretsPolicySection,
+    SeverityOverride, SvgPolicySection, ValidatorsSection,
+};
+use crate::history::{MemoryHistory, VerdictHistory};
+use crate::ranges;
+use crate::report::{rule_category, FileReport, PolicyDecision};
+use crate::rules;
+use crate::sniff;
+use crate::validators::{self, ValidatorOutcome, ValidatorRegistry, ValidatorStatus};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+/// Resultado de decisão aplicável a um arquivo depois das validações.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Decision {
+    /// Converte a decisão em texto (`ALLOW`/`WARN`/`DENY`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Decision::Allow => "ALLOW",
+            Decision::Warn => "WARN",
+            Decision::Deny => "DENY",
+        }
+    }
+
+    /// Retorna severidade ordinal para comparação (maior é mais crítico).
+    pub fn severity(&self) -> u8 {
+        match self {
+            Decision::Allow => 0,
+            Decision::Warn => 1,
+            Decision::Deny => 2,
+        }
+    }
+}
+
+/// Resultado completo da aplicação da política em um arquivo.
+#[derive(Debug, Clone)]
+pub struct DecisionOutcome {
+    pub decision: Decision,
+    pub rules_triggered: Vec<String>,
+    /// Primeira regra registrada com a maior severidade.
+    pub primary_rule: Option<String>,
+}
+
+impl DecisionOutcome {
+    pub fn new() -> Self {
+        Self {
+            decision: Decision::Allow,
+            rules_triggered: Vec::new(),
+            primary_rule: None,
+        }
+    }
+
+    /// Registra regra acionada e eleva severidade conforme necessário.
+    pub fn record(&mut self, severity: Decision, rule: impl Into<String>) {
+        let rule_string = rule.into();
+        if self.primary_rule.is_none() || severity.severity() > self.decision.severity() {
+            self.primary_rule = Some(rule_string.clone());
+        }
+        self.rules_triggered.push(rule_string);
+        if severity.severity() > self.decision.severity() {
+            self.decision = severity;
+        }
+    }
+}
+
+impl DecisionOutcome {
+    /// Códigos estáveis das regras disparadas, na mesma ordem de `rules_triggered`.
+    pub fn rule_codes(&self) -> Vec<String> {
+        self.rules_triggered
+            .iter()
+            .map(|rule| rules::code_for(rule))
+            .collect()
+    }
+
+    /// Incorpora as regras de outro resultado (ex.: regras de sessão). Quando ele
+    /// eleva a decisão, a regra principal dele passa a ser a principal daqui.
+    pub fn merge(&mut self, other: &DecisionOutcome) {
+        self.merge_prefixed(other, "");
+    }
+
+    /// Como [`merge`](Self::merge), prefixando as regras incorporadas (`inner:`
+    /// para o conteúdo desembrulhado). Regras de menor severidade do outro
+    /// resultado não assumem o posto de principal.
+    pub fn merge_prefixed(&mut self, other: &DecisionOutcome, prefix: &str) {
+        let escalates = other.decision.severity() > self.decision.severity();
+        if escalates || self.primary_rule.is_none() {
+            if let Some(primary) = &other.primary_rule {
+                self.primary_rule = Some(format!("{prefix}{primary}"));
+            }
+        }
+        if escalates {
+            self.decision = other.decision;
+        }
+        self.rules_triggered.extend(
+            other
+                .rules_triggered
+                .iter()
+                .map(|rule| format!("{prefix}{rule}")),
+        );
+    }
+}
+
+impl Default for DecisionOutcome {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<DecisionOutcome> for PolicyDecision {
+    fn from(value: DecisionOutcome) -> Self {
+        PolicyDecision {
+            decision: value.decision.as_str().to_string(),
+            rule_codes: value.rule_codes(),
+            primary_code: value.primary_rule.as_deref().map(rules::code_for),
+            rules_triggered: value.rules_triggered,
+            category: value
+                .primary_rule
+                .as_deref()
+                .map(|rule| rule_category(rule).to_string()),
+            primary_rule: value.primary_rule,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResolvedPolicy {
+    pub defaults: DefaultsSection,
+    pub pdf: PdfPolicySection,
+    pub image: ImagePolicySection,
+    pub archive: ArchivePolicySection,
+    pub svg: SvgPolicySection,
+    pub office: OfficePolicySection,
+    pub pkcs7: Pkcs7PolicySection,
+    #[serde(default)]
+    pub encoding: EncodingPolicySection,
+    pub binary: BinaryPolicySection,
+    #[serde(default)]
+    pub filename: FilenamePolicySection,
+    #[serde(default)]
+    pub secrets: SecretsPolicySection,
+    #[serde(default)]
+    pub pii: PiiPolicySection,
+    pub validators: ValidatorsSection,
+    #[serde(default)]
+    pub routing: RoutingPolicySection,
+}
+
+impl ResolvedPolicy {
+    pub fn from_config(config: &PolicyConfig) -> Self {
+        Self {
+            defaults: config.defaults.clone(),
+            pdf: config.pdf.clone(),
+            image: config.image.clone(),
+            archive: config.archive.clone(),
+            svg: config.svg.clone(),
+            office: config.office.clone(),
+            pkcs7: config.pkcs7.clone(),
+            encoding: config.encoding.clone(),
+            binary: config.binary.clone(),
+            filename: config.filename.clone(),
+            secrets: config.secrets.clone(),
+            pii: config.pii.clone(),
+            validators: config.validators.clone(),
+            routing: config.routing.clone(),
+        }
+    }
+
+    /// Aplica o `set` de um override (`secao.campo[.subcampo]: valor`); um campo
+    /// sem seção que não seja uma seção vale para `defaults` (`max_size_mb: 5`).
+    /// Valores fora da faixa válida são rejeitados ou reduzidos ao teto
+    /// ([`ranges::check_policy`]); em caso de erro a política fica inalterada.
+    pub fn apply_set(&mut self, set: &BTreeMap<String, serde_yaml::Value>) -> Result<()> {
+        let mut next = self.clone();
+        next.apply_set_unchecked(set)?;
+        ranges::check_policy(&mut next)?;
+        *self = next;
+        Ok(())
+    }
+
+    /// [`ResolvedPolicy::apply_set`] sem conferir as faixas.
+    pub(crate) fn apply_set_unchecked(
+        &mut self,
+        set: &BTreeMap<String, serde_yaml::Value>,
+    ) -> Result<()> {
+        if set.is_empty() {
+            return Ok(());
+        }
+        let mut tree = serde_yaml::to_value(&*self)?;
+        for (path, value) in set {
+            let is_section = tree
+                .as_mapping()
+                .is_some_and(|sections| sections.contains_key(path.as_str()));
+            let path = if path.contains('.') || is_section {
+                path.clone()
+            } else {
+                format!("defaults.{path}")
+            };
+            let mut node = &mut tree;
+            for key in path.split('.') {
+                let Some(mapping) = node.as_mapping_mut() else {
+                    bail!("campo '{path}' não é uma seção");
+                };
+                let key = serde_yaml::Value::String(key.to_string());
+                if !mapping.contains_key(&key) {
+                    bail!("campo desconhecido em override: '{path}'");
+                }
+                node = mapping.get_mut(&key).expect("chave conferida");
+            }
+            *node = value.clone();
+        }
+        *self = serde_yaml::from_value(tree).context("valor inválido em override")?;
+        Ok(())
+    }
+}
+
+/// MIME normalizado: essência (`tipo/subtipo`) em minúsculas e parâmetros.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MimeValue {
+    essence: String,
+    params: Vec<(String, String)>,
+}
+
+impl MimeValue {
+    /// Separa `tipo/subtipo; chave=valor` ignorando caixa, espaços e aspas.
+    fn parse(raw: &str) -> Self {
+        let mut parts = raw.split(';');
+        let essence = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+        let params = parts
+            .filter_map(|param| {
+                let (key, value) = param.split_once('=')?;
+                let key = key.trim().to_ascii_lowercase();
+                let value = value.trim().trim_matches('"').to_ascii_lowercase();
+                (!key.is_empty()).then_some((key, value))
+            })
+            .collect();
+        Self { essence, params }
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct CompiledPattern {
+    raw: String,
+    mime: MimeValue,
+    /// Sufixo estruturado (`+zip`, `+xml`) quando o padrão é `*/*+sufixo`.
+    suffix: Option<String>,
+}
+
+impl CompiledPattern {
+    fn new(raw: String) -> Self {
+        let mut mime = MimeValue::parse(&raw);
+        // `+zip` é atalho para `*/*+zip`.
+        if mime.essence.starts_with('+') {
+            mime.essence = format!("*/*{}", mime.essence);
+        }
+        let suffix = mime
+            .essence
+            .strip_prefix("*/*+")
+            .filter(|suffix| !suffix.is_empty() && !suffix.contains(['*', '?']))
+            .map(str::to_string);
+        Self { raw, mime, suffix }
+    }
+
+    /// Compara a essência (glob) e exige que cada parâmetro do padrão esteja
+    /// presente no valor; `*/*+zip` também cobre o tipo base `application/zip`.
+    fn matches(&self, value: &MimeValue) -> bool {
+        let essence = matches_pattern(&self.mime.essence, &value.essence)
+            || self
+                .suffix
+                .as_deref()
+                .is_some_and(|suffix| value.essence == format!("application/{suffix}"));
+        essence
+            && self
+                .mime
+                .params
+                .iter()
+                .all(|(key, expected)| value.param(key) == Some(expected.as_str()))
+    }
+}
+
+#[derive(Debug, Default)]
+struct CompiledDefaults {
+    allow: Vec<CompiledPattern>,
+    deny: Vec<CompiledPattern>,
+    max_size_bytes: Option<u64>,
+}
+
+/// Executor de políticas carregadas a partir de YAML.
+#[derive(Debug)]
+pub struct PolicyEngine {
+    config: PolicyConfig,
+    compiled_defaults: CompiledDefaults,
+    custom_signatures: Vec<sniff::CustomSignature>,
+    validators: Option<ValidatorRegistry>,
+    history: Option<Arc<dyn VerdictHistory>>,
+}
+
+impl PolicyEngine {
+    /// Cria o motor a partir da configuração validada.
+    pub fn new(config: PolicyConfig) -> Self {
+        let compiled_defaults = compile_defaults(&config.defaults);
+        // Entradas inválidas já são recusadas ao carregar a política.
+        let custom_signatures = config
+            .sniff
+            .custom
+            .iter()
+            .filter_map(|section| sniff::CustomSignature::compile(section).ok())
+            .collect();
+        // Com `history.set`, o histórico em memória vale até que outro seja associado.
+        let history = (!config.history.set.is_empty())
+            .then(|| Arc::new(MemoryHistory::new()) as Arc<dyn VerdictHistory>);
+        Self {
+            config,
+            compiled_defaults,
+            custom_signatures,
+            validators: None,
+            history,
+        }
+    }
+
+    /// Assinaturas de `sniff.custom`, verificadas antes da base magic.
+    pub fn custom_signatures(&self) -> &[sniff::CustomSignature] {
+        &self.custom_signatures
+    }
+
+    /// Associa um registro de validadores (ex.: com plugins externos da política).
+    pub fn with_validators(mut self, validators: ValidatorRegistry) -> Self {
+        self.validators = Some(validators);
+        self
+    }
+
+    /// Validadores usados pela análise; sem registro próprio, os embutidos.
+    pub fn validators(&self) -> &ValidatorRegistry {
+        self.validators
+            .as_ref()
+            .unwrap_or_else(|| ValidatorRegistry::shared_builtin())
+    }
+
+    /// Falha quando os validadores não atendem a `require` da política.
+    pub fn check_requirements(&self) -> Result<()> {
+        self.validators().check_requirements(&self.config.require)
+    }
+
+    /// Associa um histórico de veredictos (ex.: persistido em `history.path`).
+    pub fn with_history(mut self, history: Arc<dyn VerdictHistory>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Histórico que recebe as decisões; `None` sem `history.set` na política.
+    pub fn history(&self) -> Option<&dyn VerdictHistory> {
+        self.history.as_deref()
+    }
+
+    /// Indica se o hash já recebeu WARN ao menos `history.min_warnings` vezes.
+    pub fn is_repeat_offender(&self, sha256: &str) -> bool {
+        let history = &self.config.history;
+        match self.history() {
+            Some(store) if !history.set.is_empty() => {
+                store.warnings(sha256) >= history.min_warnings.unwrap_or(1).max(1)
+            }
+            _ => false,
+        }
+    }
+
+    /// Resolve a política para um arquivo, aplicando os overrides ativos agora.
+    pub fn resolve(&self, report: &FileReport) -> ResolvedPolicy {
+        self.resolve_at(report, OffsetDateTime::now_utc())
+    }
+
+    /// Igual a [`PolicyEngine::resolve`], avaliando `active_when` no instante `now`.
+    pub fn resolve_at(&self, report: &FileReport, now: OffsetDateTime) -> ResolvedPolicy {
+        let mut resolved = ResolvedPolicy::from_config(&self.config);
+        let mime = MimeValue::parse(&report.sniff.mime_real).essence;
+        for rule in &self.config.overrides {
+            if rule.applies(&mime, now) {
+                // Políticas carregadas de YAML já tiveram os `set` validados.
+                let _ = resolved.apply_set(&rule.set);
+            }
+        }
+        if !report.partially_read && self.is_repeat_offender(&report.sha256) {
+            let _ = resolved.apply_set(&self.config.history.set);
+        }
+        resolved
+    }
+
+    /// Aplica decisão para um arquivo considerando validadores e limites.
+    pub fn decide(
+        &self,
+        report: &FileReport,
+        validators: &[ValidatorOutcome],
+        resolved: Option<&ResolvedPolicy>,
+    ) -> DecisionOutcome {
+        let mut outcome = DecisionOutcome::new();
+
+        for validator in validators {
+            match validator.status {
+                ValidatorStatus::Deny | ValidatorStatus::Warn => {
+                    self.record_finding(&mut outcome, validator)
+                }
+                ValidatorStatus::Error if validator.is_timeout() => outcome.record(
+                    Decision::Deny,
+                    format!("validator:{}:error:timeout", validator.name),
+                ),
+                // O `generic` já rodou no lugar e decide; a falha fica como alerta.
+                ValidatorStatus::Error if validator.fell_back() => outcome.record(
+                    Decision::Warn,
+                    format!("validator:{}:error:fallback", validator.name),
+                ),
+                ValidatorStatus::Error => outcome.record(
+                    Decision::Deny,
+                    format!("validator:{}:error", validator.name),
+                ),
+                ValidatorStatus::Pass | ValidatorStatus::Skipped => {}
+            }
+        }
+
+        let defaults = resolved
+            .map(|policy| &policy.defaults)
+            .unwrap_or(&self.config.defaults);
+        // Overrides podem trocar as listas de MIME; só então elas são recompiladas.
+        let recompiled;
+        let compiled = if defaults.allow_types == self.config.defaults.allow_types
+            && defaults.deny_types == self.config.defaults.deny_types
+            && defaults.max_size_mb == self.config.defaults.max_size_mb
+        {
+            &self.compiled_defaults
+        } else {
+            recompiled = compile_defaults(defaults);
+            &recompiled
+        };
+        let mime = MimeValue::parse(&report.sniff.mime_real);
+
+        if let Some(max_size_mb) = defaults.max_size_mb {
+            let max_bytes = max_size_mb as u64 * 1024 * 1024;
+            if report.size_bytes > max_bytes {
+                outcome.record(
+                    Decision::Deny,
+                    format!("size:exceeds_max:{}>{}", report.size_bytes, max_bytes),
+                );
+            }
+        } else if let Some(max_bytes) = compiled.max_size_bytes {
+            if report.size_bytes > max_bytes {
+                outcome.record(
+                    Decision::Deny,
+                    format!("size:exceeds_max:{}>{}", report.size_bytes, max_bytes),
+                );
+            }
+        }
+
+        if report.file_name_escaped {
+            match defaults.unsafe_file_names.as_deref() {
+                Some(action) if action.eq_ignore_ascii_case("allow") => {}
+                Some(action) if action.eq_ignore_ascii_case("deny") => {
+                    outcome.record(Decision::Deny, "file:unsafe_name".to_string())
+                }
+                _ => outcome.record(Decision::Warn, "file:unsafe_name".to_string()),
+            }
+        }
+
+        if report.partially_read {
+            match defaults.partial_read_action.as_deref() {
+                Some(action) if action.eq_ignore_ascii_case("allow") => {}
+                Some(action) if action.eq_ignore_ascii_case("warn") => {
+                    outcome.record(Decision::Warn, "size:partially_read".to_string())
+                }
+                _ => outcome.record(Decision::Deny, "size:partially_read".to_string()),
+            }
+        }
+
+        if let (Some(threshold), Some(entropy)) = (defaults.entropy_threshold, &report.entropy) {
+            if entropy.entropy > threshold {
+                let severity = match defaults.entropy_action.as_deref() {
+                    Some(action) if action.eq_ignore_ascii_case("deny") => Decision::Deny,
+                    _ => Decision::Warn,
+                };
+                outcome.record(
+                    severity,
+                    format!(
+                        "entropy:exceeds_threshold:{:.2}>{threshold}",
+                        entropy.entropy
+                    ),
+                );
+            }
+        }
+
+        if let Some(structure) = &report.structure {
+            let severity = match defaults.structure_action.as_deref() {
+                Some(action) if action.eq_ignore_ascii_case("allow") => None,
+                Some(action) if action.eq_ignore_ascii_case("deny") => Some(Decision::Deny),
+                _ => Some(Decision::Warn),
+            };
+            if let Some(severity) = severity {
+                for anomaly in &structure.anomalies {
+                    outcome.record(
+                        severity,
+                        format!(
+                            "structure:{}:{}:{}",
+                            anomaly.kind, anomaly.format, anomaly.bytes
+                        ),
+                    );
+                }
+                if structure.is_polyglot() {
+                    outcome.record(
+                        severity,
+                        format!("structure:polyglot:{}", structure.formats.join("+")),
+                    );
+                }
+            }
+        }
+
+        if let Some(secrets) = &report.secrets {
+            let section = resolved
+                .map(|policy| &policy.secrets)
+                .unwrap_or(&self.config.secrets);
+            if section.enabled.unwrap_or(false) {
+                let severity = match section.action.as_deref() {
+                    Some(action) if action.eq_ignore_ascii_case("deny") => Decision::Deny,
+                    _ => Decision::Warn,
+                };
+                for (kind, count) in secrets.counts() {
+                    outcome.record(severity, format!("secrets:{kind}:{count}"));
+                }
+            }
+        }
+
+        if let Some(pii) = &report.pii {
+            let section = resolved
+                .map(|policy| &policy.pii)
+                .unwrap_or(&self.config.pii);
+            if section.enabled.unwrap_or(false) {
+                for (kind, count) in pii.counts() {
+                    let action = section.actions.get(kind).or(section.action.as_ref());
+                    let severity = match action.map(String::as_str) {
+                        Some(action) if action.eq_ignore_ascii_case("allow") => continue,
+                        Some(action) if action.eq_ignore_ascii_case("deny") => Decision::Deny,
+                        _ => Decision::Warn,
+                    };
+                    outcome.record(severity, format!("pii:{kind}:{count}"));
+                }
+            }
+        }
+
+        if let Some(pattern) = find_match(&compiled.deny, &mime) {
+            outcome.record(Decision::Deny, format!("mime:deny:{}", pattern.raw));
+        }
+
+        if defaults.deny_on_mime_mismatch.unwrap_or(false) {
+            if let Some(claimed) = report.sniff.mime_claimed.as_deref() {
+                if sniff::is_mime_mismatch(claimed, &report.sniff.mime_real) {
+                    outcome.record(
+                        Decision::Deny,
+                        format!("mime:mismatch:{claimed}!={}", report.sniff.mime_real),
+                    );
+                }
+            }
+        }
+
+        if let (Some(min), Some(score)) = (defaults.min_sniff_confidence, report.sniff.score) {
+            let severity = match defaults.sniff_confidence_action.as_deref() {
+                Some(action) if action.eq_ignore_ascii_case("deny") => Decision::Deny,
+                _ => Decision::Warn,
+            };
+            if score < min {
+                outcome.record(severity, format!("sniff:low_confidence:{score:.2}<{min}"));
+            }
+            // Candidatos só da extensão ficam para `deny_on_mime_mismatch`.
+            for candidate in report.sniff.candidates.iter().filter(|candidate| {
+                candidate.score >= min
+                    && candidate
+                        .sources
+                        .iter()
+                        .any(|source| source != sniff::EXTENSION_HINT)
+            }) {
+                outcome.record(
+                    severity,
+                    format!(
+                        "sniff:ambiguous:{}|{}",
+                        report.sniff.mime_real, candidate.mime
+                    ),
+                );
+            }
+        }
+
+        if !compiled.allow.is_empty() && find_match(&compiled.allow, &mime).is_none() {
+            outcome.record(
+                Decision::Deny,
+                format!("mime:not_allowed:{}", report.sniff.mime_real),
+            );
+        }
+
+        outcome
+    }
+
+    /// Refaz só a etapa de decisão a partir dos fatos gravados em um relatório
+    /// (listas de hashes, sniff, entropia, estrutura e resultados dos validadores),
+    /// sem reler o arquivo. Validadores não são executados de novo: chaves que
+    /// mudam o comportamento deles não alteram o resultado. Regras de `encoding`
+    /// do relatório são mantidas; as de sessão não entram.
+    pub fn replay(&self, report: &FileReport) -> DecisionOutcome {
+        let verdict = (!report.partially_read)
+            .then(|| self.hash_verdict(&report.sha256))
+            .flatten();
+        if let Some(verdict) = verdict {
+            let mut outcome = DecisionOutcome::new();
+            let rule = match verdict {
+                Decision::Deny => "hash:blocklisted",
+                _ => "hash:allowlisted",
+            };
+            outcome.record(verdict, rule);
+            return outcome;
+        }
+        let validators: Vec<ValidatorOutcome> = report
+            .validators
+            .iter()
+            .filter_map(|entry| {
+                let status = match entry.status.as_str() {
+                    "pass" => ValidatorStatus::Pass,
+                    "warn" => ValidatorStatus::Warn,
+                    "deny" => ValidatorStatus::Deny,
+                    "error" => ValidatorStatus::Error,
+                    "skipped" => ValidatorStatus::Skipped,
+                    _ => return None,
+                };
+                Some(ValidatorOutcome::new(
+                    validators::intern(&entry.name),
+                    status,
+                    entry.details.clone(),
+                ))
+            })
+            .collect();
+        let resolved = self.resolve(report);
+        let mut outcome = self.decide(report, &validators, Some(&resolved));
+
+        if let Some(inner) = report.inner.as_deref() {
+            outcome.merge_prefixed(&self.replay(inner), "inner:");
+        }
+        for rule in &report.policy.rules_triggered {
+            if rule.starts_with("encoding:limit:") {
+                outcome.record(Decision::Warn, rule.clone());
+            } else if rule.starts_with("encoding:invalid:") {
+                outcome.record(Decision::Deny, rule.clone());
+            }
+        }
+        outcome
+    }
+
+    /// Converte DENY/WARN de um validador em regra, aplicando `severity_overrides`
+    /// a cada código do achado; vale a maior severidade entre eles.
+    fn record_finding(&self, outcome: &mut DecisionOutcome, validator: &ValidatorOutcome) {
+        let status = validator.status.as_str();
+        let original = match validator.status {
+            ValidatorStatus::Deny => Decision::Deny,
+            _ => Decision::Warn,
+        };
+        let mut codes = validator.codes();
+        if codes.is_empty() {
+            codes.push(status);
+        }
+        let effective = codes
+            .iter()
+            .map(|code| {
+                let severity = self
+                    .severity_override(&format!("{}:{code}", validator.name))
+                    .or_else(|| self.severity_override(&format!("{}:{status}", validator.name)));
+                match severity {
+                    Some(SeverityOverride::Allow) => Decision::Allow,
+                    Some(SeverityOverride::Warn) => Decision::Warn,
+                    Some(SeverityOverride::Deny) => Decision::Deny,
+                    None => original,
+                }
+            })
+            .max_by_key(Decision::severity)
+            .unwrap_or(original);
+        if effective == original {
+            outcome.record(original, format!("validator:{}:{status}", validator.name));
+        } else if effective != Decision::Allow {
+            outcome.record(
+                effective,
+                format!(
+                    "validator:{}:{}:remapped",
+                    validator.name,
+                    effective.as_str().to_ascii_lowercase()
+                ),
+            );
+        }
+    }
+
+    /// Entrada de `severity_overrides` que casa com `key`; chaves sem curinga
+    /// vencem, depois as mais longas.
+    fn severity_override(&self, key: &str) -> Option<SeverityOverride> {
+        self.config
+            .severity_overrides
+            .iter()
+            .filter(|(pattern, _)| matches_pattern(pattern, key))
+            .max_by_key(|(pattern, _)| (!pattern.contains(['*', '?']), pattern.len()))
+            .map(|(_, severity)| *severity)
+    }
+
+    /// Aplica as regras da seção `session` ao conjunto de arquivos de uma submissão.
+    ///
+    /// As regras devolvidas valem para todos os arquivos da sessão; registros de
+    /// erro contam para `max_files`, mas não para tamanho nem tipo.
+    pub fn decide_session(&self, reports: &[&FileReport]) -> DecisionOutcome {
+        let mut outcome = DecisionOutcome::new();
+        let session = &self.config.session;
+        let severity = match session.action.as_deref() {
+            Some(action) if action.eq_ignore_ascii_case("warn") => Decision::Warn,
+            _ => Decision::Deny,
+        };
+
+        if let Some(max_files) = session.max_files {
+            if reports.len() > max_files as usize {
+                outcome.record(
+                    severity,
+                    format!("session:max_files:{}>{max_files}", reports.len()),
+                );
+            }
+        }
+
+        let analyzed = || reports.iter().filter(|report| report.error.is_none());
+        if let Some(max_total_size_mb) = session.max_total_size_mb {
+            let max_bytes = max_total_size_mb as u64 * 1024 * 1024;
+            let total: u64 = analyzed().map(|report| report.size_bytes).sum();
+            if total > max_bytes {
+                outcome.record(
+                    severity,
+                    format!("session:max_total_size:{total}>{max_bytes}"),
+                );
+            }
+        }
+
+        if session.forbid_mixed_executable_document.unwrap_or(false)
+            && analyzed().any(|report| is_executable(report))
+            && analyzed().any(|report| is_document(&report.sniff.mime_real))
+        {
+            outcome.record(severity, "session:mixed_executable_document".to_string());
+        }
+
+        outcome
+    }
+
+    /// Veredito das listas `hashes` para o SHA-256 (a lista de bloqueio prevalece).
+    pub fn hash_verdict(&self, sha256: &str) -> Option<Decision> {
+        let hashes = &self.config.hashes;
+        if hashes.deny_digests.contains(sha256) {
+            Some(Decision::Deny)
+        } else if hashes.allow_digests.contains(sha256) {
+            Some(Decision::Allow)
+        } else {
+            None
+        }
+    }
+
+    /// Fornece referência à configuração original.
+    pub fn config(&self) -> &PolicyConfig {
+        &self.config
+    }
+}
+
+/// Executáveis são reconhecidos pelo validador `binary` (cabeçalho PE/ELF/Mach-O).
+fn is_executable(report: &FileReport) -> bool {
+    report.validators.iter().any(|entry| entry.name == "binary")
+}
+
+fn is_document(mime: &str) -> bool {
+    let essence = MimeValue::parse(mime).essence;
+    matches!(
+        essence.as_str(),
+        "application/pdf"
+            | "application/rtf"
+            | "text/rtf"
+            | "application/msword"
+            | "application/vnd.ms-excel"
+            | "application/vnd.ms-powerpoint"
+    ) || essence.starts_with("application/vnd.openxmlformats-officedocument.")
+        || essence.starts_with("application/vnd.oasis.opendocument.")
+}
+
+fn compile_defaults(defaults: &DefaultsSection) -> CompiledDefaults {
+    let allow = defaults
+        .allow_types
+        .iter()
+        .map(|pattern| CompiledPattern::new(pattern.clone()))
+        .collect();
+    let deny = defaults
+        .deny_types
+        .iter()
+        .map(|pattern| CompiledPattern::new(pattern.clone()))
+        .collect();
+    let max_size_bytes = defaults.max_size_mb.map(|mb| mb as u64 * 1024 * 1024);
+
+    CompiledDefaults {
+        allow,
+        deny,
+        max_size_bytes,
+    }
+}
+
+fn find_match<'a>(
+    patterns: &'a [CompiledPattern],
+    value: &MimeValue,
+) -> Option<&'a CompiledPattern> {
+    patterns.iter().find(|pattern| pattern.matches(value))
+}
+
+/// Casa `value` com um padrão de MIME com curingas `*` e `?`.
+pub fn matches_pattern(pattern: &str, value: &str) -> bool {
+    matches_pattern_bytes(pattern.as_bytes(), value.as_bytes())
+}
+
+fn matches_pattern_bytes(pattern: &[u8], value: &[u8]) -> bool {
+    if pattern.is_empty() {
+        return value.is_empty();
+    }
+
+    match pattern[0] {
+        b'*' => {
+            let mut idx = 0;
+            while idx < pattern.len() && pattern[idx] == b'*' {
+                idx += 1;
+            }
+            if idx == pattern.len() {
+                return true;
+            }
+            for offset in 0..=value.len() {
+                if matches_pattern_bytes(&pattern[idx..], &value[offset..]) {
+                    return true;
+                }
+            }
+            false
+        }
+        b'?' => {
+            if value.is_empty() {
+                false
+            } else {
+                matches_pattern_bytes(&pattern[1..], &value[1..])
+            }
+        }
+        ch => {
+            if value.first() == Some(&ch) {
+                matches_pattern_bytes(&pattern[1..], &value[1..])
+            } else {
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{FileReport, SniffReport, ValidatorEntry};
+    use crate::sniff::SniffCandidate;
+    use serde_json::json;
+    use std::path::Path;
+
+    #[test]
+    fn decision_variants_match_strings() {
+        assert_eq!(Decision::Allow.as_str(), "ALLOW");
+        assert_eq!(Decision::Warn.as_str(), "WARN");
+        assert_eq!(Decision::Deny.as_str(), "DENY");
+    }
+
+    #[test]
+    fn decision_severity_orders_correctly() {
+        assert!(Decision::Allow.severity() < Decision::Warn.severity());
+        assert!(Decision::Warn.severity() < Decision::Deny.severity());
+    }
+
+    #[test]
+    fn engine_holds_config_reference() {
+        let config = PolicyConfig::default();
+        let engine = PolicyEngine::new(config.clone());
+        assert_eq!(
+            engine.config().defaults.max_size_mb,
+            config.defaults.max_size_mb
+        );
+    }
+
+    #[test]
+    fn deny_list_blocks_matching_mime() {
+        let mut config = PolicyConfig::default();
+        config.defaults.deny_types = vec!["application/x-rpm".into()];
+        let engine = PolicyEngine::new(config);
+        let report = sample_report("application/x-rpm", 1024);
+        let outcome = engine.decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert!(outcome
+            .rules_triggered
+            .iter()
+            .any(|rule| rule == "mime:deny:application/x-rpm"));
+    }
+
+    #[test]
+    fn allow_list_denies_when_mime_absent() {
+        let mut config = PolicyConfig::default();
+        config.defaults.allow_types = vec!["image/*".into(), "application/pdf".into()];
+        let engine = PolicyEngine::new(config);
+        let report = sample_report("video/mp4", 2048);
+        let outcome = engine.decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert!(outcome
+            .rules_triggered
+            .iter()
+            .any(|rule| rule == "mime:not_allowed:video/mp4"));
+    }
+
+    #[test]
+    fn allow_list_matches_wildcard() {
+        let mut config = PolicyConfig::default();
+        config.defaults.allow_types = vec!["image/*".into()];
+        let engine = PolicyEngine::new(config);
+        let report = sample_report("image/png", 512);
+        let outcome = engine.decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Allow);
+    }
+
+    #[test]
+    fn mime_matching_ignores_case_and_honors_parameters() {
+        let mut config = PolicyConfig::default();
+        config.defaults.allow_types = vec!["Text/Plain; charset=UTF-8".into()];
+        let engine = PolicyEngine::new(config);
+
+        let report = sample_report("text/plain; Charset=\"utf-8\"; format=flowed", 10);
+        assert_eq!(engine.decide(&report, &[], None).decision, Decision::Allow);
+        let report = sample_report("TEXT/PLAIN; charset=iso-8859-1", 10);
+        assert_eq!(engine.decide(&report, &[], None).decision, Decision::Deny);
+        let report = sample_report("text/plain", 10);
+        assert_eq!(engine.decide(&report, &[], None).decision, Decision::Deny);
+    }
+
+    #[test]
+    fn structured_suffix_patterns_match_container_formats() {
+        let mut config = PolicyConfig::default();
+        config.defaults.deny_types = vec!["*/*+zip".into(), "+xml".into()];
+        let engine = PolicyEngine::new(config);
+
+        for mime in [
+            "application/epub+zip",
+            "application/zip",
+            "image/svg+xml; charset=utf-8",
+        ] {
+            let outcome = engine.decide(&sample_report(mime, 10), &[], None);
+            assert_eq!(outcome.decision, Decision::Deny, "{mime}");
+        }
+        let outcome = engine.decide(&sample_report("application/gzip", 10), &[], None);
+        assert_eq!(outcome.decision, Decision::Allow);
+        let outcome = engine.decide(&sample_report("application/xml", 10), &[], None);
+        assert_eq!(outcome.rules_triggered, vec!["mime:deny:+xml".to_string()]);
+    }
+
+    #[test]
+    fn primary_rule_is_first_rule_with_highest_severity() {
+        let mut outcome = DecisionOutcome::new();
+        outcome.record(Decision::Warn, "entropy:exceeds_threshold:7.90>7.5");
+        outcome.record(Decision::Deny, "validator:pdf:deny");
+        outcome.record(Decision::Deny, "mime:deny:application/pdf");
+        outcome.record(Decision::Warn, "file:unsafe_name");
+        let decision = PolicyDecision::from(outcome);
+        assert_eq!(decision.primary_rule.as_deref(), Some("validator:pdf:deny"));
+        assert_eq!(decision.category.as_deref(), Some("validator"));
+
+        let decision = PolicyDecision::from(DecisionOutcome::new());
+        assert!(decision.primary_rule.is_none() && decision.category.is_none());
+    }
+
+    #[test]
+    fn size_limit_triggers_deny() {
+        let mut config = PolicyConfig::default();
+        config.defaults.max_size_mb = Some(1);
+        let engine = PolicyEngine::new(config);
+        let report = sample_report("application/pdf", 2 * 1024 * 1024);
+        let outcome = engine.decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert!(outcome
+            .rules_triggered
+            .iter()
+            .any(|rule| rule.starts_with("size:exceeds_max:")));
+    }
+
+    #[test]
+    fn validator_denies_propagate_to_policy() {
+        let config = PolicyConfig::default();
+        let engine = PolicyEngine::new(config);
+        let report = sample_report("application/pdf", 1024);
+        let validator = ValidatorOutcome::deny("pdf", "teste");
+        let outcome = engine.decide(&report, &[validator], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert!(outcome
+            .rules_triggered
+            .iter()
+            .any(|rule| rule.starts_with("validator:pdf")));
+    }
+
+    #[test]
+    fn validator_timeout_is_reported_as_error_timeout() {
+        let engine = PolicyEngine::new(PolicyConfig::default());
+        let report = sample_report("application/zip", 1024);
+        let validator = ValidatorOutcome::timeout("archive");
+        let outcome = engine.decide(&report, &[validator], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert_eq!(
+            outcome.rules_triggered,
+            vec!["validator:archive:error:timeout"]
+        );
+    }
+
+    #[test]
+    fn entropy_threshold_warns_by_default_and_denies_when_configured() {
+        let mut config = PolicyConfig::default();
+        config.defaults.entropy_threshold = Some(7.5);
+        let mut report = sample_report("application/octet-stream", 1024);
+        let data: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+        report.entropy = Some(crate::analyzers::analyze_entropy(&data, 1024));
+
+        let outcome = PolicyEngine::new(config.clone()).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Warn);
+        assert!(outcome
+            .rules_triggered
+            .iter()
+            .any(|rule| rule == "entropy:exceeds_threshold:8.00>7.5"));
+
+        config.defaults.entropy_action = Some("deny".into());
+        let outcome = PolicyEngine::new(config).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+    }
+
+    #[test]
+    fn structure_anomalies_follow_structure_action() {
+        let mut config = PolicyConfig::default();
+        let mut report = sample_report("image/jpeg", 1024);
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xD9];
+        data.extend_from_slice(b"%PDF-1.4\n%%EOF");
+        report.structure = crate::analyzers::structure::analyze_structure(&data);
+
+        let outcome = PolicyEngine::new(config.clone()).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Warn);
+        assert_eq!(
+            outcome.rules_triggered,
+            vec![
+                "structure:trailing_data:jpeg:14",
+                "structure:prepended_data:pdf:4",
+                "structure:polyglot:jpeg+pdf",
+            ]
+        );
+
+        config.defaults.structure_action = Some("deny".into());
+        let outcome = PolicyEngine::new(config.clone()).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+
+        config.defaults.structure_action = Some("allow".into());
+        let outcome = PolicyEngine::new(config).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Allow);
+    }
+
+    #[test]
+    fn pii_findings_follow_per_kind_actions() {
+        let mut config = PolicyConfig::default();
+        let mut report = sample_report("text/plain", 1024);
+        report.pii = crate::analyzers::pii::analyze_pii(
+            "text/plain",
+            b"contato@example.com 11.222.333/0001-81 529.982.247-25",
+            &crate::limits::Deadline::none(),
+        );
+
+        let outcome = PolicyEngine::new(config.clone()).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Allow);
+
+        config.pii.enabled = Some(true);
+        let outcome = PolicyEngine::new(config.clone()).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Warn);
+        assert_eq!(
+            outcome.rules_triggered,
+            vec!["pii:cnpj:1", "pii:cpf:1", "pii:email:1"]
+        );
+
+        config.pii.action = Some("allow".into());
+        config.pii.actions.insert("cpf".into(), "deny".into());
+        let outcome = PolicyEngine::new(config).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert_eq!(outcome.rules_triggered, vec!["pii:cpf:1"]);
+    }
+
+    #[test]
+    fn mime_mismatch_is_denied_only_when_enabled() {
+        let mut config = PolicyConfig::default();
+        let mut report = sample_report("application/x-msdownload", 1024);
+        report.sniff.mime_claimed = Some("application/pdf".into());
+
+        let outcome = PolicyEngine::new(config.clone()).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Allow);
+
+        config.defaults.deny_on_mime_mismatch = Some(true);
+        let outcome = PolicyEngine::new(config).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert_eq!(
+            outcome.rules_triggered,
+            vec!["mime:mismatch:application/pdf!=application/x-msdownload"]
+        );
+    }
+
+    #[test]
+    fn low_sniff_confidence_and_conflicting_candidates_are_flagged() {
+        let mut report = sample_report("application/octet-stream", 64);
+        report.sniff.score = Some(0.1);
+        report.sniff.candidates = vec![SniffCandidate {
+            mime: "image/png".into(),
+            score: 0.3,
+            sources: vec![sniff::EXTENSION_HINT.into()],
+        }];
+        let mut config = PolicyConfig::default();
+        let outcome = PolicyEngine::new(config.clone()).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Allow);
+
+        config.defaults.min_sniff_confidence = Some(0.5);
+        let outcome = PolicyEngine::new(config.clone()).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Warn);
+        assert_eq!(
+            outcome.rules_triggered,
+            vec!["sniff:low_confidence:0.10<0.5"]
+        );
+
+        report.sniff.mime_real = "image/jpeg".into();
+        report.sniff.score = Some(0.9);
+        report.sniff.candidates = vec![SniffCandidate {
+            mime: "application/zip".into(),
+            score: 0.8,
+            sources: vec![sniff::INFER_BACKEND.into()],
+        }];
+        config.defaults.sniff_confidence_action = Some("deny".into());
+        let outcome = PolicyEngine::new(config).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert_eq!(
+            outcome.rules_triggered,
+            vec!["sniff:ambiguous:image/jpeg|application/zip"]
+        );
+    }
+
+    #[test]
+    fn severity_overrides_remap_validator_findings_by_code() {
+        let config = PolicyConfig::from_yaml_str(
+            "severity_overrides:\n  \"pdf:javascript\": warn\n  \"archive:*\": deny\n  \"svg:*\": allow\n",
+        )
+        .expect("policy should parse");
+        let engine = PolicyEngine::new(config);
+        let report = sample_report("application/pdf", 1024);
+        let javascript = ValidatorOutcome::deny("pdf", "JavaScript detectado em PDF")
+            .with_details(json!({ "codes": ["javascript"] }));
+        let outcome = engine.decide(&report, &[javascript], None);
+        assert_eq!(outcome.decision, Decision::Warn);
+        assert_eq!(outcome.rules_triggered, ["validator:pdf:warn:remapped"]);
+
+        // Outro código do mesmo achado mantém o DENY.
+        let mixed = ValidatorOutcome::deny("pdf", "JavaScript e EmbeddedFiles")
+            .with_details(json!({ "codes": ["javascript", "embedded_files"] }));
+        let outcome = engine.decide(&report, &[mixed], None);
+        assert_eq!(outcome.rules_triggered, ["validator:pdf:deny"]);
+
+        let archive = ValidatorOutcome::warn("archive", "entrada suspeita");
+        let svg = ValidatorOutcome::deny("svg", "<script> encontrado");
+        let outcome = engine.decide(&report, &[archive, svg], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert_eq!(outcome.rules_triggered, ["validator:archive:deny:remapped"]);
+
+        let invalid = "severity_overrides:\n  javascript: warn\n";
+        assert!(PolicyConfig::from_yaml_str(invalid).is_err());
+    }
+
+    #[test]
+    fn control_characters_in_file_name_are_flagged() {
+        let sniff = SniffReport::new("text/plain".into(), None, None);
+        let report = FileReport::new(
+            Path::new("nota\n{\"decision\":\"ALLOW\"}\u{1b}[2J.txt"),
+            10,
+            "deadbeef".into(),
+            sniff,
+        );
+        assert!(report.file_name_escaped);
+
+        let mut config = PolicyConfig::default();
+        let outcome = PolicyEngine::new(config.clone()).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Warn);
+        assert_eq!(outcome.rules_triggered, vec!["file:unsafe_name"]);
+
+        config.defaults.unsafe_file_names = Some("deny".into());
+        let outcome = PolicyEngine::new(config).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+    }
+
+    #[test]
+    fn partially_read_files_are_denied_by_default() {
+        let mut report = sample_report("application/octet-stream", 1 << 40);
+        report.partially_read = true;
+        let mut config = PolicyConfig::default();
+        let outcome = PolicyEngine::new(config.clone()).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert_eq!(outcome.rules_triggered, vec!["size:partially_read"]);
+
+        config.defaults.partial_read_action = Some("warn".into());
+        let outcome = PolicyEngine::new(config).decide(&report, &[], None);
+        assert_eq!(outcome.decision, Decision::Warn);
+    }
+
+    #[test]
+    fn session_rules_cover_count_total_size_and_mixed_types() {
+        let config = PolicyConfig::from_yaml_str(
+            "session:\n  max_total_size_mb: 1\n  max_files: 2\n  forbid_mixed_executable_document: true\n",
+        )
+        .unwrap();
+        let engine = PolicyEngine::new(config);
+        let pdf = sample_report("application/pdf", 700 * 1024);
+        let mut exe = sample_report("application/x-msdownload", 400 * 1024);
+        exe.validators.push(ValidatorEntry {
+            name: "binary".into(),
+            status: "warn".into(),
+            details: serde_json::Value::Null,
+            version: None,
+        });
+        let missing = FileReport::error(Path::new("missing.bin"), "falha");
+
+        let outcome = engine.decide_session(&[&pdf, &exe, &missing]);
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert_eq!(
+            outcome.rules_triggered,
+            vec![
+                "session:max_files:3>2".to_string(),
+                format!("session:max_total_size:{}>{}", 1100 * 1024, 1024 * 1024),
+                "session:mixed_executable_document".to_string(),
+            ]
+        );
+        assert!(engine.decide_session(&[&pdf]).rules_triggered.is_empty());
+
+        let mut file_outcome = DecisionOutcome::new();
+        file_outcome.record(Decision::Warn, "entropy:exceeds_threshold");
+        file_outcome.merge(&outcome);
+        assert_eq!(file_outcome.decision, Decision::Deny);
+        assert_eq!(
+            file_outcome.primary_rule.as_deref(),
+            Some("session:max_files:3>2")
+        );
+    }
+
+    #[test]
+    fn overrides_apply_by_mime_and_time_window() {
+        let config = PolicyConfig::from_yaml_str(
+            r#"
+pdf:
+  max_pages: 50
+overrides:
+  - if_mime: ["application/pdf"]
+    active_when:
+      days: [mon-fri]
+      hours: ["19:00-07:00"]
+      utc_offset: "-03:00"
+    set:
+      pdf.max_pages: 5
+      defaults.deny_types: ["application/zip"]
+"#,
+        )
+        .expect("policy");
+        let engine = PolicyEngine::new(config);
+        let pdf = sample_report("application/pdf", 10);
+        // Sexta 23:30 em -03:00 (sábado 02:30 UTC) está na janela; o dia é o local.
+        let at = |timestamp| OffsetDateTime::from_unix_timestamp(timestamp).unwrap();
+        let friday_night = at(1_792_204_200); // 2026-10-17 02:30 UTC
+        let tuesday_noon = at(1_791_903_600); // 2026-10-13 15:00 UTC
+        assert_eq!(engine.resolve_at(&pdf, friday_night).pdf.max_pages, Some(5));
+        assert_eq!(
+            engine.resolve_at(&pdf, tuesday_noon).pdf.max_pages,
+            Some(50)
+        );
+
+        let zip = sample_report("application/zip", 10);
+        assert_eq!(
+            engine.resolve_at(&zip, friday_night).pdf.max_pages,
+            Some(50)
+        );
+        let night = engine.resolve_at(&pdf, friday_night);
+        let outcome = engine.decide(&sample_report("application/zip", 10), &[], Some(&night));
+        assert_eq!(outcome.decision, Decision::Deny);
+
+        let invalid = "overrides:\n  - set:\n      pdf.max_pagez: 5\n";
+        assert!(PolicyConfig::from_yaml_str(invalid).is_err());
+        let invalid = "overrides:\n  - active_when:\n      hours: [\"25-26\"]\n";
+        assert!(PolicyConfig::from_yaml_str(invalid).is_err());
+    }
+
+    #[test]
+    fn repeat_offenders_get_history_set() {
+        let config = PolicyConfig::from_yaml_str(
+            "pdf:\n  max_pages: 50\nhistory:\n  set:\n    pdf.max_pages: 5\n",
+        )
+        .expect("policy");
+        let engine = PolicyEngine::new(config);
+        let pdf = sample_report("application/pdf", 10);
+        assert_eq!(engine.resolve(&pdf).pdf.max_pages, Some(50));
+
+        let history = engine.history().expect("histórico em memória");
+        history.record(&pdf.sha256, Decision::Allow);
+        assert!(!engine.is_repeat_offender(&pdf.sha256));
+        history.record(&pdf.sha256, Decision::Warn);
+        assert_eq!(engine.resolve(&pdf).pdf.max_pages, Some(5));
+
+        assert!(PolicyEngine::new(PolicyConfig::default())
+            .history()
+            .is_none());
+        let invalid = "history:\n  set:\n    pdf.max_pagez: 5\n";
+        assert!(PolicyConfig::from_yaml_str(invalid).is_err());
+    }
+
+    fn sample_report(mime: &str, size: u64) -> FileReport {
+        let sniff = SniffReport::new(mime.to_string(), None, None);
+        FileReport::new(Path::new("sample.bin"), size, "deadbeef".into(), sniff)
+    }
+}
@@ -0,0 +1,182 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Pré-checagem de upload: avalia só as regras de metadados (MIME declarado,
+//! tamanho e nome) para que o cliente recuse envios impossíveis antes de
+//! transferir os bytes.
+//!
+//! O veredito não substitui o `scan`: o conteúdo ainda passa pelos validadores
+//! listados em `validators` quando for enviado.
+
+use crate::limits::Deadline;
+use crate::policy::{DecisionOutcome, PolicyEngine};
+use crate::report::{FileReport, PolicyDecision, SniffReport};
+use crate::sniff::ClaimedMimeMap;
+use crate::validators::{validate_filename, ValidationContext};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Nome usado quando o cliente não informa o nome do arquivo.
+const DEFAULT_NAME: &str = "upload.bin";
+
+/// Metadados conhecidos antes do envio.
+#[derive(Debug, Clone, Default)]
+pub struct PrecheckRequest {
+    pub mime: String,
+    pub size_bytes: Option<u64>,
+    /// Nome do arquivo; habilita as regras de nome e de MIME divergente da extensão.
+    pub name: Option<String>,
+}
+
+/// Resultado da pré-checagem.
+#[derive(Debug, Clone, Serialize)]
+pub struct PrecheckReport {
+    pub mime: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    /// Decisão considerando só os metadados (`ALLOW` ainda depende do conteúdo).
+    pub policy: PolicyDecision,
+    /// Tamanho máximo aceito para o MIME, já com os overrides ativos.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size_bytes: Option<u64>,
+    /// Validadores de conteúdo que rodarão no envio.
+    pub validators: Vec<&'static str>,
+    /// Seções da política aplicadas por esses validadores (ex.: `image.max_dimensions`).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub constraints: BTreeMap<String, Value>,
+}
+
+/// Avalia as regras de metadados de `policy_engine` para `request`; sem política,
+/// tudo é permitido.
+pub fn precheck(
+    policy_engine: Option<&PolicyEngine>,
+    request: &PrecheckRequest,
+    claimed_mimes: &ClaimedMimeMap,
+) -> PrecheckReport {
+    let mime = request.mime.trim().to_ascii_lowercase();
+    let name = Path::new(request.name.as_deref().unwrap_or(DEFAULT_NAME));
+    let ext = request.name.as_ref().and_then(|_| {
+        name.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| format!(".{}", ext.to_ascii_lowercase()))
+    });
+    let mut sniff = SniffReport::new(mime.clone(), None, ext);
+    sniff.mime_claimed = sniff
+        .ext
+        .as_deref()
+        .and_then(|ext| claimed_mimes.claimed_for(ext))
+        .map(str::to_string);
+    let report = FileReport::new(name, request.size_bytes.unwrap_or(0), String::new(), sniff);
+
+    let mut precheck = PrecheckReport {
+        mime,
+        size_bytes: request.size_bytes,
+        policy: DecisionOutcome::new().into(),
+        max_size_bytes: None,
+        validators: Vec::new(),
+        constraints: BTreeMap::new(),
+    };
+    let Some(engine) = policy_engine else {
+        return precheck;
+    };
+
+    let resolved = engine.resolve(&report);
+    let file_name = request.name.as_deref();
+    let name_check: Vec<_> = file_name
+        .filter(|_| resolved.validators.is_enabled("filename"))
+        .map(|file_name| validate_filename(file_name, Some(&resolved)))
+        .into_iter()
+        .collect();
+    precheck.policy = engine.decide(&report, &name_check, Some(&resolved)).into();
+    precheck.max_size_bytes = resolved
+        .defaults
+        .max_size_mb
+        .map(|mb| mb as u64 * 1024 * 1024);
+    // Sem bytes, só os validadores escolhidos pelo MIME se aplicam.
+    precheck.validators = engine.validators().selected(&ValidationContext {
+        mime: &precheck.mime,
+        data: &[],
+        policy: Some(&resolved),
+        deadline: &Deadline::none(),
+        file_name,
+    });
+    if let Ok(Value::Object(sections)) = serde_json::to_value(&resolved) {
+        precheck.constraints = sections
+            .into_iter()
+            .filter(|(section, _)| precheck.validators.contains(&section.as_str()))
+            .collect();
+    }
+    precheck
+}
+
+/// Interpreta tamanhos como `12MB`, `512k` ou `1048576`; múltiplos são binários,
+/// como `defaults.max_size_mb`.
+pub fn parse_size(raw: &str) -> Result<u64> {
+    let raw = raw.trim();
+    let split = raw
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(raw.len());
+    let (digits, unit) = raw.split_at(split);
+    let value: u64 = digits
+        .parse()
+        .with_context(|| format!("tamanho inválido: {raw}"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        other => bail!("unidade de tamanho desconhecida: {other}"),
+    };
+    value
+        .checked_mul(multiplier)
+        .with_context(|| format!("tamanho fora do limite: {raw}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PolicyConfig;
+
+    #[test]
+    fn precheck_reports_metadata_verdict_and_constraints() {
+        let config = PolicyConfig::from_yaml_str(
+            "defaults:\n  max_size_mb: 10\n  allow_types: [\"image/*\", \"application/pdf\"]\n\
+             image:\n  max_dimensions: [4000, 4000]\n\
+             overrides:\n  - if_mime: [application/pdf]\n    set:\n      defaults.max_size_mb: 50\n",
+        )
+        .expect("policy");
+        let engine = PolicyEngine::new(config);
+        let claimed = ClaimedMimeMap::default();
+        let check = |mime: &str, size: &str, name: Option<&str>| {
+            let request = PrecheckRequest {
+                mime: mime.into(),
+                size_bytes: Some(parse_size(size).expect("size")),
+                name: name.map(str::to_string),
+            };
+            precheck(Some(&engine), &request, &claimed)
+        };
+
+        let png = check("image/png", "12MB", None);
+        assert_eq!(png.policy.decision, "DENY");
+        assert_eq!(png.max_size_bytes, Some(10 << 20));
+        assert_eq!(png.validators, ["image"]);
+        assert_eq!(png.constraints["image"]["max_dimensions"][0], 4000);
+
+        let pdf = check("application/pdf", "12MB", Some("contrato.pdf"));
+        assert_eq!(pdf.policy.decision, "ALLOW");
+        assert_eq!(pdf.max_size_bytes, Some(50 << 20));
+
+        let exe = check("application/pdf", "1k", Some("fatura.pdf.exe"));
+        assert_eq!(exe.policy.decision, "DENY");
+        assert_eq!(exe.validators, ["pdf", "filename"]);
+
+        let zip = check("application/zip", "1k", None);
+        assert!(zip.policy.rules_triggered[0].starts_with("mime:not_allowed"));
+        assert!(parse_size("3 parsecs").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Prévias pequenas de arquivos bloqueados, para revisão sem abrir o original.
+//!
+//! A prévia é sempre um PNG recodificado de no máximo [`PREVIEW_MAX_SIDE`] pixels
+//! no maior lado. A decodificação roda com limites do `image` (dimensões e
+//! alocação) e isolada de pânicos: conteúdo hostil resulta em erro, nunca em
+//! consumo sem limite. PDFs não são renderizados; a prévia usa a primeira imagem
+//! JPEG (`/DCTDecode`) do documento, que em digitalizações é a própria página.
+
+use crate::validators::image::DEFAULT_MAX_PIXELS;
+use image::codecs::png::PngEncoder;
+use image::{DynamicImage, ImageFormat, ImageReader, Limits};
+use std::io::Cursor;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use thiserror::Error;
+
+/// Maior lado da prévia, em pixels.
+pub const PREVIEW_MAX_SIDE: u32 = 256;
+/// Largura ou altura máxima aceita na imagem de origem.
+const MAX_SOURCE_SIDE: u32 = 16_384;
+/// Memória máxima alocada pelo decodificador.
+const MAX_DECODE_ALLOC: u64 = 256 * 1024 * 1024;
+
+/// PNG da prévia e a origem usada (`image` ou `pdf:dctdecode`).
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub source: &'static str,
+}
+
+#[derive(Debug, Error)]
+pub enum PreviewError {
+    #[error("formato sem prévia disponível: {0}")]
+    Unsupported(String),
+    #[error("conteúdo inválido: {0}")]
+    Invalid(String),
+}
+
+/// Gera a prévia de `data` conforme o MIME real detectado.
+pub fn render_preview(mime: &str, data: &[u8]) -> Result<Preview, PreviewError> {
+    let (image, source) = match mime {
+        "image/png" | "image/jpeg" | "image/gif" | "image/webp" | "image/bmp" => (data, "image"),
+        "application/pdf" => match first_jpeg_stream(data) {
+            Some(stream) => (stream, "pdf:dctdecode"),
+            None => {
+                return Err(PreviewError::Unsupported(
+                    "PDF sem imagem JPEG para a prévia".to_string(),
+                ))
+            }
+        },
+        other => return Err(PreviewError::Unsupported(other.to_string())),
+    };
+    catch_unwind(AssertUnwindSafe(|| thumbnail(image, source))).unwrap_or_else(|_| {
+        Err(PreviewError::Invalid(
+            "decodificador interrompido com pânico".to_string(),
+        ))
+    })
+}
+
+fn thumbnail(data: &[u8], source: &'static str) -> Result<Preview, PreviewError> {
+    let invalid = |err: image::ImageError| PreviewError::Invalid(err.to_string());
+    let format = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|err| PreviewError::Invalid(err.to_string()))?
+        .format()
+        .ok_or_else(|| PreviewError::Unsupported("formato de imagem desconhecido".to_string()))?;
+    if !matches!(
+        format,
+        ImageFormat::Png
+            | ImageFormat::Jpeg
+            | ImageFormat::Gif
+            | ImageFormat::WebP
+            | ImageFormat::Bmp
+    ) {
+        return Err(PreviewError::Unsupported(format!("{format:?}")));
+    }
+    let (width, height) = bounded_reader(data, format)
+        .into_dimensions()
+        .map_err(invalid)?;
+    let pixels = u64::from(width) * u64::from(height);
+    if pixels > DEFAULT_MAX_PIXELS {
+        return Err(PreviewError::Invalid(format!(
+            "imagem excede o orçamento de pixels: {pixels} > {DEFAULT_MAX_PIXELS}"
+        )));
+    }
+    // Só o primeiro quadro de GIFs; a recodificação em RGBA descarta metadados e perfis.
+    let image = bounded_reader(data, format).decode().map_err(invalid)?;
+    let small = DynamicImage::ImageRgba8(
+        image
+            .thumbnail(PREVIEW_MAX_SIDE, PREVIEW_MAX_SIDE)
+            .to_rgba8(),
+    );
+    let mut output = Vec::new();
+    small
+        .write_with_encoder(PngEncoder::new(&mut output))
+        .map_err(invalid)?;
+    Ok(Preview {
+        data: output,
+        width: small.width(),
+        height: small.height(),
+        source,
+    })
+}
+
+fn bounded_reader(data: &[u8], format: ImageFormat) -> ImageReader<Cursor<&[u8]>> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_SIDE);
+    limits.max_image_height = Some(MAX_SOURCE_SIDE);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    reader
+}
+
+/// Dados do primeiro stream `/DCTDecode` do PDF (o filtro precisa estar no
+/// mesmo objeto que o stream).
+fn first_jpeg_stream(data: &[u8]) -> Option<&[u8]> {
+    let mut cursor = 0;
+    while let Some(found) = find(&data[cursor..], b"/DCTDecode") {
+        let filter = cursor + found;
+        cursor = filter + b"/DCTDecode".len();
+        let keyword = cursor + find(&data[cursor..], b"stream")?;
+        if find(&data[cursor..keyword], b"endobj").is_some() {
+            continue;
+        }
+        let mut start = keyword + b"stream".len();
+        if data[start..].starts_with(b"\r\n") {
+            start += 2;
+        } else if data[start..].starts_with(b"\n") {
+            start += 1;
+        }
+        let end = find(&data[start..], b"endstream").map_or(data.len(), |len| start + len);
+        return Some(&data[start..end]);
+    }
+    None
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::jpeg::JpegEncoder;
+    use image::{ImageBuffer, Rgb};
+
+    fn jpeg(width: u32, height: u32) -> Vec<u8> {
+        let image = ImageBuffer::from_fn(width, height, |x, _| Rgb([x as u8, 80, 160]));
+        let mut output = Vec::new();
+        DynamicImage::ImageRgb8(image)
+            .write_with_encoder(JpegEncoder::new(&mut output))
+            .expect("jpeg");
+        output
+    }
+
+    #[test]
+    fn previews_are_bounded_png_from_images_and_pdf_jpegs() {
+        let preview = render_preview("image/jpeg", &jpeg(1024, 512)).expect("prévia");
+        assert_eq!((preview.width, preview.height), (256, 128));
+        assert_eq!(preview.source, "image");
+        assert!(preview.data.starts_with(b"\x89PNG"));
+
+        let mut pdf =
+            b"%PDF-1.4\n1 0 obj\n<< /Type /XObject /Subtype /Image /Filter /DCTDecode >>\nstream\n"
+                .to_vec();
+        pdf.extend(jpeg(64, 300));
+        pdf.extend(b"\nendstream\nendobj\n%%EOF\n");
+        let preview = render_preview("application/pdf", &pdf).expect("prévia do PDF");
+        assert_eq!(preview.source, "pdf:dctdecode");
+        assert_eq!(preview.height, PREVIEW_MAX_SIDE);
+
+        assert!(matches!(
+            render_preview("application/pdf", b"%PDF-1.4\n%%EOF\n"),
+            Err(PreviewError::Unsupported(_))
+        ));
+        assert!(matches!(
+            render_preview("image/svg+xml", b"<svg/>"),
+            Err(PreviewError::Unsupported(_))
+        ));
+        assert!(matches!(
+            render_preview("image/png", b"\x89PNG\r\n\x1a\nlixo"),
+            Err(PreviewError::Invalid(_))
+        ));
+    }
+}
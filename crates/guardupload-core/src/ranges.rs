@@ -0,0 +1,316 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Faixas válidas dos campos numéricos da política, conferidas no carregamento.
+//!
+//! Valores sem sentido, que fariam a regra negar tudo ou nunca disparar
+//! (`zip_max_ratio: 0`, `entropy_threshold: 9`), são rejeitados com erro. Tetos
+//! operacionais (`max_size_mb` acima de 10 GiB, timeouts acima de 10 minutos) são
+//! reduzidos ao limite com um aviso em [`PolicyConfig::warnings`]. Os `set` de
+//! `overrides` e `history` passam pelas mesmas faixas. Limites por tipo iguais a
+//! zero (`image.max_frames: 0`) continuam aceitos: recusam todo arquivo do tipo
+//! de propósito.
+
+use crate::config::{
+    ArchivePolicySection, DefaultsSection, EncodingPolicySection, FilenamePolicySection,
+    ImagePolicySection, PolicyConfig,
+};
+use crate::policy::ResolvedPolicy;
+use anyhow::{bail, Result};
+use std::fmt::Display;
+
+/// Teto de `max_size_mb` e `encoding.max_decoded_mb` (10 GiB).
+pub const MAX_SIZE_MB: u32 = 10 * 1024;
+/// Teto de `entropy_window_bytes`.
+pub const MAX_ENTROPY_WINDOW: usize = 16 * 1024 * 1024;
+/// Teto de `archive.zip_max_depth`; além disso a recursão não traz ganho real.
+pub const MAX_ZIP_DEPTH: u32 = 32;
+/// Teto de `filename.max_length` em bytes.
+pub const MAX_FILENAME_LENGTH: u32 = 4096;
+/// Teto dos `timeout_ms` de integrações (10 minutos).
+pub const MAX_TIMEOUT_MS: u64 = 10 * 60 * 1000;
+
+/// Confere campos e acumula os avisos de ajuste.
+struct RangeCheck<'a> {
+    warnings: &'a mut Vec<String>,
+}
+
+impl RangeCheck<'_> {
+    /// Rejeita valores abaixo de `min`.
+    fn at_least<T: PartialOrd + Copy + Display>(
+        &mut self,
+        path: &str,
+        value: Option<T>,
+        min: T,
+        reason: &str,
+    ) -> Result<()> {
+        match value {
+            Some(value) if !(min..).contains(&value) => {
+                bail!("{path} = {value} inválido: mínimo {min} ({reason})")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Rejeita valores fora de `min..=max` (inclusive NaN).
+    fn within<T: PartialOrd + Copy + Display>(
+        &mut self,
+        path: &str,
+        value: Option<T>,
+        (min, max): (T, T),
+        reason: &str,
+    ) -> Result<()> {
+        match value {
+            Some(value) if !(min..=max).contains(&value) => {
+                bail!("{path} = {value} inválido: deve ficar entre {min} e {max} ({reason})")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reduz valores acima de `max` ao teto, com aviso.
+    fn at_most<T: PartialOrd + Copy + Display>(
+        &mut self,
+        path: &str,
+        value: &mut Option<T>,
+        max: T,
+    ) {
+        if let Some(current) = *value {
+            if current > max {
+                self.warnings.push(format!(
+                    "{path} = {current} excede o teto {max}; usando {max}"
+                ));
+                *value = Some(max);
+            }
+        }
+    }
+}
+
+/// Confere as seções que overrides podem alterar; devolve os avisos de ajuste.
+pub fn check_policy(policy: &mut ResolvedPolicy) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+    let mut check = RangeCheck {
+        warnings: &mut warnings,
+    };
+    check_defaults(&mut check, &mut policy.defaults)?;
+    check_image(&mut check, &policy.image)?;
+    check_archive(&mut check, &mut policy.archive)?;
+    check_encoding(&mut check, &mut policy.encoding)?;
+    check_filename(&mut check, &mut policy.filename)?;
+    Ok(warnings)
+}
+
+/// Confere a política inteira (seções, campos globais e `set` de overrides e
+/// histórico), ajustando os tetos; devolve os avisos.
+pub fn check_config(config: &mut PolicyConfig) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+    let mut check = RangeCheck {
+        warnings: &mut warnings,
+    };
+    check_defaults(&mut check, &mut config.defaults)?;
+    check_image(&mut check, &config.image)?;
+    check_archive(&mut check, &mut config.archive)?;
+    check_encoding(&mut check, &mut config.encoding)?;
+    check_filename(&mut check, &mut config.filename)?;
+
+    check.at_least(
+        "history.min_warnings",
+        config.history.min_warnings,
+        1,
+        "0 trataria todo hash como reincidente",
+    )?;
+    check_timeout(
+        &mut check,
+        "clamav.timeout_ms",
+        &mut config.clamav.timeout_ms,
+    )?;
+    check_timeout(
+        &mut check,
+        "notifications.timeout_ms",
+        &mut config.notifications.timeout_ms,
+    )?;
+    for (index, plugin) in config.plugins.iter_mut().enumerate() {
+        check_timeout(
+            &mut check,
+            &format!("plugins[{index}].timeout_ms"),
+            &mut plugin.timeout_ms,
+        )?;
+    }
+
+    let base = ResolvedPolicy::from_config(config);
+    let sets = config
+        .overrides
+        .iter()
+        .enumerate()
+        .map(|(index, rule)| (format!("overrides[{index}].set"), &rule.set))
+        .chain([("history.set".to_string(), &config.history.set)]);
+    for (origin, set) in sets {
+        if set.is_empty() {
+            continue;
+        }
+        let mut resolved = base.clone();
+        resolved.apply_set_unchecked(set)?;
+        let adjusted =
+            check_policy(&mut resolved).map_err(|err| err.context(format!("{origin} inválido")))?;
+        warnings.extend(
+            adjusted
+                .into_iter()
+                .map(|warning| format!("{origin}: {warning}")),
+        );
+    }
+    Ok(warnings)
+}
+
+fn check_defaults(check: &mut RangeCheck<'_>, defaults: &mut DefaultsSection) -> Result<()> {
+    check.at_least(
+        "defaults.max_size_mb",
+        defaults.max_size_mb,
+        1,
+        "0 negaria todo arquivo não vazio",
+    )?;
+    check.at_most(
+        "defaults.max_size_mb",
+        &mut defaults.max_size_mb,
+        MAX_SIZE_MB,
+    );
+    check.within(
+        "defaults.entropy_threshold",
+        defaults.entropy_threshold,
+        (0.0, 8.0),
+        "a entropia vai de 0 a 8 bits por byte",
+    )?;
+    check.at_least(
+        "defaults.entropy_window_bytes",
+        defaults.entropy_window_bytes,
+        1,
+        "a janela não pode ser vazia",
+    )?;
+    check.at_most(
+        "defaults.entropy_window_bytes",
+        &mut defaults.entropy_window_bytes,
+        MAX_ENTROPY_WINDOW,
+    );
+    check.within(
+        "defaults.min_sniff_confidence",
+        defaults.min_sniff_confidence,
+        (0.0, 1.0),
+        "o score do sniff vai de 0 a 1",
+    )
+}
+
+fn check_image(check: &mut RangeCheck<'_>, image: &ImagePolicySection) -> Result<()> {
+    check.within(
+        "image.aspect_ratio_tolerance",
+        image.aspect_ratio_tolerance,
+        (0.0, 1.0),
+        "tolerância relativa; 1 já aceita qualquer proporção",
+    )?;
+    if let (Some(min), Some(max)) = (image.min_dimensions, image.max_dimensions) {
+        if min[0] > max[0] || min[1] > max[1] {
+            bail!(
+                "image.min_dimensions {min:?} inválido: excede image.max_dimensions {max:?} e nenhuma imagem seria aceita"
+            );
+        }
+    }
+    Ok(())
+}
+
+fn check_archive(check: &mut RangeCheck<'_>, archive: &mut ArchivePolicySection) -> Result<()> {
+    check.at_least(
+        "archive.zip_max_ratio",
+        archive.zip_max_ratio,
+        1,
+        "abaixo de 1 nega todo arquivo compactado; use 1 para o limite mais restrito",
+    )?;
+    check.at_most(
+        "archive.zip_max_depth",
+        &mut archive.zip_max_depth,
+        MAX_ZIP_DEPTH,
+    );
+    Ok(())
+}
+
+fn check_encoding(check: &mut RangeCheck<'_>, encoding: &mut EncodingPolicySection) -> Result<()> {
+    check.at_least(
+        "encoding.max_decoded_mb",
+        encoding.max_decoded_mb,
+        1,
+        "0 alertaria todo conteúdo decodificado",
+    )?;
+    check.at_most(
+        "encoding.max_decoded_mb",
+        &mut encoding.max_decoded_mb,
+        u64::from(MAX_SIZE_MB),
+    );
+    Ok(())
+}
+
+fn check_filename(check: &mut RangeCheck<'_>, filename: &mut FilenamePolicySection) -> Result<()> {
+    check.at_least(
+        "filename.max_length",
+        filename.max_length,
+        1,
+        "0 negaria todo nome",
+    )?;
+    check.at_most(
+        "filename.max_length",
+        &mut filename.max_length,
+        MAX_FILENAME_LENGTH,
+    );
+    Ok(())
+}
+
+fn check_timeout(
+    check: &mut RangeCheck<'_>,
+    path: &str,
+    timeout_ms: &mut Option<u64>,
+) -> Result<()> {
+    check.at_least(path, *timeout_ms, 1, "0 expiraria toda chamada")?;
+    check.at_most(path, timeout_ms, MAX_TIMEOUT_MS);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nonsensical_values_are_rejected_and_ceilings_clamped() {
+        let config = PolicyConfig::from_yaml_str(
+            "defaults:\n  max_size_mb: 20480\narchive:\n  zip_max_depth: 100\n\
+             clamav:\n  timeout_ms: 3600000\n\
+             overrides:\n  - if_mime: [application/zip]\n    set:\n      filename.max_length: 9000\n",
+        )
+        .expect("política com tetos ajustados");
+        assert_eq!(config.defaults.max_size_mb, Some(MAX_SIZE_MB));
+        assert_eq!(config.archive.zip_max_depth, Some(MAX_ZIP_DEPTH));
+        assert_eq!(config.clamav.timeout_ms, Some(MAX_TIMEOUT_MS));
+        assert_eq!(config.warnings.len(), 4);
+        assert!(config.warnings[3].starts_with("overrides[0].set: filename.max_length = 9000"));
+
+        let mut resolved = ResolvedPolicy::from_config(&config);
+        resolved
+            .apply_set(&config.overrides[0].set)
+            .expect("override");
+        assert_eq!(resolved.filename.max_length, Some(MAX_FILENAME_LENGTH));
+
+        PolicyConfig::from_yaml_str("image:\n  max_dimensions: [0, 0]\n  max_frames: 0\n")
+            .expect("zero recusa todas as imagens de propósito");
+
+        for invalid in [
+            "archive:\n  zip_max_ratio: 0\n",
+            "defaults:\n  max_size_mb: 0\n",
+            "defaults:\n  entropy_threshold: 9.5\n",
+            "defaults:\n  entropy_threshold: .nan\n",
+            "defaults:\n  min_sniff_confidence: -0.1\n",
+            "image:\n  min_dimensions: [800, 600]\n  max_dimensions: [640, 480]\n",
+            "plugins:\n  - name: x\n    command: [x]\n    timeout_ms: 0\n",
+            "overrides:\n  - set:\n      defaults.entropy_threshold: 12\n",
+        ] {
+            let err = PolicyConfig::from_yaml_str(invalid).expect_err(invalid);
+            assert!(format!("{err:#}").contains("inválido"), "{err:#}");
+        }
+    }
+}
@@ -0,0 +1,935 @@
+// GuardUpload
+// Criado em: 2025-11-01
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Estruturas dos relatórios JSON (por arquivo e agregados).
+
+use crate::analyzers::pii::PiiAnalysis;
+use crate::analyzers::secrets::SecretsAnalysis;
+use crate::analyzers::structure::StructureAnalysis;
+use crate::analyzers::EntropyAnalysis;
+use crate::config::RedactField;
+use crate::risk::RiskSummary;
+use crate::sniff::{self, SniffAttempt, SniffCandidate, SniffDatabaseInfo};
+use crate::transfer::TransferEncoding;
+use crate::validators::ValidatorOutcome;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+
+/// Relatório por arquivo conforme SPEC.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileReport {
+    pub version: String,
+    pub generated_at: String,
+    pub file: PathBuf,
+    /// `true` quando o nome continha caracteres de controle e foi escapado.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub file_name_escaped: bool,
+    /// Rótulo da raiz escaneada que contém o arquivo (`scan --label`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// Caminho relativo à raiz rotulada, com `/` como separador.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relative_path: Option<String>,
+    pub size_bytes: u64,
+    /// Submissão lógica à qual o arquivo pertence (`--session-id` ou campo `session`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// `true` quando o arquivo excedeu o limite de leitura: só o início e o fim
+    /// foram lidos, `sha256` fica vazio e `partial_sha256` cobre esses trechos.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub partially_read: bool,
+    pub sha256: String,
+    /// SHA-256 do início seguido do fim de um arquivo lido parcialmente; não
+    /// identifica o conteúdo e não é consultado em listas de hashes nem no histórico.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub partial_sha256: Option<String>,
+    /// Digests extras pedidos via `--hash` (algoritmo → hex).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub hashes: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_owner: Option<String>,
+    pub sniff: SniffReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entropy: Option<EntropyAnalysis>,
+    /// Bytes fora da estrutura do formato ou conteúdo poliglota; ausente quando limpo.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub structure: Option<StructureAnalysis>,
+    /// Credenciais encontradas em conteúdo textual (`secrets.enabled`).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub secrets: Option<SecretsAnalysis>,
+    /// Dados pessoais encontrados em texto ou PDF (`pii.enabled`).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub pii: Option<PiiAnalysis>,
+    pub validators: Vec<ValidatorEntry>,
+    pub policy: PolicyDecision,
+    /// Decisão anterior do mesmo caminho quando ela mudou (modo `watch`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_decision: Option<String>,
+    pub timings_ms: TimingBreakdown,
+    #[serde(default, skip_serializing_if = "ByteUsage::is_empty")]
+    pub bytes: ByteUsage,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub notes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Codificação de transferência removida antes de analisar `inner`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub transfer_encoding: Option<TransferEncoding>,
+    /// Relatório do conteúdo extraído de um envelope assinado (PKCS#7/S-MIME) ou
+    /// decodificado de base64/quoted-printable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inner: Option<Box<FileReport>>,
+    /// Prioridade para filas de moderação, preenchida após a decisão final.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub risk: Option<RiskSummary>,
+    /// Prévia PNG gravada por `scan --previews-dir` para arquivos DENY.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub preview: Option<String>,
+}
+
+impl FileReport {
+    pub fn new(file: &Path, size_bytes: u64, sha256: String, sniff: SniffReport) -> Self {
+        let (file, file_name_escaped) = match escape_control_chars(&file.to_string_lossy()) {
+            Cow::Owned(escaped) => (PathBuf::from(escaped), true),
+            Cow::Borrowed(_) => (file.to_path_buf(), false),
+        };
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: OffsetDateTime::now_utc()
+                .format(&time::format_description::well_known::Rfc3339)
+                .unwrap_or_default(),
+            file,
+            file_name_escaped,
+            root: None,
+            relative_path: None,
+            size_bytes,
+            session: None,
+            partially_read: false,
+            sha256,
+            partial_sha256: None,
+            hashes: BTreeMap::new(),
+            file_owner: None,
+            sniff,
+            entropy: None,
+            structure: None,
+            secrets: None,
+            pii: None,
+            validators: Vec::new(),
+            policy: PolicyDecision::default(),
+            previous_decision: None,
+            timings_ms: TimingBreakdown::default(),
+            bytes: ByteUsage::default(),
+            notes: Vec::new(),
+            error: None,
+            transfer_encoding: None,
+            inner: None,
+            risk: None,
+            preview: None,
+        }
+    }
+
+    /// Registro para arquivos que não puderam ser processados (`decision: "ERROR"`).
+    pub fn error(file: &Path, message: impl Into<String>) -> Self {
+        let sniff = SniffReport::new("application/octet-stream".to_string(), None, None);
+        let mut report = Self::new(file, 0, String::new(), sniff);
+        report.policy = PolicyDecision {
+            decision: "ERROR".to_string(),
+            rules_triggered: vec!["error:processing".to_string()],
+            primary_rule: Some("error:processing".to_string()),
+            category: Some("error".to_string()),
+            rule_codes: vec![crate::rules::code_for("error:processing")],
+            primary_code: Some(crate::rules::code_for("error:processing")),
+        };
+        report.error = Some(escape_control_chars(&message.into()).into_owned());
+        report
+    }
+
+    /// Remove ou substitui por hash os campos sensíveis solicitados pela política.
+    pub fn redact(&mut self, fields: &[RedactField]) {
+        for field in fields {
+            match field {
+                RedactField::FileOwner => {
+                    if let Some(owner) = self.file_owner.as_mut() {
+                        let digest = hex::encode(Sha256::digest(owner.as_bytes()));
+                        *owner = format!("sha256:{}", &digest[..16]);
+                    }
+                }
+                RedactField::ExifGps => self.remove_validator_detail("gps"),
+                RedactField::Preview => {
+                    self.preview = None;
+                    self.remove_validator_detail("preview");
+                }
+            }
+        }
+        if let Some(inner) = self.inner.as_mut() {
+            inner.redact(fields);
+        }
+    }
+
+    fn remove_validator_detail(&mut self, key: &str) {
+        for entry in &mut self.validators {
+            if let Some(details) = entry.details.as_object_mut() {
+                details.remove(key);
+            }
+        }
+    }
+}
+
+/// Escapa caracteres de controle (CR/LF, ESC/ANSI, C1) e marcas bidirecionais
+/// para que nomes de arquivo não injetem linhas em logs nem sequências no terminal.
+pub fn escape_control_chars(value: &str) -> Cow<'_, str> {
+    let is_unsafe = |ch: char| {
+        ch.is_control()
+            || matches!(ch, '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+    };
+    if !value.chars().any(is_unsafe) {
+        return Cow::Borrowed(value);
+    }
+    let mut escaped = String::with_capacity(value.len() + 8);
+    for ch in value.chars() {
+        match ch {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ch if (ch as u32) < 0x80 && is_unsafe(ch) => {
+                let _ = write!(escaped, "\\x{:02x}", ch as u32);
+            }
+            ch if is_unsafe(ch) => {
+                let _ = write!(escaped, "\\u{{{:04x}}}", ch as u32);
+            }
+            ch => escaped.push(ch),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Registro compacto (somente veredito) para integrações sensíveis a banda.
+///
+/// O schema é estável: apenas `file`, `decision` e `rules`.
+#[derive(Debug, Clone, Serialize)]
+pub struct VerdictRecord {
+    pub file: PathBuf,
+    pub decision: String,
+    pub rules: Vec<String>,
+}
+
+impl From<&FileReport> for VerdictRecord {
+    fn from(report: &FileReport) -> Self {
+        Self {
+            file: report.file.clone(),
+            decision: report.policy.decision.clone(),
+            rules: report.policy.rules_triggered.clone(),
+        }
+    }
+}
+
+/// Resumo agregado conforme SPEC.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct SummaryReport {
+    pub scanned: u64,
+    pub allow: u64,
+    pub warn: u64,
+    pub deny: u64,
+    pub errors: u64,
+    /// Soma de `size_bytes` dos arquivos analisados.
+    pub bytes_scanned: u64,
+    /// Bytes lidos, processados por hash e descomprimidos no scan inteiro.
+    #[serde(skip_serializing_if = "ByteUsage::is_empty")]
+    pub bytes: ByteUsage,
+    /// Duração total do scan (ms).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<f32>,
+    /// Quantas vezes cada regra foi disparada.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rules: BTreeMap<String, u64>,
+    /// Decisões por MIME real detectado.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_mime: BTreeMap<String, DecisionCounts>,
+    /// Maiores arquivos (até [`SummaryReport::TOP_FILES`]).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub largest: Vec<FileStat>,
+    /// Arquivos mais lentos (até [`SummaryReport::TOP_FILES`]).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub slowest: Vec<FileStat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings_ms: Option<TimingSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourceUsage>,
+    /// Chamadas por integração de rede sob `rate_limits` (`webhook`, `clamav`).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rate_limits: BTreeMap<String, RateLimitStats>,
+    /// Comparação com o resumo de uma execução anterior (`--previous-summary`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trend: Option<SummaryTrend>,
+    #[serde(skip)]
+    timing_samples: TimingSamples,
+    #[serde(skip)]
+    file_stats: Vec<FileStat>,
+}
+
+/// Campos de um resumo anterior usados na comparação; os demais são ignorados.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct PreviousSummary {
+    pub scanned: u64,
+    pub deny: u64,
+    pub bytes_scanned: u64,
+    pub elapsed_ms: Option<f32>,
+    pub rules: BTreeMap<String, u64>,
+}
+
+/// Variação do scan atual em relação ao resumo anterior.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SummaryTrend {
+    pub previous_scanned: u64,
+    /// Fração de arquivos com DENY (0–1) no scan atual e no anterior.
+    pub deny_rate: f64,
+    pub previous_deny_rate: f64,
+    /// `deny_rate - previous_deny_rate`; positivo indica piora.
+    pub deny_rate_delta: f64,
+    /// Regras disparadas agora que não apareciam no resumo anterior.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub new_rules: Vec<String>,
+    /// Regras do resumo anterior que não dispararam agora.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resolved_rules: Vec<String>,
+    /// Bytes analisados por segundo (ausente sem `elapsed_ms`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throughput_bytes_per_sec: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_throughput_bytes_per_sec: Option<f64>,
+    /// Variação percentual do throughput; negativa indica scan mais lento.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throughput_change_pct: Option<f64>,
+}
+
+fn deny_rate(deny: u64, scanned: u64) -> f64 {
+    if scanned == 0 {
+        0.0
+    } else {
+        deny as f64 / scanned as f64
+    }
+}
+
+fn throughput(bytes: u64, elapsed_ms: Option<f32>) -> Option<f64> {
+    elapsed_ms
+        .filter(|elapsed| *elapsed > 0.0)
+        .map(|elapsed| bytes as f64 * 1000.0 / f64::from(elapsed))
+}
+
+/// Contagem de decisões de um grupo (ex.: um MIME).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct DecisionCounts {
+    pub allow: u64,
+    pub warn: u64,
+    pub deny: u64,
+    pub errors: u64,
+}
+
+impl DecisionCounts {
+    fn add(&mut self, decision: &str) {
+        match decision {
+            "ALLOW" => self.allow += 1,
+            "WARN" => self.warn += 1,
+            "DENY" => self.deny += 1,
+            "ERROR" => self.errors += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Arquivo listado em `largest`/`slowest`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileStat {
+    pub file: PathBuf,
+    pub size_bytes: u64,
+    pub total_ms: f32,
+    pub decision: String,
+}
+
+impl SummaryReport {
+    /// Quantidade de arquivos em `largest` e `slowest`.
+    pub const TOP_FILES: usize = 10;
+
+    pub fn update(&mut self, decision: &PolicyDecision) {
+        self.scanned += 1;
+        match decision.decision.as_str() {
+            "ALLOW" => self.allow += 1,
+            "WARN" => self.warn += 1,
+            "DENY" => self.deny += 1,
+            "ERROR" => self.errors += 1,
+            _ => {}
+        }
+        for rule in &decision.rules_triggered {
+            *self.rules.entry(rule.clone()).or_insert(0) += 1;
+        }
+    }
+
+    /// Acumula bytes, decisão por MIME e candidatos a `largest`/`slowest` de um arquivo.
+    ///
+    /// Deve ser chamado após [`SummaryReport::update`]; registros de erro entram só nas contagens.
+    pub fn record_file(&mut self, report: &FileReport) {
+        if report.error.is_some() {
+            return;
+        }
+        self.bytes_scanned += report.size_bytes;
+        self.bytes.add(&report.bytes);
+        self.by_mime
+            .entry(report.sniff.mime_real.clone())
+            .or_default()
+            .add(&report.policy.decision);
+        self.file_stats.push(FileStat {
+            file: report.file.clone(),
+            size_bytes: report.size_bytes,
+            total_ms: report.timings_ms.total,
+            decision: report.policy.decision.clone(),
+        });
+    }
+
+    /// Acumula as medições de tempo de um arquivo para os percentis agregados.
+    pub fn record_timings(&mut self, timings: &TimingBreakdown) {
+        let samples = &mut self.timing_samples;
+        samples.total.push(timings.total);
+        samples.read.extend(timings.read);
+        samples.hash.extend(timings.hash);
+        samples.sniff.extend(timings.sniff);
+        samples.validate.extend(timings.validate);
+    }
+
+    /// Compara com o resumo anterior; chamar depois de preencher `elapsed_ms`.
+    pub fn compare_with(&self, previous: &PreviousSummary) -> SummaryTrend {
+        let deny_rate_now = deny_rate(self.deny, self.scanned);
+        let previous_deny_rate = deny_rate(previous.deny, previous.scanned);
+        let throughput_now = throughput(self.bytes_scanned, self.elapsed_ms);
+        let previous_throughput = throughput(previous.bytes_scanned, previous.elapsed_ms);
+        SummaryTrend {
+            previous_scanned: previous.scanned,
+            deny_rate: deny_rate_now,
+            previous_deny_rate,
+            deny_rate_delta: deny_rate_now - previous_deny_rate,
+            new_rules: self
+                .rules
+                .keys()
+                .filter(|rule| !previous.rules.contains_key(*rule))
+                .cloned()
+                .collect(),
+            resolved_rules: previous
+                .rules
+                .keys()
+                .filter(|rule| !self.rules.contains_key(*rule))
+                .cloned()
+                .collect(),
+            throughput_bytes_per_sec: throughput_now,
+            previous_throughput_bytes_per_sec: previous_throughput,
+            throughput_change_pct: match (throughput_now, previous_throughput) {
+                (Some(now), Some(before)) if before > 0.0 => Some((now - before) / before * 100.0),
+                _ => None,
+            },
+        }
+    }
+
+    /// Calcula os percentis a partir das amostras acumuladas.
+    pub fn finalize(&mut self) {
+        let mut stats = std::mem::take(&mut self.file_stats);
+        stats.sort_by_key(|stat| std::cmp::Reverse(stat.size_bytes));
+        self.largest = stats.iter().take(Self::TOP_FILES).cloned().collect();
+        stats.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+        self.slowest = stats.into_iter().take(Self::TOP_FILES).collect();
+
+        let samples = &self.timing_samples;
+        if samples.total.is_empty() {
+            self.timings_ms = None;
+            return;
+        }
+        self.timings_ms = Some(TimingSummary {
+            total: PercentileStats::from_samples(&samples.total),
+            read: PercentileStats::from_samples(&samples.read),
+            hash: PercentileStats::from_samples(&samples.hash),
+            sniff: PercentileStats::from_samples(&samples.sniff),
+            validate: PercentileStats::from_samples(&samples.validate),
+        });
+    }
+}
+
+/// Metadados do scan para reprodução (versão da ferramenta e base de sniff).
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanMetadata {
+    pub version: String,
+    pub sniff: SniffDatabaseInfo,
+}
+
+impl ScanMetadata {
+    pub fn collect() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            sniff: sniff::database_info(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct TimingSamples {
+    total: Vec<f32>,
+    read: Vec<f32>,
+    hash: Vec<f32>,
+    sniff: Vec<f32>,
+    validate: Vec<f32>,
+}
+
+/// Percentis de tempo (ms) por etapa no resumo.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimingSummary {
+    pub total: Option<PercentileStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read: Option<PercentileStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<PercentileStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sniff: Option<PercentileStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validate: Option<PercentileStats>,
+}
+
+/// Estatísticas p50/p95/max (método nearest-rank).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PercentileStats {
+    pub p50: f32,
+    pub p95: f32,
+    pub max: f32,
+}
+
+impl PercentileStats {
+    pub fn from_samples(samples: &[f32]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let rank = |percentile: f32| {
+            let idx = ((percentile / 100.0) * sorted.len() as f32).ceil() as usize;
+            sorted[idx.clamp(1, sorted.len()) - 1]
+        };
+        Some(Self {
+            p50: rank(50.0),
+            p95: rank(95.0),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Uso de recursos do processo medido ao final do scan.
+///
+/// Campos ausentes indicam que a plataforma não expõe a medição.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResourceUsage {
+    pub wall_ms: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_user_ms: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_system_ms: Option<f32>,
+    /// Pico de memória residente (RSS).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_rss_bytes: Option<u64>,
+    /// Bytes lidos/escritos via chamadas de sistema (inclui cache de páginas).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub io_read_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub io_write_bytes: Option<u64>,
+}
+
+/// Contagens de um limitador de taxa: chamadas liberadas na hora, adiadas até
+/// haver ficha e descartadas (`on_limit: drop` ou `max_wait_ms` estourado).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RateLimitStats {
+    pub allowed: u64,
+    pub deferred: u64,
+    pub dropped: u64,
+}
+
+/// Estrutura do bloco `sniff` do relatório.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SniffReport {
+    pub magic: Option<String>,
+    pub mime_real: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_claimed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<String>,
+    /// Tentativas de sniff quando a primeira foi ambígua (janelas maiores e
+    /// assinaturas profundas).
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub escalation: Vec<SniffAttempt>,
+    /// Score do `mime_real` (0 a 1), somando base magic, `infer` e extensão.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub score: Option<f32>,
+    /// Outros MIMEs plausíveis em ordem de score (ex.: extensão de outra família).
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub candidates: Vec<SniffCandidate>,
+}
+
+impl SniffReport {
+    pub fn new(mime_real: String, magic: Option<String>, ext: Option<String>) -> Self {
+        Self {
+            magic,
+            mime_real,
+            mime_claimed: None,
+            ext,
+            escalation: Vec::new(),
+            score: None,
+            candidates: Vec::new(),
+        }
+    }
+}
+
+/// Entrada do array `validators`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorEntry {
+    pub name: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "serde_json::Value::is_null", default)]
+    pub details: serde_json::Value,
+    /// Versão do validador (`2.1.0`), para reproduzir o resultado.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub version: Option<String>,
+}
+
+impl From<&ValidatorOutcome> for ValidatorEntry {
+    fn from(outcome: &ValidatorOutcome) -> Self {
+        Self {
+            name: outcome.name.to_string(),
+            status: outcome.status.as_str().to_string(),
+            details: outcome.details.clone(),
+            version: outcome.version.map(|version| version.to_string()),
+        }
+    }
+}
+
+/// Decisão de política que será exibida no relatório.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyDecision {
+    pub decision: String,
+    #[serde(default)]
+    pub rules_triggered: Vec<String>,
+    /// Regra de maior severidade (a primeira, em caso de empate) que explica a decisão.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_rule: Option<String>,
+    /// Família da regra principal (`mime`, `validator`, `size`...).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Código estável de cada regra de `rules_triggered`, na mesma ordem.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rule_codes: Vec<String>,
+    /// Código da regra principal (`guardupload explain <código>`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_code: Option<String>,
+}
+
+impl Default for PolicyDecision {
+    fn default() -> Self {
+        Self {
+            decision: "ALLOW".to_string(),
+            rules_triggered: Vec::new(),
+            primary_rule: None,
+            category: None,
+            rule_codes: Vec::new(),
+            primary_code: None,
+        }
+    }
+}
+
+/// Família de uma regra: o prefixo antes do primeiro `:` (`validator:pdf:deny` → `validator`).
+pub fn rule_category(rule: &str) -> &str {
+    rule.split(':').next().unwrap_or(rule)
+}
+
+/// Bytes processados por etapa, para planejamento de capacidade e detecção de
+/// anomalias (ex.: volume descomprimido muito acima do habitual).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteUsage {
+    /// Bytes de conteúdo lidos e analisados.
+    pub read: u64,
+    /// Bytes processados pelas funções de hash (conteúdo × algoritmos).
+    pub hashed: u64,
+    /// Bytes produzidos por descompressão ou extração de entradas.
+    pub decompressed: u64,
+    /// `decompressed` por validador.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_validator: BTreeMap<String, u64>,
+}
+
+impl ByteUsage {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Soma as contagens de `other` (ex.: conteúdo extraído de um envelope).
+    pub fn add(&mut self, other: &ByteUsage) {
+        self.read += other.read;
+        self.hashed += other.hashed;
+        self.decompressed += other.decompressed;
+        for (validator, bytes) in &other.by_validator {
+            *self.by_validator.entry(validator.clone()).or_default() += bytes;
+        }
+    }
+}
+
+/// Medição de tempo por etapa.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TimingBreakdown {
+    pub total: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sniff: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validate: Option<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_with_control_characters_are_escaped() {
+        let sniff = SniffReport::new("text/plain".into(), None, None);
+        let report = FileReport::new(
+            Path::new("a\r\nb\u{1b}[31m\u{202e}fdp.exe"),
+            1,
+            String::new(),
+            sniff,
+        );
+        assert_eq!(
+            report.file,
+            PathBuf::from("a\\r\\nb\\x1b[31m\\u{202e}fdp.exe")
+        );
+        assert!(report.file_name_escaped);
+        assert!(matches!(escape_control_chars("ok.txt"), Cow::Borrowed(_)));
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"file_name_escaped\":true"));
+    }
+
+    #[test]
+    fn file_report_initial_state_is_consistent() {
+        let sniff = SniffReport::new(
+            "application/octet-stream".into(),
+            Some("FF".into()),
+            Some(".bin".into()),
+        );
+        let report = FileReport::new(Path::new("sample.bin"), 42, "abcd".into(), sniff.clone());
+        assert_eq!(report.file, PathBuf::from("sample.bin"));
+        assert_eq!(report.size_bytes, 42);
+        assert_eq!(report.sniff.mime_real, "application/octet-stream");
+        assert_eq!(report.sniff.magic, Some("FF".into()));
+        assert_eq!(report.sniff.ext, Some(".bin".into()));
+        assert_eq!(report.policy.decision, "ALLOW");
+        assert!(report.validators.is_empty());
+        assert!(report.notes.is_empty());
+    }
+
+    #[test]
+    fn redact_hashes_owner_and_drops_sensitive_details() {
+        let sniff = SniffReport::new("image/jpeg".into(), None, None);
+        let mut report = FileReport::new(Path::new("photo.jpg"), 10, "abcd".into(), sniff);
+        report.file_owner = Some("uid:1000".into());
+        report.validators.push(ValidatorEntry {
+            name: "image".into(),
+            status: "pass".into(),
+            details: serde_json::json!({ "width": 10, "gps": [1.0, 2.0], "preview": "x" }),
+            version: None,
+        });
+
+        report.redact(&[RedactField::FileOwner, RedactField::ExifGps]);
+
+        let owner = report.file_owner.as_deref().unwrap();
+        assert!(owner.starts_with("sha256:"));
+        assert_ne!(owner, "uid:1000");
+        let details = &report.validators[0].details;
+        assert!(details.get("gps").is_none());
+        assert_eq!(details["preview"], "x");
+        assert_eq!(details["width"], 10);
+    }
+
+    #[test]
+    fn verdict_record_serializes_only_stable_fields() {
+        let sniff = SniffReport::new("application/pdf".into(), None, None);
+        let mut report = FileReport::new(Path::new("a.pdf"), 1, "ff".into(), sniff);
+        report.policy = PolicyDecision {
+            decision: "DENY".into(),
+            rules_triggered: vec!["validator:pdf:deny".into()],
+            ..PolicyDecision::default()
+        };
+        let value = serde_json::to_value(VerdictRecord::from(&report)).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "file": "a.pdf",
+                "decision": "DENY",
+                "rules": ["validator:pdf:deny"],
+            })
+        );
+    }
+
+    #[test]
+    fn percentile_stats_use_nearest_rank() {
+        let samples: Vec<f32> = (1..=20).map(|v| v as f32).collect();
+        let stats = PercentileStats::from_samples(&samples).unwrap();
+        assert_eq!(stats.p50, 10.0);
+        assert_eq!(stats.p95, 19.0);
+        assert_eq!(stats.max, 20.0);
+        assert!(PercentileStats::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn summary_finalize_aggregates_timings() {
+        let mut summary = SummaryReport::default();
+        for total in [1.0, 2.0, 3.0] {
+            summary.record_timings(&TimingBreakdown {
+                total,
+                sniff: Some(total / 2.0),
+                ..TimingBreakdown::default()
+            });
+        }
+        summary.finalize();
+        let timings = summary.timings_ms.expect("timings");
+        assert_eq!(timings.total.unwrap().max, 3.0);
+        assert_eq!(timings.sniff.unwrap().p50, 1.0);
+        assert!(timings.validate.is_none());
+    }
+
+    #[test]
+    fn summary_report_counts_by_decision() {
+        let mut summary = SummaryReport::default();
+        summary.update(&PolicyDecision {
+            decision: "ALLOW".into(),
+            rules_triggered: vec![],
+            ..PolicyDecision::default()
+        });
+        summary.update(&PolicyDecision {
+            decision: "WARN".into(),
+            rules_triggered: vec![],
+            ..PolicyDecision::default()
+        });
+        summary.update(&PolicyDecision {
+            decision: "DENY".into(),
+            rules_triggered: vec![],
+            ..PolicyDecision::default()
+        });
+        assert_eq!(summary.scanned, 3);
+        assert_eq!(summary.allow, 1);
+        assert_eq!(summary.warn, 1);
+        assert_eq!(summary.deny, 1);
+        assert_eq!(summary.errors, 0);
+    }
+
+    #[test]
+    fn summary_breaks_down_rules_mimes_and_top_files() {
+        let mut summary = SummaryReport::default();
+        for (name, mime, size, total, decision, rules) in [
+            (
+                "a.pdf",
+                "application/pdf",
+                300,
+                5.0,
+                "DENY",
+                vec!["mime:deny"],
+            ),
+            ("b.pdf", "application/pdf", 100, 9.0, "ALLOW", vec![]),
+            (
+                "c.exe",
+                "application/x-msdownload",
+                200,
+                1.0,
+                "DENY",
+                vec!["mime:deny"],
+            ),
+        ] {
+            let sniff = SniffReport::new(mime.to_string(), None, None);
+            let mut report = FileReport::new(Path::new(name), size, String::new(), sniff);
+            report.timings_ms.total = total;
+            report.policy = PolicyDecision {
+                decision: decision.into(),
+                rules_triggered: rules.into_iter().map(String::from).collect(),
+                ..PolicyDecision::default()
+            };
+            summary.update(&report.policy);
+            summary.record_file(&report);
+        }
+        let error = FileReport::error(Path::new("missing.bin"), "falha");
+        summary.update(&error.policy);
+        summary.record_file(&error);
+        summary.finalize();
+
+        assert_eq!(summary.bytes_scanned, 600);
+        assert_eq!(summary.rules["mime:deny"], 2);
+        assert_eq!(summary.rules["error:processing"], 1);
+        assert_eq!(
+            summary.by_mime["application/pdf"],
+            DecisionCounts {
+                allow: 1,
+                deny: 1,
+                ..DecisionCounts::default()
+            }
+        );
+        assert!(!summary.by_mime.contains_key("application/octet-stream"));
+        assert_eq!(summary.largest[0].file, Path::new("a.pdf"));
+        assert_eq!(summary.slowest[0].file, Path::new("b.pdf"));
+        assert_eq!(summary.slowest.len(), 3);
+    }
+
+    #[test]
+    fn summary_trend_compares_deny_rate_rules_and_throughput() {
+        let mut summary = SummaryReport {
+            scanned: 4,
+            deny: 2,
+            bytes_scanned: 4000,
+            elapsed_ms: Some(1000.0),
+            ..SummaryReport::default()
+        };
+        summary.rules.insert("mime:deny".into(), 1);
+        summary
+            .rules
+            .insert("structure:polyglot:jpeg+zip".into(), 1);
+        let previous: PreviousSummary = serde_json::from_str(
+            r#"{"scanned": 10, "deny": 1, "bytes_scanned": 8000, "elapsed_ms": 1000.0,
+                "rules": {"mime:deny": 1, "size:deny": 1}, "allow": 9}"#,
+        )
+        .expect("previous summary");
+
+        let trend = summary.compare_with(&previous);
+        assert_eq!(trend.deny_rate, 0.5);
+        assert!((trend.deny_rate_delta - 0.4).abs() < 1e-9);
+        assert_eq!(trend.new_rules, vec!["structure:polyglot:jpeg+zip"]);
+        assert_eq!(trend.resolved_rules, vec!["size:deny"]);
+        assert_eq!(trend.throughput_change_pct, Some(-50.0));
+
+        let empty = summary.compare_with(&PreviousSummary::default());
+        assert_eq!(empty.previous_deny_rate, 0.0);
+        assert_eq!(empty.throughput_change_pct, None);
+    }
+
+    #[test]
+    fn error_report_is_counted_separately() {
+        let report = FileReport::error(Path::new("missing.bin"), "falha ao abrir missing.bin");
+        assert_eq!(report.policy.decision, "ERROR");
+        assert_eq!(report.error.as_deref(), Some("falha ao abrir missing.bin"));
+        let value = serde_json::to_value(&report).unwrap();
+        assert_eq!(value["policy"]["decision"], "ERROR");
+
+        let mut summary = SummaryReport::default();
+        summary.update(&report.policy);
+        assert_eq!(summary.scanned, 1);
+        assert_eq!(summary.errors, 1);
+        assert_eq!(summary.deny, 0);
+    }
+}
@@ -0,0 +1,140 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Prioridade de revisão para filas de moderação.
+//!
+//! Combina decisão, categorias das regras disparadas, popularidade (cópias do
+//! mesmo SHA-256) e tamanho em um score de 0 a 100, para ordenar a fila por
+//! risco em vez da ordem de chegada.
+
+use crate::report::{rule_category, FileReport};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Teto de cada fator; a soma dos tetos é 100.
+const MAX_DECISION: u32 = 50;
+const MAX_CATEGORIES: u32 = 25;
+const MAX_POPULARITY: u32 = 15;
+const MAX_SIZE: u32 = 10;
+
+/// Campo `risk` do relatório.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskSummary {
+    /// 0 a 100; maior primeiro na fila.
+    pub priority: u32,
+    /// `critical` (75+), `high` (50+), `medium` (25+) ou `low`.
+    pub level: String,
+    /// Cópias do mesmo SHA-256 vistas até este arquivo (inclusive).
+    pub copies: u64,
+    pub factors: RiskFactors,
+}
+
+/// Pontos de cada fator em [`RiskSummary::priority`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskFactors {
+    pub decision: u32,
+    pub categories: u32,
+    pub popularity: u32,
+    pub size: u32,
+}
+
+/// Calcula a prioridade do relatório já decidido; `copies` conta o próprio arquivo.
+pub fn assess(report: &FileReport, copies: u64) -> RiskSummary {
+    let decision = match report.policy.decision.as_str() {
+        "DENY" => MAX_DECISION,
+        "ERROR" => 35,
+        "WARN" => 25,
+        _ => 0,
+    };
+    let categories: BTreeSet<&str> = report
+        .policy
+        .rules_triggered
+        .iter()
+        .filter(|rule| !rule.ends_with("hash:allowlisted"))
+        .map(|rule| rule_category(rule.trim_start_matches("inner:")))
+        .collect();
+    let categories = categories
+        .into_iter()
+        .map(category_weight)
+        .sum::<u32>()
+        .min(MAX_CATEGORIES);
+    // 5 pontos a cada vez que as cópias dobram.
+    let popularity = (copies.max(1).ilog2() * 5).min(MAX_POPULARITY);
+    let size = match report.size_bytes {
+        size if size >= 100 * 1024 * 1024 => MAX_SIZE,
+        size if size >= 10 * 1024 * 1024 => 6,
+        size if size >= 1024 * 1024 => 3,
+        _ => 0,
+    };
+    let priority = decision + categories + popularity + size;
+    RiskSummary {
+        priority,
+        level: match priority {
+            75.. => "critical",
+            50.. => "high",
+            25.. => "medium",
+            _ => "low",
+        }
+        .to_string(),
+        copies: copies.max(1),
+        factors: RiskFactors {
+            decision,
+            categories,
+            popularity,
+            size,
+        },
+    }
+}
+
+/// Peso de cada família de regra (`hash`, `validator`, `mime`...).
+fn category_weight(category: &str) -> u32 {
+    match category {
+        "hash" => 25,
+        "validator" | "structure" | "secrets" => 15,
+        "mime" => 12,
+        "sniff" | "entropy" | "encoding" => 8,
+        "size" => 3,
+        _ => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{PolicyDecision, SniffReport};
+    use std::path::Path;
+
+    #[test]
+    fn priority_combines_decision_categories_copies_and_size() {
+        let sniff = SniffReport::new("application/pdf".into(), None, None);
+        let mut report = FileReport::new(Path::new("a.pdf"), 2 * 1024 * 1024, "ab".into(), sniff);
+        let allowed = assess(&report, 1);
+        assert_eq!(allowed.priority, 3);
+        assert_eq!(allowed.level, "low");
+
+        report.policy = PolicyDecision {
+            decision: "DENY".into(),
+            rules_triggered: vec![
+                "mime:mismatch:application/pdf!=application/x-msdownload".into(),
+                "inner:validator:pdf:deny".into(),
+                "validator:pdf:deny:javascript".into(),
+            ],
+            ..PolicyDecision::default()
+        };
+        let denied = assess(&report, 5);
+        assert_eq!(
+            denied.factors,
+            RiskFactors {
+                decision: 50,
+                categories: 25,
+                popularity: 10,
+                size: 3,
+            }
+        );
+        assert_eq!(denied.priority, 88);
+        assert_eq!(denied.level, "critical");
+        assert!(denied.priority > assess(&report, 1).priority);
+    }
+}
@@ -0,0 +1,339 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Códigos estáveis das regras de política (`GU-SIZE-001`, `GU-PDF-DENY`...).
+//!
+//! As regras disparadas continuam como texto livre (`size:exceeds_max:123>456`),
+//! com valores variáveis; o código identifica só o tipo da regra, para alertas e
+//! para `guardupload explain`. Regras de validador usam o nome do validador e o
+//! resultado (`validator:pdf:deny` → `GU-PDF-DENY`).
+
+/// Documentação de um código de regra.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleDoc {
+    pub code: String,
+    /// Prefixo das regras com este código (`size:exceeds_max`, `validator:pdf:deny`).
+    pub rule: String,
+    pub description: String,
+    pub remediation: String,
+}
+
+/// Código das regras sem correspondência no catálogo.
+pub const UNKNOWN_CODE: &str = "GU-UNKNOWN";
+
+/// Código, prefixo da regra, descrição e orientação.
+const CATALOG: &[(&str, &str, &str, &str)] = &[
+    (
+        "GU-SIZE-001",
+        "size:exceeds_max",
+        "Arquivo excede o tamanho máximo da política (`defaults.max_size_mb`).",
+        "Reduza ou divida o arquivo; se o tamanho for legítimo, ajuste `max_size_mb` via override para o MIME.",
+    ),
+    (
+        "GU-SIZE-002",
+        "size:partially_read",
+        "Arquivo excede o limite de leitura e foi analisado só no início e no fim.",
+        "Aumente o limite de leitura ou defina `defaults.partial_read_action` conforme o risco aceito.",
+    ),
+    (
+        "GU-MIME-001",
+        "mime:deny",
+        "MIME real bloqueado pela política (`defaults.deny_types`).",
+        "Envie o conteúdo em um formato permitido; o MIME é detectado pelos bytes, não pela extensão.",
+    ),
+    (
+        "GU-MIME-002",
+        "mime:mismatch",
+        "Extensão ou MIME declarado não corresponde ao MIME real.",
+        "Corrija a extensão do arquivo; divergências costumam indicar disfarce de executáveis.",
+    ),
+    (
+        "GU-MIME-003",
+        "mime:not_allowed",
+        "MIME real fora da lista permitida (`defaults.allow_types`).",
+        "Converta para um formato permitido ou inclua o MIME em `allow_types` se for esperado.",
+    ),
+    (
+        "GU-SNIFF-001",
+        "sniff:low_confidence",
+        "MIME detectado com score abaixo de `defaults.min_sniff_confidence` (só o tipo base ou uma única fonte fraca).",
+        "Confira o conteúdo manualmente; formatos raros sem assinatura conhecida caem aqui. `defaults.sniff_confidence_action` define WARN ou DENY.",
+    ),
+    (
+        "GU-SNIFF-002",
+        "sniff:ambiguous",
+        "Outro backend de sniff aponta um MIME de outra família com score acima de `defaults.min_sniff_confidence`.",
+        "Trate como suspeito: conteúdo válido como dois tipos é comum em disfarces. Compare `sniff.candidates` no relatório.",
+    ),
+    (
+        "GU-FILE-001",
+        "file:unsafe_name",
+        "Nome de arquivo com caracteres de controle ou sequências inseguras.",
+        "Renomeie o arquivo; `defaults.unsafe_file_names` define se o caso gera WARN, DENY ou nada.",
+    ),
+    (
+        "GU-HASH-001",
+        "hash:blocklisted",
+        "SHA-256 presente na lista de bloqueio.",
+        "Conteúdo conhecido como malicioso ou proibido; não há ajuste além de remover o hash da lista.",
+    ),
+    (
+        "GU-HASH-002",
+        "hash:allowlisted",
+        "SHA-256 presente na lista de permissão; a análise de conteúdo foi dispensada.",
+        "Nenhuma ação necessária; revise a lista de permissão periodicamente.",
+    ),
+    (
+        "GU-ENTROPY-001",
+        "entropy:exceeds_threshold",
+        "Entropia acima do limite configurado, típica de conteúdo cifrado ou compactado.",
+        "Confirme a origem do arquivo; ajuste `defaults.entropy_threshold` ou `entropy_action` se for esperado.",
+    ),
+    (
+        "GU-STRUCT-001",
+        "structure:trailing_data",
+        "Bytes após o fim da estrutura do formato (depois do EOI do JPEG ou do IEND do PNG).",
+        "Regrave o arquivo no editor de origem; dados anexados costumam esconder outro arquivo. Ajuste `defaults.structure_action` se for esperado.",
+    ),
+    (
+        "GU-STRUCT-002",
+        "structure:prepended_data",
+        "Bytes antes do início da estrutura (ZIP após outro conteúdo, `%PDF-` fora do offset 0).",
+        "Envie o arquivo original sem o conteúdo prefixado; executáveis autoextraíveis também caem aqui.",
+    ),
+    (
+        "GU-STRUCT-003",
+        "structure:polyglot",
+        "Conteúdo válido em mais de um formato ao mesmo tempo (ex.: ZIP e PDF).",
+        "Trate como suspeito: poliglotas são usados para driblar validação por tipo. `defaults.structure_action` define WARN ou DENY.",
+    ),
+    (
+        "GU-SECRET-001",
+        "secrets:aws_access_key",
+        "Chave de acesso AWS (`AKIA...`/`ASIA...`) no conteúdo do arquivo.",
+        "Revogue a chave no IAM e remova-a do arquivo; chaves publicadas devem ser consideradas comprometidas.",
+    ),
+    (
+        "GU-SECRET-002",
+        "secrets:private_key",
+        "Bloco PEM de chave privada (`-----BEGIN ... PRIVATE KEY-----`).",
+        "Substitua o par de chaves e envie apenas a chave pública ou o certificado.",
+    ),
+    (
+        "GU-SECRET-003",
+        "secrets:jwt",
+        "Token JWT no conteúdo, possivelmente uma sessão ou credencial de API ainda válida.",
+        "Invalide o token no emissor e remova-o de logs e dumps antes do envio.",
+    ),
+    (
+        "GU-SECRET-004",
+        "secrets:high_entropy",
+        "Valor de alta entropia atribuído a um campo de credencial (`api_key`, `secret`, `token`, `password`).",
+        "Troque a credencial e substitua o valor por um marcador. `secrets.action` define WARN ou DENY.",
+    ),
+    (
+        "GU-PII-001",
+        "pii:email",
+        "Endereços de e-mail no conteúdo (dado pessoal sob LGPD/GDPR).",
+        "Confirme a base legal para armazenar o documento ou anonimize os endereços antes do envio. `pii.actions` ajusta a ação por tipo.",
+    ),
+    (
+        "GU-PII-002",
+        "pii:cpf",
+        "CPF com dígitos verificadores válidos no conteúdo.",
+        "Mascare os CPFs ou restrinja o documento a fluxos autorizados a tratar dados pessoais.",
+    ),
+    (
+        "GU-PII-003",
+        "pii:cnpj",
+        "CNPJ com dígitos verificadores válidos no conteúdo.",
+        "CNPJ identifica empresas; use `pii.actions.cnpj: allow` se não for tratado como dado pessoal no seu contexto.",
+    ),
+    (
+        "GU-PII-004",
+        "pii:credit_card",
+        "Número de cartão válido pelo algoritmo de Luhn no conteúdo.",
+        "Remova o número completo (PCI DSS permite no máximo os 4 últimos dígitos) e reenvie o documento.",
+    ),
+    (
+        "GU-ENC-001",
+        "encoding:invalid",
+        "Conteúdo com codificação declarada em `encoding.declared` não pôde ser decodificado.",
+        "Confira se a integração grava o arquivo na codificação declarada ou ajuste o glob.",
+    ),
+    (
+        "GU-ENC-002",
+        "encoding:limit",
+        "Conteúdo codificado excede `encoding.max_decoded_mb` e não foi inspecionado.",
+        "Aumente `encoding.max_decoded_mb` ou envie o arquivo sem codificação.",
+    ),
+    (
+        "GU-SESSION-001",
+        "session:max_files",
+        "Sessão excede a quantidade máxima de arquivos (`session.max_files`).",
+        "Envie os arquivos em lotes menores.",
+    ),
+    (
+        "GU-SESSION-002",
+        "session:max_total_size",
+        "Sessão excede o tamanho total máximo (`session.max_total_size_mb`).",
+        "Envie os arquivos em lotes menores ou reduza o tamanho total.",
+    ),
+    (
+        "GU-SESSION-003",
+        "session:mixed_executable_document",
+        "Sessão mistura executáveis e documentos.",
+        "Separe executáveis e documentos em envios distintos.",
+    ),
+    (
+        "GU-ACCEPT-001",
+        "accept:failed",
+        "Arquivo permitido, mas a cópia para `--accept-dir` falhou.",
+        "Confira permissões e espaço livre no diretório de aceitos; o arquivo não foi entregue.",
+    ),
+    (
+        "GU-ACCEPT-002",
+        "accept:partially_read",
+        "Arquivo permitido, mas lido só parcialmente; a cópia para `--accept-dir` não pode ser conferida.",
+        "Aumente `--max-read-bytes` para que o arquivo seja lido inteiro antes de ser aceito.",
+    ),
+    (
+        "GU-ERROR-001",
+        "error:processing",
+        "Falha ao processar o arquivo (leitura ou análise).",
+        "Consulte o campo `error` do relatório; falhas de leitura costumam ser permissões ou arquivos removidos.",
+    ),
+];
+
+/// Resultados de validador, do sufixo do código ao da regra.
+const VALIDATOR_STATUSES: &[(&str, &str, &str, &str)] = &[
+    (
+        "DENY",
+        "deny",
+        "Validador '{name}' encontrou uma violação no conteúdo.",
+        "Veja `validators[].details` no relatório: a mensagem indica o limite ou a construção recusada.",
+    ),
+    (
+        "WARN",
+        "warn",
+        "Validador '{name}' sinalizou o conteúdo como suspeito.",
+        "Revise o arquivo manualmente; `defaults.report_all_findings` lista todos os achados.",
+    ),
+    (
+        "ERROR",
+        "error",
+        "Validador '{name}' não conseguiu analisar o conteúdo.",
+        "O arquivo pode estar corrompido; `routing.on_validator_error: fallback` aplica o validador genérico.",
+    ),
+    (
+        "TIMEOUT",
+        "error:timeout",
+        "Validador '{name}' excedeu o prazo de análise.",
+        "Aumente `--timeout` ou reduza o tamanho do arquivo.",
+    ),
+    (
+        "FALLBACK",
+        "error:fallback",
+        "Validador '{name}' falhou e o validador genérico decidiu no lugar dele.",
+        "Confira se o arquivo abre normalmente; a falha do validador especializado fica registrada.",
+    ),
+];
+
+/// Código estável de uma regra disparada (prefixos `inner:` são ignorados).
+pub fn code_for(rule: &str) -> String {
+    let rule = rule.trim_start_matches("inner:");
+    if let Some(rest) = rule.strip_prefix("validator:") {
+        let (name, status) = rest.split_once(':').unwrap_or((rest, "error"));
+        let suffix = VALIDATOR_STATUSES
+            .iter()
+            .rev()
+            .find(|(_, prefix, _, _)| status.starts_with(prefix))
+            .map_or("ERROR", |(suffix, _, _, _)| *suffix);
+        return format!("GU-{}-{suffix}", name.to_ascii_uppercase());
+    }
+    CATALOG
+        .iter()
+        .find(|(_, prefix, _, _)| {
+            rule.strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(':'))
+        })
+        .map_or(UNKNOWN_CODE, |(code, _, _, _)| *code)
+        .to_string()
+}
+
+/// Documentação do código (sem diferenciar maiúsculas), se conhecido.
+pub fn explain(code: &str) -> Option<RuleDoc> {
+    let code = code.trim().to_ascii_uppercase();
+    if let Some(&(code, rule, description, remediation)) =
+        CATALOG.iter().find(|(known, _, _, _)| *known == code)
+    {
+        return Some(RuleDoc {
+            code: code.to_string(),
+            rule: rule.to_string(),
+            description: description.to_string(),
+            remediation: remediation.to_string(),
+        });
+    }
+    let (name, suffix) = code.strip_prefix("GU-")?.rsplit_once('-')?;
+    let &(_, status, description, remediation) = VALIDATOR_STATUSES
+        .iter()
+        .find(|(known, _, _, _)| *known == suffix)?;
+    let name = name.to_ascii_lowercase();
+    if name.is_empty() {
+        return None;
+    }
+    Some(RuleDoc {
+        rule: format!("validator:{name}:{status}"),
+        description: description.replace("{name}", &name),
+        remediation: remediation.to_string(),
+        code,
+    })
+}
+
+/// Regras que um validador pode disparar (`validator:<nome>:deny`...), na ordem
+/// de [`VALIDATOR_STATUSES`].
+pub fn validator_rules(name: &str) -> Vec<String> {
+    VALIDATOR_STATUSES
+        .iter()
+        .map(|(_, status, _, _)| format!("validator:{name}:{status}"))
+        .collect()
+}
+
+/// Códigos do catálogo fixo, na ordem de documentação.
+pub fn catalog() -> Vec<RuleDoc> {
+    CATALOG
+        .iter()
+        .filter_map(|(code, _, _, _)| explain(code))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_map_to_stable_codes_and_back() {
+        assert_eq!(code_for("size:exceeds_max:123>456"), "GU-SIZE-001");
+        assert_eq!(code_for("inner:mime:deny:application/pdf"), "GU-MIME-001");
+        assert_eq!(code_for("validator:pdf:deny"), "GU-PDF-DENY");
+        assert_eq!(
+            code_for("validator:disk_image:error:timeout"),
+            "GU-DISK_IMAGE-TIMEOUT"
+        );
+        assert_eq!(code_for("sizes:whatever"), UNKNOWN_CODE);
+
+        let doc = explain("gu-pdf-fallback").expect("validador");
+        assert_eq!(doc.rule, "validator:pdf:error:fallback");
+        assert!(doc.description.contains("'pdf'"));
+        assert_eq!(
+            explain("GU-SIZE-001").expect("size").rule,
+            "size:exceeds_max"
+        );
+        assert!(explain("GU-PDF-XYZ").is_none());
+        for doc in catalog() {
+            assert_eq!(code_for(&doc.rule), doc.code);
+        }
+    }
+}
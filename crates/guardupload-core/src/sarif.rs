@@ -0,0 +1,253 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Exportação dos relatórios em SARIF 2.1.0 (GitHub Code Scanning e afins).
+
+use crate::report::FileReport;
+use crate::rules;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+pub const SARIF_VERSION: &str = "2.1.0";
+
+/// Documento SARIF com um único `run` do GuardUpload.
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifDriver {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRule {
+    pub id: String,
+    pub short_description: SarifMessage,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    pub rule_index: usize,
+    pub level: &'static str,
+    pub message: SarifMessage,
+    pub locations: Vec<Value>,
+    pub properties: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+/// Acumula resultados arquivo a arquivo e monta o documento no final do scan.
+#[derive(Debug, Clone, Default)]
+pub struct SarifBuilder {
+    rules: Vec<SarifRule>,
+    results: Vec<SarifResult>,
+}
+
+impl SarifBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Converte cada regra disparada no relatório em um `result` SARIF.
+    pub fn push(&mut self, report: &FileReport) {
+        let level = level_for(&report.policy.decision);
+        let uri = report.file.to_string_lossy().replace('\\', "/");
+        for rule in &report.policy.rules_triggered {
+            let rule_id = rule_id(rule);
+            let rule_index = self.rule_index(&rule_id);
+            let mut text = format!("{}: {} ({rule})", uri, report.policy.decision);
+            if let Some(message) = validator_message(report, rule) {
+                text.push_str(&format!(" — {message}"));
+            } else if let Some(error) = report.error.as_deref() {
+                text.push_str(&format!(" — {error}"));
+            }
+            self.results.push(SarifResult {
+                rule_id,
+                rule_index,
+                level,
+                message: SarifMessage { text },
+                locations: vec![json!({
+                    "physicalLocation": { "artifactLocation": { "uri": uri } }
+                })],
+                properties: json!({
+                    "decision": report.policy.decision,
+                    "rule": rule,
+                    "code": rules::code_for(rule),
+                    "sha256": report.sha256,
+                    "mime_real": report.sniff.mime_real,
+                }),
+            });
+        }
+    }
+
+    pub fn build(self) -> SarifLog {
+        SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: "GuardUpload",
+                        version: env!("CARGO_PKG_VERSION"),
+                        rules: self.rules,
+                    },
+                },
+                results: self.results,
+            }],
+        }
+    }
+
+    fn rule_index(&mut self, rule_id: &str) -> usize {
+        if let Some(index) = self.rules.iter().position(|rule| rule.id == rule_id) {
+            return index;
+        }
+        self.rules.push(SarifRule {
+            id: rule_id.to_string(),
+            short_description: SarifMessage {
+                text: describe_rule(rule_id),
+            },
+        });
+        self.rules.len() - 1
+    }
+}
+
+/// Mapeia a decisão do arquivo para o `level` SARIF.
+fn level_for(decision: &str) -> &'static str {
+    match decision {
+        "DENY" | "ERROR" => "error",
+        "WARN" => "warning",
+        _ => "note",
+    }
+}
+
+/// Identificador estável: as duas primeiras partes da regra (`validator:pdf`,
+/// `size:exceeds_max`), sem valores variáveis como tamanhos e MIMEs.
+fn rule_id(rule: &str) -> String {
+    rule.splitn(3, ':').take(2).collect::<Vec<_>>().join(":")
+}
+
+fn describe_rule(rule_id: &str) -> String {
+    match rule_id.split_once(':') {
+        Some(("validator", name)) => format!("Validador '{name}' sinalizou o arquivo"),
+        Some(("size", "partially_read")) => {
+            "Arquivo excede o limite de leitura e foi analisado só no início e no fim".to_string()
+        }
+        Some(("size", _)) => "Arquivo excede o tamanho máximo da política".to_string(),
+        Some(("mime", "deny")) => "MIME real bloqueado pela política".to_string(),
+        Some(("mime", "mismatch")) => "Extensão não corresponde ao MIME real".to_string(),
+        Some(("mime", "not_allowed")) => "MIME real fora da lista permitida".to_string(),
+        Some(("file", "unsafe_name")) => "Nome de arquivo com caracteres de controle".to_string(),
+        Some(("hash", "blocklisted")) => "SHA-256 presente na lista de bloqueio".to_string(),
+        Some(("hash", "allowlisted")) => "SHA-256 presente na lista de permissão".to_string(),
+        Some(("session", "max_files")) => {
+            "Sessão excede a quantidade máxima de arquivos".to_string()
+        }
+        Some(("session", "max_total_size")) => {
+            "Sessão excede o tamanho total máximo da política".to_string()
+        }
+        Some(("session", "mixed_executable_document")) => {
+            "Sessão mistura executáveis e documentos".to_string()
+        }
+        Some(("entropy", _)) => "Entropia acima do limite configurado".to_string(),
+        Some(("accept", "failed")) => "Cópia do arquivo aceito falhou".to_string(),
+        Some(("accept", "partially_read")) => {
+            "Arquivo lido parcialmente não pode ser aceito".to_string()
+        }
+        Some(("error", _)) => "Falha ao processar o arquivo".to_string(),
+        _ => rule_id.to_string(),
+    }
+}
+
+fn validator_message<'a>(report: &'a FileReport, rule: &str) -> Option<&'a str> {
+    let name = rule.strip_prefix("validator:")?.split(':').next()?;
+    report
+        .validators
+        .iter()
+        .find(|entry| entry.name == name)
+        .and_then(|entry| entry.details["message"].as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{PolicyDecision, SniffReport, ValidatorEntry};
+    use std::path::Path;
+
+    fn report(file: &str, decision: &str, rules: &[&str]) -> FileReport {
+        let sniff = SniffReport::new("application/pdf".into(), None, None);
+        let mut report = FileReport::new(Path::new(file), 10, "abcd".into(), sniff);
+        report.policy = PolicyDecision {
+            decision: decision.into(),
+            rules_triggered: rules.iter().map(|rule| rule.to_string()).collect(),
+            ..PolicyDecision::default()
+        };
+        report
+    }
+
+    #[test]
+    fn rules_become_results_with_levels_and_locations() {
+        let mut denied = report("docs/a.pdf", "DENY", &["validator:pdf:deny"]);
+        denied.validators.push(ValidatorEntry {
+            name: "pdf".into(),
+            status: "deny".into(),
+            details: json!({ "message": "JavaScript detectado em PDF" }),
+            version: None,
+        });
+        let warned = report("b.bin", "WARN", &["entropy:exceeds_threshold:7.90>7.5"]);
+        let allowed = report("c.txt", "ALLOW", &[]);
+
+        let mut builder = SarifBuilder::new();
+        for item in [&denied, &warned, &allowed] {
+            builder.push(item);
+        }
+        let log = serde_json::to_value(builder.build()).unwrap();
+
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "validator:pdf");
+        assert_eq!(
+            run["tool"]["driver"]["rules"][1]["id"],
+            "entropy:exceeds_threshold"
+        );
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "docs/a.pdf"
+        );
+        assert!(results[0]["message"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("JavaScript detectado"));
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(results[1]["ruleIndex"], 1);
+    }
+}
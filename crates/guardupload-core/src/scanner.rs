@@ -0,0 +1,574 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! API programática para embutir o GuardUpload em serviços (ex.: handlers Axum)
+//! sem `ScanRequest`, caminhos em disco ou a CLI.
+//!
+//! ```
+//! use guardupload_core::config::PolicyConfig;
+//! use guardupload_core::scanner::{FileMeta, Scanner};
+//!
+//! let config = PolicyConfig::from_yaml_str("defaults:\n  allow_types: [\"text/plain\"]\n").unwrap();
+//! let scanner = Scanner::new(config);
+//! let report = scanner.scan_bytes(b"hello world\n", Some(FileMeta::named("nota.txt")));
+//! assert_eq!(report.policy.decision, "ALLOW");
+//! ```
+
+use crate::analyzers::hashes::HashAlgorithm;
+use crate::config::PolicyConfig;
+use crate::limits::Deadline;
+use crate::pipeline;
+use crate::policy::{DecisionOutcome, PolicyEngine};
+use crate::report::FileReport;
+use crate::sniff::ClaimedMimeMap;
+use crate::validators::{Validator, ValidatorRegistry};
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+#[cfg(feature = "tokio")]
+use std::sync::Arc;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Nome usado no relatório quando o chamador não informa [`FileMeta`].
+const UNNAMED: &str = "upload";
+
+/// Tamanho dos blocos lidos de streams.
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Metadados opcionais do conteúdo analisado.
+#[derive(Debug, Clone, Default)]
+pub struct FileMeta {
+    /// Nome original (rótulo do relatório e origem da extensão/MIME declarado).
+    pub name: Option<PathBuf>,
+    /// Submissão lógica à qual o arquivo pertence.
+    pub session: Option<String>,
+}
+
+impl FileMeta {
+    pub fn named(name: impl Into<PathBuf>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Self::default()
+        }
+    }
+}
+
+/// Analisador reutilizável: compila a política uma vez e atende várias requisições.
+#[derive(Debug)]
+pub struct Scanner {
+    policy_engine: Option<PolicyEngine>,
+    claimed_mimes: ClaimedMimeMap,
+    hash_algorithms: Vec<HashAlgorithm>,
+    timeout: Option<Duration>,
+    max_read_bytes: Option<u64>,
+    validators: ValidatorRegistry,
+}
+
+impl Scanner {
+    /// Cria um analisador que aplica `config` a cada arquivo.
+    pub fn new(config: PolicyConfig) -> Self {
+        Self {
+            policy_engine: Some(PolicyEngine::new(config)),
+            ..Self::without_policy()
+        }
+    }
+
+    /// Analisador sem política: tudo é ALLOW, exceto falhas de validadores.
+    pub fn without_policy() -> Self {
+        Self {
+            policy_engine: None,
+            claimed_mimes: ClaimedMimeMap::default(),
+            hash_algorithms: Vec::new(),
+            timeout: None,
+            max_read_bytes: None,
+            validators: ValidatorRegistry::builtin(),
+        }
+    }
+
+    /// Tabela extensão → MIME declarado usada na checagem de divergência.
+    pub fn with_claimed_mimes(mut self, claimed_mimes: ClaimedMimeMap) -> Self {
+        self.claimed_mimes = claimed_mimes;
+        self
+    }
+
+    /// Digests extras em `hashes`, além do SHA-256.
+    pub fn with_hashes(mut self, hash_algorithms: Vec<HashAlgorithm>) -> Self {
+        self.hash_algorithms = hash_algorithms;
+        self
+    }
+
+    /// Prazo por arquivo repassado aos validadores.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Limite de bytes lidos por [`Scanner::scan_reader`]; acima dele só o início e o
+    /// fim são analisados (`partially_read`).
+    pub fn with_max_read_bytes(mut self, max_read_bytes: u64) -> Self {
+        self.max_read_bytes = Some(max_read_bytes);
+        self
+    }
+
+    /// Registra um validador customizado, consultado antes do fallback `generic`.
+    pub fn with_validator(mut self, validator: impl Validator + 'static) -> Self {
+        self.validators.register(validator);
+        self
+    }
+
+    /// Substitui o registro de validadores (por exemplo, sem algum embutido).
+    pub fn with_validators(mut self, validators: ValidatorRegistry) -> Self {
+        self.validators = validators;
+        self
+    }
+
+    /// Confere `require` da política contra os validadores registrados; chame
+    /// depois de montar o analisador para falhar antes do primeiro arquivo.
+    pub fn check_requirements(&self) -> Result<()> {
+        match &self.policy_engine {
+            Some(engine) => self.validators.check_requirements(&engine.config().require),
+            None => Ok(()),
+        }
+    }
+
+    /// Analisa um conteúdo em memória. Falhas viram relatório com decisão `ERROR`.
+    pub fn scan_bytes(&self, data: &[u8], meta: Option<FileMeta>) -> FileReport {
+        let meta = meta.unwrap_or_default();
+        let name = meta.name.unwrap_or_else(|| PathBuf::from(UNNAMED));
+        let result = pipeline::analyze_bytes_with_validators(
+            &name,
+            data,
+            self.policy_engine.as_ref(),
+            &self.claimed_mimes,
+            &self.hash_algorithms,
+            &self.deadline(),
+            &self.validators,
+        );
+        self.finish(&name, result, meta.session)
+    }
+
+    /// Lê `reader` até o fim e analisa o conteúdo, respeitando `with_max_read_bytes`.
+    ///
+    /// Erros de leitura são devolvidos; falhas de análise viram relatório `ERROR`.
+    pub fn scan_reader(&self, reader: impl Read, meta: Option<FileMeta>) -> Result<FileReport> {
+        let mut reader = reader;
+        let mut buffer = HeadTail::new(self.max_read_bytes);
+        let mut chunk = vec![0u8; READ_CHUNK];
+        loop {
+            let read = reader.read(&mut chunk).context("falha ao ler conteúdo")?;
+            if read == 0 {
+                break;
+            }
+            buffer.push(&chunk[..read]);
+        }
+        Ok(self.scan_buffered(buffer, meta))
+    }
+
+    /// Lê e analisa o arquivo em `path`, que também rotula o relatório. Falhas
+    /// de abertura ou leitura viram relatório `ERROR`.
+    pub fn scan_path(&self, path: &Path) -> FileReport {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) => return FileReport::error(path, format!("falha ao abrir arquivo: {err}")),
+        };
+        self.scan_reader(file, Some(FileMeta::named(path)))
+            .unwrap_or_else(|err| FileReport::error(path, format!("{err:#}")))
+    }
+
+    /// Analisa os arquivos de `paths` em sequência, entregando cada relatório a
+    /// `on_report` assim que fica pronto. Diretórios não são percorridos.
+    pub fn scan_paths_with<P: AsRef<Path>>(
+        &self,
+        paths: impl IntoIterator<Item = P>,
+        mut on_report: impl FnMut(FileReport),
+    ) {
+        for path in paths {
+            on_report(self.scan_path(path.as_ref()));
+        }
+    }
+
+    /// Variante paralela de [`Scanner::scan_paths_with`]: `threads` workers
+    /// recebem os caminhos por canal e devolvem os relatórios por outro; o
+    /// callback roda na thread chamadora, na ordem de conclusão.
+    pub fn scan_paths_parallel_with<P: AsRef<Path> + Send>(
+        &self,
+        paths: impl IntoIterator<Item = P>,
+        threads: usize,
+        mut on_report: impl FnMut(FileReport),
+    ) {
+        if threads <= 1 {
+            return self.scan_paths_with(paths, on_report);
+        }
+        let (path_sender, path_receiver) = mpsc::channel::<P>();
+        let path_receiver = Mutex::new(path_receiver);
+        let (report_sender, reports) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                let path_receiver = &path_receiver;
+                let report_sender = report_sender.clone();
+                scope.spawn(move || loop {
+                    let next = path_receiver
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .recv();
+                    let Ok(path) = next else {
+                        break;
+                    };
+                    if report_sender.send(self.scan_path(path.as_ref())).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(report_sender);
+            for path in paths {
+                let _ = path_sender.send(path);
+            }
+            drop(path_sender);
+            for report in reports {
+                on_report(report);
+            }
+        });
+    }
+
+    /// Analisa o conteúdo acumulado: inteiro ou, acima do limite, início e fim.
+    fn scan_buffered(&self, buffer: HeadTail, meta: Option<FileMeta>) -> FileReport {
+        let (head, tail, size_bytes) = buffer.finish();
+        let Some(tail) = tail else {
+            return self.scan_bytes(&head, meta);
+        };
+        let meta = meta.unwrap_or_default();
+        let name = meta.name.unwrap_or_else(|| PathBuf::from(UNNAMED));
+        let result = pipeline::analyze_partial(
+            &name,
+            &head,
+            &tail,
+            size_bytes,
+            self.policy_engine.as_ref(),
+            &self.claimed_mimes,
+        );
+        self.finish(&name, result, meta.session)
+    }
+
+    fn deadline(&self) -> Deadline {
+        self.timeout.map(Deadline::after).unwrap_or_default()
+    }
+
+    fn finish(
+        &self,
+        name: &Path,
+        result: Result<(FileReport, DecisionOutcome)>,
+        session: Option<String>,
+    ) -> FileReport {
+        let mut report = match result {
+            Ok((mut report, outcome)) => {
+                report.policy = outcome.into();
+                if let Some(engine) = self.policy_engine.as_ref() {
+                    report.redact(&engine.config().report.redact);
+                }
+                report
+            }
+            Err(err) => FileReport::error(name, format!("{err:#}")),
+        };
+        report.session = session;
+        report
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Scanner {
+    /// Variante assíncrona de [`Scanner::scan_reader`]: lê o stream no runtime e
+    /// executa a análise (CPU) em `spawn_blocking`, sem ocupar as threads do runtime.
+    pub async fn scan_stream(
+        self: &Arc<Self>,
+        reader: impl AsyncRead + Unpin,
+        meta: Option<FileMeta>,
+    ) -> Result<FileReport> {
+        let mut reader = reader;
+        let mut buffer = HeadTail::new(self.max_read_bytes);
+        let mut chunk = vec![0u8; READ_CHUNK];
+        loop {
+            let read = reader
+                .read(&mut chunk)
+                .await
+                .context("falha ao ler conteúdo")?;
+            if read == 0 {
+                break;
+            }
+            buffer.push(&chunk[..read]);
+        }
+        let scanner = Arc::clone(self);
+        tokio::task::spawn_blocking(move || scanner.scan_buffered(buffer, meta))
+            .await
+            .context("falha na tarefa de análise")
+    }
+
+    /// Analisa vários conteúdos em memória com até `concurrency` análises simultâneas
+    /// em `spawn_blocking`; os relatórios seguem a ordem de entrada.
+    pub async fn scan_batch(
+        self: &Arc<Self>,
+        items: impl IntoIterator<Item = (Vec<u8>, Option<FileMeta>)>,
+        concurrency: usize,
+    ) -> Vec<FileReport> {
+        let mut pending = tokio::task::JoinSet::new();
+        let mut reports: Vec<Option<FileReport>> = Vec::new();
+        let mut names = Vec::new();
+        for (index, (data, meta)) in items.into_iter().enumerate() {
+            if pending.len() >= concurrency.max(1) {
+                collect_batch_result(pending.join_next().await, &mut reports);
+            }
+            names.push(meta.as_ref().and_then(|meta| meta.name.clone()));
+            reports.push(None);
+            let scanner = Arc::clone(self);
+            pending.spawn_blocking(move || (index, scanner.scan_bytes(&data, meta)));
+        }
+        while let Some(result) = pending.join_next().await {
+            collect_batch_result(Some(result), &mut reports);
+        }
+        reports
+            .into_iter()
+            .zip(names)
+            .map(|(report, name)| {
+                report.unwrap_or_else(|| {
+                    let name = name.unwrap_or_else(|| PathBuf::from(UNNAMED));
+                    FileReport::error(&name, "tarefa de análise interrompida")
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "tokio")]
+fn collect_batch_result(
+    result: Option<Result<(usize, FileReport), tokio::task::JoinError>>,
+    reports: &mut [Option<FileReport>],
+) {
+    // Tarefas que entraram em pânico ficam sem relatório e viram `ERROR` no final.
+    if let Some(Ok((index, report))) = result {
+        reports[index] = Some(report);
+    }
+}
+
+/// Acumula um conteúdo lido em blocos; acima de `cap` guarda só a primeira e a
+/// última metade do limite, sem manter o conteúdo inteiro em memória.
+struct HeadTail {
+    cap: Option<u64>,
+    head: Vec<u8>,
+    rest: Vec<u8>,
+    size_bytes: u64,
+}
+
+impl HeadTail {
+    fn new(cap: Option<u64>) -> Self {
+        Self {
+            cap,
+            head: Vec::new(),
+            rest: Vec::new(),
+            size_bytes: 0,
+        }
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        self.size_bytes += chunk.len() as u64;
+        let Some(cap) = self.cap else {
+            self.head.extend_from_slice(chunk);
+            return;
+        };
+        let room = (cap as usize)
+            .saturating_sub(self.head.len())
+            .min(chunk.len());
+        self.head.extend_from_slice(&chunk[..room]);
+        self.rest.extend_from_slice(&chunk[room..]);
+        let tail_len = (cap / 2) as usize;
+        if self.rest.len() > tail_len.max(READ_CHUNK) * 2 {
+            self.rest.drain(..self.rest.len() - tail_len);
+        }
+    }
+
+    /// Devolve `(head, None, tamanho)` quando o conteúdo coube inteiro no limite.
+    fn finish(mut self) -> (Vec<u8>, Option<Vec<u8>>, u64) {
+        let Some(cap) = self.cap.filter(|cap| self.size_bytes > *cap) else {
+            return (self.head, None, self.size_bytes);
+        };
+        let tail_len = (cap / 2) as usize;
+        let mut tail = self.head.split_off((cap as usize) - tail_len);
+        tail.extend_from_slice(&self.rest);
+        let tail = tail.split_off(tail.len() - tail_len);
+        (self.head, Some(tail), self.size_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_bytes_applies_policy_and_round_trips_report() {
+        let config =
+            PolicyConfig::from_yaml_str("defaults:\n  deny_types: [\"application/pdf\"]\n")
+                .expect("policy");
+        let scanner = Scanner::new(config).with_hashes(vec![HashAlgorithm::Md5]);
+        let report = scanner.scan_bytes(
+            b"%PDF-1.4\n%%EOF\n",
+            Some(FileMeta {
+                name: Some("fatura.pdf".into()),
+                session: Some("s-1".into()),
+            }),
+        );
+        assert_eq!(report.policy.decision, "DENY");
+        assert_eq!(report.session.as_deref(), Some("s-1"));
+        assert!(report.hashes.contains_key("md5"));
+
+        let json = serde_json::to_string(&report).expect("json");
+        let back: FileReport = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(back.policy.rules_triggered, report.policy.rules_triggered);
+        assert_eq!(back.sha256, report.sha256);
+        assert_eq!(serde_json::to_string(&back).expect("json"), json);
+    }
+
+    #[test]
+    fn custom_validator_denies_unless_disabled_by_policy() {
+        struct NoTabs;
+
+        impl Validator for NoTabs {
+            fn name(&self) -> &'static str {
+                "no_tabs"
+            }
+
+            fn applies_to(&self, ctx: &crate::validators::ValidationContext<'_>) -> bool {
+                ctx.mime == "text/plain"
+            }
+
+            fn validate(
+                &self,
+                ctx: &crate::validators::ValidationContext<'_>,
+            ) -> crate::validators::ValidatorOutcome {
+                if ctx.data.contains(&b'\t') {
+                    crate::validators::ValidatorOutcome::deny(self.name(), "tabulação proibida")
+                } else {
+                    crate::validators::ValidatorOutcome::pass(self.name())
+                }
+            }
+        }
+
+        let meta = || Some(FileMeta::named("dados.txt"));
+        let report = Scanner::new(PolicyConfig::default())
+            .with_validator(NoTabs)
+            .scan_bytes(b"a\tb\n", meta());
+        assert_eq!(report.validators[0].name, "no_tabs");
+        assert_eq!(report.policy.decision, "DENY");
+
+        let config =
+            PolicyConfig::from_yaml_str("validators:\n  disabled: [no_tabs]\n").expect("policy");
+        let report = Scanner::new(config)
+            .with_validator(NoTabs)
+            .scan_bytes(b"a\tb\n", meta());
+        assert_eq!(report.validators[0].name, "generic");
+        assert_eq!(report.policy.decision, "ALLOW");
+    }
+
+    #[test]
+    fn scan_reader_keeps_head_and_tail_beyond_cap() {
+        let mut content = b"%PDF-1.4\n".to_vec();
+        content.resize(300_000, b'a');
+        content.extend_from_slice(b"fim");
+
+        let scanner = Scanner::without_policy().with_max_read_bytes(100);
+        let report = scanner.scan_reader(content.as_slice(), None).expect("scan");
+        assert!(report.partially_read);
+        assert_eq!(report.size_bytes, content.len() as u64);
+        assert_eq!(report.file, Path::new(UNNAMED));
+        let expected = [&content[..50], &content[content.len() - 50..]].concat();
+        let mut buffer = HeadTail::new(Some(100));
+        for chunk in content.chunks(7_000) {
+            buffer.push(chunk);
+        }
+        let (head, tail, _) = buffer.finish();
+        assert_eq!([head, tail.expect("tail")].concat(), expected);
+        // O digest parcial não identifica o conteúdo e não entra em `sha256`.
+        assert!(report.sha256.is_empty());
+        let partial = hex::encode(<sha2::Sha256 as sha2::Digest>::digest(&expected));
+        assert_eq!(report.partial_sha256.as_deref(), Some(partial.as_str()));
+
+        // Nem uma allowlist com o digest parcial dispensa a regra de leitura parcial.
+        let mut config = PolicyConfig::default();
+        config.hashes.allow_digests.insert(partial);
+        let report = Scanner::new(config)
+            .with_max_read_bytes(100)
+            .scan_reader(content.as_slice(), None)
+            .expect("scan");
+        assert_eq!(report.policy.decision, "DENY");
+        assert_eq!(report.policy.rules_triggered, vec!["size:partially_read"]);
+
+        let small = scanner.scan_reader(&b"hello"[..], None).expect("scan");
+        assert!(!small.partially_read);
+    }
+
+    #[test]
+    fn path_callbacks_report_each_file_once() {
+        let dir =
+            std::env::temp_dir().join(format!("guardupload-scan-paths-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("dir");
+        let mut paths = Vec::new();
+        for index in 0..6 {
+            let path = dir.join(format!("{index}.txt"));
+            std::fs::write(&path, format!("arquivo {index}\n")).expect("write");
+            paths.push(path);
+        }
+        paths.push(dir.join("ausente.txt"));
+
+        let scanner = Scanner::new(PolicyConfig::default());
+        let mut sequential = Vec::new();
+        scanner.scan_paths_with(&paths, |report| sequential.push(report.policy.decision));
+        let mut parallel = Vec::new();
+        scanner.scan_paths_parallel_with(&paths, 3, |report| parallel.push(report.file));
+        std::fs::remove_dir_all(&dir).expect("cleanup");
+
+        assert_eq!(sequential.len(), paths.len());
+        assert_eq!(sequential.last().map(String::as_str), Some("ERROR"));
+        parallel.sort();
+        let mut expected = paths.clone();
+        expected.sort();
+        assert_eq!(parallel, expected);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn async_stream_and_batch_match_sync_results() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("runtime");
+        let config =
+            PolicyConfig::from_yaml_str("defaults:\n  deny_types: [\"application/pdf\"]\n")
+                .expect("policy");
+        let scanner = Arc::new(Scanner::new(config));
+
+        let report = runtime
+            .block_on(scanner.scan_stream(&b"%PDF-1.4\n%%EOF\n"[..], None))
+            .expect("stream");
+        assert_eq!(report.policy.decision, "DENY");
+
+        let items = vec![
+            (
+                b"%PDF-1.4\n%%EOF\n".to_vec(),
+                Some(FileMeta::named("a.pdf")),
+            ),
+            (b"hello world\n".to_vec(), Some(FileMeta::named("b.txt"))),
+            (b"%PDF-1.4\n%%EOF\n".to_vec(), None),
+        ];
+        let reports = runtime.block_on(scanner.scan_batch(items, 2));
+        let decisions: Vec<&str> = reports
+            .iter()
+            .map(|report| report.policy.decision.as_str())
+            .collect();
+        assert_eq!(decisions, vec!["DENY", "ALLOW", "DENY"]);
+        assert_eq!(reports[1].file, Path::new("b.txt"));
+    }
+}
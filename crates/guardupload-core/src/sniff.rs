@@ -0,0 +1,743 @@
+// GuardUpload
+// Criado em: 2025-11-01
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Sniff de MIME real baseado em magic bytes e heurísticas.
+
+use crate::config::CustomMagicSection;
+use crate::validators::{detect_disk_image, DiskImageKind};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::env::{split_paths, var_os};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tree_magic_mini::from_u8;
+
+#[cfg(feature = "embedded-magic")]
+mod embedded;
+#[cfg(feature = "embedded-magic")]
+pub use embedded::{embedded_mime, EMBEDDED_SIGNATURE_BACKEND};
+
+/// Biblioteca de detecção e faixa de versão declarada no manifesto.
+pub const SNIFF_LIBRARY: &str = "tree_magic_mini";
+pub const SNIFF_LIBRARY_VERSION_REQ: &str = "3.0";
+
+/// Proveniência dos dados de detecção usados no scan.
+#[derive(Debug, Clone, Serialize)]
+pub struct SniffDatabaseInfo {
+    pub library: String,
+    pub library_version_req: String,
+    /// `system` quando há base shared-mime-info carregada, `embedded` quando
+    /// faltam bases e o build traz a feature `embedded-magic`, `none` quando só
+    /// restam as heurísticas internas da biblioteca.
+    pub source: String,
+    pub databases: Vec<MagicDatabaseFile>,
+}
+
+/// Arquivo `magic` do shared-mime-info encontrado nos diretórios XDG.
+#[derive(Debug, Clone, Serialize)]
+pub struct MagicDatabaseFile {
+    pub path: PathBuf,
+    pub sha256: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shared_mime_info_version: Option<String>,
+}
+
+/// Reproduz a busca de `tree_magic_mini` (`TREE_MAGIC_DIR`, `XDG_DATA_DIRS`,
+/// `XDG_DATA_HOME`) e identifica cada base `magic` efetivamente disponível.
+pub fn database_info() -> SniffDatabaseInfo {
+    let databases: Vec<MagicDatabaseFile> = magic_search_dirs()
+        .into_iter()
+        .filter_map(|dir| {
+            let path = dir.join("magic");
+            let content = std::fs::read(&path).ok()?;
+            let shared_mime_info_version = std::fs::read_to_string(dir.join("version"))
+                .ok()
+                .map(|version| version.trim().to_string())
+                .filter(|version| !version.is_empty());
+            Some(MagicDatabaseFile {
+                path,
+                sha256: hex::encode(Sha256::digest(&content)),
+                shared_mime_info_version,
+            })
+        })
+        .collect();
+    SniffDatabaseInfo {
+        library: SNIFF_LIBRARY.to_string(),
+        library_version_req: SNIFF_LIBRARY_VERSION_REQ.to_string(),
+        source: if !databases.is_empty() {
+            "system"
+        } else if cfg!(feature = "embedded-magic") {
+            "embedded"
+        } else {
+            "none"
+        }
+        .to_string(),
+        databases,
+    }
+}
+
+fn magic_search_dirs() -> Vec<PathBuf> {
+    if let Some(dir) = var_os("TREE_MAGIC_DIR") {
+        return vec![PathBuf::from(dir)];
+    }
+    let data_dirs = match var_os("XDG_DATA_DIRS") {
+        Some(dirs) if !dirs.is_empty() => dirs,
+        _ => OsString::from("/usr/local/share/:/usr/share/"),
+    };
+    let mut dirs: Vec<PathBuf> = split_paths(&data_dirs)
+        .map(|base| base.join("mime"))
+        .collect();
+    let data_home = match var_os("XDG_DATA_HOME") {
+        Some(home) if !home.is_empty() => Some(PathBuf::from(home)),
+        _ => var_os("HOME").map(|home| Path::new(&home).join(".local/share")),
+    };
+    dirs.extend(data_home.map(|home| home.join("mime")));
+    dirs
+}
+
+/// MIME implícito por extensão, usado quando nenhum manifesto é informado.
+const BUILTIN_CLAIMED_MIMES: &[(&str, &str)] = &[
+    (".pdf", "application/pdf"),
+    (".png", "image/png"),
+    (".jpg", "image/jpeg"),
+    (".jpeg", "image/jpeg"),
+    (".gif", "image/gif"),
+    (".webp", "image/webp"),
+    (".bmp", "image/bmp"),
+    (".tif", "image/tiff"),
+    (".tiff", "image/tiff"),
+    (".svg", "image/svg+xml"),
+    (".txt", "text/plain"),
+    (".csv", "text/csv"),
+    (".md", "text/markdown"),
+    (".json", "application/json"),
+    (".xml", "application/xml"),
+    (".html", "text/html"),
+    (".htm", "text/html"),
+    (".rtf", "application/rtf"),
+    (".zip", "application/zip"),
+    (".gz", "application/gzip"),
+    (".7z", "application/x-7z-compressed"),
+    (".tar", "application/x-tar"),
+    (
+        ".docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    (
+        ".xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    (
+        ".pptx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+    (".odt", "application/vnd.oasis.opendocument.text"),
+    (".ods", "application/vnd.oasis.opendocument.spreadsheet"),
+    (".doc", "application/msword"),
+    (".xls", "application/vnd.ms-excel"),
+    (".ppt", "application/vnd.ms-powerpoint"),
+    (".msi", "application/x-msi"),
+    (".exe", "application/x-msdownload"),
+    (".dll", "application/x-msdownload"),
+    (".iso", "application/x-cd-image"),
+    (".mp3", "audio/mpeg"),
+    (".mp4", "video/mp4"),
+];
+
+/// Tabela extensão → MIME declarado (`sniff.mime_claimed`).
+#[derive(Debug, Clone)]
+pub struct ClaimedMimeMap {
+    entries: BTreeMap<String, String>,
+}
+
+impl Default for ClaimedMimeMap {
+    fn default() -> Self {
+        let entries = BUILTIN_CLAIMED_MIMES
+            .iter()
+            .map(|(ext, mime)| (ext.to_string(), mime.to_string()))
+            .collect();
+        Self { entries }
+    }
+}
+
+impl ClaimedMimeMap {
+    /// Carrega um manifesto YAML/JSON (`{".ext": "tipo/mime"}`) sobre a tabela embutida.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("falha ao abrir mapa de MIME: {}", path.display()))?;
+        Self::from_yaml_str(&raw)
+            .with_context(|| format!("mapa de MIME inválido: {}", path.display()))
+    }
+
+    /// Interpreta o manifesto a partir de texto; entradas sobrescrevem a tabela embutida.
+    pub fn from_yaml_str(raw: &str) -> Result<Self> {
+        let overrides: BTreeMap<String, String> =
+            serde_yaml::from_str(raw).context("falha ao parsear mapa de MIME")?;
+        let mut map = Self::default();
+        for (ext, mime) in overrides {
+            let ext = ext.trim().trim_start_matches('.').to_ascii_lowercase();
+            map.entries
+                .insert(format!(".{ext}"), mime.trim().to_ascii_lowercase());
+        }
+        Ok(map)
+    }
+
+    /// MIME implícito para a extensão (formato `.ext`, como em `sniff.ext`).
+    pub fn claimed_for(&self, ext: &str) -> Option<&str> {
+        self.entries
+            .get(&ext.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+/// Indica se o MIME real contradiz o MIME implícito pela extensão.
+///
+/// Tipos da mesma família (texto, contêineres ZIP, OLE, executáveis) são
+/// tolerados, pois a base de sniff nem sempre distingue subtipos.
+pub fn is_mime_mismatch(claimed: &str, real: &str) -> bool {
+    let claimed = claimed.to_ascii_lowercase();
+    let real = real.to_ascii_lowercase();
+    if claimed == real || matches!(real.as_str(), "application/x-zerosize" | "inode/x-empty") {
+        return false;
+    }
+    mime_family(&claimed) != mime_family(&real)
+}
+
+fn mime_family(mime: &str) -> &str {
+    match mime {
+        mime if mime.starts_with("text/")
+            || mime.ends_with("+xml")
+            || mime.ends_with("+json")
+            || matches!(
+                mime,
+                "application/json"
+                    | "application/xml"
+                    | "application/javascript"
+                    | "application/rtf"
+                    | "application/x-shellscript"
+            ) =>
+        {
+            "text"
+        }
+        "application/zip"
+        | "application/java-archive"
+        | "application/epub+zip"
+        | "application/vnd.android.package-archive" => "zip",
+        mime if mime.starts_with("application/vnd.openxmlformats-officedocument.")
+            || mime.starts_with("application/vnd.oasis.opendocument.") =>
+        {
+            "zip"
+        }
+        "application/msword"
+        | "application/vnd.ms-excel"
+        | "application/vnd.ms-powerpoint"
+        | "application/vnd.ms-outlook"
+        | "application/x-ole-storage"
+        | "application/x-msi" => "ole",
+        "application/x-msdownload"
+        | "application/x-dosexec"
+        | "application/x-ms-dos-executable"
+        | "application/vnd.microsoft.portable-executable" => "pe",
+        "image/x-icon" | "image/vnd.microsoft.icon" => "icon",
+        "application/x-cd-image" | "application/x-iso9660-image" => "iso",
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        _ => mime,
+    }
+}
+
+/// Janelas (bytes) das tentativas de sniff; a última tentativa usa o conteúdo inteiro.
+pub const SNIFF_WINDOWS: &[usize] = &[8 * 1024, 64 * 1024, 1024 * 1024];
+
+/// Backend das assinaturas profundas verificadas quando a base magic não decide.
+pub const DEEP_SIGNATURE_BACKEND: &str = "deep_signatures";
+/// Segunda opinião por assinaturas de cabeçalho (crate `infer`).
+pub const INFER_BACKEND: &str = "infer";
+/// Fonte dos candidatos vindos da extensão do nome (`sniff.mime_claimed`).
+pub const EXTENSION_HINT: &str = "extension";
+/// Assinaturas declaradas em `sniff.custom` na política.
+pub const CUSTOM_SIGNATURE_BACKEND: &str = "custom";
+
+/// Scores de cada fonte; fontes que concordam na família somam evidência
+/// (`1 - (1 - a)(1 - b)`).
+const SCORE_FIRST_WINDOW: f32 = 0.9;
+const SCORE_INFER: f32 = 0.8;
+const SCORE_ESCALATED: f32 = 0.7;
+const SCORE_EXTENSION: f32 = 0.3;
+const SCORE_TEXT_PLAIN: f32 = 0.3;
+const SCORE_OCTET_STREAM: f32 = 0.1;
+
+/// Resultado mínimo do sniff para integrar com o pipeline.
+#[derive(Debug, Clone)]
+pub struct SniffResult {
+    pub mime_real: String,
+    pub magic: Option<String>,
+    /// Tentativas feitas quando a primeira foi ambígua (vazio caso contrário).
+    pub escalation: Vec<SniffAttempt>,
+    /// MIMEs plausíveis: o primeiro é sempre `mime_real` (a extensão sozinha
+    /// nunca decide o tipo) e os demais seguem em ordem de score.
+    pub candidates: Vec<SniffCandidate>,
+}
+
+impl SniffResult {
+    /// Score do MIME real, de 0 a 1.
+    pub fn score(&self) -> f32 {
+        self.candidates
+            .first()
+            .map_or(0.0, |candidate| candidate.score)
+    }
+
+    /// Confiança do MIME obtido, pelas faixas de [`SniffResult::score`].
+    pub fn confidence(&self) -> SniffConfidence {
+        SniffConfidence::from_score(self.score())
+    }
+
+    /// Candidatos além do MIME real.
+    pub fn alternatives(&self) -> &[SniffCandidate] {
+        self.candidates.get(1..).unwrap_or_default()
+    }
+
+    /// Soma a extensão do nome como evidência: reforça o candidato da mesma
+    /// família ou entra como alternativa.
+    pub fn add_extension_hint(&mut self, claimed: &str) {
+        add_candidate(
+            &mut self.candidates,
+            claimed,
+            SCORE_EXTENSION,
+            EXTENSION_HINT,
+        );
+    }
+}
+
+/// MIME candidato, com score de 0 a 1 e as fontes que o apontaram.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SniffCandidate {
+    pub mime: String,
+    pub score: f32,
+    pub sources: Vec<String>,
+}
+
+/// Junta `mime` ao candidato da mesma família ou o acrescenta; o primeiro
+/// candidato não muda de posição.
+fn add_candidate(candidates: &mut Vec<SniffCandidate>, mime: &str, score: f32, source: &str) {
+    match candidates
+        .iter_mut()
+        .find(|candidate| !is_mime_mismatch(&candidate.mime, mime))
+    {
+        Some(candidate) => {
+            let combined = 1.0 - (1.0 - candidate.score) * (1.0 - score);
+            candidate.score = (combined * 100.0).round() / 100.0;
+            if !candidate.sources.iter().any(|known| known == source) {
+                candidate.sources.push(source.to_string());
+            }
+        }
+        None => candidates.push(SniffCandidate {
+            mime: mime.to_string(),
+            score,
+            sources: vec![source.to_string()],
+        }),
+    }
+    if let Some(alternatives) = candidates.get_mut(1..) {
+        alternatives.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+}
+
+/// Assinatura de `sniff.custom` já decodificada.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomSignature {
+    pub mime: String,
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+    pub mask: Option<Vec<u8>>,
+}
+
+impl CustomSignature {
+    pub fn compile(section: &CustomMagicSection) -> Result<Self> {
+        let mime = section.mime.trim().to_ascii_lowercase();
+        anyhow::ensure!(mime.contains('/'), "mime '{}' inválido", section.mime);
+        let offset = usize::try_from(section.offset).context("offset fora do intervalo")?;
+        let bytes = decode_hex(&section.bytes_hex).context("bytes_hex inválido")?;
+        anyhow::ensure!(!bytes.is_empty(), "bytes_hex vazio");
+        let mask = match &section.mask {
+            Some(raw) => {
+                let mask = decode_hex(raw).context("mask inválida")?;
+                anyhow::ensure!(
+                    mask.len() == bytes.len(),
+                    "mask com {} bytes, bytes_hex com {}",
+                    mask.len(),
+                    bytes.len()
+                );
+                Some(mask)
+            }
+            None => None,
+        };
+        Ok(Self {
+            mime,
+            offset,
+            bytes,
+            mask,
+        })
+    }
+
+    pub fn matches(&self, data: &[u8]) -> bool {
+        let Some(window) = data
+            .get(self.offset..)
+            .and_then(|rest| rest.get(..self.bytes.len()))
+        else {
+            return false;
+        };
+        match &self.mask {
+            Some(mask) => window
+                .iter()
+                .zip(&self.bytes)
+                .zip(mask)
+                .all(|((byte, expected), mask)| byte & mask == expected & mask),
+            None => window == self.bytes,
+        }
+    }
+}
+
+fn decode_hex(raw: &str) -> Result<Vec<u8>> {
+    let compact: String = raw.chars().filter(|ch| !ch.is_whitespace()).collect();
+    Ok(hex::decode(compact)?)
+}
+
+/// `high` a partir de 0,85 (primeira janela da base magic, ou fontes que
+/// concordam), `medium` a partir de 0,5 (janela ampliada, assinaturas
+/// profundas/embutidas ou só o `infer`) e `low` abaixo disso (tipo base
+/// `application/octet-stream` ou `text/plain`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SniffConfidence {
+    High,
+    Medium,
+    Low,
+}
+
+impl SniffConfidence {
+    pub fn from_score(score: f32) -> Self {
+        if score >= 0.85 {
+            SniffConfidence::High
+        } else if score >= 0.5 {
+            SniffConfidence::Medium
+        } else {
+            SniffConfidence::Low
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SniffConfidence::High => "high",
+            SniffConfidence::Medium => "medium",
+            SniffConfidence::Low => "low",
+        }
+    }
+}
+
+/// Uma tentativa de sniff: backend, bytes examinados e MIME obtido.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SniffAttempt {
+    pub backend: String,
+    pub window_bytes: u64,
+    pub mime: String,
+}
+
+/// MIMEs que indicam só o tipo base (nenhuma regra magic casou).
+pub(crate) fn is_ambiguous(mime: &str) -> bool {
+    matches!(mime, "application/octet-stream" | "text/plain")
+}
+
+/// Detecta MIME utilizando `tree_magic_mini`.
+///
+/// Começa pela menor janela de [`SNIFF_WINDOWS`] e amplia enquanto o resultado
+/// for ambíguo (`application/octet-stream` ou `text/plain`), até o conteúdo
+/// inteiro; binários ainda sem tipo passam pelas assinaturas profundas (TAR e
+/// imagens de disco) e pelo `infer`. Sem base magic no sistema, a feature
+/// `embedded-magic` consulta antes a tabela embutida. As tentativas ficam em
+/// [`SniffResult::escalation`]; um palpite do `infer` que diverge do tipo
+/// obtido entra só como candidato em [`SniffResult::candidates`].
+pub fn sniff_bytes(data: &[u8]) -> Result<SniffResult> {
+    sniff_bytes_with(data, &[])
+}
+
+/// Igual a [`sniff_bytes`], com as assinaturas de `sniff.custom` verificadas
+/// antes da base magic.
+pub fn sniff_bytes_with(data: &[u8], custom: &[CustomSignature]) -> Result<SniffResult> {
+    let (mime, score, source, mut attempts) =
+        match custom.iter().find(|signature| signature.matches(data)) {
+            Some(signature) => (
+                signature.mime.clone(),
+                SCORE_FIRST_WINDOW,
+                CUSTOM_SIGNATURE_BACKEND,
+                Vec::new(),
+            ),
+            None => detect(data),
+        };
+    let mut candidates = vec![SniffCandidate {
+        mime: mime.clone(),
+        score,
+        sources: vec![source.to_string()],
+    }];
+    if source != INFER_BACKEND {
+        if let Some(found) = infer::get(data) {
+            add_candidate(
+                &mut candidates,
+                found.mime_type(),
+                SCORE_INFER,
+                INFER_BACKEND,
+            );
+        }
+    }
+    if attempts.len() == 1 {
+        attempts.clear();
+    }
+    let magic = data.get(0..8).map(|slice| {
+        slice
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<Vec<_>>()
+            .join(" ")
+    });
+    Ok(SniffResult {
+        mime_real: mime,
+        magic,
+        escalation: attempts,
+        candidates,
+    })
+}
+
+/// MIME pela base magic e, se ambíguo, pelos demais backends: tipo, score,
+/// backend que decidiu e tentativas.
+fn detect(data: &[u8]) -> (String, f32, &'static str, Vec<SniffAttempt>) {
+    let mut attempts = Vec::new();
+    let windows = SNIFF_WINDOWS
+        .iter()
+        .copied()
+        .filter(|window| *window < data.len())
+        .chain(std::iter::once(data.len()));
+    for window in windows {
+        let mime = from_u8(&data[..window]).to_string();
+        let ambiguous = is_ambiguous(&mime);
+        attempts.push(SniffAttempt {
+            backend: SNIFF_LIBRARY.to_string(),
+            window_bytes: window as u64,
+            mime,
+        });
+        if !ambiguous {
+            break;
+        }
+    }
+    let mut mime = attempts
+        .last()
+        .map(|attempt| attempt.mime.clone())
+        .unwrap_or_default();
+    let mut score = match mime.as_str() {
+        "application/octet-stream" => SCORE_OCTET_STREAM,
+        "text/plain" => SCORE_TEXT_PLAIN,
+        _ if attempts.len() == 1 => SCORE_FIRST_WINDOW,
+        _ => SCORE_ESCALATED,
+    };
+    let mut source = SNIFF_LIBRARY;
+    #[cfg(feature = "embedded-magic")]
+    if is_ambiguous(&mime) && embedded::is_active() {
+        if let Some(found) = embedded_mime(data) {
+            attempts.push(SniffAttempt {
+                backend: EMBEDDED_SIGNATURE_BACKEND.to_string(),
+                window_bytes: data.len() as u64,
+                mime: found.to_string(),
+            });
+            mime = found.to_string();
+            score = SCORE_ESCALATED;
+            source = EMBEDDED_SIGNATURE_BACKEND;
+        }
+    }
+    if mime == "application/octet-stream" {
+        if let Some(deep) = deep_signature_mime(data) {
+            attempts.push(SniffAttempt {
+                backend: DEEP_SIGNATURE_BACKEND.to_string(),
+                window_bytes: data.len() as u64,
+                mime: deep.to_string(),
+            });
+            mime = deep.to_string();
+            score = SCORE_ESCALATED;
+            source = DEEP_SIGNATURE_BACKEND;
+        }
+    }
+    if mime == "application/octet-stream" {
+        if let Some(found) = infer::get(data) {
+            attempts.push(SniffAttempt {
+                backend: INFER_BACKEND.to_string(),
+                window_bytes: data.len() as u64,
+                mime: found.mime_type().to_string(),
+            });
+            mime = found.mime_type().to_string();
+            score = SCORE_INFER;
+            source = INFER_BACKEND;
+        }
+    }
+    (mime, score, source, attempts)
+}
+
+/// Formatos com assinatura longe do início (TAR em 257, ISO em 32 KiB, rodapés
+/// de VHD/DMG) ou em estruturas internas (MSI, FAT).
+fn deep_signature_mime(data: &[u8]) -> Option<&'static str> {
+    if data.get(257..262) == Some(b"ustar") {
+        return Some("application/x-tar");
+    }
+    let mime = match detect_disk_image(data)? {
+        DiskImageKind::Iso9660 => "application/x-iso9660-image",
+        DiskImageKind::Udf => "application/x-udf-image",
+        DiskImageKind::Vhd => "application/x-vhd",
+        DiskImageKind::Vhdx => "application/x-vhdx",
+        DiskImageKind::Vmdk => "application/x-vmdk",
+        DiskImageKind::Dmg => "application/x-apple-diskimage",
+        DiskImageKind::FatImage => "application/x-raw-disk-image",
+        DiskImageKind::Msi => "application/x-msi",
+    };
+    Some(mime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff_bytes_detects_text_plain() {
+        let data = b"Hello world\n";
+        let result = sniff_bytes(data).expect("sniff should succeed");
+        assert_eq!(result.mime_real, "text/plain");
+        assert_eq!(result.magic.as_deref(), Some("48 65 6C 6C 6F 20 77 6F"));
+    }
+
+    #[test]
+    fn ambiguous_results_escalate_to_larger_windows_and_deep_signatures() {
+        let mut iso = vec![0u8; 40 * 1024];
+        iso[0x8000] = 1;
+        iso[0x8001..0x8006].copy_from_slice(b"CD001");
+        let result = sniff_bytes(&iso).expect("sniff should succeed");
+        assert_ne!(result.mime_real, "application/octet-stream");
+        assert_eq!(result.escalation[0].window_bytes, 8 * 1024);
+        assert_eq!(result.escalation[0].mime, "application/octet-stream");
+        assert_eq!(
+            result
+                .escalation
+                .last()
+                .map(|attempt| attempt.mime.as_str()),
+            Some(result.mime_real.as_str())
+        );
+
+        let mut vhd = vec![0u8; 4096];
+        vhd[4096 - 512..4096 - 504].copy_from_slice(b"conectix");
+        let result = sniff_bytes(&vhd).expect("sniff should succeed");
+        assert_eq!(result.mime_real, "application/x-vhd");
+        assert_eq!(
+            result
+                .escalation
+                .last()
+                .map(|attempt| attempt.backend.as_str()),
+            Some(DEEP_SIGNATURE_BACKEND)
+        );
+        assert_eq!(result.confidence(), SniffConfidence::Medium);
+
+        let pdf = sniff_bytes(b"%PDF-1.4\n").expect("sniff should succeed");
+        assert!(pdf.escalation.is_empty());
+        assert_eq!(pdf.confidence(), SniffConfidence::High);
+        let text = sniff_bytes(b"Hello world\n").expect("sniff should succeed");
+        assert_eq!(text.confidence(), SniffConfidence::Low);
+    }
+
+    #[test]
+    fn candidates_combine_backends_and_extension_hints() {
+        let mut pdf = sniff_bytes(b"%PDF-1.4\n%%EOF\n").expect("sniff should succeed");
+        assert_eq!(pdf.candidates.len(), 1);
+        assert_eq!(pdf.candidates[0].mime, "application/pdf");
+        assert_eq!(pdf.candidates[0].sources, [SNIFF_LIBRARY, INFER_BACKEND]);
+        assert_eq!(pdf.score(), 0.98);
+        pdf.add_extension_hint("application/pdf");
+        assert_eq!(
+            pdf.candidates[0].sources.last().map(String::as_str),
+            Some(EXTENSION_HINT)
+        );
+
+        // Binário sem assinatura com nome `.png`: a extensão vira alternativa.
+        let mut unknown = sniff_bytes(&[0x00, 0xA5].repeat(32)).expect("sniff should succeed");
+        unknown.add_extension_hint("image/png");
+        assert_eq!(unknown.mime_real, "application/octet-stream");
+        assert_eq!(unknown.confidence(), SniffConfidence::Low);
+        assert_eq!(
+            unknown.alternatives(),
+            [SniffCandidate {
+                mime: "image/png".into(),
+                score: 0.3,
+                sources: vec![EXTENSION_HINT.into()],
+            }]
+        );
+    }
+
+    #[test]
+    fn custom_signatures_take_precedence_over_magic() {
+        let raw = "sniff:\n  custom:\n    - mime: application/x-acme-ledger\n      offset: 9\n      bytes_hex: \"41 43 4D 00\"\n      mask: \"FF FF FF F0\"\n";
+        let policy = crate::config::PolicyConfig::from_yaml_str(raw).expect("policy should parse");
+        let custom: Vec<CustomSignature> = policy
+            .sniff
+            .custom
+            .iter()
+            .map(|section| CustomSignature::compile(section).expect("signature"))
+            .collect();
+
+        let data = b"%PDF-1.4\nACM\x07 ledger";
+        assert_eq!(
+            sniff_bytes(data).expect("sniff").mime_real,
+            "application/pdf"
+        );
+        let result = sniff_bytes_with(data, &custom).expect("sniff should succeed");
+        assert_eq!(result.mime_real, "application/x-acme-ledger");
+        assert_eq!(result.candidates[0].sources, [CUSTOM_SIGNATURE_BACKEND]);
+        assert_eq!(result.alternatives()[0].mime, "application/pdf");
+        assert!(!custom[0].matches(b"%PDF-1.4\nACMX"));
+        assert!(!custom[0].matches(b"%PDF-1.4\nAC"));
+
+        let short_mask = raw.replace("FF FF FF F0", "FF FF");
+        assert!(crate::config::PolicyConfig::from_yaml_str(&short_mask).is_err());
+        let bad_hex = raw.replace("41 43 4D 00", "41 4");
+        assert!(crate::config::PolicyConfig::from_yaml_str(&bad_hex).is_err());
+    }
+
+    #[test]
+    fn database_info_is_consistent_with_source() {
+        let info = database_info();
+        assert_eq!(info.library, SNIFF_LIBRARY);
+        assert_eq!(info.source != "system", info.databases.is_empty());
+        for database in &info.databases {
+            assert!(database.path.ends_with("mime/magic") || database.path.ends_with("magic"));
+            assert_eq!(database.sha256.len(), 64);
+        }
+    }
+
+    #[test]
+    fn claimed_mime_map_merges_manifest_and_detects_mismatch() {
+        let map =
+            ClaimedMimeMap::from_yaml_str("dat: application/x-custom\n.PDF: application/pdf\n")
+                .expect("manifest should parse");
+        assert_eq!(map.claimed_for(".dat"), Some("application/x-custom"));
+        assert_eq!(map.claimed_for(".PDF"), Some("application/pdf"));
+        assert_eq!(map.claimed_for(".unknown"), None);
+
+        assert!(is_mime_mismatch(
+            "application/pdf",
+            "application/x-msdownload"
+        ));
+        assert!(!is_mime_mismatch("text/csv", "text/plain"));
+        assert!(!is_mime_mismatch(
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            "application/zip"
+        ));
+        assert!(!is_mime_mismatch(
+            "application/x-msdownload",
+            "application/x-dosexec"
+        ));
+    }
+}
@@ -0,0 +1,127 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Assinaturas embutidas para hosts sem a base shared-mime-info.
+//!
+//! Sem o arquivo `magic` do sistema, o `tree_magic_mini` só distingue texto de
+//! binário. Esta tabela cobre os formatos que o pipeline valida (documentos,
+//! imagens, containers e executáveis) com os mesmos MIMEs da base do sistema.
+
+use std::sync::OnceLock;
+
+/// Backend registrado nas tentativas de sniff resolvidas por esta tabela.
+pub const EMBEDDED_SIGNATURE_BACKEND: &str = "embedded_signatures";
+
+/// Partes `(offset, bytes)` que precisam casar, e o MIME resultante.
+type Signature = (&'static [(usize, &'static [u8])], &'static str);
+
+/// Ordem importa: assinaturas mais específicas antes das genéricas.
+const SIGNATURES: &[Signature] = &[
+    (&[(0, b"%PDF-")], "application/pdf"),
+    (&[(0, b"\x89PNG\r\n\x1a\n")], "image/png"),
+    (&[(0, b"\xFF\xD8\xFF")], "image/jpeg"),
+    (&[(0, b"GIF87a")], "image/gif"),
+    (&[(0, b"GIF89a")], "image/gif"),
+    (&[(0, b"RIFF"), (8, b"WEBP")], "image/webp"),
+    (&[(0, b"RIFF"), (8, b"WAVE")], "audio/x-wav"),
+    (&[(0, b"RIFF"), (8, b"AVI ")], "video/x-msvideo"),
+    (&[(0, b"II*\0")], "image/tiff"),
+    (&[(0, b"MM\0*")], "image/tiff"),
+    (&[(0, b"\0\0\x01\0")], "image/vnd.microsoft.icon"),
+    (&[(0, b"BM"), (6, b"\0\0\0\0")], "image/bmp"),
+    (&[(4, b"ftypheic")], "image/heif"),
+    (&[(4, b"ftypavif")], "image/avif"),
+    (&[(4, b"ftyp")], "video/mp4"),
+    (&[(0, b"\x1A\x45\xDF\xA3")], "video/x-matroska"),
+    (&[(0, b"OggS")], "audio/ogg"),
+    (&[(0, b"fLaC")], "audio/flac"),
+    (&[(0, b"ID3")], "audio/mpeg"),
+    (&[(0, b"PK\x03\x04")], "application/zip"),
+    (&[(0, b"PK\x05\x06")], "application/zip"),
+    (&[(0, b"\x1F\x8B")], "application/gzip"),
+    (&[(0, b"7z\xBC\xAF\x27\x1C")], "application/x-7z-compressed"),
+    (&[(0, b"Rar!\x1A\x07")], "application/vnd.rar"),
+    (&[(0, b"BZh")], "application/x-bzip2"),
+    (&[(0, b"\xFD7zXZ\0")], "application/x-xz"),
+    (&[(0, b"\x28\xB5\x2F\xFD")], "application/zstd"),
+    (&[(257, b"ustar")], "application/x-tar"),
+    (
+        &[(0, b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1")],
+        "application/x-ole-storage",
+    ),
+    (&[(0, b"MZ")], "application/x-msdownload"),
+    (&[(0, b"\x7FELF")], "application/x-executable"),
+    (&[(0, b"\xFE\xED\xFA\xCE")], "application/x-mach-binary"),
+    (&[(0, b"\xFE\xED\xFA\xCF")], "application/x-mach-binary"),
+    (&[(0, b"\xCE\xFA\xED\xFE")], "application/x-mach-binary"),
+    (&[(0, b"\xCF\xFA\xED\xFE")], "application/x-mach-binary"),
+    (&[(0, b"\0asm")], "application/wasm"),
+    (&[(0, b"SQLite format 3\0")], "application/vnd.sqlite3"),
+    (&[(0, b"{\\rtf")], "application/rtf"),
+    (&[(0, b"%!PS")], "application/postscript"),
+    (&[(0, b"<svg")], "image/svg+xml"),
+    (&[(0, b"<?xml")], "application/xml"),
+    (&[(0, b"<!DOCTYPE html")], "text/html"),
+    (&[(0, b"<html")], "text/html"),
+    (&[(0, b"#!")], "application/x-shellscript"),
+];
+
+/// Janela em que um `<svg` após o prólogo XML identifica SVG.
+const SVG_WINDOW: usize = 1024;
+
+/// MIME pela tabela embutida; `None` quando nenhuma assinatura casa.
+pub fn embedded_mime(data: &[u8]) -> Option<&'static str> {
+    let mime = SIGNATURES
+        .iter()
+        .find(|(parts, _)| {
+            parts
+                .iter()
+                .all(|(offset, bytes)| data.get(*offset..offset + bytes.len()) == Some(*bytes))
+        })
+        .map(|(_, mime)| *mime)?;
+    if mime == "application/xml"
+        && data[..data.len().min(SVG_WINDOW)]
+            .windows(4)
+            .any(|window| window == b"<svg")
+    {
+        return Some("image/svg+xml");
+    }
+    Some(mime)
+}
+
+/// A tabela só é consultada quando nenhum arquivo `magic` do sistema existe.
+pub fn is_active() -> bool {
+    static ACTIVE: OnceLock<bool> = OnceLock::new();
+    *ACTIVE.get_or_init(|| {
+        !super::magic_search_dirs()
+            .iter()
+            .any(|dir| dir.join("magic").is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_signatures_cover_validated_formats() {
+        assert_eq!(embedded_mime(b"%PDF-1.7\n"), Some("application/pdf"));
+        assert_eq!(embedded_mime(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(embedded_mime(b"PK\x03\x04\x14\0"), Some("application/zip"));
+        assert_eq!(embedded_mime(b"MZ\x90\0"), Some("application/x-msdownload"));
+        assert_eq!(
+            embedded_mime(b"<?xml version=\"1.0\"?>\n<svg xmlns=\"\"/>"),
+            Some("image/svg+xml")
+        );
+        assert_eq!(
+            embedded_mime(b"<?xml version=\"1.0\"?><a/>"),
+            Some("application/xml")
+        );
+        let mut tar = vec![0u8; 512];
+        tar[257..262].copy_from_slice(b"ustar");
+        assert_eq!(embedded_mime(&tar), Some("application/x-tar"));
+        assert_eq!(embedded_mime(b"texto qualquer"), None);
+    }
+}
@@ -0,0 +1,243 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Codificações de transferência (base64, quoted-printable) usadas por e-mail e
+//! por integrações que gravam uploads já codificados.
+//!
+//! A detecção é conservadora: base64 exige só caracteres do alfabeto, linhas de
+//! tamanho uniforme e comprimento múltiplo de 4; quoted-printable exige quebras
+//! suaves (`=\n`) e escapes `=XX` válidos. O pipeline ainda descarta a detecção
+//! de base64 quando o conteúdo decodificado não tem tipo reconhecido.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Mínimo de caracteres para considerar um conteúdo base64 sem declaração.
+const MIN_DETECTED_BASE64: usize = 64;
+/// Comprimento máximo de linha do quoted-printable (RFC 2045), sem o CRLF.
+const QP_MAX_LINE: usize = 76;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransferEncoding {
+    Base64,
+    QuotedPrintable,
+}
+
+impl TransferEncoding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TransferEncoding::Base64 => "base64",
+            TransferEncoding::QuotedPrintable => "quoted-printable",
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TransferError {
+    #[error("conteúdo {encoding} inválido: {reason}")]
+    Invalid {
+        encoding: &'static str,
+        reason: String,
+    },
+    #[error("conteúdo decodificado excede o limite de {limit} bytes")]
+    LimitExceeded { limit: u64 },
+}
+
+/// Codificação aparente do conteúdo, se houver.
+pub fn detect_transfer_encoding(data: &[u8]) -> Option<TransferEncoding> {
+    if looks_like_base64(data) {
+        Some(TransferEncoding::Base64)
+    } else if looks_like_quoted_printable(data) {
+        Some(TransferEncoding::QuotedPrintable)
+    } else {
+        None
+    }
+}
+
+/// Decodifica `data`, recusando saídas maiores que `max_bytes`.
+pub fn decode_transfer(
+    data: &[u8],
+    encoding: TransferEncoding,
+    max_bytes: u64,
+) -> Result<Vec<u8>, TransferError> {
+    match encoding {
+        TransferEncoding::Base64 => decode_base64(data, max_bytes),
+        TransferEncoding::QuotedPrintable => decode_quoted_printable(data, max_bytes),
+    }
+}
+
+fn is_base64_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'+' | b'/' | b'=')
+}
+
+fn looks_like_base64(data: &[u8]) -> bool {
+    let start = data
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    let end = data
+        .iter()
+        .rposition(|byte| !byte.is_ascii_whitespace())
+        .map_or(start, |last| last + 1);
+    let lines: Vec<&[u8]> = data[start..end]
+        .split(|byte| *byte == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .collect();
+    let Some((last, body)) = lines.split_last() else {
+        return false;
+    };
+    // Linhas quebradas (MIME usa 76) têm o mesmo tamanho, exceto a última.
+    let uniform = match body.first() {
+        Some(first) => {
+            body.iter().all(|line| line.len() == first.len()) && last.len() <= first.len()
+        }
+        None => true,
+    };
+    let compact: Vec<u8> = lines.concat();
+    let padding = compact
+        .iter()
+        .rev()
+        .take_while(|byte| **byte == b'=')
+        .count();
+    uniform
+        && compact.len() >= MIN_DETECTED_BASE64
+        && compact.chunks_exact(4).remainder().is_empty()
+        && padding <= 2
+        && compact.iter().all(|byte| is_base64_char(*byte))
+        && !compact[..compact.len() - padding].contains(&b'=')
+}
+
+fn looks_like_quoted_printable(data: &[u8]) -> bool {
+    if !data
+        .iter()
+        .all(|byte| matches!(byte, b'\t' | b'\r' | b'\n' | 0x20..=0x7E))
+    {
+        return false;
+    }
+    let mut soft_breaks = 0;
+    let mut escapes = 0;
+    for line in data.split(|byte| *byte == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.len() > QP_MAX_LINE {
+            return false;
+        }
+        let mut pos = 0;
+        while let Some(offset) = line[pos..].iter().position(|byte| *byte == b'=') {
+            pos += offset;
+            match line.get(pos + 1..pos + 3) {
+                None if pos + 1 == line.len() => soft_breaks += 1,
+                Some(hex) if hex.iter().all(|byte| byte.is_ascii_hexdigit()) => escapes += 1,
+                _ => return false,
+            }
+            pos += 1;
+        }
+    }
+    soft_breaks > 0 && escapes > 0
+}
+
+fn decode_base64(data: &[u8], max_bytes: u64) -> Result<Vec<u8>, TransferError> {
+    use base64::Engine as _;
+
+    let compact: Vec<u8> = data
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    if (compact.len() as u64 / 4).saturating_mul(3) > max_bytes {
+        return Err(TransferError::LimitExceeded { limit: max_bytes });
+    }
+    base64::engine::general_purpose::STANDARD
+        .decode(compact)
+        .map_err(|err| TransferError::Invalid {
+            encoding: TransferEncoding::Base64.as_str(),
+            reason: err.to_string(),
+        })
+}
+
+fn decode_quoted_printable(data: &[u8], max_bytes: u64) -> Result<Vec<u8>, TransferError> {
+    let invalid = |reason: String| TransferError::Invalid {
+        encoding: TransferEncoding::QuotedPrintable.as_str(),
+        reason,
+    };
+    let mut decoded = Vec::with_capacity(data.len().min(max_bytes as usize));
+    let mut pos = 0;
+    while pos < data.len() {
+        if decoded.len() as u64 >= max_bytes {
+            return Err(TransferError::LimitExceeded { limit: max_bytes });
+        }
+        if data[pos] != b'=' {
+            decoded.push(data[pos]);
+            pos += 1;
+            continue;
+        }
+        match data.get(pos + 1..) {
+            Some([b'\r', b'\n', ..]) => pos += 3,
+            Some([b'\n', ..]) => pos += 2,
+            Some([]) => pos += 1,
+            Some([high, low, ..]) if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => {
+                let hex = [*high, *low];
+                let text = std::str::from_utf8(&hex).expect("dígitos hexadecimais");
+                decoded.push(u8::from_str_radix(text, 16).expect("dígitos hexadecimais"));
+                pos += 3;
+            }
+            _ => return Err(invalid(format!("escape '=' inválido no offset {pos}"))),
+        }
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine as _;
+
+    #[test]
+    fn detects_and_decodes_wrapped_base64() {
+        let pdf = b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\ntrailer\n%%EOF\n".repeat(2);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&pdf);
+        let wrapped: Vec<u8> = encoded
+            .as_bytes()
+            .chunks(76)
+            .flat_map(|line| [line, b"\r\n"].concat())
+            .collect();
+        assert_eq!(
+            detect_transfer_encoding(&wrapped),
+            Some(TransferEncoding::Base64)
+        );
+        assert_eq!(
+            decode_transfer(&wrapped, TransferEncoding::Base64, 1024).expect("decode"),
+            pdf
+        );
+        assert!(matches!(
+            decode_transfer(&wrapped, TransferEncoding::Base64, 16),
+            Err(TransferError::LimitExceeded { limit: 16 })
+        ));
+
+        assert_eq!(detect_transfer_encoding(b"texto simples com espacos"), None);
+        assert_eq!(detect_transfer_encoding(b"abcd"), None);
+    }
+
+    #[test]
+    fn detects_and_decodes_quoted_printable() {
+        let encoded =
+            b"<html><body>Ol=C3=A1, mundo! Esta linha =\r\ncontinua aqui.</body></html>\r\n";
+        assert_eq!(
+            detect_transfer_encoding(encoded),
+            Some(TransferEncoding::QuotedPrintable)
+        );
+        let decoded =
+            decode_transfer(encoded, TransferEncoding::QuotedPrintable, 1024).expect("decode");
+        assert_eq!(
+            decoded,
+            "<html><body>Olá, mundo! Esta linha continua aqui.</body></html>\r\n".as_bytes()
+        );
+        assert!(matches!(
+            decode_transfer(b"a=ZZ", TransferEncoding::QuotedPrintable, 1024),
+            Err(TransferError::Invalid { .. })
+        ));
+        assert_eq!(detect_transfer_encoding(b"a = b\nc = d\n"), None);
+    }
+}
@@ -0,0 +1,1088 @@
+// GuardUpload
+// Criado em: 2025-11-01
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Validador básico de arquivos ZIP.
+
+use super::{Findings, ValidatorOutcome};
+use crate::config::{ArchiveMetadataChecks, ArchivePolicySection};
+use crate::decompress::{
+    default_backend, read_zip_entry, read_zip_entry_head, DecompressError, DecompressLimits,
+};
+use crate::limits::Deadline;
+use crate::policy::ResolvedPolicy;
+use crate::sniff::sniff_bytes;
+use flate2::Crc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use time::{Date, Month, OffsetDateTime};
+use zip::read::ZipFile;
+use zip::ZipArchive;
+
+/// Orçamento de descompressão de `verify_crc` sem `max_total_uncompressed_mb`.
+const DEFAULT_CRC_BUDGET_BYTES: u64 = 256 * 1024 * 1024;
+/// Limite de `details.index` sem `archive.index_max_entries`.
+const DEFAULT_INDEX_MAX_ENTRIES: u32 = 1000;
+/// Bytes iniciais descomprimidos de cada entrada para o MIME do índice.
+const INDEX_SNIFF_BYTES: u64 = 8 * 1024;
+
+/// Entrada do inventário (`archive.index_entries`).
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct IndexEntry {
+    pub(crate) name: String,
+    pub(crate) size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) compressed_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) ratio: Option<f64>,
+    /// MIME do início do conteúdo; ausente quando a entrada não foi descomprimida.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) mime: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) is_dir: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) encrypted: bool,
+}
+
+/// Máximo de entradas do inventário, ou `None` quando ele está desligado.
+pub(crate) fn index_limit(policy: &ArchivePolicySection) -> Option<usize> {
+    policy.index_entries.unwrap_or(false).then(|| {
+        policy
+            .index_max_entries
+            .unwrap_or(DEFAULT_INDEX_MAX_ENTRIES) as usize
+    })
+}
+
+/// Campos `index` e `index_truncated` dos detalhes.
+pub(crate) fn index_details(index: &[IndexEntry], total_entries: usize) -> Value {
+    json!({
+        "index": index,
+        "index_truncated": total_entries > index.len(),
+    })
+}
+
+pub fn validate_archive(
+    mime: &str,
+    data: &[u8],
+    policy: Option<&ResolvedPolicy>,
+    deadline: &Deadline,
+) -> ValidatorOutcome {
+    let name = "archive";
+    let archive_policy = policy.map(|p| p.archive.clone()).unwrap_or_default();
+    let cursor = Cursor::new(data);
+
+    let mut archive = match ZipArchive::new(cursor) {
+        Ok(archive) => archive,
+        Err(err) => {
+            return ValidatorOutcome::error(name, format!("arquivo ZIP inválido ({mime}): {err}"))
+        }
+    };
+
+    let mut findings = Findings::new(policy);
+    let mut total_ratio = 0.0f64;
+    let mut worst_ratio = 0.0f64;
+    let mut file_count = 0usize;
+    let mut encrypted_entries = Vec::new();
+    let mut entry_names = Vec::new();
+    let mut spans = Vec::new();
+    let mut normalized_names = BTreeMap::new();
+    let mut declared_total = 0u64;
+    let max_total_bytes = archive_policy
+        .max_total_uncompressed_mb
+        .map(|mb| mb.saturating_mul(1024 * 1024));
+    let forbid_overlapping = archive_policy.forbid_overlapping_entries.unwrap_or(true);
+    let today = OffsetDateTime::now_utc().date();
+
+    // Progresso anexado quando a varredura é interrompida antes do fim.
+    let total_entries = archive.len();
+    let progress = |file_count: usize, encrypted_entries: &[String], worst_ratio: f64| {
+        json!({
+            "mime": mime,
+            "entries": file_count,
+            "total_entries": total_entries,
+            "encrypted_entries": encrypted_entries,
+            "worst_ratio": worst_ratio,
+        })
+    };
+
+    if let Some(max_entries) = archive_policy.max_entries {
+        if total_entries > max_entries as usize
+            && findings.push(format!(
+                "ZIP possui {total_entries} entradas, acima de archive.max_entries ({max_entries})"
+            ))
+        {
+            return findings.into_deny(name).with_partial_details(progress(
+                file_count,
+                &encrypted_entries,
+                worst_ratio,
+            ));
+        }
+    }
+
+    for i in 0..total_entries {
+        if deadline.is_expired() {
+            return ValidatorOutcome::timeout(name).with_partial_details(progress(
+                file_count,
+                &encrypted_entries,
+                worst_ratio,
+            ));
+        }
+        let file = match archive.by_index_raw(i) {
+            Ok(file) => file,
+            Err(err) => {
+                return ValidatorOutcome::deny(name, format!("falha ao ler entrada do ZIP: {err}"))
+            }
+        };
+        file_count += 1;
+        if !file.is_dir() {
+            entry_names.push(file.name().to_string());
+        }
+        spans.push((
+            file.header_start(),
+            file.data_start().saturating_add(file.compressed_size()),
+            file.name().to_string(),
+        ));
+
+        if forbid_overlapping {
+            let duplicate =
+                normalized_names.insert(normalize_entry_name(file.name()), file.name().to_string());
+            if let Some(previous) = duplicate {
+                if findings.push(format!(
+                    "entrada '{}' duplica o nome de '{previous}'",
+                    file.name()
+                )) {
+                    return findings.into_deny(name).with_partial_details(progress(
+                        file_count,
+                        &encrypted_entries,
+                        worst_ratio,
+                    ));
+                }
+            }
+        }
+
+        let before = declared_total;
+        declared_total = declared_total.saturating_add(file.size());
+        if let Some(max_bytes) = max_total_bytes {
+            if declared_total > max_bytes
+                && before <= max_bytes
+                && findings.push(format!(
+                    "soma descomprimida das entradas excede archive.max_total_uncompressed_mb ({declared_total} > {max_bytes} bytes)"
+                ))
+            {
+                return findings.into_deny(name).with_partial_details(progress(
+                    file_count,
+                    &encrypted_entries,
+                    worst_ratio,
+                ));
+            }
+        }
+
+        if is_encrypted(data, &file) {
+            encrypted_entries.push(file.name().to_string());
+        }
+
+        if let Some(issue) = entry_violation(&file, &archive_policy) {
+            if findings.push(issue) {
+                return findings.into_deny(name).with_partial_details(progress(
+                    file_count,
+                    &encrypted_entries,
+                    worst_ratio,
+                ));
+            }
+        }
+
+        for issue in metadata_anomalies(data, &file, &archive_policy.metadata_checks, today) {
+            if findings.push(issue) {
+                return findings.into_deny(name).with_partial_details(progress(
+                    file_count,
+                    &encrypted_entries,
+                    worst_ratio,
+                ));
+            }
+        }
+
+        if let Some(ratio) = compression_ratio(&file) {
+            total_ratio += ratio;
+            if ratio > worst_ratio {
+                worst_ratio = ratio;
+            }
+            if let Some(max_ratio) = archive_policy.zip_max_ratio {
+                if ratio > max_ratio as f64
+                    && findings.push(format!(
+                        "entrada '{}' excede zip_max_ratio (ratio={ratio:.2} > {max_ratio})",
+                        file.name()
+                    ))
+                {
+                    return findings.into_deny(name).with_partial_details(progress(
+                        file_count,
+                        &encrypted_entries,
+                        worst_ratio,
+                    ));
+                }
+            }
+        }
+
+        if let Some(max_depth) = archive_policy.zip_max_depth {
+            let depth = depth_of(file.name());
+            if depth > max_depth as usize
+                && findings.push(format!(
+                    "profundidade excede limite ({depth} > {max_depth}) na entrada '{}'",
+                    file.name()
+                ))
+            {
+                return findings.into_deny(name).with_partial_details(progress(
+                    file_count,
+                    &encrypted_entries,
+                    worst_ratio,
+                ));
+            }
+        }
+    }
+
+    if forbid_overlapping {
+        for issue in overlapping_entries(&mut spans) {
+            if findings.push(issue) {
+                return findings.into_deny(name).with_partial_details(progress(
+                    file_count,
+                    &encrypted_entries,
+                    worst_ratio,
+                ));
+            }
+        }
+    }
+
+    let mut crc_verified = None;
+    if archive_policy.verify_crc.unwrap_or(false) {
+        let budget = max_total_bytes.unwrap_or(DEFAULT_CRC_BUDGET_BYTES);
+        let check = verify_crcs(&mut archive, data, budget, deadline);
+        if check.timed_out {
+            return ValidatorOutcome::timeout(name).with_partial_details(progress(
+                file_count,
+                &encrypted_entries,
+                worst_ratio,
+            ));
+        }
+        for issue in check.issues {
+            if findings.push(issue) {
+                return findings.into_deny(name).with_partial_details(progress(
+                    file_count,
+                    &encrypted_entries,
+                    worst_ratio,
+                ));
+            }
+        }
+        crc_verified = Some(check.verified);
+    }
+
+    if !encrypted_entries.is_empty()
+        && archive_policy.forbid_encrypted_entries.unwrap_or(false)
+        && findings.push(format!(
+            "ZIP possui entradas criptografadas: {}",
+            encrypted_entries.join(", ")
+        ))
+    {
+        let mut outcome = findings.into_deny(name);
+        outcome.details["encrypted_entries"] = json!(encrypted_entries);
+        return outcome;
+    }
+
+    let (missing, unexpected) = manifest_violations(&entry_names, &archive_policy);
+    if !missing.is_empty() || !unexpected.is_empty() {
+        let mut problems = Vec::new();
+        if !missing.is_empty() {
+            problems.push(format!(
+                "entradas obrigatórias ausentes: {}",
+                missing.join(", ")
+            ));
+        }
+        if !unexpected.is_empty() {
+            problems.push(format!(
+                "entradas não permitidas: {}",
+                unexpected.join(", ")
+            ));
+        }
+        if findings.push(format!("ZIP fora do manifesto ({})", problems.join("; "))) {
+            let mut outcome = findings.into_deny(name);
+            outcome.details["missing_entries"] = json!(missing);
+            outcome.details["unexpected_entries"] = json!(unexpected);
+            return outcome;
+        }
+    }
+    let index = index_limit(&archive_policy).map(|limit| {
+        index_details(
+            &build_index(&mut archive, data, limit, deadline),
+            total_entries,
+        )
+    });
+    if !findings.is_empty() {
+        let mut outcome = findings
+            .into_deny(name)
+            .with_details(progress(file_count, &encrypted_entries, worst_ratio))
+            .with_details(json!({
+                "missing_entries": missing,
+                "unexpected_entries": unexpected,
+            }));
+        if let Some(index) = index {
+            outcome = outcome.with_details(index);
+        }
+        return outcome;
+    }
+
+    let mut outcome = ValidatorOutcome::pass(name);
+    outcome.details = json!({
+        "mime": mime,
+        "entries": file_count,
+        "encrypted_entries": encrypted_entries,
+        "avg_ratio": if file_count > 0 {
+            Some(total_ratio / file_count as f64)
+        } else {
+            None
+        },
+        "worst_ratio": if file_count > 0 { Some(worst_ratio) } else { None },
+        "total_uncompressed_bytes": declared_total,
+    });
+    if let Some(verified) = crc_verified {
+        outcome.details["crc_verified_entries"] = json!(verified);
+    }
+    if let Some(index) = index {
+        outcome = outcome.with_details(index);
+    }
+    outcome
+}
+
+/// Inventário das primeiras `limit` entradas; o MIME vem do início descomprimido
+/// de cada arquivo não cifrado e fica ausente após o prazo ou em métodos sem suporte.
+fn build_index(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    data: &[u8],
+    limit: usize,
+    deadline: &Deadline,
+) -> Vec<IndexEntry> {
+    let mut index = Vec::new();
+    for position in 0..archive.len().min(limit) {
+        let mut entry = match archive.by_index_raw(position) {
+            Ok(file) => IndexEntry {
+                name: file.name().to_string(),
+                size: file.size(),
+                compressed_size: Some(file.compressed_size()),
+                ratio: compression_ratio(&file),
+                mime: None,
+                is_dir: file.is_dir(),
+                encrypted: is_encrypted(data, &file),
+            },
+            Err(_) => break,
+        };
+        if !entry.is_dir && !entry.encrypted && !deadline.is_expired() {
+            entry.mime = read_zip_entry_head(
+                archive,
+                position,
+                default_backend(),
+                INDEX_SNIFF_BYTES,
+                deadline,
+            )
+            .ok()
+            .and_then(|head| sniff_bytes(&head).ok())
+            .map(|sniff| sniff.mime_real);
+        }
+        index.push(entry);
+    }
+    index
+}
+
+/// Nome comparável entre entradas: separadores `/`, sem `./` ou `/` iniciais e
+/// sem diferenciar maiúsculas (sistemas de arquivos case-insensitive).
+pub(crate) fn normalize_entry_name(name: &str) -> String {
+    let name = name.replace('\\', "/");
+    let mut name = name.as_str();
+    loop {
+        let trimmed = name.trim_start_matches("./").trim_start_matches('/');
+        if trimmed.len() == name.len() {
+            break;
+        }
+        name = trimmed;
+    }
+    name.trim_end_matches('/').to_lowercase()
+}
+
+/// Entradas cujo intervalo (header local até o fim dos dados) invade o de outra,
+/// como nos ZIPs que reaproveitam os mesmos bytes em várias entradas.
+fn overlapping_entries(spans: &mut [(u64, u64, String)]) -> Vec<String> {
+    spans.sort_by_key(|(start, _, _)| *start);
+    let mut issues = Vec::new();
+    let mut furthest: Option<&(u64, u64, String)> = None;
+    for span in spans.iter() {
+        if let Some(previous) = furthest {
+            if span.0 < previous.1 {
+                issues.push(format!(
+                    "entrada '{}' sobrepõe os dados de '{}' (offset {})",
+                    span.2, previous.2, span.0
+                ));
+            }
+        }
+        match furthest {
+            Some(previous) if previous.1 >= span.1 => {}
+            _ => furthest = Some(span),
+        }
+    }
+    issues
+}
+
+/// Resultado de `verify_crc`.
+struct CrcCheck {
+    verified: usize,
+    issues: Vec<String>,
+    timed_out: bool,
+}
+
+/// Descomprime as entradas dentro de `budget` bytes somados e confere CRC-32 e
+/// tamanho; entradas cifradas ou com método não suportado são puladas.
+fn verify_crcs(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    data: &[u8],
+    budget: u64,
+    deadline: &Deadline,
+) -> CrcCheck {
+    let mut check = CrcCheck {
+        verified: 0,
+        issues: Vec::new(),
+        timed_out: false,
+    };
+    let mut used = 0u64;
+    for index in 0..archive.len() {
+        let (entry, expected_crc, declared_size) = match archive.by_index_raw(index) {
+            Ok(file) if file.is_dir() || is_encrypted(data, &file) => continue,
+            Ok(file) => (file.name().to_string(), file.crc32(), file.size()),
+            Err(_) => continue,
+        };
+        let limits =
+            DecompressLimits::new(budget.saturating_sub(used)).with_deadline(deadline.clone());
+        match read_zip_entry(archive, index, default_backend(), &limits) {
+            Ok(content) => {
+                used += content.len() as u64;
+                let mut crc = Crc::new();
+                crc.update(&content);
+                if crc.sum() != expected_crc {
+                    check.issues.push(format!(
+                        "entrada '{entry}' com CRC-32 inválido ({:08x} != {expected_crc:08x})",
+                        crc.sum()
+                    ));
+                } else if content.len() as u64 != declared_size {
+                    check.issues.push(format!(
+                        "entrada '{entry}' descomprime para {} bytes, declarados {declared_size}",
+                        content.len()
+                    ));
+                } else {
+                    check.verified += 1;
+                }
+            }
+            Err(DecompressError::Timeout) => {
+                check.timed_out = true;
+                break;
+            }
+            Err(DecompressError::LimitExceeded { .. }) => {
+                check.issues.push(format!(
+                    "descompressão excede o orçamento de {budget} bytes na entrada '{entry}'"
+                ));
+                break;
+            }
+            Err(DecompressError::Unsupported(_)) => {}
+            Err(DecompressError::Corrupt(err)) => check.issues.push(format!(
+                "entrada '{entry}' com fluxo comprimido inválido: {err}"
+            )),
+        }
+    }
+    check
+}
+
+fn entry_violation(file: &ZipFile<'_>, policy: &ArchivePolicySection) -> Option<String> {
+    if policy.forbid_path_traversal.unwrap_or(true) && file.enclosed_name().is_none() {
+        return Some(format!("entrada '{}' possui path traversal", file.name()));
+    }
+
+    if policy.forbid_symlinks.unwrap_or(true) && file.unix_mode().is_some_and(is_symlink) {
+        return Some(format!("entrada '{}' é symlink não permitido", file.name()));
+    }
+
+    None
+}
+
+/// Anomalias de `archive.metadata_checks` na entrada, lidas dos headers brutos.
+fn metadata_anomalies(
+    data: &[u8],
+    file: &ZipFile<'_>,
+    checks: &ArchiveMetadataChecks,
+    today: Date,
+) -> Vec<String> {
+    let mut issues = Vec::new();
+    let central = file.central_header_start() as usize;
+    let (Some(time), Some(date)) = (read_u16(data, central + 12), read_u16(data, central + 14))
+    else {
+        return issues;
+    };
+    match dos_date(date, time) {
+        Err(reason) if checks.invalid_dates.unwrap_or(false) => issues.push(format!(
+            "entrada '{}' com data DOS impossível ({reason})",
+            file.name()
+        )),
+        Err(_) => {}
+        Ok(modified) => {
+            if let Some(max_days) = checks.max_future_days {
+                let limit = today.saturating_add(time::Duration::days(max_days.into()));
+                if modified > limit {
+                    issues.push(format!(
+                        "entrada '{}' datada no futuro ({modified})",
+                        file.name()
+                    ));
+                }
+            }
+            if let Some(min_year) = checks.min_year {
+                if modified.year() < i32::from(min_year) {
+                    issues.push(format!(
+                        "entrada '{}' datada antes de {min_year} ({modified})",
+                        file.name()
+                    ));
+                }
+            }
+        }
+    }
+    if checks.header_mismatch.unwrap_or(false) {
+        if let Some(fields) = header_mismatches(data, central, file.header_start() as usize) {
+            issues.push(format!(
+                "entrada '{}' diverge entre header local e central directory ({})",
+                file.name(),
+                fields.join(", ")
+            ));
+        }
+    }
+    issues
+}
+
+/// Converte data/hora DOS; o erro descreve o campo impossível.
+fn dos_date(date: u16, time: u16) -> Result<Date, String> {
+    let year = 1980 + i32::from(date >> 9);
+    let month = ((date >> 5) & 0x0f) as u8;
+    let day = (date & 0x1f) as u8;
+    let hour = time >> 11;
+    let minute = (time >> 5) & 0x3f;
+    let second = (time & 0x1f) * 2;
+    let month = Month::try_from(month).map_err(|_| format!("mês {month}"))?;
+    let parsed = Date::from_calendar_date(year, month, day)
+        .map_err(|_| format!("dia {day}/{}", month as u8))?;
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err(format!("hora {hour:02}:{minute:02}:{second:02}"));
+    }
+    Ok(parsed)
+}
+
+/// Campos que divergem entre o header local e o central directory. Sizes e CRC
+/// ficam de fora quando o data descriptor (bit 3) ou o ZIP64 os tornam legítimos.
+fn header_mismatches(data: &[u8], central: usize, local: usize) -> Option<Vec<&'static str>> {
+    if data.get(local..local + 4)? != b"PK\x03\x04" {
+        return Some(vec!["assinatura do header local"]);
+    }
+    let local_flags = read_u16(data, local + 6)?;
+    let mut fields = Vec::new();
+    if local_flags & 0x0008 == 0 {
+        let pairs = [
+            (
+                "crc32",
+                read_u32(data, local + 14)?,
+                read_u32(data, central + 16)?,
+            ),
+            (
+                "compressed_size",
+                read_u32(data, local + 18)?,
+                read_u32(data, central + 20)?,
+            ),
+            (
+                "size",
+                read_u32(data, local + 22)?,
+                read_u32(data, central + 24)?,
+            ),
+        ];
+        for (field, local_value, central_value) in pairs {
+            let zip64 = field != "crc32" && (local_value == u32::MAX || central_value == u32::MAX);
+            if local_value != central_value && !zip64 {
+                fields.push(field);
+            }
+        }
+    }
+    let local_name_len = usize::from(read_u16(data, local + 26)?);
+    let central_name_len = usize::from(read_u16(data, central + 28)?);
+    let local_name = data.get(local + 30..local + 30 + local_name_len)?;
+    let central_name = data.get(central + 46..central + 46 + central_name_len)?;
+    if local_name != central_name {
+        fields.push("name");
+    }
+    (!fields.is_empty()).then_some(fields)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Entradas de `required_entries` ausentes e entradas fora de `allowed_entry_globs`.
+pub(crate) fn manifest_violations(
+    entries: &[String],
+    policy: &ArchivePolicySection,
+) -> (Vec<String>, Vec<String>) {
+    let missing = policy
+        .required_entries
+        .iter()
+        .filter(|required| !entries.contains(required))
+        .cloned()
+        .collect();
+    let unexpected = if policy.allowed_entry_globs.is_empty() {
+        Vec::new()
+    } else {
+        entries
+            .iter()
+            .filter(|entry| {
+                !policy.required_entries.contains(entry)
+                    && !policy
+                        .allowed_entry_globs
+                        .iter()
+                        .any(|glob| glob_matches(glob, entry))
+            })
+            .cloned()
+            .collect()
+    };
+    (missing, unexpected)
+}
+
+/// Casa `name` com `glob` em tempo O(glob × name): `?` e `*` não atravessam `/`, `**` atravessa.
+pub fn glob_matches(glob: &str, name: &str) -> bool {
+    enum Token {
+        Literal(char),
+        AnyChar,
+        Segment,
+        Path,
+    }
+    let mut tokens = Vec::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(ch) = chars.next() {
+        tokens.push(match ch {
+            '*' if chars.peek() == Some(&'*') => {
+                while chars.peek() == Some(&'*') {
+                    chars.next();
+                }
+                Token::Path
+            }
+            '*' => Token::Segment,
+            '?' => Token::AnyChar,
+            ch => Token::Literal(ch),
+        });
+    }
+
+    let name: Vec<char> = name.chars().collect();
+    // reachable[j]: o prefixo de tamanho j de `name` casa com os tokens já consumidos.
+    let mut reachable = vec![false; name.len() + 1];
+    reachable[0] = true;
+    for token in &tokens {
+        let mut next = vec![false; name.len() + 1];
+        for j in 0..=name.len() {
+            match token {
+                Token::Literal(ch) => next[j] = j > 0 && reachable[j - 1] && name[j - 1] == *ch,
+                Token::AnyChar => next[j] = j > 0 && reachable[j - 1] && name[j - 1] != '/',
+                Token::Segment => {
+                    next[j] = reachable[j] || (j > 0 && next[j - 1] && name[j - 1] != '/')
+                }
+                Token::Path => next[j] = reachable[j] || (j > 0 && next[j - 1]),
+            }
+        }
+        reachable = next;
+    }
+    reachable[name.len()]
+}
+
+/// Lê o bit 0 (criptografia) do general purpose flag no central directory.
+pub(crate) fn is_encrypted(data: &[u8], file: &ZipFile<'_>) -> bool {
+    let offset = file.central_header_start() as usize + 8;
+    data.get(offset..offset + 2)
+        .map(|flags| u16::from_le_bytes([flags[0], flags[1]]) & 0x0001 != 0)
+        .unwrap_or(false)
+}
+
+fn compression_ratio(file: &ZipFile<'_>) -> Option<f64> {
+    let compressed = file.compressed_size() as f64;
+    let uncompressed = file.size() as f64;
+    if uncompressed == 0.0 {
+        return Some(1.0);
+    }
+    if compressed == 0.0 {
+        return Some(f64::INFINITY);
+    }
+    Some(uncompressed / compressed)
+}
+
+pub(crate) fn depth_of(name: &str) -> usize {
+    name.trim_end_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .count()
+}
+
+pub(crate) fn is_symlink(unix_mode: u32) -> bool {
+    (unix_mode & 0o170000) == 0o120000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validators::ValidatorStatus;
+    use std::io::Write;
+    use zip::write::FileOptions;
+
+    fn build_zip(entries: &[&str]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for entry in entries {
+            writer
+                .start_file(*entry, FileOptions::default())
+                .expect("start file");
+            writer.write_all(b"conteudo").expect("write entry");
+        }
+        writer.finish().expect("finish zip").into_inner()
+    }
+
+    /// Liga o bit de criptografia de uma entrada nos headers local e central.
+    fn mark_encrypted(data: &mut [u8], entry: &str) {
+        let name = entry.as_bytes();
+        for (signature, name_offset) in [(b"PK\x03\x04", 30), (b"PK\x01\x02", 46)] {
+            let pos = (0..data.len() - name_offset)
+                .find(|&idx| {
+                    &data[idx..idx + 4] == signature && data[idx + name_offset..].starts_with(name)
+                })
+                .expect("header");
+            let flag_offset = if name_offset == 30 { 6 } else { 8 };
+            data[pos + flag_offset] |= 0x01;
+        }
+    }
+
+    #[test]
+    fn encrypted_entries_are_reported() {
+        let mut data = build_zip(&["a.txt", "secret.txt"]);
+        mark_encrypted(&mut data, "secret.txt");
+        let outcome = validate_archive("application/zip", &data, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(outcome.details["encrypted_entries"], json!(["secret.txt"]));
+    }
+
+    #[test]
+    fn encrypted_entries_are_denied_when_forbidden() {
+        let mut data = build_zip(&["secret.txt"]);
+        mark_encrypted(&mut data, "secret.txt");
+        let policy = ResolvedPolicy {
+            archive: ArchivePolicySection {
+                forbid_encrypted_entries: Some(true),
+                ..ArchivePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let outcome = validate_archive("application/zip", &data, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(outcome.details["encrypted_entries"], json!(["secret.txt"]));
+    }
+
+    #[test]
+    fn exhaustive_mode_collects_every_violation() {
+        let mut data = build_zip(&["../fora.txt", "a/b/c/d.txt", "secret.txt"]);
+        mark_encrypted(&mut data, "secret.txt");
+        let mut policy = ResolvedPolicy {
+            archive: ArchivePolicySection {
+                zip_max_depth: Some(2),
+                forbid_encrypted_entries: Some(true),
+                ..ArchivePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let first = validate_archive("application/zip", &data, Some(&policy), &Deadline::none());
+        assert!(matches!(first.status, ValidatorStatus::Deny));
+        assert!(first.details.get("findings").is_none());
+
+        policy.defaults.report_all_findings = Some(true);
+        let all = validate_archive("application/zip", &data, Some(&policy), &Deadline::none());
+        assert!(matches!(all.status, ValidatorStatus::Deny));
+        let findings = all.details["findings"].as_array().expect("findings");
+        assert_eq!(findings.len(), 3, "{findings:?}");
+        assert!(findings[0].as_str().unwrap().contains("path traversal"));
+        assert!(findings[1].as_str().unwrap().contains("profundidade"));
+        assert!(findings[2].as_str().unwrap().contains("criptografadas"));
+        assert_eq!(all.details["entries"], 3);
+    }
+
+    #[test]
+    fn manifest_constrained_archive_reports_missing_and_unexpected_entries() {
+        let policy = ResolvedPolicy {
+            archive: ArchivePolicySection {
+                required_entries: vec!["data.csv".into(), "metadata.json".into()],
+                allowed_entry_globs: vec!["anexos/*.pdf".into()],
+                ..ArchivePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let ok = build_zip(&["data.csv", "metadata.json", "anexos/a.pdf"]);
+        let outcome = validate_archive("application/zip", &ok, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+
+        let bad = build_zip(&["data.csv", "anexos/sub/b.pdf", "run.exe"]);
+        let outcome = validate_archive("application/zip", &bad, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(outcome.details["missing_entries"], json!(["metadata.json"]));
+        assert_eq!(
+            outcome.details["unexpected_entries"],
+            json!(["anexos/sub/b.pdf", "run.exe"])
+        );
+        assert!(glob_matches("anexos/**.pdf", "anexos/sub/b.pdf"));
+        assert!(!glob_matches("?.csv", "ab.csv"));
+    }
+
+    /// Localiza o header (`PK\x03\x04` ou `PK\x01\x02`) da entrada.
+    fn header_offset(data: &[u8], signature: &[u8; 4], entry: &str) -> usize {
+        let name_offset = if signature == b"PK\x03\x04" { 30 } else { 46 };
+        (0..data.len() - name_offset)
+            .find(|&idx| {
+                &data[idx..idx + 4] == signature
+                    && data[idx + name_offset..].starts_with(entry.as_bytes())
+            })
+            .expect("header")
+    }
+
+    #[test]
+    fn metadata_checks_flag_crafted_headers() {
+        let mut data = build_zip(&["data.txt", "futuro.txt", "tamanho.txt"]);
+        // 30/02 é impossível; 2107 está além de qualquer tolerância.
+        let central = header_offset(&data, b"PK\x01\x02", "data.txt");
+        data[central + 14..central + 16]
+            .copy_from_slice(&((45 << 9) | (2 << 5) | 30u16).to_le_bytes());
+        let central = header_offset(&data, b"PK\x01\x02", "futuro.txt");
+        data[central + 14..central + 16]
+            .copy_from_slice(&((127 << 9) | (1 << 5) | 1u16).to_le_bytes());
+        let local = header_offset(&data, b"PK\x03\x04", "tamanho.txt");
+        data[local + 22] ^= 0x40;
+
+        let mut policy = ResolvedPolicy::default();
+        let outcome = validate_archive("application/zip", &data, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+
+        policy.defaults.report_all_findings = Some(true);
+        policy.archive.metadata_checks = ArchiveMetadataChecks {
+            invalid_dates: Some(true),
+            max_future_days: Some(1),
+            min_year: Some(1981),
+            header_mismatch: Some(true),
+        };
+        let outcome = validate_archive("application/zip", &data, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        let findings = outcome.details["findings"].as_array().expect("findings");
+        assert_eq!(findings.len(), 4, "{findings:?}");
+        let message = outcome.details["message"].as_str().unwrap();
+        assert!(message.contains("'data.txt' com data DOS impossível (dia 30/2)"));
+        assert!(message.contains("'futuro.txt' datada no futuro (2107-01-01)"));
+        // O ZipWriter grava 1980-01-01 quando a data não é informada.
+        assert!(message.contains("datada antes de 1981 (1980-01-01)"));
+        assert!(
+            message.contains("'tamanho.txt' diverge entre header local e central directory (size)")
+        );
+    }
+
+    fn archive_policy(archive: ArchivePolicySection) -> ResolvedPolicy {
+        ResolvedPolicy {
+            archive,
+            defaults: crate::config::DefaultsSection {
+                report_all_findings: Some(true),
+                ..Default::default()
+            },
+            ..ResolvedPolicy::default()
+        }
+    }
+
+    #[test]
+    fn index_lists_bounded_entries_with_inner_mime() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .add_directory("docs/", FileOptions::default())
+            .expect("dir");
+        writer
+            .start_file("docs/a.pdf", FileOptions::default())
+            .expect("start file");
+        writer.write_all(b"%PDF-1.4\n%%EOF\n").expect("write entry");
+        writer
+            .start_file("b.txt", FileOptions::default())
+            .expect("start file");
+        writer.write_all(b"conteudo").expect("write entry");
+        let data = writer.finish().expect("finish zip").into_inner();
+
+        let policy = archive_policy(ArchivePolicySection {
+            index_entries: Some(true),
+            index_max_entries: Some(2),
+            ..ArchivePolicySection::default()
+        });
+        let outcome = validate_archive("application/zip", &data, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(outcome.details["index_truncated"], true);
+        let index = outcome.details["index"].as_array().expect("index");
+        assert_eq!(index.len(), 2);
+        assert_eq!(index[0]["name"], "docs/");
+        assert_eq!(index[0]["is_dir"], true);
+        assert!(index[0].get("mime").is_none());
+        assert_eq!(index[1]["name"], "docs/a.pdf");
+        assert_eq!(index[1]["size"], 15);
+        assert_eq!(index[1]["mime"], "application/pdf");
+        assert!(index[1]["ratio"].is_number());
+
+        let outcome = validate_archive("application/zip", &data, None, &Deadline::none());
+        assert!(outcome.details.get("index").is_none());
+    }
+
+    #[test]
+    fn overlapping_and_duplicated_entries_are_denied() {
+        let mut data = build_zip(&["a.txt", "b.txt", "dir/c.txt", "./DIR/C.txt"]);
+        let first = header_offset(&data, b"PK\x03\x04", "a.txt") as u32;
+        let central = header_offset(&data, b"PK\x01\x02", "b.txt");
+        data[central + 42..central + 46].copy_from_slice(&first.to_le_bytes());
+
+        let outcome = validate_archive(
+            "application/zip",
+            &data,
+            Some(&archive_policy(ArchivePolicySection::default())),
+            &Deadline::none(),
+        );
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        let findings = outcome.details["findings"].to_string();
+        assert!(findings.contains("'./DIR/C.txt' duplica o nome de 'dir/c.txt'"));
+        assert!(findings.contains("sobrepõe os dados"));
+
+        let allowed = ArchivePolicySection {
+            forbid_overlapping_entries: Some(false),
+            ..ArchivePolicySection::default()
+        };
+        let outcome = validate_archive(
+            "application/zip",
+            &data,
+            Some(&archive_policy(allowed)),
+            &Deadline::none(),
+        );
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+    }
+
+    #[test]
+    fn crc_and_archive_wide_budgets_are_enforced() {
+        let mut data = build_zip(&["a.txt", "b.txt"]);
+        let local = header_offset(&data, b"PK\x03\x04", "b.txt");
+        let central = header_offset(&data, b"PK\x01\x02", "b.txt");
+        for offset in [local + 14, central + 16] {
+            data[offset] ^= 0xFF;
+        }
+        let verify = ArchivePolicySection {
+            verify_crc: Some(true),
+            ..ArchivePolicySection::default()
+        };
+        let outcome = validate_archive(
+            "application/zip",
+            &data,
+            Some(&archive_policy(verify.clone())),
+            &Deadline::none(),
+        );
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert!(outcome.details["message"]
+            .as_str()
+            .unwrap()
+            .contains("'b.txt' com CRC-32 inválido"));
+
+        let clean = build_zip(&["a.txt", "b.txt", "c.txt"]);
+        let outcome = validate_archive(
+            "application/zip",
+            &clean,
+            Some(&archive_policy(verify)),
+            &Deadline::none(),
+        );
+        assert_eq!(outcome.details["crc_verified_entries"], 3);
+        assert_eq!(outcome.details["total_uncompressed_bytes"], 24);
+
+        let limited = ArchivePolicySection {
+            max_entries: Some(2),
+            max_total_uncompressed_mb: Some(0),
+            ..ArchivePolicySection::default()
+        };
+        let outcome = validate_archive(
+            "application/zip",
+            &clean,
+            Some(&archive_policy(limited)),
+            &Deadline::none(),
+        );
+        let findings = outcome.details["findings"].to_string();
+        assert!(findings.contains("acima de archive.max_entries (2)"));
+        assert!(findings.contains("max_total_uncompressed_mb (8 > 0 bytes)"));
+    }
+
+    #[test]
+    fn zip64_entries_pass_header_and_crc_checks() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("grande.bin", FileOptions::default().large_file(true))
+            .expect("start file");
+        writer.write_all(&[7u8; 4096]).expect("write entry");
+        let data = writer.finish().expect("finish zip").into_inner();
+        let policy = ArchivePolicySection {
+            verify_crc: Some(true),
+            metadata_checks: ArchiveMetadataChecks {
+                header_mismatch: Some(true),
+                ..ArchiveMetadataChecks::default()
+            },
+            ..ArchivePolicySection::default()
+        };
+        let outcome = validate_archive(
+            "application/zip",
+            &data,
+            Some(&archive_policy(policy)),
+            &Deadline::none(),
+        );
+        assert!(
+            matches!(outcome.status, ValidatorStatus::Pass),
+            "{outcome:?}"
+        );
+        assert_eq!(outcome.details["total_uncompressed_bytes"], 4096);
+        assert_eq!(outcome.details["crc_verified_entries"], 1);
+    }
+
+    #[test]
+    fn expired_deadline_returns_partial_timeout() {
+        let data = build_zip(&["a.txt", "b.txt"]);
+        let deadline = Deadline::after(std::time::Duration::ZERO);
+        let outcome = validate_archive("application/zip", &data, None, &deadline);
+        assert!(outcome.is_timeout());
+        assert_eq!(outcome.details["partial"], true);
+        assert_eq!(outcome.details["entries"], 0);
+        assert_eq!(outcome.details["total_entries"], 2);
+    }
+
+    #[test]
+    fn budget_deny_keeps_partial_progress() {
+        let data = build_zip(&["a.txt", "b/c/d/e.txt", "f.txt"]);
+        let policy = ResolvedPolicy {
+            archive: ArchivePolicySection {
+                zip_max_depth: Some(2),
+                ..ArchivePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let outcome = validate_archive("application/zip", &data, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert!(outcome.details["message"]
+            .as_str()
+            .unwrap()
+            .contains("profundidade"));
+        assert_eq!(outcome.details["partial"], true);
+        assert_eq!(outcome.details["entries"], 2);
+        assert_eq!(outcome.details["total_entries"], 3);
+    }
+}
@@ -0,0 +1,299 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Validador de executáveis (PE, ELF e Mach-O), reconhecidos pelo cabeçalho
+//! independentemente do MIME detectado ou da extensão.
+
+use super::ValidatorOutcome;
+use crate::policy::ResolvedPolicy;
+use goblin::mach::load_command::CommandVariant;
+use goblin::mach::{Mach, MachO};
+use goblin::Object;
+use serde::Serialize;
+use serde_json::json;
+
+const MACHO_MAGICS: [[u8; 4]; 6] = [
+    [0xFE, 0xED, 0xFA, 0xCE],
+    [0xFE, 0xED, 0xFA, 0xCF],
+    [0xCE, 0xFA, 0xED, 0xFE],
+    [0xCF, 0xFA, 0xED, 0xFE],
+    [0xCA, 0xFE, 0xBA, 0xBE],
+    [0xBE, 0xBA, 0xFE, 0xCA],
+];
+
+/// Formato de executável reconhecido.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryFormat {
+    Pe,
+    Elf,
+    MachO,
+}
+
+impl BinaryFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BinaryFormat::Pe => "pe",
+            BinaryFormat::Elf => "elf",
+            BinaryFormat::MachO => "macho",
+        }
+    }
+}
+
+/// Resumo do cabeçalho do executável.
+#[derive(Debug, Clone, Serialize)]
+pub struct BinaryInfo {
+    pub format: BinaryFormat,
+    /// `false` quando o cabeçalho foi reconhecido, mas o arquivo está truncado
+    /// ou malformado demais para ser interpretado.
+    pub parsed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bits: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    pub sections: usize,
+    pub libraries: usize,
+    pub imports: usize,
+    /// Presença de assinatura embutida (Authenticode/LC_CODE_SIGNATURE); não verificada.
+    pub signed: bool,
+}
+
+impl BinaryInfo {
+    fn unparsed(format: BinaryFormat) -> Self {
+        Self {
+            format,
+            parsed: false,
+            arch: None,
+            bits: None,
+            kind: None,
+            sections: 0,
+            libraries: 0,
+            imports: 0,
+            signed: false,
+        }
+    }
+}
+
+/// Reconhece PE/ELF/Mach-O pelos magic bytes e extrai o resumo do cabeçalho.
+pub fn detect_binary(data: &[u8]) -> Option<BinaryInfo> {
+    let format = magic_format(data)?;
+    let info = match Object::parse(data) {
+        Ok(Object::PE(pe)) => BinaryInfo {
+            format,
+            parsed: true,
+            arch: Some(pe_machine(pe.header.coff_header.machine).to_string()),
+            bits: Some(if pe.is_64 { 64 } else { 32 }),
+            kind: Some(if pe.is_lib { "dll" } else { "exe" }.to_string()),
+            sections: pe.sections.len(),
+            libraries: pe.libraries.len(),
+            imports: pe.imports.len(),
+            signed: pe
+                .header
+                .optional_header
+                .and_then(|header| header.data_directories.get_certificate_table().copied())
+                .is_some_and(|table| table.size > 0),
+        },
+        Ok(Object::Elf(elf)) => BinaryInfo {
+            format,
+            parsed: true,
+            arch: Some(goblin::elf::header::machine_to_str(elf.header.e_machine).to_string()),
+            bits: Some(if elf.is_64 { 64 } else { 32 }),
+            kind: Some(goblin::elf::header::et_to_str(elf.header.e_type).to_string()),
+            sections: elf.section_headers.len(),
+            libraries: elf.libraries.len(),
+            imports: elf
+                .dynsyms
+                .iter()
+                .filter(|symbol| symbol.is_import())
+                .count(),
+            signed: false,
+        },
+        Ok(Object::Mach(Mach::Binary(macho))) => macho_info(&macho, None),
+        Ok(Object::Mach(Mach::Fat(fat))) => {
+            let arches: Vec<String> = fat
+                .iter_arches()
+                .filter_map(Result::ok)
+                .map(|arch| cpu_name(arch.cputype, arch.cpusubtype))
+                .collect();
+            match fat.get(0) {
+                Ok(goblin::mach::SingleArch::MachO(macho)) => {
+                    macho_info(&macho, Some(format!("universal({})", arches.join(","))))
+                }
+                _ => BinaryInfo::unparsed(format),
+            }
+        }
+        _ => BinaryInfo::unparsed(format),
+    };
+    Some(info)
+}
+
+fn magic_format(data: &[u8]) -> Option<BinaryFormat> {
+    if data.starts_with(b"\x7fELF") {
+        return Some(BinaryFormat::Elf);
+    }
+    if data
+        .get(..4)
+        .is_some_and(|magic| MACHO_MAGICS.iter().any(|m| m == magic))
+    {
+        // 0xCAFEBABE também é class Java: Mach-O universal tem poucas arquiteturas.
+        let fat_arches = data
+            .get(4..8)
+            .map(|n| u32::from_be_bytes([n[0], n[1], n[2], n[3]]));
+        if data.starts_with(&[0xCA, 0xFE, 0xBA, 0xBE]) && !matches!(fat_arches, Some(n) if n <= 30)
+        {
+            return None;
+        }
+        return Some(BinaryFormat::MachO);
+    }
+    if data.starts_with(b"MZ") {
+        let offset = data
+            .get(0x3C..0x40)
+            .map(|raw| u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as usize)?;
+        if data.get(offset..offset.checked_add(4)?) == Some(&b"PE\0\0"[..]) {
+            return Some(BinaryFormat::Pe);
+        }
+    }
+    None
+}
+
+fn macho_info(macho: &MachO<'_>, arch: Option<String>) -> BinaryInfo {
+    BinaryInfo {
+        format: BinaryFormat::MachO,
+        parsed: true,
+        arch: Some(
+            arch.unwrap_or_else(|| cpu_name(macho.header.cputype(), macho.header.cpusubtype())),
+        ),
+        bits: Some(if macho.is_64 { 64 } else { 32 }),
+        kind: Some(goblin::mach::header::filetype_to_str(macho.header.filetype).to_string()),
+        sections: macho
+            .segments
+            .iter()
+            .map(|segment| segment.nsects as usize)
+            .sum(),
+        libraries: macho.libs.iter().filter(|lib| **lib != "self").count(),
+        imports: macho.imports().map(|imports| imports.len()).unwrap_or(0),
+        signed: macho
+            .load_commands
+            .iter()
+            .any(|command| matches!(command.command, CommandVariant::CodeSignature(_))),
+    }
+}
+
+fn cpu_name(cputype: u32, cpusubtype: u32) -> String {
+    goblin::mach::constants::cputype::get_arch_name_from_types(cputype, cpusubtype)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("cpu:{cputype:#x}"))
+}
+
+fn pe_machine(machine: u16) -> &'static str {
+    match machine {
+        0x014c => "x86",
+        0x8664 => "x86_64",
+        0x01c0 | 0x01c4 => "arm",
+        0xaa64 => "aarch64",
+        0x0200 => "ia64",
+        _ => "unknown",
+    }
+}
+
+/// Aplica a política `binary` ao executável detectado.
+pub fn validate_binary(info: &BinaryInfo, policy: Option<&ResolvedPolicy>) -> ValidatorOutcome {
+    let name = "binary";
+    let binary_policy = policy.map(|p| p.binary.clone()).unwrap_or_default();
+    let details = serde_json::to_value(info).unwrap_or_else(|_| json!({}));
+    let label = format!(
+        "executável {} detectado{}",
+        info.format.as_str(),
+        info.arch
+            .as_deref()
+            .map(|arch| format!(" ({arch})"))
+            .unwrap_or_default()
+    );
+
+    let outcome = if binary_policy.deny_all.unwrap_or(false) {
+        ValidatorOutcome::deny(name, label)
+    } else if binary_policy.allow_signed_only.unwrap_or(false) {
+        if info.signed {
+            ValidatorOutcome::pass(name)
+        } else {
+            ValidatorOutcome::deny(name, format!("{label} sem assinatura"))
+        }
+    } else {
+        ValidatorOutcome::warn(name, label)
+    };
+    outcome.with_details(details)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BinaryPolicySection, PolicyConfig};
+    use crate::validators::ValidatorStatus;
+
+    /// ELF64 mínimo (somente cabeçalho, sem seções).
+    fn tiny_elf() -> Vec<u8> {
+        let mut elf = vec![0u8; 64];
+        elf[..4].copy_from_slice(b"\x7fELF");
+        elf[4] = 2; // ELFCLASS64
+        elf[5] = 1; // little-endian
+        elf[6] = 1; // EV_CURRENT
+        elf[16..18].copy_from_slice(&2u16.to_le_bytes()); // ET_EXEC
+        elf[18..20].copy_from_slice(&0x3Eu16.to_le_bytes()); // x86-64
+        elf[20..24].copy_from_slice(&1u32.to_le_bytes());
+        elf[52..54].copy_from_slice(&64u16.to_le_bytes()); // e_ehsize
+        elf
+    }
+
+    #[test]
+    fn detects_elf_and_truncated_pe() {
+        let info = detect_binary(&tiny_elf()).expect("elf");
+        assert_eq!(info.format, BinaryFormat::Elf);
+        assert!(info.parsed);
+        assert_eq!(info.arch.as_deref(), Some("X86_64"));
+        assert_eq!(info.bits, Some(64));
+        assert!(!info.signed);
+
+        let mut pe = vec![0u8; 0x84];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        pe[0x80..0x84].copy_from_slice(b"PE\0\0");
+        let info = detect_binary(&pe).expect("pe");
+        assert_eq!(info.format, BinaryFormat::Pe);
+        assert!(!info.parsed);
+
+        assert!(detect_binary(b"MZ but not an executable").is_none());
+        assert!(detect_binary(&[0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 52]).is_none());
+    }
+
+    #[test]
+    fn policy_controls_binary_decision() {
+        let info = detect_binary(&tiny_elf()).expect("elf");
+        assert!(matches!(
+            validate_binary(&info, None).status,
+            ValidatorStatus::Warn
+        ));
+
+        let mut config = PolicyConfig {
+            binary: BinaryPolicySection {
+                deny_all: None,
+                allow_signed_only: Some(true),
+            },
+            ..PolicyConfig::default()
+        };
+        let resolved = ResolvedPolicy::from_config(&config);
+        let outcome = validate_binary(&info, Some(&resolved));
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(outcome.details["format"], "elf");
+
+        config.binary.deny_all = Some(true);
+        let resolved = ResolvedPolicy::from_config(&config);
+        assert!(matches!(
+            validate_binary(&info, Some(&resolved)).status,
+            ValidatorStatus::Deny
+        ));
+    }
+}
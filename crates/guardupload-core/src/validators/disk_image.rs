@@ -0,0 +1,490 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Detecção de imagens de disco (ISO/UDF/VHD/VMDK/DMG/IMG) e instaladores MSI.
+//!
+//! Com `archive.enumerate_iso`, a árvore ISO 9660 é listada e segue para
+//! [`validate_listing`], como as entradas de 7z e RAR.
+
+use super::listing::{validate_listing, ListedEntry, Listing, ListingError};
+use super::ole::is_ole;
+use super::{ValidatorOutcome, ValidatorStatus};
+use crate::limits::Deadline;
+use crate::policy::ResolvedPolicy;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::io::Cursor;
+
+/// Tamanho de setor padrão do ISO 9660 e posição do primeiro descritor de volume.
+const ISO_SECTOR: usize = 2048;
+const ISO_DESCRIPTORS_START: usize = 16 * ISO_SECTOR;
+
+/// Limite de entradas visitadas ao enumerar uma ISO (`truncated` quando atingido).
+const MAX_ISO_ENTRIES: usize = 10_000;
+
+/// CLSIDs da raiz de pacotes Windows Installer (MSI, MSP e MST).
+const MSI_CLSIDS: &[&str] = &[
+    "000c1084-0000-0000-c000-000000000046",
+    "000c1086-0000-0000-c000-000000000046",
+    "000c1082-0000-0000-c000-000000000046",
+];
+
+/// Tipos de imagem de disco/instalador reconhecidos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiskImageKind {
+    Iso9660,
+    Udf,
+    Vhd,
+    Vhdx,
+    Vmdk,
+    Dmg,
+    FatImage,
+    Msi,
+}
+
+impl DiskImageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiskImageKind::Iso9660 => "iso9660",
+            DiskImageKind::Udf => "udf",
+            DiskImageKind::Vhd => "vhd",
+            DiskImageKind::Vhdx => "vhdx",
+            DiskImageKind::Vmdk => "vmdk",
+            DiskImageKind::Dmg => "dmg",
+            DiskImageKind::FatImage => "fat_image",
+            DiskImageKind::Msi => "msi",
+        }
+    }
+}
+
+/// Identifica imagens de disco e instaladores pelas estruturas internas.
+pub fn detect_disk_image(data: &[u8]) -> Option<DiskImageKind> {
+    if is_msi(data) {
+        return Some(DiskImageKind::Msi);
+    }
+    if data.starts_with(b"vhdxfile") {
+        return Some(DiskImageKind::Vhdx);
+    }
+    if data.starts_with(b"KDMV") || data.starts_with(b"# Disk DescriptorFile") {
+        return Some(DiskImageKind::Vmdk);
+    }
+    let footer = data.len().checked_sub(512).map(|start| &data[start..]);
+    if data.starts_with(b"conectix") || footer.is_some_and(|tail| tail.starts_with(b"conectix")) {
+        return Some(DiskImageKind::Vhd);
+    }
+    if footer.is_some_and(|tail| tail.starts_with(b"koly")) {
+        return Some(DiskImageKind::Dmg);
+    }
+    let identifiers = volume_identifiers(data);
+    if identifiers
+        .iter()
+        .any(|id| *id == b"NSR02" || *id == b"NSR03")
+    {
+        return Some(DiskImageKind::Udf);
+    }
+    if identifiers.iter().any(|id| *id == b"CD001") {
+        return Some(DiskImageKind::Iso9660);
+    }
+    if is_fat_image(data) {
+        return Some(DiskImageKind::FatImage);
+    }
+    None
+}
+
+pub fn validate_disk_image(
+    kind: DiskImageKind,
+    data: &[u8],
+    policy: Option<&ResolvedPolicy>,
+    deadline: &Deadline,
+) -> ValidatorOutcome {
+    let name = "disk_image";
+    let defaults = policy.map(|p| p.defaults.clone()).unwrap_or_default();
+    let archive_policy = policy.map(|p| p.archive.clone()).unwrap_or_default();
+    let mut details = json!({ "kind": kind.as_str() });
+
+    if archive_policy.enumerate_iso.unwrap_or(false)
+        && matches!(kind, DiskImageKind::Iso9660 | DiskImageKind::Udf)
+    {
+        if let Some(listing) = list_iso9660(data, deadline) {
+            let mut truncated = false;
+            let listing = listing.map(|mut listing| {
+                truncated = listing.entries.len() > MAX_ISO_ENTRIES;
+                listing.entries.truncate(MAX_ISO_ENTRIES);
+                listing
+            });
+            let listed = validate_listing(name, "iso9660", listing, policy, deadline)
+                .with_details(json!({ "kind": kind.as_str(), "truncated": truncated }));
+            if !matches!(listed.status, ValidatorStatus::Pass) {
+                return listed;
+            }
+            if let (Some(details), Value::Object(listed)) =
+                (details.as_object_mut(), listed.details)
+            {
+                details.extend(listed);
+            }
+        }
+    }
+
+    let message = if kind == DiskImageKind::Msi {
+        "instalador Windows (MSI) detectado".to_string()
+    } else {
+        format!("imagem de disco detectada ({})", kind.as_str())
+    };
+    let action = defaults.images_and_installers.as_deref().unwrap_or("deny");
+    let outcome = if action.eq_ignore_ascii_case("allow") {
+        ValidatorOutcome::pass(name)
+    } else if action.eq_ignore_ascii_case("warn") {
+        ValidatorOutcome::warn(name, message)
+    } else {
+        ValidatorOutcome::deny(name, message)
+    };
+    outcome.with_details(details)
+}
+
+fn is_msi(data: &[u8]) -> bool {
+    if !is_ole(data) {
+        return false;
+    }
+    cfb::CompoundFile::open(Cursor::new(data))
+        .map(|compound| {
+            let clsid = compound.root_entry().clsid().to_string();
+            MSI_CLSIDS.contains(&clsid.as_str())
+        })
+        .unwrap_or(false)
+}
+
+/// Identificadores (`CD001`, `BEA01`, `NSR02`...) da sequência de descritores de volume.
+fn volume_identifiers(data: &[u8]) -> Vec<&[u8]> {
+    (0..16)
+        .filter_map(|index| {
+            let start = ISO_DESCRIPTORS_START + index * ISO_SECTOR + 1;
+            data.get(start..start + 5)
+        })
+        .take_while(|id| id.iter().all(u8::is_ascii_alphanumeric))
+        .collect()
+}
+
+/// Boot sector com assinatura 0x55AA e rótulo de sistema de arquivos FAT.
+fn is_fat_image(data: &[u8]) -> bool {
+    data.get(510..512) == Some(&[0x55, 0xAA][..])
+        && (data.get(0x36..0x3B) == Some(&b"FAT12"[..])
+            || data.get(0x36..0x3B) == Some(&b"FAT16"[..])
+            || data.get(0x52..0x57) == Some(&b"FAT32"[..]))
+}
+
+/// Percorre a árvore de diretórios ISO 9660 a partir do Primary Volume Descriptor,
+/// com no máximo [`MAX_ISO_ENTRIES`] + 1 entradas (a excedente marca `truncated`).
+/// `None` quando não há descritor ISO 9660 (ex.: UDF puro).
+fn list_iso9660(data: &[u8], deadline: &Deadline) -> Option<Result<Listing, ListingError>> {
+    let pvd = data.get(ISO_DESCRIPTORS_START..ISO_DESCRIPTORS_START + ISO_SECTOR)?;
+    if pvd[0] != 1 || &pvd[1..6] != b"CD001" {
+        return None;
+    }
+    let block_size = u16::from_le_bytes([pvd[128], pvd[129]]) as usize;
+    if block_size == 0 {
+        return Some(Err(ListingError::Invalid(
+            "tamanho de bloco zero".to_string(),
+        )));
+    }
+    let Some(root) = DirectoryRecord::parse(&pvd[156..190]) else {
+        return Some(Err(ListingError::Invalid(
+            "registro do diretório raiz inválido".to_string(),
+        )));
+    };
+
+    let mut listing = Listing::default();
+    let mut visited = HashSet::new();
+    let mut pending = vec![(root.extent, root.size, String::new())];
+    while let Some((extent, size, prefix)) = pending.pop() {
+        if !visited.insert(extent) {
+            continue;
+        }
+        let Some(directory) = (extent as usize)
+            .checked_mul(block_size)
+            .and_then(|start| Some(start..start.checked_add(size as usize)?))
+            .and_then(|range| data.get(range))
+        else {
+            continue;
+        };
+        let mut offset = 0usize;
+        while offset < directory.len() {
+            if deadline.is_expired() {
+                return Some(Err(ListingError::Timeout));
+            }
+            let length = directory[offset] as usize;
+            if length == 0 {
+                // Registros não cruzam setores: pula para o próximo bloco.
+                offset = (offset / block_size + 1) * block_size;
+                continue;
+            }
+            let Some(record) = directory
+                .get(offset..offset + length)
+                .and_then(DirectoryRecord::parse)
+            else {
+                break;
+            };
+            offset += length;
+            if record.name == "\0" || record.name == "\u{1}" {
+                continue;
+            }
+
+            let path = if prefix.is_empty() {
+                record.name
+            } else {
+                format!("{prefix}/{}", record.name)
+            };
+            listing.entries.push(ListedEntry {
+                name: path.clone(),
+                size: u64::from(record.size),
+                is_dir: record.is_dir,
+                ..ListedEntry::default()
+            });
+            if listing.entries.len() > MAX_ISO_ENTRIES {
+                return Some(Ok(listing));
+            }
+            if record.is_dir {
+                pending.push((record.extent, record.size, path));
+            }
+        }
+    }
+    Some(Ok(listing))
+}
+
+struct DirectoryRecord {
+    extent: u32,
+    size: u32,
+    is_dir: bool,
+    name: String,
+}
+
+impl DirectoryRecord {
+    fn parse(record: &[u8]) -> Option<Self> {
+        let name_len = *record.get(32)? as usize;
+        let raw_name = record.get(33..33 + name_len)?;
+        let name = String::from_utf8_lossy(raw_name);
+        Some(Self {
+            extent: u32::from_le_bytes(record.get(2..6)?.try_into().ok()?),
+            size: u32::from_le_bytes(record.get(10..14)?.try_into().ok()?),
+            is_dir: record.get(25)? & 0x02 != 0,
+            name: name.split(';').next().unwrap_or_default().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ArchivePolicySection, DefaultsSection};
+    use std::io::Write;
+
+    fn dir_record(name: &[u8], extent: u32, size: u32, is_dir: bool) -> Vec<u8> {
+        let mut record = vec![0u8; 33];
+        record[2..6].copy_from_slice(&extent.to_le_bytes());
+        record[10..14].copy_from_slice(&size.to_le_bytes());
+        record[25] = if is_dir { 0x02 } else { 0 };
+        record[32] = name.len() as u8;
+        record.extend_from_slice(name);
+        if record.len() % 2 == 1 {
+            record.push(0);
+        }
+        record[0] = record.len() as u8;
+        record
+    }
+
+    fn write_sector(image: &mut [u8], sector: usize, records: &[Vec<u8>]) {
+        let mut offset = sector * ISO_SECTOR;
+        for record in records {
+            image[offset..offset + record.len()].copy_from_slice(record);
+            offset += record.len();
+        }
+    }
+
+    /// ISO mínima: `/README.TXT` e `/DOCS/A.PDF`.
+    fn build_iso() -> Vec<u8> {
+        let mut image = vec![0u8; 20 * ISO_SECTOR];
+        let pvd = ISO_DESCRIPTORS_START;
+        image[pvd] = 1;
+        image[pvd + 1..pvd + 6].copy_from_slice(b"CD001");
+        image[pvd + 6] = 1;
+        image[pvd + 128..pvd + 130].copy_from_slice(&(ISO_SECTOR as u16).to_le_bytes());
+        let root = dir_record(b"\0", 18, ISO_SECTOR as u32, true);
+        image[pvd + 156..pvd + 156 + root.len()].copy_from_slice(&root);
+        let terminator = pvd + ISO_SECTOR;
+        image[terminator] = 255;
+        image[terminator + 1..terminator + 6].copy_from_slice(b"CD001");
+
+        write_sector(
+            &mut image,
+            18,
+            &[
+                dir_record(b"\0", 18, ISO_SECTOR as u32, true),
+                dir_record(b"\x01", 18, ISO_SECTOR as u32, true),
+                dir_record(b"README.TXT;1", 0, 10, false),
+                dir_record(b"DOCS", 19, ISO_SECTOR as u32, true),
+            ],
+        );
+        write_sector(
+            &mut image,
+            19,
+            &[
+                dir_record(b"\0", 19, ISO_SECTOR as u32, true),
+                dir_record(b"\x01", 18, ISO_SECTOR as u32, true),
+                dir_record(b"A.PDF;1", 0, 10, false),
+            ],
+        );
+        image
+    }
+
+    fn policy(action: &str, enumerate: bool) -> ResolvedPolicy {
+        ResolvedPolicy {
+            defaults: DefaultsSection {
+                images_and_installers: Some(action.to_string()),
+                ..DefaultsSection::default()
+            },
+            archive: ArchivePolicySection {
+                enumerate_iso: Some(enumerate),
+                zip_max_depth: Some(1),
+                ..ArchivePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        }
+    }
+
+    #[test]
+    fn iso_is_detected_and_denied_by_default() {
+        let data = build_iso();
+        assert_eq!(detect_disk_image(&data), Some(DiskImageKind::Iso9660));
+        let outcome = validate_disk_image(DiskImageKind::Iso9660, &data, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(outcome.details["kind"], "iso9660");
+        assert!(outcome.details.get("entries").is_none());
+    }
+
+    #[test]
+    fn iso_contents_are_enumerated_under_depth_limit() {
+        let data = build_iso();
+        let outcome = validate_disk_image(
+            DiskImageKind::Iso9660,
+            &data,
+            Some(&policy("allow", true)),
+            &Deadline::none(),
+        );
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(
+            outcome.details["entries"],
+            json!(["README.TXT", "DOCS", "DOCS/A.PDF"])
+        );
+        assert!(outcome.details["message"]
+            .as_str()
+            .unwrap()
+            .contains("profundidade excede limite (2 > 1)"));
+        assert_eq!(outcome.details["kind"], "iso9660");
+    }
+
+    #[test]
+    fn iso_listing_applies_archive_checks() {
+        let mut policy = policy("allow", true);
+        policy.archive.zip_max_depth = None;
+        let outcome = validate_disk_image(
+            DiskImageKind::Iso9660,
+            &build_iso(),
+            Some(&policy),
+            &Deadline::none(),
+        );
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(outcome.details["kind"], "iso9660");
+        assert_eq!(outcome.details["entry_count"], 3);
+        assert_eq!(outcome.details["truncated"], false);
+
+        let mut data = build_iso();
+        write_sector(
+            &mut data,
+            19,
+            &[
+                dir_record(b"\0", 19, ISO_SECTOR as u32, true),
+                dir_record(b"A.PDF;1", 0, 10, false),
+                dir_record(b"A.PDF;2", 0, 10, false),
+                dir_record(b"../EVIL.SH;1", 0, 10, false),
+            ],
+        );
+        policy.defaults.report_all_findings = Some(true);
+        let outcome = validate_disk_image(
+            DiskImageKind::Iso9660,
+            &data,
+            Some(&policy),
+            &Deadline::none(),
+        );
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        let message = outcome.details["message"].as_str().unwrap();
+        assert!(message.contains("'DOCS/A.PDF' duplica"), "{message}");
+        assert!(
+            message.contains("'DOCS/../EVIL.SH' possui path traversal"),
+            "{message}"
+        );
+
+        policy.archive.max_entries = Some(2);
+        let outcome = validate_disk_image(
+            DiskImageKind::Iso9660,
+            &data,
+            Some(&policy),
+            &Deadline::none(),
+        );
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert!(outcome.details["message"]
+            .as_str()
+            .unwrap()
+            .contains("archive.max_entries"));
+    }
+
+    #[test]
+    fn msi_is_detected_by_root_clsid() {
+        let mut compound = cfb::CompoundFile::create(Cursor::new(Vec::new())).expect("create");
+        compound
+            .create_stream("/SummaryInformation")
+            .expect("stream")
+            .write_all(b"msi")
+            .expect("write");
+        compound.flush().expect("flush");
+        let mut data = compound.into_inner().into_inner();
+        // CLSID da raiz gravado direto na entrada "Root Entry" (deslocamento 0x50),
+        // com os três primeiros campos em little-endian.
+        let root_name: Vec<u8> = "Root Entry"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let root = data
+            .windows(root_name.len())
+            .position(|window| window == root_name)
+            .expect("root entry");
+        let hex: String = MSI_CLSIDS[0].chars().filter(|ch| *ch != '-').collect();
+        let mut clsid = hex::decode(hex).expect("clsid");
+        clsid[..4].reverse();
+        clsid[4..6].reverse();
+        clsid[6..8].reverse();
+        data[root + 0x50..root + 0x60].copy_from_slice(&clsid);
+
+        assert_eq!(detect_disk_image(&data), Some(DiskImageKind::Msi));
+        let outcome = validate_disk_image(
+            DiskImageKind::Msi,
+            &data,
+            Some(&policy("warn", false)),
+            &Deadline::none(),
+        );
+        assert!(matches!(outcome.status, ValidatorStatus::Warn));
+    }
+
+    #[test]
+    fn virtual_disks_are_detected() {
+        assert_eq!(
+            detect_disk_image(b"vhdxfile\0\0\0\0"),
+            Some(DiskImageKind::Vhdx)
+        );
+        assert_eq!(
+            detect_disk_image(b"KDMV\x01\0\0\0"),
+            Some(DiskImageKind::Vmdk)
+        );
+        assert_eq!(detect_disk_image(b"plain text"), None);
+    }
+}
@@ -0,0 +1,334 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Leitura mínima de EXIF (TIFF/IFD) em JPEG, PNG e WebP para checagens de
+//! consistência e inventário de metadados.
+
+use crate::report::escape_control_chars;
+
+/// Limite de entradas lidas por IFD (arquivos maliciosos declaram milhares).
+const MAX_IFD_ENTRIES: usize = 512;
+
+/// Tamanho máximo guardado de campos texto (fabricante, modelo, software).
+const MAX_ASCII_BYTES: usize = 256;
+
+const TAG_MAKE: u16 = 0x010F;
+const TAG_MODEL: u16 = 0x0110;
+const TAG_ORIENTATION: u16 = 0x0112;
+const TAG_SOFTWARE: u16 = 0x0131;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_GPS_IFD: u16 = 0x8825;
+const TAG_GPS_LATITUDE: u16 = 0x0002;
+const TAG_GPS_LONGITUDE: u16 = 0x0004;
+const TAG_PIXEL_X: u16 = 0xA002;
+const TAG_PIXEL_Y: u16 = 0xA003;
+const TAG_THUMBNAIL_OFFSET: u16 = 0x0201;
+const TAG_THUMBNAIL_LENGTH: u16 = 0x0202;
+
+/// Campos EXIF relevantes para comparar com a imagem decodificada.
+#[derive(Debug, Default)]
+pub(crate) struct ExifSummary<'a> {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub orientation: Option<u16>,
+    /// Miniatura JPEG embutida (IFD1).
+    pub thumbnail: Option<&'a [u8]>,
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub software: Option<String>,
+    /// IFD GPS com latitude ou longitude.
+    pub gps: bool,
+}
+
+/// Localiza o bloco TIFF do EXIF no contêiner da imagem.
+pub(crate) fn find_exif(data: &[u8]) -> Option<&[u8]> {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        jpeg_exif(data)
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png_exif(data)
+    } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        webp_exif(data)
+    } else {
+        None
+    }
+}
+
+fn jpeg_exif(data: &[u8]) -> Option<&[u8]> {
+    jpeg_segment(data, 0xE1, b"Exif\0\0")
+}
+
+/// Corpo (sem `prefix`) do primeiro segmento JPEG `marker` que começa com `prefix`.
+pub(crate) fn jpeg_segment<'a>(data: &'a [u8], marker: u8, prefix: &[u8]) -> Option<&'a [u8]> {
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        // SOS/EOI: depois disso só há dados comprimidos.
+        if data[pos + 1] == 0xDA || data[pos + 1] == 0xD9 {
+            return None;
+        }
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let segment = data.get(pos + 4..pos + 2 + length)?;
+        if data[pos + 1] == marker {
+            if let Some(body) = segment.strip_prefix(prefix) {
+                return Some(body);
+            }
+        }
+        pos += 2 + length;
+    }
+    None
+}
+
+fn png_exif(data: &[u8]) -> Option<&[u8]> {
+    let mut pos = 8;
+    while pos + 8 <= data.len() {
+        let length = u32::from_be_bytes(data[pos..pos + 4].try_into().ok()?) as usize;
+        let kind = &data[pos + 4..pos + 8];
+        let body = data.get(pos + 8..(pos + 8).checked_add(length)?)?;
+        match kind {
+            b"eXIf" => return Some(body),
+            b"IDAT" | b"IEND" => return None,
+            _ => {}
+        }
+        pos += 12 + length;
+    }
+    None
+}
+
+fn webp_exif(data: &[u8]) -> Option<&[u8]> {
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let length = u32::from_le_bytes(data[pos + 4..pos + 8].try_into().ok()?) as usize;
+        let body = data.get(pos + 8..(pos + 8).checked_add(length)?)?;
+        if &data[pos..pos + 4] == b"EXIF" {
+            return Some(body.strip_prefix(b"Exif\0\0").unwrap_or(body));
+        }
+        pos += 8 + length + (length & 1);
+    }
+    None
+}
+
+/// Interpreta o bloco TIFF (IFD0, sub-IFD EXIF e IFD1 da miniatura).
+pub(crate) fn parse_exif(tiff: &[u8]) -> Option<ExifSummary<'_>> {
+    let reader = TiffReader::new(tiff)?;
+    let ifd0_offset = reader.u32_at(4)? as usize;
+    let (ifd0, ifd1_offset) = reader.ifd(ifd0_offset)?;
+
+    let mut summary = ExifSummary {
+        orientation: reader
+            .find_u32(&ifd0, TAG_ORIENTATION)
+            .map(|value| value as u16),
+        make: reader.find_ascii(&ifd0, TAG_MAKE),
+        model: reader.find_ascii(&ifd0, TAG_MODEL),
+        software: reader.find_ascii(&ifd0, TAG_SOFTWARE),
+        ..ExifSummary::default()
+    };
+    if let Some(gps_offset) = reader.find_u32(&ifd0, TAG_GPS_IFD) {
+        if let Some((gps, _)) = reader.ifd(gps_offset as usize) {
+            summary.gps = gps
+                .iter()
+                .any(|entry| matches!(entry.tag, TAG_GPS_LATITUDE | TAG_GPS_LONGITUDE));
+        }
+    }
+    if let Some(exif_offset) = reader.find_u32(&ifd0, TAG_EXIF_IFD) {
+        if let Some((exif, _)) = reader.ifd(exif_offset as usize) {
+            summary.width = reader.find_u32(&exif, TAG_PIXEL_X);
+            summary.height = reader.find_u32(&exif, TAG_PIXEL_Y);
+        }
+    }
+    if ifd1_offset != 0 && ifd1_offset != ifd0_offset {
+        if let Some((ifd1, _)) = reader.ifd(ifd1_offset) {
+            let offset = reader.find_u32(&ifd1, TAG_THUMBNAIL_OFFSET);
+            let length = reader.find_u32(&ifd1, TAG_THUMBNAIL_LENGTH);
+            if let (Some(offset), Some(length)) = (offset, length) {
+                let start = offset as usize;
+                summary.thumbnail = tiff.get(start..start.checked_add(length as usize)?);
+            }
+        }
+    }
+    Some(summary)
+}
+
+/// Entrada de IFD: tag, tipo, quantidade e o campo de valor/offset (4 bytes).
+#[derive(Debug, Clone, Copy)]
+struct IfdEntry {
+    tag: u16,
+    kind: u16,
+    count: u32,
+    value: [u8; 4],
+}
+
+struct TiffReader<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> TiffReader<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        let reader = Self {
+            data,
+            little_endian,
+        };
+        (reader.u16_at(2)? == 42).then_some(reader)
+    }
+
+    fn u16_from(&self, bytes: [u8; 2]) -> u16 {
+        if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        }
+    }
+
+    fn u32_from(&self, bytes: [u8; 4]) -> u32 {
+        if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        }
+    }
+
+    fn u16_at(&self, offset: usize) -> Option<u16> {
+        Some(self.u16_from(self.data.get(offset..offset + 2)?.try_into().ok()?))
+    }
+
+    fn u32_at(&self, offset: usize) -> Option<u32> {
+        Some(self.u32_from(self.data.get(offset..offset + 4)?.try_into().ok()?))
+    }
+
+    /// Lê as entradas de um IFD e o offset do próximo.
+    fn ifd(&self, offset: usize) -> Option<(Vec<IfdEntry>, usize)> {
+        let count = self.u16_at(offset)? as usize;
+        let entries = (0..count.min(MAX_IFD_ENTRIES))
+            .map_while(|index| {
+                let base = offset + 2 + index * 12;
+                Some(IfdEntry {
+                    tag: self.u16_at(base)?,
+                    kind: self.u16_at(base + 2)?,
+                    count: self.u32_at(base + 4)?,
+                    value: self.data.get(base + 8..base + 12)?.try_into().ok()?,
+                })
+            })
+            .collect();
+        let next = self.u32_at(offset + 2 + count * 12).unwrap_or(0) as usize;
+        Some((entries, next))
+    }
+
+    /// Valor escalar SHORT (3) ou LONG (4) de uma tag.
+    fn find_u32(&self, entries: &[IfdEntry], tag: u16) -> Option<u32> {
+        let entry = entries.iter().find(|entry| entry.tag == tag)?;
+        match entry.kind {
+            3 => Some(self.u16_from([entry.value[0], entry.value[1]]) as u32),
+            4 => Some(self.u32_from(entry.value)),
+            _ => None,
+        }
+    }
+
+    /// Texto ASCII (2) de uma tag, sem NULs finais e com caracteres de controle escapados.
+    fn find_ascii(&self, entries: &[IfdEntry], tag: u16) -> Option<String> {
+        let entry = entries
+            .iter()
+            .find(|entry| entry.tag == tag && entry.kind == 2)?;
+        let len = entry.count as usize;
+        let bytes = if len <= 4 {
+            &entry.value[..len]
+        } else {
+            let offset = self.u32_from(entry.value) as usize;
+            self.data.get(offset..offset.checked_add(len)?)?
+        };
+        let bytes = &bytes[..bytes.len().min(MAX_ASCII_BYTES)];
+        let text = String::from_utf8_lossy(bytes);
+        let text = text.trim_end_matches('\0').trim();
+        (!text.is_empty()).then(|| escape_control_chars(text).into_owned())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Segmento APP1 (`Exif\0\0` + TIFF little-endian) com dimensões e orientação.
+    pub(crate) fn app1_segment(width: u32, height: u32, orientation: u16) -> Vec<u8> {
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        // IFD0: Orientation + ponteiro para o sub-IFD EXIF.
+        let exif_offset = 8 + 2 + 2 * 12 + 4;
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        push_entry(&mut tiff, TAG_ORIENTATION, 3, orientation as u32);
+        push_entry(&mut tiff, TAG_EXIF_IFD, 4, exif_offset);
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        push_entry(&mut tiff, TAG_PIXEL_X, 4, width);
+        push_entry(&mut tiff, TAG_PIXEL_Y, 4, height);
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+        segment.extend_from_slice(b"Exif\0\0");
+        segment.extend_from_slice(&tiff);
+        segment
+    }
+
+    fn push_entry(out: &mut Vec<u8>, tag: u16, kind: u16, value: u32) {
+        out.extend_from_slice(&tag.to_le_bytes());
+        out.extend_from_slice(&kind.to_le_bytes());
+        out.extend_from_slice(&1u32.to_le_bytes());
+        if kind == 3 {
+            out.extend_from_slice(&(value as u16).to_le_bytes());
+            out.extend_from_slice(&[0, 0]);
+        } else {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn parses_dimensions_and_orientation_from_jpeg_app1() {
+        let jpeg = [&[0xFF, 0xD8][..], &app1_segment(640, 480, 6), &[0xFF, 0xD9]].concat();
+        let tiff = find_exif(&jpeg).expect("exif");
+        let summary = parse_exif(tiff).expect("parse");
+        assert_eq!(summary.width, Some(640));
+        assert_eq!(summary.height, Some(480));
+        assert_eq!(summary.orientation, Some(6));
+        assert!(summary.thumbnail.is_none());
+        assert!(parse_exif(b"XX*\0").is_none());
+    }
+
+    #[test]
+    fn reads_camera_strings_and_gps_presence() {
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        // IFD0: Make (texto fora da entrada), Model (texto na entrada) e ponteiro GPS.
+        let make_offset = 8 + 2 + 3 * 12 + 4;
+        let gps_offset = make_offset + 8;
+        tiff.extend_from_slice(&3u16.to_le_bytes());
+        for (tag, count, value) in [
+            (TAG_MAKE, 8u32, (make_offset as u32).to_le_bytes()),
+            (TAG_MODEL, 3, *b"X1\0\0"),
+        ] {
+            tiff.extend_from_slice(&tag.to_le_bytes());
+            tiff.extend_from_slice(&2u16.to_le_bytes());
+            tiff.extend_from_slice(&count.to_le_bytes());
+            tiff.extend_from_slice(&value);
+        }
+        push_entry(&mut tiff, TAG_GPS_IFD, 4, gps_offset as u32);
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        tiff.extend_from_slice(b"Canon\x1b\0\0");
+        tiff.extend_from_slice(&1u16.to_le_bytes());
+        push_entry(&mut tiff, TAG_GPS_LATITUDE, 4, 0);
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+
+        let summary = parse_exif(&tiff).expect("parse");
+        assert_eq!(summary.make.as_deref(), Some("Canon\\x1b"));
+        assert_eq!(summary.model.as_deref(), Some("X1"));
+        assert!(summary.software.is_none());
+        assert!(summary.gps);
+    }
+}
@@ -0,0 +1,182 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Validador do nome do arquivo enviado: caracteres de controle, marcas
+//! bidirecionais (extensão falsificada com RTLO), nomes reservados do Windows,
+//! nomes longos demais, extensão dupla (`fatura.pdf.exe`) e hífen inicial
+//! (interpretado como opção por ferramentas de linha de comando).
+
+use super::{Findings, ValidatorOutcome};
+use crate::policy::{matches_pattern, ResolvedPolicy};
+use serde_json::json;
+
+const NAME: &str = "filename";
+/// `filename.max_length` padrão, em bytes (limite comum de sistemas de arquivos).
+const DEFAULT_MAX_LENGTH: u32 = 255;
+
+/// Dispositivos que o Windows reserva com qualquer extensão (`con.txt`).
+const RESERVED_DEVICES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Extensões executáveis ou de script que encerram a extensão dupla.
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "scr", "com", "pif", "bat", "cmd", "js", "jse", "vbs", "vbe", "wsf", "wsh", "hta",
+    "msi", "lnk", "ps1", "jar", "dll", "cpl", "sh", "app", "apk",
+];
+
+/// Extensões de documento e mídia usadas como isca antes da executável.
+const DECOY_EXTENSIONS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "rtf", "txt", "csv", "jpg",
+    "jpeg", "png", "gif", "bmp", "webp", "mp3", "mp4", "avi", "mov", "zip", "rar", "7z", "html",
+];
+
+/// Marcas bidirecionais explícitas (embeddings, overrides, isolates e marcas).
+fn is_bidi_control(ch: char) -> bool {
+    matches!(ch, '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}' | '\u{200e}' | '\u{200f}' | '\u{061c}')
+}
+
+/// Valida `name` (só o nome, sem diretórios) contra a seção `filename` da política.
+pub fn validate_filename(name: &str, policy: Option<&ResolvedPolicy>) -> ValidatorOutcome {
+    let section = policy.map(|policy| &policy.filename);
+    let details = json!({ "name_bytes": name.len() });
+    let mut findings = Findings::new(policy);
+    let lowered = name.to_lowercase();
+
+    if name.chars().any(char::is_control)
+        && findings.push_code("control_chars", "nome com caracteres de controle")
+    {
+        return findings.into_deny(NAME).with_details(details);
+    }
+    if name.chars().any(is_bidi_control)
+        && findings.push_code(
+            "bidi_override",
+            "nome com marca bidirecional (extensão pode estar falsificada)",
+        )
+    {
+        return findings.into_deny(NAME).with_details(details);
+    }
+    let max_length = section
+        .and_then(|section| section.max_length)
+        .unwrap_or(DEFAULT_MAX_LENGTH);
+    if name.len() > max_length as usize
+        && findings.push_code(
+            "too_long",
+            format!("nome com {} bytes excede {max_length}", name.len()),
+        )
+    {
+        return findings.into_deny(NAME).with_details(details);
+    }
+    if name.starts_with('-') && findings.push_code("leading_dash", "nome começa com hífen") {
+        return findings.into_deny(NAME).with_details(details);
+    }
+    // O Windows reserva o nome com qualquer extensão e descarta espaços antes dela.
+    let stem = lowered
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(' ');
+    if RESERVED_DEVICES.contains(&stem)
+        && findings.push_code(
+            "reserved_device",
+            format!("nome reservado do Windows: {stem}"),
+        )
+    {
+        return findings.into_deny(NAME).with_details(details);
+    }
+    if let Some((decoy, executable)) = double_extension(&lowered) {
+        if findings.push_code(
+            "double_extension",
+            format!("extensão dupla: .{decoy}.{executable}"),
+        ) {
+            return findings.into_deny(NAME).with_details(details);
+        }
+    }
+
+    if let Some(section) = section {
+        let matches = |pattern: &String| matches_pattern(&pattern.to_lowercase(), &lowered);
+        if let Some(pattern) = section.deny.iter().find(|pattern| matches(pattern)) {
+            if findings.push_code("denied_pattern", format!("nome bloqueado por {pattern}")) {
+                return findings.into_deny(NAME).with_details(details);
+            }
+        }
+        if !section.allow.is_empty() && !section.allow.iter().any(matches) {
+            findings.push_code("not_allowed", "nome fora de filename.allow");
+        }
+    }
+
+    if findings.is_empty() {
+        ValidatorOutcome::pass(NAME)
+    } else {
+        findings.into_deny(NAME).with_details(details)
+    }
+}
+
+/// Última extensão executável precedida por uma de documento (`.pdf.exe`),
+/// ignorando espaços de preenchimento entre elas.
+fn double_extension(lowered: &str) -> Option<(&str, &str)> {
+    let mut parts = lowered.trim_start_matches('.').rsplit('.').map(str::trim);
+    let executable = parts.next()?;
+    let decoy = parts.next()?;
+    parts.next()?;
+    (EXECUTABLE_EXTENSIONS.contains(&executable) && DECOY_EXTENSIONS.contains(&decoy))
+        .then_some((decoy, executable))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FilenamePolicySection;
+    use crate::validators::ValidatorStatus;
+
+    fn codes(name: &str, policy: Option<&ResolvedPolicy>) -> Vec<String> {
+        let outcome = validate_filename(name, policy);
+        match outcome.status {
+            ValidatorStatus::Pass => Vec::new(),
+            _ => outcome.codes().into_iter().map(str::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn unsafe_names_are_denied_with_stable_codes() {
+        assert!(codes("relatorio.final.pdf", None).is_empty());
+        assert!(codes("jquery.min.js", None).is_empty());
+        assert_eq!(codes("fatura.pdf.exe", None), ["double_extension"]);
+        assert_eq!(codes("foto.JPG   .Scr", None), ["double_extension"]);
+        assert_eq!(codes("fatura\u{202e}fdp.exe", None), ["bidi_override"]);
+        assert_eq!(codes("a\nb.txt", None), ["control_chars"]);
+        assert_eq!(codes("CON.txt", None), ["reserved_device"]);
+        assert_eq!(codes("lpt1 .log", None), ["reserved_device"]);
+        assert!(codes("console.txt", None).is_empty());
+        assert_eq!(codes("-rf.txt", None), ["leading_dash"]);
+        assert_eq!(codes(&"a".repeat(256), None), ["too_long"]);
+
+        let policy = ResolvedPolicy {
+            filename: FilenamePolicySection {
+                max_length: Some(12),
+                allow: vec!["*.pdf".into(), "*.png".into()],
+                deny: vec!["*senha*".into()],
+            },
+            ..ResolvedPolicy::default()
+        };
+        assert!(codes("nota.PDF", Some(&policy)).is_empty());
+        assert_eq!(codes("nota.docx", Some(&policy)), ["not_allowed"]);
+        assert_eq!(codes("SENHAS.pdf", Some(&policy)), ["denied_pattern"]);
+        assert_eq!(codes("contrato-2026.pdf", Some(&policy)), ["too_long"]);
+
+        let exhaustive = ResolvedPolicy {
+            defaults: crate::config::DefaultsSection {
+                report_all_findings: Some(true),
+                ..Default::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        assert_eq!(
+            codes("-nul.pdf.exe", Some(&exhaustive)),
+            ["leading_dash", "double_extension"]
+        );
+    }
+}
@@ -0,0 +1,581 @@
+// GuardUpload
+// Criado em: 2025-11-01
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Validador de imagens (PNG/JPEG/WebP/GIF etc.).
+
+use super::exif::{self, ExifSummary};
+use super::metadata;
+use super::ValidatorOutcome;
+use crate::limits::Deadline;
+use crate::policy::ResolvedPolicy;
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, ImageReader};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+
+/// Diferença relativa tolerada entre dimensões EXIF e decodificadas.
+const EXIF_DIMENSION_TOLERANCE: f64 = 0.10;
+/// Fator máximo entre as proporções da miniatura EXIF e da imagem.
+const THUMBNAIL_ASPECT_FACTOR: f64 = 1.5;
+/// Diferença relativa padrão tolerada em `allowed_aspect_ratios`.
+const DEFAULT_ASPECT_TOLERANCE: f64 = 0.01;
+/// `image.max_pixels` padrão: 64 megapixels.
+pub(crate) const DEFAULT_MAX_PIXELS: u64 = 64_000_000;
+
+pub fn validate_image(
+    mime: &str,
+    data: &[u8],
+    policy: Option<&ResolvedPolicy>,
+    deadline: &Deadline,
+) -> ValidatorOutcome {
+    let name = "image";
+    let reader = match ImageReader::new(Cursor::new(data)).with_guessed_format() {
+        Ok(reader) => reader,
+        Err(err) => {
+            return ValidatorOutcome::error(
+                name,
+                format!("falha ao detectar formato de imagem: {err}"),
+            )
+        }
+    };
+
+    let format = reader.format();
+    let dimensions = match reader.into_dimensions() {
+        Ok(dim) => dim,
+        Err(err) => {
+            return ValidatorOutcome::deny(
+                name,
+                format!("não foi possível ler dimensões da imagem: {err}"),
+            )
+        }
+    };
+
+    let (width, height) = dimensions;
+    let mut details = json!({
+        "mime": mime,
+        "width": width,
+        "height": height,
+    });
+    if let Some(fmt) = format {
+        let extensions = fmt.extensions_str();
+        if !extensions.is_empty() {
+            details["format"] = json!(extensions.join(","));
+        }
+    }
+
+    let image_policy = policy.map(|p| p.image.clone()).unwrap_or_default();
+
+    if let Some([max_w, max_h]) = image_policy.max_dimensions {
+        if width > max_w || height > max_h {
+            return ValidatorOutcome::deny(
+                name,
+                format!(
+                    "dimensões excedem o limite configurado: {width}x{height} > {max_w}x{max_h}"
+                ),
+            )
+            .with_partial_details(details);
+        }
+    }
+
+    // Antes de qualquer decodificação: um PNG de poucos bytes pode declarar
+    // 100000x100000 e esgotar a memória de quem o abrir depois.
+    let max_pixels = image_policy.max_pixels.unwrap_or(DEFAULT_MAX_PIXELS);
+    if max_pixels > 0 {
+        let frames = if mime.eq_ignore_ascii_case("image/gif") {
+            gif_frame_count(data).max(1)
+        } else {
+            1
+        };
+        let pixels = u64::from(width)
+            .saturating_mul(u64::from(height))
+            .saturating_mul(u64::from(frames));
+        if pixels > max_pixels {
+            details["pixels"] = json!(pixels);
+            return ValidatorOutcome::deny(
+                name,
+                format!(
+                    "imagem excede o orçamento de pixels: {pixels} > {max_pixels} \
+                     ({width}x{height}, {frames} frame(s))"
+                ),
+            )
+            .with_partial_details(details);
+        }
+    }
+
+    if let Some([min_w, min_h]) = image_policy.min_dimensions {
+        if width < min_w || height < min_h {
+            return ValidatorOutcome::deny(
+                name,
+                format!(
+                    "dimensões abaixo do mínimo configurado: {width}x{height} < {min_w}x{min_h}"
+                ),
+            )
+            .with_partial_details(details);
+        }
+    }
+
+    if !image_policy.allowed_aspect_ratios.is_empty() && height > 0 {
+        let ratio = width as f64 / height as f64;
+        details["aspect_ratio"] = json!((ratio * 10_000.0).round() / 10_000.0);
+        let tolerance = image_policy
+            .aspect_ratio_tolerance
+            .unwrap_or(DEFAULT_ASPECT_TOLERANCE);
+        let mut allowed = Vec::new();
+        for raw in &image_policy.allowed_aspect_ratios {
+            match parse_aspect_ratio(raw) {
+                Some(expected) => allowed.push(expected),
+                None => {
+                    return ValidatorOutcome::error(
+                        name,
+                        format!("proporção inválida em allowed_aspect_ratios: '{raw}'"),
+                    )
+                }
+            }
+        }
+        if !allowed
+            .iter()
+            .any(|expected| ((ratio - expected) / expected).abs() <= tolerance)
+        {
+            return ValidatorOutcome::deny(
+                name,
+                format!(
+                    "proporção {width}x{height} ({ratio:.4}) fora das permitidas: {}",
+                    image_policy.allowed_aspect_ratios.join(", ")
+                ),
+            )
+            .with_partial_details(details);
+        }
+    }
+
+    let mut frame_count = 1u32;
+    if mime.eq_ignore_ascii_case("image/gif") && image_policy.header_only.unwrap_or(false) {
+        frame_count = gif_frame_count(data).max(1);
+    } else if mime.eq_ignore_ascii_case("image/gif") {
+        if let Ok(decoder) = GifDecoder::new(Cursor::new(data)) {
+            frame_count = 0;
+            for _frame in decoder.into_frames() {
+                if deadline.is_expired() {
+                    details["frames"] = json!(frame_count);
+                    return ValidatorOutcome::timeout(name).with_partial_details(details);
+                }
+                frame_count += 1;
+                // Não há por que decodificar além do limite: o excesso já basta para negar.
+                if image_policy
+                    .max_frames
+                    .is_some_and(|max_frames| frame_count > max_frames)
+                {
+                    break;
+                }
+            }
+        }
+    }
+    details["frames"] = json!(frame_count);
+
+    if let Some(max_frames) = image_policy.max_frames {
+        if frame_count > max_frames {
+            return ValidatorOutcome::deny(
+                name,
+                format!("frames excedem o limite permitido: mais de {max_frames}"),
+            )
+            .with_partial_details(details);
+        }
+    }
+
+    let exif_summary = exif::find_exif(data).and_then(exif::parse_exif);
+    let metadata = metadata::inspect(data, exif_summary.as_ref());
+    if !metadata.sources.is_empty() {
+        details["metadata"] = json!(metadata);
+    }
+    if metadata.gps && image_policy.deny_gps_metadata.unwrap_or(false) {
+        return ValidatorOutcome::deny(name, "imagem contém coordenadas GPS nos metadados")
+            .with_partial_details(details);
+    }
+
+    if let Some(summary) = exif_summary {
+        let mismatches = exif_mismatches(&summary, width, height, &mut details);
+        let action = image_policy.exif_mismatch.as_deref().unwrap_or("warn");
+        if !mismatches.is_empty() && !action.eq_ignore_ascii_case("allow") {
+            let message = format!("metadados EXIF inconsistentes: {}", mismatches.join("; "));
+            let outcome = if action.eq_ignore_ascii_case("deny") {
+                ValidatorOutcome::deny(name, message)
+            } else {
+                ValidatorOutcome::warn(name, message)
+            };
+            return outcome.with_details(details);
+        }
+    }
+
+    if image_policy.warn_on_metadata.unwrap_or(false) && !metadata.sources.is_empty() {
+        return ValidatorOutcome::warn(
+            name,
+            format!("imagem contém metadados: {}", metadata.sources.join(", ")),
+        )
+        .with_details(details);
+    }
+
+    if let Some(mode) = image_policy.strip_metadata.as_deref() {
+        if mode.eq_ignore_ascii_case("deny") {
+            match metadata::strip_metadata(data) {
+                Some(stripped) => {
+                    details["sanitized"] = json!({
+                        "sha256": hex::encode(Sha256::digest(&stripped.data)),
+                        "size_bytes": stripped.data.len(),
+                        "removed": stripped.removed,
+                    });
+                }
+                None => {
+                    return ValidatorOutcome::warn(
+                        name,
+                        format!(
+                            "strip de metadados não suportado para {mime} — arquivo retornado sem alterações"
+                        ),
+                    )
+                    .with_details(details);
+                }
+            }
+        }
+    }
+
+    let mut outcome = ValidatorOutcome::pass(name);
+    outcome.details = details;
+    outcome
+}
+
+/// Conta os frames de um GIF percorrendo os blocos, sem descomprimir; em arquivos
+/// truncados, devolve os frames encontrados até o corte.
+pub(crate) fn gif_frame_count(data: &[u8]) -> u32 {
+    let color_table = |flags: u8| {
+        if flags & 0x80 != 0 {
+            3 << ((flags & 0x07) + 1)
+        } else {
+            0
+        }
+    };
+    let Some(&screen_flags) = data.get(10).filter(|_| data.starts_with(b"GIF")) else {
+        return 0;
+    };
+    let mut pos = 13 + color_table(screen_flags);
+    let mut frames = 0;
+    loop {
+        let next = match data.get(pos) {
+            // Descritor de imagem: 10 bytes, tabela local, tamanho mínimo do LZW e dados.
+            Some(0x2C) => data.get(pos + 9).and_then(|&flags| {
+                frames += 1;
+                skip_sub_blocks(data, pos + 10 + color_table(flags) + 1)
+            }),
+            // Extensão: rótulo e sub-blocos.
+            Some(0x21) => skip_sub_blocks(data, pos + 2),
+            _ => None,
+        };
+        match next {
+            Some(next) => pos = next,
+            None => return frames,
+        }
+    }
+}
+
+/// Posição logo após a sequência de sub-blocos GIF iniciada em `pos`.
+fn skip_sub_blocks(data: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = usize::from(*data.get(pos)?);
+        pos += 1;
+        if len == 0 {
+            return Some(pos);
+        }
+        pos += len;
+    }
+}
+
+/// Interpreta `"16:9"` ou `"1.91"` como largura/altura.
+fn parse_aspect_ratio(raw: &str) -> Option<f64> {
+    let ratio = match raw.split_once(':') {
+        Some((w, h)) => w.trim().parse::<f64>().ok()? / h.trim().parse::<f64>().ok()?,
+        None => raw.trim().parse::<f64>().ok()?,
+    };
+    (ratio.is_finite() && ratio > 0.0).then_some(ratio)
+}
+
+/// Compara EXIF declarado com a imagem decodificada e registra os campos em `details.exif`.
+fn exif_mismatches(
+    summary: &ExifSummary<'_>,
+    width: u32,
+    height: u32,
+    details: &mut serde_json::Value,
+) -> Vec<String> {
+    let mut exif_details = json!({});
+    let mut mismatches = Vec::new();
+
+    if let (Some(exif_w), Some(exif_h)) = (summary.width, summary.height) {
+        exif_details["width"] = json!(exif_w);
+        exif_details["height"] = json!(exif_h);
+        // Alguns editores gravam as dimensões já rotacionadas.
+        if !dimensions_close((exif_w, exif_h), (width, height))
+            && !dimensions_close((exif_h, exif_w), (width, height))
+        {
+            mismatches.push(format!(
+                "dimensões EXIF {exif_w}x{exif_h} divergem das decodificadas {width}x{height}"
+            ));
+        }
+    }
+    if let Some(orientation) = summary.orientation {
+        exif_details["orientation"] = json!(orientation);
+        if !(1..=8).contains(&orientation) {
+            mismatches.push(format!("orientação EXIF inválida: {orientation}"));
+        }
+    }
+    if let Some(thumbnail) = summary.thumbnail {
+        let thumbnail_dimensions = ImageReader::new(Cursor::new(thumbnail))
+            .with_guessed_format()
+            .ok()
+            .and_then(|reader| reader.into_dimensions().ok());
+        match thumbnail_dimensions {
+            Some((thumb_w, thumb_h)) => {
+                exif_details["thumbnail"] = json!({ "width": thumb_w, "height": thumb_h });
+                if thumb_w.max(thumb_h) > width.max(height) {
+                    mismatches.push(format!(
+                        "miniatura EXIF {thumb_w}x{thumb_h} maior que a imagem {width}x{height}"
+                    ));
+                } else if aspect_factor((thumb_w, thumb_h), (width, height))
+                    > THUMBNAIL_ASPECT_FACTOR
+                {
+                    mismatches.push(format!(
+                        "proporção da miniatura EXIF {thumb_w}x{thumb_h} diverge da imagem {width}x{height}"
+                    ));
+                }
+            }
+            None => mismatches.push("miniatura EXIF ilegível".to_string()),
+        }
+    }
+
+    details["exif"] = exif_details;
+    mismatches
+}
+
+fn dimensions_close(declared: (u32, u32), actual: (u32, u32)) -> bool {
+    let close =
+        |a: u32, b: u32| (a as f64 - b as f64).abs() <= EXIF_DIMENSION_TOLERANCE * a.max(b) as f64;
+    close(declared.0, actual.0) && close(declared.1, actual.1)
+}
+
+/// Menor fator entre proporções, considerando a miniatura em qualquer orientação.
+fn aspect_factor(thumbnail: (u32, u32), image: (u32, u32)) -> f64 {
+    let ratio = |(w, h): (u32, u32)| w.max(1) as f64 / h.max(1) as f64;
+    let image_ratio = ratio(image);
+    [ratio(thumbnail), ratio((thumbnail.1, thumbnail.0))]
+        .into_iter()
+        .map(|thumb_ratio| (thumb_ratio / image_ratio).max(image_ratio / thumb_ratio))
+        .fold(f64::INFINITY, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ImagePolicySection;
+    use crate::validators::exif::tests::app1_segment;
+    use crate::validators::ValidatorStatus;
+    use image::codecs::gif::GifEncoder;
+    use image::codecs::jpeg::JpegEncoder;
+    use image::codecs::png::PngEncoder;
+    use image::{ExtendedColorType, ImageEncoder};
+
+    fn jpeg_with_exif(width: u32, height: u32, exif: &[u8]) -> Vec<u8> {
+        let pixels = vec![128u8; (width * height * 3) as usize];
+        let mut jpeg = Vec::new();
+        JpegEncoder::new(&mut jpeg)
+            .encode(&pixels, width, height, ExtendedColorType::Rgb8)
+            .expect("encode");
+        [&jpeg[..2], exif, &jpeg[2..]].concat()
+    }
+
+    #[test]
+    fn exif_dimensions_are_compared_with_decoded_image() {
+        let consistent = jpeg_with_exif(32, 16, &app1_segment(32, 16, 1));
+        let outcome = validate_image("image/jpeg", &consistent, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(outcome.details["exif"]["width"], 32);
+
+        let rotated = jpeg_with_exif(32, 16, &app1_segment(16, 32, 6));
+        let outcome = validate_image("image/jpeg", &rotated, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+
+        let tampered = jpeg_with_exif(32, 16, &app1_segment(4000, 3000, 9));
+        let outcome = validate_image("image/jpeg", &tampered, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Warn));
+        let message = outcome.details["message"].as_str().unwrap();
+        assert!(message.contains("4000x3000"));
+        assert!(message.contains("orientação EXIF inválida: 9"));
+    }
+
+    #[test]
+    fn shape_requirements_report_measured_values() {
+        let policy = |min: Option<[u32; 2]>, ratios: &[&str]| ResolvedPolicy {
+            image: ImagePolicySection {
+                min_dimensions: min,
+                allowed_aspect_ratios: ratios.iter().map(|ratio| ratio.to_string()).collect(),
+                ..ImagePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let banner = jpeg_with_exif(32, 18, &[]);
+        let validate = |policy: &ResolvedPolicy| {
+            validate_image("image/jpeg", &banner, Some(policy), &Deadline::none())
+        };
+
+        let outcome = validate(&policy(Some([16, 16]), &["1:1", "16:9"]));
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(outcome.details["aspect_ratio"], 1.7778);
+
+        let outcome = validate(&policy(None, &["1:1"]));
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert!(outcome.details["message"]
+            .as_str()
+            .unwrap()
+            .contains("32x18 (1.7778)"));
+
+        let outcome = validate(&policy(Some([64, 64]), &[]));
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(outcome.details["width"], 32);
+
+        let outcome = validate(&policy(None, &["quadrado"]));
+        assert!(matches!(outcome.status, ValidatorStatus::Error));
+    }
+
+    #[test]
+    fn pixel_budget_counts_gif_frames_from_headers() {
+        let mut gif = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut gif);
+            let frame = image::RgbaImage::new(10, 10);
+            for _ in 0..3 {
+                encoder
+                    .encode_frame(image::Frame::new(frame.clone()))
+                    .expect("frame");
+            }
+        }
+        assert_eq!(gif_frame_count(&gif), 3);
+        assert_eq!(gif_frame_count(&gif[..gif.len() / 2]), 1);
+
+        let policy = |max_pixels| ResolvedPolicy {
+            image: ImagePolicySection {
+                max_pixels: Some(max_pixels),
+                ..ImagePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let validate = |policy: &ResolvedPolicy| {
+            validate_image("image/gif", &gif, Some(policy), &Deadline::none())
+        };
+
+        let outcome = validate(&policy(250));
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(outcome.details["pixels"], 300);
+        assert!(matches!(
+            validate(&policy(300)).status,
+            ValidatorStatus::Pass
+        ));
+        assert!(matches!(validate(&policy(0)).status, ValidatorStatus::Pass));
+
+        let header_only = ResolvedPolicy {
+            image: ImagePolicySection {
+                header_only: Some(true),
+                max_frames: Some(2),
+                ..ImagePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let outcome = validate(&header_only);
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(outcome.details["frames"], 3);
+    }
+
+    #[test]
+    fn gps_metadata_denies_and_metadata_warns_when_configured() {
+        let xmp = b"<x:xmpmeta><rdf:Description exif:GPSLatitude=\"23,33.0S\" \
+            exif:GPSLongitude=\"46,38.0W\"/></x:xmpmeta>";
+        let mut app1 = vec![0xFF, 0xE1];
+        let payload = [&b"http://ns.adobe.com/xap/1.0/\0"[..], xmp].concat();
+        app1.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        app1.extend_from_slice(&payload);
+        let segments = [app1_segment(16, 16, 1), app1].concat();
+        let photo = jpeg_with_exif(16, 16, &segments);
+        let policy = |deny_gps, warn| ResolvedPolicy {
+            image: ImagePolicySection {
+                deny_gps_metadata: Some(deny_gps),
+                warn_on_metadata: Some(warn),
+                ..ImagePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let validate = |policy: &ResolvedPolicy| {
+            validate_image("image/jpeg", &photo, Some(policy), &Deadline::none())
+        };
+
+        let outcome = validate(&policy(true, false));
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(outcome.details["metadata"]["gps"], true);
+
+        let outcome = validate(&policy(false, true));
+        assert!(matches!(outcome.status, ValidatorStatus::Warn));
+        assert_eq!(
+            outcome.details["message"],
+            "imagem contém metadados: exif, xmp"
+        );
+
+        let outcome = validate(&policy(false, false));
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(
+            outcome.details["metadata"]["sources"],
+            json!(["exif", "xmp"])
+        );
+    }
+
+    #[test]
+    fn strip_metadata_deny_records_sanitized_copy() {
+        let policy = ResolvedPolicy {
+            image: ImagePolicySection {
+                strip_metadata: Some("deny".into()),
+                ..ImagePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let comment = [&[0xFF, 0xFE, 0x00, 0x06][..], b"nota"].concat();
+        let photo = jpeg_with_exif(16, 16, &[app1_segment(16, 16, 1), comment].concat());
+        let outcome = validate_image("image/jpeg", &photo, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(
+            outcome.details["sanitized"]["removed"],
+            json!(["exif", "comment"])
+        );
+        let stripped = metadata::strip_metadata(&photo).expect("jpeg");
+        assert_eq!(
+            outcome.details["sanitized"]["sha256"],
+            hex::encode(Sha256::digest(&stripped.data))
+        );
+        assert!(!stripped.data.windows(5).any(|window| window == b"Exif\0"));
+        assert!(image::load_from_memory(&stripped.data).is_ok());
+
+        let mut png = Vec::new();
+        PngEncoder::new(&mut png)
+            .write_image(&[0u8; 4 * 4 * 3], 4, 4, ExtendedColorType::Rgb8)
+            .expect("png");
+        let text = b"\0\0\0\x0ctEXtAuthor\0Maria\0\0\0\0";
+        let tagged = [&png[..33], &text[..], &png[33..]].concat();
+        let stripped = metadata::strip_metadata(&tagged).expect("png");
+        assert_eq!(stripped.data, png);
+        assert_eq!(stripped.removed, vec!["text"]);
+
+        let outcome = validate_image("image/gif", &gif_bytes(), Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Warn));
+    }
+
+    fn gif_bytes() -> Vec<u8> {
+        let mut gif = Vec::new();
+        GifEncoder::new(&mut gif)
+            .encode_frame(image::Frame::new(image::RgbaImage::new(2, 2)))
+            .expect("frame");
+        gif
+    }
+}
@@ -0,0 +1,273 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Checagens comuns a containers listados pelos cabeçalhos (7z, RAR).
+//!
+//! Os validadores de cada formato só extraem a lista de entradas; path traversal,
+//! symlinks, criptografia, nomes repetidos, profundidade, manifesto e limites de
+//! entradas, volume e taxa seguem a seção `archive` da política, como no ZIP.
+
+use super::archive::{
+    depth_of, index_details, index_limit, manifest_violations, normalize_entry_name, IndexEntry,
+};
+use super::{Findings, ValidatorOutcome};
+use crate::limits::Deadline;
+use crate::policy::ResolvedPolicy;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Limite de nomes listados em `details.entries` (o total continua em `entry_count`).
+const MAX_LISTED_ENTRIES: usize = 200;
+
+/// Entrada lida do cabeçalho do container.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ListedEntry {
+    pub(crate) name: String,
+    pub(crate) size: u64,
+    pub(crate) is_dir: bool,
+    pub(crate) encrypted: bool,
+    /// Symlink, hardlink ou junção (atributos Unix ou registro de redirecionamento).
+    pub(crate) link: bool,
+}
+
+/// Conteúdo listado de um container.
+#[derive(Debug, Default)]
+pub(crate) struct Listing {
+    pub(crate) entries: Vec<ListedEntry>,
+    /// Blocos comprimidos (rótulo, bytes descomprimidos, bytes comprimidos), base
+    /// de `archive.zip_max_ratio`: uma entrada no RAR, um folder sólido no 7z.
+    pub(crate) blocks: Vec<(String, u64, u64)>,
+    /// Cabeçalhos cifrados: nenhuma entrada pôde ser listada.
+    pub(crate) encrypted_headers: bool,
+}
+
+/// Falha ao listar o container.
+#[derive(Debug)]
+pub(crate) enum ListingError {
+    /// Estrutura válida, mas codificação sem suporte (ex.: cabeçalho com BZip2).
+    Unsupported(String),
+    Invalid(String),
+    Timeout,
+}
+
+/// Aplica a seção `archive` da política à listagem de um container.
+pub(crate) fn validate_listing(
+    name: &'static str,
+    format: &str,
+    listing: Result<Listing, ListingError>,
+    policy: Option<&ResolvedPolicy>,
+    deadline: &Deadline,
+) -> ValidatorOutcome {
+    let listing = match listing {
+        Ok(listing) => listing,
+        Err(ListingError::Unsupported(message)) => {
+            return ValidatorOutcome::warn(name, message).with_details(json!({ "format": format }))
+        }
+        Err(ListingError::Invalid(message)) => {
+            return ValidatorOutcome::error(name, format!("arquivo {format} inválido: {message}"))
+        }
+        Err(ListingError::Timeout) => {
+            return ValidatorOutcome::timeout(name)
+                .with_partial_details(json!({ "format": format }))
+        }
+    };
+    let archive_policy = policy.map(|p| p.archive.clone()).unwrap_or_default();
+    let mut findings = Findings::new(policy);
+
+    if listing.encrypted_headers {
+        let message =
+            format!("{format} com cabeçalhos cifrados; as entradas não podem ser listadas");
+        let details = json!({ "format": format, "encrypted_headers": true });
+        if archive_policy.forbid_encrypted_entries.unwrap_or(false) {
+            findings.push(message);
+            return findings.into_deny(name).with_details(details);
+        }
+        return ValidatorOutcome::warn(name, message).with_details(details);
+    }
+
+    let encrypted_entries: Vec<&str> = listing
+        .entries
+        .iter()
+        .filter(|entry| entry.encrypted)
+        .map(|entry| entry.name.as_str())
+        .collect();
+    let total_uncompressed = listing
+        .entries
+        .iter()
+        .fold(0u64, |total, entry| total.saturating_add(entry.size));
+    let ratios: Vec<(&str, f64)> = listing
+        .blocks
+        .iter()
+        .map(|(label, unpacked, packed)| {
+            let ratio = match (*unpacked, *packed) {
+                (0, _) => 1.0,
+                (_, 0) => f64::INFINITY,
+                (unpacked, packed) => unpacked as f64 / packed as f64,
+            };
+            (label.as_str(), ratio)
+        })
+        .collect();
+    let worst_ratio = ratios
+        .iter()
+        .map(|(_, ratio)| *ratio)
+        .fold(None, |worst: Option<f64>, ratio| {
+            Some(worst.map_or(ratio, |worst| worst.max(ratio)))
+        });
+    let mut summary = json!({
+        "format": format,
+        "entry_count": listing.entries.len(),
+        "entries": listing
+            .entries
+            .iter()
+            .take(MAX_LISTED_ENTRIES)
+            .map(|entry| entry.name.as_str())
+            .collect::<Vec<_>>(),
+        "encrypted_entries": encrypted_entries,
+        "total_uncompressed_bytes": total_uncompressed,
+        "worst_ratio": worst_ratio,
+    });
+    // Sem descompressão do conteúdo: o inventário traz só nome e tamanho.
+    if let Some(limit) = index_limit(&archive_policy) {
+        let index: Vec<IndexEntry> = listing
+            .entries
+            .iter()
+            .take(limit)
+            .map(|entry| IndexEntry {
+                name: entry.name.clone(),
+                size: entry.size,
+                is_dir: entry.is_dir,
+                encrypted: entry.encrypted,
+                ..IndexEntry::default()
+            })
+            .collect();
+        if let (Some(summary), Value::Object(index)) = (
+            summary.as_object_mut(),
+            index_details(&index, listing.entries.len()),
+        ) {
+            summary.extend(index);
+        }
+    }
+
+    if let Some(max_entries) = archive_policy.max_entries {
+        if listing.entries.len() > max_entries as usize
+            && findings.push(format!(
+                "{format} possui {} entradas, acima de archive.max_entries ({max_entries})",
+                listing.entries.len()
+            ))
+        {
+            return findings.into_deny(name).with_partial_details(summary);
+        }
+    }
+
+    let forbid_traversal = archive_policy.forbid_path_traversal.unwrap_or(true);
+    let forbid_links = archive_policy.forbid_symlinks.unwrap_or(true);
+    let forbid_duplicates = archive_policy.forbid_overlapping_entries.unwrap_or(true);
+    let mut normalized_names = BTreeMap::new();
+    for entry in &listing.entries {
+        if deadline.is_expired() {
+            return ValidatorOutcome::timeout(name).with_partial_details(summary);
+        }
+        let mut issues = Vec::new();
+        if forbid_traversal && is_unsafe_name(&entry.name) {
+            issues.push(format!("entrada '{}' possui path traversal", entry.name));
+        }
+        if forbid_links && entry.link {
+            issues.push(format!("entrada '{}' é symlink não permitido", entry.name));
+        }
+        if forbid_duplicates {
+            if let Some(previous) =
+                normalized_names.insert(normalize_entry_name(&entry.name), entry.name.as_str())
+            {
+                issues.push(format!(
+                    "entrada '{}' duplica o nome de '{previous}'",
+                    entry.name
+                ));
+            }
+        }
+        if let Some(max_depth) = archive_policy.zip_max_depth {
+            let depth = depth_of(&entry.name);
+            if depth > max_depth as usize {
+                issues.push(format!(
+                    "profundidade excede limite ({depth} > {max_depth}) na entrada '{}'",
+                    entry.name
+                ));
+            }
+        }
+        for issue in issues {
+            if findings.push(issue) {
+                return findings.into_deny(name).with_partial_details(summary);
+            }
+        }
+    }
+
+    if let Some(max_mb) = archive_policy.max_total_uncompressed_mb {
+        let max_bytes = max_mb.saturating_mul(1024 * 1024);
+        if total_uncompressed > max_bytes
+            && findings.push(format!(
+                "soma descomprimida das entradas excede archive.max_total_uncompressed_mb ({total_uncompressed} > {max_bytes} bytes)"
+            ))
+        {
+            return findings.into_deny(name).with_partial_details(summary);
+        }
+    }
+
+    if let Some(max_ratio) = archive_policy.zip_max_ratio {
+        for (label, ratio) in &ratios {
+            if *ratio > max_ratio as f64
+                && findings.push(format!(
+                    "bloco '{label}' excede zip_max_ratio (ratio={ratio:.2} > {max_ratio})"
+                ))
+            {
+                return findings.into_deny(name).with_partial_details(summary);
+            }
+        }
+    }
+
+    if !encrypted_entries.is_empty()
+        && archive_policy.forbid_encrypted_entries.unwrap_or(false)
+        && findings.push(format!(
+            "{format} possui entradas criptografadas: {}",
+            encrypted_entries.join(", ")
+        ))
+    {
+        return findings.into_deny(name).with_partial_details(summary);
+    }
+
+    let names: Vec<String> = listing
+        .entries
+        .iter()
+        .filter(|entry| !entry.is_dir)
+        .map(|entry| entry.name.clone())
+        .collect();
+    let (missing, unexpected) = manifest_violations(&names, &archive_policy);
+    if !missing.is_empty() {
+        findings.push(format!(
+            "entradas obrigatórias ausentes: {}",
+            missing.join(", ")
+        ));
+    }
+    if !unexpected.is_empty() {
+        findings.push(format!(
+            "entradas não permitidas: {}",
+            unexpected.join(", ")
+        ));
+    }
+
+    if !findings.is_empty() {
+        return findings.into_deny(name).with_details(summary);
+    }
+    let mut outcome = ValidatorOutcome::pass(name);
+    outcome.details = summary;
+    outcome
+}
+
+/// Caminho absoluto, com unidade (`C:`), NUL ou componente `..`.
+fn is_unsafe_name(name: &str) -> bool {
+    let name = name.replace('\\', "/");
+    name.starts_with('/')
+        || name.as_bytes().get(1) == Some(&b':')
+        || name.contains('\0')
+        || name.split('/').any(|component| component == "..")
+}
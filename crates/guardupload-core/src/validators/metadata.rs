@@ -0,0 +1,290 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Inventário de metadados de imagens (EXIF, XMP e IPTC) para políticas de
+//! privacidade: câmera, software e presença de coordenadas GPS. Também remove
+//! esses blocos (e perfis ICC) de JPEG, PNG e WebP sem recodificar a imagem.
+
+use super::exif::{jpeg_segment, ExifSummary};
+use crate::report::escape_control_chars;
+use serde::Serialize;
+
+const XMP_START: &[u8] = b"<x:xmpmeta";
+const XMP_END: &[u8] = b"</x:xmpmeta>";
+/// Limite do pacote XMP examinado.
+const MAX_XMP_BYTES: usize = 1024 * 1024;
+/// Tamanho máximo guardado de campos texto do XMP.
+const MAX_XMP_VALUE_BYTES: usize = 256;
+
+/// Metadados encontrados na imagem (`details.metadata`).
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct ImageMetadata {
+    /// Blocos presentes: `exif`, `xmp`, `iptc`.
+    pub sources: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_make: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub software: Option<String>,
+    /// Coordenadas GPS no EXIF ou no XMP.
+    pub gps: bool,
+}
+
+/// Reúne EXIF (já interpretado), XMP e IPTC; campos do EXIF têm precedência.
+pub(crate) fn inspect(data: &[u8], exif: Option<&ExifSummary<'_>>) -> ImageMetadata {
+    let mut metadata = ImageMetadata::default();
+    if let Some(exif) = exif {
+        metadata.sources.push("exif");
+        metadata.camera_make = exif.make.clone();
+        metadata.camera_model = exif.model.clone();
+        metadata.software = exif.software.clone();
+        metadata.gps = exif.gps;
+    }
+    if let Some(packet) = find_xmp(data) {
+        metadata.sources.push("xmp");
+        let field = |name: &str| xmp_value(packet, name);
+        metadata.camera_make = metadata.camera_make.or_else(|| field("tiff:Make"));
+        metadata.camera_model = metadata.camera_model.or_else(|| field("tiff:Model"));
+        metadata.software = metadata
+            .software
+            .or_else(|| field("xmp:CreatorTool"))
+            .or_else(|| field("tiff:Software"));
+        metadata.gps |= find(packet, b"exif:GPSLatitude").is_some()
+            || find(packet, b"exif:GPSLongitude").is_some();
+    }
+    if has_iptc(data) {
+        metadata.sources.push("iptc");
+    }
+    metadata
+}
+
+/// Pacote `<x:xmpmeta>...</x:xmpmeta>` (JPEG APP1, PNG iTXt ou chunk WebP `XMP `).
+fn find_xmp(data: &[u8]) -> Option<&[u8]> {
+    let start = find(data, XMP_START)?;
+    let window = &data[start..data.len().min(start.saturating_add(MAX_XMP_BYTES))];
+    let end = find(window, XMP_END).map_or(window.len(), |end| end + XMP_END.len());
+    Some(&window[..end])
+}
+
+/// Valor de uma propriedade XMP, como atributo (`tiff:Model="X"`) ou elemento
+/// (`<tiff:Model>X</tiff:Model>`).
+fn xmp_value(packet: &[u8], name: &str) -> Option<String> {
+    let attribute = format!("{name}=\"");
+    let element = format!("<{name}>");
+    let (start, terminator) = match find(packet, attribute.as_bytes()) {
+        Some(pos) => (pos + attribute.len(), b'"'),
+        None => (find(packet, element.as_bytes())? + element.len(), b'<'),
+    };
+    let rest = &packet[start..];
+    let value = &rest[..rest
+        .iter()
+        .position(|&byte| byte == terminator)
+        .unwrap_or(rest.len())
+        .min(MAX_XMP_VALUE_BYTES)];
+    let text = String::from_utf8_lossy(value);
+    let text = text.trim();
+    (!text.is_empty()).then(|| escape_control_chars(text).into_owned())
+}
+
+/// IPTC-IIM: recurso `8BIM` 0x0404 no APP13 do JPEG ou perfil bruto em texto PNG.
+fn has_iptc(data: &[u8]) -> bool {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        jpeg_segment(data, 0xED, b"Photoshop 3.0\0")
+            .is_some_and(|resources| find(resources, b"8BIM\x04\x04").is_some())
+    } else if data.starts_with(PNG_SIGNATURE) {
+        find(data, b"Raw profile type iptc").is_some()
+    } else {
+        false
+    }
+}
+
+/// Imagem reescrita sem metadados.
+#[derive(Debug, Clone)]
+pub struct StrippedImage {
+    pub data: Vec<u8>,
+    /// Blocos removidos (`exif`, `xmp`, `iptc`, `icc`, `comment`, `text`), sem repetição.
+    pub removed: Vec<&'static str>,
+}
+
+/// Remove EXIF, XMP, IPTC, ICC e comentários de JPEG, PNG ou WebP, copiando os
+/// demais blocos byte a byte. `None` para outros formatos ou estrutura inválida.
+pub fn strip_metadata(data: &[u8]) -> Option<StrippedImage> {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        strip_jpeg(data)
+    } else if data.starts_with(PNG_SIGNATURE) {
+        strip_png(data)
+    } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        strip_webp(data)
+    } else {
+        None
+    }
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// Bits do cabeçalho VP8X que anunciam ICC, EXIF e XMP.
+const VP8X_METADATA_FLAGS: u8 = 0x20 | 0x08 | 0x04;
+
+fn strip_jpeg(data: &[u8]) -> Option<StrippedImage> {
+    let mut stripped = StrippedImage {
+        data: data[..2].to_vec(),
+        removed: Vec::new(),
+    };
+    let mut pos = 2;
+    loop {
+        if *data.get(pos)? != 0xFF {
+            return None;
+        }
+        let marker = *data.get(pos + 1)?;
+        match marker {
+            // Preenchimento entre marcadores.
+            0xFF => {
+                pos += 1;
+                continue;
+            }
+            // SOS/EOI: o restante é o fluxo comprimido e vai intacto.
+            0xDA | 0xD9 => {
+                stripped.data.extend_from_slice(&data[pos..]);
+                return Some(stripped);
+            }
+            0x01 | 0xD0..=0xD7 => {
+                stripped.data.extend_from_slice(&data[pos..pos + 2]);
+                pos += 2;
+                continue;
+            }
+            _ => {}
+        }
+        let length = usize::from(u16::from_be_bytes([
+            *data.get(pos + 2)?,
+            *data.get(pos + 3)?,
+        ]));
+        let segment = data.get(pos..pos + 2 + length)?;
+        let body = &segment[4.min(segment.len())..];
+        let kind = match marker {
+            0xE1 if body.starts_with(b"Exif\0") => Some("exif"),
+            0xE1 if body.starts_with(b"http://ns.adobe.com/") => Some("xmp"),
+            0xE2 if body.starts_with(b"ICC_PROFILE\0") => Some("icc"),
+            0xED => Some("iptc"),
+            0xFE => Some("comment"),
+            _ => None,
+        };
+        match kind {
+            Some(kind) => stripped.note(kind),
+            None => stripped.data.extend_from_slice(segment),
+        }
+        pos += 2 + length;
+    }
+}
+
+fn strip_png(data: &[u8]) -> Option<StrippedImage> {
+    let mut stripped = StrippedImage {
+        data: PNG_SIGNATURE.to_vec(),
+        removed: Vec::new(),
+    };
+    let mut pos = PNG_SIGNATURE.len();
+    while pos < data.len() {
+        let length = u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize;
+        let chunk = data.get(pos..pos.checked_add(12 + length)?)?;
+        let body = &chunk[8..8 + length];
+        let kind = match &chunk[4..8] {
+            b"eXIf" => Some("exif"),
+            b"iCCP" => Some("icc"),
+            b"iTXt" if body.starts_with(b"XML:com.adobe.xmp\0") => Some("xmp"),
+            b"iTXt" | b"tEXt" | b"zTXt" if find(body, b"Raw profile type iptc").is_some() => {
+                Some("iptc")
+            }
+            b"iTXt" | b"tEXt" | b"zTXt" => Some("text"),
+            _ => None,
+        };
+        match kind {
+            Some(kind) => stripped.note(kind),
+            None => stripped.data.extend_from_slice(chunk),
+        }
+        pos += chunk.len();
+        if &chunk[4..8] == b"IEND" {
+            break;
+        }
+    }
+    Some(stripped)
+}
+
+fn strip_webp(data: &[u8]) -> Option<StrippedImage> {
+    let mut stripped = StrippedImage {
+        data: data[..12].to_vec(),
+        removed: Vec::new(),
+    };
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let length = u32::from_le_bytes(data[pos + 4..pos + 8].try_into().ok()?) as usize;
+        let padded = length + (length & 1);
+        let chunk = data.get(pos..pos.checked_add(8 + padded)?)?;
+        let kind = match &chunk[..4] {
+            b"EXIF" => Some("exif"),
+            b"XMP " => Some("xmp"),
+            b"ICCP" => Some("icc"),
+            _ => None,
+        };
+        match kind {
+            Some(kind) => stripped.note(kind),
+            None => {
+                let start = stripped.data.len();
+                stripped.data.extend_from_slice(chunk);
+                if &chunk[..4] == b"VP8X" && length > 0 {
+                    stripped.data[start + 8] &= !VP8X_METADATA_FLAGS;
+                }
+            }
+        }
+        pos += chunk.len();
+    }
+    let riff_size = u32::try_from(stripped.data.len() - 8).ok()?;
+    stripped.data[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Some(stripped)
+}
+
+impl StrippedImage {
+    fn note(&mut self, kind: &'static str) {
+        if !self.removed.contains(&kind) {
+            self.removed.push(kind);
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xmp_and_iptc_fill_missing_exif_fields() {
+        let xmp = b"<x:xmpmeta><rdf:Description tiff:Model=\"EOS R5\" \
+            exif:GPSLatitude=\"23,33.0S\"><xmp:CreatorTool>Editor 2.1</xmp:CreatorTool>\
+            </rdf:Description></x:xmpmeta>";
+        let mut iptc = b"Photoshop 3.0\0".to_vec();
+        iptc.extend_from_slice(b"8BIM\x04\x04\0\0\0\0\0\0");
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xED];
+        jpeg.extend_from_slice(&((iptc.len() + 2) as u16).to_be_bytes());
+        jpeg.extend_from_slice(&iptc);
+        jpeg.extend_from_slice(xmp);
+
+        let exif = ExifSummary {
+            make: Some("Canon".into()),
+            ..ExifSummary::default()
+        };
+        let metadata = inspect(&jpeg, Some(&exif));
+        assert_eq!(metadata.sources, vec!["exif", "xmp", "iptc"]);
+        assert_eq!(metadata.camera_make.as_deref(), Some("Canon"));
+        assert_eq!(metadata.camera_model.as_deref(), Some("EOS R5"));
+        assert_eq!(metadata.software.as_deref(), Some("Editor 2.1"));
+        assert!(metadata.gps);
+
+        let clean = inspect(&[0xFF, 0xD8, 0xFF, 0xD9], None);
+        assert!(clean.sources.is_empty() && !clean.gps);
+    }
+}
@@ -0,0 +1,370 @@
+// GuardUpload
+// Criado em: 2025-11-01
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Conjunto de validadores específicos por tipo de conteúdo.
+
+pub(crate) mod archive;
+mod binary;
+mod disk_image;
+mod exif;
+mod filename;
+mod generic;
+pub(crate) mod image;
+mod listing;
+mod metadata;
+mod office;
+mod ole;
+pub(crate) mod pdf;
+mod pkcs7;
+mod rar;
+mod registry;
+mod sevenzip;
+mod svg;
+mod version;
+
+use crate::limits::Deadline;
+use crate::policy::ResolvedPolicy;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+pub use archive::{glob_matches, validate_archive};
+pub use binary::{detect_binary, validate_binary, BinaryFormat, BinaryInfo};
+pub use disk_image::{detect_disk_image, validate_disk_image, DiskImageKind};
+pub use filename::validate_filename;
+pub use generic::validate_generic;
+pub use image::validate_image;
+pub use metadata::{strip_metadata, StrippedImage};
+pub use office::{is_ooxml, validate_office};
+pub use ole::{is_ole, validate_ole};
+pub use pdf::validate_pdf;
+pub use pkcs7::{detect_signed, signed_inner_content, validate_pkcs7, SignedEnvelope};
+pub use rar::{is_rar, validate_rar};
+pub use registry::{
+    active_validators, ActiveValidator, RuleCapability, ValidationContext, Validator,
+    ValidatorCapability, ValidatorRegistry, ANY_VALIDATOR,
+};
+pub use sevenzip::{is_sevenzip, validate_sevenzip};
+pub use svg::validate_svg;
+pub use version::{ValidatorVersion, VersionRequirement};
+
+/// Resultado padrão devolvido pelos validadores.
+#[derive(Debug, Clone)]
+pub struct ValidatorOutcome {
+    pub name: &'static str,
+    pub status: ValidatorStatus,
+    pub details: Value,
+    /// Versão do validador que produziu o resultado; ausente em `skipped`.
+    pub version: Option<ValidatorVersion>,
+}
+
+impl ValidatorOutcome {
+    pub fn new(name: &'static str, status: ValidatorStatus, details: Value) -> Self {
+        Self {
+            name,
+            status,
+            details,
+            version: None,
+        }
+    }
+
+    pub fn pass(name: &'static str) -> Self {
+        Self::new(name, ValidatorStatus::Pass, Value::Null)
+    }
+
+    pub fn warn(name: &'static str, message: impl Into<String>) -> Self {
+        Self::new(
+            name,
+            ValidatorStatus::Warn,
+            json!({ "message": message.into() }),
+        )
+    }
+
+    pub fn deny(name: &'static str, message: impl Into<String>) -> Self {
+        Self::new(
+            name,
+            ValidatorStatus::Deny,
+            json!({ "message": message.into() }),
+        )
+    }
+
+    /// Validador aplicável desligado por `validators.enabled`/`validators.disabled`.
+    pub fn skipped(name: &'static str) -> Self {
+        Self::new(
+            name,
+            ValidatorStatus::Skipped,
+            json!({ "reason": "disabled_by_policy" }),
+        )
+    }
+
+    pub fn error(name: &'static str, message: impl Into<String>) -> Self {
+        Self::new(
+            name,
+            ValidatorStatus::Error,
+            json!({ "message": message.into() }),
+        )
+    }
+
+    /// Resultado parcial de um validador interrompido pelo prazo (`error:timeout`).
+    pub fn timeout(name: &'static str) -> Self {
+        Self::new(
+            name,
+            ValidatorStatus::Error,
+            json!({
+                "message": "tempo limite excedido; resultado parcial",
+                "error": "timeout",
+            }),
+        )
+    }
+
+    /// Indica se o resultado foi interrompido pelo prazo.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self.status, ValidatorStatus::Error) && self.details["error"] == "timeout"
+    }
+
+    /// Códigos dos achados em `details.codes` (vazio quando o validador não os informa).
+    pub fn codes(&self) -> Vec<&str> {
+        self.details["codes"]
+            .as_array()
+            .map(|codes| codes.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Indica se a falha foi coberta pelo validador `generic` (`routing.on_validator_error`).
+    pub fn fell_back(&self) -> bool {
+        matches!(self.status, ValidatorStatus::Error) && self.details.get("fallback").is_some()
+    }
+
+    /// Mescla campos adicionais em `details`, preservando os já existentes.
+    pub fn with_details(mut self, extra: Value) -> Self {
+        match (self.details.as_object_mut(), extra) {
+            (Some(existing), Value::Object(extra)) => existing.extend(extra),
+            (_, extra) => self.details = extra,
+        }
+        self
+    }
+
+    /// Anexa o que foi coletado antes de uma interrupção (prazo ou limite),
+    /// marcado com `partial: true`.
+    pub fn with_partial_details(self, collected: Value) -> Self {
+        self.with_details(collected)
+            .with_details(json!({ "partial": true }))
+    }
+}
+
+/// Estado da validação conforme schema do relatório.
+#[derive(Debug, Clone, Copy)]
+pub enum ValidatorStatus {
+    Pass,
+    Warn,
+    Deny,
+    Error,
+    /// Não executado por estar desligado na política (registrado para auditoria).
+    Skipped,
+}
+
+impl ValidatorStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidatorStatus::Pass => "pass",
+            ValidatorStatus::Warn => "warn",
+            ValidatorStatus::Deny => "deny",
+            ValidatorStatus::Error => "error",
+            ValidatorStatus::Skipped => "skipped",
+        }
+    }
+}
+
+/// Rota de despacho: validador e os critérios que o selecionam (MIME real,
+/// `magic:<formato>` quando o reconhecimento é pelo conteúdo ou `name:<glob>`
+/// pelo nome enviado).
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ValidatorRoute {
+    pub validator: &'static str,
+    pub matches: &'static [&'static str],
+}
+
+/// Rotas na ordem em que `evaluate_validators` as avalia.
+pub const VALIDATOR_ROUTES: &[ValidatorRoute] = &[
+    ValidatorRoute {
+        validator: "pkcs7",
+        matches: &["magic:pkcs7-der", "magic:pkcs7-pem", "multipart/signed"],
+    },
+    ValidatorRoute {
+        validator: "binary",
+        matches: &["magic:pe", "magic:elf", "magic:macho"],
+    },
+    ValidatorRoute {
+        validator: "svg",
+        matches: &["image/svg+xml", "magic:svg", "name:*.svg"],
+    },
+    ValidatorRoute {
+        validator: "image",
+        matches: &["image/*"],
+    },
+    ValidatorRoute {
+        validator: "pdf",
+        matches: &["application/pdf"],
+    },
+    ValidatorRoute {
+        validator: "archive",
+        matches: &[
+            "application/zip",
+            "application/x-zip-compressed",
+            "application/x-zip",
+            "multipart/x-zip",
+            "application/vnd.openxmlformats-officedocument.*",
+        ],
+    },
+    ValidatorRoute {
+        validator: "office",
+        matches: &["application/vnd.openxmlformats-officedocument.*"],
+    },
+    ValidatorRoute {
+        validator: "sevenzip",
+        matches: &["magic:7z"],
+    },
+    ValidatorRoute {
+        validator: "rar",
+        matches: &["magic:rar"],
+    },
+    ValidatorRoute {
+        validator: "disk_image",
+        matches: &[
+            "magic:iso9660",
+            "magic:udf",
+            "magic:vhd",
+            "magic:vhdx",
+            "magic:vmdk",
+            "magic:dmg",
+            "magic:fat_image",
+            "magic:msi",
+        ],
+    },
+    ValidatorRoute {
+        validator: "ole",
+        matches: &["magic:ole"],
+    },
+    ValidatorRoute {
+        validator: "filename",
+        matches: &["*/*"],
+    },
+    ValidatorRoute {
+        validator: "generic",
+        matches: &["*/*"],
+    },
+];
+
+/// Executa os validadores embutidos com base no conteúdo e no MIME real do arquivo.
+pub fn evaluate_validators(
+    mime: &str,
+    data: &[u8],
+    policy: Option<&ResolvedPolicy>,
+    deadline: &Deadline,
+) -> Vec<ValidatorOutcome> {
+    ValidatorRegistry::shared_builtin().evaluate(&ValidationContext {
+        mime,
+        data,
+        policy,
+        deadline,
+        file_name: None,
+    })
+}
+
+/// Nomes de validador são `&'static str`; nomes vindos de fora (plugins,
+/// relatórios relidos) são alocados uma única vez.
+pub fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+    let mut names = NAMES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let names = names.get_or_insert_with(HashSet::new);
+    if let Some(existing) = names.get(name) {
+        return existing;
+    }
+    let leaked: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    names.insert(leaked);
+    leaked
+}
+
+/// Violações encontradas por um validador.
+///
+/// Por padrão o validador para na primeira; com `defaults.report_all_findings`
+/// todas são coletadas e listadas em `details.findings`.
+pub(crate) struct Findings {
+    exhaustive: bool,
+    pub(crate) messages: Vec<String>,
+    /// Códigos estáveis dos achados (`details.codes`), base de `severity_overrides`.
+    codes: Vec<&'static str>,
+}
+
+impl Findings {
+    pub(crate) fn new(policy: Option<&ResolvedPolicy>) -> Self {
+        Self {
+            exhaustive: policy
+                .and_then(|policy| policy.defaults.report_all_findings)
+                .unwrap_or(false),
+            messages: Vec::new(),
+            codes: Vec::new(),
+        }
+    }
+
+    /// Registra a violação; devolve `true` quando o validador deve parar.
+    pub(crate) fn push(&mut self, message: impl Into<String>) -> bool {
+        self.messages.push(message.into());
+        !self.exhaustive
+    }
+
+    /// Igual a [`Findings::push`], com o código do achado (ex.: `javascript`).
+    pub(crate) fn push_code(&mut self, code: &'static str, message: impl Into<String>) -> bool {
+        self.push_codes(&[code], message)
+    }
+
+    /// Um achado que cobre vários códigos (ex.: ações PDF proibidas).
+    pub(crate) fn push_codes(
+        &mut self,
+        codes: &[&'static str],
+        message: impl Into<String>,
+    ) -> bool {
+        for code in codes {
+            if !self.codes.contains(code) {
+                self.codes.push(code);
+            }
+        }
+        self.push(message)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// DENY com as mensagens coletadas.
+    pub(crate) fn into_deny(self, name: &'static str) -> ValidatorOutcome {
+        let mut outcome = ValidatorOutcome::deny(name, self.messages.join("; "));
+        if !self.codes.is_empty() {
+            outcome = outcome.with_details(json!({ "codes": self.codes }));
+        }
+        if self.exhaustive {
+            outcome.with_details(json!({ "findings": self.messages }))
+        } else {
+            outcome
+        }
+    }
+}
+
+/// Busca `needle` em `haystack` ignorando maiúsculas/minúsculas ASCII.
+pub(crate) fn contains_case_insensitive(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| eq_ascii_case(window, needle))
+}
+
+pub(crate) fn eq_ascii_case(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(x, y)| x.eq_ignore_ascii_case(y))
+}
@@ -0,0 +1,311 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Validador de documentos Office OOXML (docx/xlsx/pptx).
+
+use super::{contains_case_insensitive, ValidatorOutcome};
+use crate::decompress::{default_backend, read_zip_entry, DecompressLimits};
+use crate::policy::ResolvedPolicy;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde_json::{json, Map, Value};
+use std::io::Cursor;
+use zip::ZipArchive;
+
+/// Limite de leitura por parte XML inspecionada (rels, docProps, document).
+const DEFAULT_MAX_PART_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Verifica se o conteúdo é um container ZIP com `[Content_Types].xml`.
+pub fn is_ooxml(data: &[u8]) -> bool {
+    ZipArchive::new(Cursor::new(data))
+        .map(|archive| {
+            archive
+                .file_names()
+                .any(|name| name == "[Content_Types].xml")
+        })
+        .unwrap_or(false)
+}
+
+pub fn validate_office(data: &[u8], policy: Option<&ResolvedPolicy>) -> ValidatorOutcome {
+    let name = "office";
+    let office_policy = policy.map(|p| p.office.clone()).unwrap_or_default();
+    let max_part_bytes = office_policy
+        .max_part_bytes
+        .unwrap_or(DEFAULT_MAX_PART_BYTES);
+
+    let mut archive = match ZipArchive::new(Cursor::new(data)) {
+        Ok(archive) => archive,
+        Err(err) => {
+            return ValidatorOutcome::deny(name, format!("container OOXML inválido: {err}"))
+        }
+    };
+    // Ordem do diretório central: `read_part` acessa as partes pelo índice.
+    let names: Vec<String> = (0..archive.len())
+        .map(|index| {
+            archive
+                .by_index_raw(index)
+                .map(|entry| entry.name().to_string())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let macros: Vec<&String> = names
+        .iter()
+        .filter(|entry| entry.to_ascii_lowercase().ends_with("vbaproject.bin"))
+        .collect();
+
+    let mut external_relationships = Vec::new();
+    let mut dde_links = Vec::new();
+    for (index, entry) in names.iter().enumerate() {
+        let lowered = entry.to_ascii_lowercase();
+        if lowered.ends_with(".rels") {
+            if let Some(part) = read_part(&mut archive, index, max_part_bytes) {
+                external_relationships.extend(external_targets(entry, &part));
+            }
+        } else if lowered.ends_with(".xml")
+            && (lowered.starts_with("word/")
+                || lowered.starts_with("xl/externallinks/")
+                || lowered.starts_with("ppt/"))
+        {
+            if let Some(part) = read_part(&mut archive, index, max_part_bytes) {
+                if has_dde(&part) {
+                    dde_links.push(entry.clone());
+                }
+            }
+        }
+    }
+
+    let metadata = read_metadata(&mut archive, &names, max_part_bytes);
+    let details = json!({
+        "document_type": document_type(&names),
+        "macros": macros,
+        "external_relationships": external_relationships,
+        "dde_links": dde_links,
+        "metadata": metadata,
+    });
+
+    let outcome = if !macros.is_empty() && !office_policy.allow_macros.unwrap_or(false) {
+        ValidatorOutcome::deny(name, "documento Office contém macros (vbaProject.bin)")
+    } else if !dde_links.is_empty() && office_policy.forbid_dde.unwrap_or(true) {
+        ValidatorOutcome::deny(name, "documento Office contém links DDE")
+    } else if !external_relationships.is_empty() {
+        let message = "documento Office referencia recursos externos";
+        if office_policy.forbid_external_relationships.unwrap_or(false) {
+            ValidatorOutcome::deny(name, message)
+        } else {
+            ValidatorOutcome::warn(name, message)
+        }
+    } else {
+        ValidatorOutcome::pass(name)
+    };
+
+    outcome.with_details(details)
+}
+
+/// Partes acima de `max_bytes` (`office.max_part_bytes`), corrompidas ou cifradas
+/// são ignoradas.
+fn read_part(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    index: usize,
+    max_bytes: u64,
+) -> Option<Vec<u8>> {
+    read_zip_entry(
+        archive,
+        index,
+        default_backend(),
+        &DecompressLimits::new(max_bytes),
+    )
+    .ok()
+}
+
+fn document_type(names: &[String]) -> &'static str {
+    let has_prefix = |prefix: &str| names.iter().any(|entry| entry.starts_with(prefix));
+    if has_prefix("word/") {
+        "word"
+    } else if has_prefix("xl/") {
+        "excel"
+    } else if has_prefix("ppt/") {
+        "powerpoint"
+    } else {
+        "unknown"
+    }
+}
+
+/// Extrai relacionamentos com `TargetMode="External"` de uma parte `.rels`.
+fn external_targets(part_name: &str, xml: &[u8]) -> Vec<Value> {
+    let mut reader = Reader::from_reader(xml);
+    let mut targets = Vec::new();
+    while let Ok(event) = reader.read_event() {
+        match event {
+            Event::Start(element) | Event::Empty(element)
+                if element.local_name().as_ref() == b"Relationship" =>
+            {
+                let mut target = None;
+                let mut kind = None;
+                let mut external = false;
+                for attribute in element.attributes().flatten() {
+                    let value = attribute
+                        .unescape_value()
+                        .map(|value| value.to_string())
+                        .unwrap_or_default();
+                    match attribute.key.as_ref() {
+                        b"Target" => target = Some(value),
+                        b"Type" => kind = value.rsplit('/').next().map(str::to_string),
+                        b"TargetMode" => external = value.eq_ignore_ascii_case("External"),
+                        _ => {}
+                    }
+                }
+                if external {
+                    targets.push(json!({ "part": part_name, "target": target, "type": kind }));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    targets
+}
+
+fn has_dde(xml: &[u8]) -> bool {
+    contains_case_insensitive(xml, b"DDEAUTO")
+        || contains_case_insensitive(xml, b"<ddeLink")
+        || contains_case_insensitive(xml, b">DDE ")
+        || contains_case_insensitive(xml, b"instr=\" DDE")
+}
+
+/// Lê propriedades básicas de `docProps/core.xml` e `docProps/app.xml`.
+fn read_metadata(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    names: &[String],
+    max_part_bytes: u64,
+) -> Value {
+    const FIELDS: &[&str] = &[
+        "title",
+        "subject",
+        "creator",
+        "keywords",
+        "lastModifiedBy",
+        "created",
+        "modified",
+        "Application",
+        "AppVersion",
+        "Company",
+    ];
+    let mut metadata = Map::new();
+    for part in ["docProps/core.xml", "docProps/app.xml"] {
+        let Some(xml) = names
+            .iter()
+            .position(|name| name == part)
+            .and_then(|index| read_part(archive, index, max_part_bytes))
+        else {
+            continue;
+        };
+        let mut reader = Reader::from_reader(xml.as_slice());
+        let mut current: Option<String> = None;
+        while let Ok(event) = reader.read_event() {
+            match event {
+                Event::Start(element) => {
+                    let local = String::from_utf8_lossy(element.local_name().as_ref()).to_string();
+                    current = FIELDS.contains(&local.as_str()).then_some(local);
+                }
+                Event::Text(text) => {
+                    if let (Some(field), Ok(value)) = (current.take(), text.unescape()) {
+                        let value = value.trim();
+                        if !value.is_empty() {
+                            metadata.insert(field, json!(value));
+                        }
+                    }
+                }
+                Event::End(_) => current = None,
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+    }
+    Value::Object(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OfficePolicySection;
+    use crate::validators::ValidatorStatus;
+    use std::io::Write;
+    use zip::write::FileOptions;
+
+    const PLAIN_DOCUMENT: &str = "<w:document><w:body/></w:document>";
+
+    fn build_docx(extra: &[(&str, &str)]) -> Vec<u8> {
+        build_docx_with(PLAIN_DOCUMENT, extra)
+    }
+
+    fn build_docx_with(document: &str, extra: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let base = [
+            ("[Content_Types].xml", "<Types/>"),
+            ("word/document.xml", document),
+            (
+                "docProps/core.xml",
+                "<cp:coreProperties><dc:title>Relatório</dc:title><dc:creator>Ana</dc:creator></cp:coreProperties>",
+            ),
+        ];
+        for (entry, content) in base.iter().chain(extra.iter()) {
+            writer
+                .start_file(*entry, FileOptions::default())
+                .expect("start file");
+            writer.write_all(content.as_bytes()).expect("write");
+        }
+        writer.finish().expect("finish").into_inner()
+    }
+
+    #[test]
+    fn plain_docx_passes_with_metadata() {
+        let data = build_docx(&[]);
+        assert!(is_ooxml(&data));
+        let outcome = validate_office(&data, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(outcome.details["document_type"], "word");
+        assert_eq!(outcome.details["metadata"]["title"], "Relatório");
+        assert_eq!(outcome.details["metadata"]["creator"], "Ana");
+    }
+
+    #[test]
+    fn macros_are_denied_unless_allowed() {
+        let data = build_docx(&[("word/vbaProject.bin", "binario")]);
+        let outcome = validate_office(&data, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(outcome.details["macros"], json!(["word/vbaProject.bin"]));
+
+        let policy = ResolvedPolicy {
+            office: OfficePolicySection {
+                allow_macros: Some(true),
+                ..OfficePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let outcome = validate_office(&data, Some(&policy));
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+    }
+
+    #[test]
+    fn external_relationships_are_flagged() {
+        let rels = r#"<Relationships><Relationship Id="rId9" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/attachedTemplate" Target="https://evil.example/t.dotm" TargetMode="External"/></Relationships>"#;
+        let data = build_docx(&[("word/_rels/settings.xml.rels", rels)]);
+        let outcome = validate_office(&data, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Warn));
+        let relationship = &outcome.details["external_relationships"][0];
+        assert_eq!(relationship["target"], "https://evil.example/t.dotm");
+        assert_eq!(relationship["type"], "attachedTemplate");
+    }
+
+    #[test]
+    fn dde_fields_are_denied() {
+        let document = r#"<w:document><w:instrText> DDEAUTO c:\\windows\\system32\\cmd.exe "/k calc"</w:instrText></w:document>"#;
+        let data = build_docx_with(document, &[]);
+        let outcome = validate_office(&data, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(outcome.details["dde_links"], json!(["word/document.xml"]));
+    }
+}
@@ -0,0 +1,138 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Validador de documentos Office legados (OLE2 / Compound File: .doc/.xls/.ppt).
+
+use super::ValidatorOutcome;
+use crate::policy::ResolvedPolicy;
+use serde_json::json;
+use std::io::Cursor;
+
+/// Assinatura do cabeçalho Compound File Binary.
+pub const OLE_MAGIC: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// Storages que carregam projetos VBA nos formatos Word/Excel/PowerPoint.
+const MACRO_STORAGES: &[&str] = &["macros", "_vba_project_cur", "vba"];
+
+/// Stream de objetos OLE incorporados (pacotes com executáveis, scripts etc.).
+const OLE10_NATIVE: &str = "\u{1}Ole10Native";
+
+pub fn is_ole(data: &[u8]) -> bool {
+    data.starts_with(OLE_MAGIC)
+}
+
+pub fn validate_ole(data: &[u8], policy: Option<&ResolvedPolicy>) -> ValidatorOutcome {
+    let name = "ole";
+    let office_policy = policy.map(|p| p.office.clone()).unwrap_or_default();
+
+    let compound = match cfb::CompoundFile::open(Cursor::new(data)) {
+        Ok(compound) => compound,
+        Err(err) => return ValidatorOutcome::deny(name, format!("arquivo OLE2 inválido: {err}")),
+    };
+
+    let mut macro_storages = Vec::new();
+    let mut embedded_objects = Vec::new();
+    let mut entries = 0usize;
+    for entry in compound.walk() {
+        entries += 1;
+        let path = entry.path().to_string_lossy().replace('\u{1}', "\\x01");
+        if entry.is_storage()
+            && MACRO_STORAGES.contains(&entry.name().to_ascii_lowercase().as_str())
+        {
+            macro_storages.push(path);
+        } else if entry.is_stream() && entry.name() == OLE10_NATIVE {
+            embedded_objects.push(path);
+        }
+    }
+
+    let details = json!({
+        "entries": entries,
+        "macro_storages": macro_storages,
+        "embedded_objects": embedded_objects,
+    });
+
+    let outcome = if !macro_storages.is_empty() && !office_policy.allow_macros.unwrap_or(false) {
+        ValidatorOutcome::deny(name, "documento OLE2 contém macros VBA")
+    } else if !embedded_objects.is_empty() {
+        let message = "documento OLE2 contém objetos incorporados (Ole10Native)";
+        if office_policy.forbid_embedded_objects.unwrap_or(false) {
+            ValidatorOutcome::deny(name, message)
+        } else {
+            ValidatorOutcome::warn(name, message)
+        }
+    } else {
+        ValidatorOutcome::pass(name)
+    };
+    outcome.with_details(details)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OfficePolicySection;
+    use crate::validators::ValidatorStatus;
+    use std::io::Write;
+
+    fn build_ole(storages: &[&str], streams: &[&str]) -> Vec<u8> {
+        let mut compound = cfb::CompoundFile::create(Cursor::new(Vec::new())).expect("create");
+        compound
+            .create_stream("/WordDocument")
+            .expect("stream")
+            .write_all(b"conteudo")
+            .expect("write");
+        for storage in storages {
+            compound.create_storage_all(storage).expect("storage");
+        }
+        for stream in streams {
+            compound
+                .create_stream(stream)
+                .expect("stream")
+                .write_all(b"payload")
+                .expect("write");
+        }
+        compound.flush().expect("flush");
+        compound.into_inner().into_inner()
+    }
+
+    #[test]
+    fn plain_document_passes() {
+        let data = build_ole(&[], &[]);
+        assert!(is_ole(&data));
+        let outcome = validate_ole(&data, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+    }
+
+    #[test]
+    fn macro_storage_is_denied_unless_allowed() {
+        let data = build_ole(&["/Macros/VBA"], &[]);
+        let outcome = validate_ole(&data, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(
+            outcome.details["macro_storages"].as_array().unwrap().len(),
+            2
+        );
+
+        let policy = ResolvedPolicy {
+            office: OfficePolicySection {
+                allow_macros: Some(true),
+                ..OfficePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let outcome = validate_ole(&data, Some(&policy));
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+    }
+
+    #[test]
+    fn embedded_ole10native_is_flagged() {
+        let data = build_ole(&["/ObjectPool/_1"], &["/ObjectPool/_1/\u{1}Ole10Native"]);
+        let outcome = validate_ole(&data, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Warn));
+        assert_eq!(
+            outcome.details["embedded_objects"],
+            json!(["/ObjectPool/_1/\\x01Ole10Native"])
+        );
+    }
+}
@@ -0,0 +1,600 @@
+// GuardUpload
+// Criado em: 2025-11-01
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Validador básico de PDFs.
+
+use super::{contains_case_insensitive, eq_ascii_case, Findings, ValidatorOutcome};
+use crate::config::PdfPolicySection;
+use crate::limits::Deadline;
+use crate::policy::ResolvedPolicy;
+use serde_json::{json, Value};
+
+/// Tokens de fontes embutidas (Type 1, TrueType e CFF/OpenType).
+const FONT_FILE_TOKENS: &[&[u8]] = &[b"/FontFile", b"/FontFile2", b"/FontFile3"];
+
+/// Ações PDF inspecionadas: (tipo reportado, token procurado).
+const PDF_ACTIONS: &[(&str, &[u8])] = &[
+    ("open_action", b"/OpenAction"),
+    ("launch", b"/Launch"),
+    ("uri", b"/URI"),
+    ("acroform", b"/AcroForm"),
+];
+
+pub fn validate_pdf(
+    data: &[u8],
+    policy: Option<&ResolvedPolicy>,
+    deadline: &Deadline,
+) -> ValidatorOutcome {
+    let name = "pdf";
+    if data.len() < 8 || !data.starts_with(b"%PDF-") {
+        return ValidatorOutcome::deny(name, "arquivo não possui header %PDF- válido")
+            .with_details(json!({ "codes": ["invalid_header"] }));
+    }
+
+    let pdf_policy = policy.map(|p| p.pdf.clone()).unwrap_or_default();
+    let mut findings = Findings::new(policy);
+    let mut details = json!({
+        "size_bytes": data.len(),
+    });
+
+    let page_count = count_occurrences(data, b"/Type /Page");
+    details["page_count"] = json!(page_count);
+
+    if let Some(max_pages) = pdf_policy.max_pages {
+        if page_count as u32 > max_pages
+            && findings.push_code(
+                "max_pages",
+                format!("PDF excede limite de páginas: {page_count} > {max_pages}"),
+            )
+        {
+            return findings.into_deny(name).with_partial_details(details);
+        }
+    }
+
+    let Some(structure) = measure_structure(data, deadline) else {
+        if !findings.is_empty() {
+            details["findings"] = json!(findings.messages);
+        }
+        return ValidatorOutcome::timeout(name).with_partial_details(details);
+    };
+    details["object_count"] = json!(structure.objects);
+    details["embedded_fonts"] = json!(structure.embedded_fonts);
+    details["largest_stream_bytes"] = json!(structure.largest_stream);
+    let limits = [
+        (
+            "objetos",
+            structure.objects,
+            pdf_policy.max_objects,
+            "max_objects",
+        ),
+        (
+            "fontes embutidas",
+            structure.embedded_fonts,
+            pdf_policy.max_embedded_fonts.map(u64::from),
+            "max_embedded_fonts",
+        ),
+        (
+            "bytes no maior stream",
+            structure.largest_stream,
+            pdf_policy.max_stream_bytes,
+            "max_stream_bytes",
+        ),
+    ];
+    for (label, value, limit, knob) in limits {
+        if let Some(limit) = limit {
+            if value > limit
+                && findings.push_code(
+                    knob,
+                    format!("PDF excede {knob}: {value} {label} > {limit}"),
+                )
+            {
+                return findings.into_deny(name).with_partial_details(details);
+            }
+        }
+    }
+
+    if pdf_policy.allow_javascript != Some(true)
+        && (mentions_name(data, b"/JavaScript") || mentions_name(data, b"/JS"))
+        && findings.push_code("javascript", "JavaScript detectado em PDF")
+    {
+        return findings.into_deny(name);
+    }
+
+    if pdf_policy.forbid_embedded_files.unwrap_or(true)
+        && mentions_name(data, b"/EmbeddedFiles")
+        && findings.push_code("embedded_files", "PDF possui EmbeddedFiles não permitidos")
+    {
+        return findings.into_deny(name);
+    }
+
+    let encrypted = !find_name_tokens(data, b"/Encrypt").is_empty();
+    details["encrypted"] = json!(encrypted);
+    if encrypted
+        && pdf_policy.forbid_encrypted.unwrap_or(false)
+        && findings.push_code(
+            "encrypted",
+            "PDF criptografado (/Encrypt) não pode ser inspecionado",
+        )
+    {
+        return findings.into_deny(name);
+    }
+
+    let (actions, timed_out) = find_actions(data, deadline);
+    if !actions.is_empty() {
+        details["actions"] = Value::Array(actions.iter().map(PdfAction::to_json).collect());
+    }
+    if timed_out {
+        if !findings.is_empty() {
+            details["findings"] = json!(findings.messages);
+        }
+        return ValidatorOutcome::timeout(name).with_partial_details(details);
+    }
+
+    let forbidden: Vec<&'static str> = PDF_ACTIONS
+        .iter()
+        .map(|(kind, _)| *kind)
+        .filter(|kind| is_forbidden(kind, &pdf_policy))
+        .filter(|kind| actions.iter().any(|action| action.kind == *kind))
+        .collect();
+    if !forbidden.is_empty()
+        && findings.push_codes(
+            &forbidden,
+            format!("PDF possui ações proibidas: {}", forbidden.join(", ")),
+        )
+    {
+        let mut outcome = findings.into_deny(name);
+        outcome.details["actions"] = details["actions"].clone();
+        return outcome;
+    }
+    if !findings.is_empty() {
+        return findings.into_deny(name).with_details(details);
+    }
+
+    let mut outcome = ValidatorOutcome::pass(name);
+    outcome.details = details;
+    outcome
+}
+
+/// Ação encontrada no PDF com a posição onde foi localizada.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PdfAction {
+    kind: &'static str,
+    offset: usize,
+    object: Option<String>,
+}
+
+impl PdfAction {
+    fn to_json(&self) -> Value {
+        json!({
+            "kind": self.kind,
+            "offset": self.offset,
+            "object": self.object,
+        })
+    }
+}
+
+/// Contagens estruturais usadas pelos limites `max_objects`,
+/// `max_embedded_fonts` e `max_stream_bytes`.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct PdfStructure {
+    objects: u64,
+    embedded_fonts: u64,
+    largest_stream: u64,
+}
+
+/// Mede a estrutura do PDF; `None` se o prazo expirou no meio.
+fn measure_structure(data: &[u8], deadline: &Deadline) -> Option<PdfStructure> {
+    let mut structure = PdfStructure::default();
+    for offset in find_name_tokens(data, b"obj") {
+        if is_object_header(data, offset) {
+            structure.objects += 1;
+        }
+    }
+    if deadline.is_expired() {
+        return None;
+    }
+    // Objetos comprimidos em object streams não têm `N G obj` próprio. Os
+    // delimitadores são localizados uma vez e cada dicionário é lido uma só vez.
+    let opens = find_all(data, b"<<");
+    let closes = find_all(data, b">>");
+    let mut last_dictionary = None;
+    for offset in find_name_tokens(data, b"/ObjStm") {
+        if deadline.is_expired() {
+            return None;
+        }
+        let dictionary = enclosing_dictionary(&opens, &closes, offset, data.len());
+        if last_dictionary.replace(dictionary.start) == Some(dictionary.start) {
+            continue;
+        }
+        structure.objects += dictionary_integer(&data[dictionary], b"/N").unwrap_or(0);
+    }
+    structure.embedded_fonts = FONT_FILE_TOKENS
+        .iter()
+        .map(|token| find_name_tokens(data, token).len() as u64)
+        .sum();
+    if deadline.is_expired() {
+        return None;
+    }
+
+    let mut cursor = 0;
+    while let Some(found) = find_bytes(&data[cursor..], b"stream") {
+        let start = cursor + found;
+        cursor = start + b"stream".len();
+        if start >= 3 && &data[start - 3..start] == b"end" {
+            continue;
+        }
+        let body_start = match data.get(cursor..) {
+            Some([b'\r', b'\n', ..]) => cursor + 2,
+            Some([b'\n' | b'\r', ..]) => cursor + 1,
+            _ => continue,
+        };
+        if deadline.is_expired() {
+            return None;
+        }
+        let body_len =
+            find_bytes(&data[body_start..], b"endstream").unwrap_or(data.len() - body_start);
+        cursor = body_start + body_len;
+        // O fim de linha antes de `endstream` não faz parte dos dados.
+        let body = &data[body_start..cursor];
+        let eol = if body.ends_with(b"\r\n") {
+            2
+        } else {
+            usize::from(body.ends_with(b"\n") || body.ends_with(b"\r"))
+        };
+        structure.largest_stream = structure.largest_stream.max((body_len - eol) as u64);
+    }
+    Some(structure)
+}
+
+/// `obj` precedido de `N G` (número e geração) inicia um objeto indireto.
+fn is_object_header(data: &[u8], offset: usize) -> bool {
+    let mut parts = data[offset.saturating_sub(24)..offset]
+        .rsplit(|byte| byte.is_ascii_whitespace())
+        .filter(|part| !part.is_empty());
+    let is_number =
+        |part: Option<&[u8]>| part.is_some_and(|part| part.iter().all(u8::is_ascii_digit));
+    offset > 0
+        && data[offset - 1].is_ascii_whitespace()
+        && is_number(parts.next())
+        && is_number(parts.next())
+}
+
+/// Trecho do dicionário que contém `offset`: do último `<<` antes dele até o
+/// próximo `<<` ou `>>`, de modo que dicionários aninhados não se sobreponham.
+fn enclosing_dictionary(
+    opens: &[usize],
+    closes: &[usize],
+    offset: usize,
+    len: usize,
+) -> std::ops::Range<usize> {
+    let start = opens[..opens.partition_point(|&open| open < offset)]
+        .last()
+        .copied()
+        .unwrap_or(offset);
+    let next = |positions: &[usize]| {
+        positions
+            .get(positions.partition_point(|&position| position < offset))
+            .copied()
+            .unwrap_or(len)
+    };
+    start..next(opens).min(next(closes))
+}
+
+/// Valor inteiro de `key` em um trecho de dicionário.
+fn dictionary_integer(dictionary: &[u8], key: &[u8]) -> Option<u64> {
+    let position = find_name_tokens(dictionary, key).into_iter().next()?;
+    let digits: String = dictionary[name_end(dictionary, position + 1)..]
+        .iter()
+        .skip_while(|byte| byte.is_ascii_whitespace())
+        .take_while(|byte| byte.is_ascii_digit())
+        .map(|byte| *byte as char)
+        .collect();
+    digits.parse().ok()
+}
+
+/// Posições de todas as ocorrências de `needle`.
+fn find_all(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+    haystack
+        .windows(needle.len())
+        .enumerate()
+        .filter(|(_, window)| *window == needle)
+        .map(|(position, _)| position)
+        .collect()
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Localiza as ações e seus objetos; devolve `true` se o prazo expirou no meio.
+fn find_actions(data: &[u8], deadline: &Deadline) -> (Vec<PdfAction>, bool) {
+    let mut actions = Vec::new();
+    let mut timed_out = false;
+    let headers = find_all(data, b" obj");
+    let ends = find_all(data, b"endobj");
+    'tokens: for (kind, token) in PDF_ACTIONS {
+        for offset in find_name_tokens(data, token) {
+            if deadline.is_expired() {
+                timed_out = true;
+                break 'tokens;
+            }
+            actions.push(PdfAction {
+                kind,
+                offset,
+                object: enclosing_object(data, &headers, &ends, offset),
+            });
+        }
+    }
+    actions.sort_by_key(|action| action.offset);
+    // `/S /URI /URI (...)` é uma única ação: tipo e chave no mesmo dicionário.
+    let mut previous: Option<(&str, usize)> = None;
+    actions.retain(|action| {
+        let repeated = previous.is_some_and(|(kind, offset)| {
+            kind == action.kind && find_bytes(&data[offset..action.offset], b">>").is_none()
+        });
+        previous = Some((action.kind, action.offset));
+        !repeated
+    });
+    (actions, timed_out)
+}
+
+fn is_forbidden(kind: &str, policy: &PdfPolicySection) -> bool {
+    match kind {
+        "open_action" => policy.forbid_open_action.unwrap_or(false),
+        "launch" => policy.forbid_launch.unwrap_or(true),
+        "uri" => policy.forbid_external_uris.unwrap_or(false),
+        "acroform" => policy.forbid_acroform.unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Localiza nomes PDF completos (o token não pode continuar com outro caractere
+/// regular). Nomes com `/` são comparados já sem os escapes `#xx`, de modo que
+/// `/L#61unch` também é encontrado como `/Launch`.
+fn find_name_tokens(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+    if let Some(name) = needle.strip_prefix(b"/") {
+        return find_all(haystack, b"/")
+            .into_iter()
+            .filter(|&idx| {
+                let raw = &haystack[idx + 1..name_end(haystack, idx + 1)];
+                if raw.contains(&b'#') {
+                    eq_ascii_case(&decode_name(raw), name)
+                } else {
+                    eq_ascii_case(raw, name)
+                }
+            })
+            .collect();
+    }
+    if haystack.len() < needle.len() {
+        return Vec::new();
+    }
+    (0..=haystack.len() - needle.len())
+        .filter(|&idx| eq_ascii_case(&haystack[idx..idx + needle.len()], needle))
+        .filter(|&idx| {
+            let next = haystack.get(idx + needle.len());
+            !matches!(next, Some(byte) if is_regular_char(*byte))
+        })
+        .collect()
+}
+
+/// Nome citado literalmente (inclusive como prefixo) ou na forma escapada.
+fn mentions_name(data: &[u8], name: &[u8]) -> bool {
+    contains_case_insensitive(data, name) || !find_name_tokens(data, name).is_empty()
+}
+
+/// Identifica o objeto indireto (`N G obj`) que contém o offset informado, a
+/// partir das posições de ` obj` e `endobj` localizadas uma única vez.
+fn enclosing_object(
+    data: &[u8],
+    headers: &[usize],
+    ends: &[usize],
+    offset: usize,
+) -> Option<String> {
+    let last_before = |positions: &[usize], len: usize| {
+        positions[..positions.partition_point(|&position| position + len <= offset)]
+            .last()
+            .copied()
+    };
+    let obj_pos = last_before(headers, 4)?;
+    if last_before(ends, 6).is_some_and(|end_pos| end_pos > obj_pos) {
+        return None;
+    }
+    let head = std::str::from_utf8(&data[obj_pos.saturating_sub(24)..obj_pos]).ok()?;
+    let mut parts = head.split_ascii_whitespace().rev();
+    let generation: u32 = parts.next()?.parse().ok()?;
+    let number: u32 = parts.next()?.parse().ok()?;
+    Some(format!("{number} {generation} obj"))
+}
+
+fn is_regular_char(byte: u8) -> bool {
+    !byte.is_ascii_whitespace() && !b"()<>[]{}/%".contains(&byte)
+}
+
+/// Fim (exclusivo) do nome PDF cujo primeiro caractere após a `/` está em `start`.
+pub(crate) fn name_end(data: &[u8], start: usize) -> usize {
+    start
+        + data[start.min(data.len())..]
+            .iter()
+            .take_while(|byte| is_regular_char(**byte))
+            .count()
+}
+
+/// Decodifica os escapes `#xx` de um nome PDF (`Open#41ction` vira `OpenAction`);
+/// `#` sem dois dígitos hexadecimais é mantido literalmente.
+pub(crate) fn decode_name(raw: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(raw.len());
+    let mut pos = 0;
+    while pos < raw.len() {
+        let escaped = (raw[pos] == b'#')
+            .then(|| raw.get(pos + 1..pos + 3))
+            .flatten()
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                pos += 3;
+            }
+            None => {
+                decoded.push(raw[pos]);
+                pos += 1;
+            }
+        }
+    }
+    decoded
+}
+
+fn count_occurrences(haystack: &[u8], needle: &[u8]) -> usize {
+    haystack
+        .windows(needle.len())
+        .filter(|window| eq_ascii_case(window, needle))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validators::ValidatorStatus;
+
+    const PDF_WITH_ACTIONS: &[u8] = b"%PDF-1.7\n1 0 obj\n<< /Type /Catalog /OpenAction 2 0 R /AcroForm 3 0 R >>\nendobj\n2 0 obj\n<< /S /URI /URI (https://example.com) >>\nendobj\n";
+
+    fn policy_with(pdf: PdfPolicySection) -> ResolvedPolicy {
+        ResolvedPolicy {
+            pdf,
+            ..ResolvedPolicy::default()
+        }
+    }
+
+    #[test]
+    fn actions_are_reported_with_location() {
+        let outcome = validate_pdf(PDF_WITH_ACTIONS, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        let actions = outcome.details["actions"].as_array().expect("actions");
+        let kinds: Vec<&str> = actions
+            .iter()
+            .map(|action| action["kind"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, vec!["open_action", "acroform", "uri"]);
+        assert_eq!(actions[0]["object"], "1 0 obj");
+        assert_eq!(actions[2]["object"], "2 0 obj");
+    }
+
+    #[test]
+    fn escaped_names_are_decoded_before_matching() {
+        let data = b"%PDF-1.7\n1 0 obj\n<< /Type /Catalog /Open#41ction 2 0 R >>\nendobj\n\
+            2 0 obj\n<< /S /L#61unch /F (cmd.exe) >>\nendobj\n\
+            3 0 obj\n<< /S /URI /URI (a) >> << /S /URI /URI (b) >>\nendobj\n";
+        let policy = policy_with(PdfPolicySection {
+            forbid_open_action: Some(true),
+            ..PdfPolicySection::default()
+        });
+        let outcome = validate_pdf(data, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert_eq!(outcome.codes(), ["open_action", "launch"]);
+        let actions = outcome.details["actions"].as_array().expect("actions");
+        assert_eq!(actions.len(), 4, "{actions:?}");
+        assert_eq!(actions[1]["object"], "2 0 obj");
+        assert_eq!(actions[3]["object"], "3 0 obj");
+
+        let outcome = validate_pdf(
+            b"%PDF-1.7\n1 0 obj\n<< /S /J#61vaScript /J#53 (app.alert(1)) >>\nendobj\n",
+            None,
+            &Deadline::none(),
+        );
+        assert_eq!(outcome.codes(), ["javascript"]);
+    }
+
+    #[test]
+    fn forbidden_action_denies_and_keeps_details() {
+        let policy = policy_with(PdfPolicySection {
+            forbid_external_uris: Some(true),
+            ..PdfPolicySection::default()
+        });
+        let outcome = validate_pdf(PDF_WITH_ACTIONS, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert!(outcome.details["message"].as_str().unwrap().contains("uri"));
+        assert!(outcome.details["actions"].is_array());
+        assert_eq!(outcome.codes(), ["uri"]);
+    }
+
+    #[test]
+    fn encrypted_pdf_is_flagged_and_denied_when_forbidden() {
+        let data = b"%PDF-1.6\ntrailer\n<< /Root 1 0 R /Encrypt 5 0 R >>\n";
+        let outcome = validate_pdf(data, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(outcome.details["encrypted"], true);
+
+        let policy = policy_with(PdfPolicySection {
+            forbid_encrypted: Some(true),
+            ..PdfPolicySection::default()
+        });
+        let outcome = validate_pdf(data, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+    }
+
+    #[test]
+    fn launch_is_denied_by_default() {
+        let data = b"%PDF-1.4\n4 0 obj\n<< /S /Launch /F (cmd.exe) >>\nendobj\n";
+        let outcome = validate_pdf(data, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+    }
+
+    #[test]
+    fn structural_limits_count_objects_fonts_and_streams() {
+        let data = b"%PDF-1.7\n1 0 obj\n<< /Type /Catalog >>\nendobj\n\
+            2 0 obj\n<< /Type /FontDescriptor /FontFile2 3 0 R >>\nendobj\n\
+            3 0 obj\n<< /Length 10 >>\nstream\n0123456789\nendstream\nendobj\n\
+            4 0 obj\n<< /Type /ObjStm /N 500 /First 10 >>\nstream\r\nabc\nendstream\nendobj\n";
+        let outcome = validate_pdf(data, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(outcome.details["object_count"], 504);
+        assert_eq!(outcome.details["embedded_fonts"], 1);
+        assert_eq!(outcome.details["largest_stream_bytes"], 10);
+
+        let mut policy = policy_with(PdfPolicySection {
+            max_objects: Some(100),
+            max_embedded_fonts: Some(0),
+            max_stream_bytes: Some(8),
+            ..PdfPolicySection::default()
+        });
+        policy.defaults.report_all_findings = Some(true);
+        let outcome = validate_pdf(data, Some(&policy), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        let findings = outcome.details["findings"].as_array().expect("findings");
+        assert_eq!(findings.len(), 3, "{findings:?}");
+        assert_eq!(
+            outcome.codes(),
+            ["max_objects", "max_embedded_fonts", "max_stream_bytes"]
+        );
+        assert!(findings[0]
+            .as_str()
+            .unwrap()
+            .contains("max_objects: 504 objetos > 100"));
+    }
+
+    #[test]
+    fn object_stream_counts_read_each_dictionary_once() {
+        let mut data = b"%PDF-1.7\n1 0 obj\n<< /Type /ObjStm /ObjStm /N 3 >>\nendobj\n".to_vec();
+        // Dicionários aninhados sem `>>`: cada `/ObjStm` só enxerga o seu trecho.
+        for _ in 0..20_000 {
+            data.extend_from_slice(b"<< /Type /ObjStm /N 2 ");
+        }
+        let outcome = validate_pdf(&data, None, &Deadline::none());
+        assert_eq!(outcome.details["object_count"], 1 + 3 + 40_000);
+    }
+
+    #[test]
+    fn expired_deadline_interrupts_action_walk() {
+        let deadline = Deadline::after(std::time::Duration::ZERO);
+        let outcome = validate_pdf(PDF_WITH_ACTIONS, None, &deadline);
+        assert!(outcome.is_timeout());
+        assert_eq!(outcome.details["partial"], true);
+        assert_eq!(outcome.details["page_count"], 0);
+        assert!(outcome.details.get("actions").is_none());
+    }
+}
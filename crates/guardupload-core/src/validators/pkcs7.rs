@@ -0,0 +1,831 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Detecção de conteúdo assinado/envelopado (PKCS#7/CMS em DER ou PEM e S/MIME).
+//!
+//! Apenas a estrutura é inspecionada: a assinatura não é verificada
+//! criptograficamente. O conteúdo interno é devolvido para que o pipeline o
+//! analise com a mesma política.
+
+use super::{contains_case_insensitive, ValidatorOutcome};
+use crate::policy::ResolvedPolicy;
+use base64::Engine as _;
+use serde_json::{json, Value};
+
+const OID_DATA: &str = "1.2.840.113549.1.7.1";
+const OID_SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const OID_ENVELOPED_DATA: &str = "1.2.840.113549.1.7.3";
+const OID_SIGNING_TIME: &str = "1.2.840.113549.1.9.5";
+
+/// OIDs `signedData`/`envelopedData` codificados em DER (prefixo comum).
+const PKCS7_OID_PREFIX: &[u8] = &[0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07];
+
+/// Região inicial onde os cabeçalhos S/MIME precisam aparecer.
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+
+/// Profundidade máxima de aninhamento BER aceita (proteção contra recursão).
+const MAX_BER_DEPTH: usize = 32;
+
+/// Envelope identificado e, quando possível, seu conteúdo interno.
+#[derive(Debug, Clone)]
+pub struct SignedEnvelope {
+    /// `der`, `pem`, `smime` ou `smime_detached`.
+    pub format: &'static str,
+    pub info: Option<Pkcs7Info>,
+    /// Conteúdo assinado (encapsulado ou da parte MIME destacada).
+    pub inner: Option<Vec<u8>>,
+}
+
+/// Estrutura extraída do `ContentInfo`.
+#[derive(Debug, Clone, Default)]
+pub struct Pkcs7Info {
+    pub content_type: String,
+    pub inner_content_type: Option<String>,
+    pub inner: Option<Vec<u8>>,
+    pub signers: Vec<Value>,
+    pub certificates: Vec<Value>,
+}
+
+/// Detecta PKCS#7/CMS (DER/PEM) ou mensagens S/MIME.
+pub fn detect_signed(data: &[u8]) -> Option<SignedEnvelope> {
+    if data.first() == Some(&0x30) && looks_like_pkcs7_der(data) {
+        let info = parse_content_info(data);
+        let inner = info.as_ref().and_then(|info| info.inner.clone());
+        return Some(SignedEnvelope {
+            format: "der",
+            info,
+            inner,
+        });
+    }
+
+    let head = String::from_utf8_lossy(&data[..data.len().min(64)]);
+    let trimmed = head.trim_start();
+    if trimmed.starts_with("-----BEGIN PKCS7-----") || trimmed.starts_with("-----BEGIN CMS-----") {
+        let der = decode_pem(std::str::from_utf8(data).ok()?);
+        let info = der.as_deref().and_then(parse_content_info);
+        let inner = info.as_ref().and_then(|info| info.inner.clone());
+        return Some(SignedEnvelope {
+            format: "pem",
+            info,
+            inner,
+        });
+    }
+
+    detect_smime(data)
+}
+
+/// Conteúdo interno a ser reanalisado, se houver.
+pub fn signed_inner_content(data: &[u8]) -> Option<Vec<u8>> {
+    detect_signed(data)?.inner
+}
+
+pub fn validate_pkcs7(
+    envelope: &SignedEnvelope,
+    policy: Option<&ResolvedPolicy>,
+) -> ValidatorOutcome {
+    let name = "pkcs7";
+    let pkcs7_policy = policy.map(|p| p.pkcs7.clone()).unwrap_or_default();
+    let Some(info) = envelope.info.as_ref() else {
+        return ValidatorOutcome::deny(name, "envelope PKCS#7/S-MIME inválido")
+            .with_details(json!({ "format": envelope.format }));
+    };
+
+    let details = json!({
+        "format": envelope.format,
+        "content_type": content_type_name(&info.content_type),
+        "inner_content_type": info.inner_content_type.as_deref().map(content_type_name),
+        "detached": envelope.format == "smime_detached" || (info.content_type == OID_SIGNED_DATA && info.inner.is_none()),
+        "inner_size": envelope.inner.as_ref().map(Vec::len),
+        "signers": info.signers,
+        "certificates": info.certificates,
+    });
+
+    let outcome = if info.content_type == OID_ENVELOPED_DATA {
+        let message = "conteúdo PKCS#7 criptografado (EnvelopedData) não pode ser inspecionado";
+        if pkcs7_policy.forbid_enveloped.unwrap_or(false) {
+            ValidatorOutcome::deny(name, message)
+        } else {
+            ValidatorOutcome::warn(name, message)
+        }
+    } else {
+        ValidatorOutcome::pass(name)
+    };
+    outcome.with_details(details)
+}
+
+fn looks_like_pkcs7_der(data: &[u8]) -> bool {
+    data.get(..32)
+        .unwrap_or(data)
+        .windows(PKCS7_OID_PREFIX.len() + 1)
+        .any(|window| {
+            window.starts_with(PKCS7_OID_PREFIX)
+                && matches!(window[PKCS7_OID_PREFIX.len()], 0x02 | 0x03)
+        })
+}
+
+fn decode_pem(text: &str) -> Option<Vec<u8>> {
+    let body: String = text
+        .lines()
+        .skip_while(|line| !line.starts_with("-----BEGIN"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("-----END"))
+        .flat_map(|line| line.trim().chars())
+        .collect();
+    base64::engine::general_purpose::STANDARD.decode(body).ok()
+}
+
+fn decode_base64_body(body: &[u8]) -> Option<Vec<u8>> {
+    let cleaned: Vec<u8> = body
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(cleaned)
+        .ok()
+}
+
+/// Separa cabeçalhos e corpo de uma mensagem/parte MIME.
+fn split_mime(data: &[u8]) -> (&[u8], &[u8]) {
+    for separator in [&b"\r\n\r\n"[..], &b"\n\n"[..]] {
+        if let Some(pos) = data
+            .windows(separator.len())
+            .position(|window| window == separator)
+        {
+            return (&data[..pos], &data[pos + separator.len()..]);
+        }
+    }
+    (data, &[])
+}
+
+/// Separa uma parte multipart; partes sem cabeçalhos (ex.: `openssl smime -binary`)
+/// são tratadas como corpo puro.
+fn split_part(part: &[u8]) -> (&[u8], &[u8]) {
+    if let Some(body) = part
+        .strip_prefix(b"\r\n")
+        .or_else(|| part.strip_prefix(b"\n"))
+    {
+        return (&[], body);
+    }
+    let first_line = part.split(|byte| *byte == b'\n').next().unwrap_or_default();
+    let has_header = first_line
+        .iter()
+        .position(|byte| *byte == b':')
+        .is_some_and(|colon| {
+            colon > 0
+                && first_line[..colon]
+                    .iter()
+                    .all(|byte| byte.is_ascii_alphanumeric() || *byte == b'-')
+        });
+    if has_header {
+        split_mime(part)
+    } else {
+        (&[], part)
+    }
+}
+
+fn header_value(headers: &str, name: &str) -> Option<String> {
+    let mut value: Option<String> = None;
+    for line in headers.lines() {
+        if let Some(current) = value.as_mut() {
+            if line.starts_with([' ', '\t']) {
+                current.push(' ');
+                current.push_str(line.trim());
+                continue;
+            }
+            break;
+        }
+        if let Some((key, rest)) = line.split_once(':') {
+            if key.trim().eq_ignore_ascii_case(name) {
+                value = Some(rest.trim().to_string());
+            }
+        }
+    }
+    value
+}
+
+fn mime_parameter(value: &str, parameter: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|item| {
+        let (key, raw) = item.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(parameter)
+            .then(|| raw.trim().trim_matches('"').to_string())
+    })
+}
+
+fn detect_smime(data: &[u8]) -> Option<SignedEnvelope> {
+    let head = &data[..data.len().min(MAX_HEADER_BYTES)];
+    if !contains_case_insensitive(head, b"content-type:") {
+        return None;
+    }
+    let (headers, body) = split_mime(data);
+    let headers = std::str::from_utf8(headers).ok()?;
+    let content_type = header_value(headers, "Content-Type")?;
+    let lowered = content_type.to_ascii_lowercase();
+
+    if lowered.starts_with("application/pkcs7-mime")
+        || lowered.starts_with("application/x-pkcs7-mime")
+    {
+        let info = decode_base64_body(body)
+            .as_deref()
+            .and_then(parse_content_info);
+        let inner = info.as_ref().and_then(|info| info.inner.clone());
+        return Some(SignedEnvelope {
+            format: "smime",
+            info,
+            inner,
+        });
+    }
+
+    if lowered.starts_with("multipart/signed") && lowered.contains("pkcs7-signature") {
+        let boundary = mime_parameter(&content_type, "boundary")?;
+        let delimiter = format!("--{boundary}");
+        let parts = split_multipart(body, delimiter.as_bytes());
+        let mut info = None;
+        let mut inner = None;
+        for part in parts {
+            let (part_headers, part_body) = split_part(part);
+            let part_headers = String::from_utf8_lossy(part_headers);
+            let part_type = header_value(&part_headers, "Content-Type")
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            if part_type.contains("pkcs7-signature") {
+                info = decode_base64_body(part_body)
+                    .as_deref()
+                    .and_then(parse_content_info);
+            } else if inner.is_none() {
+                let encoding =
+                    header_value(&part_headers, "Content-Transfer-Encoding").unwrap_or_default();
+                inner = if encoding.eq_ignore_ascii_case("base64") {
+                    decode_base64_body(part_body)
+                } else {
+                    Some(part_body.to_vec())
+                };
+            }
+        }
+        return Some(SignedEnvelope {
+            format: "smime_detached",
+            info,
+            inner,
+        });
+    }
+    None
+}
+
+fn split_multipart<'a>(body: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = Vec::new();
+    let mut positions = Vec::new();
+    let mut idx = 0;
+    while let Some(pos) = body[idx..]
+        .windows(delimiter.len())
+        .position(|window| window == delimiter)
+    {
+        positions.push(idx + pos);
+        idx += pos + delimiter.len();
+    }
+    for pair in positions.windows(2) {
+        let start = pair[0] + delimiter.len();
+        let part = &body[start..pair[1]];
+        let part = part
+            .strip_prefix(b"\r\n")
+            .or_else(|| part.strip_prefix(b"\n"))
+            .unwrap_or(part);
+        let part = part
+            .strip_suffix(b"\r\n")
+            .or_else(|| part.strip_suffix(b"\n"))
+            .unwrap_or(part);
+        parts.push(part);
+    }
+    parts
+}
+
+/// Elemento TLV de uma codificação DER/BER.
+#[derive(Debug, Clone, Copy)]
+struct Tlv<'a> {
+    tag: u8,
+    content: &'a [u8],
+    /// Nível de aninhamento a partir do elemento raiz, limitado por `MAX_BER_DEPTH`.
+    depth: usize,
+}
+
+impl<'a> Tlv<'a> {
+    fn is_constructed(&self) -> bool {
+        self.tag & 0x20 != 0
+    }
+
+    fn children(&self) -> Vec<Tlv<'a>> {
+        children(self.content, self.depth + 1)
+    }
+}
+
+/// Lê um TLV (suporta comprimento indefinido BER); devolve o elemento e bytes consumidos.
+fn read_tlv(data: &[u8], depth: usize) -> Option<(Tlv<'_>, usize)> {
+    if depth > MAX_BER_DEPTH {
+        return None;
+    }
+    let tag = *data.first()?;
+    if tag & 0x1F == 0x1F {
+        return None;
+    }
+    let first = *data.get(1)? as usize;
+    if first == 0x80 {
+        if tag & 0x20 == 0 {
+            return None;
+        }
+        let mut offset = 2;
+        loop {
+            if data.get(offset..offset + 2)? == [0, 0] {
+                return Some((
+                    Tlv {
+                        tag,
+                        content: &data[2..offset],
+                        depth,
+                    },
+                    offset + 2,
+                ));
+            }
+            let (_, used) = read_tlv(&data[offset..], depth + 1)?;
+            offset += used;
+        }
+    }
+    let (length, header) = if first < 0x80 {
+        (first, 2)
+    } else {
+        let count = first & 0x7F;
+        if count > 4 {
+            return None;
+        }
+        let bytes = data.get(2..2 + count)?;
+        let length = bytes
+            .iter()
+            .fold(0usize, |acc, byte| (acc << 8) | *byte as usize);
+        (length, 2 + count)
+    };
+    let content = data.get(header..header.checked_add(length)?)?;
+    Some((
+        Tlv {
+            tag,
+            content,
+            depth,
+        },
+        header + length,
+    ))
+}
+
+fn children(mut data: &[u8], depth: usize) -> Vec<Tlv<'_>> {
+    let mut items = Vec::new();
+    while !data.is_empty() && !data.starts_with(&[0, 0]) {
+        let Some((tlv, used)) = read_tlv(data, depth) else {
+            break;
+        };
+        items.push(tlv);
+        data = &data[used..];
+    }
+    items
+}
+
+fn oid_string(tlv: &Tlv<'_>) -> Option<String> {
+    if tlv.tag != 0x06 || tlv.content.is_empty() {
+        return None;
+    }
+    let mut arcs = Vec::new();
+    let mut value = 0u64;
+    for byte in tlv.content {
+        value = (value << 7) | (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            arcs.push(value);
+            value = 0;
+        }
+    }
+    // O primeiro subidentificador codifica os dois primeiros arcos (X*40 + Y).
+    let first = *arcs.first()?;
+    let (root, second) = if first < 80 {
+        (first / 40, first % 40)
+    } else {
+        (2, first - 80)
+    };
+    let parts: Vec<u64> = [root, second]
+        .into_iter()
+        .chain(arcs.into_iter().skip(1))
+        .collect();
+    Some(
+        parts
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join("."),
+    )
+}
+
+/// Conteúdo de OCTET STRING primitiva ou construída (BER); partes além de
+/// `MAX_BER_DEPTH` são descartadas.
+fn octets(tlv: &Tlv<'_>) -> Vec<u8> {
+    if tlv.is_constructed() {
+        tlv.children().iter().flat_map(octets).collect()
+    } else {
+        tlv.content.to_vec()
+    }
+}
+
+fn parse_content_info(der: &[u8]) -> Option<Pkcs7Info> {
+    let (top, _) = read_tlv(der, 0)?;
+    if top.tag != 0x30 {
+        return None;
+    }
+    let items = top.children();
+    let content_type = oid_string(items.first()?)?;
+    match content_type.as_str() {
+        OID_SIGNED_DATA => {
+            let explicit = items.get(1).filter(|tlv| tlv.tag == 0xA0)?;
+            let signed_data = explicit.children().into_iter().next()?;
+            parse_signed_data(&signed_data)
+        }
+        OID_ENVELOPED_DATA => Some(Pkcs7Info {
+            content_type,
+            ..Pkcs7Info::default()
+        }),
+        _ => None,
+    }
+}
+
+fn parse_signed_data(signed_data: &Tlv<'_>) -> Option<Pkcs7Info> {
+    let items = signed_data.children();
+    let encap = items.get(2).filter(|tlv| tlv.tag == 0x30)?.children();
+    let inner_content_type = encap.first().and_then(oid_string);
+    let inner = encap
+        .get(1)
+        .filter(|tlv| tlv.tag == 0xA0)
+        .and_then(|explicit| explicit.children().into_iter().next())
+        .map(|octet_string| octets(&octet_string));
+
+    let mut certificates = Vec::new();
+    let mut signers = Vec::new();
+    for item in items.iter().skip(3) {
+        match item.tag {
+            0xA0 => {
+                certificates = item
+                    .children()
+                    .iter()
+                    .filter_map(parse_certificate)
+                    .collect()
+            }
+            0x31 => signers = item.children().iter().filter_map(parse_signer).collect(),
+            _ => {}
+        }
+    }
+    for signer in &mut signers {
+        let subject = certificates
+            .iter()
+            .find(|cert| cert["serial"] == signer["serial"] && cert["issuer"] == signer["issuer"])
+            .map(|cert| cert["subject"].clone());
+        if let Some(subject) = subject {
+            signer["subject"] = subject;
+        }
+    }
+
+    Some(Pkcs7Info {
+        content_type: OID_SIGNED_DATA.to_string(),
+        inner_content_type,
+        inner,
+        signers,
+        certificates,
+    })
+}
+
+fn parse_certificate(certificate: &Tlv<'_>) -> Option<Value> {
+    let tbs = certificate.children().into_iter().next()?.children();
+    let base = usize::from(tbs.first()?.tag == 0xA0);
+    let serial = hex::encode(tbs.get(base)?.content);
+    let issuer = name_string(tbs.get(base + 2)?);
+    let validity = tbs.get(base + 3)?.children();
+    let subject = name_string(tbs.get(base + 4)?);
+    Some(json!({
+        "subject": subject,
+        "issuer": issuer,
+        "serial": serial,
+        "not_before": validity.first().map(time_string),
+        "not_after": validity.get(1).map(time_string),
+    }))
+}
+
+fn parse_signer(signer: &Tlv<'_>) -> Option<Value> {
+    let items = signer.children();
+    let sid = items.get(1)?;
+    let (issuer, serial, key_id) = match sid.tag {
+        0x30 => {
+            let parts = sid.children();
+            (
+                Some(name_string(parts.first()?)),
+                Some(hex::encode(parts.get(1)?.content)),
+                None,
+            )
+        }
+        0x80 => (None, None, Some(hex::encode(sid.content))),
+        _ => return None,
+    };
+    let digest_algorithm = items
+        .get(2)
+        .and_then(|alg| alg.children().first().and_then(oid_string));
+
+    let mut index = 3;
+    let mut signing_time = None;
+    if let Some(attributes) = items.get(index).filter(|tlv| tlv.tag == 0xA0) {
+        for attribute in attributes.children() {
+            let parts = attribute.children();
+            if parts.first().and_then(oid_string).as_deref() == Some(OID_SIGNING_TIME) {
+                signing_time = parts
+                    .get(1)
+                    .and_then(|set| set.children().first().map(time_string));
+            }
+        }
+        index += 1;
+    }
+    let signature_algorithm = items
+        .get(index)
+        .and_then(|alg| alg.children().first().and_then(oid_string));
+
+    Some(json!({
+        "issuer": issuer,
+        "serial": serial,
+        "subject_key_id": key_id,
+        "digest_algorithm": digest_algorithm.as_deref().map(algorithm_name),
+        "signature_algorithm": signature_algorithm.as_deref().map(algorithm_name),
+        "signing_time": signing_time,
+    }))
+}
+
+/// Converte um `Name` X.501 em `CN=..., O=...`.
+fn name_string(name: &Tlv<'_>) -> String {
+    name.children()
+        .iter()
+        .flat_map(|set| set.children())
+        .filter_map(|attribute| {
+            let parts = attribute.children();
+            let oid = oid_string(parts.first()?)?;
+            let value = String::from_utf8_lossy(parts.get(1)?.content).to_string();
+            Some(format!("{}={value}", attribute_name(&oid)))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn time_string(tlv: &Tlv<'_>) -> String {
+    String::from_utf8_lossy(tlv.content).to_string()
+}
+
+fn attribute_name(oid: &str) -> &str {
+    match oid {
+        "2.5.4.3" => "CN",
+        "2.5.4.5" => "serialNumber",
+        "2.5.4.6" => "C",
+        "2.5.4.7" => "L",
+        "2.5.4.8" => "ST",
+        "2.5.4.10" => "O",
+        "2.5.4.11" => "OU",
+        "1.2.840.113549.1.9.1" => "emailAddress",
+        other => other,
+    }
+}
+
+fn algorithm_name(oid: &str) -> String {
+    match oid {
+        "1.2.840.113549.2.5" => "md5",
+        "1.3.14.3.2.26" => "sha1",
+        "2.16.840.1.101.3.4.2.1" => "sha256",
+        "2.16.840.1.101.3.4.2.2" => "sha384",
+        "2.16.840.1.101.3.4.2.3" => "sha512",
+        "1.2.840.113549.1.1.1" => "rsaEncryption",
+        "1.2.840.113549.1.1.5" => "sha1WithRSAEncryption",
+        "1.2.840.113549.1.1.11" => "sha256WithRSAEncryption",
+        "1.2.840.113549.1.1.12" => "sha384WithRSAEncryption",
+        "1.2.840.113549.1.1.13" => "sha512WithRSAEncryption",
+        "1.2.840.10045.4.3.2" => "ecdsa-with-SHA256",
+        "1.2.840.10045.4.3.3" => "ecdsa-with-SHA384",
+        other => other,
+    }
+    .to_string()
+}
+
+fn content_type_name(oid: &str) -> String {
+    match oid {
+        OID_DATA => "data",
+        OID_SIGNED_DATA => "signed_data",
+        OID_ENVELOPED_DATA => "enveloped_data",
+        other => other,
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Pkcs7PolicySection;
+    use crate::validators::ValidatorStatus;
+
+    fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        if content.len() < 0x80 {
+            out.push(content.len() as u8);
+        } else {
+            out.push(0x82);
+            out.extend_from_slice(&(content.len() as u16).to_be_bytes());
+        }
+        out.extend_from_slice(content);
+        out
+    }
+
+    fn seq(items: &[Vec<u8>]) -> Vec<u8> {
+        tlv(0x30, &items.concat())
+    }
+
+    fn set(items: &[Vec<u8>]) -> Vec<u8> {
+        tlv(0x31, &items.concat())
+    }
+
+    fn oid(encoded: &[u8]) -> Vec<u8> {
+        tlv(0x06, encoded)
+    }
+
+    fn common_name(value: &str) -> Vec<u8> {
+        seq(&[set(&[seq(&[
+            oid(&[0x55, 0x04, 0x03]),
+            tlv(0x0C, value.as_bytes()),
+        ])])])
+    }
+
+    const SIGNED_DATA_OID: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02];
+    const DATA_OID: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x01];
+    const SHA256_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+
+    /// SignedData mínimo com um certificado e um signatário (assinatura fictícia).
+    fn build_signed(content: &[u8]) -> Vec<u8> {
+        let issuer = common_name("AC Teste");
+        let serial = tlv(0x02, &[0x01, 0x02]);
+        let algorithm = seq(&[oid(SHA256_OID)]);
+        let certificate = seq(&[
+            seq(&[
+                tlv(0xA0, &tlv(0x02, &[0x02])),
+                serial.clone(),
+                algorithm.clone(),
+                issuer.clone(),
+                seq(&[tlv(0x17, b"260101000000Z"), tlv(0x17, b"270101000000Z")]),
+                common_name("Ana Signatária"),
+            ]),
+            algorithm.clone(),
+            tlv(0x03, &[0x00]),
+        ]);
+        let signer = seq(&[
+            tlv(0x02, &[0x01]),
+            seq(&[issuer, serial]),
+            algorithm.clone(),
+            seq(&[oid(&[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0B])]),
+            tlv(0x04, &[0xAA; 8]),
+        ]);
+        let signed_data = seq(&[
+            tlv(0x02, &[0x01]),
+            set(&[algorithm]),
+            seq(&[oid(DATA_OID), tlv(0xA0, &tlv(0x04, content))]),
+            tlv(0xA0, &certificate),
+            set(&[signer]),
+        ]);
+        seq(&[oid(SIGNED_DATA_OID), tlv(0xA0, &signed_data)])
+    }
+
+    #[test]
+    fn der_signed_data_reports_signer_and_inner_content() {
+        let data = build_signed(b"%PDF-1.7 conteudo");
+        let envelope = detect_signed(&data).expect("pkcs7");
+        assert_eq!(envelope.format, "der");
+        assert_eq!(envelope.inner.as_deref(), Some(&b"%PDF-1.7 conteudo"[..]));
+
+        let outcome = validate_pkcs7(&envelope, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        let signer = &outcome.details["signers"][0];
+        assert_eq!(signer["issuer"], "CN=AC Teste");
+        assert_eq!(signer["subject"], "CN=Ana Signatária");
+        assert_eq!(signer["serial"], "0102");
+        assert_eq!(signer["digest_algorithm"], "sha256");
+        assert_eq!(signer["signature_algorithm"], "sha256WithRSAEncryption");
+        assert_eq!(outcome.details["inner_content_type"], "data");
+        assert_eq!(
+            outcome.details["certificates"][0]["not_after"],
+            "270101000000Z"
+        );
+    }
+
+    #[test]
+    fn smime_opaque_message_is_unwrapped() {
+        let der = build_signed(b"texto assinado");
+        let body = base64::engine::general_purpose::STANDARD.encode(der);
+        let message = format!(
+            "MIME-Version: 1.0\r\nContent-Type: application/pkcs7-mime; smime-type=signed-data;\r\n name=\"smime.p7m\"\r\nContent-Transfer-Encoding: base64\r\n\r\n{body}\r\n"
+        );
+        let envelope = detect_signed(message.as_bytes()).expect("smime");
+        assert_eq!(envelope.format, "smime");
+        assert_eq!(envelope.inner.as_deref(), Some(&b"texto assinado"[..]));
+        assert_eq!(
+            signed_inner_content(message.as_bytes()).unwrap(),
+            b"texto assinado"
+        );
+    }
+
+    #[test]
+    fn smime_detached_signature_uses_first_part() {
+        let der = build_signed(b"");
+        let signature = base64::engine::general_purpose::STANDARD.encode(der);
+        let message = format!(
+            "Content-Type: multipart/signed; protocol=\"application/pkcs7-signature\"; boundary=\"XYZ\"\n\n--XYZ\nContent-Type: text/plain\n\nolá\n--XYZ\nContent-Type: application/pkcs7-signature\nContent-Transfer-Encoding: base64\n\n{signature}\n--XYZ--\n"
+        );
+        let envelope = detect_signed(message.as_bytes()).expect("smime");
+        assert_eq!(envelope.format, "smime_detached");
+        assert_eq!(envelope.inner.as_deref(), Some("olá".as_bytes()));
+        let outcome = validate_pkcs7(&envelope, None);
+        assert_eq!(outcome.details["detached"], true);
+        assert_eq!(outcome.details["signers"][0]["issuer"], "CN=AC Teste");
+    }
+
+    #[test]
+    fn enveloped_data_warns_or_denies() {
+        let enveloped_oid = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x03];
+        let data = seq(&[oid(&enveloped_oid), tlv(0xA0, &seq(&[tlv(0x02, &[0])]))]);
+        let envelope = detect_signed(&data).expect("pkcs7");
+        assert!(envelope.inner.is_none());
+        let outcome = validate_pkcs7(&envelope, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Warn));
+
+        let policy = ResolvedPolicy {
+            pkcs7: Pkcs7PolicySection {
+                forbid_enveloped: Some(true),
+                ..Pkcs7PolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let outcome = validate_pkcs7(&envelope, Some(&policy));
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+    }
+
+    #[test]
+    fn ber_indefinite_length_is_supported() {
+        // SEQUENCE indefinida contendo OCTET STRING construída em dois pedaços.
+        let data = [
+            0x30, 0x80, 0x24, 0x80, 0x04, 0x02, b'o', b'l', 0x04, 0x01, b'a', 0x00, 0x00, 0x00,
+            0x00,
+        ];
+        let (top, used) = read_tlv(&data, 0).expect("tlv");
+        assert_eq!(used, data.len());
+        assert_eq!(octets(&top.children()[0]), b"ola");
+    }
+
+    /// TLV com comprimento definido na forma curta ou longa.
+    fn der_header(tag: u8, length: usize) -> Vec<u8> {
+        if length < 0x80 {
+            return vec![tag, length as u8];
+        }
+        let bytes = (length as u32).to_be_bytes();
+        let skip = bytes.iter().take_while(|&&byte| byte == 0).count();
+        let mut header = vec![tag, 0x80 | (4 - skip) as u8];
+        header.extend_from_slice(&bytes[skip..]);
+        header
+    }
+
+    fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = der_header(tag, content.len());
+        out.extend_from_slice(content);
+        out
+    }
+
+    #[test]
+    fn deeply_nested_definite_length_octets_stop_at_max_depth() {
+        // 200 mil OCTET STRINGs construídas aninhadas: os cabeçalhos só prefixam
+        // o conteúdo, então são montados de dentro para fora.
+        let mut headers = Vec::new();
+        let mut length = 3;
+        for _ in 0..200_000 {
+            let header = der_header(0x24, length);
+            length += header.len();
+            headers.push(header);
+        }
+        let mut nested: Vec<u8> = headers.into_iter().rev().flatten().collect();
+        nested.extend_from_slice(&[0x04, 0x01, b'x']);
+
+        let (top, used) = read_tlv(&nested, 0).expect("tlv");
+        assert_eq!(used, nested.len());
+        assert!(octets(&top).is_empty());
+
+        let oid_data = [
+            0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x01,
+        ];
+        let oid_signed = [
+            0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02,
+        ];
+        let encap = der(0x30, &[&oid_data[..], &der(0xA0, &nested)].concat());
+        let signed_data = der(
+            0x30,
+            &[&[0x02, 0x01, 0x01, 0x31, 0x00][..], &encap].concat(),
+        );
+        let p7m = der(0x30, &[&oid_signed[..], &der(0xA0, &signed_data)].concat());
+        assert_eq!(signed_inner_content(&p7m), Some(Vec::new()));
+    }
+}
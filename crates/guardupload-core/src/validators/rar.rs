@@ -0,0 +1,485 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Listagem de arquivos RAR (formatos 1.5–4.x e 5.0) pelos cabeçalhos de bloco.
+//!
+//! O conteúdo nunca é descomprimido: nomes, tamanhos, atributos e flags de
+//! criptografia vêm dos cabeçalhos de arquivo, com CRC conferido. As entradas
+//! seguem para [`validate_listing`].
+
+use super::archive::is_symlink;
+use super::listing::{validate_listing, ListedEntry, Listing, ListingError};
+use super::ValidatorOutcome;
+use crate::limits::Deadline;
+use crate::policy::ResolvedPolicy;
+use flate2::Crc;
+
+const SIGNATURE_V4: &[u8] = b"Rar!\x1A\x07\x00";
+const SIGNATURE_V5: &[u8] = b"Rar!\x1A\x07\x01\x00";
+
+const V4_MAIN: u8 = 0x73;
+const V4_FILE: u8 = 0x74;
+const V4_END: u8 = 0x7B;
+const V4_LONG_BLOCK: u16 = 0x8000;
+const V4_MAIN_ENCRYPTED_HEADERS: u16 = 0x0080;
+const V4_FILE_ENCRYPTED: u16 = 0x0004;
+const V4_FILE_DIRECTORY: u16 = 0x00E0;
+const V4_FILE_LARGE: u16 = 0x0100;
+const V4_FILE_UNICODE: u16 = 0x0200;
+
+const V5_FILE: u64 = 2;
+const V5_ENCRYPTION: u64 = 4;
+const V5_END: u64 = 5;
+const V5_HAS_EXTRA: u64 = 0x01;
+const V5_HAS_DATA: u64 = 0x02;
+const V5_FILE_DIRECTORY: u64 = 0x01;
+const V5_FILE_MTIME: u64 = 0x02;
+const V5_FILE_CRC: u64 = 0x04;
+const V5_EXTRA_ENCRYPTION: u64 = 0x01;
+const V5_EXTRA_REDIRECTION: u64 = 0x05;
+
+const HOST_UNIX_V4: u8 = 3;
+const HOST_UNIX_V5: u64 = 1;
+const ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+
+/// Indica se o conteúdo começa com a assinatura do RAR 4 ou 5.
+pub fn is_rar(data: &[u8]) -> bool {
+    data.starts_with(SIGNATURE_V4) || data.starts_with(SIGNATURE_V5)
+}
+
+pub fn validate_rar(
+    data: &[u8],
+    policy: Option<&ResolvedPolicy>,
+    deadline: &Deadline,
+) -> ValidatorOutcome {
+    let listing = if data.starts_with(SIGNATURE_V5) {
+        list_v5(data, deadline)
+    } else if data.starts_with(SIGNATURE_V4) {
+        list_v4(data, deadline)
+    } else {
+        Err(invalid("assinatura ausente"))
+    };
+    validate_listing("rar", "rar", listing, policy, deadline)
+}
+
+fn invalid(message: impl Into<String>) -> ListingError {
+    ListingError::Invalid(message.into())
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(bytes);
+    crc.sum()
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> Result<u16, ListingError> {
+    bytes
+        .get(at..at + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| invalid("cabeçalho truncado"))
+}
+
+fn le_u32(bytes: &[u8], at: usize) -> Result<u32, ListingError> {
+    bytes
+        .get(at..at + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid("cabeçalho truncado"))
+}
+
+/// Symlink pelo modo Unix ou ponto de reparse nos atributos do Windows.
+fn is_link(unix_host: bool, attributes: u32) -> bool {
+    if unix_host {
+        is_symlink(attributes)
+    } else {
+        attributes & ATTRIBUTE_REPARSE_POINT != 0
+    }
+}
+
+/// Registra a entrada e, se tiver conteúdo, o bloco usado no cálculo de taxa.
+fn push_entry(listing: &mut Listing, entry: ListedEntry, packed: u64) {
+    if !entry.is_dir {
+        listing
+            .blocks
+            .push((entry.name.clone(), entry.size, packed));
+    }
+    listing.entries.push(entry);
+}
+
+/// RAR 1.5–4.x: blocos com CRC de 16 bits, tipo, flags e tamanho fixos.
+fn list_v4(data: &[u8], deadline: &Deadline) -> Result<Listing, ListingError> {
+    let mut listing = Listing::default();
+    let mut pos = SIGNATURE_V4.len();
+    while pos < data.len() {
+        if deadline.is_expired() {
+            return Err(ListingError::Timeout);
+        }
+        let block_type = *data.get(pos + 2).ok_or_else(|| invalid("bloco truncado"))?;
+        let flags = le_u16(data, pos + 3)?;
+        let head_size = le_u16(data, pos + 5)? as usize;
+        let head = data
+            .get(pos..pos + head_size)
+            .filter(|_| head_size >= 7)
+            .ok_or_else(|| invalid("cabeçalho de bloco truncado"))?;
+        if crc32(&head[2..]) as u16 != le_u16(head, 0)? {
+            return Err(invalid(format!(
+                "CRC do cabeçalho no offset {pos} não confere"
+            )));
+        }
+        let mut packed = 0u64;
+        if flags & V4_LONG_BLOCK != 0 {
+            packed = u64::from(le_u32(head, 7)?);
+        }
+        match block_type {
+            V4_MAIN if flags & V4_MAIN_ENCRYPTED_HEADERS != 0 => {
+                listing.encrypted_headers = true;
+                return Ok(listing);
+            }
+            V4_FILE => {
+                let mut unpacked = u64::from(le_u32(head, 11)?);
+                let mut name_at = 32;
+                if flags & V4_FILE_LARGE != 0 {
+                    packed |= u64::from(le_u32(head, 32)?) << 32;
+                    unpacked |= u64::from(le_u32(head, 36)?) << 32;
+                    name_at = 40;
+                }
+                let name_len = le_u16(head, 26)? as usize;
+                let raw_name = head
+                    .get(name_at..name_at + name_len)
+                    .ok_or_else(|| invalid("nome de entrada truncado"))?;
+                // Nomes Unicode trazem a versão ASCII antes do NUL.
+                let raw_name = if flags & V4_FILE_UNICODE != 0 {
+                    raw_name.split(|byte| *byte == 0).next().unwrap_or_default()
+                } else {
+                    raw_name
+                };
+                let attributes = le_u32(head, 28)?;
+                let entry = ListedEntry {
+                    name: String::from_utf8_lossy(raw_name).replace('\\', "/"),
+                    size: unpacked,
+                    is_dir: flags & V4_FILE_DIRECTORY == V4_FILE_DIRECTORY,
+                    encrypted: flags & V4_FILE_ENCRYPTED != 0,
+                    link: is_link(head[15] == HOST_UNIX_V4, attributes),
+                };
+                push_entry(&mut listing, entry, packed);
+            }
+            V4_END => break,
+            _ => {}
+        }
+        pos = pos
+            .checked_add(head_size)
+            .and_then(|pos| usize::try_from(packed).ok()?.checked_add(pos))
+            .ok_or_else(|| invalid("tamanho de bloco inválido"))?;
+    }
+    Ok(listing)
+}
+
+/// Leitor dos campos de tamanho variável do RAR 5 (7 bits por byte).
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn vint(&mut self) -> Result<u64, ListingError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or_else(|| invalid("cabeçalho truncado"))?;
+            self.pos += 1;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("número de tamanho variável inválido"))
+    }
+
+    fn bytes(&mut self, len: u64) -> Result<&'a [u8], ListingError> {
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.pos.checked_add(len))
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| invalid("cabeçalho truncado"))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, ListingError> {
+        le_u32(self.bytes(4)?, 0)
+    }
+}
+
+/// RAR 5.0: blocos com CRC32, tamanho do cabeçalho e campos variáveis.
+fn list_v5(data: &[u8], deadline: &Deadline) -> Result<Listing, ListingError> {
+    let mut listing = Listing::default();
+    let mut pos = SIGNATURE_V5.len();
+    while pos < data.len() {
+        if deadline.is_expired() {
+            return Err(ListingError::Timeout);
+        }
+        let crc = le_u32(data, pos)?;
+        let mut size_reader = Reader { data, pos: pos + 4 };
+        let head_size = size_reader.vint()?;
+        let head_start = size_reader.pos;
+        let head_end = usize::try_from(head_size)
+            .ok()
+            .and_then(|size| head_start.checked_add(size))
+            .filter(|end| *end <= data.len())
+            .ok_or_else(|| invalid("cabeçalho de bloco truncado"))?;
+        if crc32(&data[pos + 4..head_end]) != crc {
+            return Err(invalid(format!(
+                "CRC do cabeçalho no offset {pos} não confere"
+            )));
+        }
+        let mut reader = Reader {
+            data: &data[..head_end],
+            pos: head_start,
+        };
+        let block_type = reader.vint()?;
+        let flags = reader.vint()?;
+        let extra_size = if flags & V5_HAS_EXTRA != 0 {
+            reader.vint()?
+        } else {
+            0
+        };
+        let data_size = if flags & V5_HAS_DATA != 0 {
+            reader.vint()?
+        } else {
+            0
+        };
+        match block_type {
+            V5_ENCRYPTION => {
+                listing.encrypted_headers = true;
+                return Ok(listing);
+            }
+            V5_FILE => {
+                let extra_start = usize::try_from(extra_size)
+                    .ok()
+                    .and_then(|size| head_end.checked_sub(size))
+                    .ok_or_else(|| invalid("área extra inválida"))?;
+                let entry = read_v5_file(&mut reader, &data[extra_start..head_end])?;
+                push_entry(&mut listing, entry, data_size);
+            }
+            V5_END => break,
+            _ => {}
+        }
+        pos = usize::try_from(data_size)
+            .ok()
+            .and_then(|size| head_end.checked_add(size))
+            .ok_or_else(|| invalid("tamanho de bloco inválido"))?;
+    }
+    Ok(listing)
+}
+
+fn read_v5_file(reader: &mut Reader<'_>, extra: &[u8]) -> Result<ListedEntry, ListingError> {
+    let file_flags = reader.vint()?;
+    let size = reader.vint()?;
+    let attributes = reader.vint()?;
+    if file_flags & V5_FILE_MTIME != 0 {
+        reader.u32()?;
+    }
+    if file_flags & V5_FILE_CRC != 0 {
+        reader.u32()?;
+    }
+    let _compression = reader.vint()?;
+    let host = reader.vint()?;
+    let name_len = reader.vint()?;
+    let name = String::from_utf8_lossy(reader.bytes(name_len)?).into_owned();
+
+    let mut entry = ListedEntry {
+        name,
+        size,
+        is_dir: file_flags & V5_FILE_DIRECTORY != 0,
+        encrypted: false,
+        link: is_link(host == HOST_UNIX_V5, attributes as u32),
+    };
+    let mut records = Reader {
+        data: extra,
+        pos: 0,
+    };
+    while records.pos < extra.len() {
+        let record_size = records.vint()?;
+        let record = records.bytes(record_size)?;
+        let record_type = Reader {
+            data: record,
+            pos: 0,
+        }
+        .vint()?;
+        match record_type {
+            V5_EXTRA_ENCRYPTION => entry.encrypted = true,
+            V5_EXTRA_REDIRECTION => entry.link = true,
+            _ => {}
+        }
+    }
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ArchivePolicySection;
+    use crate::validators::ValidatorStatus;
+
+    fn vint(mut value: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                out.push(byte);
+                return out;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    fn v5_block(block_type: u64, body: &[u8], extra: &[u8], content: &[u8]) -> Vec<u8> {
+        let mut flags = 0;
+        if !extra.is_empty() {
+            flags |= V5_HAS_EXTRA;
+        }
+        if !content.is_empty() {
+            flags |= V5_HAS_DATA;
+        }
+        let mut fields = vint(block_type);
+        fields.extend(vint(flags));
+        if !extra.is_empty() {
+            fields.extend(vint(extra.len() as u64));
+        }
+        if !content.is_empty() {
+            fields.extend(vint(content.len() as u64));
+        }
+        fields.extend_from_slice(body);
+        fields.extend_from_slice(extra);
+        let mut head = vint(fields.len() as u64);
+        head.extend(fields);
+        let mut block = crc32(&head).to_le_bytes().to_vec();
+        block.extend(head);
+        block.extend_from_slice(content);
+        block
+    }
+
+    fn v5_file(name: &str, content: &[u8], mode: u64, link_target: Option<&str>) -> Vec<u8> {
+        let mut body = vint(V5_FILE_CRC);
+        body.extend(vint(content.len() as u64));
+        body.extend(vint(mode));
+        body.extend(crc32(content).to_le_bytes());
+        body.extend(vint(0));
+        body.extend(vint(HOST_UNIX_V5));
+        body.extend(vint(name.len() as u64));
+        body.extend_from_slice(name.as_bytes());
+        let mut extra = Vec::new();
+        if let Some(target) = link_target {
+            let mut record = vint(V5_EXTRA_REDIRECTION);
+            record.extend(vint(1));
+            record.extend(vint(0));
+            record.extend(vint(target.len() as u64));
+            record.extend_from_slice(target.as_bytes());
+            extra = vint(record.len() as u64);
+            extra.extend(record);
+        }
+        v5_block(V5_FILE, &body, &extra, content)
+    }
+
+    fn v4_block(block_type: u8, flags: u16, body: &[u8]) -> Vec<u8> {
+        let mut head = vec![block_type];
+        head.extend(flags.to_le_bytes());
+        head.extend((7 + body.len() as u16).to_le_bytes());
+        head.extend_from_slice(body);
+        let mut block = (crc32(&head) as u16).to_le_bytes().to_vec();
+        block.extend(head);
+        block
+    }
+
+    fn v4_file(name: &str, content: &[u8], flags: u16) -> Vec<u8> {
+        let mut body = (content.len() as u32).to_le_bytes().to_vec();
+        body.extend((content.len() as u32).to_le_bytes());
+        body.push(HOST_UNIX_V4);
+        body.extend(crc32(content).to_le_bytes());
+        body.extend([0, 0, 0, 0, 20, 0x30]);
+        body.extend((name.len() as u16).to_le_bytes());
+        body.extend(0o100644u32.to_le_bytes());
+        body.extend_from_slice(name.as_bytes());
+        let mut block = v4_block(V4_FILE, V4_LONG_BLOCK | flags, &body);
+        block.extend_from_slice(content);
+        block
+    }
+
+    #[test]
+    fn rar5_lists_entries_and_denies_traversal_and_links() {
+        let mut data = SIGNATURE_V5.to_vec();
+        data.extend(v5_block(1, &vint(0), &[], &[]));
+        data.extend(v5_file("docs/a.txt", b"ola mundo\n", 0o100644, None));
+        data.extend(v5_file("link", b"", 0o120777, Some("/etc/passwd")));
+        data.extend(v5_file("../fora.txt", b"x", 0o100644, None));
+        data.extend(v5_block(V5_END, &vint(0), &[], &[]));
+        assert!(is_rar(&data));
+
+        let listing = list_v5(&data, &Deadline::none()).expect("listing");
+        let names: Vec<&str> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["docs/a.txt", "link", "../fora.txt"]);
+        assert_eq!(listing.entries[0].size, 10);
+        assert!(listing.entries[1].link);
+
+        let outcome = validate_rar(&data, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert!(outcome.details["message"]
+            .as_str()
+            .unwrap()
+            .contains("'link' é symlink"));
+
+        let allow_links = ResolvedPolicy {
+            archive: ArchivePolicySection {
+                forbid_symlinks: Some(false),
+                ..ArchivePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let outcome = validate_rar(&data, Some(&allow_links), &Deadline::none());
+        assert!(outcome.details["message"]
+            .as_str()
+            .unwrap()
+            .contains("'../fora.txt' possui path traversal"));
+
+        let mut corrupted = data.clone();
+        corrupted[SIGNATURE_V5.len() + 20] ^= 0xFF;
+        let outcome = validate_rar(&corrupted, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Error));
+    }
+
+    #[test]
+    fn rar4_reports_encrypted_entries_and_headers() {
+        let mut data = SIGNATURE_V4.to_vec();
+        data.extend(v4_block(V4_MAIN, 0, &[0; 6]));
+        data.extend(v4_file("docs\\a.txt", b"ola mundo\n", 0));
+        data.extend(v4_file("segredo.txt", b"zzzz", V4_FILE_ENCRYPTED));
+        data.extend(v4_block(V4_END, 0x4000, &[]));
+
+        let outcome = validate_rar(&data, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(outcome.details["entries"][0], "docs/a.txt");
+        assert_eq!(outcome.details["encrypted_entries"][0], "segredo.txt");
+
+        let forbid = ResolvedPolicy {
+            archive: ArchivePolicySection {
+                forbid_encrypted_entries: Some(true),
+                ..ArchivePolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let outcome = validate_rar(&data, Some(&forbid), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+
+        let mut headers = SIGNATURE_V4.to_vec();
+        headers.extend(v4_block(V4_MAIN, V4_MAIN_ENCRYPTED_HEADERS, &[0; 6]));
+        headers.extend_from_slice(&[0xAB; 32]);
+        let outcome = validate_rar(&headers, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Warn));
+        assert_eq!(outcome.details["encrypted_headers"], true);
+    }
+}
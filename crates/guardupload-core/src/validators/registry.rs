@@ -0,0 +1,1080 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Trait de validadores e registro ordenado usado no despacho por conteúdo/MIME.
+
+use super::svg::has_svg_root;
+use super::{
+    detect_binary, detect_disk_image, detect_signed, is_ole, is_ooxml, is_rar, is_sevenzip,
+    validate_archive, validate_binary, validate_disk_image, validate_filename, validate_generic,
+    validate_image, validate_office, validate_ole, validate_pdf, validate_pkcs7, validate_rar,
+    validate_sevenzip, validate_svg, ValidatorOutcome, ValidatorStatus, ValidatorVersion,
+    VersionRequirement, VALIDATOR_ROUTES,
+};
+use crate::config::{ValidatorErrorAction, ValidatorsSection};
+use crate::decompress;
+use crate::limits::Deadline;
+use crate::policy::ResolvedPolicy;
+use crate::rules;
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::thread::{self, ThreadId};
+use std::time::Instant;
+
+/// Valor de [`Validator::supplements`] que complementa qualquer validador principal.
+pub const ANY_VALIDATOR: &str = "*";
+
+/// Entrada de um validador: conteúdo, MIME real, política resolvida e prazo.
+#[derive(Clone, Copy)]
+pub struct ValidationContext<'a> {
+    pub mime: &'a str,
+    pub data: &'a [u8],
+    pub policy: Option<&'a ResolvedPolicy>,
+    pub deadline: &'a Deadline,
+    /// Nome do arquivo enviado, sem diretórios; ausente em conteúdo desembrulhado.
+    pub file_name: Option<&'a str>,
+}
+
+/// Validador plugável.
+///
+/// Validadores principais são exclusivos: só o primeiro aplicável do registro
+/// roda. Complementares (`supplements`) rodam depois do principal que complementam.
+pub trait Validator: Send + Sync {
+    /// Nome estável, usado em `validators[].name`, regras `validator:<nome>:*` e na
+    /// seção `validators` da política.
+    fn name(&self) -> &'static str;
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool;
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome;
+
+    /// Versão gravada em `validators[].version` e conferida contra `require`.
+    fn version(&self) -> ValidatorVersion {
+        ValidatorVersion::INITIAL
+    }
+
+    /// Nome do validador principal que este complementa ([`ANY_VALIDATOR`] para todos).
+    fn supplements(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Critérios de seleção: MIMEs (globs como `image/*`), `magic:<formato>`
+    /// quando o reconhecimento é pelo conteúdo ou `name:<glob>` pelo nome
+    /// enviado. Vazio quando vale para qualquer
+    /// conteúdo; o padrão vem de [`VALIDATOR_ROUTES`].
+    fn mime_patterns(&self) -> Vec<&str> {
+        VALIDATOR_ROUTES
+            .iter()
+            .filter(|route| route.validator == self.name())
+            .flat_map(|route| route.matches.iter().copied())
+            .collect()
+    }
+
+    /// Chaves da política lidas pelo validador (`image.max_pixels`).
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &[]
+    }
+}
+
+/// Descrição de um validador registrado (`guardupload validators list`).
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidatorCapability {
+    pub name: &'static str,
+    pub version: ValidatorVersion,
+    /// Validador principal complementado; ausente em validadores principais.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplements: Option<&'static str>,
+    pub mime_patterns: Vec<String>,
+    pub policy_keys: Vec<&'static str>,
+    pub rules: Vec<RuleCapability>,
+}
+
+/// Regra que o validador pode disparar e o código estável dela.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleCapability {
+    pub rule: String,
+    pub code: String,
+}
+
+/// Validadores na ordem de despacho.
+pub struct ValidatorRegistry {
+    validators: Vec<Box<dyn Validator>>,
+}
+
+impl ValidatorRegistry {
+    /// Registro com os validadores embutidos (ordem de `VALIDATOR_ROUTES`).
+    pub fn builtin() -> Self {
+        Self {
+            validators: vec![
+                Box::new(Pkcs7Validator),
+                Box::new(BinaryValidator),
+                Box::new(SvgValidator),
+                Box::new(ImageValidator),
+                Box::new(PdfValidator),
+                Box::new(ArchiveValidator),
+                Box::new(OfficeValidator),
+                Box::new(SevenZipValidator),
+                Box::new(RarValidator),
+                Box::new(DiskImageValidator),
+                Box::new(OleValidator),
+                Box::new(FilenameValidator),
+                Box::new(GenericValidator),
+            ],
+        }
+    }
+
+    /// Registro compartilhado com os validadores embutidos.
+    pub fn shared_builtin() -> &'static Self {
+        static BUILTIN: OnceLock<ValidatorRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(Self::builtin)
+    }
+
+    /// Acrescenta um validador antes do fallback `generic`: validadores principais
+    /// cobrem MIMEs que os embutidos não tratam; para substituir um embutido,
+    /// remova-o com [`ValidatorRegistry::remove`] ou desabilite-o na política.
+    pub fn register(&mut self, validator: impl Validator + 'static) {
+        let position = self
+            .validators
+            .iter()
+            .position(|existing| existing.name() == GenericValidator.name())
+            .unwrap_or(self.validators.len());
+        self.validators.insert(position, Box::new(validator));
+    }
+
+    /// Remove o validador com o nome informado; devolve `true` se existia.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.validators.len();
+        self.validators.retain(|validator| validator.name() != name);
+        self.validators.len() != before
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.validators
+            .iter()
+            .map(|validator| validator.name())
+            .collect()
+    }
+
+    /// Capacidades dos validadores na ordem de despacho. `error:fallback` só
+    /// aparece em validadores principais diferentes do `generic`.
+    pub fn capabilities(&self) -> Vec<ValidatorCapability> {
+        self.validators
+            .iter()
+            .map(|validator| {
+                let fallback = validator.supplements().is_none()
+                    && validator.name() != GenericValidator.name();
+                let rules = rules::validator_rules(validator.name())
+                    .into_iter()
+                    .filter(|rule| fallback || !rule.ends_with(":error:fallback"))
+                    .map(|rule| RuleCapability {
+                        code: rules::code_for(&rule),
+                        rule,
+                    })
+                    .collect();
+                ValidatorCapability {
+                    name: validator.name(),
+                    version: validator.version(),
+                    supplements: validator.supplements(),
+                    mime_patterns: validator
+                        .mime_patterns()
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect(),
+                    policy_keys: validator.policy_keys().to_vec(),
+                    rules,
+                }
+            })
+            .collect()
+    }
+
+    /// Confere os requisitos de versão da política (`require`); falha listando
+    /// os validadores ausentes ou antigos demais para as garantias exigidas.
+    pub fn check_requirements(&self, require: &BTreeMap<String, String>) -> Result<()> {
+        let mut failures = Vec::new();
+        for (name, raw) in require {
+            let requirement = VersionRequirement::parse(raw)?;
+            match self
+                .validators
+                .iter()
+                .find(|validator| validator.name() == name)
+            {
+                None => failures.push(format!("{name} ({raw}): validador não registrado")),
+                Some(validator) if !requirement.matches(validator.version()) => {
+                    failures.push(format!(
+                        "{name} ({raw}): este binário traz a versão {}",
+                        validator.version()
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+        if !failures.is_empty() {
+            bail!(
+                "a política exige validadores indisponíveis: {}",
+                failures.join("; ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Executa o primeiro validador principal aplicável e os complementares dele,
+    /// respeitando `validators.enabled`/`validators.disabled` da política. Os
+    /// validadores desligados que teriam rodado entram no fim como `skipped`.
+    ///
+    /// Se o principal falhar (exceto por timeout), `routing.on_validator_error`
+    /// decide: `fallback` executa também o `generic` logo após ele, `deny`
+    /// converte a falha em violação e `error` (padrão) a mantém.
+    pub fn evaluate(&self, ctx: &ValidationContext<'_>) -> Vec<ValidatorOutcome> {
+        let mut outcomes = self.run_selected(ctx);
+        outcomes.extend(self.skipped(ctx).into_iter().map(ValidatorOutcome::skipped));
+        outcomes
+    }
+
+    fn run_selected(&self, ctx: &ValidationContext<'_>) -> Vec<ValidatorOutcome> {
+        let mut outcomes: Vec<ValidatorOutcome> = self
+            .select(ctx)
+            .into_iter()
+            .map(|validator| run(validator, ctx))
+            .collect();
+        let action = ctx
+            .policy
+            .and_then(|policy| policy.routing.on_validator_error)
+            .unwrap_or(ValidatorErrorAction::Error);
+        let Some(primary) = outcomes.first_mut() else {
+            return outcomes;
+        };
+        if !matches!(primary.status, ValidatorStatus::Error)
+            || primary.is_timeout()
+            || primary.name == GenericValidator.name()
+        {
+            return outcomes;
+        }
+        match action {
+            ValidatorErrorAction::Error => {}
+            ValidatorErrorAction::Deny => primary.status = ValidatorStatus::Deny,
+            ValidatorErrorAction::Fallback => {
+                let generic = self.validators.iter().find(|validator| {
+                    validator.name() == GenericValidator.name()
+                        && match ctx.policy {
+                            Some(policy) => policy.validators.is_enabled(validator.name()),
+                            None => true,
+                        }
+                });
+                if let Some(generic) = generic {
+                    primary.details["fallback"] = json!(generic.name());
+                    outcomes.insert(1, run(generic.as_ref(), ctx));
+                }
+            }
+        }
+        outcomes
+    }
+
+    /// Nomes dos validadores que [`ValidatorRegistry::evaluate`] executaria, sem executá-los.
+    pub fn selected(&self, ctx: &ValidationContext<'_>) -> Vec<&'static str> {
+        self.select(ctx)
+            .into_iter()
+            .map(|validator| validator.name())
+            .collect()
+    }
+
+    /// Validadores desligados pela política que rodariam sem ela: os que o
+    /// despacho escolheria ignorando a seleção e os complementares do principal
+    /// efetivamente escolhido.
+    fn skipped(&self, ctx: &ValidationContext<'_>) -> Vec<&'static str> {
+        let Some(selection) = ctx.policy.map(|policy| &policy.validators) else {
+            return Vec::new();
+        };
+        let is_disabled = |validator: &&dyn Validator| !selection.is_enabled(validator.name());
+        let mut skipped: Vec<&'static str> = self
+            .select_with(ctx, None)
+            .into_iter()
+            .filter(is_disabled)
+            .map(|validator| validator.name())
+            .collect();
+        if let Some(primary) = self.select(ctx).first() {
+            for validator in self.validators.iter().map(Box::as_ref) {
+                if is_disabled(&validator)
+                    && !skipped.contains(&validator.name())
+                    && validator
+                        .supplements()
+                        .is_some_and(|target| target == primary.name() || target == ANY_VALIDATOR)
+                    && validator.applies_to(ctx)
+                {
+                    skipped.push(validator.name());
+                }
+            }
+        }
+        skipped
+    }
+
+    fn select(&self, ctx: &ValidationContext<'_>) -> Vec<&dyn Validator> {
+        self.select_with(ctx, ctx.policy.map(|policy| &policy.validators))
+    }
+
+    fn select_with(
+        &self,
+        ctx: &ValidationContext<'_>,
+        selection: Option<&ValidatorsSection>,
+    ) -> Vec<&dyn Validator> {
+        let enabled = || {
+            self.validators
+                .iter()
+                .map(Box::as_ref)
+                .filter(move |validator| match selection {
+                    Some(selection) => selection.is_enabled(validator.name()),
+                    None => true,
+                })
+        };
+        let Some(primary) = enabled()
+            .find(|validator| validator.supplements().is_none() && validator.applies_to(ctx))
+        else {
+            return Vec::new();
+        };
+
+        let mut selected = vec![primary];
+        selected.extend(enabled().filter(|validator| {
+            validator
+                .supplements()
+                .is_some_and(|target| target == primary.name() || target == ANY_VALIDATOR)
+                && validator.applies_to(ctx)
+        }));
+        selected
+    }
+}
+
+/// Validador em execução em alguma thread (diagnóstico de travamentos).
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveValidator {
+    pub thread: String,
+    pub validator: &'static str,
+    pub mime: String,
+    pub size_bytes: usize,
+    pub elapsed_ms: u64,
+}
+
+struct Running {
+    thread: ThreadId,
+    thread_name: String,
+    validator: &'static str,
+    mime: String,
+    size_bytes: usize,
+    started: Instant,
+}
+
+static RUNNING: Mutex<Vec<Running>> = Mutex::new(Vec::new());
+
+/// Remove o registro de [`RUNNING`] quando o validador termina (ou entra em pânico).
+struct RunningGuard(ThreadId);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        let mut running = RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(index) = running.iter().rposition(|entry| entry.thread == self.0) {
+            running.remove(index);
+        }
+    }
+}
+
+/// Validadores em execução agora, do mais antigo ao mais recente.
+pub fn active_validators() -> Vec<ActiveValidator> {
+    let running = RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+    running
+        .iter()
+        .map(|entry| ActiveValidator {
+            thread: entry.thread_name.clone(),
+            validator: entry.validator,
+            mime: entry.mime.clone(),
+            size_bytes: entry.size_bytes,
+            elapsed_ms: entry.started.elapsed().as_millis() as u64,
+        })
+        .collect()
+}
+
+fn track(validator: &dyn Validator, ctx: &ValidationContext<'_>) -> RunningGuard {
+    let current = thread::current();
+    let thread_name = current
+        .name()
+        .map_or_else(|| format!("{:?}", current.id()), str::to_string);
+    RUNNING
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(Running {
+            thread: current.id(),
+            thread_name,
+            validator: validator.name(),
+            mime: ctx.mime.to_string(),
+            size_bytes: ctx.data.len(),
+            started: Instant::now(),
+        });
+    RunningGuard(current.id())
+}
+
+/// Executa o validador registrando em `details.decompressed_bytes` o volume
+/// descomprimido por ele, quando houver.
+fn run(validator: &dyn Validator, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+    let _running = track(validator, ctx);
+    #[cfg(feature = "tracing")]
+    let span = tracing::debug_span!(
+        "validator",
+        validator = validator.name(),
+        mime = ctx.mime,
+        size_bytes = ctx.data.len(),
+        status = tracing::field::Empty,
+    );
+    #[cfg(feature = "tracing")]
+    let _entered = span.enter();
+    let before = decompress::decompressed_bytes();
+    let mut outcome = validator.validate(ctx);
+    outcome.version = Some(validator.version());
+    #[cfg(feature = "tracing")]
+    span.record("status", outcome.status.as_str());
+    match decompress::decompressed_bytes() - before {
+        0 => outcome,
+        used => outcome.with_details(json!({ "decompressed_bytes": used })),
+    }
+}
+
+impl Default for ValidatorRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl fmt::Debug for ValidatorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// Envelopes assinados vêm antes do MIME: o sniff costuma enxergar o conteúdo interno.
+struct Pkcs7Validator;
+
+impl Validator for Pkcs7Validator {
+    fn name(&self) -> &'static str {
+        "pkcs7"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        detect_signed(ctx.data).is_some()
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        match detect_signed(ctx.data) {
+            Some(envelope) => validate_pkcs7(&envelope, ctx.policy),
+            None => ValidatorOutcome::error(self.name(), "envelope assinado não reconhecido"),
+        }
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &["pkcs7.forbid_enveloped"]
+    }
+}
+
+/// Executáveis são reconhecidos pelo cabeçalho, qualquer que seja o MIME/extensão.
+struct BinaryValidator;
+
+impl Validator for BinaryValidator {
+    fn name(&self) -> &'static str {
+        "binary"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        detect_binary(ctx.data).is_some()
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        match detect_binary(ctx.data) {
+            Some(binary) => validate_binary(&binary, ctx.policy),
+            None => ValidatorOutcome::error(self.name(), "executável não reconhecido"),
+        }
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &["binary.deny_all", "binary.allow_signed_only"]
+    }
+}
+
+struct SvgValidator;
+
+impl Validator for SvgValidator {
+    fn name(&self) -> &'static str {
+        "svg"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        // Sem o prólogo `<?xml` o SVG costuma ser detectado como texto; a raiz
+        // `<svg>` ou a extensão declarada bastam para inspecioná-lo.
+        ctx.mime == "image/svg+xml"
+            || has_svg_root(ctx.data)
+            || ctx.file_name.is_some_and(|name| {
+                Path::new(name)
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+            })
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        validate_svg(ctx.data, ctx.policy)
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &[
+            "svg.forbid_scripts",
+            "svg.forbid_event_handlers",
+            "svg.forbid_foreign_object",
+            "svg.forbid_external_references",
+            "svg.forbid_data_uris",
+        ]
+    }
+}
+
+struct ImageValidator;
+
+impl Validator for ImageValidator {
+    fn name(&self) -> &'static str {
+        "image"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        ctx.mime.starts_with("image/")
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        validate_image(ctx.mime, ctx.data, ctx.policy, ctx.deadline)
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &[
+            "image.max_dimensions",
+            "image.min_dimensions",
+            "image.allowed_aspect_ratios",
+            "image.aspect_ratio_tolerance",
+            "image.max_frames",
+            "image.header_only",
+            "image.max_pixels",
+            "image.strip_metadata",
+            "image.exif_mismatch",
+            "image.deny_gps_metadata",
+            "image.warn_on_metadata",
+        ]
+    }
+}
+
+struct PdfValidator;
+
+impl Validator for PdfValidator {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        ctx.mime == "application/pdf"
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        validate_pdf(ctx.data, ctx.policy, ctx.deadline)
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &[
+            "pdf.allow_javascript",
+            "pdf.max_pages",
+            "pdf.forbid_embedded_files",
+            "pdf.forbid_open_action",
+            "pdf.forbid_launch",
+            "pdf.forbid_external_uris",
+            "pdf.forbid_acroform",
+            "pdf.forbid_encrypted",
+            "pdf.max_objects",
+            "pdf.max_embedded_fonts",
+            "pdf.max_stream_bytes",
+        ]
+    }
+}
+
+fn is_zip_mime(mime: &str) -> bool {
+    matches!(
+        mime,
+        "application/zip"
+            | "application/x-zip-compressed"
+            | "application/x-zip"
+            | "multipart/x-zip"
+    ) || is_ooxml_mime(mime)
+}
+
+fn is_ooxml_mime(mime: &str) -> bool {
+    mime.starts_with("application/vnd.openxmlformats-officedocument.")
+}
+
+struct ArchiveValidator;
+
+impl Validator for ArchiveValidator {
+    fn name(&self) -> &'static str {
+        "archive"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        is_zip_mime(ctx.mime)
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        validate_archive(ctx.mime, ctx.data, ctx.policy, ctx.deadline)
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &[
+            "archive.zip_max_depth",
+            "archive.zip_max_ratio",
+            "archive.forbid_symlinks",
+            "archive.forbid_path_traversal",
+            "archive.forbid_encrypted_entries",
+            "archive.required_entries",
+            "archive.allowed_entry_globs",
+            "archive.metadata_checks.invalid_dates",
+            "archive.metadata_checks.max_future_days",
+            "archive.metadata_checks.min_year",
+            "archive.metadata_checks.header_mismatch",
+            "archive.max_entries",
+            "archive.max_total_uncompressed_mb",
+            "archive.verify_crc",
+            "archive.forbid_overlapping_entries",
+            "archive.index_entries",
+            "archive.index_max_entries",
+        ]
+    }
+}
+
+/// Complementa o `archive` em documentos OOXML (macros, DDE, relações externas).
+struct OfficeValidator;
+
+impl Validator for OfficeValidator {
+    fn name(&self) -> &'static str {
+        "office"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        is_zip_mime(ctx.mime) && is_ooxml(ctx.data)
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        if ctx.deadline.is_expired() {
+            return ValidatorOutcome::timeout(self.name()).with_partial_details(json!({}));
+        }
+        validate_office(ctx.data, ctx.policy)
+    }
+
+    fn supplements(&self) -> Option<&'static str> {
+        Some("archive")
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &[
+            "office.allow_macros",
+            "office.forbid_external_relationships",
+            "office.forbid_dde",
+            "office.forbid_embedded_objects",
+            "office.max_part_bytes",
+        ]
+    }
+}
+
+/// Chaves da seção `archive` aplicadas às listagens de 7z e RAR.
+const LISTING_POLICY_KEYS: &[&str] = &[
+    "archive.zip_max_depth",
+    "archive.zip_max_ratio",
+    "archive.forbid_symlinks",
+    "archive.forbid_path_traversal",
+    "archive.forbid_encrypted_entries",
+    "archive.required_entries",
+    "archive.allowed_entry_globs",
+    "archive.max_entries",
+    "archive.max_total_uncompressed_mb",
+    "archive.forbid_overlapping_entries",
+    "archive.index_entries",
+    "archive.index_max_entries",
+];
+
+struct SevenZipValidator;
+
+impl Validator for SevenZipValidator {
+    fn name(&self) -> &'static str {
+        "sevenzip"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        is_sevenzip(ctx.data)
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        validate_sevenzip(ctx.data, ctx.policy, ctx.deadline)
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        LISTING_POLICY_KEYS
+    }
+}
+
+struct RarValidator;
+
+impl Validator for RarValidator {
+    fn name(&self) -> &'static str {
+        "rar"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        is_rar(ctx.data)
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        validate_rar(ctx.data, ctx.policy, ctx.deadline)
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        LISTING_POLICY_KEYS
+    }
+}
+
+struct DiskImageValidator;
+
+impl Validator for DiskImageValidator {
+    fn name(&self) -> &'static str {
+        "disk_image"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        detect_disk_image(ctx.data).is_some()
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        match detect_disk_image(ctx.data) {
+            Some(kind) => validate_disk_image(kind, ctx.data, ctx.policy, ctx.deadline),
+            None => ValidatorOutcome::error(self.name(), "imagem de disco não reconhecida"),
+        }
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &["archive.enumerate_iso", "archive.zip_max_depth"]
+    }
+}
+
+struct OleValidator;
+
+impl Validator for OleValidator {
+    fn name(&self) -> &'static str {
+        "ole"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        is_ole(ctx.data)
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        validate_ole(ctx.data, ctx.policy)
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &["office.allow_macros", "office.forbid_embedded_objects"]
+    }
+}
+
+/// Fallback para qualquer conteúdo sem validador específico.
+struct GenericValidator;
+
+impl Validator for GenericValidator {
+    fn name(&self) -> &'static str {
+        "generic"
+    }
+
+    fn applies_to(&self, _ctx: &ValidationContext<'_>) -> bool {
+        true
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        validate_generic(ctx.mime, ctx.data, ctx.policy)
+    }
+}
+
+/// Complementa qualquer validador quando o nome do arquivo é conhecido.
+struct FilenameValidator;
+
+impl Validator for FilenameValidator {
+    fn name(&self) -> &'static str {
+        "filename"
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        ctx.file_name.is_some()
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        validate_filename(ctx.file_name.unwrap_or_default(), ctx.policy)
+    }
+
+    fn supplements(&self) -> Option<&'static str> {
+        Some(ANY_VALIDATOR)
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &["filename.max_length", "filename.allow", "filename.deny"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{RoutingPolicySection, ValidatorsSection};
+
+    struct CsvHeaderValidator;
+
+    impl Validator for CsvHeaderValidator {
+        fn name(&self) -> &'static str {
+            "csv_header"
+        }
+
+        fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+            ctx.mime.starts_with("text/")
+        }
+
+        fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+            if ctx.data.starts_with(b"id,") {
+                ValidatorOutcome::pass(self.name())
+            } else {
+                ValidatorOutcome::deny(self.name(), "cabeçalho CSV ausente")
+            }
+        }
+    }
+
+    fn evaluate(registry: &ValidatorRegistry, policy: Option<&ResolvedPolicy>) -> Vec<String> {
+        let ctx = ValidationContext {
+            mime: "text/plain",
+            data: b"nome;valor\n",
+            policy,
+            deadline: &Deadline::none(),
+            file_name: None,
+        };
+        registry
+            .evaluate(&ctx)
+            .iter()
+            .map(|outcome| format!("{}:{}", outcome.name, outcome.status.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn custom_validators_run_before_fallback_and_policy_can_disable_them() {
+        let mut registry = ValidatorRegistry::builtin();
+        assert_eq!(evaluate(&registry, None), vec!["generic:pass"]);
+
+        registry.register(CsvHeaderValidator);
+        assert_eq!(registry.names().last(), Some(&"generic"));
+        assert_eq!(evaluate(&registry, None), vec!["csv_header:deny"]);
+
+        let policy = ResolvedPolicy {
+            validators: ValidatorsSection {
+                disabled: vec!["csv_header".into()],
+                ..ValidatorsSection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        assert_eq!(
+            evaluate(&registry, Some(&policy)),
+            vec!["generic:pass", "csv_header:skipped"]
+        );
+        let everything = ResolvedPolicy {
+            validators: ValidatorsSection {
+                disabled: vec!["*".into()],
+                ..ValidatorsSection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        assert_eq!(
+            evaluate(&registry, Some(&everything)),
+            vec!["csv_header:skipped"]
+        );
+
+        assert!(registry.remove("csv_header"));
+        assert!(!registry.remove("csv_header"));
+        let outcome = &ValidatorRegistry::shared_builtin().evaluate(&ValidationContext {
+            mime: "application/pdf",
+            data: b"%PDF-1.4\n%%EOF\n",
+            policy: None,
+            deadline: &Deadline::none(),
+            file_name: None,
+        })[0];
+        assert_eq!(outcome.name, "pdf");
+        assert!(!matches!(outcome.status, ValidatorStatus::Error));
+    }
+
+    #[test]
+    fn svg_without_prolog_is_routed_by_root_element_or_name() {
+        let route = |mime, data: &[u8], file_name| {
+            let outcome = &ValidatorRegistry::shared_builtin().evaluate(&ValidationContext {
+                mime,
+                data,
+                policy: None,
+                deadline: &Deadline::none(),
+                file_name,
+            })[0];
+            format!("{}:{}", outcome.name, outcome.status.as_str())
+        };
+        let script: &[u8] =
+            b"<svg xmlns=\"http://www.w3.org/2000/svg\"><script>alert(1)</script></svg>";
+        assert_eq!(route("text/plain", script, None), "svg:deny");
+        let doctype: &[u8] = b"\xEF\xBB\xBF <!-- logo -->\n<!DOCTYPE svg [ <!ENTITY a \"b\"> ]>\n\
+            <svg onload=\"alert(1)\"/>";
+        assert_eq!(route("text/html", doctype, None), "svg:deny");
+        assert_eq!(
+            route("text/plain", b"<!DOCTYPE svg><svg onload=\"x()\"/>", None),
+            "svg:deny"
+        );
+        assert_eq!(
+            route("text/plain", b"desenho", Some("logo.SVG")),
+            "svg:deny"
+        );
+        assert_eq!(
+            route("text/plain", b"<html><svg/></html>", None),
+            "generic:pass"
+        );
+    }
+
+    struct BrokenValidator;
+
+    impl Validator for BrokenValidator {
+        fn name(&self) -> &'static str {
+            "broken"
+        }
+
+        fn applies_to(&self, _ctx: &ValidationContext<'_>) -> bool {
+            true
+        }
+
+        fn validate(&self, _ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+            ValidatorOutcome::error(self.name(), "estrutura ilegível")
+        }
+    }
+
+    #[test]
+    fn validator_errors_follow_routing_policy() {
+        let mut registry = ValidatorRegistry::builtin();
+        registry.register(BrokenValidator);
+        let routed = |action| ResolvedPolicy {
+            routing: RoutingPolicySection {
+                on_validator_error: Some(action),
+            },
+            ..ResolvedPolicy::default()
+        };
+
+        assert_eq!(evaluate(&registry, None), vec!["broken:error"]);
+        assert_eq!(
+            evaluate(&registry, Some(&routed(ValidatorErrorAction::Deny))),
+            vec!["broken:deny"]
+        );
+        let fallback = routed(ValidatorErrorAction::Fallback);
+        assert_eq!(
+            evaluate(&registry, Some(&fallback)),
+            vec!["broken:error", "generic:pass"]
+        );
+
+        let engine = crate::policy::PolicyEngine::new(crate::config::PolicyConfig::default());
+        let report = crate::report::FileReport::new(
+            std::path::Path::new("a.txt"),
+            5,
+            "abc".into(),
+            crate::report::SniffReport::new("text/plain".into(), None, None),
+        );
+        let ctx = ValidationContext {
+            mime: "text/plain",
+            data: b"texto",
+            policy: Some(&fallback),
+            deadline: &Deadline::none(),
+            file_name: None,
+        };
+        let outcome = engine.decide(&report, &registry.evaluate(&ctx), Some(&fallback));
+        assert_eq!(outcome.decision, crate::policy::Decision::Warn);
+        assert_eq!(
+            outcome.primary_rule.as_deref(),
+            Some("validator:broken:error:fallback")
+        );
+    }
+
+    #[test]
+    fn capabilities_describe_builtin_validators() {
+        let capabilities = ValidatorRegistry::builtin().capabilities();
+        let names: Vec<_> = capabilities
+            .iter()
+            .map(|capability| capability.name)
+            .collect();
+        assert_eq!(names, ValidatorRegistry::builtin().names());
+
+        let image = capabilities.iter().find(|c| c.name == "image").unwrap();
+        assert_eq!(image.mime_patterns, vec!["image/*"]);
+        assert!(image
+            .rules
+            .iter()
+            .any(|rule| rule.rule == "validator:image:deny" && rule.code == "GU-IMAGE-DENY"));
+
+        let office = capabilities.iter().find(|c| c.name == "office").unwrap();
+        assert_eq!(office.supplements, Some("archive"));
+        assert!(office
+            .rules
+            .iter()
+            .all(|rule| rule.code != "GU-OFFICE-FALLBACK"));
+
+        // Chaves declaradas precisam existir na política (evita divergência com config.rs).
+        let policy = serde_json::to_value(crate::config::PolicyConfig::default()).unwrap();
+        for key in capabilities.iter().flat_map(|c| c.policy_keys.iter()) {
+            let pointer = format!("/{}", key.replace('.', "/"));
+            assert!(
+                policy.pointer(&pointer).is_some(),
+                "chave desconhecida: {key}"
+            );
+        }
+    }
+
+    #[test]
+    fn policy_version_pins_fail_fast_and_outcomes_record_versions() {
+        let registry = ValidatorRegistry::builtin();
+        let pins = |yaml: &str| {
+            crate::config::PolicyConfig::from_yaml_str(yaml)
+                .expect("política")
+                .require
+        };
+        registry
+            .check_requirements(&pins("require:\n  pdf: \">=1\"\n  image: \"1\"\n"))
+            .expect("versões atendidas");
+
+        let err = registry
+            .check_requirements(&pins("require:\n  pdf: \">=2\"\n  yara: \">=1\"\n"))
+            .expect_err("pdf antigo e yara ausente");
+        let message = err.to_string();
+        assert!(
+            message.contains("pdf (>=2): este binário traz a versão 1.0.0"),
+            "{message}"
+        );
+        assert!(
+            message.contains("yara (>=1): validador não registrado"),
+            "{message}"
+        );
+        assert!(
+            crate::config::PolicyConfig::from_yaml_str("require:\n  pdf: \">=dois\"\n").is_err()
+        );
+
+        let ctx = ValidationContext {
+            mime: "text/plain",
+            data: b"texto",
+            policy: None,
+            deadline: &Deadline::none(),
+            file_name: None,
+        };
+        let outcomes = registry.evaluate(&ctx);
+        assert_eq!(outcomes[0].version, Some(ValidatorVersion::INITIAL));
+        let entry = crate::report::ValidatorEntry::from(&outcomes[0]);
+        assert_eq!(entry.version.as_deref(), Some("1.0.0"));
+    }
+}
@@ -0,0 +1,789 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Listagem de arquivos 7-Zip pelo cabeçalho, sem descomprimir o conteúdo.
+//!
+//! Cabeçalhos comprimidos (`EncodedHeader`, padrão do 7-Zip) são decodificados
+//! com LZMA/LZMA2 dentro de um orçamento; cabeçalhos cifrados com AES não podem
+//! ser listados. As entradas seguem para [`validate_listing`].
+
+use super::archive::is_symlink;
+use super::listing::{validate_listing, ListedEntry, Listing, ListingError};
+use super::ValidatorOutcome;
+use crate::decompress::{decompress_lzma, decompress_lzma2, DecompressError, DecompressLimits};
+use crate::limits::Deadline;
+use crate::policy::ResolvedPolicy;
+
+const SIGNATURE: &[u8] = b"7z\xBC\xAF\x27\x1C";
+/// Máximo de bytes de um cabeçalho comprimido depois de descomprimido.
+const MAX_HEADER_BYTES: u64 = 64 * 1024 * 1024;
+/// Cabeçalhos codificados aninhados aceitos.
+const MAX_HEADER_LAYERS: usize = 4;
+/// Máximo de itens (arquivos, folders, coders, streams) de uma contagem do cabeçalho.
+const MAX_ITEMS: u64 = 1 << 20;
+
+const ID_END: u8 = 0x00;
+const ID_HEADER: u8 = 0x01;
+const ID_ARCHIVE_PROPERTIES: u8 = 0x02;
+const ID_ADDITIONAL_STREAMS: u8 = 0x03;
+const ID_MAIN_STREAMS: u8 = 0x04;
+const ID_FILES_INFO: u8 = 0x05;
+const ID_PACK_INFO: u8 = 0x06;
+const ID_UNPACK_INFO: u8 = 0x07;
+const ID_SUBSTREAMS_INFO: u8 = 0x08;
+const ID_SIZE: u8 = 0x09;
+const ID_CRC: u8 = 0x0A;
+const ID_FOLDER: u8 = 0x0B;
+const ID_CODERS_UNPACK_SIZE: u8 = 0x0C;
+const ID_NUM_UNPACK_STREAM: u8 = 0x0D;
+const ID_EMPTY_STREAM: u8 = 0x0E;
+const ID_EMPTY_FILE: u8 = 0x0F;
+const ID_NAMES: u8 = 0x11;
+const ID_ATTRIBUTES: u8 = 0x15;
+const ID_ENCODED_HEADER: u8 = 0x17;
+
+const CODER_COPY: &[u8] = &[0x00];
+const CODER_LZMA: &[u8] = &[0x03, 0x01, 0x01];
+const CODER_LZMA2: &[u8] = &[0x21];
+const CODER_AES: &[u8] = &[0x06, 0xF1, 0x07, 0x01];
+
+const ATTRIBUTE_DIRECTORY: u32 = 0x10;
+const ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+/// Os 16 bits altos dos atributos trazem o modo Unix.
+const ATTRIBUTE_UNIX_EXTENSION: u32 = 0x8000;
+
+/// Indica se o conteúdo começa com a assinatura do 7z.
+pub fn is_sevenzip(data: &[u8]) -> bool {
+    data.starts_with(SIGNATURE)
+}
+
+pub fn validate_sevenzip(
+    data: &[u8],
+    policy: Option<&ResolvedPolicy>,
+    deadline: &Deadline,
+) -> ValidatorOutcome {
+    validate_listing("sevenzip", "7z", list(data, deadline), policy, deadline)
+}
+
+fn invalid(message: impl Into<String>) -> ListingError {
+    ListingError::Invalid(message.into())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn byte(&mut self) -> Result<u8, ListingError> {
+        let byte = *self
+            .data
+            .get(self.pos)
+            .ok_or_else(|| invalid("cabeçalho truncado"))?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self, len: u64) -> Result<&'a [u8], ListingError> {
+        let len = usize::try_from(len).map_err(|_| invalid("cabeçalho truncado"))?;
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| invalid("cabeçalho truncado"))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, ListingError> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Número de tamanho variável: os bits 1 iniciais do primeiro byte indicam
+    /// quantos bytes seguem.
+    fn number(&mut self) -> Result<u64, ListingError> {
+        let first = self.byte()?;
+        let mut mask = 0x80u8;
+        let mut value = 0u64;
+        for index in 0..8 {
+            if first & mask == 0 {
+                let high = u64::from(first & mask.wrapping_sub(1));
+                return Ok(value | (high << (8 * index)));
+            }
+            value |= u64::from(self.byte()?) << (8 * index);
+            mask >>= 1;
+        }
+        Ok(value)
+    }
+
+    /// Contagem usada para alocar vetores; cada item ocupa ao menos um bit do
+    /// cabeçalho e o total fica limitado a [`MAX_ITEMS`].
+    fn count(&mut self) -> Result<usize, ListingError> {
+        let value = self.number()?;
+        if value > MAX_ITEMS || value > (self.data.len() as u64).saturating_mul(8) {
+            return Err(invalid("contagem inconsistente no cabeçalho"));
+        }
+        Ok(value as usize)
+    }
+
+    fn bits(&mut self, count: usize) -> Result<Vec<bool>, ListingError> {
+        let bytes = self.bytes(count.div_ceil(8) as u64)?;
+        Ok((0..count)
+            .map(|index| bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+            .collect())
+    }
+
+    /// Vetor precedido do byte "todos definidos".
+    fn defined(&mut self, count: usize) -> Result<Vec<bool>, ListingError> {
+        if self.byte()? != 0 {
+            return Ok(vec![true; count]);
+        }
+        self.bits(count)
+    }
+
+    fn skip_digests(&mut self, count: usize) -> Result<Vec<bool>, ListingError> {
+        let defined = self.defined(count)?;
+        for _ in defined.iter().filter(|defined| **defined) {
+            self.u32()?;
+        }
+        Ok(defined)
+    }
+
+    fn expect(&mut self, id: u8) -> Result<(), ListingError> {
+        match self.byte()? {
+            found if found == id => Ok(()),
+            found => Err(invalid(format!(
+                "propriedade {found:#04x} inesperada (esperada {id:#04x})"
+            ))),
+        }
+    }
+}
+
+struct Coder {
+    id: Vec<u8>,
+    properties: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Folder {
+    coders: Vec<Coder>,
+    out_streams: usize,
+    unpack_sizes: Vec<u64>,
+    bound_outputs: Vec<u64>,
+    packed_streams: usize,
+    crc_defined: bool,
+}
+
+impl Folder {
+    /// Tamanho da saída final: o stream de saída que não alimenta outro coder.
+    fn unpack_size(&self, deadline: &Deadline) -> Result<u64, ListingError> {
+        for index in (0..self.out_streams).rev() {
+            if deadline.is_expired() {
+                return Err(ListingError::Timeout);
+            }
+            if !self.bound_outputs.contains(&(index as u64)) {
+                return Ok(self.unpack_sizes.get(index).copied().unwrap_or(0));
+            }
+        }
+        Ok(0)
+    }
+
+    fn is_encrypted(&self) -> bool {
+        self.coders.iter().any(|coder| coder.id == CODER_AES)
+    }
+}
+
+#[derive(Default)]
+struct StreamsInfo {
+    pack_pos: u64,
+    pack_sizes: Vec<u64>,
+    folders: Vec<Folder>,
+    /// Tamanho de cada stream descomprimido, folder a folder.
+    substreams: Vec<(usize, u64)>,
+}
+
+fn read_streams_info(
+    reader: &mut Reader<'_>,
+    deadline: &Deadline,
+) -> Result<StreamsInfo, ListingError> {
+    let mut info = StreamsInfo::default();
+    let mut substream_counts: Option<Vec<usize>> = None;
+    let mut substream_sizes: Option<Vec<(usize, u64)>> = None;
+    loop {
+        match reader.byte()? {
+            ID_END => break,
+            ID_PACK_INFO => {
+                info.pack_pos = reader.number()?;
+                let count = reader.count()?;
+                loop {
+                    match reader.byte()? {
+                        ID_END => break,
+                        ID_SIZE => {
+                            info.pack_sizes = (0..count)
+                                .map(|_| reader.number())
+                                .collect::<Result<_, _>>()?
+                        }
+                        ID_CRC => {
+                            reader.skip_digests(count)?;
+                        }
+                        other => return Err(invalid(format!("PackInfo com {other:#04x}"))),
+                    }
+                }
+            }
+            ID_UNPACK_INFO => info.folders = read_folders(reader, deadline)?,
+            ID_SUBSTREAMS_INFO => {
+                let (counts, sizes) = read_substreams(reader, &info.folders, deadline)?;
+                substream_counts = Some(counts);
+                substream_sizes = sizes;
+            }
+            other => return Err(invalid(format!("StreamsInfo com {other:#04x}"))),
+        }
+    }
+    info.substreams = match substream_sizes {
+        Some(sizes) => sizes,
+        None => {
+            let counts = substream_counts.unwrap_or_else(|| vec![1; info.folders.len()]);
+            let mut sizes = Vec::new();
+            for (index, (folder, count)) in info.folders.iter().zip(counts).enumerate() {
+                match count {
+                    0 => {}
+                    1 => sizes.push((index, folder.unpack_size(deadline)?)),
+                    _ => return Err(invalid("SubStreamsInfo sem tamanhos")),
+                }
+            }
+            sizes
+        }
+    };
+    Ok(info)
+}
+
+fn read_folders(reader: &mut Reader<'_>, deadline: &Deadline) -> Result<Vec<Folder>, ListingError> {
+    reader.expect(ID_FOLDER)?;
+    let count = reader.count()?;
+    if reader.byte()? != 0 {
+        return Err(ListingError::Unsupported(
+            "7z com folders externos não é suportado".to_string(),
+        ));
+    }
+    let mut folders = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        let mut folder = Folder::default();
+        let mut in_streams = 0usize;
+        for _ in 0..reader.count()? {
+            let flags = reader.byte()?;
+            if flags & 0x80 != 0 {
+                return Err(ListingError::Unsupported(
+                    "7z com métodos alternativos não é suportado".to_string(),
+                ));
+            }
+            let id = reader.bytes(u64::from(flags & 0x0F))?.to_vec();
+            let (inputs, outputs) = if flags & 0x10 != 0 {
+                (reader.count()?, reader.count()?)
+            } else {
+                (1, 1)
+            };
+            let properties = if flags & 0x20 != 0 {
+                let len = reader.number()?;
+                reader.bytes(len)?.to_vec()
+            } else {
+                Vec::new()
+            };
+            in_streams += inputs;
+            folder.out_streams += outputs;
+            folder.coders.push(Coder { id, properties });
+        }
+        let bind_pairs = folder.out_streams.saturating_sub(1);
+        for _ in 0..bind_pairs {
+            if deadline.is_expired() {
+                return Err(ListingError::Timeout);
+            }
+            reader.number()?;
+            folder.bound_outputs.push(reader.number()?);
+        }
+        folder.packed_streams = in_streams
+            .checked_sub(bind_pairs)
+            .ok_or_else(|| invalid("folder sem stream comprimido"))?;
+        if folder.packed_streams > 1 {
+            for _ in 0..folder.packed_streams {
+                reader.number()?;
+            }
+        }
+        folders.push(folder);
+    }
+    reader.expect(ID_CODERS_UNPACK_SIZE)?;
+    for folder in &mut folders {
+        folder.unpack_sizes = (0..folder.out_streams)
+            .map(|_| reader.number())
+            .collect::<Result<_, _>>()?;
+    }
+    loop {
+        match reader.byte()? {
+            ID_END => break,
+            ID_CRC => {
+                let defined = reader.skip_digests(folders.len())?;
+                for (folder, defined) in folders.iter_mut().zip(defined) {
+                    folder.crc_defined = defined;
+                }
+            }
+            other => return Err(invalid(format!("UnpackInfo com {other:#04x}"))),
+        }
+    }
+    Ok(folders)
+}
+
+type Substreams = (Vec<usize>, Option<Vec<(usize, u64)>>);
+
+fn read_substreams(
+    reader: &mut Reader<'_>,
+    folders: &[Folder],
+    deadline: &Deadline,
+) -> Result<Substreams, ListingError> {
+    let mut counts = vec![1usize; folders.len()];
+    let mut sizes = None;
+    loop {
+        match reader.byte()? {
+            ID_END => break,
+            ID_NUM_UNPACK_STREAM => {
+                for count in &mut counts {
+                    *count = reader.count()?;
+                }
+            }
+            ID_SIZE => {
+                let mut list = Vec::new();
+                for (index, (folder, count)) in folders.iter().zip(&counts).enumerate() {
+                    if *count == 0 {
+                        continue;
+                    }
+                    let mut sum = 0u64;
+                    for _ in 1..*count {
+                        let size = reader.number()?;
+                        sum = sum.saturating_add(size);
+                        list.push((index, size));
+                    }
+                    let last = folder
+                        .unpack_size(deadline)?
+                        .checked_sub(sum)
+                        .ok_or_else(|| invalid("tamanhos de substream maiores que o folder"))?;
+                    list.push((index, last));
+                }
+                sizes = Some(list);
+            }
+            ID_CRC => {
+                let unknown = folders
+                    .iter()
+                    .zip(&counts)
+                    .map(|(folder, count)| match count {
+                        1 if folder.crc_defined => 0,
+                        count => *count,
+                    })
+                    .sum();
+                reader.skip_digests(unknown)?;
+            }
+            other => return Err(invalid(format!("SubStreamsInfo com {other:#04x}"))),
+        }
+    }
+    if sizes.is_none() && counts.iter().any(|count| *count > 1) {
+        // Sem ID_SIZE, só folders com um único stream são válidos.
+        return Err(invalid("SubStreamsInfo sem tamanhos"));
+    }
+    Ok((counts, sizes))
+}
+
+fn map_decompress(err: DecompressError) -> ListingError {
+    match err {
+        DecompressError::Timeout => ListingError::Timeout,
+        DecompressError::LimitExceeded { limit } => {
+            invalid(format!("cabeçalho descomprimido excede {limit} bytes"))
+        }
+        DecompressError::Unsupported(method) => {
+            ListingError::Unsupported(format!("cabeçalho 7z com método {method} não suportado"))
+        }
+        DecompressError::Corrupt(err) => invalid(format!("cabeçalho comprimido inválido: {err}")),
+    }
+}
+
+/// Decodifica um `EncodedHeader`; `Ok(None)` quando o cabeçalho é cifrado.
+fn decode_header(
+    data: &[u8],
+    reader: &mut Reader<'_>,
+    deadline: &Deadline,
+) -> Result<Option<Vec<u8>>, ListingError> {
+    let info = read_streams_info(reader, deadline)?;
+    let [folder] = info.folders.as_slice() else {
+        return Err(invalid("cabeçalho codificado com mais de um folder"));
+    };
+    if folder.is_encrypted() {
+        return Ok(None);
+    }
+    let [coder] = folder.coders.as_slice() else {
+        return Err(ListingError::Unsupported(
+            "cabeçalho 7z com cadeia de coders não suportada".to_string(),
+        ));
+    };
+    let start = 32u64
+        .checked_add(info.pack_pos)
+        .and_then(|start| usize::try_from(start).ok())
+        .ok_or_else(|| invalid("offset do cabeçalho inválido"))?;
+    let packed = info
+        .pack_sizes
+        .first()
+        .and_then(|size| usize::try_from(*size).ok())
+        .ok_or_else(|| invalid("cabeçalho codificado sem tamanho"))?;
+    let compressed = start
+        .checked_add(packed)
+        .and_then(|end| data.get(start..end))
+        .ok_or_else(|| invalid("cabeçalho codificado fora do arquivo"))?;
+    let limits = DecompressLimits::new(MAX_HEADER_BYTES).with_deadline(deadline.clone());
+    let unpack_size = folder.unpack_size(deadline)?;
+    let decoded = match coder.id.as_slice() {
+        CODER_LZMA => decompress_lzma(&coder.properties, compressed, unpack_size, &limits),
+        CODER_LZMA2 => decompress_lzma2(compressed, unpack_size, &limits),
+        CODER_COPY => Ok(compressed.to_vec()),
+        other => Err(DecompressError::Unsupported(hex::encode(other))),
+    };
+    decoded.map(Some).map_err(map_decompress)
+}
+
+fn list(data: &[u8], deadline: &Deadline) -> Result<Listing, ListingError> {
+    if data.len() < 32 || !is_sevenzip(data) {
+        return Err(invalid("assinatura ausente"));
+    }
+    let mut start = Reader::new(&data[12..32]);
+    let offset = start.bytes(8)?;
+    let size = start.bytes(8)?;
+    let offset = u64::from_le_bytes(offset.try_into().expect("8 bytes"));
+    let size = u64::from_le_bytes(size.try_into().expect("8 bytes"));
+    if size == 0 {
+        return Ok(Listing::default());
+    }
+    let header_start = 32u64
+        .checked_add(offset)
+        .and_then(|start| usize::try_from(start).ok())
+        .ok_or_else(|| invalid("offset do cabeçalho inválido"))?;
+    let header = usize::try_from(size)
+        .ok()
+        .and_then(|size| header_start.checked_add(size))
+        .and_then(|end| data.get(header_start..end))
+        .ok_or_else(|| invalid("cabeçalho fora do arquivo"))?;
+
+    list_header(data, header, deadline, 0)
+}
+
+/// Lê o cabeçalho, decodificando `EncodedHeader` até [`MAX_HEADER_LAYERS`] vezes.
+fn list_header(
+    data: &[u8],
+    header: &[u8],
+    deadline: &Deadline,
+    layer: usize,
+) -> Result<Listing, ListingError> {
+    let mut reader = Reader::new(header);
+    match reader.byte()? {
+        ID_HEADER => read_header(&mut reader, deadline),
+        ID_ENCODED_HEADER if layer < MAX_HEADER_LAYERS => {
+            match decode_header(data, &mut reader, deadline)? {
+                Some(decoded) => list_header(data, &decoded, deadline, layer + 1),
+                None => Ok(Listing {
+                    encrypted_headers: true,
+                    ..Listing::default()
+                }),
+            }
+        }
+        ID_ENCODED_HEADER => Err(invalid("cabeçalhos codificados aninhados demais")),
+        other => Err(invalid(format!("cabeçalho com {other:#04x}"))),
+    }
+}
+
+fn read_header(reader: &mut Reader<'_>, deadline: &Deadline) -> Result<Listing, ListingError> {
+    let mut streams = StreamsInfo::default();
+    let mut listing = Listing::default();
+    loop {
+        match reader.byte()? {
+            ID_END => break,
+            ID_ARCHIVE_PROPERTIES => loop {
+                if reader.byte()? == ID_END {
+                    break;
+                }
+                let len = reader.number()?;
+                reader.bytes(len)?;
+            },
+            ID_ADDITIONAL_STREAMS => {
+                read_streams_info(reader, deadline)?;
+            }
+            ID_MAIN_STREAMS => streams = read_streams_info(reader, deadline)?,
+            ID_FILES_INFO => listing.entries = read_files(reader, &streams)?,
+            other => return Err(invalid(format!("Header com {other:#04x}"))),
+        }
+    }
+
+    let mut pack_index = 0usize;
+    for (index, folder) in streams.folders.iter().enumerate() {
+        let packed = streams
+            .pack_sizes
+            .iter()
+            .skip(pack_index)
+            .take(folder.packed_streams)
+            .fold(0u64, |total, size| total.saturating_add(*size));
+        pack_index += folder.packed_streams;
+        listing.blocks.push((
+            format!("folder {index}"),
+            folder.unpack_size(deadline)?,
+            packed,
+        ));
+    }
+    Ok(listing)
+}
+
+fn read_files(
+    reader: &mut Reader<'_>,
+    streams: &StreamsInfo,
+) -> Result<Vec<ListedEntry>, ListingError> {
+    let count = reader.count()?;
+    // As entradas crescem conforme os nomes são lidos: a contagem declarada não
+    // aloca nada sozinha.
+    let mut entries: Vec<ListedEntry> = Vec::new();
+    let mut attributes: Vec<Option<u32>> = Vec::new();
+    let mut empty_stream = Vec::new();
+    let mut empty_file = Vec::new();
+    loop {
+        let property = reader.byte()?;
+        if property == ID_END {
+            break;
+        }
+        let len = reader.number()?;
+        let mut body = Reader::new(reader.bytes(len)?);
+        match property {
+            ID_EMPTY_STREAM => empty_stream = body.bits(count)?,
+            ID_EMPTY_FILE => {
+                let empties = empty_stream.iter().filter(|empty| **empty).count();
+                empty_file = body.bits(empties)?;
+            }
+            ID_NAMES => {
+                if body.byte()? != 0 {
+                    return Err(ListingError::Unsupported(
+                        "7z com nomes externos não é suportado".to_string(),
+                    ));
+                }
+                let units: Vec<u16> = body.data[body.pos..]
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                    .collect();
+                let mut names = units.split(|unit| *unit == 0);
+                entries.clear();
+                for _ in 0..count {
+                    let name = names.next().ok_or_else(|| invalid("nomes truncados"))?;
+                    entries.push(ListedEntry {
+                        name: char::decode_utf16(name.iter().copied())
+                            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                            .collect(),
+                        ..ListedEntry::default()
+                    });
+                }
+            }
+            ID_ATTRIBUTES => {
+                let defined = body.defined(count)?;
+                if body.byte()? != 0 {
+                    return Err(ListingError::Unsupported(
+                        "7z com atributos externos não é suportado".to_string(),
+                    ));
+                }
+                attributes.clear();
+                for defined in defined {
+                    attributes.push(if defined { Some(body.u32()?) } else { None });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if entries.len() < count {
+        // Sem nomes no cabeçalho; `count` já está limitado por `MAX_ITEMS`.
+        entries.resize_with(count, ListedEntry::default);
+    }
+    for (entry, attributes) in entries.iter_mut().zip(&attributes) {
+        let Some(attributes) = *attributes else {
+            continue;
+        };
+        entry.is_dir |= attributes & ATTRIBUTE_DIRECTORY != 0;
+        entry.link |= attributes & ATTRIBUTE_REPARSE_POINT != 0
+            || (attributes & ATTRIBUTE_UNIX_EXTENSION != 0 && is_symlink(attributes >> 16));
+    }
+    if empty_stream.is_empty() {
+        empty_stream = vec![false; count];
+    }
+
+    let mut substreams = streams.substreams.iter();
+    let mut empty_index = 0usize;
+    for (entry, empty) in entries.iter_mut().zip(&empty_stream) {
+        if *empty {
+            let is_file = empty_file.get(empty_index).copied().unwrap_or(false);
+            empty_index += 1;
+            entry.is_dir |= !is_file;
+            continue;
+        }
+        let (folder, size) = substreams
+            .next()
+            .ok_or_else(|| invalid("mais arquivos do que streams"))?;
+        entry.size = *size;
+        entry.encrypted = streams.folders[*folder].is_encrypted();
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ArchivePolicySection;
+    use crate::validators::ValidatorStatus;
+
+    /// `bsdtar --format 7zip -cf lz.7z d` com `d/a.txt` e o symlink `d/link`
+    /// (cabeçalho comprimido com LZMA).
+    const ENCODED_HEADER: &str = concat!(
+        "377abcaf271c000396b55aeb980000000000000021000000000000006f1b2b83",
+        "001760b8c22e39e30ba5c65dfe2aa4fd5e8dafa1d61668ffffc0230000000081",
+        "3307ae0fcfd96fbc0febea9e010d62038dd34c423f0e79575547bc86d7ed303a",
+        "cd516fb31d449fd5be804e79cfea0fe9fd8841230ef7716017c96fd726fe70ef",
+        "e1fc6b06bf6a408a9f4603181828bed3065f9daa4882698544c6c0b70c0d1d20",
+        "367d3ccf3f2b6673589e828566cad23083e2ffffc908c00017061d01097b0007",
+        "0b01000123030101055d000080000c80bb0a01d4b445c30000",
+    );
+
+    fn policy(archive: ArchivePolicySection) -> ResolvedPolicy {
+        ResolvedPolicy {
+            archive,
+            ..ResolvedPolicy::default()
+        }
+    }
+
+    #[test]
+    fn lists_entries_from_compressed_header_and_denies_symlinks() {
+        let data = hex::decode(ENCODED_HEADER).expect("hex");
+        let listing = list(&data, &Deadline::none()).expect("listing");
+        let names: Vec<&str> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["d/link", "d/a.txt", "d"]);
+        assert!(listing.entries[0].link);
+        assert_eq!(listing.entries[1].size, 10);
+        assert!(listing.entries[2].is_dir);
+
+        let outcome = validate_sevenzip(&data, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert!(outcome.details["message"]
+            .as_str()
+            .unwrap()
+            .contains("'d/link' é symlink"));
+
+        let allow_links = policy(ArchivePolicySection {
+            forbid_symlinks: Some(false),
+            ..ArchivePolicySection::default()
+        });
+        let outcome = validate_sevenzip(&data, Some(&allow_links), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(outcome.details["entry_count"], 3);
+    }
+
+    #[test]
+    fn plain_header_traversal_and_encrypted_header() {
+        // `bsdtar -s ',^d/a.txt,../fora.txt,' --options 7zip:compression=store`.
+        let data = hex::decode(concat!(
+            "377abcaf271c000329969c700a000000000000006600000000000000f8b0769a",
+            "6f6c61206d756e646f0a0104060001090a00070b01000101000c0a00080a0103",
+            "d202b9000005011119002e002e002f0066006f00720061002e00740078007400",
+            "0000140a01000948cda0f95ddd01120a01000948cda0f95ddd01130a0100d1ee",
+            "cda0f95ddd01150601002080a4810000",
+        ))
+        .expect("hex");
+        let outcome = validate_sevenzip(&data, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        assert!(outcome.details["message"]
+            .as_str()
+            .unwrap()
+            .contains("'../fora.txt' possui path traversal"));
+
+        // EncodedHeader cujo único coder é AES-256.
+        let mut encrypted = SIGNATURE.to_vec();
+        let header = [
+            ID_ENCODED_HEADER,
+            ID_PACK_INFO,
+            0,
+            1,
+            ID_SIZE,
+            4,
+            ID_END,
+            ID_UNPACK_INFO,
+            ID_FOLDER,
+            1,
+            0,
+            1,
+            0x24,
+            0x06,
+            0xF1,
+            0x07,
+            0x01,
+            0,
+            ID_CODERS_UNPACK_SIZE,
+            8,
+            ID_END,
+            ID_END,
+        ];
+        encrypted.extend_from_slice(&[0, 4, 0, 0, 0, 0]);
+        encrypted.extend_from_slice(&4u64.to_le_bytes());
+        encrypted.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        encrypted.extend_from_slice(&[0; 4]);
+        encrypted.extend_from_slice(&[0xAA; 4]);
+        encrypted.extend_from_slice(&header);
+        let outcome = validate_sevenzip(&encrypted, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Warn));
+        assert_eq!(outcome.details["encrypted_headers"], true);
+        let forbid = policy(ArchivePolicySection {
+            forbid_encrypted_entries: Some(true),
+            ..ArchivePolicySection::default()
+        });
+        let outcome = validate_sevenzip(&encrypted, Some(&forbid), &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+    }
+
+    #[test]
+    fn oversized_counts_are_rejected_before_allocating() {
+        // FilesInfo declarando MAX_ITEMS + 1 arquivos num cabeçalho com bits suficientes.
+        let mut header = vec![ID_HEADER, ID_FILES_INFO, 0xFF];
+        header.extend_from_slice(&(MAX_ITEMS + 1).to_le_bytes());
+        header.resize(MAX_ITEMS as usize / 8 + 64, 0);
+        let mut data = SIGNATURE.to_vec();
+        data.extend_from_slice(&[0, 4, 0, 0, 0, 0]);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&header);
+        let outcome = validate_sevenzip(&data, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Error));
+        assert!(outcome.details["message"]
+            .as_str()
+            .unwrap()
+            .contains("contagem inconsistente"));
+
+        // Contagem aceita, mas nomes truncados: nada é alocado pela contagem.
+        let mut header = vec![ID_HEADER, ID_FILES_INFO, 0xC0, 0x00, 0x10, ID_NAMES, 3, 0];
+        header.extend_from_slice(&[b'a', 0]);
+        header.push(ID_END);
+        header.resize(4096, 0);
+        let mut data = SIGNATURE.to_vec();
+        data.extend_from_slice(&[0, 4, 0, 0, 0, 0]);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&header);
+        let outcome = validate_sevenzip(&data, None, &Deadline::none());
+        assert!(matches!(outcome.status, ValidatorStatus::Error));
+        assert!(outcome.details["message"]
+            .as_str()
+            .unwrap()
+            .contains("nomes truncados"));
+    }
+}
@@ -0,0 +1,296 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Validador de SVG (scripts, handlers de evento e referências externas).
+
+use super::ValidatorOutcome;
+use crate::config::SvgPolicySection;
+use crate::policy::ResolvedPolicy;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
+use serde_json::{json, Value};
+
+/// Namespace de `xlink:href`, com qualquer prefixo declarado para ele.
+const XLINK_NAMESPACE: &[u8] = b"http://www.w3.org/1999/xlink";
+
+pub fn validate_svg(data: &[u8], policy: Option<&ResolvedPolicy>) -> ValidatorOutcome {
+    let name = "svg";
+    let svg_policy = policy.map(|p| p.svg.clone()).unwrap_or_default();
+
+    let findings = match scan_svg(data) {
+        Ok(findings) => findings,
+        Err(err) => return ValidatorOutcome::deny(name, format!("SVG malformado: {err}")),
+    };
+
+    let forbidden: Vec<&SvgFinding> = findings
+        .iter()
+        .filter(|finding| is_forbidden(finding.kind, &svg_policy))
+        .collect();
+    let findings_json = Value::Array(findings.iter().map(SvgFinding::to_json).collect());
+
+    if !forbidden.is_empty() {
+        let mut kinds: Vec<&str> = forbidden.iter().map(|finding| finding.kind).collect();
+        kinds.sort_unstable();
+        kinds.dedup();
+        let mut outcome = ValidatorOutcome::deny(
+            name,
+            format!("SVG possui conteúdo ativo proibido: {}", kinds.join(", ")),
+        );
+        outcome.details["findings"] = findings_json;
+        return outcome;
+    }
+
+    let mut outcome = ValidatorOutcome::pass(name);
+    outcome.details = json!({
+        "size_bytes": data.len(),
+        "findings": findings_json,
+    });
+    outcome
+}
+
+/// Elemento ou atributo potencialmente ativo encontrado no SVG.
+#[derive(Debug, Clone)]
+struct SvgFinding {
+    kind: &'static str,
+    element: String,
+    attribute: Option<String>,
+    value: Option<String>,
+}
+
+impl SvgFinding {
+    fn to_json(&self) -> Value {
+        json!({
+            "kind": self.kind,
+            "element": self.element,
+            "attribute": self.attribute,
+            "value": self.value,
+        })
+    }
+}
+
+/// Documento cujo elemento raiz é `<svg>`, depois de BOM, espaços, instruções de
+/// processamento, comentários e doctype; não exige o prólogo `<?xml`.
+pub(crate) fn has_svg_root(data: &[u8]) -> bool {
+    let mut rest = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    loop {
+        rest = rest.trim_ascii_start();
+        let skip_to = |rest: &[u8], end: &[u8]| {
+            rest.windows(end.len())
+                .position(|window| window == end)
+                .map(|pos| pos + end.len())
+        };
+        let skipped = if rest.starts_with(b"<?") {
+            skip_to(rest, b"?>")
+        } else if rest.starts_with(b"<!--") {
+            skip_to(rest, b"-->")
+        } else if rest.len() >= 9 && rest[..9].eq_ignore_ascii_case(b"<!DOCTYPE") {
+            // Subconjunto interno (`[...]`) pode conter `>` antes do fim do doctype.
+            let close = rest.iter().position(|byte| *byte == b'>');
+            match rest.iter().position(|byte| *byte == b'[') {
+                Some(open) if close.is_none_or(|close| open < close) => skip_to(rest, b"]")
+                    .and_then(|end| skip_to(&rest[end..], b">").map(|pos| end + pos)),
+                _ => close.map(|close| close + 1),
+            }
+        } else {
+            let Some(tag) = rest.strip_prefix(b"<") else {
+                return false;
+            };
+            let name_len = tag
+                .iter()
+                .position(|byte| byte.is_ascii_whitespace() || matches!(byte, b'>' | b'/'))
+                .unwrap_or(tag.len());
+            let name = &tag[..name_len];
+            let local = name.rsplit(|byte| *byte == b':').next().unwrap_or(name);
+            return local.eq_ignore_ascii_case(b"svg");
+        };
+        match skipped {
+            Some(skipped) => rest = &rest[skipped..],
+            None => return false,
+        }
+    }
+}
+
+fn scan_svg(data: &[u8]) -> Result<Vec<SvgFinding>, String> {
+    let mut reader = NsReader::from_reader(data);
+    let mut findings = Vec::new();
+    let mut saw_svg_root = false;
+
+    loop {
+        match reader.read_event().map_err(|err| err.to_string())? {
+            Event::Start(element) | Event::Empty(element) => {
+                let local = String::from_utf8_lossy(element.local_name().as_ref()).to_lowercase();
+                saw_svg_root |= local == "svg";
+                inspect_element(&reader, &element, &local, &mut findings)
+                    .map_err(|err| err.to_string())?;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !saw_svg_root {
+        return Err("elemento <svg> ausente".to_string());
+    }
+    Ok(findings)
+}
+
+fn inspect_element(
+    reader: &NsReader<&[u8]>,
+    element: &BytesStart<'_>,
+    local: &str,
+    findings: &mut Vec<SvgFinding>,
+) -> Result<(), quick_xml::Error> {
+    match local {
+        "script" => findings.push(SvgFinding {
+            kind: "script",
+            element: local.to_string(),
+            attribute: None,
+            value: None,
+        }),
+        "foreignobject" => findings.push(SvgFinding {
+            kind: "foreign_object",
+            element: local.to_string(),
+            attribute: None,
+            value: None,
+        }),
+        _ => {}
+    }
+
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::InvalidAttr)?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).to_lowercase();
+        let value = attribute.unescape_value()?.trim().to_string();
+
+        // `href` sem prefixo (SVG 2) ou no namespace XLink, com qualquer prefixo;
+        // prefixos não declarados também contam, por cautela.
+        let (namespace, local_key) = reader.resolve_attribute(attribute.key);
+        let is_href = local_key.as_ref().eq_ignore_ascii_case(b"href")
+            && match namespace {
+                ResolveResult::Unbound | ResolveResult::Unknown(_) => true,
+                ResolveResult::Bound(Namespace(uri)) => uri == XLINK_NAMESPACE,
+            };
+
+        let kind = if key.starts_with("on") {
+            Some("event_handler")
+        } else if is_href {
+            classify_reference(&value)
+        } else {
+            None
+        };
+
+        if let Some(kind) = kind {
+            findings.push(SvgFinding {
+                kind,
+                element: local.to_string(),
+                attribute: Some(key),
+                value: Some(value.chars().take(128).collect()),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Classifica um `href`: fragmentos locais são seguros, `data:` e URLs externas não.
+fn classify_reference(value: &str) -> Option<&'static str> {
+    let lowered = value.to_ascii_lowercase();
+    if value.is_empty() || value.starts_with('#') {
+        None
+    } else if lowered.starts_with("data:") {
+        Some("data_uri")
+    } else if lowered.starts_with("javascript:") {
+        Some("script")
+    } else {
+        Some("external_reference")
+    }
+}
+
+fn is_forbidden(kind: &str, policy: &SvgPolicySection) -> bool {
+    match kind {
+        "script" => policy.forbid_scripts.unwrap_or(true),
+        "event_handler" => policy.forbid_event_handlers.unwrap_or(true),
+        "foreign_object" => policy.forbid_foreign_object.unwrap_or(true),
+        "external_reference" => policy.forbid_external_references.unwrap_or(true),
+        "data_uri" => policy.forbid_data_uris.unwrap_or(true),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validators::ValidatorStatus;
+
+    #[test]
+    fn clean_svg_passes() {
+        let data = br##"<svg xmlns="http://www.w3.org/2000/svg"><defs><circle id="c" r="4"/></defs><use href="#c"/></svg>"##;
+        let outcome = validate_svg(data, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Pass));
+        assert_eq!(outcome.details["findings"], json!([]));
+    }
+
+    #[test]
+    fn active_content_is_denied_with_findings() {
+        let data = br#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" onload="alert(1)"><script>alert(2)</script><image xlink:href="https://evil.example/x.png"/><foreignObject/></svg>"#;
+        let outcome = validate_svg(data, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        let kinds: Vec<&str> = outcome.details["findings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|finding| finding["kind"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "event_handler",
+                "script",
+                "external_reference",
+                "foreign_object"
+            ]
+        );
+    }
+
+    #[test]
+    fn xlink_href_is_resolved_by_namespace_not_prefix() {
+        let data = br#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:l="http://www.w3.org/1999/xlink" xmlns:o="urn:outro"><image l:href="https://evil.example/x.png"/><use o:href="https://example.com/y"/><script/><image href="data:,x"/><script/></svg>"#;
+        let outcome = validate_svg(data, None);
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+        let attributes: Vec<&str> = outcome.details["findings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|finding| finding["attribute"].as_str())
+            .collect();
+        assert_eq!(attributes, vec!["l:href", "href"]);
+        assert_eq!(
+            outcome.details["message"],
+            "SVG possui conteúdo ativo proibido: data_uri, external_reference, script"
+        );
+    }
+
+    #[test]
+    fn data_uri_can_be_allowed_by_policy() {
+        let data = br#"<svg xmlns="http://www.w3.org/2000/svg"><image href="data:image/png;base64,AAAA"/></svg>"#;
+        let denied = validate_svg(data, None);
+        assert!(matches!(denied.status, ValidatorStatus::Deny));
+
+        let policy = ResolvedPolicy {
+            svg: SvgPolicySection {
+                forbid_data_uris: Some(false),
+                ..SvgPolicySection::default()
+            },
+            ..ResolvedPolicy::default()
+        };
+        let allowed = validate_svg(data, Some(&policy));
+        assert!(matches!(allowed.status, ValidatorStatus::Pass));
+    }
+
+    #[test]
+    fn malformed_svg_is_denied() {
+        let outcome = validate_svg(b"<svg><g></svg>", None);
+        assert!(matches!(outcome.status, ValidatorStatus::Deny));
+    }
+}
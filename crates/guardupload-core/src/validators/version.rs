@@ -0,0 +1,153 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Versões semânticas dos validadores e requisitos de versão da política (`require`).
+//!
+//! O major de um validador sobe quando ele passa a garantir algo novo (uma
+//! verificação que antes não existia); o minor, em ajustes compatíveis. A
+//! política fixa o mínimo de que depende (`require: { pdf: ">=2" }`) e binários
+//! mais antigos recusam a política no carregamento.
+
+use anyhow::{bail, Context, Result};
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// Versão `major.minor.patch` de um validador.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ValidatorVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ValidatorVersion {
+    /// Versão padrão de validadores que não declaram outra.
+    pub const INITIAL: Self = Self::new(1, 0, 0);
+
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Interpreta `2`, `2.1` ou `2.1.3`; partes ausentes valem 0.
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut parts = [0u32; 3];
+        for (index, part) in raw.trim().split('.').enumerate() {
+            let slot = parts
+                .get_mut(index)
+                .with_context(|| format!("versão '{raw}' com partes demais"))?;
+            *slot = part
+                .parse()
+                .with_context(|| format!("versão '{raw}' inválida"))?;
+        }
+        Ok(Self::new(parts[0], parts[1], parts[2]))
+    }
+}
+
+impl Default for ValidatorVersion {
+    fn default() -> Self {
+        Self::INITIAL
+    }
+}
+
+impl fmt::Display for ValidatorVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl Serialize for ValidatorVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    LessOrEqual,
+    Less,
+}
+
+/// Requisito como `>=2`, `>=1.2, <3` ou `=2.0.1`; sem operador vale `>=`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRequirement {
+    comparators: Vec<(Comparison, ValidatorVersion)>,
+}
+
+impl VersionRequirement {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut comparators = Vec::new();
+        for part in raw.split(',') {
+            let part = part.trim();
+            let (comparison, version) = [
+                (">=", Comparison::GreaterOrEqual),
+                ("<=", Comparison::LessOrEqual),
+                (">", Comparison::Greater),
+                ("<", Comparison::Less),
+                ("=", Comparison::Equal),
+            ]
+            .into_iter()
+            .find_map(|(operator, comparison)| {
+                part.strip_prefix(operator)
+                    .map(|version| (comparison, version))
+            })
+            .unwrap_or((Comparison::GreaterOrEqual, part));
+            if version.trim().is_empty() {
+                bail!("requisito de versão '{raw}' sem versão");
+            }
+            comparators.push((comparison, ValidatorVersion::parse(version)?));
+        }
+        Ok(Self { comparators })
+    }
+
+    /// `true` quando `version` atende a todas as comparações.
+    pub fn matches(&self, version: ValidatorVersion) -> bool {
+        self.comparators
+            .iter()
+            .all(|&(comparison, required)| match comparison {
+                Comparison::Greater => version > required,
+                Comparison::GreaterOrEqual => version >= required,
+                Comparison::Equal => version == required,
+                Comparison::LessOrEqual => version <= required,
+                Comparison::Less => version < required,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requirements_compare_partial_versions() {
+        let v2 = ValidatorVersion::parse("2").unwrap();
+        assert_eq!(v2.to_string(), "2.0.0");
+        assert!(
+            ValidatorVersion::parse("1.2.3").unwrap() > ValidatorVersion::parse("1.2").unwrap()
+        );
+
+        let pin = VersionRequirement::parse(">=1.2, <3").unwrap();
+        assert!(pin.matches(ValidatorVersion::new(1, 2, 0)));
+        assert!(pin.matches(ValidatorVersion::new(2, 9, 9)));
+        assert!(!pin.matches(ValidatorVersion::new(1, 1, 9)));
+        assert!(!pin.matches(ValidatorVersion::new(3, 0, 0)));
+        assert!(VersionRequirement::parse("2")
+            .unwrap()
+            .matches(ValidatorVersion::new(2, 0, 1)));
+        assert!(!VersionRequirement::parse("=2")
+            .unwrap()
+            .matches(ValidatorVersion::new(2, 0, 1)));
+
+        for invalid in [">=", "2.x", "1.2.3.4", ">= 2, "] {
+            assert!(VersionRequirement::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+}
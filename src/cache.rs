@@ -0,0 +1,345 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Cache em disco de resultados do `scan` (`--cache-dir`).
+//!
+//! Cada entrada guarda o `FileReport` já decidido em
+//! `<dir>/<sha256[..2]>/<sha256>-<contexto>.json`, onde o contexto é o digest da
+//! versão do crate, da política carregada (incluindo os digests lidos das listas
+//! de `hashes`), das opções que alteram o relatório (`--hash`) e do caminho
+//! analisado (nome e extensão entram nas regras), além do mapa de MIME declarado
+//! por extensão (`--claimed-mime-map`). Um conteúdo alterado muda o SHA-256 e uma
+//! política nova muda o contexto, então entradas antigas simplesmente deixam de
+//! ser consultadas. Resultados parciais (prazo esgotado, leitura parcial) e os
+//! que passaram pelo clamd ou por plugins externos, cujas assinaturas mudam sem
+//! mudar a política, não são gravados.
+
+use crate::analyzers::hashes::HashAlgorithm;
+use crate::policy::{Decision, DecisionOutcome, PolicyEngine};
+use crate::report::FileReport;
+use crate::sniff::ClaimedMimeMap;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const ENTRY_EXTENSION: &str = "json";
+
+/// Cache de resultados aberto para uma combinação de política e opções.
+#[derive(Debug)]
+pub struct ResultCache {
+    dir: PathBuf,
+    /// Digest de versão, política e opções, combinado ao caminho em cada entrada.
+    context: Vec<u8>,
+    /// Validadores com estado externo (`clamav` e plugins); resultados em que
+    /// eles rodaram não são guardados.
+    external_validators: Vec<String>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ResultCache {
+    /// Abre (criando se preciso) o diretório do cache.
+    pub fn open(
+        dir: &Path,
+        policy_engine: Option<&PolicyEngine>,
+        claimed_mimes: &ClaimedMimeMap,
+        hash_algorithms: &[HashAlgorithm],
+    ) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("falha ao criar cache {}", dir.display()))?;
+        // As listas de `hashes` podem apontar para arquivos: o conteúdo carregado
+        // (fora da serialização da política) também entra no contexto.
+        let policy = policy_engine
+            .map(|engine| {
+                let config = engine.config();
+                serde_json::to_string(&(
+                    config,
+                    &config.hashes.allow_digests,
+                    &config.hashes.deny_digests,
+                ))
+            })
+            .transpose()?;
+        let mut context = Sha256::new();
+        context.update(env!("CARGO_PKG_VERSION"));
+        context.update([0]);
+        context.update(policy.unwrap_or_default());
+        context.update([0]);
+        context.update(format!("{hash_algorithms:?}"));
+        context.update([0]);
+        context.update(format!("{claimed_mimes:?}"));
+        let mut external_validators = vec!["clamav".to_string()];
+        if let Some(engine) = policy_engine {
+            external_validators.extend(
+                engine
+                    .config()
+                    .plugins
+                    .iter()
+                    .map(|plugin| plugin.name.clone()),
+            );
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            context: context.finalize().to_vec(),
+            external_validators,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        })
+    }
+
+    /// Resultado guardado para o conteúdo `sha256` em `path`, se houver.
+    pub fn get(&self, sha256: &str, path: &Path) -> Option<(FileReport, DecisionOutcome)> {
+        let entry = self.entry_path(sha256, path);
+        let cached = fs::read(&entry)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<FileReport>(&data).ok())
+            .filter(|report| report.sha256 == sha256)
+            .and_then(|report| outcome_of(&report).map(|outcome| (report, outcome)));
+        let counter = if cached.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    /// Guarda o relatório de `path` com a decisão aplicada; falhas de gravação
+    /// só geram aviso. Resultados parciais ou de validadores externos não são
+    /// guardados.
+    pub fn put(&self, path: &Path, report: &FileReport, outcome: &DecisionOutcome) {
+        if is_partial(report, outcome) || self.ran_external_validator(report) {
+            return;
+        }
+        let entry = self.entry_path(&report.sha256, path);
+        let mut stored = report.clone();
+        stored.policy = outcome.clone().into();
+        if let Err(err) = write_entry(&entry, &stored) {
+            tracing::warn!(cache = %entry.display(), "resultado não gravado no cache: {err:#}");
+        }
+    }
+
+    /// Consultas atendidas pelo cache e consultas sem entrada.
+    pub fn stats(&self) -> (u64, u64) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+
+    fn ran_external_validator(&self, report: &FileReport) -> bool {
+        report.validators.iter().any(|validator| {
+            validator.status != "skipped" && self.external_validators.contains(&validator.name)
+        })
+    }
+
+    fn entry_path(&self, sha256: &str, path: &Path) -> PathBuf {
+        let mut key = Sha256::new();
+        key.update(&self.context);
+        key.update(path.to_string_lossy().as_bytes());
+        let key = hex::encode(key.finalize());
+        let shard = sha256.get(..2).unwrap_or("00");
+        self.dir
+            .join(shard)
+            .join(format!("{sha256}-{}.{ENTRY_EXTENSION}", &key[..16]))
+    }
+}
+
+/// Remove as entradas do cache em `dir` (e os subdiretórios que ficarem vazios);
+/// outros arquivos são preservados. Devolve quantas entradas foram removidas.
+pub fn clear(dir: &Path) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    let shards = fs::read_dir(dir).with_context(|| format!("falha ao ler {}", dir.display()))?;
+    for shard in shards {
+        let shard = shard?.path();
+        if !shard.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&shard)? {
+            let entry = entry?.path();
+            if entry.extension().is_some_and(|ext| ext == ENTRY_EXTENSION) {
+                fs::remove_file(&entry)
+                    .with_context(|| format!("falha ao remover {}", entry.display()))?;
+                removed += 1;
+            }
+        }
+        let _ = fs::remove_dir(&shard);
+    }
+    Ok(removed)
+}
+
+fn write_entry(entry: &Path, report: &FileReport) -> Result<()> {
+    if let Some(parent) = entry.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temp = entry.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, serde_json::to_vec(report)?)?;
+    fs::rename(&temp, entry)?;
+    Ok(())
+}
+
+/// Indica se o resultado depende de um prazo ou de uma leitura parcial e não
+/// pode ser reutilizado.
+fn is_partial(report: &FileReport, outcome: &DecisionOutcome) -> bool {
+    report.partially_read
+        || outcome
+            .rules_triggered
+            .iter()
+            .any(|rule| rule.starts_with("validator:") && rule.ends_with(":error:timeout"))
+        || report
+            .validators
+            .iter()
+            .any(|validator| validator.details["partial"] == true)
+}
+
+/// Reconstrói a decisão registrada no relatório.
+fn outcome_of(report: &FileReport) -> Option<DecisionOutcome> {
+    let decision = match report.policy.decision.as_str() {
+        "ALLOW" => Decision::Allow,
+        "WARN" => Decision::Warn,
+        "DENY" => Decision::Deny,
+        _ => return None,
+    };
+    Some(DecisionOutcome {
+        decision,
+        rules_triggered: report.policy.rules_triggered.clone(),
+        primary_rule: report.policy.primary_rule.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PolicyConfig;
+    use crate::report::SniffReport;
+
+    #[test]
+    fn entries_depend_on_policy_and_path() {
+        let dir = tempfile::tempdir().expect("dir");
+        let strict = PolicyEngine::new(PolicyConfig::default());
+        let mut config = PolicyConfig::default();
+        config.defaults.max_size_mb = Some(1);
+        let other = PolicyEngine::new(config);
+
+        let sniff = SniffReport::new("text/plain".into(), None, None);
+        let path = Path::new("/dados/a.txt");
+        let report = FileReport::new(path, 3, "abcdef".into(), sniff);
+        let mut outcome = DecisionOutcome::new();
+        outcome.record(Decision::Warn, "file:unsafe_name");
+
+        let cache = ResultCache::open(dir.path(), Some(&strict), &ClaimedMimeMap::default(), &[])
+            .expect("cache");
+        cache.put(path, &report, &outcome);
+        let (cached, cached_outcome) = cache.get("abcdef", path).expect("hit");
+        assert_eq!(cached.policy.decision, "WARN");
+        assert_eq!(
+            cached_outcome.primary_rule.as_deref(),
+            Some("file:unsafe_name")
+        );
+        assert!(cache.get("abcdef", Path::new("/dados/b.txt")).is_none());
+        assert_eq!(cache.stats(), (1, 1));
+
+        let reopened = ResultCache::open(dir.path(), Some(&other), &ClaimedMimeMap::default(), &[])
+            .expect("cache");
+        assert!(reopened.get("abcdef", path).is_none());
+
+        assert_eq!(clear(dir.path()).expect("clear"), 1);
+        assert!(cache.get("abcdef", path).is_none());
+    }
+
+    #[test]
+    fn digest_lists_change_the_context_and_partial_results_are_not_stored() {
+        let dir = tempfile::tempdir().expect("dir");
+        let sniff = SniffReport::new("text/plain".into(), None, None);
+        let path = Path::new("/dados/a.txt");
+        let report = FileReport::new(path, 3, "abcdef".into(), sniff);
+        let outcome = DecisionOutcome::new();
+
+        let mut config = PolicyConfig::default();
+        config.hashes.deny = vec!["lista.txt".into()];
+        let claimed = ClaimedMimeMap::default();
+        let cache = ResultCache::open(
+            dir.path(),
+            Some(&PolicyEngine::new(config.clone())),
+            &claimed,
+            &[],
+        )
+        .expect("cache");
+        cache.put(path, &report, &outcome);
+        assert!(cache.get("abcdef", path).is_some());
+        // Mesmo YAML, mas a lista de hashes carregada mudou.
+        config.hashes.deny_digests.insert("abcdef".into());
+        let reloaded =
+            ResultCache::open(dir.path(), Some(&PolicyEngine::new(config)), &claimed, &[])
+                .expect("cache");
+        assert!(reloaded.get("abcdef", path).is_none());
+
+        let mut timed_out = DecisionOutcome::new();
+        timed_out.record(Decision::Warn, "validator:archive:error:timeout");
+        let other = Path::new("/dados/b.txt");
+        cache.put(other, &report, &timed_out);
+        assert!(cache.get("abcdef", other).is_none());
+
+        let mut partial = report.clone();
+        partial.validators.push(crate::report::ValidatorEntry {
+            name: "pdf".into(),
+            status: "WARN".into(),
+            details: serde_json::json!({ "partial": true }),
+            version: None,
+        });
+        cache.put(other, &partial, &outcome);
+        assert!(cache.get("abcdef", other).is_none());
+    }
+
+    #[test]
+    fn claimed_mime_map_changes_the_context_and_external_validators_are_not_stored() {
+        let dir = tempfile::tempdir().expect("dir");
+        let sniff = SniffReport::new("text/plain".into(), None, None);
+        let path = Path::new("/dados/a.txt");
+        let report = FileReport::new(path, 3, "abcdef".into(), sniff);
+        let outcome = DecisionOutcome::new();
+
+        let mut config = PolicyConfig::default();
+        config.plugins.push(
+            serde_json::from_value(serde_json::json!({ "name": "yara", "command": ["yara-json"] }))
+                .expect("plugin"),
+        );
+        let engine = PolicyEngine::new(config);
+        let cache = ResultCache::open(dir.path(), Some(&engine), &ClaimedMimeMap::default(), &[])
+            .expect("cache");
+        cache.put(path, &report, &outcome);
+        assert!(cache.get("abcdef", path).is_some());
+        let claimed = ClaimedMimeMap::from_yaml_str("{ '.txt': application/pdf }").expect("map");
+        let remapped = ResultCache::open(dir.path(), Some(&engine), &claimed, &[]).expect("cache");
+        assert!(remapped.get("abcdef", path).is_none());
+
+        let other = Path::new("/dados/b.txt");
+        for (name, status, stored) in [
+            ("clamav", "pass", false),
+            ("yara", "deny", false),
+            ("yara", "skipped", true),
+        ] {
+            let mut scanned = report.clone();
+            scanned.validators.push(crate::report::ValidatorEntry {
+                name: name.into(),
+                status: status.into(),
+                details: serde_json::Value::Null,
+                version: None,
+            });
+            cache.put(other, &scanned, &outcome);
+            assert_eq!(
+                cache.get("abcdef", other).is_some(),
+                stored,
+                "{name}:{status}"
+            );
+        }
+    }
+}
@@ -0,0 +1,275 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Validador `clamav`: envia o conteúdo ao clamd pelo protocolo `INSTREAM`.
+//!
+//! O conteúdo segue em blocos `<tamanho u32 big-endian><bytes>`, terminados por um
+//! bloco vazio; o clamd responde `stream: OK` ou `stream: <assinatura> FOUND`.
+//! Consultas descartadas por `rate_limits.clamav` seguem a regra do clamd
+//! indisponível (`fail_open`).
+
+use crate::config::ClamavPolicySection;
+use crate::rate_limit::{Permit, RateLimiter};
+use crate::validators::{ValidationContext, Validator, ValidatorOutcome, ANY_VALIDATOR};
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::json;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+const CHUNK_BYTES: usize = 64 * 1024;
+/// Respostas do clamd são curtas; acima disso a conexão é tratada como inválida.
+const MAX_REPLY_BYTES: u64 = 4096;
+
+/// Endereço do clamd.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClamdSocket {
+    Tcp(String),
+    Unix(String),
+}
+
+impl ClamdSocket {
+    pub fn parse(raw: &str) -> Self {
+        if let Some(address) = raw.strip_prefix("tcp://") {
+            Self::Tcp(address.to_string())
+        } else if let Some(path) = raw.strip_prefix("unix://") {
+            Self::Unix(path.to_string())
+        } else if raw.starts_with('/') || raw.starts_with('.') {
+            Self::Unix(raw.to_string())
+        } else {
+            Self::Tcp(raw.to_string())
+        }
+    }
+}
+
+/// Resultado de uma varredura no clamd.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClamdVerdict {
+    Clean,
+    Found(String),
+}
+
+#[derive(Debug)]
+pub struct ClamavValidator {
+    socket: ClamdSocket,
+    timeout: Duration,
+    fail_open: bool,
+    limiter: Option<Arc<RateLimiter>>,
+}
+
+impl ClamavValidator {
+    /// Validador configurado pela seção `clamav`; `None` sem `clamav.socket`.
+    pub fn from_policy(section: &ClamavPolicySection) -> Option<Self> {
+        let socket = section.socket.as_deref()?;
+        Some(Self {
+            socket: ClamdSocket::parse(socket),
+            timeout: Duration::from_millis(section.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)),
+            fail_open: section.fail_open.unwrap_or(false),
+            limiter: None,
+        })
+    }
+
+    /// Limita as consultas com o limitador compartilhado de `rate_limits.clamav`.
+    pub fn with_rate_limiter(mut self, limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Envia `data` ao clamd e interpreta a resposta.
+    pub fn scan(&self, data: &[u8]) -> Result<ClamdVerdict> {
+        let reply = match &self.socket {
+            ClamdSocket::Tcp(address) => {
+                let target = address
+                    .to_socket_addrs()
+                    .with_context(|| format!("endereço clamd inválido: {address}"))?
+                    .next()
+                    .with_context(|| format!("endereço clamd sem resolução: {address}"))?;
+                let stream = TcpStream::connect_timeout(&target, self.timeout)
+                    .with_context(|| format!("falha ao conectar ao clamd em {address}"))?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                instream(stream, data)?
+            }
+            #[cfg(unix)]
+            ClamdSocket::Unix(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)
+                    .with_context(|| format!("falha ao conectar ao clamd em {path}"))?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                instream(stream, data)?
+            }
+            #[cfg(not(unix))]
+            ClamdSocket::Unix(path) => bail!("socket Unix indisponível nesta plataforma: {path}"),
+        };
+        parse_reply(&reply)
+    }
+}
+
+impl Validator for ClamavValidator {
+    fn name(&self) -> &'static str {
+        "clamav"
+    }
+
+    fn applies_to(&self, _ctx: &ValidationContext<'_>) -> bool {
+        true
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        let name = self.name();
+        let permit = self
+            .limiter
+            .as_deref()
+            .map(|limiter| limiter.acquire(Instant::now()));
+        let verdict = match permit {
+            Some(Permit::Dropped) => Err(anyhow!(
+                "consulta descartada pelo limite de taxa (rate_limits.clamav)"
+            )),
+            _ => self.scan(ctx.data),
+        };
+        match verdict {
+            Ok(ClamdVerdict::Clean) => ValidatorOutcome::pass(name),
+            Ok(ClamdVerdict::Found(signature)) => {
+                ValidatorOutcome::deny(name, format!("malware detectado: {signature}"))
+                    .with_details(json!({ "signature": signature }))
+            }
+            Err(err) => {
+                let message = format!("clamd indisponível: {err:#}");
+                let outcome = if self.fail_open {
+                    ValidatorOutcome::warn(name, message)
+                } else {
+                    ValidatorOutcome::deny(name, message)
+                };
+                outcome.with_details(json!({ "fail_open": self.fail_open }))
+            }
+        }
+    }
+
+    fn supplements(&self) -> Option<&'static str> {
+        Some(ANY_VALIDATOR)
+    }
+
+    fn policy_keys(&self) -> &'static [&'static str] {
+        &["clamav.socket", "clamav.timeout_ms", "clamav.fail_open"]
+    }
+}
+
+fn instream(mut stream: impl Read + Write, data: &[u8]) -> Result<String> {
+    stream.write_all(b"zINSTREAM\0")?;
+    for chunk in data.chunks(CHUNK_BYTES) {
+        stream.write_all(&(chunk.len() as u32).to_be_bytes())?;
+        stream.write_all(chunk)?;
+    }
+    stream.write_all(&0u32.to_be_bytes())?;
+    stream.flush()?;
+
+    let mut reply = Vec::new();
+    stream
+        .take(MAX_REPLY_BYTES)
+        .read_to_end(&mut reply)
+        .context("falha ao ler resposta do clamd")?;
+    let reply = String::from_utf8_lossy(&reply);
+    Ok(reply.trim_end_matches(['\0', '\n']).to_string())
+}
+
+fn parse_reply(reply: &str) -> Result<ClamdVerdict> {
+    let status = reply.strip_prefix("stream:").unwrap_or(reply).trim();
+    if status == "OK" {
+        Ok(ClamdVerdict::Clean)
+    } else if let Some(signature) = status.strip_suffix(" FOUND") {
+        Ok(ClamdVerdict::Found(signature.trim().to_string()))
+    } else {
+        bail!("resposta inesperada do clamd: {reply}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::Deadline;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// clamd falso: lê um `INSTREAM` e acusa conteúdos que contenham "EICAR".
+    fn fake_clamd() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut command = [0u8; 10];
+                stream.read_exact(&mut command).unwrap();
+                assert_eq!(&command, b"zINSTREAM\0");
+                let mut content = Vec::new();
+                loop {
+                    let mut size = [0u8; 4];
+                    stream.read_exact(&mut size).unwrap();
+                    let size = u32::from_be_bytes(size) as usize;
+                    if size == 0 {
+                        break;
+                    }
+                    let mut chunk = vec![0u8; size];
+                    stream.read_exact(&mut chunk).unwrap();
+                    content.extend(chunk);
+                }
+                let found = content.windows(5).any(|window| window == b"EICAR");
+                let reply: &[u8] = if found {
+                    b"stream: Eicar-Test-Signature FOUND\0"
+                } else {
+                    b"stream: OK\0"
+                };
+                stream.write_all(reply).unwrap();
+            }
+        });
+        address
+    }
+
+    fn validate(validator: &ClamavValidator, data: &[u8]) -> ValidatorOutcome {
+        validator.validate(&ValidationContext {
+            mime: "application/octet-stream",
+            data,
+            policy: None,
+            deadline: &Deadline::none(),
+            file_name: None,
+        })
+    }
+
+    #[test]
+    fn instream_reports_signature_and_clean_content() {
+        let validator = ClamavValidator::from_policy(&ClamavPolicySection {
+            socket: Some(format!("tcp://{}", fake_clamd())),
+            ..ClamavPolicySection::default()
+        })
+        .expect("socket");
+        let mut infected = vec![b'x'; CHUNK_BYTES + 10];
+        infected.extend_from_slice(b"EICAR");
+        let outcome = validate(&validator, &infected);
+        assert_eq!(outcome.status.as_str(), "deny");
+        assert_eq!(outcome.details["signature"], "Eicar-Test-Signature");
+        assert_eq!(validate(&validator, b"limpo").status.as_str(), "pass");
+    }
+
+    #[test]
+    fn unreachable_clamd_fails_closed_unless_configured_open() {
+        let closed_port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let section = |fail_open| ClamavPolicySection {
+            socket: Some(closed_port.clone()),
+            timeout_ms: Some(500),
+            fail_open: Some(fail_open),
+        };
+        let closed = ClamavValidator::from_policy(&section(false)).unwrap();
+        assert_eq!(validate(&closed, b"x").status.as_str(), "deny");
+        let open = ClamavValidator::from_policy(&section(true)).unwrap();
+        assert_eq!(validate(&open, b"x").status.as_str(), "warn");
+        assert_eq!(
+            ClamdSocket::parse("/run/clamav/clamd.ctl"),
+            ClamdSocket::Unix("/run/clamav/clamd.ctl".into())
+        );
+    }
+}
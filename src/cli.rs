@@ -5,10 +5,38 @@
 
 //! Camada de interface de linha de comando baseada em `clap`.
 
-use crate::engine::{BenchOutcome, BenchRequest, Engine, ScanOutcome, ScanRequest};
-use anyhow::Result;
-use clap::{Args, Parser, Subcommand, ValueEnum};
-use std::path::PathBuf;
+use crate::analyzers::hashes::HashAlgorithm;
+use crate::cache;
+use crate::config::{PolicyConfig, PolicyOverride};
+use crate::corpus::{self, CorpusFetchRequest};
+use crate::decompress::default_backend;
+use crate::engine::{
+    load_policy, BenchOutcome, BenchRequest, Engine, PrecheckCommand, RootLabel, ScanOutcome,
+    ScanRequest, WalkFilter,
+};
+use crate::heartbeat::HeartbeatConfig;
+use crate::limits::ResourceProfile;
+use crate::output::DEFAULT_PARTIAL_SUFFIX;
+use crate::plugins;
+use crate::precheck::{parse_size, PrecheckRequest};
+use crate::probe::{self, SniffRequest};
+use crate::rules;
+use crate::sanitize::{self, SanitizeRequest};
+use crate::server::{self, ServeRequest};
+use crate::service::{self, ServiceInstallRequest, ServiceRunRequest, ServiceTask};
+use crate::similar::{self, SimilarRequest};
+use crate::simulate::{self, SimulateRequest};
+use crate::sniff::{ClaimedMimeMap, CustomSignature};
+use crate::validators::{ValidatorRegistry, ValidatorRoute, VALIDATOR_ROUTES};
+use crate::watch::{self, WatchRequest};
+use anyhow::{bail, Context, Result};
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// Objeto auxiliar para executar a CLI.
 pub struct GuardUploadCli;
@@ -18,54 +46,346 @@ pub struct GuardUploadCli;
 #[command(
     name = "guardupload",
     version,
+    disable_version_flag = true,
     about = "GuardUpload — validação segura de uploads"
 )]
 pub struct Cli {
+    /// Exibe a versão.
+    #[arg(short = 'V', long, action = ArgAction::SetTrue)]
+    version: bool,
+
+    /// Com `--version`, emite versão, commit, features e rotas de validadores em JSON.
+    #[arg(long, requires = "version")]
+    json: bool,
+
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
+}
+
+/// Informações de build para orquestradores conferirem a capacidade do scanner.
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub git_commit: &'static str,
+    pub target: String,
+    pub features: Vec<&'static str>,
+    pub validators: Vec<&'static str>,
+    pub routes: &'static [ValidatorRoute],
+    /// Backend de descompressão ZIP/gzip (`miniz_oxide` ou `zlib-ng`).
+    pub decompression: &'static str,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        let mut validators: Vec<&'static str> = VALIDATOR_ROUTES
+            .iter()
+            .map(|route| route.validator)
+            .collect();
+        validators.sort_unstable();
+        validators.dedup();
+        Self {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            git_commit: env!("GUARDUPLOAD_GIT_COMMIT"),
+            target: format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+            features: enabled_features(),
+            validators,
+            routes: VALIDATOR_ROUTES,
+            decompression: default_backend().name(),
+        }
+    }
+}
+
+fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "cli") {
+        features.push("cli");
+    }
+    if cfg!(feature = "zlib-ng") {
+        features.push("zlib-ng");
+    }
+    if cfg!(feature = "embedded-magic") {
+        features.push("embedded-magic");
+    }
+    features
 }
 
 /// Conjunto de subcomandos disponíveis.
 #[derive(Debug, Subcommand)]
 enum Commands {
     /// Executa varredura em arquivos, diretórios ou stdin.
-    Scan(ScanArgs),
+    Scan(Box<ScanArgs>),
     /// Executa medições de benchmark (stub inicial).
     Bench(BenchArgs),
+    /// Gerencia o cache de resultados do `scan --cache-dir`.
+    Cache(CacheArgs),
+    /// Gerencia corpora de teste locais.
+    Corpus(CorpusArgs),
+    /// Descreve um código de regra (`GU-PDF-DENY`) e como tratá-lo.
+    Explain(ExplainArgs),
+    /// Sobe uma API HTTP (`POST /v1/scan`) com o mesmo pipeline do `scan`.
+    Serve(ServeArgs),
+    /// Ferramentas sobre a política (simulação de overrides).
+    Policy(PolicyArgs),
+    /// Avalia as regras de metadados (MIME, tamanho, nome) antes do envio do arquivo.
+    Precheck(PrecheckArgs),
+    /// Reconstrói imagens, PDFs e ZIPs sem conteúdo ativo (CDR) em outro diretório.
+    Sanitize(SanitizeArgs),
+    /// Instala ou executa `watch`/`serve` como serviço (systemd ou Windows).
+    Service(ServiceArgs),
+    /// Agrupa um relatório JSONL (`scan --hash tlsh`) por distância TLSH.
+    Similar(SimilarArgs),
+    /// Mostra só o sniff de MIME (real, magic, extensão e confiança), sem validadores.
+    Sniff(SniffArgs),
+    /// Lista os validadores registrados, os MIMEs, chaves de política e regras de cada um.
+    Validators(ValidatorsArgs),
+    /// Monitora um diretório e analisa arquivos novos ou alterados.
+    Watch(WatchArgs),
 }
 
 /// Opções do subcomando `scan`.
 #[derive(Debug, Args)]
 pub struct ScanArgs {
     /// Caminhos de arquivos ou diretórios a serem verificados.
-    #[arg(required = true)]
+    #[arg(required_unless_present = "input_json")]
     pub paths: Vec<PathBuf>,
 
+    /// Arquivo JSON/JSONL com entradas `{name, content_b64}` (`-` para stdin).
+    #[arg(long)]
+    pub input_json: Option<PathBuf>,
+
     /// Caminho para o arquivo de política YAML.
     #[arg(long)]
     pub policy: Option<PathBuf>,
 
+    /// Política embutida aplicada quando `--policy` não é informado.
+    #[arg(long, value_enum, default_value = "strict")]
+    pub builtin_policy: BuiltinPolicy,
+
     /// Caminho para salvar o relatório JSONL detalhado.
     #[arg(long)]
     pub json: Option<PathBuf>,
 
+    /// Diretório que recebe um relatório indentado por arquivo (`<sha256>.json`).
+    #[arg(long)]
+    pub json_dir: Option<PathBuf>,
+
     /// Caminho para salvar o resumo agregado.
     #[arg(long)]
     pub summary: Option<PathBuf>,
 
-    /// Ação quando encontrar WARN/DENY/ERROR.
-    #[arg(long, value_enum, default_value = "deny")]
-    pub fail_on: FailOn,
+    /// Resumo de uma execução anterior; o novo resumo ganha `trend` com as variações.
+    #[arg(long, requires = "summary")]
+    pub previous_summary: Option<PathBuf>,
+
+    /// Caminho para salvar o relatório HTML autocontido.
+    #[arg(long)]
+    pub html: Option<PathBuf>,
+
+    /// Caminho para salvar o relatório JUnit XML (um testcase por arquivo).
+    #[arg(long)]
+    pub junit: Option<PathBuf>,
+
+    /// Sufixo dos arquivos de saída em andamento; cada um é renomeado para o nome final ao concluir.
+    #[arg(long, value_name = "SUFIXO", default_value = DEFAULT_PARTIAL_SUFFIX)]
+    pub partial_suffix: String,
+
+    /// Diretório que recebe uma cópia dos arquivos com decisão ALLOW.
+    #[arg(long)]
+    pub accept_dir: Option<PathBuf>,
+
+    /// Aceita por hard link em vez de cópia. O destino compartilha o inode com o
+    /// original: alterações posteriores no original aparecem no arquivo aceito.
+    #[arg(long, requires = "accept_dir")]
+    pub accept_hard_link: bool,
+
+    /// Diretório que recebe as imagens reescritas sem metadados (`image.strip_metadata: deny`).
+    #[arg(long)]
+    pub sanitized_dir: Option<PathBuf>,
+
+    /// Diretório que recebe prévias PNG (até 256 px) das imagens e PDFs com decisão DENY.
+    #[arg(long)]
+    pub previews_dir: Option<PathBuf>,
+
+    /// Formato de cada registro emitido (stdout ou `--json`).
+    #[arg(long, value_enum, default_value = "jsonl")]
+    pub format: OutputFormat,
+
+    /// Ação quando encontrar WARN/DENY/ERROR (padrão: `defaults.fail_on` da política ou `deny`).
+    #[arg(long, value_enum)]
+    pub fail_on: Option<FailOn>,
 
     /// Limite de tempo por arquivo.
     #[arg(long)]
     pub timeout: Option<u64>,
 
+    /// Registra o progresso do scan (arquivo atual, concluídos, taxa) a cada N segundos.
+    #[arg(long, value_name = "SEGUNDOS")]
+    pub heartbeat: Option<u64>,
+
+    /// Registra um dump dos validadores em execução quando um arquivo passa N segundos sem concluir.
+    #[arg(long, value_name = "SEGUNDOS")]
+    pub stall_after: Option<u64>,
+
+    /// Manifesto YAML/JSON extensão → MIME declarado (complementa a tabela embutida).
+    #[arg(long)]
+    pub claimed_mime_map: Option<PathBuf>,
+
+    /// Digests extras no relatório, além do SHA-256 (md5, sha1, sha512, blake3; repetível).
+    #[arg(long = "hash", value_name = "ALGO", value_delimiter = ',')]
+    pub hashes: Vec<HashAlgorithm>,
+
+    /// Limite de bytes lidos por arquivo; acima dele só início e fim são lidos.
+    #[arg(long)]
+    pub max_read_bytes: Option<u64>,
+
+    /// Perfil de recursos: `embedded` reduz memória (leitura parcial acima de
+    /// 16 MiB, validadores só por cabeçalho, buffers menores).
+    #[arg(long, default_value = "default")]
+    pub profile: ResourceProfile,
+
+    /// Agrupa os arquivos em uma submissão (campo `session` e regras da seção `session`).
+    #[arg(long)]
+    pub session_id: Option<String>,
+
+    /// Validadores listam todas as violações de cada arquivo em vez de parar na primeira.
+    #[arg(long)]
+    pub report_all_findings: bool,
+
+    /// Analisa só os arquivos que casam com o glob nas varreduras de diretório (repetível).
+    #[arg(long = "include", value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Ignora arquivos e diretórios que casam com o glob (repetível; vence `--include`).
+    #[arg(long = "exclude", value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Profundidade máxima abaixo de cada diretório informado (1 = só o próprio diretório).
+    #[arg(long)]
+    pub max_depth: Option<usize>,
+
+    /// Inclui arquivos e diretórios ocultos (iniciados por `.`); é o padrão.
+    #[arg(long, overrides_with = "no_hidden")]
+    pub hidden: bool,
+
+    /// Ignora arquivos e diretórios ocultos (iniciados por `.`).
+    #[arg(long, overrides_with = "hidden")]
+    pub no_hidden: bool,
+
+    /// Respeita `.gitignore`, `.git/info/exclude` e o gitignore global ao percorrer diretórios.
+    #[arg(long)]
+    pub respect_gitignore: bool,
+
+    /// Arquivo extra com regras no formato gitignore (repetível).
+    #[arg(long = "ignore-file", value_name = "PATH")]
+    pub ignore_files: Vec<PathBuf>,
+
+    /// Nomeia uma raiz (`nome=caminho`, repetível): registros ganham `root` e `relative_path`.
+    #[arg(long = "label", value_name = "NOME=CAMINHO")]
+    pub labels: Vec<RootLabel>,
+
+    /// Serializa registros, SARIF e resumo em JSON canônico (RFC 8785), próprio para hash e assinatura.
+    #[arg(long)]
+    pub canonical_json: bool,
+
+    /// Envia cada decisão WARN/DENY como JSON ao endpoint (substitui `notifications.webhook_url`).
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// Reutiliza resultados de execuções anteriores (chave: SHA-256, política e versão).
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Ignora `--cache-dir` nesta execução.
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Nível de log global.
+    #[arg(long, value_enum, default_value = "info")]
+    pub log_level: LogLevel,
+}
+
+/// Opções do subcomando `serve`.
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Endereço de escuta (`host:porta`).
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    pub bind: String,
+
+    /// Caminho para o arquivo de política YAML (recarregado quando alterado).
+    #[arg(long)]
+    pub policy: Option<PathBuf>,
+
+    /// Política embutida aplicada quando `--policy` não é informado.
+    #[arg(long, value_enum, default_value = "strict")]
+    pub builtin_policy: BuiltinPolicy,
+
+    /// Tamanho máximo do corpo da requisição, em MB.
+    #[arg(long, default_value_t = 25)]
+    pub max_body_mb: u64,
+
+    /// Número máximo de análises simultâneas.
+    #[arg(long, default_value_t = 4)]
+    pub concurrency: usize,
+
+    /// Limite de tempo por requisição.
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Manifesto YAML/JSON extensão → MIME declarado.
+    #[arg(long)]
+    pub claimed_mime_map: Option<PathBuf>,
+
+    /// Grava as métricas Prometheus periodicamente no arquivo (textfile collector).
+    #[arg(long)]
+    pub metrics_textfile: Option<PathBuf>,
+
+    /// Intervalo entre gravações de `--metrics-textfile`, em segundos.
+    #[arg(long, default_value_t = 15)]
+    pub metrics_interval_secs: u64,
+
+    /// Envia cada decisão WARN/DENY como JSON ao endpoint (substitui `notifications.webhook_url`).
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
     /// Nível de log global.
     #[arg(long, value_enum, default_value = "info")]
     pub log_level: LogLevel,
 }
 
+/// Opções do subcomando `precheck`.
+#[derive(Debug, Args)]
+pub struct PrecheckArgs {
+    /// MIME declarado pelo cliente.
+    #[arg(long)]
+    pub mime: String,
+
+    /// Tamanho do arquivo (`12MB`, `512k`, bytes).
+    #[arg(long, value_parser = parse_size_arg)]
+    pub size: Option<u64>,
+
+    /// Nome do arquivo, para as regras de nome e de extensão.
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Caminho para o arquivo de política YAML.
+    #[arg(long)]
+    pub policy: Option<PathBuf>,
+
+    /// Política embutida aplicada quando `--policy` não é informado.
+    #[arg(long, value_enum, default_value = "strict")]
+    pub builtin_policy: BuiltinPolicy,
+
+    /// Manifesto YAML/JSON extensão → MIME declarado.
+    #[arg(long)]
+    pub claimed_mime_map: Option<PathBuf>,
+}
+
+fn parse_size_arg(raw: &str) -> Result<u64, String> {
+    parse_size(raw).map_err(|err| format!("{err:#}"))
+}
+
 /// Opções do subcomando `bench`.
 #[derive(Debug, Args)]
 pub struct BenchArgs {
@@ -78,6 +398,294 @@ pub struct BenchArgs {
     pub report: Option<PathBuf>,
 }
 
+/// Opções do subcomando `corpus`.
+#[derive(Debug, Args)]
+pub struct CorpusArgs {
+    #[command(subcommand)]
+    command: CorpusCommand,
+}
+
+/// Operações disponíveis sobre corpora.
+#[derive(Debug, Subcommand)]
+enum CorpusCommand {
+    /// Baixa ou atualiza um corpus a partir de um manifesto (URL ou caminho).
+    Fetch(CorpusFetchArgs),
+}
+
+/// Opções do `corpus fetch`.
+#[derive(Debug, Args)]
+pub struct CorpusFetchArgs {
+    /// URL (ou caminho local) do manifesto JSON do corpus.
+    pub manifest: String,
+
+    /// Diretório de destino do corpus.
+    #[arg(long, default_value = "corpus")]
+    pub dest: PathBuf,
+}
+
+/// Opções do subcomando `cache`.
+#[derive(Debug, Args)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    command: CacheCommand,
+}
+
+/// Operações disponíveis sobre o cache.
+#[derive(Debug, Subcommand)]
+enum CacheCommand {
+    /// Remove as entradas do cache de resultados.
+    Clear(CacheClearArgs),
+}
+
+/// Opções do `cache clear`.
+#[derive(Debug, Args)]
+pub struct CacheClearArgs {
+    /// Diretório usado em `scan --cache-dir`.
+    #[arg(long)]
+    pub cache_dir: PathBuf,
+}
+
+/// Opções do subcomando `policy`.
+#[derive(Debug, Args)]
+pub struct PolicyArgs {
+    #[command(subcommand)]
+    command: PolicyCommand,
+}
+
+/// Operações disponíveis sobre a política.
+#[derive(Debug, Subcommand)]
+enum PolicyCommand {
+    /// Refaz só a decisão de um relatório gravado com overrides propostos e resume as mudanças.
+    Simulate(PolicySimulateArgs),
+}
+
+/// Opções do `policy simulate`.
+#[derive(Debug, Args)]
+pub struct PolicySimulateArgs {
+    /// Relatório do `scan` (JSONL, `pretty` ou `json-array`) com os fatos de cada arquivo.
+    #[arg(long = "json")]
+    pub report: PathBuf,
+
+    /// Override proposto em YAML ou em uma linha (`if_mime: [image/*] set: {max_size_mb: 5}`); repetível.
+    #[arg(long = "override", required = true)]
+    pub overrides: Vec<PolicyOverride>,
+
+    /// Política base do relatório.
+    #[arg(long)]
+    pub policy: Option<PathBuf>,
+
+    /// Política embutida usada como base quando `--policy` não é informado.
+    #[arg(long, value_enum, default_value = "strict")]
+    pub builtin_policy: BuiltinPolicy,
+}
+
+/// Opções do subcomando `sanitize`.
+#[derive(Debug, Args)]
+pub struct SanitizeArgs {
+    /// Arquivos ou diretórios a reconstruir.
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+
+    /// Diretório que recebe as versões reconstruídas.
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// JSONL com o mapeamento original → reconstruído (stdout quando ausente).
+    #[arg(long)]
+    pub json: Option<PathBuf>,
+}
+
+/// Opções do subcomando `sniff`.
+#[derive(Debug, Args)]
+pub struct SniffArgs {
+    /// Arquivos ou diretórios a inspecionar.
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+
+    /// Emite um objeto JSON por arquivo em vez do texto tabulado.
+    #[arg(long)]
+    pub json: bool,
+
+    /// Manifesto YAML/JSON extensão → MIME declarado.
+    #[arg(long)]
+    pub claimed_mime_map: Option<PathBuf>,
+
+    /// Política cujas assinaturas `sniff.custom` valem antes da base magic.
+    #[arg(long)]
+    pub policy: Option<PathBuf>,
+}
+
+/// Opções do subcomando `explain`.
+#[derive(Debug, Args)]
+pub struct ExplainArgs {
+    /// Código da regra (`rule_codes` do relatório); sem código, lista o catálogo.
+    pub code: Option<String>,
+}
+
+/// Opções do subcomando `validators`.
+#[derive(Debug, Args)]
+pub struct ValidatorsArgs {
+    #[command(subcommand)]
+    command: ValidatorsCommand,
+}
+
+/// Operações disponíveis sobre o registro de validadores.
+#[derive(Debug, Subcommand)]
+enum ValidatorsCommand {
+    /// Lista os validadores na ordem de despacho.
+    List(ValidatorsListArgs),
+}
+
+/// Opções do `validators list`.
+#[derive(Debug, Args)]
+pub struct ValidatorsListArgs {
+    /// Emite um array JSON em vez da tabela.
+    #[arg(long)]
+    pub json: bool,
+
+    /// Política cujos plugins e clamd entram no registro.
+    #[arg(long)]
+    pub policy: Option<PathBuf>,
+}
+
+/// Opções do subcomando `watch`.
+#[derive(Debug, Args)]
+pub struct WatchArgs {
+    /// Diretório monitorado (recursivo).
+    pub dir: PathBuf,
+
+    /// Caminho para o arquivo de política YAML.
+    #[arg(long)]
+    pub policy: Option<PathBuf>,
+
+    /// Política embutida aplicada quando `--policy` não é informado.
+    #[arg(long, value_enum, default_value = "strict")]
+    pub builtin_policy: BuiltinPolicy,
+
+    /// JSONL onde os registros são acrescentados (stdout quando ausente).
+    #[arg(long)]
+    pub json: Option<PathBuf>,
+
+    /// Diretório para onde arquivos com decisão DENY são movidos.
+    #[arg(long)]
+    pub quarantine_dir: Option<PathBuf>,
+
+    /// Manifesto YAML/JSON extensão → MIME declarado.
+    #[arg(long)]
+    pub claimed_mime_map: Option<PathBuf>,
+
+    /// Digests extras no relatório (como no `scan`).
+    #[arg(long = "hash", value_name = "ALGO", value_delimiter = ',')]
+    pub hashes: Vec<HashAlgorithm>,
+
+    /// Limite de tempo por arquivo.
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Limite de bytes lidos por arquivo (como no `scan`).
+    #[arg(long)]
+    pub max_read_bytes: Option<u64>,
+
+    /// Perfil de recursos (como no `scan`).
+    #[arg(long, default_value = "default")]
+    pub profile: ResourceProfile,
+
+    /// Milissegundos sem novos eventos antes de analisar um arquivo.
+    #[arg(long, default_value_t = 500)]
+    pub settle_ms: u64,
+
+    /// Grava registros só para arquivos novos ou cuja decisão mudou.
+    #[arg(long)]
+    pub changes_only: bool,
+
+    /// Grava as métricas Prometheus periodicamente no arquivo (textfile collector).
+    #[arg(long)]
+    pub metrics_textfile: Option<PathBuf>,
+
+    /// Intervalo entre gravações de `--metrics-textfile`, em segundos.
+    #[arg(long, default_value_t = 15)]
+    pub metrics_interval_secs: u64,
+
+    /// Envia cada decisão WARN/DENY como JSON ao endpoint (substitui `notifications.webhook_url`).
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// Nível de log global.
+    #[arg(long, value_enum, default_value = "info")]
+    pub log_level: LogLevel,
+}
+
+/// Opções do subcomando `service`.
+#[derive(Debug, Args)]
+pub struct ServiceArgs {
+    #[command(subcommand)]
+    command: ServiceCommand,
+}
+
+/// Operações disponíveis sobre o serviço.
+#[derive(Debug, Subcommand)]
+enum ServiceCommand {
+    /// Gera e instala a unit systemd (Unix) ou registra o serviço no SCM (Windows).
+    Install(ServiceInstallArgs),
+    /// Executa a tarefa em primeiro plano, encerrando em SIGTERM/Stop do serviço.
+    Run(ServiceRunArgs),
+}
+
+/// Opções do `service install`.
+#[derive(Debug, Args)]
+pub struct ServiceInstallArgs {
+    /// Nome da unit systemd / serviço Windows.
+    #[arg(long, default_value = service::DEFAULT_SERVICE_NAME)]
+    pub name: String,
+
+    /// Arquivo que recebe os logs do serviço (acrescentados ao final).
+    #[arg(long)]
+    pub log_file: Option<PathBuf>,
+
+    /// Segundos de espera antes de reiniciar após falha.
+    #[arg(long, default_value_t = 5)]
+    pub restart_sec: u64,
+
+    /// Diretório das units systemd.
+    #[arg(long, default_value = "/etc/systemd/system")]
+    pub unit_dir: PathBuf,
+
+    /// Apenas imprime a unit (ou os comandos `sc.exe`), sem instalar.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Tarefa executada pelo serviço, após `--` (ex.: `-- watch /srv/uploads --quarantine-dir /srv/q`).
+    #[arg(last = true, required = true)]
+    pub args: Vec<String>,
+}
+
+/// Opções do `service run`.
+#[derive(Debug, Args)]
+pub struct ServiceRunArgs {
+    /// Nome do serviço (usado pelo SCM no Windows).
+    #[arg(long, default_value = service::DEFAULT_SERVICE_NAME)]
+    pub name: String,
+
+    /// Arquivo que recebe os logs (acrescentados ao final) em vez do stdout.
+    #[arg(long)]
+    pub log_file: Option<PathBuf>,
+
+    /// Tarefa executada, após `--` (`watch ...` ou `serve ...`).
+    #[arg(last = true, required = true)]
+    pub args: Vec<String>,
+}
+
+/// Opções do subcomando `similar`.
+#[derive(Debug, Args)]
+pub struct SimilarArgs {
+    /// Relatório JSONL com `hashes.tlsh` (gerado por `scan --hash tlsh`).
+    pub report: PathBuf,
+
+    /// Distância TLSH máxima entre arquivos do mesmo grupo.
+    #[arg(long, default_value_t = 50)]
+    pub threshold: u32,
+}
+
 /// Representa as escolhas do parâmetro --fail-on.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
 pub enum FailOn {
@@ -86,6 +694,32 @@ pub enum FailOn {
     Error,
 }
 
+/// Políticas embutidas disponíveis sem arquivo YAML.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum BuiltinPolicy {
+    /// Limite de 25 MB, bloqueio de executáveis/scripts e checagens de arquivos compactados.
+    Strict,
+    /// Bloqueia apenas executáveis e arquivos compactados inseguros.
+    Permissive,
+    /// Sem política: tudo é ALLOW (exceto falhas de validadores).
+    None,
+}
+
+/// Formatos de saída por arquivo.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// `FileReport` completo, um JSON por linha.
+    Jsonl,
+    /// Array JSON único com os `FileReport`, um por linha.
+    JsonArray,
+    /// `FileReport` indentado, um documento após o outro (ignorado com `--canonical-json`).
+    Pretty,
+    /// Apenas `{file, decision, rules}` por linha.
+    Verdict,
+    /// Documento SARIF 2.1.0 único ao final do scan.
+    Sarif,
+}
+
 /// Representa os níveis de log aceitos pela CLI.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
 pub enum LogLevel {
@@ -112,14 +746,26 @@ impl GuardUploadCli {
     /// Analisa argumentos, inicializa observabilidade e delega ao engine.
     pub fn run() -> Result<i32> {
         let cli = Cli::parse();
+        if cli.version {
+            if cli.json {
+                println!("{}", serde_json::to_string(&BuildInfo::current())?);
+            } else {
+                println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+            }
+            return Ok(0);
+        }
+        let Some(command) = cli.command else {
+            Cli::command().print_help()?;
+            return Ok(2);
+        };
 
-        let log_level = cli.scan_log_level();
-        configure_logging(log_level);
+        let log_level = command.log_level();
+        configure_logging(log_level, command.log_file())?;
 
         let engine = Engine::new();
-        let exit_code = match cli.command {
+        let exit_code = match command {
             Commands::Scan(args) => {
-                let request = ScanRequest::from(args);
+                let request = ScanRequest::from(*args);
                 let outcome: ScanOutcome = engine.scan(request)?;
                 outcome.exit_code
             }
@@ -128,40 +774,318 @@ impl GuardUploadCli {
                 let outcome: BenchOutcome = engine.bench(request)?;
                 outcome.exit_code
             }
+            Commands::Cache(args) => match args.command {
+                CacheCommand::Clear(args) => {
+                    let removed = cache::clear(&args.cache_dir)?;
+                    tracing::info!(
+                        removed,
+                        cache = %args.cache_dir.display(),
+                        "cache de resultados limpo"
+                    );
+                    0
+                }
+            },
+            Commands::Corpus(args) => match args.command {
+                CorpusCommand::Fetch(args) => {
+                    let request = CorpusFetchRequest::from(args);
+                    let outcome = corpus::fetch(&request)?;
+                    tracing::info!(
+                        downloaded = outcome.downloaded,
+                        up_to_date = outcome.up_to_date,
+                        dest = %request.dest.display(),
+                        "corpus atualizado"
+                    );
+                    outcome.exit_code
+                }
+            },
+            Commands::Explain(args) => {
+                match args.code.as_deref() {
+                    Some(code) => {
+                        let Some(doc) = rules::explain(code) else {
+                            bail!("código de regra desconhecido: {code}");
+                        };
+                        println!("{} ({})", doc.code, doc.rule);
+                        println!("{}", doc.description);
+                        println!("Como tratar: {}", doc.remediation);
+                    }
+                    None => {
+                        for doc in rules::catalog() {
+                            println!("{:<16} {:<36} {}", doc.code, doc.rule, doc.description);
+                        }
+                        println!(
+"{:<16} {:<36} Resultado de validador (DENY, WARN, ERROR, TIMEOUT, FALLBACK).",
+"GU-<VALIDADOR>-*", "validator:<nome>:*"
+);
+                    }
+                }
+                0
+            }
+            Commands::Serve(args) => {
+                server::serve(ServeRequest::from(args), Arc::new(AtomicBool::new(false)))?;
+                0
+            }
+            Commands::Policy(args) => match args.command {
+                PolicyCommand::Simulate(args) => {
+                    let summary = simulate::run(SimulateRequest {
+                        report: args.report,
+                        policy: load_policy(args.policy.as_deref(), args.builtin_policy)?,
+                        overrides: args.overrides,
+                    })?;
+                    println!("{}", serde_json::to_string_pretty(&summary)?);
+                    0
+                }
+            },
+            Commands::Precheck(args) => {
+                let outcome = engine.precheck(PrecheckCommand::from(args))?;
+                println!("{}", serde_json::to_string(&outcome.report)?);
+                outcome.exit_code
+            }
+            Commands::Sanitize(args) => {
+                let outcome = sanitize::run(&SanitizeRequest {
+                    paths: args.paths,
+                    out: args.out,
+                    json: args.json,
+                })?;
+                tracing::info!(
+                    sanitized = outcome.sanitized,
+                    unsupported = outcome.unsupported,
+                    failed = outcome.failed,
+                    "reconstrução concluída"
+                );
+                outcome.exit_code
+            }
+            Commands::Service(args) => match args.command {
+                ServiceCommand::Install(args) => {
+                    parse_service_task(&args.args)?;
+                    service::install(&ServiceInstallRequest::from(args))?;
+                    0
+                }
+                ServiceCommand::Run(args) => {
+                    let (task, _) = parse_service_task(&args.args)?;
+                    service::run(ServiceRunRequest {
+                        name: args.name,
+                        task,
+                    })?;
+                    0
+                }
+            },
+            Commands::Validators(args) => match args.command {
+                ValidatorsCommand::List(args) => {
+                    let capabilities = match args.policy.as_deref() {
+                        Some(path) => plugins::policy_engine(PolicyConfig::from_path(path)?)?
+                            .validators()
+                            .capabilities(),
+                        None => ValidatorRegistry::builtin().capabilities(),
+                    };
+                    if args.json {
+                        println!("{}", serde_json::to_string_pretty(&capabilities)?);
+                    } else {
+                        for capability in &capabilities {
+                            let role = match capability.supplements {
+                                Some(target) => format!("complementa {target}"),
+                                None => "principal".to_string(),
+                            };
+                            println!(
+                                "{:<12} {:<8} {:<20} {}",
+                                capability.name,
+                                capability.version.to_string(),
+                                role,
+                                capability.mime_patterns.join(", ")
+                            );
+                        }
+                    }
+                    0
+                }
+            },
+            Commands::Watch(args) => {
+                watch::watch(WatchRequest::from(args), Arc::new(AtomicBool::new(false)))?;
+                0
+            }
+            Commands::Sniff(args) => {
+                let claimed_mimes = match args.claimed_mime_map.as_deref() {
+                    Some(path) => ClaimedMimeMap::from_path(path)?,
+                    None => ClaimedMimeMap::default(),
+                };
+                let custom_signatures = match args.policy.as_deref() {
+                    Some(path) => PolicyConfig::from_path(path)?
+                        .sniff
+                        .custom
+                        .iter()
+                        .map(CustomSignature::compile)
+                        .collect::<Result<_>>()?,
+                    None => Vec::new(),
+                };
+                let outcome = probe::run(&SniffRequest {
+                    paths: args.paths,
+                    json: args.json,
+                    claimed_mimes,
+                    custom_signatures,
+                })?;
+                tracing::debug!(
+                    sniffed = outcome.sniffed,
+                    mismatches = outcome.mismatches,
+                    failed = outcome.failed,
+                    "sniff concluído"
+                );
+                outcome.exit_code
+            }
+            Commands::Similar(args) => {
+                let outcome = similar::run(&SimilarRequest::from(args))?;
+                for cluster in &outcome.clusters {
+                    println!("{}", serde_json::to_string(cluster)?);
+                }
+                tracing::info!(
+                    clusters = outcome.clusters.len(),
+                    skipped = outcome.skipped,
+                    "agrupamento por similaridade concluído"
+                );
+                outcome.exit_code
+            }
         };
         Ok(exit_code)
     }
 }
 
-impl Cli {
-    fn scan_log_level(&self) -> LogLevel {
-        match &self.command {
+impl Commands {
+    fn log_level(&self) -> LogLevel {
+        match self {
             Commands::Scan(args) => args.log_level,
-            Commands::Bench(_) => LogLevel::Info,
+            Commands::Serve(args) => args.log_level,
+            Commands::Watch(args) => args.log_level,
+            Commands::Service(ServiceArgs {
+                command: ServiceCommand::Run(args),
+            }) => parse_service_task(&args.args)
+                .map(|(_, level)| level)
+                .unwrap_or(LogLevel::Info),
+            Commands::Bench(_)
+            | Commands::Cache(_)
+            | Commands::Corpus(_)
+            | Commands::Explain(_)
+            | Commands::Policy(_)
+            | Commands::Precheck(_)
+            | Commands::Sanitize(_)
+            | Commands::Service(_)
+            | Commands::Similar(_)
+            | Commands::Sniff(_)
+            | Commands::Validators(_) => LogLevel::Info,
+        }
+    }
+
+    fn log_file(&self) -> Option<&Path> {
+        match self {
+            Commands::Service(ServiceArgs {
+                command: ServiceCommand::Run(args),
+            }) => args.log_file.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Interpreta a tarefa de um serviço com o mesmo parser da CLI; apenas `watch` e `serve`.
+fn parse_service_task(args: &[String]) -> Result<(ServiceTask, LogLevel)> {
+    let cli =
+        Cli::try_parse_from(std::iter::once("guardupload").chain(args.iter().map(String::as_str)))?;
+    match cli.command {
+        Some(Commands::Watch(args)) => {
+            let level = args.log_level;
+            Ok((ServiceTask::Watch(WatchRequest::from(args)), level))
         }
+        Some(Commands::Serve(args)) => {
+            let level = args.log_level;
+            Ok((ServiceTask::Serve(ServeRequest::from(args)), level))
+        }
+        _ => bail!("o serviço aceita apenas as tarefas `watch` ou `serve`"),
     }
 }
 
-fn configure_logging(level: LogLevel) {
+fn configure_logging(level: LogLevel, log_file: Option<&Path>) -> Result<()> {
     use tracing_subscriber::fmt::format::FmtSpan;
     let lvl: tracing::Level = level.into();
-    let subscriber = tracing_subscriber::fmt()
+    let builder = tracing_subscriber::fmt()
         .with_max_level(lvl)
         .json()
-        .with_span_events(FmtSpan::ENTER | FmtSpan::EXIT)
-        .finish();
-    let _ = tracing::subscriber::set_global_default(subscriber);
+        .with_span_events(FmtSpan::CLOSE);
+    let _ = match log_file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| {
+                    format!("não foi possível abrir arquivo de log {}", path.display())
+                })?;
+            tracing::subscriber::set_global_default(
+                builder
+                    .with_ansi(false)
+                    .with_writer(Mutex::new(file))
+                    .finish(),
+            )
+        }
+        None => tracing::subscriber::set_global_default(builder.finish()),
+    };
+    Ok(())
 }
 
 impl From<ScanArgs> for ScanRequest {
     fn from(args: ScanArgs) -> Self {
         Self {
             paths: args.paths,
+            input_json: args.input_json,
             policy: args.policy,
+            builtin_policy: args.builtin_policy,
             json: args.json,
+            json_dir: args.json_dir,
             summary: args.summary,
+            previous_summary: args.previous_summary,
+            html: args.html,
+            junit: args.junit,
+            partial_suffix: args.partial_suffix,
+            accept_dir: args.accept_dir,
+            accept_hard_link: args.accept_hard_link,
+            sanitized_dir: args.sanitized_dir,
+            previews_dir: args.previews_dir,
+            format: args.format,
             fail_on: args.fail_on,
             timeout: args.timeout,
+            heartbeat: HeartbeatConfig {
+                interval: args.heartbeat.map(Duration::from_secs),
+                stall_after: args.stall_after.map(Duration::from_secs),
+            },
+            claimed_mime_map: args.claimed_mime_map,
+            hashes: args.hashes,
+            max_read_bytes: args.max_read_bytes,
+            profile: args.profile,
+            session_id: args.session_id,
+            report_all_findings: args.report_all_findings,
+            canonical_json: args.canonical_json,
+            webhook: args.webhook,
+            cache_dir: args.cache_dir.filter(|_| !args.no_cache),
+            walk: WalkFilter {
+                include: args.include,
+                exclude: args.exclude,
+                max_depth: args.max_depth,
+                hidden: !args.no_hidden,
+                respect_gitignore: args.respect_gitignore,
+                ignore_files: args.ignore_files,
+            },
+            labels: args.labels,
+        }
+    }
+}
+
+impl From<ServeArgs> for ServeRequest {
+    fn from(args: ServeArgs) -> Self {
+        Self {
+            bind: args.bind,
+            policy: args.policy,
+            builtin_policy: args.builtin_policy,
+            max_body_bytes: args.max_body_mb * 1024 * 1024,
+            concurrency: args.concurrency,
+            timeout: args.timeout,
+            claimed_mime_map: args.claimed_mime_map,
+            metrics_textfile: args.metrics_textfile,
+            metrics_interval: Duration::from_secs(args.metrics_interval_secs),
+            webhook: args.webhook,
         }
     }
 }
@@ -174,3 +1098,149 @@ impl From<BenchArgs> for BenchRequest {
         }
     }
 }
+
+impl From<WatchArgs> for WatchRequest {
+    fn from(args: WatchArgs) -> Self {
+        Self {
+            dir: args.dir,
+            policy: args.policy,
+            builtin_policy: args.builtin_policy,
+            json: args.json,
+            quarantine_dir: args.quarantine_dir,
+            claimed_mime_map: args.claimed_mime_map,
+            hashes: args.hashes,
+            timeout: args.timeout,
+            max_read_bytes: args.max_read_bytes,
+            profile: args.profile,
+            settle_ms: args.settle_ms,
+            changes_only: args.changes_only,
+            metrics_textfile: args.metrics_textfile,
+            metrics_interval: Duration::from_secs(args.metrics_interval_secs),
+            webhook: args.webhook,
+        }
+    }
+}
+
+impl From<ServiceInstallArgs> for ServiceInstallRequest {
+    fn from(args: ServiceInstallArgs) -> Self {
+        Self {
+            name: args.name,
+            log_file: args.log_file,
+            restart_sec: args.restart_sec,
+            unit_dir: args.unit_dir,
+            dry_run: args.dry_run,
+            args: args.args,
+        }
+    }
+}
+
+impl From<PrecheckArgs> for PrecheckCommand {
+    fn from(args: PrecheckArgs) -> Self {
+        Self {
+            metadata: PrecheckRequest {
+                mime: args.mime,
+                size_bytes: args.size,
+                name: args.name,
+            },
+            policy: args.policy,
+            builtin_policy: args.builtin_policy,
+            claimed_mime_map: args.claimed_mime_map,
+        }
+    }
+}
+
+impl From<SimilarArgs> for SimilarRequest {
+    fn from(args: SimilarArgs) -> Self {
+        Self {
+            report: args.report,
+            threshold: args.threshold,
+        }
+    }
+}
+
+impl From<CorpusFetchArgs> for CorpusFetchRequest {
+    fn from(args: CorpusFetchArgs) -> Self {
+        Self {
+            manifest: args.manifest,
+            dest: args.dest,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_json_requires_version_and_lists_routes() {
+        let cli = Cli::try_parse_from(["guardupload", "--version", "--json"]).expect("parse");
+        assert!(cli.version && cli.json && cli.command.is_none());
+        assert!(Cli::try_parse_from(["guardupload", "--json"]).is_err());
+
+        let info = serde_json::to_value(BuildInfo::current()).expect("json");
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert!(!info["git_commit"].as_str().unwrap_or_default().is_empty());
+        assert!(info["validators"]
+            .as_array()
+            .is_some_and(|validators| validators.iter().any(|name| name == "pdf")));
+        assert_eq!(info["routes"][0]["validator"], "pkcs7");
+    }
+
+    #[test]
+    fn service_task_accepts_only_watch_or_serve() {
+        let args = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+        let (task, level) =
+            parse_service_task(&args("watch /srv/uploads --log-level debug")).expect("watch");
+        assert!(
+            matches!(task, ServiceTask::Watch(ref request) if request.dir == Path::new("/srv/uploads"))
+        );
+        assert_eq!(level, LogLevel::Debug);
+        assert!(parse_service_task(&args("serve --bind 127.0.0.1:9000")).is_ok());
+        assert!(parse_service_task(&args("scan /tmp")).is_err());
+
+        let cli = Cli::try_parse_from([
+            "guardupload",
+            "service",
+            "run",
+            "--log-file",
+            "/tmp/g.log",
+            "--",
+            "watch",
+            "/srv",
+        ])
+        .expect("parse");
+        let command = cli.command.expect("command");
+        assert_eq!(command.log_file(), Some(Path::new("/tmp/g.log")));
+    }
+
+    #[test]
+    fn failed_accept_is_recorded_in_the_report() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, b"alpha").expect("write");
+        // Um arquivo comum no lugar do diretório de aceitos faz a cópia falhar.
+        let blocked = dir.path().join("aceitos");
+        std::fs::write(&blocked, b"").expect("write");
+        let json = dir.path().join("out.jsonl");
+        let cli = Cli::try_parse_from([
+            "guardupload".as_ref(),
+            "scan".as_ref(),
+            path.as_os_str(),
+            "--accept-dir".as_ref(),
+            blocked.as_os_str(),
+            "--json".as_ref(),
+            json.as_os_str(),
+        ])
+        .expect("parse");
+        let Some(Commands::Scan(args)) = cli.command else {
+            panic!("scan esperado");
+        };
+        let outcome = Engine::new().scan((*args).into()).expect("scan");
+        assert_eq!(outcome.exit_code, 1);
+        let written = std::fs::read_to_string(&json).expect("jsonl");
+        let record: serde_json::Value =
+            serde_json::from_str(written.lines().next().expect("record")).expect("json");
+        assert_eq!(record["policy"]["decision"], "DENY");
+        assert_eq!(record["policy"]["primary_rule"], "accept:failed");
+    }
+}
@@ -0,0 +1,227 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Download e atualização de corpora de teste verificados por hash.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// Nome do manifesto gravado no diretório de destino.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Manifesto de corpus (JSON) com os arquivos e seus hashes esperados.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusManifest {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub files: Vec<CorpusEntry>,
+}
+
+/// Arquivo individual do corpus.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusEntry {
+    /// Caminho relativo dentro do diretório de destino.
+    pub path: String,
+    /// URL (absoluta ou relativa ao manifesto) de onde baixar o conteúdo.
+    pub url: String,
+    /// SHA-256 esperado (hex).
+    pub sha256: String,
+    /// Decisão esperada (`ALLOW`/`WARN`/`DENY`) usada por `bench`.
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Requisição do subcomando `corpus fetch`.
+#[derive(Debug)]
+pub struct CorpusFetchRequest {
+    pub manifest: String,
+    pub dest: PathBuf,
+}
+
+/// Resultado do `corpus fetch`.
+#[derive(Debug)]
+pub struct CorpusFetchOutcome {
+    pub downloaded: usize,
+    pub up_to_date: usize,
+    pub exit_code: i32,
+}
+
+/// Baixa (ou atualiza) o corpus descrito no manifesto para `request.dest`.
+pub fn fetch(request: &CorpusFetchRequest) -> Result<CorpusFetchOutcome> {
+    let raw = read_source(&request.manifest)
+        .with_context(|| format!("falha ao obter manifesto {}", request.manifest))?;
+    let manifest: CorpusManifest = serde_json::from_slice(&raw)
+        .with_context(|| format!("manifesto inválido {}", request.manifest))?;
+
+    std::fs::create_dir_all(&request.dest)
+        .with_context(|| format!("não foi possível criar {}", request.dest.display()))?;
+
+    let mut downloaded = 0;
+    let mut up_to_date = 0;
+    for entry in &manifest.files {
+        let target = request.dest.join(safe_relative_path(&entry.path)?);
+        if target.is_file() && sha256_of_file(&target)?.eq_ignore_ascii_case(&entry.sha256) {
+            up_to_date += 1;
+            continue;
+        }
+
+        let source = resolve_url(&request.manifest, &entry.url);
+        let bytes =
+            read_source(&source).with_context(|| format!("falha ao baixar {}", entry.url))?;
+        let digest = hex::encode(Sha256::digest(&bytes));
+        if !digest.eq_ignore_ascii_case(&entry.sha256) {
+            bail!(
+                "hash divergente para {}: esperado {}, obtido {digest}",
+                entry.path,
+                entry.sha256
+            );
+        }
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = File::create(&target)
+            .with_context(|| format!("não foi possível gravar {}", target.display()))?;
+        file.write_all(&bytes)?;
+        downloaded += 1;
+        tracing::info!(file = %entry.path, "arquivo do corpus baixado");
+    }
+
+    let manifest_path = request.dest.join(MANIFEST_FILE);
+    std::fs::write(&manifest_path, serde_json::to_vec_pretty(&manifest)?)
+        .with_context(|| format!("não foi possível gravar {}", manifest_path.display()))?;
+
+    Ok(CorpusFetchOutcome {
+        downloaded,
+        up_to_date,
+        exit_code: 0,
+    })
+}
+
+fn is_remote(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+fn read_source(location: &str) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    if is_remote(location) {
+        let response = ureq::get(location).call()?;
+        response.into_reader().read_to_end(&mut buffer)?;
+    } else {
+        let path = location.strip_prefix("file://").unwrap_or(location);
+        File::open(path)?.read_to_end(&mut buffer)?;
+    }
+    Ok(buffer)
+}
+
+/// Resolve URLs relativas em relação ao local do manifesto.
+fn resolve_url(manifest: &str, url: &str) -> String {
+    if is_remote(url) || url.starts_with("file://") || url.starts_with('/') {
+        return url.to_string();
+    }
+    match manifest.rfind('/') {
+        Some(idx) => format!("{}/{url}", &manifest[..idx]),
+        None => url.to_string(),
+    }
+}
+
+/// Rejeita caminhos absolutos ou com `..` para evitar escrita fora do destino.
+fn safe_relative_path(raw: &str) -> Result<PathBuf> {
+    let path = Path::new(raw);
+    if path
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        bail!("caminho inseguro no manifesto: {raw}");
+    }
+    Ok(path.to_path_buf())
+}
+
+fn sha256_of_file(path: &Path) -> Result<String> {
+    let bytes = std::fs::read(path)?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn resolve_url_handles_relative_entries() {
+        assert_eq!(
+            resolve_url("https://example.com/corpus/manifest.json", "benign/a.txt"),
+            "https://example.com/corpus/benign/a.txt"
+        );
+        assert_eq!(
+            resolve_url(
+                "https://example.com/m.json",
+                "https://cdn.example.com/b.pdf"
+            ),
+            "https://cdn.example.com/b.pdf"
+        );
+    }
+
+    #[test]
+    fn safe_relative_path_rejects_traversal() {
+        assert!(safe_relative_path("benign/a.txt").is_ok());
+        assert!(safe_relative_path("../escape.txt").is_err());
+        assert!(safe_relative_path("/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn fetch_verifies_hashes_and_skips_up_to_date_files() {
+        let source = tempdir().expect("source");
+        let dest = tempdir().expect("dest");
+        std::fs::write(source.path().join("a.txt"), b"alpha").expect("write");
+        let manifest = CorpusManifest {
+            name: Some("teste".into()),
+            files: vec![CorpusEntry {
+                path: "benign/a.txt".into(),
+                url: "a.txt".into(),
+                sha256: hex::encode(Sha256::digest(b"alpha")),
+                label: Some("ALLOW".into()),
+            }],
+        };
+        let manifest_path = source.path().join("manifest.json");
+        std::fs::write(&manifest_path, serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+        let request = CorpusFetchRequest {
+            manifest: manifest_path.display().to_string(),
+            dest: dest.path().to_path_buf(),
+        };
+        let first = fetch(&request).expect("fetch");
+        assert_eq!(first.downloaded, 1);
+        assert!(dest.path().join("benign/a.txt").is_file());
+        assert!(dest.path().join(MANIFEST_FILE).is_file());
+
+        let second = fetch(&request).expect("refetch");
+        assert_eq!(second.downloaded, 0);
+        assert_eq!(second.up_to_date, 1);
+    }
+
+    #[test]
+    fn fetch_rejects_hash_mismatch() {
+        let source = tempdir().expect("source");
+        let dest = tempdir().expect("dest");
+        std::fs::write(source.path().join("a.txt"), b"alpha").expect("write");
+        let manifest = serde_json::json!({
+            "files": [{ "path": "a.txt", "url": "a.txt", "sha256": "00" }]
+        });
+        let manifest_path = source.path().join("manifest.json");
+        std::fs::write(&manifest_path, manifest.to_string()).unwrap();
+
+        let request = CorpusFetchRequest {
+            manifest: manifest_path.display().to_string(),
+            dest: dest.path().to_path_buf(),
+        };
+        assert!(fetch(&request).is_err());
+        assert!(!dest.path().join("a.txt").exists());
+    }
+}
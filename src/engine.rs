@@ -5,18 +5,45 @@
 
 //! Coordena o pipeline de sniffing, validação e decisão de política.
 
-use crate::cli::FailOn;
-use crate::config::PolicyConfig;
+use crate::analyzers::hashes::HashAlgorithm;
+use crate::cache::ResultCache;
+use crate::canonical;
+use crate::cli::{BuiltinPolicy, FailOn, OutputFormat};
+use crate::config::{PolicyConfig, RedactField, BUILTIN_PERMISSIVE_POLICY, BUILTIN_STRICT_POLICY};
+use crate::heartbeat::{Heartbeat, HeartbeatConfig};
+use crate::html::HtmlReportBuilder;
+use crate::junit::JunitReportBuilder;
+use crate::limits::{Deadline, ResourceProfile};
+use crate::output::{write_atomic, AtomicFile};
+use crate::pipeline::{self, InlineInput};
+use crate::plugins;
 use crate::policy::{Decision, DecisionOutcome, PolicyEngine};
-use crate::report::{FileReport, PolicyDecision, SniffReport, SummaryReport, ValidatorEntry};
-use crate::sniff;
-use crate::validators::evaluate_validators;
-use anyhow::{Context, Result};
+use crate::precheck::{precheck, PrecheckReport, PrecheckRequest};
+use crate::preview::{render_preview, PreviewError};
+use crate::rate_limit;
+use crate::report::{
+    escape_control_chars, ByteUsage, FileReport, PreviousSummary, ScanMetadata, SummaryReport,
+    TimingBreakdown, VerdictRecord,
+};
+use crate::resources;
+use crate::risk;
+use crate::sarif::SarifBuilder;
+use crate::sniff::ClaimedMimeMap;
+use crate::validators::{glob_matches, strip_metadata};
+use crate::webhook::Notifier;
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use ignore::WalkBuilder;
 use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{BufReader, Read, Write};
-use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Caracteres do SHA-256 registrados no span de cada arquivo.
+const SPAN_SHA256_PREFIX: usize = 12;
 
 /// Responsável por executar o fluxo completo para cada arquivo analisado.
 #[derive(Debug, Default)]
@@ -26,11 +53,157 @@ pub struct Engine;
 #[derive(Debug)]
 pub struct ScanRequest {
     pub paths: Vec<PathBuf>,
+    pub input_json: Option<PathBuf>,
     pub policy: Option<PathBuf>,
+    pub builtin_policy: BuiltinPolicy,
     pub json: Option<PathBuf>,
+    /// Um relatório indentado por arquivo, nomeado pelo SHA-256 do conteúdo.
+    pub json_dir: Option<PathBuf>,
     pub summary: Option<PathBuf>,
-    pub fail_on: FailOn,
+    /// Resumo anterior comparado ao atual (`trend` no resumo).
+    pub previous_summary: Option<PathBuf>,
+    pub html: Option<PathBuf>,
+    /// Relatório JUnit XML: WARN e DENY viram falhas de teste.
+    pub junit: Option<PathBuf>,
+    pub accept_dir: Option<PathBuf>,
+    /// Aceita por hard link em vez de cópia (`--accept-hard-link`).
+    pub accept_hard_link: bool,
+    /// Diretório que recebe as imagens sem metadados (`image.strip_metadata: deny`).
+    pub sanitized_dir: Option<PathBuf>,
+    /// Diretório das prévias PNG dos arquivos DENY (`<sha256>.png`).
+    pub previews_dir: Option<PathBuf>,
+    pub format: OutputFormat,
+    pub fail_on: Option<FailOn>,
     pub timeout: Option<u64>,
+    /// Heartbeat de progresso e detecção de travamento.
+    pub heartbeat: HeartbeatConfig,
+    /// Sufixo dos arquivos de saída em andamento, renomeados ao concluir.
+    pub partial_suffix: String,
+    pub claimed_mime_map: Option<PathBuf>,
+    pub hashes: Vec<HashAlgorithm>,
+    pub max_read_bytes: Option<u64>,
+    /// Perfil de recursos; `embedded` ajusta a política e o limite de leitura.
+    pub profile: ResourceProfile,
+    /// Sessão atribuída a todos os arquivos (entradas inline podem declarar a própria).
+    pub session_id: Option<String>,
+    /// Liga `defaults.report_all_findings` na política carregada.
+    pub report_all_findings: bool,
+    /// Registros, SARIF e resumo em JSON canônico (RFC 8785).
+    pub canonical_json: bool,
+    /// Webhook que recebe as decisões WARN/DENY (substitui `notifications.webhook_url`).
+    pub webhook: Option<String>,
+    /// Diretório do cache de resultados (desligado com `--no-cache`).
+    pub cache_dir: Option<PathBuf>,
+    /// Filtros aplicados ao percorrer diretórios.
+    pub walk: WalkFilter,
+    /// Rótulos de raiz (`--label nome=caminho`) gravados em `root`/`relative_path`.
+    pub labels: Vec<RootLabel>,
+}
+
+/// Raiz de varredura com nome estável entre máquinas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootLabel {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl FromStr for RootLabel {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        match raw.split_once('=') {
+            Some((name, path)) if !name.trim().is_empty() && !path.is_empty() => Ok(Self {
+                name: name.trim().to_string(),
+                path: PathBuf::from(path),
+            }),
+            _ => Err(anyhow!("rótulo inválido (esperado nome=caminho): {raw}")),
+        }
+    }
+}
+
+/// Rótulo e caminho relativo (separado por `/`) da raiz mais específica que contém `path`.
+fn label_for(path: &Path, labels: &[RootLabel]) -> Option<(String, String)> {
+    labels
+        .iter()
+        .filter_map(|label| {
+            path.strip_prefix(&label.path)
+                .ok()
+                .map(|relative| (label, relative))
+        })
+        .max_by_key(|(label, _)| label.path.components().count())
+        .map(|(label, relative)| {
+            let relative = safe_relative(relative)
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            (
+                label.name.clone(),
+                escape_control_chars(&relative).into_owned(),
+            )
+        })
+}
+
+/// Filtros da varredura de diretórios; arquivos informados diretamente não passam por eles.
+#[derive(Debug, Clone)]
+pub struct WalkFilter {
+    /// Globs de arquivos aceitos; vazio aceita todos.
+    pub include: Vec<String>,
+    /// Globs de arquivos e diretórios ignorados (diretórios são podados inteiros).
+    pub exclude: Vec<String>,
+    /// Profundidade máxima abaixo de cada diretório raiz.
+    pub max_depth: Option<usize>,
+    /// Percorre entradas ocultas (nome iniciado por `.`).
+    pub hidden: bool,
+    /// Aplica `.gitignore`, `.git/info/exclude` e o gitignore global do usuário.
+    pub respect_gitignore: bool,
+    /// Arquivos extras no formato gitignore.
+    pub ignore_files: Vec<PathBuf>,
+}
+
+impl Default for WalkFilter {
+    fn default() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_depth: None,
+            hidden: true,
+            respect_gitignore: false,
+            ignore_files: Vec::new(),
+        }
+    }
+}
+
+impl WalkFilter {
+    /// Diz se a entrada, com caminho relativo à raiz da varredura, deve ser mantida.
+    ///
+    /// Globs sem `/` casam com o nome da entrada (`node_modules`, `*.tmp`); com `/`,
+    /// com o caminho relativo inteiro (`docs/**/*.pdf`).
+    fn admits(&self, relative: &Path, is_dir: bool) -> bool {
+        let name = relative
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        if !self.hidden && name.starts_with('.') {
+            return false;
+        }
+        let relative = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let matches = |glob: &String| {
+            if glob.contains('/') {
+                glob_matches(glob, &relative)
+            } else {
+                glob_matches(glob, &name)
+            }
+        };
+        if self.exclude.iter().any(matches) {
+            return false;
+        }
+        is_dir || self.include.is_empty() || self.include.iter().any(matches)
+    }
 }
 
 /// Resultado do comando `scan`, contendo o código de saída sugerido.
@@ -39,6 +212,22 @@ pub struct ScanOutcome {
     pub exit_code: i32,
 }
 
+/// Campos derivados do subcomando `precheck`.
+#[derive(Debug)]
+pub struct PrecheckCommand {
+    pub metadata: PrecheckRequest,
+    pub policy: Option<PathBuf>,
+    pub builtin_policy: BuiltinPolicy,
+    pub claimed_mime_map: Option<PathBuf>,
+}
+
+/// Resultado do subcomando `precheck`; código 1 quando os metadados já levam a DENY.
+#[derive(Debug)]
+pub struct PrecheckOutcome {
+    pub report: PrecheckReport,
+    pub exit_code: i32,
+}
+
 /// Requisição para o subcomando `bench` (esqueleto).
 #[derive(Debug)]
 pub struct BenchRequest {
@@ -60,78 +249,293 @@ impl Engine {
 
     /// Executa varredura completa baseada nos caminhos recebidos.
     pub fn scan(&self, mut request: ScanRequest) -> Result<ScanOutcome> {
-        let policy_engine = if let Some(ref policy_path) = request.policy {
-            let config = PolicyConfig::from_path(policy_path)?;
-            Some(PolicyEngine::new(config))
-        } else {
-            None
+        let scan_started = Instant::now();
+        let policy_engine = load_policy(request.policy.as_deref(), request.builtin_policy)?
+            .map(|mut config| {
+                if request.report_all_findings {
+                    config.defaults.report_all_findings = Some(true);
+                }
+                request.profile.apply(&mut config);
+                plugins::policy_engine(config)
+            })
+            .transpose()?;
+        request.max_read_bytes = request.max_read_bytes.or(request.profile.max_read_bytes());
+        let claimed_mimes = match request.claimed_mime_map.as_deref() {
+            Some(path) => ClaimedMimeMap::from_path(path)?,
+            None => ClaimedMimeMap::default(),
+        };
+        let cache = match request.cache_dir.as_deref() {
+            // Com `history.set`, a decisão depende do histórico e não pode ser reutilizada.
+            Some(_)
+                if policy_engine
+                    .as_ref()
+                    .is_some_and(|engine| engine.history().is_some()) =>
+            {
+                tracing::warn!("cache de resultados ignorado: a política usa history.set");
+                None
+            }
+            // Overrides com `active_when` dependem do horário do scan.
+            Some(_)
+                if policy_engine.as_ref().is_some_and(|engine| {
+                    engine
+                        .config()
+                        .overrides
+                        .iter()
+                        .any(|rule| rule.active_when.is_some())
+                }) =>
+            {
+                tracing::warn!("cache de resultados ignorado: a política usa active_when");
+                None
+            }
+            Some(dir) => Some(ResultCache::open(
+                dir,
+                policy_engine.as_ref(),
+                &claimed_mimes,
+                &request.hashes,
+            )?),
+            None => None,
         };
 
-        let targets = collect_targets(&request.paths)?;
+        let mut targets: Vec<ScanTarget> = collect_targets(&request.paths, &request.walk)?
+            .into_iter()
+            .map(ScanTarget::File)
+            .collect();
+        if let Some(ref input_json) = request.input_json {
+            targets.extend(
+                load_inline_inputs(input_json)?
+                    .into_iter()
+                    .map(ScanTarget::Inline),
+            );
+        }
 
         let mut summary = SummaryReport::default();
-        let mut highest_decision = Decision::Allow;
+        let metadata = ScanMetadata::collect();
+        tracing::info!(
+            sniff_source = %metadata.sniff.source,
+            sniff_databases = ?metadata.sniff.databases,
+            "base de detecção MIME carregada"
+        );
+        summary.metadata = Some(metadata);
 
-        let mut json_writer = if let Some(ref json_path) = request.json {
-            Some(std::io::BufWriter::new(
-                File::create(json_path).with_context(|| {
-                    format!(
-                        "não foi possível criar arquivo JSON {}",
-                        json_path.display()
-                    )
-                })?,
-            ))
-        } else {
-            None
+        let json_writer = request
+            .json
+            .as_deref()
+            .map(|json_path| AtomicFile::create(json_path, &request.partial_suffix))
+            .transpose()?;
+        if let Some(json_dir) = request.json_dir.as_ref() {
+            std::fs::create_dir_all(json_dir).with_context(|| {
+                format!("não foi possível criar diretório {}", json_dir.display())
+            })?;
+        }
+
+        let mut output = ScanOutput {
+            format: request.format,
+            json_dir: request.json_dir.as_deref(),
+            records: 0,
+            accept_dir: request.accept_dir.as_deref(),
+            accept_hard_link: request.accept_hard_link,
+            sanitized_dir: request.sanitized_dir.as_deref(),
+            previews_dir: request.previews_dir.as_deref(),
+            roots: &request.paths,
+            summary,
+            highest_decision: Decision::Allow,
+            json_writer,
+            partial_suffix: &request.partial_suffix,
+            sarif: SarifBuilder::new(),
+            html: request.html.as_ref().map(|_| HtmlReportBuilder::new()),
+            junit: request.junit.as_ref().map(|_| JunitReportBuilder::new()),
+            canonical: request.canonical_json,
+            labels: &request.labels,
+            policy_engine: policy_engine.as_ref(),
+            notifier: Notifier::new(request.webhook.clone()),
+            copies: BTreeMap::new(),
         };
+        // Arquivos com sessão aguardam o fim do scan para as regras da seção `session`.
+        let mut sessions: BTreeMap<String, Vec<AnalyzedTarget>> = BTreeMap::new();
+        let heartbeat = Heartbeat::start(request.heartbeat, targets.len());
 
         for target in targets {
-            match process_file(&target, policy_engine.as_ref()) {
-                Ok((mut report, outcome)) => {
-                    highest_decision = compare_decision(highest_decision, outcome.decision);
-                    let policy_decision: PolicyDecision = outcome.clone().into();
-                    summary.update(&policy_decision);
-                    report.policy = policy_decision;
-
-                    tracing::debug!(
-                        file = %target.display(),
-                        decision = %report.policy.decision,
-                        size_bytes = report.size_bytes,
-                        mime = %report.sniff.mime_real,
-                        rules = ?report.policy.rules_triggered,
-                        "arquivo analisado"
-                    );
+            let deadline = request
+                .timeout
+                .map(|secs| Deadline::after(Duration::from_secs(secs)))
+                .unwrap_or_default();
+            if let Some(heartbeat) = heartbeat.as_ref() {
+                heartbeat.progress().begin(&target.label());
+            }
+            let (mut report, outcome) = match process_target(
+                &target,
+                policy_engine.as_ref(),
+                &claimed_mimes,
+                &request.hashes,
+                request.max_read_bytes,
+                &deadline,
+                cache.as_ref(),
+            ) {
+                Ok((report, outcome)) => (report, Some(outcome)),
+                Err(err) => {
+                    tracing::error!(file = %target.label(), "falha ao processar arquivo: {err:?}");
+                    let report = FileReport::error(Path::new(&target.label()), format!("{err:#}"));
+                    (report, None)
+                }
+            };
+            if let Some(heartbeat) = heartbeat.as_ref() {
+                heartbeat.progress().finish(report.size_bytes);
+            }
+            report.session = target
+                .session()
+                .or(request.session_id.as_deref())
+                .map(String::from);
+            match report.session.clone() {
+                Some(session) if policy_engine.is_some() => {
+                    sessions.entry(session).or_default().push(AnalyzedTarget {
+                        target,
+                        report,
+                        outcome,
+                    });
+                }
+                _ => output.emit(&target, report, outcome)?,
+            }
+        }
 
-                    if let Some(writer) = json_writer.as_mut() {
-                        serde_json::to_writer(&mut *writer, &report)?;
-                        writer.write_all(b"\n")?;
-                        writer.flush()?;
-                    } else {
-                        let line = serde_json::to_string(&report)?;
-                        println!("{line}");
-                    }
+        if let Some(policy_engine) = policy_engine.as_ref() {
+            for (session, members) in sessions {
+                let reports: Vec<&FileReport> =
+                    members.iter().map(|member| &member.report).collect();
+                let session_outcome = policy_engine.decide_session(&reports);
+                if !session_outcome.rules_triggered.is_empty() {
+                    tracing::warn!(
+                        session = %escape_control_chars(&session),
+                        files = members.len(),
+                        rules = ?session_outcome.rules_triggered,
+                        "regras de sessão acionadas"
+                    );
                 }
-                Err(err) => {
-                    tracing::error!(file = ?target, "falha ao processar arquivo: {err:?}");
-                    // Tratamos erro operacional como decisão DENY para respeitar fail_on.
-                    highest_decision = Decision::Deny;
+                for mut member in members {
+                    if let Some(outcome) = member.outcome.as_mut() {
+                        outcome.merge(&session_outcome);
+                    }
+                    output.emit(&member.target, member.report, member.outcome)?;
                 }
             }
         }
 
+        drop(heartbeat);
+        let ScanOutput {
+            mut summary,
+            highest_decision,
+            mut json_writer,
+            sarif,
+            html,
+            junit,
+            canonical,
+            records,
+            notifier,
+            ..
+        } = output;
+        // Conclui as entregas pendentes antes de ler as contagens dos limitadores.
+        drop(notifier);
+        if request.format == OutputFormat::JsonArray {
+            let close = if records == 0 { "[]\n" } else { "\n]\n" };
+            write_raw(json_writer.as_mut(), close)?;
+        }
+        if request.format == OutputFormat::Sarif {
+            let document = if canonical {
+                canonical::to_string(&sarif.build())?
+            } else {
+                serde_json::to_string_pretty(&sarif.build())?
+            };
+            write_record(json_writer.as_mut(), &document)?;
+        }
+        if let Some(json_writer) = json_writer {
+            json_writer.commit()?;
+        }
+
+        summary.finalize();
+        summary.elapsed_ms = Some(pipeline::elapsed_ms(scan_started));
+        summary.resources = Some(resources::collect(scan_started.elapsed()));
+        summary.rate_limits = rate_limit::stats();
+        for (integration, stats) in &summary.rate_limits {
+            if stats.dropped > 0 {
+                tracing::warn!(
+                    integration = %integration,
+                    deferred = stats.deferred,
+                    dropped = stats.dropped,
+                    "chamadas descartadas pelo limite de taxa"
+                );
+            }
+        }
+        if let Some(previous_path) = request.previous_summary.as_ref() {
+            let raw = std::fs::read_to_string(previous_path).with_context(|| {
+                format!(
+                    "não foi possível ler resumo anterior {}",
+                    previous_path.display()
+                )
+            })?;
+            let previous: PreviousSummary = serde_json::from_str(&raw)
+                .with_context(|| format!("resumo anterior inválido {}", previous_path.display()))?;
+            let trend = summary.compare_with(&previous);
+            tracing::info!(
+                deny_rate_delta = trend.deny_rate_delta,
+                new_rules = ?trend.new_rules,
+                throughput_change_pct = ?trend.throughput_change_pct,
+                "comparação com o resumo anterior"
+            );
+            summary.trend = Some(trend);
+        }
         if let Some(summary_path) = request.summary.take() {
-            let mut writer =
-                std::io::BufWriter::new(File::create(&summary_path).with_context(|| {
-                    format!("não foi possível criar summary {}", summary_path.display())
-                })?);
-            serde_json::to_writer_pretty(&mut writer, &summary)?;
-            writer.flush()?;
+            let document = if canonical {
+                canonical::to_string(&summary)?
+            } else {
+                serde_json::to_string_pretty(&summary)?
+            };
+            write_atomic(&summary_path, &request.partial_suffix, document).with_context(|| {
+                format!("não foi possível criar summary {}", summary_path.display())
+            })?;
+        }
+        if let (Some(html_path), Some(html)) = (request.html.as_ref(), html.as_ref()) {
+            write_atomic(html_path, &request.partial_suffix, html.render(&summary)).with_context(
+                || {
+                    format!(
+                        "não foi possível criar relatório HTML {}",
+                        html_path.display()
+                    )
+                },
+            )?;
+        }
+        if let (Some(junit_path), Some(junit)) = (request.junit.as_ref(), junit.as_ref()) {
+            write_atomic(junit_path, &request.partial_suffix, junit.render(&summary))
+                .with_context(|| {
+                    format!(
+                        "não foi possível criar relatório JUnit {}",
+                        junit_path.display()
+                    )
+                })?;
         }
 
-        let exit_code = compute_exit_code(request.fail_on, highest_decision);
+        if let Some(cache) = cache.as_ref() {
+            let (hits, misses) = cache.stats();
+            tracing::info!(hits, misses, "cache de resultados");
+        }
+
+        let fail_on = resolve_fail_on(request.fail_on, policy_engine.as_ref())?;
+        let exit_code = compute_exit_code(fail_on, highest_decision);
         Ok(ScanOutcome { exit_code })
     }
 
+    /// Avalia só as regras de metadados da política para um upload ainda não enviado.
+    pub fn precheck(&self, request: PrecheckCommand) -> Result<PrecheckOutcome> {
+        let policy_engine = load_policy(request.policy.as_deref(), request.builtin_policy)?
+            .map(plugins::policy_engine)
+            .transpose()?;
+        let claimed_mimes = match request.claimed_mime_map.as_deref() {
+            Some(path) => ClaimedMimeMap::from_path(path)?,
+            None => ClaimedMimeMap::default(),
+        };
+        let report = precheck(policy_engine.as_ref(), &request.metadata, &claimed_mimes);
+        let exit_code = i32::from(report.policy.decision == Decision::Deny.as_str());
+        Ok(PrecheckOutcome { report, exit_code })
+    }
+
     /// Esqueleto do comando `bench`, ainda não implementado.
     pub fn bench(&self, request: BenchRequest) -> Result<BenchOutcome> {
         tracing::warn!(
@@ -142,7 +546,294 @@ impl Engine {
     }
 }
 
-fn collect_targets(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+/// Arquivo analisado cuja emissão aguarda as regras de sessão.
+struct AnalyzedTarget {
+    target: ScanTarget,
+    report: FileReport,
+    /// `None` quando o processamento falhou (registro `ERROR`).
+    outcome: Option<DecisionOutcome>,
+}
+
+/// Destinos dos registros do `scan` (JSONL/SARIF, HTML, resumo, `--accept-dir`,
+/// `--sanitized-dir`, `--previews-dir`).
+struct ScanOutput<'a> {
+    format: OutputFormat,
+    json_dir: Option<&'a Path>,
+    /// Registros já gravados (separadores do `--format json-array`).
+    records: usize,
+    accept_dir: Option<&'a Path>,
+    accept_hard_link: bool,
+    sanitized_dir: Option<&'a Path>,
+    previews_dir: Option<&'a Path>,
+    roots: &'a [PathBuf],
+    summary: SummaryReport,
+    highest_decision: Decision,
+    json_writer: Option<AtomicFile>,
+    partial_suffix: &'a str,
+    sarif: SarifBuilder,
+    html: Option<HtmlReportBuilder>,
+    junit: Option<JunitReportBuilder>,
+    canonical: bool,
+    labels: &'a [RootLabel],
+    policy_engine: Option<&'a PolicyEngine>,
+    notifier: Notifier,
+    /// Arquivos já emitidos por SHA-256 (popularidade em `risk`).
+    copies: BTreeMap<String, u64>,
+}
+
+impl ScanOutput<'_> {
+    /// Aplica a decisão final ao relatório, aceita o arquivo se ALLOW e grava o registro.
+    fn emit(
+        &mut self,
+        target: &ScanTarget,
+        mut report: FileReport,
+        outcome: Option<DecisionOutcome>,
+    ) -> Result<()> {
+        if let ScanTarget::File(path) = target {
+            if let Some((root, relative_path)) = label_for(path, self.labels) {
+                report.root = Some(root);
+                report.relative_path = Some(relative_path);
+            }
+        }
+        match outcome {
+            Some(mut outcome) => {
+                self.summary.record_timings(&report.timings_ms);
+                // Falhas no aceite viram regra antes de a decisão ir para o relatório,
+                // para que SARIF, JUnit, webhook e métricas batam com o código de saída.
+                if let Some(accept_dir) = self.accept_dir {
+                    if outcome.decision == Decision::Allow && report.partially_read {
+                        // O SHA-256 parcial não permite conferir a cópia.
+                        tracing::error!(
+                            file = %target.label(),
+                            "arquivo lido parcialmente não é aceito"
+                        );
+                        outcome.record(Decision::Deny, "accept:partially_read");
+                    } else if outcome.decision == Decision::Allow {
+                        if let Err(err) = accept_target(
+                            target,
+                            &report.sha256,
+                            accept_dir,
+                            self.roots,
+                            self.accept_hard_link,
+                        ) {
+                            tracing::error!(
+                                file = %target.label(),
+                                "falha ao copiar arquivo aceito: {err:#}"
+                            );
+                            outcome.record(Decision::Deny, "accept:failed");
+                        }
+                    }
+                }
+                self.highest_decision = compare_decision(self.highest_decision, outcome.decision);
+                let decision = outcome.decision;
+                report.policy = outcome.into();
+
+                if let Some(sanitized_dir) = self.sanitized_dir {
+                    if decision != Decision::Deny {
+                        if let Err(err) =
+                            sanitize_target(target, &mut report, sanitized_dir, self.roots)
+                        {
+                            tracing::error!(
+                                file = %target.label(),
+                                "falha ao gravar imagem sem metadados: {err:#}"
+                            );
+                            self.highest_decision = Decision::Deny;
+                        }
+                    }
+                }
+
+                if let Some(previews_dir) = self.previews_dir {
+                    if decision == Decision::Deny {
+                        // A prévia é só apoio à revisão: falhar nela não muda a decisão.
+                        if let Err(err) =
+                            preview_target(target, &mut report, previews_dir, self.policy_engine)
+                        {
+                            tracing::warn!(
+                                file = %target.label(),
+                                "prévia não gerada: {err:#}"
+                            );
+                        }
+                    }
+                }
+
+                tracing::debug!(
+                    file = %target.label(),
+                    decision = %report.policy.decision,
+                    size_bytes = report.size_bytes,
+                    mime = %report.sniff.mime_real,
+                    rules = ?report.policy.rules_triggered,
+                    "arquivo analisado"
+                );
+            }
+            // Tratamos erro operacional como decisão DENY para respeitar fail_on.
+            None => self.highest_decision = Decision::Deny,
+        }
+        let copies = if report.sha256.is_empty() {
+            1
+        } else {
+            let copies = self.copies.entry(report.sha256.clone()).or_default();
+            *copies += 1;
+            *copies
+        };
+        report.risk = Some(risk::assess(&report, copies));
+        self.notifier.notify(self.policy_engine, &report);
+        self.summary.update(&report.policy);
+        self.summary.record_file(&report);
+        if let Some(html) = self.html.as_mut() {
+            html.push(&report);
+        }
+        if let Some(junit) = self.junit.as_mut() {
+            junit.push(&report);
+        }
+        if let Some(json_dir) = self.json_dir {
+            write_report_file(
+                json_dir,
+                target,
+                &report,
+                self.canonical,
+                self.partial_suffix,
+            )?;
+        }
+
+        let line = match self.format {
+            OutputFormat::Jsonl | OutputFormat::Pretty if self.canonical => {
+                canonical::to_string(&report)?
+            }
+            OutputFormat::Jsonl => serde_json::to_string(&report)?,
+            OutputFormat::Pretty => serde_json::to_string_pretty(&report)?,
+            OutputFormat::JsonArray => {
+                let record = if self.canonical {
+                    canonical::to_string(&report)?
+                } else {
+                    serde_json::to_string(&report)?
+                };
+                let separator = if self.records == 0 { "[\n" } else { ",\n" };
+                self.records += 1;
+                return write_raw(self.json_writer.as_mut(), &format!("{separator}{record}"));
+            }
+            OutputFormat::Verdict if self.canonical => {
+                canonical::to_string(&VerdictRecord::from(&report))?
+            }
+            OutputFormat::Verdict => serde_json::to_string(&VerdictRecord::from(&report))?,
+            OutputFormat::Sarif => {
+                self.sarif.push(&report);
+                return Ok(());
+            }
+        };
+        write_record(self.json_writer.as_mut(), &line)
+    }
+}
+
+/// Carrega a política do arquivo informado ou, na ausência dele, a política embutida.
+pub(crate) fn load_policy(
+    path: Option<&Path>,
+    builtin: BuiltinPolicy,
+) -> Result<Option<PolicyConfig>> {
+    let config = match (path, builtin) {
+        (Some(path), _) => Some(PolicyConfig::from_path(path)?),
+        (None, BuiltinPolicy::Strict) => Some(PolicyConfig::from_yaml_str(BUILTIN_STRICT_POLICY)?),
+        (None, BuiltinPolicy::Permissive) => {
+            Some(PolicyConfig::from_yaml_str(BUILTIN_PERMISSIVE_POLICY)?)
+        }
+        (None, BuiltinPolicy::None) => None,
+    };
+    if let Some(config) = &config {
+        log_policy_warnings(config);
+    }
+    Ok(config)
+}
+
+/// Registra os valores da política reduzidos ao teto no carregamento.
+pub(crate) fn log_policy_warnings(config: &PolicyConfig) {
+    for warning in &config.warnings {
+        tracing::warn!("política ajustada: {warning}");
+    }
+}
+
+/// Item a ser analisado: arquivo em disco ou conteúdo recebido inline.
+#[derive(Debug)]
+enum ScanTarget {
+    File(PathBuf),
+    Inline(InlineInput),
+}
+
+impl ScanTarget {
+    /// Sessão declarada na própria entrada inline.
+    fn session(&self) -> Option<&str> {
+        match self {
+            ScanTarget::File(_) => None,
+            ScanTarget::Inline(input) => input.session.as_deref(),
+        }
+    }
+
+    /// Rótulo para logs e registros de erro, com caracteres de controle escapados.
+    fn label(&self) -> String {
+        let label = match self {
+            ScanTarget::File(path) => path.display().to_string(),
+            ScanTarget::Inline(input) => input.name.clone(),
+        };
+        escape_control_chars(&label).into_owned()
+    }
+}
+
+/// Lê entradas inline de um JSON (objeto ou array) ou JSONL; `-` indica stdin.
+fn load_inline_inputs(path: &Path) -> Result<Vec<InlineInput>> {
+    let reader: Box<dyn Read> = if path == Path::new("-") {
+        Box::new(std::io::stdin())
+    } else {
+        Box::new(
+            File::open(path)
+                .with_context(|| format!("falha ao abrir entradas JSON {}", path.display()))?,
+        )
+    };
+
+    let mut inputs = Vec::new();
+    for value in serde_json::Deserializer::from_reader(BufReader::new(reader))
+        .into_iter::<serde_json::Value>()
+    {
+        let value = value.with_context(|| format!("JSON inválido em {}", path.display()))?;
+        match value {
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    inputs.push(serde_json::from_value(item)?);
+                }
+            }
+            other => inputs.push(serde_json::from_value(other)?),
+        }
+    }
+    Ok(inputs)
+}
+
+/// Configura a varredura de `root`: regras de ignore (`.gitignore`, `--ignore-file`)
+/// e os globs, profundidade e entradas ocultas de `filter`.
+fn walk_builder(root: &Path, filter: &WalkFilter) -> Result<WalkBuilder> {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .standard_filters(false)
+        .git_ignore(filter.respect_gitignore)
+        .git_exclude(filter.respect_gitignore)
+        .git_global(filter.respect_gitignore)
+        .require_git(false)
+        .max_depth(filter.max_depth);
+    for ignore_file in &filter.ignore_files {
+        if let Some(err) = builder.add_ignore(ignore_file) {
+            return Err(err)
+                .with_context(|| format!("arquivo de ignore inválido {}", ignore_file.display()));
+        }
+    }
+    let root = root.to_path_buf();
+    let filter = filter.clone();
+    builder.filter_entry(move |entry| {
+        entry.depth() == 0
+            || filter.admits(
+                entry.path().strip_prefix(&root).unwrap_or(entry.path()),
+                entry.file_type().is_some_and(|kind| kind.is_dir()),
+            )
+    });
+    Ok(builder)
+}
+
+pub(crate) fn collect_targets(paths: &[PathBuf], filter: &WalkFilter) -> Result<Vec<PathBuf>> {
     let mut targets = Vec::new();
     for path in paths {
         let metadata = std::fs::metadata(path)
@@ -150,9 +841,9 @@ fn collect_targets(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
         if metadata.is_file() {
             targets.push(path.clone());
         } else if metadata.is_dir() {
-            for entry in WalkDir::new(path) {
+            for entry in walk_builder(path, filter)?.build() {
                 let entry = entry?;
-                if entry.file_type().is_file() {
+                if entry.file_type().is_some_and(|kind| kind.is_file()) {
                     targets.push(entry.into_path());
                 }
             }
@@ -163,49 +854,412 @@ fn collect_targets(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
     Ok(targets)
 }
 
-fn process_file(
+/// Coloca um arquivo ALLOW em `accept_dir` preservando o caminho relativo à raiz
+/// escaneada. Por padrão grava uma cópia; com `hard_link` tenta um hard link
+/// (cópia se falhar), que compartilha o inode com o original: uma escrita
+/// posterior no original altera o arquivo aceito sem nova análise. O destino é
+/// conferido contra o SHA-256 analisado para não aceitar conteúdo alterado depois.
+fn accept_target(
+    target: &ScanTarget,
+    sha256: &str,
+    accept_dir: &Path,
+    roots: &[PathBuf],
+    hard_link: bool,
+) -> Result<PathBuf> {
+    let relative = match target {
+        ScanTarget::File(path) => relative_to_roots(path, roots),
+        ScanTarget::Inline(input) => safe_relative(Path::new(&input.name)),
+    };
+    if relative.as_os_str().is_empty() {
+        anyhow::bail!("nome de destino vazio para {}", target.label());
+    }
+    let dest = accept_dir.join(&relative);
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("não foi possível criar {}", parent.display()))?;
+    }
+    if std::fs::symlink_metadata(&dest).is_ok() {
+        std::fs::remove_file(&dest)
+            .with_context(|| format!("não foi possível substituir {}", dest.display()))?;
+    }
+    match target {
+        ScanTarget::File(path) => {
+            if !hard_link || std::fs::hard_link(path, &dest).is_err() {
+                std::fs::copy(path, &dest).with_context(|| {
+                    format!("falha ao copiar {} para {}", path.display(), dest.display())
+                })?;
+            }
+        }
+        ScanTarget::Inline(input) => std::fs::write(&dest, input.decode()?)
+            .with_context(|| format!("falha ao gravar {}", dest.display()))?,
+    }
+
+    let written = hex::encode(Sha256::digest(std::fs::read(&dest)?));
+    if written != sha256 {
+        let _ = std::fs::remove_file(&dest);
+        anyhow::bail!("conteúdo de {} mudou após a análise", target.label());
+    }
+    Ok(dest)
+}
+
+/// Grava em `sanitized_dir` a versão sem metadados calculada pelo validador de
+/// imagem (`details.sanitized`), no mesmo caminho relativo usado por
+/// `--accept-dir`. O original é relido e ambos os SHA-256 são conferidos; o
+/// caminho gravado fica em `details.sanitized.path`.
+fn sanitize_target(
+    target: &ScanTarget,
+    report: &mut FileReport,
+    sanitized_dir: &Path,
+    roots: &[PathBuf],
+) -> Result<()> {
+    let Some(sanitized) = report
+        .validators
+        .iter_mut()
+        .find(|outcome| outcome.name == "image")
+        .and_then(|outcome| outcome.details.get_mut("sanitized"))
+    else {
+        return Ok(());
+    };
+    if report.partially_read {
+        anyhow::bail!("arquivo lido parcialmente não pode ser reescrito");
+    }
+    let data = match target {
+        ScanTarget::File(path) => std::fs::read(path)
+            .with_context(|| format!("não foi possível ler {}", path.display()))?,
+        ScanTarget::Inline(input) => input.decode()?,
+    };
+    if hex::encode(Sha256::digest(&data)) != report.sha256 {
+        anyhow::bail!("conteúdo de {} mudou após a análise", target.label());
+    }
+    let stripped = strip_metadata(&data)
+        .with_context(|| format!("formato de {} não suportado", target.label()))?;
+    if sanitized["sha256"] != hex::encode(Sha256::digest(&stripped.data)) {
+        anyhow::bail!("imagem reescrita difere da analisada");
+    }
+
+    let relative = match target {
+        ScanTarget::File(path) => relative_to_roots(path, roots),
+        ScanTarget::Inline(input) => safe_relative(Path::new(&input.name)),
+    };
+    if relative.as_os_str().is_empty() {
+        anyhow::bail!("nome de destino vazio para {}", target.label());
+    }
+    let dest = sanitized_dir.join(&relative);
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("não foi possível criar {}", parent.display()))?;
+    }
+    if std::fs::symlink_metadata(&dest).is_ok() {
+        std::fs::remove_file(&dest)
+            .with_context(|| format!("não foi possível substituir {}", dest.display()))?;
+    }
+    std::fs::write(&dest, &stripped.data)
+        .with_context(|| format!("falha ao gravar {}", dest.display()))?;
+    sanitized["path"] = serde_json::Value::String(dest.display().to_string());
+    Ok(())
+}
+
+/// Grava em `previews_dir` a prévia PNG (`<sha256>.png`) de uma imagem ou PDF
+/// bloqueado e registra o caminho em `preview`. Formatos sem prévia e políticas
+/// com `report.redact: [preview]` são ignorados; o original é relido e o SHA-256
+/// conferido antes da decodificação.
+fn preview_target(
+    target: &ScanTarget,
+    report: &mut FileReport,
+    previews_dir: &Path,
+    policy_engine: Option<&PolicyEngine>,
+) -> Result<()> {
+    let redacted = policy_engine.is_some_and(|engine| {
+        engine
+            .config()
+            .report
+            .redact
+            .contains(&RedactField::Preview)
+    });
+    if redacted || report.partially_read || report.sha256.is_empty() {
+        return Ok(());
+    }
+    let data = match target {
+        ScanTarget::File(path) => std::fs::read(path)
+            .with_context(|| format!("não foi possível ler {}", path.display()))?,
+        ScanTarget::Inline(input) => input.decode()?,
+    };
+    if hex::encode(Sha256::digest(&data)) != report.sha256 {
+        anyhow::bail!("conteúdo de {} mudou após a análise", target.label());
+    }
+    let preview = match render_preview(&report.sniff.mime_real, &data) {
+        Ok(preview) => preview,
+        Err(PreviewError::Unsupported(_)) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    std::fs::create_dir_all(previews_dir)
+        .with_context(|| format!("não foi possível criar {}", previews_dir.display()))?;
+    let dest = previews_dir.join(format!("{}.png", report.sha256));
+    if std::fs::symlink_metadata(&dest).is_ok() {
+        std::fs::remove_file(&dest)
+            .with_context(|| format!("não foi possível substituir {}", dest.display()))?;
+    }
+    std::fs::write(&dest, &preview.data)
+        .with_context(|| format!("falha ao gravar {}", dest.display()))?;
+    report.preview = Some(dest.display().to_string());
+    Ok(())
+}
+
+/// Caminho do arquivo relativo à raiz (`scan <paths...>`) que o originou.
+pub(crate) fn relative_to_roots(path: &Path, roots: &[PathBuf]) -> PathBuf {
+    roots
+        .iter()
+        .filter(|root| root.as_path() != path)
+        .find_map(|root| path.strip_prefix(root).ok())
+        .map(safe_relative)
+        .unwrap_or_else(|| path.file_name().map(PathBuf::from).unwrap_or_default())
+}
+
+/// Mantém apenas componentes normais (sem `..`, raiz ou prefixos de unidade).
+fn safe_relative(path: &Path) -> PathBuf {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Escreve um registro no arquivo `--json` ou, na ausência dele, no stdout.
+fn write_record(writer: Option<&mut impl Write>, record: &str) -> Result<()> {
+    write_raw(writer, &format!("{record}\n"))
+}
+
+/// Grava o texto sem acrescentar quebra de linha (`--format json-array`).
+fn write_raw(writer: Option<&mut impl Write>, text: &str) -> Result<()> {
+    if let Some(writer) = writer {
+        writer.write_all(text.as_bytes())?;
+        writer.flush()?;
+    } else {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(text.as_bytes())?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+/// Grava o relatório em `<dir>/<sha256>.json`. Leituras parciais usam
+/// `partial-<partial_sha256>.json`; falhas de leitura não têm hash do conteúdo e
+/// usam `error-<sha256 do rótulo>.json`.
+fn write_report_file(
+    dir: &Path,
+    target: &ScanTarget,
+    report: &FileReport,
+    canonical: bool,
+    partial_suffix: &str,
+) -> Result<()> {
+    let name = match report.partial_sha256.as_deref() {
+        _ if !report.sha256.is_empty() => format!("{}.json", report.sha256),
+        Some(partial) => format!("partial-{partial}.json"),
+        None => format!(
+            "error-{}.json",
+            hex::encode(Sha256::digest(target.label().as_bytes()))
+        ),
+    };
+    let document = if canonical {
+        canonical::to_string(report)?
+    } else {
+        serde_json::to_string_pretty(report)?
+    };
+    let path = dir.join(name);
+    write_atomic(&path, partial_suffix, format!("{document}\n"))
+        .with_context(|| format!("falha ao gravar relatório {}", path.display()))
+}
+
+fn process_target(
+    target: &ScanTarget,
+    policy_engine: Option<&PolicyEngine>,
+    claimed_mimes: &ClaimedMimeMap,
+    hash_algorithms: &[HashAlgorithm],
+    max_read_bytes: Option<u64>,
+    deadline: &Deadline,
+    cache: Option<&ResultCache>,
+) -> Result<(FileReport, DecisionOutcome)> {
+    match target {
+        ScanTarget::File(path) => process_file(
+            path,
+            policy_engine,
+            claimed_mimes,
+            hash_algorithms,
+            max_read_bytes,
+            deadline,
+            cache,
+        ),
+        ScanTarget::Inline(input) => {
+            let span = file_span(&input.name);
+            let _entered = span.enter();
+            let data = input.decode()?;
+            let (mut report, outcome) = pipeline::analyze_bytes(
+                Path::new(&input.name),
+                &data,
+                policy_engine,
+                claimed_mimes,
+                hash_algorithms,
+                deadline,
+            )?;
+            record_file_span(&span, &report);
+            if let Some(engine) = policy_engine {
+                report.redact(&engine.config().report.redact);
+            }
+            Ok((report, outcome))
+        }
+    }
+}
+
+/// Span de um arquivo; os spans dos validadores (`guardupload-core/tracing`)
+/// ficam aninhados nele, e o fechamento registra a duração total.
+pub(crate) fn file_span(name: &str) -> tracing::Span {
+    tracing::info_span!(
+        "file",
+        file = %escape_control_chars(name),
+        sha256 = tracing::field::Empty,
+        mime = tracing::field::Empty,
+        size_bytes = tracing::field::Empty,
+    )
+}
+
+/// Preenche os campos do span conhecidos só depois da análise.
+pub(crate) fn record_file_span(span: &tracing::Span, report: &FileReport) {
+    span.record(
+        "sha256",
+        &report.sha256[..report.sha256.len().min(SPAN_SHA256_PREFIX)],
+    );
+    span.record("mime", report.sniff.mime_real.as_str());
+    span.record("size_bytes", report.size_bytes);
+}
+
+pub(crate) fn process_file(
     path: &Path,
     policy_engine: Option<&PolicyEngine>,
+    claimed_mimes: &ClaimedMimeMap,
+    hash_algorithms: &[HashAlgorithm],
+    max_read_bytes: Option<u64>,
+    deadline: &Deadline,
+    cache: Option<&ResultCache>,
 ) -> Result<(FileReport, DecisionOutcome)> {
-    let file = File::open(path).with_context(|| format!("falha ao abrir {}", path.display()))?;
-    let mut reader = BufReader::new(file);
-    let mut buffer = Vec::new();
-    reader.read_to_end(&mut buffer)?;
-
-    let size_bytes = buffer.len() as u64;
-    let digest = Sha256::digest(&buffer);
-    let sha256 = hex::encode(digest);
-
-    let sniff_result = sniff::sniff_bytes(&buffer)?;
-    let ext = path
-        .extension()
-        .and_then(|s| s.to_str())
-        .map(|s| format!(".{}", s.to_ascii_lowercase()));
-    let sniff_report = SniffReport::new(sniff_result.mime_real, sniff_result.magic, ext);
-
-    let mut report = FileReport::new(path, size_bytes, sha256, sniff_report);
-
-    let resolved_policy = policy_engine.map(|engine| engine.resolve(&report));
-    let resolved_policy_ref = resolved_policy.as_ref();
-    let validator_outcomes = evaluate_validators(
-        report.sniff.mime_real.as_str(),
-        &buffer,
-        resolved_policy_ref,
+    let span = file_span(&path.display().to_string());
+    let _entered = span.enter();
+    let result = read_and_analyze(
+        path,
+        policy_engine,
+        claimed_mimes,
+        hash_algorithms,
+        max_read_bytes,
+        deadline,
+        cache,
     );
-    report.validators = validator_outcomes
-        .iter()
-        .map(ValidatorEntry::from)
-        .collect();
+    if let Ok((report, _)) = &result {
+        record_file_span(&span, report);
+    }
+    result
+}
 
-    let outcome = if let Some(engine) = policy_engine {
-        engine.decide(&report, &validator_outcomes, resolved_policy_ref)
-    } else {
-        DecisionOutcome::new()
+fn read_and_analyze(
+    path: &Path,
+    policy_engine: Option<&PolicyEngine>,
+    claimed_mimes: &ClaimedMimeMap,
+    hash_algorithms: &[HashAlgorithm],
+    max_read_bytes: Option<u64>,
+    deadline: &Deadline,
+    cache: Option<&ResultCache>,
+) -> Result<(FileReport, DecisionOutcome)> {
+    let read_started = Instant::now();
+    let file = File::open(path).with_context(|| format!("falha ao abrir {}", path.display()))?;
+    let size_bytes = file.metadata()?.len();
+
+    let (mut report, outcome, read_ms) = match max_read_bytes.filter(|cap| size_bytes > *cap) {
+        Some(cap) => {
+            let (head, tail) = read_head_tail(file, size_bytes, cap)?;
+            let read_ms = pipeline::elapsed_ms(read_started);
+            let (report, outcome) = pipeline::analyze_partial(
+                path,
+                &head,
+                &tail,
+                size_bytes,
+                policy_engine,
+                claimed_mimes,
+            )?;
+            (report, outcome, read_ms)
+        }
+        None => {
+            let mut reader = BufReader::new(file);
+            let mut buffer = Vec::new();
+            reader.read_to_end(&mut buffer)?;
+            let read_ms = pipeline::elapsed_ms(read_started);
+            if let Some(cache) = cache {
+                let sha256 = hex::encode(Sha256::digest(&buffer));
+                if let Some((mut report, outcome)) = cache.get(&sha256, path) {
+                    report.timings_ms = TimingBreakdown {
+                        total: read_ms,
+                        read: Some(read_ms),
+                        ..TimingBreakdown::default()
+                    };
+                    report.bytes = ByteUsage {
+                        read: buffer.len() as u64,
+                        hashed: buffer.len() as u64,
+                        ..ByteUsage::default()
+                    };
+                    report
+                        .notes
+                        .push("resultado reutilizado do cache".to_string());
+                    return Ok((report, outcome));
+                }
+            }
+            let (report, outcome) = pipeline::analyze_bytes(
+                path,
+                &buffer,
+                policy_engine,
+                claimed_mimes,
+                hash_algorithms,
+                deadline,
+            )?;
+            (report, outcome, read_ms)
+        }
     };
+    report.timings_ms.read = Some(read_ms);
+    report.timings_ms.total += read_ms;
+    report.file_owner = file_owner(path);
+
+    if let Some(engine) = policy_engine {
+        report.redact(&engine.config().report.redact);
+    }
+    if let Some(cache) = cache {
+        cache.put(path, &report, &outcome);
+    }
 
     Ok((report, outcome))
 }
 
+/// Lê a primeira e a última metade de `cap` bytes de um arquivo maior que o limite.
+fn read_head_tail(mut file: File, size_bytes: u64, cap: u64) -> Result<(Vec<u8>, Vec<u8>)> {
+    let tail_len = cap / 2;
+    let head_len = cap - tail_len;
+    let mut head = Vec::new();
+    (&mut file).take(head_len).read_to_end(&mut head)?;
+    file.seek(SeekFrom::Start(size_bytes - tail_len))?;
+    let mut tail = Vec::new();
+    file.take(tail_len).read_to_end(&mut tail)?;
+    Ok((head, tail))
+}
+
+#[cfg(unix)]
+fn file_owner(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path)
+        .ok()
+        .map(|metadata| format!("uid:{}", metadata.uid()))
+}
+
+#[cfg(not(unix))]
+fn file_owner(_path: &Path) -> Option<String> {
+    None
+}
+
 fn compare_decision(current: Decision, candidate: Decision) -> Decision {
     if candidate.severity() > current.severity() {
         candidate
@@ -214,6 +1268,19 @@ fn compare_decision(current: Decision, candidate: Decision) -> Decision {
     }
 }
 
+/// `--fail-on` prevalece; na ausência dele vale `defaults.fail_on` da política.
+fn resolve_fail_on(cli: Option<FailOn>, policy_engine: Option<&PolicyEngine>) -> Result<FailOn> {
+    if let Some(fail_on) = cli {
+        return Ok(fail_on);
+    }
+    let from_policy = policy_engine.and_then(|engine| engine.config().defaults.fail_on.as_deref());
+    match from_policy {
+        Some(value) => FailOn::from_str(value.trim(), true)
+            .map_err(|_| anyhow!("defaults.fail_on inválido na política: {value}")),
+        None => Ok(FailOn::Deny),
+    }
+}
+
 fn compute_exit_code(fail_on: FailOn, decision: Decision) -> i32 {
     let threshold = match fail_on {
         FailOn::Error => 3,
@@ -246,6 +1313,162 @@ mod tests {
         assert_eq!(compute_exit_code(FailOn::Error, Decision::Warn), 0);
     }
 
+    #[test]
+    fn fail_on_prefers_cli_then_policy_then_deny() {
+        let engine = |yaml: &str| PolicyEngine::new(PolicyConfig::from_yaml_str(yaml).unwrap());
+        let warn_policy = engine("defaults:\n  fail_on: Warn\n");
+        assert_eq!(
+            resolve_fail_on(None, Some(&warn_policy)).unwrap(),
+            FailOn::Warn
+        );
+        assert_eq!(
+            resolve_fail_on(Some(FailOn::Error), Some(&warn_policy)).unwrap(),
+            FailOn::Error
+        );
+        assert_eq!(resolve_fail_on(None, None).unwrap(), FailOn::Deny);
+        assert!(resolve_fail_on(None, Some(&engine("defaults:\n  fail_on: sempre\n"))).is_err());
+    }
+
+    #[test]
+    fn accept_target_preserves_relative_paths_and_sanitizes_inline_names() {
+        let source = tempdir().expect("source");
+        let accept = tempdir().expect("accept");
+        let nested = source.path().join("lote").join("a.txt");
+        std::fs::create_dir_all(nested.parent().unwrap()).expect("mkdir");
+        std::fs::write(&nested, b"alpha").expect("write");
+        let digest = hex::encode(Sha256::digest(b"alpha"));
+
+        let roots = [source.path().to_path_buf()];
+        let dest = accept_target(
+            &ScanTarget::File(nested.clone()),
+            &digest,
+            accept.path(),
+            &roots,
+            false,
+        )
+        .expect("accept file");
+        assert_eq!(dest, accept.path().join("lote").join("a.txt"));
+        assert_eq!(std::fs::read(&dest).expect("read"), b"alpha");
+        // Cópia independente: alterar o original não muda o arquivo aceito.
+        std::fs::write(&nested, b"omega").expect("rewrite");
+        assert_eq!(std::fs::read(&dest).expect("read"), b"alpha");
+
+        // Com hard link, o destino acompanha o original (ressalva do opt-in).
+        std::fs::write(&nested, b"alpha").expect("restore");
+        let linked = accept_target(
+            &ScanTarget::File(nested.clone()),
+            &digest,
+            accept.path(),
+            &roots,
+            true,
+        )
+        .expect("accept link");
+        std::fs::write(&nested, b"omega").expect("rewrite");
+        assert_eq!(std::fs::read(&linked).expect("read"), b"omega");
+
+        let inline = ScanTarget::Inline(InlineInput {
+            name: "../../fora.txt".into(),
+            content_b64: "YWxwaGE=".into(),
+            session: None,
+        });
+        let dest = accept_target(&inline, &digest, accept.path(), &roots, false).expect("inline");
+        assert_eq!(dest, accept.path().join("fora.txt"));
+
+        assert!(accept_target(&inline, "00", accept.path(), &roots, false).is_err());
+        assert!(!accept.path().join("fora.txt").exists());
+    }
+
+    #[test]
+    fn previews_are_skipped_when_the_policy_redacts_them() {
+        // PNG 1x1 em tons de cinza.
+        let png = hex::decode(concat!(
+            "89504e470d0a1a0a0000000d49484452000000010000000108000000003a7e9b55",
+            "0000000a49444154789c636000000002000148afa4710000000049454e44ae426082",
+        ))
+        .expect("hex");
+        let source = tempdir().expect("source");
+        let path = source.path().join("a.png");
+        std::fs::write(&path, &png).expect("write");
+        let target = ScanTarget::File(path.clone());
+        let sniff = crate::report::SniffReport::new("image/png".into(), None, None);
+        let report = FileReport::new(
+            &path,
+            png.len() as u64,
+            hex::encode(Sha256::digest(&png)),
+            sniff,
+        );
+
+        let previews = tempdir().expect("previews");
+        let mut shown = report.clone();
+        preview_target(&target, &mut shown, previews.path(), None).expect("preview");
+        assert!(shown.preview.is_some());
+
+        let redacted = tempdir().expect("redacted");
+        let engine = PolicyEngine::new(
+            PolicyConfig::from_yaml_str("report:\n  redact: [preview]\n").expect("policy"),
+        );
+        let mut hidden = report.clone();
+        preview_target(&target, &mut hidden, redacted.path(), Some(&engine)).expect("preview");
+        assert!(hidden.preview.is_none());
+        assert_eq!(std::fs::read_dir(redacted.path()).expect("dir").count(), 0);
+
+        shown.redact(&[RedactField::Preview]);
+        assert!(shown.preview.is_none());
+    }
+
+    #[test]
+    fn report_files_are_named_by_sha256_and_pretty_printed() {
+        let dir = tempdir().expect("json dir");
+        let target = ScanTarget::File(PathBuf::from("a.txt"));
+        let mut report = FileReport::error(Path::new("a.txt"), "falha");
+        write_report_file(dir.path(), &target, &report, false, ".partial").expect("error report");
+        let digest = hex::encode(Sha256::digest(b"a.txt"));
+        assert!(dir.path().join(format!("error-{digest}.json")).exists());
+
+        report.sha256 = hex::encode(Sha256::digest(b"alpha"));
+        write_report_file(dir.path(), &target, &report, false, ".partial").expect("report");
+        let written = std::fs::read_to_string(dir.path().join(format!("{}.json", report.sha256)))
+            .expect("read");
+        assert!(written.starts_with("{\n  "));
+        let parsed: serde_json::Value = serde_json::from_str(&written).expect("json");
+        assert_eq!(parsed["sha256"], report.sha256.as_str());
+    }
+
+    #[test]
+    fn files_beyond_read_cap_are_hashed_from_head_and_tail() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("grande.txt");
+        let mut content = b"%PDF-1.4\n".to_vec();
+        content.resize(1000, b'a');
+        content.extend_from_slice(b"fim");
+        std::fs::write(&path, &content).expect("write");
+
+        let engine = PolicyEngine::new(PolicyConfig::default());
+        let (report, outcome) = process_file(
+            &path,
+            Some(&engine),
+            &ClaimedMimeMap::default(),
+            &[],
+            Some(100),
+            &Deadline::none(),
+            None,
+        )
+        .expect("process");
+        assert!(report.partially_read);
+        assert_eq!(report.size_bytes, 1003);
+        assert_eq!(report.sniff.mime_real, "application/pdf");
+        let expected = [&content[..50], &content[953..]].concat();
+        assert!(report.sha256.is_empty());
+        assert_eq!(
+            report.partial_sha256,
+            Some(hex::encode(Sha256::digest(&expected)))
+        );
+        assert_eq!(outcome.decision, Decision::Deny);
+        assert!(outcome
+            .rules_triggered
+            .contains(&"size:partially_read".to_string()));
+    }
+
     #[test]
     fn collect_targets_handles_files_and_directories() {
         let dir = tempdir().expect("tempdir");
@@ -257,13 +1480,149 @@ mod tests {
         std::fs::write(&file_a, b"alpha").expect("write a");
         std::fs::write(&file_b, b"beta").expect("write b");
 
-        let mut targets = collect_targets(&[root.to_path_buf()]).expect("collect");
+        let mut targets =
+            collect_targets(&[root.to_path_buf()], &WalkFilter::default()).expect("collect");
         targets.sort();
         assert_eq!(targets.len(), 2);
         assert!(targets.contains(&file_a));
         assert!(targets.contains(&file_b));
     }
 
+    #[test]
+    fn collect_targets_applies_walk_filters() {
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        for file in [
+            "a.pdf",
+            "b.tmp",
+            ".env",
+            "node_modules/lib.pdf",
+            "docs/c.pdf",
+            "docs/deep/d.pdf",
+        ] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).expect("create dir");
+            std::fs::write(&path, b"x").expect("write");
+        }
+        let collect = |filter: WalkFilter| {
+            let mut targets: Vec<String> = collect_targets(&[root.to_path_buf()], &filter)
+                .expect("collect")
+                .iter()
+                .map(|path| path.strip_prefix(root).unwrap().display().to_string())
+                .collect();
+            targets.sort();
+            targets
+        };
+
+        let filtered = collect(WalkFilter {
+            include: vec!["*.pdf".into(), ".env".into()],
+            exclude: vec!["node_modules".into(), "docs/deep/*".into()],
+            ..WalkFilter::default()
+        });
+        assert_eq!(filtered, [".env", "a.pdf", "docs/c.pdf"]);
+
+        let shallow = collect(WalkFilter {
+            max_depth: Some(1),
+            hidden: false,
+            ..WalkFilter::default()
+        });
+        assert_eq!(shallow, ["a.pdf", "b.tmp"]);
+    }
+
+    #[test]
+    fn collect_targets_respects_gitignore_and_ignore_files() {
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        for file in ["src/main.rs", "target/debug/app", "build.log", "notes.txt"] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).expect("create dir");
+            std::fs::write(&path, b"x").expect("write");
+        }
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\n").expect("gitignore");
+        let extra = dir.path().join("extra.ignore");
+        std::fs::write(&extra, "notes.txt\nextra.ignore\n").expect("ignore file");
+
+        let count = |filter: WalkFilter| {
+            collect_targets(&[root.to_path_buf()], &filter)
+                .expect("collect")
+                .len()
+        };
+        assert_eq!(count(WalkFilter::default()), 6);
+        let filter = WalkFilter {
+            respect_gitignore: true,
+            hidden: false,
+            ignore_files: vec![extra],
+            ..WalkFilter::default()
+        };
+        let targets = collect_targets(&[root.to_path_buf()], &filter).expect("collect");
+        assert_eq!(targets, [root.join("src/main.rs")]);
+        assert!(collect_targets(
+            &[root.to_path_buf()],
+            &WalkFilter {
+                ignore_files: vec![root.join("ausente")],
+                ..WalkFilter::default()
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn root_labels_pick_most_specific_root() {
+        let labels: Vec<RootLabel> = ["a=/srv/a", "docs=/srv/a/docs/", "b=/srv/b"]
+            .iter()
+            .map(|raw| raw.parse().expect("label"))
+            .collect();
+        assert_eq!(
+            label_for(Path::new("/srv/a/docs/2024/f.pdf"), &labels),
+            Some(("docs".to_string(), "2024/f.pdf".to_string()))
+        );
+        assert_eq!(
+            label_for(Path::new("/srv/b/x.txt"), &labels),
+            Some(("b".to_string(), "x.txt".to_string()))
+        );
+        assert_eq!(label_for(Path::new("/srv/c/x.txt"), &labels), None);
+        assert!("sem-caminho".parse::<RootLabel>().is_err());
+        assert!("=/srv/a".parse::<RootLabel>().is_err());
+    }
+
+    #[test]
+    fn load_inline_inputs_accepts_jsonl_and_arrays() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("inputs.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                "{\"name\": \"a.txt\", \"content_b64\": \"YWxwaGE=\"}\n",
+                "[{\"name\": \"b.txt\", \"content_b64\": \"YmV0YQ==\"}]\n"
+            ),
+        )
+        .expect("write");
+
+        let inputs = load_inline_inputs(&path).expect("load");
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs[0].name, "a.txt");
+        assert_eq!(inputs[1].decode().expect("decode"), b"beta");
+    }
+
+    #[test]
+    fn load_policy_prefers_file_and_falls_back_to_builtin() {
+        let strict = load_policy(None, BuiltinPolicy::Strict)
+            .expect("strict")
+            .expect("config");
+        assert_eq!(strict.defaults.max_size_mb, Some(25));
+        assert!(load_policy(None, BuiltinPolicy::None)
+            .expect("none")
+            .is_none());
+
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("policy.yaml");
+        std::fs::write(&path, "defaults:\n  max_size_mb: 1\n").expect("write");
+        let from_file = load_policy(Some(&path), BuiltinPolicy::Strict)
+            .expect("file")
+            .expect("config");
+        assert_eq!(from_file.defaults.max_size_mb, Some(1));
+    }
+
     #[test]
     fn compare_decision_picks_highest_severity() {
         assert_eq!(
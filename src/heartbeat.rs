@@ -0,0 +1,319 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Heartbeat e detecção de travamento em scans longos (`--heartbeat`, `--stall-after`).
+//!
+//! Uma thread de monitoramento registra periodicamente o progresso (arquivo
+//! atual, arquivos e bytes concluídos, taxa) no log estruturado. Quando nenhum
+//! arquivo termina por `stall_after`, ela registra um dump dos validadores em
+//! execução e, no Linux, do estado de cada thread do processo
+//! (`/proc/self/task`). O Rust não captura a pilha de outra thread sem
+//! depurador; o dump aponta qual validador está preso, há quanto tempo e em
+//! que conteúdo.
+
+use crate::report::escape_control_chars;
+use crate::validators::{active_validators, ActiveValidator};
+use serde::Serialize;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Intervalo entre verificações da thread de monitoramento.
+const TICK: Duration = Duration::from_millis(250);
+
+/// Configuração do monitoramento; sem intervalo nem limite, nada é iniciado.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeartbeatConfig {
+    pub interval: Option<Duration>,
+    pub stall_after: Option<Duration>,
+}
+
+/// Progresso compartilhado entre o scan e a thread de monitoramento.
+#[derive(Debug)]
+pub struct ScanProgress {
+    files_total: usize,
+    started: Instant,
+    state: Mutex<ProgressState>,
+}
+
+#[derive(Debug)]
+struct ProgressState {
+    current: Option<String>,
+    files_done: u64,
+    bytes_done: u64,
+    last_progress: Instant,
+    /// Travamento do arquivo atual já registrado (um dump por arquivo).
+    stall_reported: bool,
+}
+
+/// Registro periódico de progresso.
+#[derive(Debug, Clone, Serialize)]
+pub struct HeartbeatRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub files_done: u64,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub elapsed_ms: u64,
+    pub files_per_sec: f64,
+    pub bytes_per_sec: f64,
+}
+
+/// Diagnóstico de um scan sem progresso.
+#[derive(Debug, Clone, Serialize)]
+pub struct StallReport {
+    pub file: String,
+    pub stalled_ms: u64,
+    pub validators: Vec<ActiveValidator>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub threads: Vec<ThreadState>,
+}
+
+/// Estado de uma thread do processo segundo `/proc/self/task/<tid>/stat`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadState {
+    pub tid: u32,
+    pub name: String,
+    /// `R` executando, `S` dormindo, `D` em espera de E/S...
+    pub state: String,
+    /// Tempo de CPU (usuário + sistema) em ticks do relógio.
+    pub cpu_ticks: u64,
+}
+
+impl ScanProgress {
+    pub fn new(files_total: usize) -> Self {
+        let now = Instant::now();
+        Self {
+            files_total,
+            started: now,
+            state: Mutex::new(ProgressState {
+                current: None,
+                files_done: 0,
+                bytes_done: 0,
+                last_progress: now,
+                stall_reported: false,
+            }),
+        }
+    }
+
+    /// Marca o início da análise de `file`.
+    pub fn begin(&self, file: &str) {
+        let mut state = self.lock();
+        state.current = Some(escape_control_chars(file).into_owned());
+        state.last_progress = Instant::now();
+        state.stall_reported = false;
+    }
+
+    /// Marca o arquivo atual como concluído.
+    pub fn finish(&self, size_bytes: u64) {
+        let mut state = self.lock();
+        state.current = None;
+        state.files_done += 1;
+        state.bytes_done += size_bytes;
+        state.last_progress = Instant::now();
+    }
+
+    pub fn snapshot(&self) -> HeartbeatRecord {
+        let state = self.lock();
+        let elapsed = self.started.elapsed();
+        let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+        HeartbeatRecord {
+            file: state.current.clone(),
+            files_done: state.files_done,
+            files_total: self.files_total,
+            bytes_done: state.bytes_done,
+            elapsed_ms: elapsed.as_millis() as u64,
+            files_per_sec: state.files_done as f64 / seconds,
+            bytes_per_sec: state.bytes_done as f64 / seconds,
+        }
+    }
+
+    /// Travamento ainda não registrado do arquivo atual, se passou de `stall_after`.
+    pub fn check_stall(&self, stall_after: Duration) -> Option<StallReport> {
+        let mut state = self.lock();
+        let stalled = state.last_progress.elapsed();
+        if state.stall_reported || stalled < stall_after {
+            return None;
+        }
+        let file = state.current.clone()?;
+        state.stall_reported = true;
+        Some(StallReport {
+            file,
+            stalled_ms: stalled.as_millis() as u64,
+            validators: active_validators(),
+            threads: thread_states(),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ProgressState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Thread de monitoramento; encerrada ao sair de escopo.
+pub struct Heartbeat {
+    progress: Arc<ScanProgress>,
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Heartbeat {
+    /// Inicia o monitoramento de um scan com `files_total` arquivos; `None`
+    /// quando a configuração não pede heartbeat nem detecção de travamento.
+    pub fn start(config: HeartbeatConfig, files_total: usize) -> Option<Self> {
+        if config.interval.is_none() && config.stall_after.is_none() {
+            return None;
+        }
+        let progress = Arc::new(ScanProgress::new(files_total));
+        let (stop, stopped) = mpsc::channel::<()>();
+        let monitored = Arc::clone(&progress);
+        let handle = std::thread::Builder::new()
+            .name("guardupload-heartbeat".to_string())
+            .spawn(move || {
+                let mut last_beat = Instant::now();
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(TICK) {
+                    if let Some(interval) = config.interval {
+                        if last_beat.elapsed() >= interval {
+                            last_beat = Instant::now();
+                            log_heartbeat(&monitored.snapshot());
+                        }
+                    }
+                    if let Some(stall) = config
+                        .stall_after
+                        .and_then(|after| monitored.check_stall(after))
+                    {
+                        log_stall(&stall);
+                    }
+                }
+            })
+            .ok()?;
+        Some(Self {
+            progress,
+            stop: Some(stop),
+            handle: Some(handle),
+        })
+    }
+
+    pub fn progress(&self) -> &ScanProgress {
+        &self.progress
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn log_heartbeat(record: &HeartbeatRecord) {
+    tracing::info!(
+        target: "guardupload::heartbeat",
+        file = record.file.as_deref().unwrap_or_default(),
+        files_done = record.files_done,
+        files_total = record.files_total,
+        bytes_done = record.bytes_done,
+        elapsed_ms = record.elapsed_ms,
+        files_per_sec = record.files_per_sec,
+        bytes_per_sec = record.bytes_per_sec,
+        "heartbeat"
+    );
+}
+
+fn log_stall(stall: &StallReport) {
+    let dump = serde_json::to_string(stall).unwrap_or_default();
+    tracing::error!(
+        target: "guardupload::heartbeat",
+        file = %stall.file,
+        stalled_ms = stall.stalled_ms,
+        validator = stall
+            .validators
+            .first()
+            .map(|active| active.validator)
+            .unwrap_or_default(),
+        dump = %dump,
+        "scan sem progresso"
+    );
+}
+
+#[cfg(target_os = "linux")]
+fn thread_states() -> Vec<ThreadState> {
+    let Ok(tasks) = std::fs::read_dir("/proc/self/task") else {
+        return Vec::new();
+    };
+    let mut states: Vec<ThreadState> = tasks
+        .flatten()
+        .filter_map(|task| {
+            let tid = task.file_name().to_str()?.parse().ok()?;
+            parse_stat(
+                tid,
+                &std::fs::read_to_string(task.path().join("stat")).ok()?,
+            )
+        })
+        .collect();
+    states.sort_by_key(|state| state.tid);
+    states
+}
+
+#[cfg(not(target_os = "linux"))]
+fn thread_states() -> Vec<ThreadState> {
+    Vec::new()
+}
+
+/// `tid (nome) estado ...`; o nome pode conter espaços e parênteses, então os
+/// campos seguintes são contados a partir do último `)`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_stat(tid: u32, stat: &str) -> Option<ThreadState> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let fields: Vec<&str> = stat.get(close + 1..)?.split_whitespace().collect();
+    let ticks = |index: usize| {
+        fields
+            .get(index)
+            .and_then(|field| field.parse::<u64>().ok())
+    };
+    Some(ThreadState {
+        tid,
+        name: stat.get(open + 1..close)?.to_string(),
+        state: fields.first()?.to_string(),
+        // utime e stime são os campos 14 e 15 do stat (11 e 12 após o estado).
+        cpu_ticks: ticks(11)? + ticks(12)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_reports_rates_and_one_stall_per_file() {
+        let progress = ScanProgress::new(3);
+        progress.begin("a.pdf");
+        progress.finish(1024);
+        progress.begin("lento\n.zip");
+        let record = progress.snapshot();
+        assert_eq!(record.file.as_deref(), Some("lento\\n.zip"));
+        assert_eq!((record.files_done, record.files_total), (1, 3));
+        assert_eq!(record.bytes_done, 1024);
+        assert!(record.files_per_sec > 0.0);
+
+        assert!(progress.check_stall(Duration::from_secs(60)).is_none());
+        let stall = progress.check_stall(Duration::ZERO).expect("travamento");
+        assert_eq!(stall.file, "lento\\n.zip");
+        assert!(progress.check_stall(Duration::ZERO).is_none());
+        progress.finish(10);
+        assert!(progress.check_stall(Duration::ZERO).is_none());
+
+        let state = parse_stat(42, "42 (worker (1)) S 1 2 3 4 5 6 7 8 9 10 7 5 0").expect("stat");
+        assert_eq!(
+            (state.name.as_str(), state.state.as_str()),
+            ("worker (1)", "S")
+        );
+        assert_eq!(state.cpu_ticks, 12);
+    }
+}
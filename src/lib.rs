@@ -7,14 +7,52 @@
 //!
 //! Este crate organiza a CLI, carregamento de políticas, sniffing de MIME,
 //! validações e geração de relatórios conforme os requisitos do SPEC.
+//!
+//! As regras de validação vivem em `guardupload-core` e são reexportadas
+//! aqui; sem a feature `cli` apenas esse núcleo é exposto.
+
+pub use guardupload_core::{
+    analyzers, canonical, config, decompress, disarm, error, history, html, junit, limits,
+    pipeline, policy, precheck, preview, report, risk, rules, sarif, scanner, sniff, validators,
+};
 
-pub mod analyzers;
+#[cfg(feature = "cli")]
+pub mod cache;
+#[cfg(feature = "cli")]
+pub mod clamav;
+#[cfg(feature = "cli")]
 pub mod cli;
-pub mod config;
+#[cfg(feature = "cli")]
+pub mod corpus;
+#[cfg(feature = "cli")]
 pub mod engine;
-pub mod error;
-pub mod limits;
-pub mod policy;
-pub mod report;
-pub mod sniff;
-pub mod validators;
+#[cfg(feature = "cli")]
+pub mod heartbeat;
+#[cfg(feature = "cli")]
+pub mod metrics;
+#[cfg(feature = "cli")]
+pub mod output;
+#[cfg(feature = "cli")]
+pub mod plugins;
+#[cfg(feature = "cli")]
+pub mod probe;
+#[cfg(feature = "cli")]
+pub mod rate_limit;
+#[cfg(feature = "cli")]
+pub mod resources;
+#[cfg(feature = "cli")]
+pub mod sanitize;
+#[cfg(feature = "cli")]
+pub mod server;
+#[cfg(feature = "cli")]
+pub mod service;
+#[cfg(feature = "cli")]
+pub mod similar;
+#[cfg(feature = "cli")]
+pub mod simulate;
+#[cfg(feature = "cli")]
+pub mod verdicts;
+#[cfg(feature = "cli")]
+pub mod watch;
+#[cfg(feature = "cli")]
+pub mod webhook;
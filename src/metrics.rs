@@ -0,0 +1,287 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Métricas Prometheus dos modos `serve` e `watch`.
+//!
+//! Os contadores são expostos em `GET /metrics` (`serve`) ou gravados
+//! periodicamente em um arquivo `.prom` para o textfile collector do
+//! node_exporter (`--metrics-textfile`).
+
+use crate::output::write_atomic;
+use crate::report::{ByteUsage, FileReport};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Limites superiores (segundos) dos buckets do histograma de latência.
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Content-Type do formato texto de exposição.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Contadores acumulados desde o início do processo.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    state: Mutex<MetricsState>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    files_scanned: u64,
+    bytes_scanned: u64,
+    /// Bytes lidos, processados por hash e descomprimidos (por validador).
+    bytes: ByteUsage,
+    decisions: BTreeMap<String, u64>,
+    rules: BTreeMap<String, u64>,
+    /// Contagem por bucket (não cumulativa; acumulada na renderização).
+    latency_buckets: [u64; LATENCY_BUCKETS.len()],
+    latency_count: u64,
+    latency_sum: f64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Contabiliza um relatório emitido e o tempo gasto para produzi-lo.
+    pub fn record(&self, report: &FileReport, elapsed: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.files_scanned += 1;
+        state.bytes_scanned += report.size_bytes;
+        state.bytes.add(&report.bytes);
+        *state
+            .decisions
+            .entry(report.policy.decision.clone())
+            .or_default() += 1;
+        for rule in &report.policy.rules_triggered {
+            *state.rules.entry(rule_key(rule).to_string()).or_default() += 1;
+        }
+        let seconds = elapsed.as_secs_f64();
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            state.latency_buckets[bucket] += 1;
+        }
+        state.latency_count += 1;
+        state.latency_sum += seconds;
+    }
+
+    /// Renderiza as métricas no formato texto do Prometheus.
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut out = String::new();
+        counter(
+            &mut out,
+            "guardupload_files_scanned_total",
+            "Arquivos analisados.",
+        );
+        let _ = writeln!(
+            out,
+            "guardupload_files_scanned_total {}",
+            state.files_scanned
+        );
+        counter(
+            &mut out,
+            "guardupload_bytes_scanned_total",
+            "Bytes dos arquivos analisados.",
+        );
+        let _ = writeln!(
+            out,
+            "guardupload_bytes_scanned_total {}",
+            state.bytes_scanned
+        );
+        counter(
+            &mut out,
+            "guardupload_bytes_read_total",
+            "Bytes de conteúdo lidos e analisados.",
+        );
+        let _ = writeln!(out, "guardupload_bytes_read_total {}", state.bytes.read);
+        counter(
+            &mut out,
+            "guardupload_bytes_hashed_total",
+            "Bytes processados pelas funções de hash.",
+        );
+        let _ = writeln!(out, "guardupload_bytes_hashed_total {}", state.bytes.hashed);
+        counter(
+            &mut out,
+            "guardupload_bytes_decompressed_total",
+            "Bytes descomprimidos por validador.",
+        );
+        for (validator, bytes) in &state.bytes.by_validator {
+            let _ = writeln!(
+                out,
+                "guardupload_bytes_decompressed_total{{validator=\"{}\"}} {bytes}",
+                escape_label(validator)
+            );
+        }
+        counter(
+            &mut out,
+            "guardupload_decisions_total",
+            "Decisões por tipo (ALLOW, WARN, DENY, ERROR).",
+        );
+        for (decision, count) in &state.decisions {
+            let _ = writeln!(
+                out,
+                "guardupload_decisions_total{{decision=\"{}\"}} {count}",
+                escape_label(decision)
+            );
+        }
+        counter(
+            &mut out,
+            "guardupload_rules_triggered_total",
+            "Regras de política disparadas, sem os valores variáveis.",
+        );
+        for (rule, count) in &state.rules {
+            let _ = writeln!(
+                out,
+                "guardupload_rules_triggered_total{{rule=\"{}\"}} {count}",
+                escape_label(rule)
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP guardupload_scan_duration_seconds Tempo de análise por arquivo.\n\
+             # TYPE guardupload_scan_duration_seconds histogram"
+        );
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(state.latency_buckets) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "guardupload_scan_duration_seconds_bucket{{le=\"{bound}\"}} {cumulative}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "guardupload_scan_duration_seconds_bucket{{le=\"+Inf\"}} {}\n\
+             guardupload_scan_duration_seconds_sum {}\n\
+             guardupload_scan_duration_seconds_count {}",
+            state.latency_count, state.latency_sum, state.latency_count
+        );
+        out
+    }
+
+    /// Grava as métricas em `path` de forma atômica (arquivo temporário + rename),
+    /// como o textfile collector espera.
+    pub fn write_textfile(&self, path: &Path) -> Result<()> {
+        write_atomic(path, ".tmp", self.render())
+    }
+}
+
+/// Exportação periódica para o textfile collector.
+#[derive(Debug)]
+pub struct TextfileExporter {
+    path: PathBuf,
+    interval: Duration,
+    last_written: Option<Instant>,
+}
+
+impl TextfileExporter {
+    pub fn new(path: PathBuf, interval: Duration) -> Self {
+        Self {
+            path,
+            interval,
+            last_written: None,
+        }
+    }
+
+    /// Grava o arquivo se o intervalo já passou desde a última gravação.
+    pub fn tick(&mut self, metrics: &Metrics) {
+        if self
+            .last_written
+            .is_some_and(|written| written.elapsed() < self.interval)
+        {
+            return;
+        }
+        self.flush(metrics);
+    }
+
+    /// Grava o arquivo imediatamente (ex.: no encerramento).
+    pub fn flush(&mut self, metrics: &Metrics) {
+        if let Err(err) = metrics.write_textfile(&self.path) {
+            tracing::warn!("métricas não exportadas: {err:#}");
+        }
+        self.last_written = Some(Instant::now());
+    }
+}
+
+fn counter(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter");
+}
+
+/// Identificador estável da regra: `mime:deny:application/x-dosexec` → `mime:deny`;
+/// regras de validador mantêm o status (`validator:pdf:deny`).
+fn rule_key(rule: &str) -> &str {
+    let segments = if rule.starts_with("validator:") { 3 } else { 2 };
+    match rule.match_indices(':').nth(segments - 1) {
+        Some((index, _)) => &rule[..index],
+        None => rule,
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{PolicyDecision, SniffReport};
+
+    fn report(decision: &str, rules: &[&str], size: u64) -> FileReport {
+        let sniff = SniffReport::new("application/pdf".into(), None, None);
+        let mut report = FileReport::new(Path::new("a.pdf"), size, String::new(), sniff);
+        report.policy = PolicyDecision {
+            decision: decision.into(),
+            rules_triggered: rules.iter().map(|rule| rule.to_string()).collect(),
+            ..PolicyDecision::default()
+        };
+        report
+    }
+
+    #[test]
+    fn render_exposes_counters_and_cumulative_histogram() {
+        let metrics = Metrics::new();
+        let mut office = report("ALLOW", &[], 100);
+        office.bytes.read = 100;
+        office.bytes.by_validator.insert("office".into(), 4096);
+        metrics.record(&office, Duration::from_millis(3));
+        metrics.record(
+            &report(
+                "DENY",
+                &[
+                    "mime:deny:application/x-dosexec",
+                    "validator:pdf:error:timeout",
+                ],
+                50,
+            ),
+            Duration::from_millis(300),
+        );
+        let text = metrics.render();
+        assert!(text.contains("guardupload_files_scanned_total 2\n"));
+        assert!(text.contains("guardupload_bytes_scanned_total 150\n"));
+        assert!(text.contains("guardupload_bytes_read_total 100\n"));
+        assert!(text.contains("guardupload_bytes_decompressed_total{validator=\"office\"} 4096\n"));
+        assert!(text.contains("guardupload_decisions_total{decision=\"DENY\"} 1\n"));
+        assert!(text.contains("guardupload_rules_triggered_total{rule=\"mime:deny\"} 1\n"));
+        assert!(text.contains("{rule=\"validator:pdf:error\"} 1\n"));
+        assert!(text.contains("guardupload_scan_duration_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("guardupload_scan_duration_seconds_bucket{le=\"0.5\"} 2\n"));
+        assert!(text.contains("guardupload_scan_duration_seconds_count 2\n"));
+
+        let dir = tempfile::tempdir().expect("dir");
+        let path = dir.path().join("guardupload.prom");
+        metrics.write_textfile(&path).expect("textfile");
+        assert_eq!(std::fs::read_to_string(&path).expect("read"), text);
+    }
+}
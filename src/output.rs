@@ -0,0 +1,150 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Gravação atômica dos arquivos de saída.
+//!
+//! O conteúdo vai para `<destino><sufixo>` no mesmo diretório e só é renomeado
+//! para o destino depois de `fsync`; quem observa o diretório nunca vê um
+//! relatório pela metade. Se a gravação não chega ao fim, o temporário é removido.
+
+use anyhow::{ensure, Context, Result};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Sufixo padrão dos arquivos em andamento (`--partial-suffix`).
+pub const DEFAULT_PARTIAL_SUFFIX: &str = ".partial";
+
+/// Arquivo de saída em andamento; [`AtomicFile::commit`] o publica no destino.
+pub struct AtomicFile {
+    path: PathBuf,
+    partial: PathBuf,
+    writer: Option<BufWriter<File>>,
+}
+
+impl AtomicFile {
+    /// Cria `<path><partial_suffix>`, truncando sobras de uma execução anterior.
+    pub fn create(path: &Path, partial_suffix: &str) -> Result<Self> {
+        let partial = partial_path(path, partial_suffix)?;
+        let file = File::create(&partial)
+            .with_context(|| format!("não foi possível criar {}", partial.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            partial,
+            writer: Some(BufWriter::new(file)),
+        })
+    }
+
+    /// Descarrega, sincroniza e renomeia para o destino.
+    pub fn commit(mut self) -> Result<()> {
+        let Some(writer) = self.writer.take() else {
+            return Ok(());
+        };
+        let file = writer
+            .into_inner()
+            .map_err(|err| err.into_error())
+            .with_context(|| format!("falha ao gravar {}", self.partial.display()))?;
+        file.sync_all()
+            .with_context(|| format!("falha ao sincronizar {}", self.partial.display()))?;
+        drop(file);
+        std::fs::rename(&self.partial, &self.path)
+            .with_context(|| format!("falha ao publicar {}", self.path.display()))?;
+        sync_parent(&self.path);
+        Ok(())
+    }
+
+    fn writer(&mut self) -> std::io::Result<&mut BufWriter<File>> {
+        self.writer
+            .as_mut()
+            .ok_or_else(|| std::io::Error::other("arquivo já publicado"))
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer()?.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer()?.flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if self.writer.take().is_some() {
+            let _ = std::fs::remove_file(&self.partial);
+        }
+    }
+}
+
+/// Grava `contents` em `path` de forma atômica.
+pub fn write_atomic(path: &Path, partial_suffix: &str, contents: impl AsRef<[u8]>) -> Result<()> {
+    let mut file = AtomicFile::create(path, partial_suffix)?;
+    file.write_all(contents.as_ref())
+        .with_context(|| format!("falha ao gravar {}", path.display()))?;
+    file.commit()
+}
+
+fn partial_path(path: &Path, partial_suffix: &str) -> Result<PathBuf> {
+    ensure!(
+        !partial_suffix.is_empty() && !partial_suffix.contains(['/', '\\']),
+        "sufixo de arquivo parcial inválido: '{partial_suffix}'"
+    );
+    let mut partial = OsString::from(path.as_os_str());
+    partial.push(partial_suffix);
+    Ok(PathBuf::from(partial))
+}
+
+/// Sincroniza o diretório para que o rename sobreviva a uma queda de energia.
+#[cfg(unix)]
+fn sync_parent(path: &Path) {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if let Ok(dir) = File::open(parent) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn outputs_appear_only_after_commit() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("report.jsonl");
+        let partial = dir.path().join("report.jsonl.tmp");
+
+        let mut file = AtomicFile::create(&path, ".tmp").expect("create");
+        writeln!(file, "{{\"file\":\"a.pdf\"}}").expect("write");
+        assert!(partial.exists() && !path.exists());
+        file.commit().expect("commit");
+        assert!(!partial.exists());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"file\":\"a.pdf\"}\n"
+        );
+
+        // Gravação abandonada não substitui a versão anterior.
+        let mut aborted = AtomicFile::create(&path, ".tmp").expect("create");
+        aborted.write_all(b"{\"file\":").expect("write");
+        drop(aborted);
+        assert!(!partial.exists());
+        assert!(std::fs::read_to_string(&path).unwrap().contains("a.pdf"));
+
+        write_atomic(&path, DEFAULT_PARTIAL_SUFFIX, "[]\n").expect("write_atomic");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[]\n");
+        assert!(AtomicFile::create(&path, "").is_err());
+        assert!(AtomicFile::create(&path, "/x").is_err());
+    }
+}
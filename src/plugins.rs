@@ -0,0 +1,348 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Validadores externos declarados em `plugins:` na política.
+//!
+//! Cada plugin é um subprocesso que recebe em stdin um JSON
+//! `{"plugin", "mime", "size_bytes", "content_b64" | "path"}` e responde em stdout
+//! `{"status": "pass|warn|deny|error", "message"?, "details"?}`. Plugins rodam em
+//! complemento ao validador embutido do arquivo.
+
+use crate::clamav::ClamavValidator;
+use crate::config::{PluginInput, PluginSection, PolicyConfig};
+use crate::policy::{matches_pattern, PolicyEngine};
+use crate::rate_limit;
+use crate::validators::{
+    intern, ValidationContext, Validator, ValidatorOutcome, ValidatorRegistry, ValidatorStatus,
+    ValidatorVersion, ANY_VALIDATOR,
+};
+use crate::verdicts::FileHistory;
+use anyhow::{bail, Context, Result};
+use base64::Engine as _;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Cria o motor de políticas registrando os plugins e o clamd declarados em `config`
+/// e abrindo o histórico de `history.path`, se houver.
+pub fn policy_engine(config: PolicyConfig) -> Result<PolicyEngine> {
+    let history = match config.history.path.as_deref() {
+        Some(path) if !config.history.set.is_empty() => {
+            Some(Arc::new(FileHistory::open(Path::new(path))?))
+        }
+        _ => None,
+    };
+    let clamav = ClamavValidator::from_policy(&config.clamav).map(|clamav| {
+        clamav.with_rate_limiter(rate_limit::shared(
+            rate_limit::CLAMAV,
+            config.rate_limits.clamav.as_ref(),
+        ))
+    });
+    let mut engine = if config.plugins.is_empty() && clamav.is_none() {
+        PolicyEngine::new(config)
+    } else {
+        let mut registry = ValidatorRegistry::builtin();
+        if let Some(clamav) = clamav {
+            registry.register(clamav);
+        }
+        for plugin in &config.plugins {
+            registry.register(ExternalValidator::new(plugin));
+        }
+        PolicyEngine::new(config).with_validators(registry)
+    };
+    if let Some(history) = history {
+        engine = engine.with_history(history);
+    }
+    engine.check_requirements()?;
+    Ok(engine)
+}
+
+/// Validador que delega a decisão a um comando externo.
+#[derive(Debug)]
+pub struct ExternalValidator {
+    name: &'static str,
+    command: Vec<String>,
+    mime_patterns: Vec<String>,
+    input: PluginInput,
+    timeout: Duration,
+    max_output_bytes: usize,
+    version: ValidatorVersion,
+}
+
+impl ExternalValidator {
+    pub fn new(section: &PluginSection) -> Self {
+        Self {
+            name: intern(&section.name),
+            command: section.command.clone(),
+            mime_patterns: section.mime_patterns.clone(),
+            input: section.input,
+            timeout: Duration::from_millis(section.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)),
+            max_output_bytes: section.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES),
+            // Conferida no carregamento da política (`plugins[].version`).
+            version: section
+                .version
+                .as_deref()
+                .and_then(|version| ValidatorVersion::parse(version).ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Executa o comando; `None` quando o prazo (do plugin ou do scan) expira.
+    fn run(&self, ctx: &ValidationContext<'_>) -> Result<Option<Vec<u8>>> {
+        let Some((program, args)) = self.command.split_first() else {
+            bail!("plugin sem comando");
+        };
+        let mut request = json!({
+            "plugin": self.name,
+            "mime": ctx.mime,
+            "size_bytes": ctx.data.len(),
+        });
+        let temp_file = match self.input {
+            PluginInput::Stdin => {
+                request["content_b64"] = base64::engine::general_purpose::STANDARD
+                    .encode(ctx.data)
+                    .into();
+                None
+            }
+            PluginInput::Path => {
+                let temp_file = TempFile::write(ctx.data)?;
+                request["path"] = temp_file.0.display().to_string().into();
+                Some(temp_file)
+            }
+        };
+        let stdin_payload = serde_json::to_vec(&request)?;
+
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("falha ao executar {program}"))?;
+
+        // stdin e stdout em threads próprias: um plugin que responde antes de ler
+        // tudo não pode travar a escrita. Nenhuma das duas é aguardada sem prazo.
+        let mut stdin = child.stdin.take().context("stdin do plugin indisponível")?;
+        thread::spawn(move || {
+            // Plugins podem ignorar o stdin e fechá-lo antes do fim.
+            let _ = stdin.write_all(&stdin_payload);
+        });
+        let mut stdout = child
+            .stdout
+            .take()
+            .context("stdout do plugin indisponível")?;
+        let limit = self.max_output_bytes as u64 + 1;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut read = || -> io::Result<Vec<u8>> {
+                let mut output = Vec::new();
+                (&mut stdout).take(limit).read_to_end(&mut output)?;
+                io::copy(&mut stdout, &mut io::sink())?;
+                Ok(output)
+            };
+            let _ = sender.send(read());
+        });
+
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if started.elapsed() >= self.timeout || ctx.deadline.is_expired() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(None);
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+        // Processos filhos do plugin herdam o stdout e podem mantê-lo aberto depois
+        // que ele termina: a leitura respeita o mesmo prazo.
+        let output = loop {
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(output) => break output?,
+                Err(RecvTimeoutError::Timeout)
+                    if started.elapsed() >= self.timeout || ctx.deadline.is_expired() =>
+                {
+                    return Ok(None);
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    bail!("leitura do stdout do plugin falhou")
+                }
+            }
+        };
+        drop(temp_file);
+
+        if output.len() > self.max_output_bytes {
+            bail!("saída excede {} bytes", self.max_output_bytes);
+        }
+        if output.iter().all(u8::is_ascii_whitespace) && !status.success() {
+            bail!("plugin terminou com {status} sem resposta");
+        }
+        Ok(Some(output))
+    }
+
+    fn parse_response(&self, output: &[u8]) -> Result<ValidatorOutcome> {
+        let response: PluginResponse =
+            serde_json::from_slice(output).context("resposta JSON inválida")?;
+        let status = match response.status.to_ascii_lowercase().as_str() {
+            "pass" => ValidatorStatus::Pass,
+            "warn" => ValidatorStatus::Warn,
+            "deny" => ValidatorStatus::Deny,
+            "error" => ValidatorStatus::Error,
+            other => bail!("status desconhecido: {other}"),
+        };
+        let mut outcome = ValidatorOutcome::new(self.name, status, response.details);
+        if let Some(message) = response.message {
+            outcome = outcome.with_details(json!({ "message": message }));
+        }
+        Ok(outcome)
+    }
+}
+
+impl Validator for ExternalValidator {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn applies_to(&self, ctx: &ValidationContext<'_>) -> bool {
+        self.mime_patterns.is_empty()
+            || self
+                .mime_patterns
+                .iter()
+                .any(|pattern| matches_pattern(pattern, ctx.mime))
+    }
+
+    fn validate(&self, ctx: &ValidationContext<'_>) -> ValidatorOutcome {
+        if ctx.deadline.is_expired() {
+            return ValidatorOutcome::timeout(self.name);
+        }
+        match self.run(ctx) {
+            Ok(Some(output)) => self.parse_response(&output).unwrap_or_else(|err| {
+                ValidatorOutcome::error(self.name, format!("plugin: {err:#}"))
+            }),
+            Ok(None) => ValidatorOutcome::timeout(self.name),
+            Err(err) => ValidatorOutcome::error(self.name, format!("plugin: {err:#}")),
+        }
+    }
+
+    fn version(&self) -> ValidatorVersion {
+        self.version
+    }
+
+    fn supplements(&self) -> Option<&'static str> {
+        Some(ANY_VALIDATOR)
+    }
+
+    fn mime_patterns(&self) -> Vec<&str> {
+        self.mime_patterns.iter().map(String::as_str).collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginResponse {
+    status: String,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    details: Value,
+}
+
+/// Arquivo temporário com o conteúdo analisado, removido ao sair de escopo.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn write(data: &[u8]) -> Result<Self> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "guardupload-plugin-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&path, data).with_context(|| format!("falha ao gravar {}", path.display()))?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::limits::Deadline;
+
+    fn plugin(script: &str, timeout_ms: u64) -> ExternalValidator {
+        ExternalValidator::new(&PluginSection {
+            name: "shell".into(),
+            command: vec!["sh".into(), "-c".into(), script.into()],
+            mime_patterns: vec!["text/*".into()],
+            input: PluginInput::Stdin,
+            timeout_ms: Some(timeout_ms),
+            max_output_bytes: None,
+            version: None,
+        })
+    }
+
+    fn validate(validator: &ExternalValidator, mime: &str) -> Option<ValidatorOutcome> {
+        let ctx = ValidationContext {
+            mime,
+            data: b"EICAR",
+            policy: None,
+            deadline: &Deadline::none(),
+            file_name: None,
+        };
+        validator.applies_to(&ctx).then(|| validator.validate(&ctx))
+    }
+
+    #[test]
+    fn plugin_reads_request_and_reports_outcome() {
+        let validator = plugin(
+            r#"grep -q '"content_b64":"RUlDQVI="' && echo '{"status":"deny","message":"assinatura"}'"#,
+            5_000,
+        );
+        let outcome = validate(&validator, "text/plain").expect("aplica");
+        assert_eq!(outcome.name, "shell");
+        assert_eq!(outcome.status.as_str(), "deny");
+        assert_eq!(outcome.details["message"], "assinatura");
+        assert!(validate(&validator, "application/pdf").is_none());
+
+        let garbage = validate(&plugin("cat >/dev/null; echo oops", 5_000), "text/plain");
+        assert_eq!(garbage.expect("aplica").status.as_str(), "error");
+    }
+
+    #[test]
+    fn slow_plugin_is_killed_at_timeout() {
+        let started = Instant::now();
+        let outcome = validate(&plugin("sleep 5", 100), "text/plain").expect("aplica");
+        assert!(outcome.is_timeout());
+        assert!(started.elapsed() < Duration::from_secs(3));
+
+        // O plugin termina, mas um processo filho segura o stdout aberto.
+        let started = Instant::now();
+        let outcome = validate(
+            &plugin("sleep 5 & echo '{\"status\":\"pass\"}'", 300),
+            "text/plain",
+        )
+        .expect("aplica");
+        assert!(outcome.is_timeout());
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,195 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Sniff de MIME isolado, sem validadores nem política (`guardupload sniff`).
+//!
+//! Mostra o que o pipeline enxerga antes de decidir: MIME real, bytes iniciais,
+//! extensão, MIME declarado pela extensão, a confiança do resultado e os
+//! candidatos alternativos.
+
+use crate::engine::{collect_targets, WalkFilter};
+use crate::report::escape_control_chars;
+use crate::sniff::{
+    is_mime_mismatch, sniff_bytes_with, ClaimedMimeMap, CustomSignature, SniffAttempt,
+    SniffCandidate,
+};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Requisição do subcomando `sniff`.
+#[derive(Debug)]
+pub struct SniffRequest {
+    pub paths: Vec<PathBuf>,
+    /// Um objeto JSON por linha em vez do texto tabulado.
+    pub json: bool,
+    pub claimed_mimes: ClaimedMimeMap,
+    /// `sniff.custom` da política informada em `--policy`.
+    pub custom_signatures: Vec<CustomSignature>,
+}
+
+/// Resultado do `sniff`.
+#[derive(Debug, Default)]
+pub struct SniffOutcome {
+    pub sniffed: usize,
+    pub mismatches: usize,
+    pub failed: usize,
+    pub exit_code: i32,
+}
+
+/// Registro por arquivo.
+#[derive(Debug, Clone, Serialize)]
+pub struct SniffRecord {
+    pub file: String,
+    pub mime_real: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magic: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<String>,
+    /// MIME implícito pela extensão (tabela embutida ou `--claimed-mime-map`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_claimed: Option<String>,
+    /// MIME real fora da família do declarado (mesma regra de `deny_on_mime_mismatch`).
+    pub mismatch: bool,
+    /// `high`, `medium` ou `low`; vazio quando o arquivo não pôde ser lido.
+    pub confidence: &'static str,
+    /// Score do `mime_real` (0 a 1).
+    pub score: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<SniffCandidate>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub escalation: Vec<SniffAttempt>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Imprime um registro por arquivo; falhas de leitura geram código de saída 1.
+pub fn run(request: &SniffRequest) -> Result<SniffOutcome> {
+    let targets = collect_targets(&request.paths, &WalkFilter::default())?;
+    let mut outcome = SniffOutcome::default();
+    for path in &targets {
+        let record = match sniff_file(path, &request.claimed_mimes, &request.custom_signatures) {
+            Ok(record) => record,
+            Err(err) => SniffRecord {
+                file: escape_control_chars(&path.display().to_string()).into_owned(),
+                mime_real: String::new(),
+                magic: None,
+                ext: None,
+                mime_claimed: None,
+                mismatch: false,
+                confidence: "",
+                score: 0.0,
+                candidates: Vec::new(),
+                escalation: Vec::new(),
+                error: Some(format!("{err:#}")),
+            },
+        };
+        if record.error.is_some() {
+            outcome.failed += 1;
+        } else {
+            outcome.sniffed += 1;
+        }
+        if record.mismatch {
+            outcome.mismatches += 1;
+        }
+        if request.json {
+            println!("{}", serde_json::to_string(&record)?);
+        } else {
+            println!("{}", text_line(&record));
+        }
+    }
+    outcome.exit_code = i32::from(outcome.failed > 0);
+    Ok(outcome)
+}
+
+fn sniff_file(
+    path: &Path,
+    claimed_mimes: &ClaimedMimeMap,
+    custom: &[CustomSignature],
+) -> Result<SniffRecord> {
+    let data = std::fs::read(path).with_context(|| format!("falha ao ler {}", path.display()))?;
+    let mut result = sniff_bytes_with(&data, custom)?;
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| format!(".{}", ext.to_ascii_lowercase()));
+    let mime_claimed = ext
+        .as_deref()
+        .and_then(|ext| claimed_mimes.claimed_for(ext))
+        .map(str::to_string);
+    if let Some(claimed) = &mime_claimed {
+        result.add_extension_hint(claimed);
+    }
+    Ok(SniffRecord {
+        file: escape_control_chars(&path.display().to_string()).into_owned(),
+        mismatch: mime_claimed
+            .as_deref()
+            .is_some_and(|claimed| is_mime_mismatch(claimed, &result.mime_real)),
+        confidence: result.confidence().as_str(),
+        score: result.score(),
+        candidates: result.alternatives().to_vec(),
+        mime_real: result.mime_real,
+        magic: result.magic,
+        ext,
+        mime_claimed,
+        escalation: result.escalation,
+        error: None,
+    })
+}
+
+/// `arquivo  mime  confiança (score)  [extensão → declarado]  magic  [alternativas]`.
+fn text_line(record: &SniffRecord) -> String {
+    if let Some(error) = &record.error {
+        return format!("{}\terro: {error}", record.file);
+    }
+    let claimed = match (&record.ext, &record.mime_claimed) {
+        (Some(ext), Some(claimed)) if record.mismatch => {
+            format!("{ext} → {claimed} (divergente)")
+        }
+        (Some(ext), Some(claimed)) => format!("{ext} → {claimed}"),
+        (Some(ext), None) => format!("{ext} → ?"),
+        (None, _) => "-".to_string(),
+    };
+    let mut line = format!(
+        "{}\t{}\t{} ({:.2})\t{claimed}\t{}",
+        record.file,
+        record.mime_real,
+        record.confidence,
+        record.score,
+        record.magic.as_deref().unwrap_or("-"),
+    );
+    for candidate in &record.candidates {
+        line.push_str(&format!("\t{}={:.2}", candidate.mime, candidate.score));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn sniff_reports_claimed_mismatch_and_confidence() {
+        let dir = tempdir().expect("tempdir");
+        let disguised = dir.path().join("relatorio.pdf");
+        std::fs::write(&disguised, b"MZ\x90\0\x03\0\0\0").expect("write");
+        let record = sniff_file(&disguised, &ClaimedMimeMap::default(), &[]).expect("sniff");
+        assert_eq!(record.ext.as_deref(), Some(".pdf"));
+        assert_eq!(record.mime_claimed.as_deref(), Some("application/pdf"));
+        assert!(record.mismatch);
+        assert_eq!(record.magic.as_deref(), Some("4D 5A 90 00 03 00 00 00"));
+        assert!(text_line(&record).contains(".pdf → application/pdf (divergente)"));
+        assert!(text_line(&record).ends_with("\tapplication/pdf=0.30"));
+
+        let pdf = dir.path().join("ok.pdf");
+        std::fs::write(&pdf, b"%PDF-1.4\n%%EOF\n").expect("write");
+        let record = sniff_file(&pdf, &ClaimedMimeMap::default(), &[]).expect("sniff");
+        assert_eq!(record.mime_real, "application/pdf");
+        assert!(!record.mismatch);
+        assert_eq!(record.confidence, "high");
+        assert!(record.candidates.is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Token bucket das integrações de rede (`rate_limits:` na política).
+//!
+//! Cada integração tem um limitador por processo, compartilhado por todas as
+//! análises e threads; ao recarregar a política (`serve`) ele é reconfigurado sem
+//! perder as contagens, que o `scan` copia para `summary.rate_limits`.
+
+use crate::config::{RateLimitAction, RateLimitSection};
+use crate::report::RateLimitStats;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Entregas do webhook de `notifications`.
+pub const WEBHOOK: &str = "webhook";
+/// Consultas ao clamd.
+pub const CLAMAV: &str = "clamav";
+const DEFAULT_MAX_WAIT_MS: u64 = 30_000;
+
+/// Resultado de [`RateLimiter::acquire`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permit {
+    Allowed,
+    /// Liberada após esperar pela ficha.
+    Deferred,
+    /// Sem ficha: a chamada não deve ser feita.
+    Dropped,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    settings: RateLimitSection,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn capacity(&self) -> f64 {
+        match self.settings.burst {
+            Some(burst) => f64::from(burst.max(1)),
+            None => self.settings.per_second.ceil().max(1.0),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.settings.per_second).min(self.capacity());
+        self.refilled_at = now;
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    bucket: Mutex<Bucket>,
+    allowed: AtomicU64,
+    deferred: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl RateLimiter {
+    /// Limitador com o bucket cheio.
+    pub fn new(settings: RateLimitSection) -> Self {
+        let mut bucket = Bucket {
+            settings,
+            tokens: 0.0,
+            refilled_at: Instant::now(),
+        };
+        bucket.tokens = bucket.capacity();
+        Self {
+            bucket: Mutex::new(bucket),
+            allowed: AtomicU64::new(0),
+            deferred: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn reconfigure(&self, settings: &RateLimitSection) {
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+        if bucket.settings != *settings {
+            bucket.refill(Instant::now());
+            bucket.settings = settings.clone();
+            bucket.tokens = bucket.tokens.min(bucket.capacity());
+        }
+    }
+
+    /// Consome uma ficha. Com `defer`, reserva a próxima e espera por ela na
+    /// thread atual, desde que a liberação não passe de `max_wait_ms` após
+    /// `requested_at` (quando a chamada foi pedida, ex.: entrada na fila).
+    pub fn acquire(&self, requested_at: Instant) -> Permit {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+            let now = Instant::now();
+            bucket.refill(now);
+            if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                None
+            } else {
+                let wait =
+                    Duration::from_secs_f64((1.0 - bucket.tokens) / bucket.settings.per_second);
+                let max_wait = Duration::from_millis(
+                    bucket.settings.max_wait_ms.unwrap_or(DEFAULT_MAX_WAIT_MS),
+                );
+                let drop = bucket.settings.on_limit == Some(RateLimitAction::Drop)
+                    || now + wait > requested_at + max_wait;
+                if drop {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Permit::Dropped;
+                }
+                // Ficha negativa: reservas seguintes esperam também por esta.
+                bucket.tokens -= 1.0;
+                Some(wait)
+            }
+        };
+        match wait {
+            None => {
+                self.allowed.fetch_add(1, Ordering::Relaxed);
+                Permit::Allowed
+            }
+            Some(wait) => {
+                thread::sleep(wait);
+                self.deferred.fetch_add(1, Ordering::Relaxed);
+                Permit::Deferred
+            }
+        }
+    }
+
+    pub fn stats(&self) -> RateLimitStats {
+        RateLimitStats {
+            allowed: self.allowed.load(Ordering::Relaxed),
+            deferred: self.deferred.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn limiters() -> &'static Mutex<BTreeMap<&'static str, Arc<RateLimiter>>> {
+    static LIMITERS: OnceLock<Mutex<BTreeMap<&'static str, Arc<RateLimiter>>>> = OnceLock::new();
+    LIMITERS.get_or_init(Mutex::default)
+}
+
+/// Limitador compartilhado da integração `name`; `None` sem `rate_limits.<name>`.
+pub fn shared(name: &'static str, settings: Option<&RateLimitSection>) -> Option<Arc<RateLimiter>> {
+    let settings = settings?;
+    let mut limiters = limiters().lock().unwrap_or_else(PoisonError::into_inner);
+    let limiter = limiters
+        .entry(name)
+        .or_insert_with(|| Arc::new(RateLimiter::new(settings.clone())));
+    limiter.reconfigure(settings);
+    Some(Arc::clone(limiter))
+}
+
+/// Contagens de cada limitador criado no processo.
+pub fn stats() -> BTreeMap<String, RateLimitStats> {
+    limiters()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .map(|(name, limiter)| (name.to_string(), limiter.stats()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(per_second: f64, burst: u32, on_limit: RateLimitAction) -> RateLimitSection {
+        RateLimitSection {
+            per_second,
+            burst: Some(burst),
+            on_limit: Some(on_limit),
+            max_wait_ms: Some(1_000),
+        }
+    }
+
+    #[test]
+    fn bucket_allows_burst_then_defers_or_drops() {
+        let dropping = RateLimiter::new(settings(1.0, 2, RateLimitAction::Drop));
+        let now = Instant::now();
+        assert_eq!(dropping.acquire(now), Permit::Allowed);
+        assert_eq!(dropping.acquire(now), Permit::Allowed);
+        assert_eq!(dropping.acquire(now), Permit::Dropped);
+        assert_eq!(
+            dropping.stats(),
+            RateLimitStats {
+                allowed: 2,
+                deferred: 0,
+                dropped: 1
+            }
+        );
+
+        let deferring = RateLimiter::new(settings(50.0, 1, RateLimitAction::Defer));
+        let started = Instant::now();
+        assert_eq!(deferring.acquire(started), Permit::Allowed);
+        assert_eq!(deferring.acquire(started), Permit::Deferred);
+        assert!(started.elapsed() >= Duration::from_millis(15));
+        // Pedido antigo demais para esperar dentro de `max_wait_ms`.
+        let stale = Instant::now() - Duration::from_millis(1_000);
+        assert_eq!(deferring.acquire(stale), Permit::Dropped);
+        assert_eq!(deferring.stats().deferred, 1);
+    }
+}
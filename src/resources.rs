@@ -0,0 +1,76 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Medição do uso de recursos do processo (CPU, pico de RSS e IO) para o summary.
+
+use crate::report::ResourceUsage;
+use std::time::Duration;
+
+/// Coleta o uso acumulado do processo até agora (`getrusage` + `/proc/self/io`).
+pub fn collect(wall: Duration) -> ResourceUsage {
+    let mut usage = ResourceUsage {
+        wall_ms: wall.as_secs_f32() * 1000.0,
+        ..ResourceUsage::default()
+    };
+    rusage(&mut usage);
+    proc_io(&mut usage);
+    usage
+}
+
+#[cfg(unix)]
+fn rusage(usage: &mut ResourceUsage) {
+    let mut raw = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+    // SAFETY: `getrusage` apenas preenche a estrutura apontada, que vive nesta função.
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, raw.as_mut_ptr()) } != 0 {
+        return;
+    }
+    // SAFETY: a chamada retornou sucesso, então a estrutura foi inicializada.
+    let raw = unsafe { raw.assume_init() };
+    let millis = |time: libc::timeval| time.tv_sec as f32 * 1000.0 + time.tv_usec as f32 / 1000.0;
+    usage.cpu_user_ms = Some(millis(raw.ru_utime));
+    usage.cpu_system_ms = Some(millis(raw.ru_stime));
+    // Linux informa `ru_maxrss` em KiB; macOS, em bytes.
+    let scale = if cfg!(target_os = "macos") { 1 } else { 1024 };
+    usage.max_rss_bytes = Some(raw.ru_maxrss.max(0) as u64 * scale);
+}
+
+#[cfg(not(unix))]
+fn rusage(_usage: &mut ResourceUsage) {}
+
+fn proc_io(usage: &mut ResourceUsage) {
+    let Ok(raw) = std::fs::read_to_string("/proc/self/io") else {
+        return;
+    };
+    for line in raw.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().parse().ok();
+        match key {
+            "rchar" => usage.io_read_bytes = value,
+            "wchar" => usage.io_write_bytes = value,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_reports_wall_time_and_process_usage() {
+        let _ = std::fs::read("Cargo.toml");
+        let usage = collect(Duration::from_millis(1500));
+        assert!((usage.wall_ms - 1500.0).abs() < 0.01);
+        if cfg!(unix) {
+            assert!(usage.cpu_user_ms.is_some());
+            assert!(usage.max_rss_bytes.is_some_and(|rss| rss > 0));
+        }
+        if cfg!(target_os = "linux") {
+            assert!(usage.io_read_bytes.is_some_and(|bytes| bytes > 0));
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Desarme e reconstrução de arquivos em disco (`guardupload sanitize`).
+
+use crate::disarm::{disarm, DisarmError};
+use crate::engine::{collect_targets, relative_to_roots, WalkFilter};
+use crate::sniff::sniff_bytes;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Requisição do subcomando `sanitize`.
+#[derive(Debug)]
+pub struct SanitizeRequest {
+    pub paths: Vec<PathBuf>,
+    /// Diretório que recebe as versões reconstruídas (caminho relativo preservado).
+    pub out: PathBuf,
+    /// JSONL com um registro por arquivo; stdout quando ausente.
+    pub json: Option<PathBuf>,
+}
+
+/// Resultado do `sanitize`.
+#[derive(Debug, Default)]
+pub struct SanitizeOutcome {
+    pub sanitized: usize,
+    pub unsupported: usize,
+    pub failed: usize,
+    pub exit_code: i32,
+}
+
+/// Registro por arquivo: original, versão reconstruída e ações aplicadas.
+#[derive(Debug, Clone, Serialize)]
+pub struct SanitizeRecord {
+    pub file: String,
+    pub mime: String,
+    pub sha256: String,
+    /// `sanitized`, `unsupported` (não copiado) ou `failed`.
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sanitized: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sanitized_sha256: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Reconstrói cada arquivo suportado em `out`. Formatos sem reconstrução não são
+/// copiados; falhas (conteúdo inválido ou ativo residual) geram código de saída 1.
+pub fn run(request: &SanitizeRequest) -> Result<SanitizeOutcome> {
+    let targets = collect_targets(&request.paths, &WalkFilter::default())?;
+    let mut writer = match request.json.as_deref() {
+        Some(path) => Some(BufWriter::new(File::create(path).with_context(|| {
+            format!("não foi possível criar arquivo JSON {}", path.display())
+        })?)),
+        None => None,
+    };
+    let mut outcome = SanitizeOutcome::default();
+    for path in &targets {
+        let record = sanitize_file(path, &request.out, &request.paths)?;
+        match record.status {
+            "sanitized" => outcome.sanitized += 1,
+            "unsupported" => outcome.unsupported += 1,
+            _ => outcome.failed += 1,
+        }
+        let line = serde_json::to_string(&record)?;
+        match writer.as_mut() {
+            Some(writer) => writeln!(writer, "{line}")?,
+            None => println!("{line}"),
+        }
+    }
+    if let Some(writer) = writer.as_mut() {
+        writer.flush()?;
+    }
+    outcome.exit_code = i32::from(outcome.failed > 0);
+    Ok(outcome)
+}
+
+fn sanitize_file(path: &Path, out: &Path, roots: &[PathBuf]) -> Result<SanitizeRecord> {
+    let data = std::fs::read(path).with_context(|| format!("falha ao ler {}", path.display()))?;
+    let mime = sniff_bytes(&data)?.mime_real;
+    let mut record = SanitizeRecord {
+        file: path.display().to_string(),
+        sha256: hex::encode(Sha256::digest(&data)),
+        mime,
+        status: "sanitized",
+        sanitized: None,
+        sanitized_sha256: None,
+        actions: Vec::new(),
+        error: None,
+    };
+    let disarmed = match disarm(&record.mime, &data) {
+        Ok(disarmed) => disarmed,
+        Err(err) => {
+            record.status = match err {
+                DisarmError::Unsupported(_) => "unsupported",
+                DisarmError::Invalid(_) | DisarmError::Residual(_) => "failed",
+            };
+            record.error = Some(err.to_string());
+            return Ok(record);
+        }
+    };
+    let dest = out.join(relative_to_roots(path, roots));
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("não foi possível criar {}", parent.display()))?;
+    }
+    std::fs::write(&dest, &disarmed.data)
+        .with_context(|| format!("falha ao gravar {}", dest.display()))?;
+    record.sanitized = Some(dest.display().to_string());
+    record.sanitized_sha256 = Some(hex::encode(Sha256::digest(&disarmed.data)));
+    record.actions = disarmed.actions;
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn sanitize_maps_originals_to_rebuilt_copies() {
+        let dir = tempdir().expect("tempdir");
+        let input = dir.path().join("in");
+        std::fs::create_dir_all(input.join("docs")).unwrap();
+        std::fs::write(
+            input.join("docs/a.pdf"),
+            b"%PDF-1.4\n1 0 obj\n<< /OpenAction 2 0 R >>\nendobj\n%%EOF\n",
+        )
+        .unwrap();
+        std::fs::write(input.join("notas.txt"), b"texto").unwrap();
+        let request = SanitizeRequest {
+            paths: vec![input.clone()],
+            out: dir.path().join("out"),
+            json: Some(dir.path().join("sanitize.jsonl")),
+        };
+
+        let outcome = run(&request).expect("sanitize");
+        assert_eq!((outcome.sanitized, outcome.unsupported), (1, 1));
+        assert_eq!(outcome.exit_code, 0);
+        let rebuilt = std::fs::read(dir.path().join("out/docs/a.pdf")).expect("pdf");
+        assert!(rebuilt.windows(11).any(|window| window == b"/XpenAction"));
+        assert!(!dir.path().join("out/notas.txt").exists());
+
+        let report = std::fs::read_to_string(dir.path().join("sanitize.jsonl")).unwrap();
+        let pdf: serde_json::Value = report
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .find(|record: &serde_json::Value| record["mime"] == "application/pdf")
+            .expect("registro do PDF");
+        assert_eq!(
+            pdf["sanitized_sha256"],
+            hex::encode(Sha256::digest(&rebuilt))
+        );
+    }
+}
@@ -0,0 +1,729 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Servidor HTTP (`guardupload serve`) que expõe o mesmo pipeline do `scan`.
+//!
+//! Rotas: `POST /v1/scan` (corpo bruto ou `multipart/form-data`) devolve o
+//! `FileReport` em JSON; `GET /v1/precheck?mime=&size=&name=` avalia só as regras
+//! de metadados antes do envio; `GET /metrics` expõe as métricas Prometheus;
+//! `GET /healthz` responde `{"status": "ok"}`.
+
+use crate::cli::BuiltinPolicy;
+use crate::config::PolicyConfig;
+use crate::engine::{file_span, load_policy, log_policy_warnings, record_file_span};
+use crate::limits::Deadline;
+use crate::metrics::{self, Metrics, TextfileExporter};
+use crate::pipeline::{self, InlineInput};
+use crate::plugins;
+use crate::policy::PolicyEngine;
+use crate::precheck::{self, parse_size, PrecheckReport, PrecheckRequest};
+use crate::report::FileReport;
+use crate::risk;
+use crate::sniff::ClaimedMimeMap;
+use crate::webhook::Notifier;
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+pub const SCAN_ROUTE: &str = "/v1/scan";
+pub const PRECHECK_ROUTE: &str = "/v1/precheck";
+pub const HEALTH_ROUTE: &str = "/healthz";
+pub const METRICS_ROUTE: &str = "/metrics";
+
+/// Nome usado quando o cliente não informa `?name=`, `X-Filename` ou `filename`.
+const DEFAULT_UPLOAD_NAME: &str = "upload.bin";
+
+/// Intervalo de verificação do sinal de encerramento.
+const SHUTDOWN_POLL: Duration = Duration::from_millis(200);
+
+/// Campos derivados do subcomando `serve`.
+#[derive(Debug)]
+pub struct ServeRequest {
+    pub bind: String,
+    pub policy: Option<PathBuf>,
+    pub builtin_policy: BuiltinPolicy,
+    pub max_body_bytes: u64,
+    pub concurrency: usize,
+    pub timeout: Option<u64>,
+    pub claimed_mime_map: Option<PathBuf>,
+    /// Arquivo `.prom` gravado a cada `metrics_interval` (textfile collector).
+    pub metrics_textfile: Option<PathBuf>,
+    pub metrics_interval: Duration,
+    /// Webhook que recebe as decisões WARN/DENY (substitui `notifications.webhook_url`).
+    pub webhook: Option<String>,
+}
+
+/// Sobe o servidor e atende requisições até `shutdown` ser sinalizado.
+pub fn serve(request: ServeRequest, shutdown: Arc<AtomicBool>) -> Result<()> {
+    let service = Arc::new(Service::new(&request)?);
+    let server = Server::http(&request.bind)
+        .map_err(|err| anyhow!("não foi possível escutar em {}: {err}", request.bind))?;
+    tracing::info!(
+        bind = %request.bind,
+        concurrency = request.concurrency,
+        max_body_bytes = request.max_body_bytes,
+        "servidor HTTP iniciado"
+    );
+    let exporter = request.metrics_textfile.clone().map(|path| {
+        let mut exporter = TextfileExporter::new(path, request.metrics_interval);
+        let service = Arc::clone(&service);
+        let shutdown = Arc::clone(&shutdown);
+        std::thread::spawn(move || {
+            while !shutdown.load(Ordering::Relaxed) {
+                exporter.tick(&service.metrics);
+                std::thread::sleep(SHUTDOWN_POLL);
+            }
+            exporter.flush(&service.metrics);
+        })
+    });
+    run(Arc::new(server), service, request.concurrency, shutdown)?;
+    if let Some(exporter) = exporter {
+        let _ = exporter.join();
+    }
+    Ok(())
+}
+
+/// Distribui as conexões entre `concurrency` workers; cada worker atende uma
+/// requisição por vez, limitando o número de análises simultâneas. Quando
+/// `shutdown` é sinalizado, os workers terminam a requisição em curso e saem.
+fn run(
+    server: Arc<Server>,
+    service: Arc<Service>,
+    concurrency: usize,
+    shutdown: Arc<AtomicBool>,
+) -> Result<()> {
+    let concurrency = concurrency.max(1);
+    {
+        let server = Arc::clone(&server);
+        std::thread::spawn(move || {
+            while !shutdown.load(Ordering::Relaxed) {
+                std::thread::sleep(SHUTDOWN_POLL);
+            }
+            tracing::info!("encerrando servidor HTTP");
+            for _ in 0..concurrency {
+                server.unblock();
+            }
+        });
+    }
+    let workers: Vec<_> = (0..concurrency)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let service = Arc::clone(&service);
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    service.handle(request);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker
+            .join()
+            .map_err(|_| anyhow!("worker HTTP encerrado com pânico"))?;
+    }
+    Ok(())
+}
+
+/// Estado compartilhado entre os workers.
+struct Service {
+    policy: PolicyStore,
+    claimed_mimes: ClaimedMimeMap,
+    max_body_bytes: u64,
+    timeout: Option<u64>,
+    metrics: Metrics,
+    notifier: Notifier,
+}
+
+/// Erro devolvido ao cliente com status HTTP e mensagem.
+#[derive(Debug)]
+struct HttpError {
+    status: u16,
+    message: String,
+}
+
+impl HttpError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Resposta de sucesso de uma rota.
+enum Reply {
+    Health,
+    Report(Box<FileReport>),
+    Precheck(Box<PrecheckReport>),
+    Metrics(String),
+}
+
+/// Arquivo extraído da requisição.
+#[derive(Debug)]
+struct Upload {
+    name: String,
+    data: Vec<u8>,
+}
+
+impl Service {
+    fn new(request: &ServeRequest) -> Result<Self> {
+        let claimed_mimes = match request.claimed_mime_map.as_deref() {
+            Some(path) => ClaimedMimeMap::from_path(path)?,
+            None => ClaimedMimeMap::default(),
+        };
+        Ok(Self {
+            policy: PolicyStore::load(request.policy.clone(), request.builtin_policy)?,
+            claimed_mimes,
+            max_body_bytes: request.max_body_bytes,
+            timeout: request.timeout,
+            metrics: Metrics::new(),
+            notifier: Notifier::new(request.webhook.clone()),
+        })
+    }
+
+    fn handle(&self, mut request: Request) {
+        let started = Instant::now();
+        let mut content_type = "application/json";
+        let (status, decision, body) = match self.route(&mut request) {
+            Ok(Reply::Health) => (200, None, json!({ "status": "ok" }).to_string()),
+            Ok(Reply::Report(report)) => {
+                self.metrics.record(&report, started.elapsed());
+                (
+                    if report.error.is_some() { 500 } else { 200 },
+                    Some(report.policy.decision.clone()),
+                    serde_json::to_string(&report).unwrap_or_default(),
+                )
+            }
+            Ok(Reply::Precheck(report)) => (
+                200,
+                Some(report.policy.decision.clone()),
+                serde_json::to_string(&report).unwrap_or_default(),
+            ),
+            Ok(Reply::Metrics(text)) => {
+                content_type = metrics::CONTENT_TYPE;
+                (200, None, text)
+            }
+            Err(err) => (
+                err.status,
+                None,
+                json!({ "error": err.message }).to_string(),
+            ),
+        };
+        tracing::debug!(
+            method = %request.method(),
+            url = %request.url(),
+            status,
+            decision = decision.as_deref().unwrap_or("-"),
+            elapsed_ms = pipeline::elapsed_ms(started),
+            "requisição HTTP atendida"
+        );
+
+        let mut response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(header("Content-Type", content_type));
+        if let Some(decision) = decision {
+            response = response.with_header(header("X-GuardUpload-Decision", &decision));
+        }
+        if let Err(err) = request.respond(response) {
+            tracing::warn!("falha ao responder requisição HTTP: {err}");
+        }
+    }
+
+    fn route(&self, request: &mut Request) -> Result<Reply, HttpError> {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((url.as_str(), ""));
+        match (request.method(), path) {
+            (Method::Post, SCAN_ROUTE) => self
+                .scan(request, query)
+                .map(|report| Reply::Report(Box::new(report))),
+            (Method::Get, PRECHECK_ROUTE) => self
+                .precheck(query)
+                .map(|report| Reply::Precheck(Box::new(report))),
+            (Method::Get, HEALTH_ROUTE) => Ok(Reply::Health),
+            (Method::Get, METRICS_ROUTE) => Ok(Reply::Metrics(self.metrics.render())),
+            (_, SCAN_ROUTE | PRECHECK_ROUTE | HEALTH_ROUTE | METRICS_ROUTE) => {
+                Err(HttpError::new(405, "método não permitido"))
+            }
+            _ => Err(HttpError::new(404, "rota não encontrada")),
+        }
+    }
+
+    fn scan(&self, request: &mut Request, query: &str) -> Result<FileReport, HttpError> {
+        let body = read_body(request, self.max_body_bytes)?;
+        let content_type = header_value(request, "Content-Type").unwrap_or_default();
+        let lowered = content_type.to_ascii_lowercase();
+        let upload = if lowered.starts_with("multipart/form-data") {
+            parse_multipart(&content_type, &body)
+                .ok_or_else(|| HttpError::new(400, "multipart sem parte de arquivo"))?
+        } else if lowered.starts_with("application/json") {
+            // Mesmo formato das entradas de `--input-json`: `{"name", "content_b64"}`.
+            let input: InlineInput = serde_json::from_slice(&body)
+                .map_err(|err| HttpError::new(400, format!("JSON de upload inválido: {err}")))?;
+            let data = input
+                .decode()
+                .map_err(|err| HttpError::new(400, format!("{err:#}")))?;
+            let name = Some(base_name(&input.name))
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| DEFAULT_UPLOAD_NAME.to_string());
+            Upload { name, data }
+        } else {
+            let name = query_param(query, "name")
+                .or_else(|| header_value(request, "X-Filename"))
+                .map(|name| base_name(&name))
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| DEFAULT_UPLOAD_NAME.to_string());
+            Upload { name, data: body }
+        };
+
+        let policy_engine = self.policy.current();
+        let deadline = self
+            .timeout
+            .map(|secs| Deadline::after(Duration::from_secs(secs)))
+            .unwrap_or_default();
+        let name = Path::new(&upload.name);
+        let span = file_span(&upload.name);
+        let _entered = span.enter();
+        let mut report = match pipeline::analyze_bytes(
+            name,
+            &upload.data,
+            policy_engine.as_deref(),
+            &self.claimed_mimes,
+            &[],
+            &deadline,
+        ) {
+            Ok((mut report, outcome)) => {
+                record_file_span(&span, &report);
+                report.policy = outcome.into();
+                if let Some(engine) = policy_engine.as_deref() {
+                    report.redact(&engine.config().report.redact);
+                }
+                report
+            }
+            Err(err) => {
+                tracing::error!(file = %upload.name, "falha ao processar upload: {err:?}");
+                FileReport::error(name, format!("{err:#}"))
+            }
+        };
+        report.risk = Some(risk::assess(&report, 1));
+        self.notifier.notify(policy_engine.as_deref(), &report);
+        Ok(report)
+    }
+
+    fn precheck(&self, query: &str) -> Result<PrecheckReport, HttpError> {
+        let mime = query_param(query, "mime")
+            .filter(|mime| !mime.is_empty())
+            .ok_or_else(|| HttpError::new(400, "parâmetro mime obrigatório"))?;
+        let size_bytes = query_param(query, "size")
+            .map(|size| parse_size(&size))
+            .transpose()
+            .map_err(|err| HttpError::new(400, format!("{err:#}")))?;
+        let request = PrecheckRequest {
+            mime,
+            size_bytes,
+            name: query_param(query, "name").map(|name| base_name(&name)),
+        };
+        let policy_engine = self.policy.current();
+        Ok(precheck::precheck(
+            policy_engine.as_deref(),
+            &request,
+            &self.claimed_mimes,
+        ))
+    }
+}
+
+/// Política compartilhada, recarregada quando o arquivo `--policy` ou uma lista
+/// de hashes referenciada por ele é alterada.
+struct PolicyStore {
+    path: Option<PathBuf>,
+    state: RwLock<LoadedPolicy>,
+}
+
+struct LoadedPolicy {
+    /// Política e listas de `hashes` observadas, com o mtime da última carga.
+    files: Vec<PathBuf>,
+    modified: Vec<Option<SystemTime>>,
+    engine: Option<Arc<PolicyEngine>>,
+}
+
+impl PolicyStore {
+    fn load(path: Option<PathBuf>, builtin: BuiltinPolicy) -> Result<Self> {
+        let engine = load_policy(path.as_deref(), builtin)?
+            .map(plugins::policy_engine)
+            .transpose()?
+            .map(Arc::new);
+        let files = path
+            .as_deref()
+            .map(|path| watched_files(path, engine.as_deref()))
+            .unwrap_or_default();
+        let modified = files.iter().map(|file| modified_at(file)).collect();
+        Ok(Self {
+            path,
+            state: RwLock::new(LoadedPolicy {
+                files,
+                modified,
+                engine,
+            }),
+        })
+    }
+
+    /// Política vigente; se o arquivo ou uma lista de hashes mudou desde a
+    /// última carga, recarrega. Uma política inválida é registrada em log e a
+    /// anterior continua em uso.
+    fn current(&self) -> Option<Arc<PolicyEngine>> {
+        if let Some(path) = self.path.as_deref() {
+            let stale = {
+                let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+                let modified: Vec<_> = state.files.iter().map(|file| modified_at(file)).collect();
+                (modified != state.modified).then_some(modified)
+            };
+            if let Some(modified) = stale {
+                self.reload(path, modified);
+            }
+        }
+        self.state
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .engine
+            .clone()
+    }
+
+    fn reload(&self, path: &Path, modified: Vec<Option<SystemTime>>) {
+        let mut state = self.state.write().unwrap_or_else(PoisonError::into_inner);
+        if state.modified == modified {
+            return;
+        }
+        state.modified = modified;
+        let loaded = PolicyConfig::from_path(path).and_then(|config| {
+            log_policy_warnings(&config);
+            plugins::policy_engine(config)
+        });
+        match loaded {
+            Ok(engine) => {
+                // A nova política pode referenciar outras listas.
+                state.files = watched_files(path, Some(&engine));
+                state.modified = state.files.iter().map(|file| modified_at(file)).collect();
+                state.engine = Some(Arc::new(engine));
+                tracing::info!(policy = %path.display(), "política recarregada");
+            }
+            Err(err) => tracing::warn!(
+                policy = %path.display(),
+                "falha ao recarregar política, mantendo a anterior: {err:#}"
+            ),
+        }
+    }
+}
+
+/// Arquivo de política seguido das listas de hashes que ele referencia.
+fn watched_files(path: &Path, engine: Option<&PolicyEngine>) -> Vec<PathBuf> {
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    std::iter::once(path.to_path_buf())
+        .chain(engine.map_or_else(Vec::new, |engine| {
+            engine.config().hashes.list_files(base_dir)
+        }))
+        .collect()
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Lê o corpo respeitando `max_body_bytes` (413 quando excedido).
+fn read_body(request: &mut Request, max_body_bytes: u64) -> Result<Vec<u8>, HttpError> {
+    let too_large = || {
+        HttpError::new(
+            413,
+            format!("corpo excede o limite de {max_body_bytes} bytes"),
+        )
+    };
+    if request
+        .body_length()
+        .is_some_and(|length| length as u64 > max_body_bytes)
+    {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(max_body_bytes + 1)
+        .read_to_end(&mut body)
+        .map_err(|err| HttpError::new(400, format!("falha ao ler corpo: {err}")))?;
+    if body.len() as u64 > max_body_bytes {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("cabeçalho HTTP válido")
+}
+
+fn header_value(request: &Request, name: &str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.as_str().as_str().eq_ignore_ascii_case(name))
+        .map(|header| header.value.as_str().to_string())
+}
+
+/// Extrai a primeira parte com `filename` de um corpo `multipart/form-data`.
+fn parse_multipart(content_type: &str, body: &[u8]) -> Option<Upload> {
+    let boundary = content_type
+        .split(';')
+        .find_map(|param| param.trim().strip_prefix("boundary="))?
+        .trim_matches('"');
+    let delimiter = format!("--{boundary}");
+    let mut rest = &body[find(body, delimiter.as_bytes())? + delimiter.len()..];
+    loop {
+        if rest.starts_with(b"--") {
+            return None;
+        }
+        let end = find(rest, delimiter.as_bytes())?;
+        let part = &rest[..end];
+        rest = &rest[end + delimiter.len()..];
+
+        let part = part.strip_prefix(b"\r\n").unwrap_or(part);
+        let header_end = find(part, b"\r\n\r\n")?;
+        let headers = String::from_utf8_lossy(&part[..header_end]);
+        let data = &part[header_end + 4..];
+        let data = data.strip_suffix(b"\r\n").unwrap_or(data);
+        let filename = headers
+            .lines()
+            .find(|line| {
+                line.to_ascii_lowercase()
+                    .starts_with("content-disposition:")
+            })
+            .and_then(|line| disposition_param(line, "filename"));
+        if let Some(filename) = filename {
+            let name = base_name(&filename);
+            return Some(Upload {
+                name: if name.is_empty() {
+                    DEFAULT_UPLOAD_NAME.to_string()
+                } else {
+                    name
+                },
+                data: data.to_vec(),
+            });
+        }
+    }
+}
+
+fn disposition_param(line: &str, name: &str) -> Option<String> {
+    line.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.trim().split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == name).then(|| percent_decode(value))
+    })
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let escaped = bytes
+                    .get(index + 1..index + 3)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match escaped {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        index += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Mantém só o nome final (clientes podem enviar caminhos completos).
+fn base_name(name: &str) -> String {
+    name.rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    fn serve_request(policy: Option<PathBuf>) -> ServeRequest {
+        ServeRequest {
+            bind: "127.0.0.1:0".into(),
+            policy,
+            builtin_policy: BuiltinPolicy::Strict,
+            max_body_bytes: 1024,
+            concurrency: 2,
+            timeout: None,
+            claimed_mime_map: None,
+            metrics_textfile: None,
+            metrics_interval: Duration::from_secs(15),
+            webhook: None,
+        }
+    }
+
+    #[test]
+    fn multipart_and_query_helpers_extract_upload() {
+        let body = b"--XyZ\r\nContent-Disposition: form-data; name=\"note\"\r\n\r\nhi\r\n\
+--XyZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"C:\\\\docs\\\\a b.pdf\"\r\n\
+Content-Type: application/pdf\r\n\r\n%PDF-1.4\r\n--XyZ--\r\n";
+        let upload = parse_multipart("multipart/form-data; boundary=XyZ", body).expect("upload");
+        assert_eq!(upload.name, "a b.pdf");
+        assert_eq!(upload.data, b"%PDF-1.4");
+        assert!(parse_multipart("multipart/form-data; boundary=XyZ", b"--XyZ--\r\n").is_none());
+
+        assert_eq!(
+            query_param("x=1&name=fatura%20final.pdf", "name").as_deref(),
+            Some("fatura final.pdf")
+        );
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    #[test]
+    fn policy_store_reloads_changed_file_and_keeps_previous_on_error() {
+        let dir = tempdir().expect("dir");
+        let path = dir.path().join("policy.yaml");
+        std::fs::write(&path, "defaults:\n  max_size_mb: 1\n").expect("write");
+        let store = PolicyStore::load(Some(path.clone()), BuiltinPolicy::None).expect("load");
+        let max_size = |store: &PolicyStore| store.current().unwrap().config().defaults.max_size_mb;
+        assert_eq!(max_size(&store), Some(1));
+
+        let touch = |secs: u64| {
+            File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(SystemTime::now() + Duration::from_secs(secs)))
+                .expect("mtime");
+        };
+        std::fs::write(&path, "defaults:\n  max_size_mb: 2\n").expect("write");
+        touch(10);
+        assert_eq!(max_size(&store), Some(2));
+
+        std::fs::write(&path, "defaults: [").expect("write");
+        touch(20);
+        assert_eq!(max_size(&store), Some(2));
+    }
+
+    #[test]
+    fn policy_store_reloads_when_a_hash_list_file_changes() {
+        let dir = tempdir().expect("dir");
+        let path = dir.path().join("policy.yaml");
+        let list = dir.path().join("deny.txt");
+        std::fs::write(&path, "hashes:\n  deny: [deny.txt]\n").expect("write");
+        std::fs::write(&list, format!("{}\n", "a".repeat(64))).expect("write");
+        let store = PolicyStore::load(Some(path), BuiltinPolicy::None).expect("load");
+        let denied =
+            |store: &PolicyStore| store.current().unwrap().config().hashes.deny_digests.len();
+        assert_eq!(denied(&store), 1);
+
+        std::fs::write(&list, format!("{}\n{}\n", "a".repeat(64), "b".repeat(64))).expect("write");
+        File::options()
+            .write(true)
+            .open(&list)
+            .and_then(|file| file.set_modified(SystemTime::now() + Duration::from_secs(10)))
+            .expect("mtime");
+        assert_eq!(denied(&store), 2);
+    }
+
+    #[test]
+    fn scan_route_returns_report_with_decision_header() {
+        let server = Arc::new(Server::http("127.0.0.1:0").expect("bind"));
+        let addr = server.server_addr().to_ip().expect("tcp");
+        let service = Arc::new(Service::new(&serve_request(None)).expect("service"));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let worker = {
+            let shutdown = Arc::clone(&shutdown);
+            std::thread::spawn(move || run(server, service, 2, shutdown))
+        };
+        let base = format!("http://{addr}");
+
+        let response = ureq::post(&format!("{base}{SCAN_ROUTE}?name=nota.txt"))
+            .send_bytes(b"hello world\n")
+            .expect("scan");
+        assert_eq!(response.header("X-GuardUpload-Decision"), Some("ALLOW"));
+        let report: serde_json::Value =
+            serde_json::from_str(&response.into_string().expect("body")).expect("json");
+        assert_eq!(report["file"], "nota.txt");
+        assert_eq!(report["sniff"]["mime_real"], "text/plain");
+
+        let precheck = ureq::get(&format!(
+            "{base}{PRECHECK_ROUTE}?mime=application/x-msdownload&size=1MB&name=setup.exe"
+        ))
+        .call()
+        .expect("precheck");
+        assert_eq!(precheck.header("X-GuardUpload-Decision"), Some("DENY"));
+        let missing_mime = ureq::get(&format!("{base}{PRECHECK_ROUTE}?size=1")).call();
+        assert!(matches!(missing_mime, Err(ureq::Error::Status(400, _))));
+
+        let metrics = ureq::get(&format!("{base}{METRICS_ROUTE}"))
+            .call()
+            .expect("metrics")
+            .into_string()
+            .expect("body");
+        assert!(metrics.contains("guardupload_decisions_total{decision=\"ALLOW\"} 1\n"));
+
+        let health = ureq::get(&format!("{base}{HEALTH_ROUTE}"))
+            .call()
+            .expect("health")
+            .into_string()
+            .expect("body");
+        let health: serde_json::Value = serde_json::from_str(&health).expect("json");
+        assert_eq!(health["status"], "ok");
+
+        let too_large = ureq::post(&format!("{base}{SCAN_ROUTE}")).send_bytes(&[0u8; 2048]);
+        assert!(matches!(too_large, Err(ureq::Error::Status(413, _))));
+
+        let json_request =
+            || ureq::post(&format!("{base}{SCAN_ROUTE}")).set("Content-Type", "application/json");
+        let response = json_request()
+            .send_string(r#"{"name": "../lote/nota.txt", "content_b64": "aGVsbG8gd29ybGQK"}"#)
+            .expect("scan json");
+        assert_eq!(response.header("X-GuardUpload-Decision"), Some("ALLOW"));
+        let report: serde_json::Value =
+            serde_json::from_str(&response.into_string().expect("body")).expect("json");
+        assert_eq!(report["file"], "nota.txt");
+        assert_eq!(report["size_bytes"], 12);
+        let invalid = json_request().send_string(r#"{"name": "a.txt", "content_b64": "@@"}"#);
+        assert!(matches!(invalid, Err(ureq::Error::Status(400, _))));
+        let oversized = json_request()
+            .send_string(&json!({ "name": "a.txt", "content_b64": "A".repeat(2048) }).to_string());
+        assert!(matches!(oversized, Err(ureq::Error::Status(413, _))));
+        let missing = ureq::get(&format!("{base}/nada")).call();
+        assert!(matches!(missing, Err(ureq::Error::Status(404, _))));
+
+        shutdown.store(true, Ordering::Relaxed);
+        worker.join().expect("join").expect("run");
+    }
+}
@@ -0,0 +1,422 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Execução de `watch`/`serve` como serviço (unit systemd ou serviço Windows)
+//! e geração da configuração de instalação pelo próprio binário.
+
+use crate::server::{self, ServeRequest};
+use crate::watch::{self, WatchRequest};
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Nome padrão da unit/serviço.
+pub const DEFAULT_SERVICE_NAME: &str = "guardupload";
+
+/// Tarefa de longa duração executada pelo serviço.
+#[derive(Debug)]
+pub enum ServiceTask {
+    Watch(WatchRequest),
+    Serve(ServeRequest),
+}
+
+impl ServiceTask {
+    fn label(&self) -> &'static str {
+        match self {
+            ServiceTask::Watch(_) => "watch",
+            ServiceTask::Serve(_) => "serve",
+        }
+    }
+
+    fn run(self, shutdown: Arc<AtomicBool>) -> Result<()> {
+        match self {
+            ServiceTask::Watch(request) => watch::watch(request, shutdown),
+            ServiceTask::Serve(request) => server::serve(request, shutdown),
+        }
+    }
+}
+
+/// Requisição do `service install`.
+#[derive(Debug)]
+pub struct ServiceInstallRequest {
+    pub name: String,
+    pub log_file: Option<PathBuf>,
+    /// Segundos de espera antes de reiniciar após falha.
+    pub restart_sec: u64,
+    /// Diretório das units systemd (ignorado no Windows).
+    pub unit_dir: PathBuf,
+    /// Apenas imprime a unit/os comandos `sc.exe`, sem instalar.
+    pub dry_run: bool,
+    /// Linha de comando da tarefa (`watch ...` ou `serve ...`), já validada.
+    pub args: Vec<String>,
+}
+
+/// Requisição do `service run`.
+#[derive(Debug)]
+pub struct ServiceRunRequest {
+    pub name: String,
+    pub task: ServiceTask,
+}
+
+/// Instala a unit systemd (Unix) ou registra o serviço no SCM (Windows).
+pub fn install(request: &ServiceInstallRequest) -> Result<()> {
+    validate_name(&request.name)?;
+    let exe = std::env::current_exe().context("não foi possível localizar o executável")?;
+    let run_args = run_arguments(request);
+
+    if cfg!(windows) {
+        for command in sc_commands(&exe, &run_args, request) {
+            if request.dry_run {
+                println!("sc.exe {}", windows_command_line(&command));
+                continue;
+            }
+            let status = Command::new("sc.exe")
+                .args(&command)
+                .status()
+                .context("falha ao executar sc.exe")?;
+            if !status.success() {
+                bail!("sc.exe {} falhou ({status})", command[0]);
+            }
+        }
+        if !request.dry_run {
+            tracing::info!(service = %request.name, "serviço Windows registrado");
+        }
+        return Ok(());
+    }
+
+    // Caminhos relativos dos argumentos continuam valendo a partir do diretório
+    // em que o `service install` foi executado.
+    let cwd = std::env::current_dir().context("não foi possível ler o diretório atual")?;
+    let unit = systemd_unit(&exe, &cwd, &run_args, request);
+    if request.dry_run {
+        print!("{unit}");
+        return Ok(());
+    }
+    let path = request.unit_dir.join(format!("{}.service", request.name));
+    std::fs::write(&path, unit)
+        .with_context(|| format!("não foi possível gravar {}", path.display()))?;
+    tracing::info!(
+        unit = %path.display(),
+        "unit instalada; ative com `systemctl daemon-reload && systemctl enable --now {}`",
+        request.name
+    );
+    Ok(())
+}
+
+/// Executa a tarefa até SIGTERM/SIGINT (Unix) ou o comando Stop do SCM (Windows).
+pub fn run(request: ServiceRunRequest) -> Result<()> {
+    let ServiceRunRequest { name, task } = request;
+    #[cfg(windows)]
+    let task = match windows::run_dispatcher(&name, task)? {
+        Some(task) => task,
+        None => return Ok(()),
+    };
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&shutdown);
+    ctrlc::set_handler(move || flag.store(true, Ordering::Relaxed))
+        .context("falha ao instalar tratador de sinais")?;
+    tracing::info!(service = %name, task = task.label(), "serviço iniciado");
+    task.run(shutdown)?;
+    tracing::info!(service = %name, "serviço encerrado");
+    Ok(())
+}
+
+/// O nome vira o arquivo `<nome>.service` em `--unit-dir`; separadores e `..`
+/// levariam a unit para fora dele.
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+        bail!("nome de serviço inválido: {name:?}");
+    }
+    Ok(())
+}
+
+/// Argumentos de `guardupload service run ...` gravados na unit/serviço.
+fn run_arguments(request: &ServiceInstallRequest) -> Vec<String> {
+    let mut args = vec![
+        "service".to_string(),
+        "run".to_string(),
+        "--name".to_string(),
+        request.name.clone(),
+    ];
+    if let Some(log_file) = &request.log_file {
+        args.push("--log-file".to_string());
+        args.push(log_file.display().to_string());
+    }
+    args.push("--".to_string());
+    args.extend(request.args.iter().cloned());
+    args
+}
+
+fn systemd_unit(
+    exe: &Path,
+    working_dir: &Path,
+    run_args: &[String],
+    request: &ServiceInstallRequest,
+) -> String {
+    let exec_start = std::iter::once(exe.display().to_string())
+        .chain(run_args.iter().cloned())
+        .map(|arg| systemd_quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let task = request.args.first().map(String::as_str).unwrap_or("watch");
+    format!(
+        "[Unit]\n\
+         Description=GuardUpload ({task}) - validação de uploads\n\
+         After=network-online.target\n\
+         Wants=network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=simple\n\
+         WorkingDirectory={working_dir}\n\
+         ExecStart={exec_start}\n\
+         Restart=on-failure\n\
+         RestartSec={restart}\n\
+         KillSignal=SIGTERM\n\
+         TimeoutStopSec=30\n\
+         \n\
+         [Install]\n\
+         WantedBy=multi-user.target\n",
+        working_dir = working_dir.display().to_string().replace('%', "%%"),
+        restart = request.restart_sec,
+    )
+}
+
+/// Aspas no estilo systemd: argumentos com espaço/aspas ficam entre `"`, `%` vira
+/// `%%` e `$` vira `$$` (sem expansão de variáveis de ambiente).
+fn systemd_quote(arg: &str) -> String {
+    let escaped = arg.replace('%', "%%").replace('$', "$$");
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|ch| ch.is_whitespace() || matches!(ch, '"' | '\'' | '\\' | ';'))
+    {
+        return escaped;
+    }
+    format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Comandos `sc.exe` para criar o serviço (início automático) e reiniciar após falhas.
+fn sc_commands(
+    exe: &Path,
+    run_args: &[String],
+    request: &ServiceInstallRequest,
+) -> Vec<Vec<String>> {
+    let bin_path = windows_command_line(
+        &std::iter::once(exe.display().to_string())
+            .chain(run_args.iter().cloned())
+            .collect::<Vec<_>>(),
+    );
+    let restart_ms = request.restart_sec.saturating_mul(1000);
+    let restart = format!("restart/{restart_ms}");
+    vec![
+        vec![
+            "create".to_string(),
+            request.name.clone(),
+            "binPath=".to_string(),
+            bin_path,
+            "start=".to_string(),
+            "auto".to_string(),
+            "DisplayName=".to_string(),
+            "GuardUpload".to_string(),
+        ],
+        vec![
+            "failure".to_string(),
+            request.name.clone(),
+            "reset=".to_string(),
+            "86400".to_string(),
+            "actions=".to_string(),
+            [restart.as_str(); 3].join("/"),
+        ],
+    ]
+}
+
+/// Junta argumentos seguindo as regras de `CommandLineToArgvW`.
+fn windows_command_line(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+                return arg.clone();
+            }
+            let mut quoted = String::from("\"");
+            let mut backslashes = 0;
+            for ch in arg.chars() {
+                match ch {
+                    '\\' => backslashes += 1,
+                    '"' => {
+                        quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                        quoted.push('"');
+                        backslashes = 0;
+                    }
+                    _ => {
+                        quoted.push_str(&"\\".repeat(backslashes));
+                        quoted.push(ch);
+                        backslashes = 0;
+                    }
+                }
+            }
+            quoted.push_str(&"\\".repeat(backslashes * 2));
+            quoted.push('"');
+            quoted
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::ServiceTask;
+    use anyhow::{anyhow, Result};
+    use std::ffi::OsString;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex, PoisonError};
+    use std::time::Duration;
+    use windows_service::service::{
+        ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus,
+        ServiceType,
+    };
+    use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+    use windows_service::{define_windows_service, service_dispatcher};
+
+    /// `ERROR_FAILED_SERVICE_CONTROLLER_CONNECT`: processo iniciado fora do SCM.
+    const NOT_STARTED_BY_SCM: i32 = 1063;
+
+    static TASK: Mutex<Option<(String, ServiceTask)>> = Mutex::new(None);
+
+    define_windows_service!(ffi_service_main, service_main);
+
+    /// Entrega a tarefa ao SCM; devolve a tarefa quando o processo roda em console.
+    pub(super) fn run_dispatcher(name: &str, task: ServiceTask) -> Result<Option<ServiceTask>> {
+        *TASK.lock().unwrap_or_else(PoisonError::into_inner) = Some((name.to_string(), task));
+        match service_dispatcher::start(name, ffi_service_main) {
+            Ok(()) => Ok(None),
+            Err(windows_service::Error::Winapi(err))
+                if err.raw_os_error() == Some(NOT_STARTED_BY_SCM) =>
+            {
+                Ok(TASK
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .take()
+                    .map(|(_, task)| task))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn service_main(_arguments: Vec<OsString>) {
+        if let Err(err) = run_service() {
+            tracing::error!("serviço encerrado com erro: {err:#}");
+        }
+    }
+
+    fn run_service() -> Result<()> {
+        let (name, task) = TASK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .ok_or_else(|| anyhow!("tarefa do serviço ausente"))?;
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&shutdown);
+        let handle = service_control_handler::register(&name, move |control| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                flag.store(true, Ordering::Relaxed);
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        })?;
+        let status = |state, controls_accepted, code| ServiceStatus {
+            service_type: ServiceType::OWN_PROCESS,
+            current_state: state,
+            controls_accepted,
+            exit_code: ServiceExitCode::ServiceSpecific(code),
+            checkpoint: 0,
+            wait_hint: Duration::default(),
+            process_id: None,
+        };
+        handle.set_service_status(status(
+            ServiceState::Running,
+            ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN,
+            0,
+        ))?;
+        tracing::info!(service = %name, task = task.label(), "serviço iniciado");
+        let result = task.run(shutdown);
+        handle.set_service_status(status(
+            ServiceState::Stopped,
+            ServiceControlAccept::empty(),
+            u32::from(result.is_err()),
+        ))?;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn install_request(args: &[&str]) -> ServiceInstallRequest {
+        ServiceInstallRequest {
+            name: "uploads".into(),
+            log_file: Some(PathBuf::from("/var/log/guard upload.log")),
+            restart_sec: 7,
+            unit_dir: PathBuf::from("/etc/systemd/system"),
+            dry_run: true,
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn systemd_unit_runs_service_with_restart_metadata() {
+        let request = install_request(&["watch", "/srv/hot folder", "--policy", "p%1$HOME.yaml"]);
+        let unit = systemd_unit(
+            Path::new("/usr/bin/guardupload"),
+            Path::new("/opt/guard%upload"),
+            &run_arguments(&request),
+            &request,
+        );
+        assert!(unit.contains(
+            "ExecStart=/usr/bin/guardupload service run --name uploads \
+             --log-file \"/var/log/guard upload.log\" -- watch \"/srv/hot folder\" \
+             --policy p%%1$$HOME.yaml\n"
+        ));
+        assert!(unit.contains("WorkingDirectory=/opt/guard%%upload\n"));
+        assert!(unit.contains("Restart=on-failure\nRestartSec=7\n"));
+        assert!(unit.contains("Description=GuardUpload (watch)"));
+    }
+
+    #[test]
+    fn service_names_cannot_leave_the_unit_dir() {
+        for name in ["../evil", "a/b", r"a\b", "..", ""] {
+            let mut request = install_request(&["watch", "/srv"]);
+            request.name = name.into();
+            assert!(install(&request).is_err(), "{name:?}");
+        }
+        assert!(validate_name("guardupload-uploads").is_ok());
+    }
+
+    #[test]
+    fn sc_commands_quote_bin_path_and_configure_failure_actions() {
+        let request = install_request(&["serve", "--bind", "0.0.0.0:8080"]);
+        let commands = sc_commands(
+            Path::new(r"C:\Program Files\GuardUpload\guardupload.exe"),
+            &run_arguments(&request),
+            &request,
+        );
+        assert_eq!(commands[0][0], "create");
+        assert!(commands[0][3].starts_with(
+            r#""C:\Program Files\GuardUpload\guardupload.exe" service run --name uploads"#
+        ));
+        assert_eq!(
+            commands[1].last().map(String::as_str),
+            Some("restart/7000/restart/7000/restart/7000")
+        );
+        assert_eq!(
+            windows_command_line(&[r#"a "b""#.to_string(), r"c\".to_string()]),
+            r#""a \"b\"" c\"#
+        );
+    }
+}
@@ -0,0 +1,213 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Agrupamento de um relatório JSONL por distância TLSH (`guardupload similar`).
+
+use crate::analyzers::tlsh::Tlsh;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+
+/// Requisição do subcomando `similar`.
+#[derive(Debug)]
+pub struct SimilarRequest {
+    /// Relatório JSONL gerado por `scan --hash tlsh`.
+    pub report: PathBuf,
+    /// Distância TLSH máxima para dois arquivos caírem no mesmo grupo.
+    pub threshold: u32,
+}
+
+/// Resultado do `similar`.
+#[derive(Debug)]
+pub struct SimilarOutcome {
+    pub clusters: Vec<Cluster>,
+    /// Registros sem `hashes.tlsh` (ex.: arquivos pequenos ou uniformes demais).
+    pub skipped: usize,
+    pub exit_code: i32,
+}
+
+/// Grupo de arquivos ligados por distância ≤ limiar (ligação simples).
+#[derive(Debug, Clone, Serialize)]
+pub struct Cluster {
+    pub cluster: usize,
+    pub decisions: BTreeMap<String, usize>,
+    pub files: Vec<ClusterMember>,
+}
+
+/// Arquivo do relatório com o digest TLSH e a distância ao vizinho mais próximo do grupo.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterMember {
+    pub file: String,
+    pub sha256: String,
+    pub decision: String,
+    pub tlsh: String,
+    pub nearest_distance: u32,
+}
+
+struct Entry {
+    file: String,
+    sha256: String,
+    decision: String,
+    digest: String,
+    tlsh: Tlsh,
+}
+
+/// Lê o relatório e devolve os grupos com dois ou mais arquivos.
+pub fn run(request: &SimilarRequest) -> Result<SimilarOutcome> {
+    let file = File::open(&request.report)
+        .with_context(|| format!("falha ao abrir {}", request.report.display()))?;
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Value = serde_json::from_str(&line).with_context(|| {
+            format!(
+                "registro inválido em {}:{}",
+                request.report.display(),
+                index + 1
+            )
+        })?;
+        match entry_from_record(&record) {
+            Some(entry) => entries.push(entry),
+            None => skipped += 1,
+        }
+    }
+
+    Ok(SimilarOutcome {
+        clusters: cluster(&entries, request.threshold),
+        skipped,
+        exit_code: 0,
+    })
+}
+
+fn entry_from_record(record: &Value) -> Option<Entry> {
+    let digest = record["hashes"]["tlsh"].as_str()?;
+    let text = |value: &Value| value.as_str().unwrap_or_default().to_string();
+    Some(Entry {
+        file: text(&record["file"]),
+        sha256: text(&record["sha256"]),
+        decision: text(&record["policy"]["decision"]),
+        digest: digest.to_string(),
+        tlsh: Tlsh::parse(digest)?,
+    })
+}
+
+fn cluster(entries: &[Entry], threshold: u32) -> Vec<Cluster> {
+    let mut parent: Vec<usize> = (0..entries.len()).collect();
+    let mut nearest = vec![u32::MAX; entries.len()];
+    for i in 0..entries.len() {
+        for j in i + 1..entries.len() {
+            let distance = entries[i].tlsh.distance(&entries[j].tlsh);
+            if distance <= threshold {
+                nearest[i] = nearest[i].min(distance);
+                nearest[j] = nearest[j].min(distance);
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for index in 0..entries.len() {
+        let root = find(&mut parent, index);
+        groups.entry(root).or_default().push(index);
+    }
+
+    groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .enumerate()
+        .map(|(id, members)| {
+            let mut decisions = BTreeMap::new();
+            let files = members
+                .into_iter()
+                .map(|index| {
+                    let entry = &entries[index];
+                    *decisions.entry(entry.decision.clone()).or_insert(0) += 1;
+                    ClusterMember {
+                        file: entry.file.clone(),
+                        sha256: entry.sha256.clone(),
+                        decision: entry.decision.clone(),
+                        tlsh: entry.digest.clone(),
+                        nearest_distance: nearest[index],
+                    }
+                })
+                .collect();
+            Cluster {
+                cluster: id + 1,
+                decisions,
+                files,
+            }
+        })
+        .collect()
+}
+
+fn find(parent: &mut [usize], mut index: usize) -> usize {
+    while parent[index] != index {
+        parent[index] = parent[parent[index]];
+        index = parent[index];
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    fn pseudo_random(seed: u32, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn groups_variants_of_denied_file() {
+        let original = pseudo_random(1, 8192);
+        let mut variant = original.clone();
+        variant[500..520].copy_from_slice(&[0x20; 20]);
+        let records = [
+            ("malware.exe", &original, "DENY"),
+            ("malware-v2.exe", &variant, "ALLOW"),
+            ("other.bin", &pseudo_random(9, 8192), "ALLOW"),
+        ];
+
+        let mut report = tempfile::NamedTempFile::new().expect("tmp");
+        for (name, data, decision) in records {
+            let record = json!({
+                "file": name,
+                "sha256": "00",
+                "hashes": { "tlsh": Tlsh::digest(data).expect("tlsh") },
+                "policy": { "decision": decision },
+            });
+            writeln!(report, "{record}").expect("write");
+        }
+        writeln!(report, "{}", json!({"file": "tiny.txt", "policy": {}})).expect("write");
+
+        let outcome = run(&SimilarRequest {
+            report: report.path().to_path_buf(),
+            threshold: 50,
+        })
+        .expect("similar");
+        assert_eq!(outcome.skipped, 1);
+        assert_eq!(outcome.clusters.len(), 1);
+        let cluster = &outcome.clusters[0];
+        let files: Vec<&str> = cluster.files.iter().map(|m| m.file.as_str()).collect();
+        assert_eq!(files, vec!["malware.exe", "malware-v2.exe"]);
+        assert_eq!(cluster.decisions["DENY"], 1);
+    }
+}
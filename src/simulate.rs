@@ -0,0 +1,208 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Simulação de overrides sobre relatórios gravados (`guardupload policy simulate`).
+//!
+//! Cada registro é decidido duas vezes com [`PolicyEngine::replay`], com a
+//! política base e com ela mais os overrides propostos, sem reler arquivos. A
+//! comparação entre as duas decisões isola o efeito do override; registros cuja
+//! decisão base difere da gravada contam em `drift` (relatório gerado com outra
+//! política ou com regras que a simulação não refaz, como as de sessão).
+
+use crate::config::{PolicyConfig, PolicyOverride};
+use crate::policy::{DecisionOutcome, PolicyEngine};
+use crate::report::FileReport;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Requisição do `policy simulate`.
+#[derive(Debug)]
+pub struct SimulateRequest {
+    /// Relatório do `scan` (JSONL, `--format pretty` ou `json-array`).
+    pub report: PathBuf,
+    /// Política base; `None` usa a configuração vazia.
+    pub policy: Option<PolicyConfig>,
+    /// Overrides propostos, aplicados depois dos da política base.
+    pub overrides: Vec<PolicyOverride>,
+}
+
+/// Resumo das mudanças de decisão causadas pelos overrides.
+#[derive(Debug, Default, Serialize)]
+pub struct SimulationSummary {
+    pub files: usize,
+    /// Registros `ERROR` ou sem SHA-256, que não têm fatos para decidir.
+    pub skipped: usize,
+    pub changed: usize,
+    /// Decisão igual, mas com regras diferentes.
+    pub rules_changed: usize,
+    /// Decisão base diferente da gravada no relatório.
+    pub drift: usize,
+    pub before: BTreeMap<String, usize>,
+    pub after: BTreeMap<String, usize>,
+    /// Contagem por transição (`ALLOW->DENY`).
+    pub transitions: BTreeMap<String, usize>,
+    pub changes: Vec<DecisionChange>,
+}
+
+/// Arquivo cuja decisão muda com os overrides.
+#[derive(Debug, Clone, Serialize)]
+pub struct DecisionChange {
+    pub file: String,
+    pub sha256: String,
+    pub mime: String,
+    pub before: String,
+    pub after: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules_added: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules_removed: Vec<String>,
+}
+
+/// Decide cada registro com e sem os overrides e resume as diferenças.
+pub fn run(request: SimulateRequest) -> Result<SimulationSummary> {
+    let base = request.policy.unwrap_or_default();
+    let proposed = request
+        .overrides
+        .into_iter()
+        .try_fold(base.clone(), PolicyConfig::with_override)?;
+    let base = PolicyEngine::new(base);
+    let proposed = PolicyEngine::new(proposed);
+
+    let mut summary = SimulationSummary::default();
+    for report in load_reports(&request.report)? {
+        summary.files += 1;
+        if report.sha256.is_empty() || report.policy.decision == "ERROR" {
+            summary.skipped += 1;
+            continue;
+        }
+        let before = base.replay(&report);
+        let after = proposed.replay(&report);
+        if before.decision.as_str() != report.policy.decision {
+            summary.drift += 1;
+        }
+        *summary
+            .before
+            .entry(before.decision.as_str().to_string())
+            .or_default() += 1;
+        *summary
+            .after
+            .entry(after.decision.as_str().to_string())
+            .or_default() += 1;
+        if before.decision != after.decision {
+            summary.changed += 1;
+            *summary
+                .transitions
+                .entry(format!(
+                    "{}->{}",
+                    before.decision.as_str(),
+                    after.decision.as_str()
+                ))
+                .or_default() += 1;
+            summary.changes.push(change(&report, &before, &after));
+        } else if before.rules_triggered != after.rules_triggered {
+            summary.rules_changed += 1;
+        }
+    }
+    Ok(summary)
+}
+
+fn change(
+    report: &FileReport,
+    before: &DecisionOutcome,
+    after: &DecisionOutcome,
+) -> DecisionChange {
+    let missing_from = |rules: &[String], other: &[String]| -> Vec<String> {
+        rules
+            .iter()
+            .filter(|rule| !other.contains(rule))
+            .cloned()
+            .collect()
+    };
+    DecisionChange {
+        file: report.file.display().to_string(),
+        sha256: report.sha256.clone(),
+        mime: report.sniff.mime_real.clone(),
+        before: before.decision.as_str().to_string(),
+        after: after.decision.as_str().to_string(),
+        rules_added: missing_from(&after.rules_triggered, &before.rules_triggered),
+        rules_removed: missing_from(&before.rules_triggered, &after.rules_triggered),
+    }
+}
+
+/// Registros do relatório: objetos em sequência (JSONL ou indentados) ou arrays.
+fn load_reports(path: &Path) -> Result<Vec<FileReport>> {
+    let file = File::open(path).with_context(|| format!("falha ao abrir {}", path.display()))?;
+    let mut reports = Vec::new();
+    for value in serde_json::Deserializer::from_reader(BufReader::new(file)).into_iter::<Value>() {
+        let value = value.with_context(|| format!("registro inválido em {}", path.display()))?;
+        let records = match value {
+            Value::Array(records) => records,
+            record => vec![record],
+        };
+        for record in records {
+            reports.push(
+                serde_json::from_value(record)
+                    .with_context(|| format!("registro inválido em {}", path.display()))?,
+            );
+        }
+    }
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::SniffReport;
+    use tempfile::tempdir;
+
+    fn report(file: &str, mime: &str, size_bytes: u64, decision: &str) -> FileReport {
+        let sniff = SniffReport::new(mime.into(), None, None);
+        let mut report = FileReport::new(Path::new(file), size_bytes, format!("sha-{file}"), sniff);
+        report.policy.decision = decision.into();
+        report
+    }
+
+    #[test]
+    fn overrides_are_replayed_against_stored_reports() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("report.jsonl");
+        let records = [
+            report("foto.png", "image/png", 8 * 1024 * 1024, "ALLOW"),
+            report("nota.pdf", "application/pdf", 8 * 1024 * 1024, "ALLOW"),
+            report("falha.bin", "application/octet-stream", 0, "ERROR"),
+        ];
+        let lines: Vec<String> = records
+            .iter()
+            .map(|record| serde_json::to_string(record).unwrap())
+            .collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let rule: PolicyOverride = "if_mime: [image/*] set: {max_size_mb: 5}"
+            .parse()
+            .expect("override");
+        assert_eq!(rule.if_mime.as_deref(), Some(&["image/*".to_string()][..]));
+        let summary = run(SimulateRequest {
+            report: path,
+            policy: None,
+            overrides: vec![rule],
+        })
+        .expect("simulate");
+
+        assert_eq!((summary.files, summary.skipped, summary.changed), (3, 1, 1));
+        assert_eq!(summary.drift, 0);
+        assert_eq!(summary.transitions["ALLOW->DENY"], 1);
+        let change = &summary.changes[0];
+        assert_eq!(change.file, "foto.png");
+        assert!(change.rules_added[0].starts_with("size:exceeds_max:"));
+
+        let unknown: PolicyOverride = "set: {defaults.nope: 1}".parse().expect("yaml");
+        assert!(PolicyConfig::default().with_override(unknown).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Histórico de veredictos persistido em `history.path` (JSONL), para que a
+//! política de reincidentes valha entre execuções da CLI.
+//!
+//! Cada linha registra um WARN: `{"sha256", "decision": "WARN", "at"}`, com `at`
+//! em segundos Unix.
+
+use crate::history::{MemoryHistory, VerdictHistory};
+use crate::policy::Decision;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct HistoryRecord {
+    sha256: String,
+    decision: String,
+    #[serde(default)]
+    at: u64,
+}
+
+/// Histórico em arquivo: carregado na abertura e acrescido a cada WARN.
+#[derive(Debug)]
+pub struct FileHistory {
+    path: PathBuf,
+    memory: MemoryHistory,
+    file: Mutex<File>,
+}
+
+impl FileHistory {
+    /// Abre (ou cria) o arquivo; linhas inválidas são ignoradas.
+    pub fn open(path: &Path) -> Result<Self> {
+        let memory = MemoryHistory::new();
+        if path.exists() {
+            let file = File::open(path)
+                .with_context(|| format!("falha ao ler histórico {}", path.display()))?;
+            for line in BufReader::new(file).lines() {
+                let line =
+                    line.with_context(|| format!("falha ao ler histórico {}", path.display()))?;
+                match serde_json::from_str::<HistoryRecord>(&line) {
+                    Ok(record) if record.decision == Decision::Warn.as_str() => {
+                        memory.record(&record.sha256, Decision::Warn);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("falha ao abrir histórico {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            memory,
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl VerdictHistory for FileHistory {
+    fn warnings(&self, sha256: &str) -> u32 {
+        self.memory.warnings(sha256)
+    }
+
+    fn record(&self, sha256: &str, decision: Decision) {
+        if decision != Decision::Warn || sha256.is_empty() {
+            return;
+        }
+        self.memory.record(sha256, decision);
+        let record = HistoryRecord {
+            sha256: sha256.to_string(),
+            decision: decision.as_str().to_string(),
+            at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or(0),
+        };
+        let Ok(mut line) = serde_json::to_string(&record) else {
+            return;
+        };
+        line.push('\n');
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Err(err) = file.write_all(line.as_bytes()) {
+            tracing::warn!(history = %self.path.display(), "histórico não gravado: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warnings_survive_reopen() {
+        let dir = tempfile::tempdir().expect("dir");
+        let path = dir.path().join("history.jsonl");
+        let history = FileHistory::open(&path).expect("open");
+        history.record("abc", Decision::Warn);
+        history.record("abc", Decision::Allow);
+        history.record("abc", Decision::Warn);
+        history.record("", Decision::Warn);
+        drop(history);
+
+        let reopened = FileHistory::open(&path).expect("reopen");
+        assert_eq!(reopened.warnings("abc"), 2);
+        assert_eq!(reopened.warnings("def"), 0);
+    }
+}
@@ -0,0 +1,527 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Monitoramento contínuo de diretórios (`guardupload watch`) para hot-folders de upload.
+
+use crate::analyzers::hashes::HashAlgorithm;
+use crate::cli::BuiltinPolicy;
+use crate::engine::{load_policy, process_file, relative_to_roots};
+use crate::limits::{Deadline, ResourceProfile};
+use crate::metrics::{Metrics, TextfileExporter};
+use crate::plugins;
+use crate::policy::{Decision, PolicyEngine};
+use crate::report::FileReport;
+use crate::risk;
+use crate::sniff::ClaimedMimeMap;
+use crate::webhook::Notifier;
+use anyhow::{Context, Result};
+use notify::event::{AccessKind, AccessMode, ModifyKind};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Espera máxima entre eventos (também o intervalo de verificação do encerramento).
+const IDLE_WAIT: Duration = Duration::from_millis(500);
+
+/// Requisição do subcomando `watch`.
+#[derive(Debug)]
+pub struct WatchRequest {
+    pub dir: PathBuf,
+    pub policy: Option<PathBuf>,
+    pub builtin_policy: BuiltinPolicy,
+    /// JSONL que recebe os registros (acrescentados ao final); stdout quando ausente.
+    pub json: Option<PathBuf>,
+    /// Diretório para onde arquivos DENY são movidos.
+    pub quarantine_dir: Option<PathBuf>,
+    pub claimed_mime_map: Option<PathBuf>,
+    pub hashes: Vec<HashAlgorithm>,
+    pub timeout: Option<u64>,
+    pub max_read_bytes: Option<u64>,
+    /// Perfil de recursos (como no `scan`), reaplicado a cada recarga da política.
+    pub profile: ResourceProfile,
+    /// Tempo sem novos eventos antes de analisar um arquivo (escrita concluída).
+    pub settle_ms: u64,
+    /// Grava registros apenas quando a decisão de um caminho muda (ou é nova).
+    pub changes_only: bool,
+    /// Arquivo `.prom` gravado a cada `metrics_interval` (textfile collector).
+    pub metrics_textfile: Option<PathBuf>,
+    pub metrics_interval: Duration,
+    /// Webhook que recebe as decisões WARN/DENY (substitui `notifications.webhook_url`).
+    pub webhook: Option<String>,
+}
+
+/// Estado compartilhado entre as análises disparadas pelos eventos.
+struct WatchSession {
+    dir: PathBuf,
+    /// Arquivo de política (canônico), recarregado quando alterado.
+    policy_path: Option<PathBuf>,
+    builtin_policy: BuiltinPolicy,
+    profile: ResourceProfile,
+    policy_engine: Option<PolicyEngine>,
+    claimed_mimes: ClaimedMimeMap,
+    hashes: Vec<HashAlgorithm>,
+    timeout: Option<u64>,
+    max_read_bytes: Option<u64>,
+    quarantine_dir: Option<PathBuf>,
+    json_path: Option<PathBuf>,
+    json: Option<File>,
+    changes_only: bool,
+    /// Última decisão por caminho, para detectar mudanças de severidade.
+    last_decisions: HashMap<PathBuf, String>,
+    metrics: Metrics,
+    exporter: Option<TextfileExporter>,
+    /// `--metrics-textfile` canônico, ignorado quando está no diretório monitorado.
+    metrics_path: Option<PathBuf>,
+    notifier: Notifier,
+}
+
+impl WatchSession {
+    fn new(request: &WatchRequest) -> Result<Self> {
+        let dir = request
+            .dir
+            .canonicalize()
+            .with_context(|| format!("diretório inválido {}", request.dir.display()))?;
+        let policy_engine = load_policy(request.policy.as_deref(), request.builtin_policy)?
+            .map(|mut config| {
+                request.profile.apply(&mut config);
+                plugins::policy_engine(config)
+            })
+            .transpose()?;
+        let policy_path = match request.policy.as_deref() {
+            Some(path) => Some(path.canonicalize()?),
+            None => None,
+        };
+        let claimed_mimes = match request.claimed_mime_map.as_deref() {
+            Some(path) => ClaimedMimeMap::from_path(path)?,
+            None => ClaimedMimeMap::default(),
+        };
+        let quarantine_dir = match request.quarantine_dir.as_deref() {
+            Some(path) => {
+                std::fs::create_dir_all(path)
+                    .with_context(|| format!("não foi possível criar {}", path.display()))?;
+                Some(path.canonicalize()?)
+            }
+            None => None,
+        };
+        let (json, json_path) = match request.json.as_deref() {
+            Some(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| {
+                        format!("não foi possível abrir arquivo JSON {}", path.display())
+                    })?;
+                (Some(file), Some(path.canonicalize()?))
+            }
+            None => (None, None),
+        };
+        let metrics_path = match request.metrics_textfile.as_deref() {
+            Some(path) => {
+                let parent = match path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent.canonicalize(),
+                    _ => std::env::current_dir(),
+                }
+                .with_context(|| format!("diretório inválido para {}", path.display()))?;
+                path.file_name().map(|name| parent.join(name))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            dir,
+            policy_path,
+            builtin_policy: request.builtin_policy,
+            profile: request.profile,
+            policy_engine,
+            claimed_mimes,
+            hashes: request.hashes.clone(),
+            timeout: request.timeout,
+            max_read_bytes: request.max_read_bytes.or(request.profile.max_read_bytes()),
+            quarantine_dir,
+            json_path,
+            json,
+            changes_only: request.changes_only,
+            last_decisions: HashMap::new(),
+            metrics: Metrics::new(),
+            exporter: request
+                .metrics_textfile
+                .clone()
+                .map(|path| TextfileExporter::new(path, request.metrics_interval)),
+            metrics_path,
+            notifier: Notifier::new(request.webhook.clone()),
+        })
+    }
+
+    /// Ignora a quarentena e o próprio JSONL quando estão dentro do diretório monitorado.
+    fn is_watchable(&self, path: &Path) -> bool {
+        !self
+            .quarantine_dir
+            .as_deref()
+            .is_some_and(|quarantine| path.starts_with(quarantine))
+            && self.json_path.as_deref() != Some(path)
+            && !self.is_policy_file(path)
+            && !self
+                .metrics_path
+                .as_deref()
+                .is_some_and(|metrics| is_metrics_file(path, metrics))
+    }
+
+    fn is_policy_file(&self, path: &Path) -> bool {
+        self.policy_path.as_deref() == Some(path)
+    }
+
+    /// Recarrega a política e reanalisa os arquivos já vistos; uma política
+    /// inválida é registrada e a anterior continua valendo.
+    fn reload_policy(&mut self) -> Result<()> {
+        let profile = self.profile;
+        let loaded =
+            load_policy(self.policy_path.as_deref(), self.builtin_policy).and_then(|config| {
+                config
+                    .map(|mut config| {
+                        profile.apply(&mut config);
+                        plugins::policy_engine(config)
+                    })
+                    .transpose()
+            });
+        match loaded {
+            Ok(engine) => self.policy_engine = engine,
+            Err(err) => {
+                tracing::error!("política não recarregada: {err:#}");
+                return Ok(());
+            }
+        }
+        tracing::info!(files = self.last_decisions.len(), "política recarregada");
+        let mut known: Vec<PathBuf> = self.last_decisions.keys().cloned().collect();
+        known.sort();
+        for path in known {
+            if path.is_file() {
+                self.process(&path)?;
+            } else {
+                self.last_decisions.remove(&path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Esquece caminhos removidos para que reapareçam como arquivos novos.
+    fn forget(&mut self, path: &Path) {
+        self.last_decisions.remove(path);
+    }
+
+    /// Analisa um arquivo, aplica a quarentena e grava o registro.
+    fn process(&mut self, path: &Path) -> Result<FileReport> {
+        let started = Instant::now();
+        let deadline = self
+            .timeout
+            .map(|secs| Deadline::after(Duration::from_secs(secs)))
+            .unwrap_or_default();
+        let mut report = match process_file(
+            path,
+            self.policy_engine.as_ref(),
+            &self.claimed_mimes,
+            &self.hashes,
+            self.max_read_bytes,
+            &deadline,
+            None,
+        ) {
+            Ok((mut report, outcome)) => {
+                let denied = outcome.decision == Decision::Deny;
+                report.policy = outcome.into();
+                if denied {
+                    if let Some(quarantine_dir) = self.quarantine_dir.as_deref() {
+                        match quarantine(path, &self.dir, quarantine_dir) {
+                            Ok(dest) => report
+                                .notes
+                                .push(format!("movido para quarentena: {}", dest.display())),
+                            Err(err) => tracing::error!(
+                                file = %path.display(),
+                                "falha ao mover para quarentena: {err:#}"
+                            ),
+                        }
+                    }
+                }
+                report
+            }
+            Err(err) => {
+                tracing::error!(file = %path.display(), "falha ao processar arquivo: {err:?}");
+                FileReport::error(path, format!("{err:#}"))
+            }
+        };
+        report.risk = Some(risk::assess(&report, 1));
+        self.metrics.record(&report, started.elapsed());
+        self.notifier.notify(self.policy_engine.as_ref(), &report);
+        let previous = if path.exists() {
+            self.last_decisions
+                .insert(path.to_path_buf(), report.policy.decision.clone())
+        } else {
+            // Movido para a quarentena: um novo upload no mesmo caminho é arquivo novo.
+            self.last_decisions.remove(path)
+        };
+        let changed = previous.as_deref() != Some(report.policy.decision.as_str());
+        if changed {
+            report.previous_decision = previous;
+        }
+        if changed || !self.changes_only {
+            let record = serde_json::to_string(&report)?;
+            match self.json.as_mut() {
+                Some(file) => {
+                    writeln!(file, "{record}")?;
+                    file.flush()?;
+                }
+                None => println!("{record}"),
+            }
+        }
+        tracing::info!(
+            file = %path.display(),
+            decision = %report.policy.decision,
+            rules = ?report.policy.rules_triggered,
+            "arquivo analisado"
+        );
+        Ok(report)
+    }
+}
+
+/// Monitora `request.dir` até `shutdown` ser sinalizado ou o canal de eventos encerrar.
+pub fn watch(request: WatchRequest, shutdown: Arc<AtomicBool>) -> Result<()> {
+    let mut session = WatchSession::new(&request)?;
+    let (tx, rx) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(tx).context("falha ao iniciar monitor de arquivos")?;
+    watcher
+        .watch(&session.dir, RecursiveMode::Recursive)
+        .with_context(|| format!("falha ao monitorar {}", session.dir.display()))?;
+    if let Some(policy_path) = session.policy_path.clone() {
+        // O diretório pai acompanha editores que substituem o arquivo ao salvar.
+        let policy_dir = policy_path.parent().unwrap_or(&policy_path);
+        if !policy_dir.starts_with(&session.dir) {
+            watcher
+                .watch(policy_dir, RecursiveMode::NonRecursive)
+                .with_context(|| format!("falha ao monitorar {}", policy_path.display()))?;
+        }
+    }
+    tracing::info!(dir = %session.dir.display(), "monitorando diretório");
+
+    let settle = Duration::from_millis(request.settle_ms);
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+    let mut reload_pending: Option<Instant> = None;
+    while !shutdown.load(Ordering::Relaxed) {
+        let wait = pending
+            .values()
+            .chain(reload_pending.iter())
+            .map(|seen| settle.saturating_sub(seen.elapsed()))
+            .min()
+            .unwrap_or(IDLE_WAIT)
+            .min(IDLE_WAIT);
+        match rx.recv_timeout(wait) {
+            Ok(Ok(event)) if is_content_event(&event.kind) => {
+                for path in event.paths {
+                    if session.is_watchable(&path) && path.starts_with(&session.dir) {
+                        pending.insert(path, Instant::now());
+                    } else if session.is_policy_file(&path) {
+                        reload_pending = Some(Instant::now());
+                    }
+                }
+            }
+            Ok(Ok(_)) | Err(RecvTimeoutError::Timeout) => {}
+            Ok(Err(err)) => tracing::warn!("erro do monitor de arquivos: {err}"),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        let ready: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, seen)| seen.elapsed() >= settle)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in ready {
+            pending.remove(&path);
+            if path.is_file() {
+                session.process(&path)?;
+            } else {
+                session.forget(&path);
+            }
+        }
+        if reload_pending.is_some_and(|seen| seen.elapsed() >= settle) {
+            reload_pending = None;
+            session.reload_policy()?;
+        }
+        if let Some(exporter) = session.exporter.as_mut() {
+            exporter.tick(&session.metrics);
+        }
+    }
+    if let Some(exporter) = session.exporter.as_mut() {
+        exporter.flush(&session.metrics);
+    }
+    tracing::info!(dir = %session.dir.display(), "monitoramento encerrado");
+    Ok(())
+}
+
+/// O arquivo de métricas e o temporário usado na gravação atômica.
+fn is_metrics_file(path: &Path, metrics: &Path) -> bool {
+    path.parent() == metrics.parent()
+        && match (path.file_name(), metrics.file_name()) {
+            (Some(name), Some(metrics)) => name
+                .to_string_lossy()
+                .starts_with(metrics.to_string_lossy().as_ref()),
+            _ => false,
+        }
+}
+
+/// Eventos que indicam conteúdo novo: criação, escrita, renomeação e fechamento após escrita.
+fn is_content_event(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_)
+            | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any)
+            | EventKind::Access(AccessKind::Close(AccessMode::Write))
+    )
+}
+
+/// Move o arquivo para a quarentena preservando o caminho relativo ao diretório
+/// monitorado; nomes já ocupados recebem sufixo numérico.
+fn quarantine(path: &Path, root: &Path, quarantine_dir: &Path) -> Result<PathBuf> {
+    let relative = relative_to_roots(path, &[root.to_path_buf()]);
+    let mut dest = quarantine_dir.join(&relative);
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("não foi possível criar {}", parent.display()))?;
+    }
+    let base = dest.clone();
+    let mut counter = 1;
+    while std::fs::symlink_metadata(&dest).is_ok() {
+        let mut name = base.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{counter}"));
+        dest = base.with_file_name(name);
+        counter += 1;
+    }
+    if std::fs::rename(path, &dest).is_err() {
+        // Diretórios em dispositivos diferentes: copia e remove o original.
+        std::fs::copy(path, &dest).with_context(|| {
+            format!("falha ao copiar {} para {}", path.display(), dest.display())
+        })?;
+        std::fs::remove_file(path)
+            .with_context(|| format!("falha ao remover {}", path.display()))?;
+    }
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn denied_files_are_quarantined_and_recorded() {
+        let root = tempdir().expect("tempdir");
+        let hot = root.path().join("entrada");
+        std::fs::create_dir_all(hot.join("lote")).expect("mkdir");
+        let policy = root.path().join("policy.yaml");
+        std::fs::write(&policy, "defaults:\n  deny_types: [\"application/pdf\"]\n")
+            .expect("policy");
+
+        let request = WatchRequest {
+            dir: hot.clone(),
+            policy: Some(policy),
+            builtin_policy: BuiltinPolicy::Strict,
+            json: Some(root.path().join("watch.jsonl")),
+            quarantine_dir: Some(hot.join("quarentena")),
+            claimed_mime_map: None,
+            hashes: Vec::new(),
+            timeout: None,
+            max_read_bytes: None,
+            profile: ResourceProfile::Default,
+            settle_ms: 0,
+            metrics_textfile: Some(hot.join("guardupload.prom")),
+            metrics_interval: Duration::from_secs(15),
+            webhook: None,
+            changes_only: false,
+        };
+        let mut session = WatchSession::new(&request).expect("session");
+        let quarantine_dir = session.quarantine_dir.clone().expect("quarantine");
+        assert!(!session.is_watchable(&quarantine_dir.join("x.pdf")));
+        assert!(!session.is_watchable(&session.dir.join("guardupload.prom.tmp")));
+
+        let denied = session.dir.join("lote/fatura.pdf");
+        std::fs::write(&denied, b"%PDF-1.4\n%%EOF\n").expect("write");
+        let allowed = session.dir.join("nota.txt");
+        std::fs::write(&allowed, b"hello world\n").expect("write");
+
+        let report = session.process(&denied).expect("process");
+        assert_eq!(report.policy.decision, "DENY");
+        assert!(!denied.exists());
+        assert!(quarantine_dir.join("lote/fatura.pdf").is_file());
+        assert!(session
+            .metrics
+            .render()
+            .contains("guardupload_decisions_total{decision=\"DENY\"} 1\n"));
+
+        std::fs::write(&denied, b"%PDF-1.4\n%%EOF\n").expect("write");
+        session.process(&denied).expect("process");
+        assert!(quarantine_dir.join("lote/fatura.pdf.1").is_file());
+
+        let report = session.process(&allowed).expect("process");
+        assert_eq!(report.policy.decision, "ALLOW");
+        assert!(allowed.exists());
+
+        let lines = std::fs::read_to_string(root.path().join("watch.jsonl")).expect("jsonl");
+        assert_eq!(lines.lines().count(), 3);
+    }
+
+    #[test]
+    fn changes_only_reports_new_files_and_decisions_changed_by_reload() {
+        let root = tempdir().expect("tempdir");
+        let hot = root.path().join("entrada");
+        std::fs::create_dir_all(&hot).expect("mkdir");
+        let policy = root.path().join("policy.yaml");
+        std::fs::write(&policy, "defaults:\n  max_size_mb: 1\n").expect("policy");
+        let jsonl = root.path().join("watch.jsonl");
+
+        let request = WatchRequest {
+            dir: hot.clone(),
+            policy: Some(policy.clone()),
+            builtin_policy: BuiltinPolicy::Strict,
+            json: Some(jsonl.clone()),
+            quarantine_dir: None,
+            claimed_mime_map: None,
+            hashes: Vec::new(),
+            timeout: None,
+            max_read_bytes: None,
+            profile: ResourceProfile::Default,
+            settle_ms: 0,
+            metrics_textfile: None,
+            metrics_interval: Duration::from_secs(15),
+            webhook: None,
+            changes_only: true,
+        };
+        let mut session = WatchSession::new(&request).expect("session");
+        assert!(!session.is_watchable(&policy.canonicalize().expect("canonical")));
+        let note = session.dir.join("nota.txt");
+        std::fs::write(&note, b"hello world\n").expect("write");
+
+        session.process(&note).expect("process");
+        session.process(&note).expect("process");
+        let records = || -> Vec<serde_json::Value> {
+            std::fs::read_to_string(&jsonl)
+                .expect("jsonl")
+                .lines()
+                .map(|line| serde_json::from_str(line).expect("record"))
+                .collect()
+        };
+        assert_eq!(records().len(), 1);
+
+        std::fs::write(&policy, "defaults:\n  deny_types: [\"text/plain\"]\n").expect("policy");
+        session.reload_policy().expect("reload");
+        let records = records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1]["policy"]["decision"], "DENY");
+        assert_eq!(records[1]["previous_decision"], "ALLOW");
+    }
+}
@@ -0,0 +1,276 @@
+// GuardUpload
+// Criado em: 2026-10-17
+// Licença: MIT
+// Empresa: SoftCtrl
+
+//! Notificação de decisões por webhook (`notifications:` na política ou `--webhook`).
+//!
+//! Cada relatório com decisão notificável (WARN/DENY por padrão) é enviado via
+//! POST como JSON por uma thread dedicada, para não atrasar a análise. Com a
+//! chave em `secret_env`, o cabeçalho `X-GuardUpload-Signature: sha256=<hex>`
+//! traz o HMAC-SHA256 do corpo. Com `rate_limits.webhook`, cada entrega passa
+//! pelo limitador compartilhado antes de sair.
+
+use crate::config::NotificationsPolicySection;
+use crate::policy::PolicyEngine;
+use crate::rate_limit::{self, Permit, RateLimiter};
+use crate::report::FileReport;
+use sha2::{Digest, Sha256};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const DEFAULT_DECISIONS: [&str; 2] = ["WARN", "DENY"];
+const DEFAULT_SECRET_ENV: &str = "GUARDUPLOAD_WEBHOOK_SECRET";
+const DEFAULT_RETRIES: u32 = 3;
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+/// Espera antes da primeira nova tentativa; dobra a cada falha.
+const RETRY_BACKOFF: Duration = Duration::from_millis(200);
+pub const SIGNATURE_HEADER: &str = "X-GuardUpload-Signature";
+const HMAC_BLOCK_BYTES: usize = 64;
+
+/// Envio pendente para o webhook.
+#[derive(Debug)]
+struct Delivery {
+    url: String,
+    decision: String,
+    body: String,
+    signature: Option<String>,
+    retries: u32,
+    timeout: Duration,
+    limiter: Option<Arc<RateLimiter>>,
+    queued_at: Instant,
+}
+
+/// Envia notificações em segundo plano; a thread é criada no primeiro envio e,
+/// ao descartar o `Notifier`, as entregas pendentes são concluídas.
+#[derive(Debug, Default)]
+pub struct Notifier {
+    /// `--webhook`: substitui `notifications.webhook_url` da política.
+    url_override: Option<String>,
+    worker: Mutex<Option<(Sender<Delivery>, JoinHandle<()>)>>,
+}
+
+impl Notifier {
+    pub fn new(url_override: Option<String>) -> Self {
+        Self {
+            url_override,
+            worker: Mutex::new(None),
+        }
+    }
+
+    /// Agenda o envio de `report` se a decisão estiver em `notifications.decisions`.
+    pub fn notify(&self, policy_engine: Option<&PolicyEngine>, report: &FileReport) {
+        let default_section = NotificationsPolicySection::default();
+        let section = policy_engine
+            .map(|engine| &engine.config().notifications)
+            .unwrap_or(&default_section);
+        let rate_limit =
+            policy_engine.and_then(|engine| engine.config().rate_limits.webhook.as_ref());
+        let Some(url) = self
+            .url_override
+            .as_deref()
+            .or(section.webhook_url.as_deref())
+        else {
+            return;
+        };
+        let decision = report.policy.decision.as_str();
+        let wanted = if section.decisions.is_empty() {
+            DEFAULT_DECISIONS.contains(&decision)
+        } else {
+            section
+                .decisions
+                .iter()
+                .any(|wanted| wanted.eq_ignore_ascii_case(decision))
+        };
+        if !wanted {
+            return;
+        }
+        let body = match serde_json::to_string(report) {
+            Ok(body) => body,
+            Err(err) => {
+                tracing::warn!("relatório não notificado: {err}");
+                return;
+            }
+        };
+        let secret_env = section.secret_env.as_deref().unwrap_or(DEFAULT_SECRET_ENV);
+        let signature = std::env::var(secret_env)
+            .ok()
+            .filter(|secret| !secret.is_empty())
+            .map(|secret| sign(secret.as_bytes(), body.as_bytes()));
+        self.enqueue(Delivery {
+            url: url.to_string(),
+            decision: decision.to_string(),
+            body,
+            signature,
+            retries: section.retries.unwrap_or(DEFAULT_RETRIES),
+            timeout: Duration::from_millis(section.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)),
+            limiter: rate_limit::shared(rate_limit::WEBHOOK, rate_limit),
+            queued_at: Instant::now(),
+        });
+    }
+
+    fn enqueue(&self, delivery: Delivery) {
+        let mut worker = self.worker.lock().unwrap_or_else(PoisonError::into_inner);
+        let (sender, _) = worker.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel::<Delivery>();
+            let handle = thread::spawn(move || {
+                for delivery in receiver {
+                    deliver(&delivery);
+                }
+            });
+            (sender, handle)
+        });
+        let _ = sender.send(delivery);
+    }
+}
+
+impl Drop for Notifier {
+    fn drop(&mut self) {
+        let worker = self
+            .worker
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some((sender, handle)) = worker {
+            drop(sender);
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Assinatura `sha256=<hex>` do corpo com HMAC-SHA256.
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    format!("sha256={}", hex::encode(hmac_sha256(secret, body)))
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; HMAC_BLOCK_BYTES];
+    if key.len() > HMAC_BLOCK_BYTES {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.map(|key_byte| key_byte ^ byte);
+    let inner = Sha256::new()
+        .chain_update(pad(0x36))
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(pad(0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+/// Envia com novas tentativas para falhas de rede, 429 e 5xx.
+fn deliver(delivery: &Delivery) {
+    if let Some(limiter) = delivery.limiter.as_deref() {
+        if limiter.acquire(delivery.queued_at) == Permit::Dropped {
+            tracing::warn!(url = %delivery.url, "notificação descartada pelo limite de taxa");
+            return;
+        }
+    }
+    let agent = ureq::AgentBuilder::new().timeout(delivery.timeout).build();
+    let mut backoff = RETRY_BACKOFF;
+    for attempt in 0..=delivery.retries {
+        if attempt > 0 {
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+        let mut request = agent
+            .post(&delivery.url)
+            .set("Content-Type", "application/json")
+            .set("X-GuardUpload-Decision", &delivery.decision);
+        if let Some(signature) = delivery.signature.as_deref() {
+            request = request.set(SIGNATURE_HEADER, signature);
+        }
+        let error = match request.send_string(&delivery.body) {
+            Ok(_) => return,
+            Err(ureq::Error::Status(status, _)) if status != 429 && status < 500 => {
+                tracing::warn!(url = %delivery.url, status, "webhook recusou a notificação");
+                return;
+            }
+            Err(err) => err,
+        };
+        tracing::debug!(url = %delivery.url, attempt, "falha no webhook: {error}");
+    }
+    tracing::warn!(
+        url = %delivery.url,
+        attempts = delivery.retries + 1,
+        "notificação não entregue"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PolicyConfig;
+    use crate::report::{PolicyDecision, SniffReport};
+    use std::path::Path;
+
+    #[test]
+    fn hmac_matches_rfc4231() {
+        assert_eq!(
+            sign(b"Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        let long_key = [0xaa; 131];
+        assert_eq!(
+            hex::encode(hmac_sha256(
+                &long_key,
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn notifies_warn_with_retry_and_skips_allow() {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("server");
+        let url = format!("http://{}/hook", server.server_addr().to_ip().expect("tcp"));
+        let policy = PolicyConfig::from_yaml_str(&format!(
+            "notifications:\n  webhook_url: {url}\n  secret_env: GUARDUPLOAD_TEST_WEBHOOK_SECRET\n"
+        ))
+        .expect("policy");
+        std::env::set_var("GUARDUPLOAD_TEST_WEBHOOK_SECRET", "segredo");
+        let engine = PolicyEngine::new(policy);
+
+        let report = |decision: &str| {
+            let sniff = SniffReport::new("text/plain".into(), None, None);
+            let mut report = FileReport::new(Path::new("a.txt"), 3, "abc".into(), sniff);
+            report.policy = PolicyDecision {
+                decision: decision.into(),
+                ..PolicyDecision::default()
+            };
+            report
+        };
+        let notifier = Notifier::new(None);
+        notifier.notify(Some(&engine), &report("ALLOW"));
+        notifier.notify(Some(&engine), &report("WARN"));
+
+        let mut first = server.recv().expect("primeira tentativa");
+        let mut body = String::new();
+        first.as_reader().read_to_string(&mut body).expect("body");
+        let _ = first.respond(tiny_http::Response::empty(503));
+        let second = server.recv().expect("nova tentativa");
+        let signature = second
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv(SIGNATURE_HEADER))
+            .map(|header| header.value.to_string());
+        let _ = second.respond(tiny_http::Response::empty(204));
+        drop(notifier);
+
+        assert!(body.contains("\"decision\":\"WARN\""));
+        assert_eq!(
+            signature.as_deref(),
+            Some(sign(b"segredo", body.as_bytes()).as_str())
+        );
+        assert!(server
+            .recv_timeout(Duration::from_millis(50))
+            .expect("recv")
+            .is_none());
+    }
+}